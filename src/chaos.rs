@@ -0,0 +1,144 @@
+//! 故障注入（chaos）：按配置的概率注入 WS 断线、丢弃订单簿消息、订单被拒与 RPC 失败，
+//! 用于在演练环境中验证断线重连、单腿恢复、merge 重试与熔断是否按预期工作。
+//!
+//! 仅在 `cargo build --features chaos` 编译时生效；默认编译下 [`hit`] 恒为 false，不影响实盘。
+//! 概率从环境变量读取（0~1，默认 0），首次调用时加载：
+//! CHAOS_WS_DISCONNECT_PROB、CHAOS_DROP_MESSAGE_PROB、CHAOS_ORDER_REJECT_PROB、CHAOS_RPC_FAILURE_PROB；
+//! CHAOS_SEED 指定随机种子以复现同一串故障。
+
+/// 可注入的故障类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// 订单簿 WS 断线（主循环按流错误处理并重建订阅）
+    WsDisconnect,
+    /// 丢弃一条订单簿消息
+    DropMessage,
+    /// 一腿订单被交易所拒绝（未成交）
+    OrderReject,
+    /// 链上 RPC 调用失败（merge 与 merge 到账核对）
+    RpcFailure,
+}
+
+impl Fault {
+    pub const ALL: [Fault; 4] = [Fault::WsDisconnect, Fault::DropMessage, Fault::OrderReject, Fault::RpcFailure];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Fault::WsDisconnect => "ws_disconnect",
+            Fault::DropMessage => "drop_message",
+            Fault::OrderReject => "order_reject",
+            Fault::RpcFailure => "rpc_failure",
+        }
+    }
+
+    #[cfg(feature = "chaos")]
+    fn env_key(&self) -> &'static str {
+        match self {
+            Fault::WsDisconnect => "CHAOS_WS_DISCONNECT_PROB",
+            Fault::DropMessage => "CHAOS_DROP_MESSAGE_PROB",
+            Fault::OrderReject => "CHAOS_ORDER_REJECT_PROB",
+            Fault::RpcFailure => "CHAOS_RPC_FAILURE_PROB",
+        }
+    }
+}
+
+#[cfg(feature = "chaos")]
+mod injector {
+    use super::Fault;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::OnceLock;
+
+    pub(super) struct Injector {
+        probs: [f64; 4],
+        state: AtomicU64,
+    }
+
+    static INJECTOR: OnceLock<Injector> = OnceLock::new();
+
+    pub(super) fn get() -> &'static Injector {
+        INJECTOR.get_or_init(|| {
+            let probs = Fault::ALL.map(|f| {
+                std::env::var(f.env_key())
+                    .ok()
+                    .and_then(|s| s.trim().parse::<f64>().ok())
+                    .unwrap_or(0.0)
+                    .clamp(0.0, 1.0)
+            });
+            let seed = std::env::var("CHAOS_SEED")
+                .ok()
+                .and_then(|s| s.trim().parse::<u64>().ok())
+                .unwrap_or_else(|| chrono::Utc::now().timestamp_nanos_opt().unwrap_or(1) as u64);
+            Injector {
+                probs,
+                state: AtomicU64::new(seed.max(1)),
+            }
+        })
+    }
+
+    impl Injector {
+        pub(super) fn prob(&self, fault: Fault) -> f64 {
+            self.probs[Fault::ALL.iter().position(|f| *f == fault).unwrap_or(0)]
+        }
+
+        /// xorshift64，[0, 1) 均匀分布
+        fn next_f64(&self) -> f64 {
+            let step = |mut x: u64| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                x
+            };
+            let prev = self
+                .state
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |x| Some(step(x)))
+                .unwrap_or(1);
+            (step(prev) >> 11) as f64 / (1u64 << 53) as f64
+        }
+
+        pub(super) fn hit(&self, fault: Fault) -> bool {
+            let prob = self.prob(fault);
+            prob > 0.0 && self.next_f64() < prob
+        }
+    }
+}
+
+/// 本次是否注入该故障；未启用 `chaos` feature 时恒为 false
+#[cfg(feature = "chaos")]
+pub fn hit(fault: Fault) -> bool {
+    let hit = injector::get().hit(fault);
+    if hit {
+        tracing::warn!(fault = fault.as_str(), "🧪 [chaos] 注入故障");
+    }
+    hit
+}
+
+/// 本次是否注入该故障；未启用 `chaos` feature 时恒为 false
+#[cfg(not(feature = "chaos"))]
+#[inline]
+pub fn hit(_fault: Fault) -> bool {
+    false
+}
+
+/// RPC 调用前的注入点：命中时返回错误
+pub fn rpc_guard(op: &str) -> anyhow::Result<()> {
+    if hit(Fault::RpcFailure) {
+        anyhow::bail!("[chaos] 注入的 RPC 故障: {}", op);
+    }
+    Ok(())
+}
+
+/// 启动时打印已启用的故障注入；未启用 feature 或概率全为 0 时不输出
+pub fn log_active() {
+    #[cfg(feature = "chaos")]
+    {
+        let injector = injector::get();
+        let active: Vec<String> = Fault::ALL
+            .iter()
+            .filter(|f| injector.prob(**f) > 0.0)
+            .map(|f| format!("{}={}", f.as_str(), injector.prob(*f)))
+            .collect();
+        if !active.is_empty() {
+            tracing::warn!("🧪 故障注入已启用（仅用于演练，切勿用于实盘资金）: {}", active.join(", "));
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! backfill 子命令：从 Data API 拉取账户的历史活动（成交 TRADE、合并 MERGE、赎回 REDEEM 等），
+//! 按原始时间写入存储后端的 journal 表（事件名 `backfill_<类型>`，如 backfill_trade），
+//! 使盈亏与统计分析覆盖机器人（或本功能）上线之前的活动。
+//!
+//! 已导入到的最新时间戳按账户记录在 state 表（backfill_cursor），重复执行只导入之后的新活动；
+//! 加 --since 可忽略记录、从指定日期重新导入（会与已导入的记录重复）。
+//!
+//! 用法示例：
+//!   poly_1hour_bot backfill
+//!   poly_1hour_bot backfill --since 2025-01-01 --types TRADE,MERGE,REDEEM,REWARD --dry-run
+
+use alloy::signers::local::LocalSigner;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use polymarket_client_sdk::types::Address;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::utils::storage::{self, Storage};
+
+/// Data API 地址（账户活动记录）
+const DATA_API_URL: &str = "https://data-api.polymarket.com";
+/// 每页条数
+const PAGE: usize = 500;
+/// 最多翻页数，防止接口异常时无限翻页
+const MAX_PAGES: usize = 400;
+/// 导入进度在 state 表中的名称
+const CURSOR_STATE: &str = "backfill_cursor";
+/// 默认导入的活动类型
+const DEFAULT_TYPES: &str = "TRADE,MERGE,REDEEM";
+
+fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|i| args.get(i + 1))
+        .map(String::as_str)
+}
+
+fn print_usage() {
+    eprintln!("用法: poly_1hour_bot backfill [--user 地址] [--since YYYY-MM-DD] [--types TRADE,MERGE,REDEEM] [--dry-run]");
+    eprintln!("  --user     账户地址（默认 POLYMARKET_PROXY_ADDRESS，未设置时为私钥对应地址）");
+    eprintln!("  --since    从该日期（UTC）开始导入，忽略已记录的导入进度");
+    eprintln!("  --types    活动类型，逗号分隔（默认 {}；另有 SPLIT / CONVERSION / REWARD）", DEFAULT_TYPES);
+    eprintln!("  --dry-run  只统计，不写入存储后端");
+}
+
+/// 账户地址：--user，否则代理钱包地址，否则私钥对应的 EOA
+fn resolve_user(args: &[String]) -> Result<Address> {
+    if let Some(user) = arg_value(args, "--user") {
+        return user.parse().context("--user 地址格式无效");
+    }
+    if let Ok(proxy) = std::env::var("POLYMARKET_PROXY_ADDRESS") {
+        if !proxy.trim().is_empty() {
+            return proxy.trim().parse().context("POLYMARKET_PROXY_ADDRESS 格式无效");
+        }
+    }
+    let key = std::env::var("POLYMARKET_PRIVATE_KEY").unwrap_or_default();
+    if key.trim().is_empty() {
+        bail!("请用 --user 指定账户地址，或设置 POLYMARKET_PROXY_ADDRESS / POLYMARKET_PRIVATE_KEY");
+    }
+    Ok(LocalSigner::from_str(key.trim())
+        .map_err(|e| anyhow::anyhow!("私钥格式无效: {}", e))?
+        .address())
+}
+
+/// 读取该账户已导入到的最新时间戳（Unix 秒）
+async fn load_cursor(backend: &dyn Storage, user: &str) -> Result<Option<i64>> {
+    Ok(backend
+        .get_state(CURSOR_STATE)
+        .await?
+        .and_then(|state| state.get(user).and_then(Value::as_i64)))
+}
+
+async fn save_cursor(backend: &dyn Storage, user: &str, ts: i64) -> Result<()> {
+    let mut state = backend.get_state(CURSOR_STATE).await?.unwrap_or_else(|| json!({}));
+    if let Some(obj) = state.as_object_mut() {
+        obj.insert(user.to_string(), json!(ts));
+    }
+    backend.put_state(CURSOR_STATE, &state).await
+}
+
+pub async fn run(args: &[String]) -> Result<()> {
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        print_usage();
+        return Ok(());
+    }
+    dotenvy::dotenv().ok();
+    let url = std::env::var("STORAGE_URL").unwrap_or_default();
+    if url.trim().is_empty() {
+        bail!("未配置 STORAGE_URL：历史活动导入到存储后端的 journal 表");
+    }
+    let dry_run = args.iter().any(|a| a == "--dry-run");
+    let types = arg_value(args, "--types").unwrap_or(DEFAULT_TYPES).to_uppercase();
+    let user = resolve_user(args)?;
+    let user_key = format!("{:#x}", user);
+
+    let backend = storage::connect(&url, "backfill").await?;
+    let start = match arg_value(args, "--since") {
+        Some(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .context("--since 格式应为 YYYY-MM-DD")?
+            .and_hms_opt(0, 0, 0)
+            .map(|t| t.and_utc().timestamp())
+            .unwrap_or(0),
+        None => load_cursor(backend.as_ref(), &user_key).await?.map_or(0, |ts| ts + 1),
+    };
+    println!(
+        "📥 导入账户 {} 的历史活动 | 类型:{} | 起始:{}",
+        user_key,
+        types,
+        DateTime::<Utc>::from_timestamp(start, 0).map_or_else(|| "最早".to_string(), |t| t.to_rfc3339())
+    );
+
+    let http = reqwest::Client::builder()
+        .timeout(Duration::from_secs(15))
+        .build()
+        .unwrap_or_default();
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    let mut latest: Option<i64> = None;
+    for page in 0..MAX_PAGES {
+        let items: Vec<Value> = http
+            .get(format!("{}/activity", DATA_API_URL))
+            .query(&[
+                ("user", user_key.clone()),
+                ("type", types.clone()),
+                ("start", start.to_string()),
+                ("sortBy", "TIMESTAMP".to_string()),
+                ("sortDirection", "ASC".to_string()),
+                ("limit", PAGE.to_string()),
+                ("offset", (page * PAGE).to_string()),
+            ])
+            .send()
+            .await
+            .context("请求活动记录失败")?
+            .error_for_status()
+            .context("活动记录返回错误状态")?
+            .json()
+            .await
+            .context("解析活动记录失败")?;
+        for item in &items {
+            let Some(ts) = item.get("timestamp").and_then(Value::as_i64) else {
+                continue;
+            };
+            let kind = item.get("type").and_then(Value::as_str).unwrap_or("unknown").to_lowercase();
+            let event = format!("backfill_{}", kind);
+            let mut line = json!({
+                "ts": DateTime::<Utc>::from_timestamp(ts, 0).unwrap_or_default().to_rfc3339(),
+                "event": event,
+                "source": "data_api",
+            });
+            if let (Some(obj), Value::Object(fields)) = (line.as_object_mut(), item.clone()) {
+                obj.extend(fields);
+            }
+            if !dry_run {
+                backend.append_event(&event, &line).await?;
+            }
+            *counts.entry(kind).or_default() += 1;
+            latest = Some(latest.map_or(ts, |l| l.max(ts)));
+        }
+        if items.len() < PAGE {
+            break;
+        }
+        if page + 1 == MAX_PAGES {
+            println!("  ⚠️ 已达翻页上限（{} 条），再次执行可继续导入之后的活动", MAX_PAGES * PAGE);
+        }
+    }
+
+    if counts.is_empty() {
+        println!("没有新的历史活动");
+        return Ok(());
+    }
+    for (kind, count) in &counts {
+        println!("  {:<12} {} 条", kind, count);
+    }
+    match (dry_run, latest) {
+        (true, _) => println!("（--dry-run：未写入存储后端）"),
+        (false, Some(ts)) => {
+            save_cursor(backend.as_ref(), &user_key, ts).await?;
+            println!("✅ 已导入 {} 条历史活动到 {} 的 journal 表", counts.values().sum::<usize>(), backend.name());
+        }
+        (false, None) => {}
+    }
+    Ok(())
+}
@@ -0,0 +1,288 @@
+//! cassette 子命令：Gamma / CLOB REST 的录制-回放代理。
+//!
+//! record 模式把请求转发到真实接口，并把每次交互（方法、路径、请求体摘要、状态码、响应体）追加到磁带文件（JSONL）；
+//! replay 模式不联网，按录制顺序确定性地返回相同响应。把 GAMMA_API_URL / CLOB_REST_URL 指向本地监听地址，
+//! 即可离线、快速地复现市场发现、认证与下单流程（订单簿 WS 不经过代理）。
+//!
+//! 请求头（含 API 凭证与签名）不写入磁带；认证响应中的 apiKey / secret / passphrase 会被替换为占位值。
+//!
+//! 用法示例：
+//!   poly_1hour_bot cassette record --file state/cassettes/discovery.jsonl
+//!   poly_1hour_bot cassette replay --file state/cassettes/discovery.jsonl
+//!   GAMMA_API_URL=http://127.0.0.1:8787 CLOB_REST_URL=http://127.0.0.1:8788 poly_1hour_bot observe --windows 1
+
+use anyhow::{bail, Context, Result};
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, Method, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::Router;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+
+use crate::config::{DEFAULT_CLOB_REST_URL, DEFAULT_GAMMA_URL};
+
+/// 认证响应中替换凭证用的占位值（secret 需是合法 base64，签名时会被解码）
+const REDACTED_API_KEY: &str = "00000000-0000-0000-0000-000000000000";
+const REDACTED_SECRET: &str = "cmVkYWN0ZWQ=";
+const REDACTED_PASSPHRASE: &str = "redacted";
+
+fn print_usage() {
+    eprintln!("用法: poly_1hour_bot cassette <record|replay> --file PATH [--gamma-listen ADDR] [--clob-listen ADDR]");
+    eprintln!("                               [--gamma-upstream URL] [--clob-upstream URL]");
+    eprintln!("  record             转发到真实接口并把交互追加写入磁带");
+    eprintln!("  replay             只从磁带回放，不联网；找不到对应录制时返回 404");
+    eprintln!("  --file PATH        磁带文件（JSONL）");
+    eprintln!("  --gamma-listen     Gamma 代理监听地址，默认 127.0.0.1:8787（GAMMA_API_URL 指向这里）");
+    eprintln!("  --clob-listen      CLOB 代理监听地址，默认 127.0.0.1:8788（CLOB_REST_URL 指向这里）");
+    eprintln!("  --gamma-upstream   录制时的 Gamma 上游，默认 {}", DEFAULT_GAMMA_URL);
+    eprintln!("  --clob-upstream    录制时的 CLOB 上游，默认 {}", DEFAULT_CLOB_REST_URL);
+}
+
+/// 磁带中的一次交互
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    /// gamma | clob
+    upstream: String,
+    method: String,
+    /// 路径 + 查询串
+    path: String,
+    /// 请求体 SHA-256（十六进制）；下单等请求体含时间戳与签名，回放时匹配不上则退回按路径顺序匹配
+    body_sha256: String,
+    status: u16,
+    #[serde(default)]
+    content_type: Option<String>,
+    body: String,
+}
+
+/// 回放索引：同一键的多次录制按顺序依次返回，用完后重复最后一次
+#[derive(Default)]
+struct Replay {
+    exact: HashMap<(String, String, String, String), VecDeque<Interaction>>,
+    by_path: HashMap<(String, String, String), VecDeque<Interaction>>,
+}
+
+impl Replay {
+    fn load(path: &str) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("打开磁带失败: {}", path))?;
+        let mut replay = Replay::default();
+        for (lineno, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry: Interaction =
+                serde_json::from_str(&line).with_context(|| format!("磁带第 {} 行格式错误", lineno + 1))?;
+            replay
+                .exact
+                .entry((entry.upstream.clone(), entry.method.clone(), entry.path.clone(), entry.body_sha256.clone()))
+                .or_default()
+                .push_back(entry.clone());
+            replay
+                .by_path
+                .entry((entry.upstream.clone(), entry.method.clone(), entry.path.clone()))
+                .or_default()
+                .push_back(entry);
+        }
+        Ok(replay)
+    }
+
+    fn next(queue: &mut VecDeque<Interaction>) -> Option<Interaction> {
+        if queue.len() > 1 {
+            queue.pop_front()
+        } else {
+            queue.front().cloned()
+        }
+    }
+
+    fn find(&mut self, upstream: &str, method: &str, path: &str, body_sha256: &str) -> Option<Interaction> {
+        let exact_key = (upstream.to_string(), method.to_string(), path.to_string(), body_sha256.to_string());
+        if let Some(entry) = self.exact.get_mut(&exact_key).and_then(Self::next) {
+            return Some(entry);
+        }
+        let path_key = (upstream.to_string(), method.to_string(), path.to_string());
+        self.by_path.get_mut(&path_key).and_then(Self::next)
+    }
+}
+
+enum Mode {
+    Record { http: reqwest::Client, writer: Mutex<File> },
+    Replay(Mutex<Replay>),
+}
+
+#[derive(Clone)]
+struct ProxyState {
+    upstream_name: &'static str,
+    upstream_url: Arc<String>,
+    mode: Arc<Mode>,
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 替换认证响应中的凭证，避免把 API secret 写进磁带
+fn redact_credentials(body: &str) -> String {
+    let Ok(serde_json::Value::Object(mut map)) = serde_json::from_str::<serde_json::Value>(body) else {
+        return body.to_string();
+    };
+    let mut redacted = false;
+    for (key, placeholder) in [
+        ("apiKey", REDACTED_API_KEY),
+        ("secret", REDACTED_SECRET),
+        ("passphrase", REDACTED_PASSPHRASE),
+    ] {
+        if let Some(value) = map.get_mut(key) {
+            *value = serde_json::Value::String(placeholder.to_string());
+            redacted = true;
+        }
+    }
+    if redacted {
+        serde_json::Value::Object(map).to_string()
+    } else {
+        body.to_string()
+    }
+}
+
+fn respond(status: u16, content_type: Option<&str>, body: String) -> Response {
+    let status = StatusCode::from_u16(status).unwrap_or(StatusCode::BAD_GATEWAY);
+    let mut response = (status, body).into_response();
+    if let Some(value) = content_type.and_then(|c| c.parse().ok()) {
+        response.headers_mut().insert(axum::http::header::CONTENT_TYPE, value);
+    }
+    response
+}
+
+async fn handle(State(state): State<ProxyState>, method: Method, uri: Uri, headers: HeaderMap, body: Bytes) -> Response {
+    let path = uri.path_and_query().map(|p| p.as_str().to_string()).unwrap_or_else(|| uri.path().to_string());
+    let body_sha256 = sha256_hex(&body);
+    match state.mode.as_ref() {
+        Mode::Replay(replay) => {
+            let found = replay
+                .lock()
+                .ok()
+                .and_then(|mut r| r.find(state.upstream_name, method.as_str(), &path, &body_sha256));
+            match found {
+                Some(entry) => respond(entry.status, entry.content_type.as_deref(), entry.body),
+                None => {
+                    warn!(upstream = state.upstream_name, method = %method, path = %path, "磁带中没有对应的录制");
+                    respond(404, Some("application/json"), r#"{"error":"cassette miss"}"#.to_string())
+                }
+            }
+        }
+        Mode::Record { http, writer } => {
+            let url = format!("{}{}", state.upstream_url.trim_end_matches('/'), path);
+            let mut request = http.request(method.clone(), &url).body(body.to_vec());
+            for (name, value) in &headers {
+                if name != axum::http::header::HOST && name != axum::http::header::CONTENT_LENGTH {
+                    request = request.header(name, value);
+                }
+            }
+            let upstream = match request.send().await {
+                Ok(upstream) => upstream,
+                Err(e) => {
+                    warn!(error = %e, url = %url, "转发到上游失败");
+                    return respond(502, None, e.to_string());
+                }
+            };
+            let status = upstream.status().as_u16();
+            let content_type = upstream
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string);
+            let text = match upstream.text().await {
+                Ok(text) => text,
+                Err(e) => return respond(502, None, e.to_string()),
+            };
+            let entry = Interaction {
+                upstream: state.upstream_name.to_string(),
+                method: method.to_string(),
+                path,
+                body_sha256,
+                status,
+                content_type: content_type.clone(),
+                body: redact_credentials(&text),
+            };
+            if let (Ok(line), Ok(mut file)) = (serde_json::to_string(&entry), writer.lock()) {
+                if let Err(e) = writeln!(file, "{}", line) {
+                    warn!(error = %e, "写入磁带失败");
+                }
+            }
+            // 调用方拿到未脱敏的原始响应，录制流程照常进行
+            respond(status, content_type.as_deref(), text)
+        }
+    }
+}
+
+fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|i| args.get(i + 1))
+        .map(String::as_str)
+}
+
+pub async fn run(args: &[String]) -> Result<()> {
+    let Some(action) = args.first().map(String::as_str).filter(|a| *a == "record" || *a == "replay") else {
+        print_usage();
+        if args.iter().any(|a| a == "--help" || a == "-h") {
+            return Ok(());
+        }
+        bail!("cassette 需要 record 或 replay");
+    };
+    let Some(path) = arg_value(args, "--file") else {
+        print_usage();
+        bail!("--file 需要参数");
+    };
+    let gamma_listen = arg_value(args, "--gamma-listen").unwrap_or("127.0.0.1:8787");
+    let clob_listen = arg_value(args, "--clob-listen").unwrap_or("127.0.0.1:8788");
+
+    let mode = if action == "record" {
+        if let Some(parent) = std::path::Path::new(path).parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("打开磁带失败: {}", path))?;
+        Mode::Record {
+            http: reqwest::Client::builder().build()?,
+            writer: Mutex::new(file),
+        }
+    } else {
+        Mode::Replay(Mutex::new(Replay::load(path)?))
+    };
+    let mode = Arc::new(mode);
+
+    let mut servers = Vec::new();
+    for (name, listen, upstream) in [
+        ("gamma", gamma_listen, arg_value(args, "--gamma-upstream").unwrap_or(DEFAULT_GAMMA_URL)),
+        ("clob", clob_listen, arg_value(args, "--clob-upstream").unwrap_or(DEFAULT_CLOB_REST_URL)),
+    ] {
+        let state = ProxyState {
+            upstream_name: name,
+            upstream_url: Arc::new(upstream.to_string()),
+            mode: mode.clone(),
+        };
+        let app = Router::new().fallback(handle).with_state(state);
+        let listener = tokio::net::TcpListener::bind(listen)
+            .await
+            .with_context(|| format!("{} 代理监听失败: {}", name, listen))?;
+        info!("📼 {} 代理（{}）已启动 | 监听:http://{} | 上游:{}", name, action, listen, upstream);
+        servers.push(tokio::spawn(async move { axum::serve(listener, app).await }));
+    }
+    println!(
+        "设置 GAMMA_API_URL=http://{} CLOB_REST_URL=http://{} 后运行机器人；Ctrl+C 退出",
+        gamma_listen, clob_listen
+    );
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => Ok(()),
+        _ = futures::future::join_all(servers) => bail!("代理服务已退出"),
+    }
+}
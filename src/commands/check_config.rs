@@ -0,0 +1,353 @@
+//! check-config 子命令：不交易，只验证完整配置能否正常运行——私钥、CLOB 认证、代理钱包类型、
+//! Polygon RPC、Gamma 可达性、时钟同步、USDC 余额与授权，打印通过/警告/失败报告，便于在第一个窗口前发现部署问题。
+//! 主循环启动前也会执行同一套检查（`preflight`），严格模式下未就绪则拒绝启动。
+//!
+//! 用法示例：
+//!   poly_1hour_bot check-config
+
+use alloy::primitives::{Address, U256};
+use alloy::providers::{Provider, ProviderBuilder};
+use alloy::signers::local::LocalSigner;
+use alloy::signers::Signer;
+use alloy::sol;
+use anyhow::Result;
+use chrono::Utc;
+use poly_1hour_bot::merge;
+use polymarket_client_sdk::clob::types::SignatureType;
+use polymarket_client_sdk::clob::{Client, Config as ClobConfig};
+use polymarket_client_sdk::{contract_config, POLYGON};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+use crate::config::Config;
+
+sol! {
+    #[sol(rpc)]
+    interface IERC20Status {
+        function balanceOf(address account) external view returns (uint256);
+        function allowance(address owner, address spender) external view returns (uint256);
+    }
+
+    #[sol(rpc)]
+    interface IERC1155Approval {
+        function isApprovedForAll(address account, address operator) external view returns (bool);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl Status {
+    fn label(&self) -> &'static str {
+        match self {
+            Status::Pass => "✅ 通过",
+            Status::Warn => "⚠️ 警告",
+            Status::Fail => "❌ 失败",
+        }
+    }
+}
+
+/// 检查项类别：严格模式按类别决定警告是否阻止启动
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Config,
+    Key,
+    Auth,
+    Proxy,
+    Rpc,
+    Gamma,
+    Clock,
+    Balance,
+    Allowance,
+}
+
+impl Category {
+    /// 余额、授权、时钟问题会导致窗口中途下单失败，严格模式下其警告也视为未就绪
+    fn strict(&self) -> bool {
+        matches!(self, Category::Clock | Category::Balance | Category::Allowance)
+    }
+}
+
+pub struct Report {
+    rows: Vec<(Status, Category, &'static str, String)>,
+}
+
+impl Report {
+    fn add(&mut self, status: Status, category: Category, name: &'static str, detail: impl Into<String>) {
+        self.rows.push((status, category, name, detail.into()));
+    }
+
+    fn print(&self) {
+        println!();
+        for (status, _, name, detail) in &self.rows {
+            println!("{:<8} {:<14} {}", status.label(), name, detail);
+        }
+        let failed = self.rows.iter().filter(|r| r.0 == Status::Fail).count();
+        let warned = self.rows.iter().filter(|r| r.0 == Status::Warn).count();
+        println!();
+        println!("共 {} 项：失败 {}，警告 {}", self.rows.len(), failed, warned);
+    }
+
+    /// 启动前就绪汇总写入日志
+    pub fn log(&self) {
+        for (status, _, name, detail) in &self.rows {
+            match status {
+                Status::Pass => info!("{} {} | {}", status.label(), name, detail),
+                Status::Warn => warn!("{} {} | {}", status.label(), name, detail),
+                Status::Fail => error!("{} {} | {}", status.label(), name, detail),
+            }
+        }
+        let failed = self.rows.iter().filter(|r| r.0 == Status::Fail).count();
+        let warned = self.rows.iter().filter(|r| r.0 == Status::Warn).count();
+        info!("🩺 启动就绪检查 | 共{}项 | 失败:{} | 警告:{}", self.rows.len(), failed, warned);
+    }
+
+    pub fn has_failure(&self) -> bool {
+        self.rows.iter().any(|r| r.0 == Status::Fail)
+    }
+
+    /// 严格模式下未就绪的检查项：任何失败，或余额/授权/时钟类的警告
+    pub fn strict_blockers(&self) -> Vec<&'static str> {
+        self.rows
+            .iter()
+            .filter(|r| r.0 == Status::Fail || (r.0 == Status::Warn && r.1.strict()))
+            .map(|r| r.2)
+            .collect()
+    }
+}
+
+/// USDC 金额（6 位小数）转为浮点数，便于展示
+fn usdc(amount: U256) -> f64 {
+    amount.to_string().parse::<f64>().unwrap_or(0.0) / 1e6
+}
+
+pub async fn run(args: &[String]) -> Result<()> {
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        eprintln!("用法: poly_1hour_bot check-config");
+        eprintln!("  验证私钥、CLOB 认证、代理钱包、RPC、Gamma、时钟、USDC 余额与授权，不下单");
+        return Ok(());
+    }
+
+    let config = match Config::from_env() {
+        Ok(config) => config,
+        Err(e) => {
+            let mut report = Report { rows: Vec::new() };
+            report.add(Status::Fail, Category::Config, "配置加载", e.to_string());
+            report.print();
+            anyhow::bail!("配置检查未通过");
+        }
+    };
+
+    let report = preflight(&config).await;
+    report.print();
+    if report.has_failure() {
+        anyhow::bail!("配置检查未通过");
+    }
+    Ok(())
+}
+
+/// 执行全部检查并返回报告（只读，不下单、不发链上交易）
+pub async fn preflight(config: &Config) -> Report {
+    let mut report = Report { rows: Vec::new() };
+    report.add(
+        Status::Pass,
+        Category::Config,
+        "配置加载",
+        format!("{} 个市场系列", config.market_series.len()),
+    );
+
+    // 私钥
+    let signer = match LocalSigner::from_str(&config.private_key) {
+        Ok(signer) => {
+            let signer = signer.with_chain_id(Some(POLYGON));
+            report.add(Status::Pass, Category::Key, "私钥", format!("EOA {:#x}", signer.address()));
+            Some(signer)
+        }
+        Err(e) => {
+            report.add(Status::Fail, Category::Key, "私钥", format!("格式无效: {}", e));
+            None
+        }
+    };
+
+    // CLOB 认证
+    if let Some(signer) = signer.as_ref() {
+        let auth = async {
+            let clob_config = ClobConfig::builder().use_server_time(true).build();
+            let mut builder = Client::new(&config.endpoints.clob_rest, clob_config)?.authentication_builder(signer);
+            if let Some(funder) = config.proxy_address {
+                builder = builder.funder(funder).signature_type(SignatureType::Proxy);
+            }
+            builder.authenticate().await.map_err(anyhow::Error::from)
+        }
+        .await;
+        match auth {
+            Ok(_) => report.add(Status::Pass, Category::Auth, "CLOB 认证", config.endpoints.clob_rest.clone()),
+            Err(e) => report.add(Status::Fail, Category::Auth, "CLOB 认证", e.to_string()),
+        }
+    }
+
+    let http = match reqwest::Client::builder().timeout(Duration::from_secs(10)).build() {
+        Ok(http) => http,
+        Err(e) => {
+            report.add(Status::Fail, Category::Config, "HTTP 客户端", e.to_string());
+            return report;
+        }
+    };
+
+    // 时钟同步：本地时间与 CLOB 服务器时间（秒级）对比，按往返中点估算偏差
+    let time_url = format!("{}/time", config.endpoints.clob_rest.trim_end_matches('/'));
+    let start = Instant::now();
+    let local_before = Utc::now();
+    let server_time = async {
+        let body = http.get(&time_url).send().await?.text().await?;
+        body.trim()
+            .parse::<i64>()
+            .map_err(|e| anyhow::anyhow!("无法解析服务器时间 {:?}: {}", body, e))
+    }
+    .await;
+    match server_time {
+        Ok(server_secs) => {
+            let local_mid = local_before + chrono::Duration::from_std(start.elapsed() / 2).unwrap_or_default();
+            let skew = (local_mid.timestamp_millis() - server_secs * 1000) as f64 / 1000.0;
+            let status = if skew.abs() > config.clock_max_skew_secs { Status::Warn } else { Status::Pass };
+            report.add(status, Category::Clock, "时钟同步", format!("本地比服务器快 {:+.1} 秒", skew));
+        }
+        Err(e) => report.add(Status::Warn, Category::Clock, "时钟同步", e.to_string()),
+    }
+
+    // Gamma
+    let gamma_url = format!("{}/markets?limit=1", config.endpoints.gamma.trim_end_matches('/'));
+    match http.get(&gamma_url).send().await {
+        Ok(resp) if resp.status().is_success() => {
+            report.add(Status::Pass, Category::Gamma, "Gamma", config.endpoints.gamma.clone())
+        }
+        Ok(resp) => report.add(Status::Fail, Category::Gamma, "Gamma", format!("HTTP {}", resp.status())),
+        Err(e) => report.add(Status::Fail, Category::Gamma, "Gamma", e.to_string()),
+    }
+
+    // RPC
+    let provider = match ProviderBuilder::new().connect(merge::RPC_URL_DEFAULT).await {
+        Ok(provider) => match provider.get_block_number().await {
+            Ok(block) => {
+                report.add(Status::Pass, Category::Rpc, "Polygon RPC", format!("最新区块 {}", block));
+                Some(provider)
+            }
+            Err(e) => {
+                report.add(Status::Fail, Category::Rpc, "Polygon RPC", e.to_string());
+                None
+            }
+        },
+        Err(e) => {
+            report.add(Status::Fail, Category::Rpc, "Polygon RPC", e.to_string());
+            None
+        }
+    };
+
+    // 代理钱包、余额与授权（需要 RPC 与私钥）
+    if let (Some(provider), Some(signer)) = (provider.as_ref(), signer.as_ref()) {
+        let eoa = signer.address();
+        let funder: Address = config.proxy_address.unwrap_or(eoa);
+
+        match config.proxy_address {
+            Some(proxy) => {
+                let code = provider.get_code_at(proxy).await.unwrap_or_default();
+                if code.is_empty() {
+                    report.add(Status::Warn, Category::Proxy, "代理钱包", format!("{:#x} 尚未部署合约", proxy));
+                } else if code.len() >= 150 {
+                    report.add(Status::Pass, Category::Proxy, "代理钱包", format!("Gnosis Safe {:#x}", proxy));
+                } else if merge::expected_proxy_wallet(eoa) == proxy {
+                    report.add(Status::Pass, Category::Proxy, "代理钱包", format!("Magic/Email 代理 {:#x}", proxy));
+                } else {
+                    report.add(
+                        Status::Fail,
+                        Category::Proxy,
+                        "代理钱包",
+                        format!("{:#x} 与 EOA 推导的代理地址 {:#x} 不一致", proxy, merge::expected_proxy_wallet(eoa)),
+                    );
+                }
+                let has_builder = ["POLY_BUILDER_API_KEY", "POLY_BUILDER_SECRET", "POLY_BUILDER_PASSPHRASE"]
+                    .iter()
+                    .all(|k| std::env::var(k).map(|v| !v.trim().is_empty()).unwrap_or(false));
+                if has_builder {
+                    report.add(Status::Pass, Category::Proxy, "Builder Keys", "已配置（可走 Relayer merge）");
+                } else {
+                    report.add(
+                        Status::Warn,
+                        Category::Proxy,
+                        "Builder Keys",
+                        "未配置，merge 只能走直接交易（EOA 需有 POL）",
+                    );
+                }
+            }
+            None => report.add(
+                Status::Warn,
+                Category::Proxy,
+                "代理钱包",
+                "未设置 POLYMARKET_PROXY_ADDRESS，使用 EOA 直接交易，定时 Merge 不可用",
+            ),
+        }
+
+        match provider.get_balance(eoa).await {
+            Ok(wei) => {
+                let pol = wei.to_string().parse::<f64>().unwrap_or(0.0) / 1e18;
+                let status = if pol < 0.05 { Status::Warn } else { Status::Pass };
+                report.add(status, Category::Balance, "EOA gas", format!("{:.4} POL", pol));
+            }
+            Err(e) => report.add(Status::Warn, Category::Balance, "EOA gas", e.to_string()),
+        }
+
+        if let Some(ctf_config) = contract_config(POLYGON, false) {
+            let usdc_token = IERC20Status::new(merge::USDC_POLYGON, provider.clone());
+            match usdc_token.balanceOf(funder).call().await {
+                Ok(balance) => {
+                    // 一次套利两腿合计最多约 2 × 单笔上限
+                    let needed = config.max_order_size_ceiling() * 2.0;
+                    let status = if usdc(balance) < needed { Status::Warn } else { Status::Pass };
+                    report.add(
+                        status,
+                        Category::Balance,
+                        "USDC 余额",
+                        format!("{:.2} USDC（{:#x}）", usdc(balance), funder),
+                    );
+                }
+                Err(e) => report.add(Status::Fail, Category::Balance, "USDC 余额", e.to_string()),
+            }
+
+            let mut spenders = vec![("CTF Exchange", ctf_config.exchange)];
+            if let Some(neg_risk) = contract_config(POLYGON, true) {
+                spenders.push(("NegRisk Exchange", neg_risk.exchange));
+            }
+            let ctf = IERC1155Approval::new(ctf_config.conditional_tokens, provider.clone());
+            for (name, spender) in spenders {
+                let allowance = usdc_token.allowance(funder, spender).call().await;
+                let approved = ctf.isApprovedForAll(funder, spender).call().await;
+                match (allowance, approved) {
+                    (Ok(allowance), Ok(approved)) => {
+                        let ok = allowance > U256::ZERO && approved;
+                        report.add(
+                            if ok { Status::Pass } else { Status::Warn },
+                            Category::Allowance,
+                            "授权",
+                            format!(
+                                "{}：USDC 授权 {:.2}，CTF 授权 {}",
+                                name,
+                                usdc(allowance),
+                                if approved { "是" } else { "否" }
+                            ),
+                        );
+                    }
+                    (Err(e), _) | (_, Err(e)) => {
+                        report.add(Status::Fail, Category::Allowance, "授权", format!("{}：{}", name, e))
+                    }
+                }
+            }
+        }
+    }
+
+    report
+}
@@ -0,0 +1,181 @@
+//! latency 子命令：从当前主机持续测量到 CLOB REST、CLOB WS 与 Gamma 的往返延迟，
+//! 帮助选择部署位置，或对比 CLOB_REST_URL / CLOB_WS_URL / GAMMA_API_URL 固定的备用端点。
+//!
+//! 用法示例：
+//!   poly_1hour_bot latency
+//!   poly_1hour_bot latency --samples 60 --interval-ms 1000
+
+use anyhow::{Context, Result};
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio::time::sleep;
+
+use crate::config::Endpoints;
+
+/// 单个端点的测量结果（毫秒）
+struct EndpointStats {
+    name: &'static str,
+    target: String,
+    /// 首次请求耗时（含 DNS/TCP/TLS 握手）
+    first_ms: Option<f64>,
+    /// 之后各次请求耗时（复用连接）
+    samples_ms: Vec<f64>,
+    errors: usize,
+}
+
+impl EndpointStats {
+    fn new(name: &'static str, target: String) -> Self {
+        Self {
+            name,
+            target,
+            first_ms: None,
+            samples_ms: Vec::new(),
+            errors: 0,
+        }
+    }
+
+    fn record(&mut self, result: Result<f64>) {
+        match result {
+            Ok(ms) if self.first_ms.is_none() => self.first_ms = Some(ms),
+            Ok(ms) => self.samples_ms.push(ms),
+            Err(_) => self.errors += 1,
+        }
+    }
+}
+
+/// 取已排序样本的百分位数
+fn percentile(sorted: &[f64], pct: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let idx = ((pct / 100.0) * (sorted.len() - 1) as f64).round() as usize;
+    sorted[idx.min(sorted.len() - 1)]
+}
+
+/// 从 ws(s)://host[:port]/path 中取出 host:port，用于 TCP 往返测量
+fn ws_host_port(url: &str) -> Result<String> {
+    let (default_port, rest) = if let Some(r) = url.strip_prefix("wss://") {
+        (443, r)
+    } else if let Some(r) = url.strip_prefix("ws://") {
+        (80, r)
+    } else {
+        anyhow::bail!("CLOB_WS_URL 须以 ws:// 或 wss:// 开头: {}", url);
+    };
+    let host = rest.split('/').next().unwrap_or(rest);
+    if host.contains(':') {
+        Ok(host.to_string())
+    } else {
+        Ok(format!("{}:{}", host, default_port))
+    }
+}
+
+/// HTTP GET 一次并返回耗时（毫秒），只要服务端有响应即视为成功
+async fn probe_http(client: &reqwest::Client, url: &str) -> Result<f64> {
+    let start = Instant::now();
+    let resp = client.get(url).send().await?;
+    let _ = resp.bytes().await?;
+    Ok(start.elapsed().as_secs_f64() * 1000.0)
+}
+
+/// TCP 建连一次并返回耗时（毫秒），近似 WS 端点的网络往返
+async fn probe_tcp(host_port: &str) -> Result<f64> {
+    let start = Instant::now();
+    let _stream = TcpStream::connect(host_port).await?;
+    Ok(start.elapsed().as_secs_f64() * 1000.0)
+}
+
+fn print_usage() {
+    eprintln!("用法: poly_1hour_bot latency [--samples N] [--interval-ms MS]");
+    eprintln!("  --samples N        每个端点采样次数，默认 20");
+    eprintln!("  --interval-ms MS   两次采样间隔（毫秒），默认 500");
+    eprintln!("  端点取自 CLOB_REST_URL / CLOB_WS_URL / GAMMA_API_URL，未设置时为官方地址");
+}
+
+pub async fn run(args: &[String]) -> Result<()> {
+    let mut samples: usize = 20;
+    let mut interval_ms: u64 = 500;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--samples" => {
+                i += 1;
+                samples = args
+                    .get(i)
+                    .context("--samples 需要参数")?
+                    .parse()
+                    .context("--samples 必须为正整数")?;
+                i += 1;
+            }
+            "--interval-ms" => {
+                i += 1;
+                interval_ms = args
+                    .get(i)
+                    .context("--interval-ms 需要参数")?
+                    .parse()
+                    .context("--interval-ms 必须为正整数")?;
+                i += 1;
+            }
+            _ => {
+                print_usage();
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let endpoints = Endpoints::from_env();
+    let clob_url = format!("{}/time", endpoints.clob_rest.trim_end_matches('/'));
+    let gamma_url = format!("{}/markets?limit=1", endpoints.gamma.trim_end_matches('/'));
+    let ws_target = ws_host_port(&endpoints.clob_ws)?;
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()?;
+
+    let mut clob = EndpointStats::new("CLOB REST", clob_url.clone());
+    let mut gamma = EndpointStats::new("Gamma", gamma_url.clone());
+    let mut ws = EndpointStats::new("CLOB WS (TCP)", ws_target.clone());
+
+    println!("测量 {} 次，间隔 {}ms ...", samples, interval_ms);
+    // 多取一次：首次单独统计（含握手），其余为复用连接后的往返
+    for n in 0..=samples {
+        let (c, g, w) = tokio::join!(
+            probe_http(&client, &clob_url),
+            probe_http(&client, &gamma_url),
+            probe_tcp(&ws_target),
+        );
+        clob.record(c);
+        gamma.record(g);
+        ws.record(w);
+        if n < samples {
+            sleep(Duration::from_millis(interval_ms)).await;
+        }
+    }
+
+    println!();
+    println!(
+        "{:<14} {:>10} {:>8} {:>8} {:>8} {:>8} {:>6}  目标",
+        "端点", "首次(ms)", "min", "p50", "p95", "max", "失败"
+    );
+    for stats in [&mut clob, &mut ws, &mut gamma] {
+        stats.samples_ms.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let sorted = &stats.samples_ms;
+        println!(
+            "{:<14} {:>10} {:>8.1} {:>8.1} {:>8.1} {:>8.1} {:>6}  {}",
+            stats.name,
+            stats
+                .first_ms
+                .map(|ms| format!("{:.1}", ms))
+                .unwrap_or_else(|| "-".to_string()),
+            sorted.first().copied().unwrap_or(0.0),
+            percentile(sorted, 50.0),
+            percentile(sorted, 95.0),
+            sorted.last().copied().unwrap_or(0.0),
+            stats.errors,
+            stats.target
+        );
+    }
+    println!();
+    println!("提示：首次耗时含握手，连接保活（HTTP_KEEPALIVE_INTERVAL_SECS）可让实盘下单接近 p50。");
+    Ok(())
+}
@@ -0,0 +1,56 @@
+//! 子命令：`poly_1hour_bot <command> [args]`，用于部署前的诊断与运维工具，执行完即退出。
+
+use anyhow::Result;
+
+pub mod backfill;
+pub mod cassette;
+pub mod check_config;
+pub mod latency;
+pub mod observe;
+pub mod optimize;
+mod rng;
+pub mod spreads;
+pub mod state;
+pub mod stress;
+
+/// 打印子命令用法
+fn print_usage() {
+    eprintln!("用法: poly_1hour_bot [command] [args]");
+    eprintln!("  不带参数          进入交易主循环");
+    eprintln!("  backfill [...]    从 Data API 导入账户历史成交 / merge / 赎回到存储后端的交易日志（--help 查看参数）");
+    eprintln!("  cassette [...]    Gamma/CLOB REST 录制-回放代理，离线复现发现、认证与下单流程（--help 查看参数）");
+    eprintln!("  check-config      部署前检查私钥、认证、代理钱包、RPC、Gamma、余额与授权，不交易");
+    eprintln!("  latency [...]     测量到 CLOB REST/WS 与 Gamma 的往返延迟（--help 查看参数）");
+    eprintln!("  observe [...]     观察模式：无需私钥，只发现市场、检测并记录套利机会，不认证不交易（--help 查看参数）");
+    eprintln!("  optimize [...]    在录制的订单簿上做参数搜索，按模拟净盈亏排序（--help 查看参数）");
+    eprintln!("  spreads [...]     查询存储后端中的价差采样，统计套利价差频率/时长并可绘图（--help 查看参数）");
+    eprintln!("  stress [...]      蒙特卡洛敞口压力模拟，输出尾部损失与上限拦截比例（--help 查看参数）");
+    eprintln!("  export-state [...] 把检查点等落盘状态打包成一个文件，用于迁移/升级（--help 查看参数）");
+    eprintln!("  import-state [...] 在新主机上解包状态文件到配置路径（--help 查看参数）");
+    eprintln!("  decrypt-state <文件> 解密加密落盘的交易日志 / 检查点并输出");
+}
+
+/// 分发子命令
+pub async fn run(command: &str, args: &[String]) -> Result<()> {
+    match command {
+        "backfill" => backfill::run(args).await,
+        "cassette" => cassette::run(args).await,
+        "check-config" => check_config::run(args).await,
+        "latency" => latency::run(args).await,
+        "observe" => observe::run(args).await,
+        "optimize" => optimize::run(args).await,
+        "spreads" => spreads::run(args).await,
+        "stress" => stress::run(args).await,
+        "export-state" => state::run_export(args),
+        "import-state" => state::run_import(args),
+        "decrypt-state" => state::run_decrypt(args),
+        "help" | "--help" | "-h" => {
+            print_usage();
+            Ok(())
+        }
+        _ => {
+            print_usage();
+            anyhow::bail!("未知子命令: {}", command)
+        }
+    }
+}
@@ -0,0 +1,255 @@
+//! observe 子命令：无私钥的观察模式。照常发现市场、订阅订单簿并运行套利检测，
+//! 把机会写入日志、交易日志与事件输出（Redis / 事件流 / Webhook），但从不认证、不下单、不 merge。
+//! 适合研究与在实盘行情上安全验证检测器；每个窗口结束时按市场打印机会统计。
+//!
+//! 用法示例：
+//!   poly_1hour_bot observe
+//!   poly_1hour_bot observe --windows 3
+
+use anyhow::Result;
+use chrono::Utc;
+use futures::StreamExt;
+use polymarket_client_sdk::types::{Decimal, B256};
+use rust_decimal_macros::dec;
+use serde_json::json;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+use tracing::{error, info, warn};
+
+use crate::config::Config;
+use crate::market::{MarketDiscoverer, MarketInfo, MarketScheduler};
+use crate::monitor::{ArbitrageDetector, OrderBookMonitor};
+use crate::utils::events::{self, BotEvent};
+use crate::utils::{journal, metrics};
+
+/// 单个市场在一个窗口内的观察统计
+#[derive(Default)]
+struct MarketStats {
+    /// 收到的订单簿对更新数
+    updates: u64,
+    /// 检测到机会的更新数
+    opportunity_ticks: u64,
+    /// 机会出现的次数（从无到有算一次）
+    episodes: u64,
+    /// 机会持续的累计时长
+    open_duration: Duration,
+    /// 当前机会开始时间
+    open_since: Option<Instant>,
+    /// 最高净利润率（%）与对应可成交份额
+    best_profit_pct: Decimal,
+    best_size: Decimal,
+    /// 最低双边卖一总价
+    min_total_ask: Option<Decimal>,
+}
+
+impl MarketStats {
+    fn close(&mut self) {
+        if let Some(since) = self.open_since.take() {
+            self.open_duration += since.elapsed();
+        }
+    }
+}
+
+fn parse_windows(args: &[String]) -> Option<u64> {
+    args.iter()
+        .position(|a| a == "--windows")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|v| v.parse().ok())
+}
+
+pub async fn run(args: &[String]) -> Result<()> {
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        eprintln!("用法: poly_1hour_bot observe [--windows N]（观察 N 个窗口后退出，默认一直运行）");
+        return Ok(());
+    }
+    let max_windows = parse_windows(args);
+    let config = Config::from_env()?;
+    crate::market::clock::init(config.market_timezone);
+    crate::market::window::init(&config.window_durations);
+    crate::utils::state_crypto::init_from_env()?;
+    journal::init(&config.journal_path);
+    crate::utils::storage::init(&config.storage_url, &config.instance_id).await?;
+    crate::utils::redis_sink::spawn(&config.redis_url, &config.redis_channel_prefix);
+    crate::utils::stream_sink::spawn(config.stream_sink.clone());
+    crate::utils::webhooks::spawn(config.webhooks.clone());
+    info!("👀 观察模式：只检测并记录套利机会，不认证、不下单");
+
+    let discoverer = MarketDiscoverer::new(
+        &config.endpoints.gamma,
+        config.market_series.clone(),
+        &config.market_cache_path,
+    )
+    .with_discovery(config.discovery_mode, config.discovery_tags.clone());
+    let scheduler = MarketScheduler::new(
+        discoverer,
+        config.max_refresh_advance_secs(),
+        config.discovery_retry_max_backoff_secs,
+        config.discovery_max_retries_per_window,
+    );
+    let detector = ArbitrageDetector::new(config.min_profit_threshold);
+    let execution_threshold = dec!(1) - Decimal::try_from(config.arbitrage_execution_spread).unwrap_or(dec!(0.01));
+
+    let mut observed = 0u64;
+    loop {
+        if max_windows.is_some_and(|n| observed >= n) {
+            return Ok(());
+        }
+        let markets = match scheduler.get_markets_immediately_or_wait().await {
+            Ok(markets) if !markets.is_empty() => markets,
+            Ok(_) => {
+                warn!("当前窗口没有可观察的市场");
+                sleep(Duration::from_secs(60)).await;
+                continue;
+            }
+            Err(e) => {
+                error!(error = %e, "获取市场失败");
+                sleep(Duration::from_secs(60)).await;
+                continue;
+            }
+        };
+        let window = MarketDiscoverer::calculate_current_window_timestamp(Utc::now());
+        let window_end =
+            chrono::DateTime::from_timestamp(MarketDiscoverer::window_end_timestamp(window), 0).unwrap_or_else(Utc::now);
+        let snapshot_url = config.book_snapshot_on_subscribe.then_some(config.endpoints.clob_rest.as_str());
+        observe_window(&markets, window_end, &detector, execution_threshold, snapshot_url).await;
+        observed += 1;
+        let remaining = (window_end - Utc::now()).to_std().unwrap_or(Duration::ZERO);
+        sleep(remaining).await;
+    }
+}
+
+/// 观察一个窗口直到结束，结束时打印统计
+async fn observe_window(
+    markets: &[MarketInfo],
+    window_end: chrono::DateTime<Utc>,
+    detector: &ArbitrageDetector,
+    execution_threshold: Decimal,
+    snapshot_url: Option<&str>,
+) {
+    let mut monitor = OrderBookMonitor::new();
+    if let Some(url) = snapshot_url {
+        monitor = monitor.with_snapshots(url);
+    }
+    for market in markets {
+        if let Err(e) = monitor.subscribe_market(market) {
+            error!(error = %e, market_id = %market.market_id, "订阅市场失败");
+        }
+    }
+    let names: HashMap<B256, String> = markets
+        .iter()
+        .map(|m| {
+            let name = if m.crypto_symbol.is_empty() { m.title.clone() } else { m.crypto_symbol.clone() };
+            (m.market_id, name)
+        })
+        .collect();
+    let mut stream = match monitor.create_orderbook_stream() {
+        Ok(stream) => stream,
+        Err(e) => {
+            error!(error = %e, "创建订单簿流失败");
+            return;
+        }
+    };
+    info!(market_count = markets.len(), "👀 开始观察本窗口");
+
+    let mut stats: HashMap<B256, MarketStats> = HashMap::new();
+    let until_end = (window_end - Utc::now()).to_std().unwrap_or(Duration::ZERO);
+    let deadline = tokio::time::sleep(until_end);
+    tokio::pin!(deadline);
+    loop {
+        let book = tokio::select! {
+            _ = &mut deadline => break,
+            book = stream.next() => book,
+        };
+        let book = match book {
+            Some(Ok(book)) => book,
+            Some(Err(e)) => {
+                warn!(error = %e, "订单簿更新错误");
+                continue;
+            }
+            None => {
+                warn!("订单簿流已结束，本窗口停止观察");
+                break;
+            }
+        };
+        let Some(pair) = monitor.handle_book_update(book) else {
+            continue;
+        };
+        let entry = stats.entry(pair.market_id).or_default();
+        entry.updates += 1;
+        let total_ask = match (pair.yes_book.asks.last(), pair.no_book.asks.last()) {
+            (Some(y), Some(n)) => y.price + n.price,
+            _ => continue,
+        };
+        entry.min_total_ask = Some(entry.min_total_ask.map_or(total_ask, |m| m.min(total_ask)));
+
+        let opp = (total_ask <= execution_threshold)
+            .then(|| detector.check_arbitrage(&pair.yes_book, &pair.no_book, &pair.market_id))
+            .flatten();
+        let Some(opp) = opp else {
+            entry.close();
+            continue;
+        };
+        entry.opportunity_ticks += 1;
+        if entry.open_since.is_none() {
+            entry.open_since = Some(Instant::now());
+            entry.episodes += 1;
+            let name = names.get(&pair.market_id).map(String::as_str).unwrap_or("");
+            info!(
+                "🚨 [观察] 套利机会 | 市场:{} | Yes:{:.4} + No:{:.4} = {:.4} | 利润:{:.2}% | 可成交:{}份",
+                name, opp.yes_ask_price, opp.no_ask_price, total_ask, opp.profit_percentage, opp.yes_size
+            );
+            events::publish(BotEvent::opportunity(&opp, name));
+            journal::record(
+                "observed_opportunity",
+                json!({
+                    "market_id": format!("{:#x}", opp.market_id),
+                    "market": name,
+                    "yes_ask": opp.yes_ask_price.to_string(),
+                    "no_ask": opp.no_ask_price.to_string(),
+                    "profit_pct": opp.profit_percentage.to_string(),
+                    "size": opp.yes_size.to_string(),
+                }),
+            );
+            metrics::incr("observed_opportunities");
+        }
+        if opp.profit_percentage > entry.best_profit_pct {
+            entry.best_profit_pct = opp.profit_percentage;
+            entry.best_size = opp.yes_size;
+        }
+    }
+
+    info!("📋 本窗口观察统计：");
+    for market in markets {
+        let Some(s) = stats.get_mut(&market.market_id) else {
+            continue;
+        };
+        s.close();
+        let name = names.get(&market.market_id).map(String::as_str).unwrap_or("");
+        info!(
+            "  {} | 更新:{} | 机会次数:{} | 机会更新:{} | 累计时长:{:.1}s | 最高利润:{:.2}%（{}份） | 最低总价:{}",
+            name,
+            s.updates,
+            s.episodes,
+            s.opportunity_ticks,
+            s.open_duration.as_secs_f64(),
+            s.best_profit_pct,
+            s.best_size,
+            s.min_total_ask.map(|t| format!("{:.4}", t)).unwrap_or_else(|| "-".to_string())
+        );
+        journal::record(
+            "observed_window_summary",
+            json!({
+                "market_id": format!("{:#x}", market.market_id),
+                "market": name,
+                "updates": s.updates,
+                "episodes": s.episodes,
+                "opportunity_ticks": s.opportunity_ticks,
+                "open_secs": s.open_duration.as_secs_f64(),
+                "best_profit_pct": s.best_profit_pct.to_string(),
+                "best_size": s.best_size.to_string(),
+                "min_total_ask": s.min_total_ask.map(|t| t.to_string()),
+            }),
+        );
+    }
+}
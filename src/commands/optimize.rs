@@ -0,0 +1,440 @@
+//! optimize 子命令：在录制的订单簿（BOOK_RECORD_PATH）上回放套利检测与下单，
+//! 对 最小利润阈值 / 套利执行价差 / 滑点 / 单笔上限 做网格或随机搜索，按模拟净盈亏排序输出。
+//!
+//! 成交模型：触发时按卖一价加滑点作为限价，在订单簿推送延迟（--book-latency-ms）与下单回报延迟（--latency-ms）
+//! 之后的同市场订单簿上逐档吃单（FAK）；成对部分按 1 USD/对结算，多出的单腿按成交时买一价卖出。不计手续费与 gas。
+//!
+//! 延迟可给多个值（逗号分隔），每种延迟组合都完整回放一遍，并按最差情况下的净盈亏排序，
+//! 便于比较参数在 50ms 与 300ms 等不同网络条件下的退化程度、挑选更稳健的设置；--jitter-ms 为每笔额外加随机抖动。
+//!
+//! 用法示例：
+//!   poly_1hour_bot optimize --data state/books.jsonl
+//!   poly_1hour_bot optimize --spread 0.01,0.02,0.03 --slippage 0,0.01 --size 5,10,20
+//!   poly_1hour_bot optimize --random 200 --spread 0.005,0.04 --slippage 0,0.03
+//!   poly_1hour_bot optimize --latency-ms 50,300 --book-latency-ms 0,100 --jitter-ms 50
+
+use anyhow::{Context, Result};
+use polymarket_client_sdk::types::Decimal;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::str::FromStr;
+
+use super::rng::XorShift;
+use crate::utils::book_recorder::BookRecord;
+
+/// 与主循环一致的两次套利最小间隔
+const MIN_TRADE_INTERVAL_MS: i64 = 3_000;
+/// 未指定 --seed 时抖动使用的固定种子，保证各组参数面对同一串抖动
+const JITTER_SEED: u64 = 0x5EED;
+
+fn print_usage() {
+    eprintln!("用法: poly_1hour_bot optimize [--data PATH] [--threshold L] [--spread L] [--slippage L] [--size L]");
+    eprintln!("                              [--random N] [--seed S] [--latency-ms L] [--book-latency-ms L]");
+    eprintln!("                              [--jitter-ms MS] [--top N]");
+    eprintln!("  --data PATH        录制文件，默认 BOOK_RECORD_PATH 或 state/books.jsonl");
+    eprintln!("  --threshold L      最小利润阈值列表（逗号分隔），默认 0,0.005,0.01");
+    eprintln!("  --spread L         套利执行价差列表，默认 0.01,0.02,0.03");
+    eprintln!("  --slippage L       滑点列表，默认 0,0.01,0.02");
+    eprintln!("  --size L           单笔上限（MAX_ORDER_SIZE_USDC）列表，默认 5,10,20");
+    eprintln!("  --random N         随机搜索 N 组（各参数在列表最小值与最大值之间均匀采样），默认网格搜索");
+    eprintln!("  --seed S           随机搜索种子，默认取当前时间（同时作为抖动种子）");
+    eprintln!("  --latency-ms L     下单到成交回报的模拟延迟列表（毫秒），默认 200");
+    eprintln!("  --book-latency-ms L 订单簿推送的模拟延迟列表（毫秒），默认 0");
+    eprintln!("  --jitter-ms MS     每笔在两种延迟上各叠加 [0, MS) 的随机抖动，默认 0");
+    eprintln!("  --top N            输出前 N 组，默认 20");
+}
+
+/// 回放用的订单簿快照
+struct Snapshot {
+    ts_ms: i64,
+    /// 卖盘，最优价在前
+    yes_asks: Vec<(Decimal, Decimal)>,
+    no_asks: Vec<(Decimal, Decimal)>,
+    yes_bid: Option<Decimal>,
+    no_bid: Option<Decimal>,
+}
+
+impl Snapshot {
+    fn from_record(record: &BookRecord) -> Option<Self> {
+        let levels = |side: &[[String; 2]]| -> Option<Vec<(Decimal, Decimal)>> {
+            side.iter()
+                .map(|[p, s]| Some((Decimal::from_str(p).ok()?, Decimal::from_str(s).ok()?)))
+                .collect()
+        };
+        Some(Self {
+            ts_ms: record.ts_ms,
+            yes_asks: levels(&record.yes_asks)?,
+            no_asks: levels(&record.no_asks)?,
+            yes_bid: record.yes_bid.as_deref().and_then(|p| Decimal::from_str(p).ok()),
+            no_bid: record.no_bid.as_deref().and_then(|p| Decimal::from_str(p).ok()),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Params {
+    threshold: Decimal,
+    spread: Decimal,
+    slippage: Decimal,
+    size: Decimal,
+}
+
+/// 一种模拟延迟组合
+#[derive(Debug, Clone, Copy)]
+struct Latency {
+    /// 订单簿推送延迟：机器人看到的总是这么久之前的订单簿
+    book_ms: i64,
+    /// 下单到成交回报的延迟
+    ack_ms: i64,
+    /// 每笔叠加的随机抖动上限
+    jitter_ms: i64,
+}
+
+impl Latency {
+    fn label(&self) -> String {
+        if self.book_ms == 0 {
+            format!("{}ms", self.ack_ms)
+        } else {
+            format!("{}+{}ms", self.book_ms, self.ack_ms)
+        }
+    }
+}
+
+struct SimResult {
+    trades: u32,
+    one_sided: u32,
+    matched: Decimal,
+    net: Decimal,
+}
+
+/// 一组参数在各延迟组合下的回放结果（与 latencies 顺序一致）
+struct Evaluation {
+    params: Params,
+    runs: Vec<SimResult>,
+}
+
+impl Evaluation {
+    /// 最差延迟下的净盈亏，用于挑选稳健参数
+    fn worst_net(&self) -> Decimal {
+        self.runs.iter().map(|r| r.net).min().unwrap_or_default()
+    }
+}
+
+/// 按限价逐档吃单，返回 (成交数量, 成交金额)
+fn walk_asks(asks: &[(Decimal, Decimal)], limit: Decimal, size: Decimal) -> (Decimal, Decimal) {
+    let mut filled = dec!(0);
+    let mut cost = dec!(0);
+    for &(price, available) in asks {
+        if price > limit || filled >= size {
+            break;
+        }
+        let take = available.min(size - filled);
+        filled += take;
+        cost += take * price;
+    }
+    (filled, cost)
+}
+
+/// 按参数回放全部录制数据；timeline 中的时间是交易所产生订单簿的时间
+fn simulate(
+    markets: &[Vec<Snapshot>],
+    timeline: &[(i64, usize, usize)],
+    params: Params,
+    latency: Latency,
+    jitter_seed: u64,
+) -> SimResult {
+    let mut rng = XorShift::seeded(Some(jitter_seed));
+    let mut jitter = || (rng.next_f64() * latency.jitter_ms as f64) as i64;
+    let mut result = SimResult {
+        trades: 0,
+        one_sided: 0,
+        matched: dec!(0),
+        net: dec!(0),
+    };
+    let mut last_trade: Option<i64> = None;
+    for &(ts, m, i) in timeline {
+        // 机器人收到这份订单簿的时间
+        let seen = ts + latency.book_ms;
+        if last_trade.is_some_and(|t| seen - t < MIN_TRADE_INTERVAL_MS) {
+            continue;
+        }
+        let snap = &markets[m][i];
+        let (Some(&(yes_ask, yes_size)), Some(&(no_ask, no_size))) = (snap.yes_asks.first(), snap.no_asks.first()) else {
+            continue;
+        };
+        // 与检测一致：卖一价取两位小数
+        let yes_price = yes_ask.round_dp(2);
+        let no_price = no_ask.round_dp(2);
+        let total = yes_price + no_price;
+        if total > dec!(1) - params.spread || dec!(1) - total < params.threshold {
+            continue;
+        }
+        let size = ((yes_size.min(no_size) * dec!(100)).floor() / dec!(100)).min(params.size);
+        let yes_limit = (yes_price + params.slippage).min(dec!(1));
+        let no_limit = (no_price + params.slippage).min(dec!(1));
+        // 交易所最小下单金额：双边均须 > $1
+        if yes_limit * size <= dec!(1) || no_limit * size <= dec!(1) {
+            continue;
+        }
+        // 成交看推送延迟 + 回报延迟（各含抖动）之后的订单簿
+        let arrive = seen + jitter() + latency.ack_ms + jitter();
+        let Some(fill) = markets[m][i..].iter().find(|s| s.ts_ms >= arrive) else {
+            continue;
+        };
+        let (yes_filled, yes_cost) = walk_asks(&fill.yes_asks, yes_limit, size);
+        let (no_filled, no_cost) = walk_asks(&fill.no_asks, no_limit, size);
+        last_trade = Some(seen);
+        result.trades += 1;
+        let matched = yes_filled.min(no_filled);
+        // 多出的单腿按买一价卖出
+        let excess_proceeds = if yes_filled > no_filled {
+            (yes_filled - no_filled) * fill.yes_bid.unwrap_or(dec!(0))
+        } else {
+            (no_filled - yes_filled) * fill.no_bid.unwrap_or(dec!(0))
+        };
+        if yes_filled != no_filled {
+            result.one_sided += 1;
+        }
+        result.matched += matched;
+        result.net += matched + excess_proceeds - yes_cost - no_cost;
+    }
+    result
+}
+
+fn parse_ms_list(flag: &str, value: Option<&String>) -> Result<Vec<i64>> {
+    value
+        .with_context(|| format!("{} 需要参数", flag))?
+        .split(',')
+        .filter(|s| !s.trim().is_empty())
+        .map(|s| s.trim().parse().with_context(|| format!("{} 必须为整数毫秒: {}", flag, s)))
+        .collect()
+}
+
+fn parse_list(flag: &str, value: Option<&String>) -> Result<Vec<Decimal>> {
+    value
+        .with_context(|| format!("{} 需要参数", flag))?
+        .split(',')
+        .filter(|s| !s.trim().is_empty())
+        .map(|s| Decimal::from_str(s.trim()).with_context(|| format!("{} 含无效数值: {}", flag, s)))
+        .collect()
+}
+
+/// 在列表最小值与最大值之间均匀采样
+fn sample(values: &[Decimal], rng: &mut XorShift, dp: u32) -> Decimal {
+    let min = values.iter().copied().min().unwrap_or_default();
+    let max = values.iter().copied().max().unwrap_or_default();
+    let unit = Decimal::try_from(rng.next_f64()).unwrap_or_default();
+    (min + (max - min) * unit).round_dp(dp)
+}
+
+pub async fn run(args: &[String]) -> Result<()> {
+    let mut data = std::env::var("BOOK_RECORD_PATH")
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or_else(|| "state/books.jsonl".to_string());
+    let mut thresholds = vec![dec!(0), dec!(0.005), dec!(0.01)];
+    let mut spreads = vec![dec!(0.01), dec!(0.02), dec!(0.03)];
+    let mut slippages = vec![dec!(0), dec!(0.01), dec!(0.02)];
+    let mut sizes = vec![dec!(5), dec!(10), dec!(20)];
+    let mut random: Option<usize> = None;
+    let mut seed: Option<u64> = None;
+    let mut ack_latencies: Vec<i64> = vec![200];
+    let mut book_latencies: Vec<i64> = vec![0];
+    let mut jitter_ms: i64 = 0;
+    let mut top: usize = 20;
+
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        let value = args.get(i + 1);
+        match flag {
+            "--data" => data = value.context("--data 需要参数")?.clone(),
+            "--threshold" => thresholds = parse_list(flag, value)?,
+            "--spread" => spreads = parse_list(flag, value)?,
+            "--slippage" => slippages = parse_list(flag, value)?,
+            "--size" => sizes = parse_list(flag, value)?,
+            "--random" => random = Some(value.context("--random 需要参数")?.parse().context("--random 必须为正整数")?),
+            "--seed" => seed = Some(value.context("--seed 需要参数")?.parse().context("--seed 必须为整数")?),
+            "--latency-ms" => ack_latencies = parse_ms_list(flag, value)?,
+            "--book-latency-ms" => book_latencies = parse_ms_list(flag, value)?,
+            "--jitter-ms" => jitter_ms = value.context("--jitter-ms 需要参数")?.parse().context("--jitter-ms 必须为整数")?,
+            "--top" => top = value.context("--top 需要参数")?.parse().context("--top 必须为正整数")?,
+            _ => {
+                print_usage();
+                std::process::exit(1);
+            }
+        }
+        i += 2;
+    }
+    if thresholds.is_empty()
+        || spreads.is_empty()
+        || slippages.is_empty()
+        || sizes.is_empty()
+        || ack_latencies.is_empty()
+        || book_latencies.is_empty()
+    {
+        anyhow::bail!("参数列表不能为空");
+    }
+    if ack_latencies.iter().chain(&book_latencies).any(|ms| *ms < 0) || jitter_ms < 0 {
+        anyhow::bail!("模拟延迟不能为负数");
+    }
+    let latencies: Vec<Latency> = book_latencies
+        .iter()
+        .flat_map(|&book_ms| {
+            ack_latencies.iter().map(move |&ack_ms| Latency { book_ms, ack_ms, jitter_ms })
+        })
+        .collect();
+    let jitter_seed = seed.unwrap_or(JITTER_SEED);
+
+    // 读取录制数据，按市场分组并按时间排序
+    let file = File::open(&data).with_context(|| format!("打开录制文件失败: {}（设置 BOOK_RECORD_PATH 运行机器人以录制）", data))?;
+    let mut by_market: HashMap<String, Vec<Snapshot>> = HashMap::new();
+    let mut skipped = 0usize;
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Ok(record) = serde_json::from_str::<BookRecord>(&line) else {
+            skipped += 1;
+            continue;
+        };
+        match Snapshot::from_record(&record) {
+            Some(snap) => by_market.entry(record.market_id).or_default().push(snap),
+            None => skipped += 1,
+        }
+    }
+    let mut markets: Vec<Vec<Snapshot>> = by_market.into_values().collect();
+    let mut timeline: Vec<(i64, usize, usize)> = Vec::new();
+    for (m, snaps) in markets.iter_mut().enumerate() {
+        snaps.sort_by_key(|s| s.ts_ms);
+        timeline.extend(snaps.iter().enumerate().map(|(i, s)| (s.ts_ms, m, i)));
+    }
+    timeline.sort_unstable();
+    if timeline.is_empty() {
+        anyhow::bail!("录制文件中没有可用记录: {}", data);
+    }
+
+    // 参数组合：网格（笛卡尔积）或随机搜索
+    let combos: Vec<Params> = match random {
+        Some(n) => {
+            let mut rng = XorShift::seeded(seed);
+            (0..n)
+                .map(|_| Params {
+                    threshold: sample(&thresholds, &mut rng, 4),
+                    spread: sample(&spreads, &mut rng, 4),
+                    slippage: sample(&slippages, &mut rng, 3),
+                    size: sample(&sizes, &mut rng, 0),
+                })
+                .collect()
+        }
+        None => {
+            let mut combos = Vec::new();
+            for &threshold in &thresholds {
+                for &spread in &spreads {
+                    for &slippage in &slippages {
+                        for &size in &sizes {
+                            combos.push(Params { threshold, spread, slippage, size });
+                        }
+                    }
+                }
+            }
+            combos
+        }
+    };
+
+    let labels: Vec<String> = latencies.iter().map(Latency::label).collect();
+    println!(
+        "回放 {} 条记录（{} 个市场，跳过 {} 条无效），{} 组参数，模拟延迟 {}{} ...",
+        timeline.len(),
+        markets.len(),
+        skipped,
+        combos.len(),
+        labels.join(" / "),
+        if jitter_ms > 0 { format!("（抖动 {}ms）", jitter_ms) } else { String::new() }
+    );
+    let mut results: Vec<Evaluation> = combos
+        .into_iter()
+        .map(|params| Evaluation {
+            params,
+            runs: latencies
+                .iter()
+                .map(|&latency| simulate(&markets, &timeline, params, latency, jitter_seed))
+                .collect(),
+        })
+        .collect();
+    results.sort_by(|a, b| b.worst_net().cmp(&a.worst_net()));
+
+    println!();
+    if latencies.len() == 1 {
+        println!(
+            "{:>4} {:>9} {:>8} {:>8} {:>8} {:>6} {:>6} {:>10} {:>10} {:>10}",
+            "排名", "利润阈值", "执行价差", "滑点", "单笔上限", "交易", "单腿", "成对(份)", "净盈亏", "每笔"
+        );
+        for (rank, e) in results.iter().take(top).enumerate() {
+            let r = &e.runs[0];
+            let per_trade = if r.trades > 0 { r.net / Decimal::from(r.trades) } else { dec!(0) };
+            println!(
+                "{:>4} {:>9} {:>8} {:>8} {:>8} {:>6} {:>6} {:>10.2} {:>10.4} {:>10.4}",
+                rank + 1,
+                e.params.threshold,
+                e.params.spread,
+                e.params.slippage,
+                e.params.size,
+                r.trades,
+                r.one_sided,
+                r.matched,
+                r.net,
+                per_trade
+            );
+        }
+    } else {
+        // 多种延迟：每列为该延迟下的净盈亏（括号内为单腿次数），按最差一列排序
+        let header: String = labels.iter().map(|l| format!(" {:>16}", l)).collect();
+        println!(
+            "{:>4} {:>9} {:>8} {:>8} {:>8}{} {:>10}",
+            "排名", "利润阈值", "执行价差", "滑点", "单笔上限", header, "最差"
+        );
+        for (rank, e) in results.iter().take(top).enumerate() {
+            let cells: String = e
+                .runs
+                .iter()
+                .map(|r| format!(" {:>16}", format!("{:.4}({})", r.net, r.one_sided)))
+                .collect();
+            println!(
+                "{:>4} {:>9} {:>8} {:>8} {:>8}{} {:>10.4}",
+                rank + 1,
+                e.params.threshold,
+                e.params.spread,
+                e.params.slippage,
+                e.params.size,
+                cells,
+                e.worst_net()
+            );
+        }
+        println!();
+        println!("各延迟下的最优参数：");
+        for (idx, label) in labels.iter().enumerate() {
+            let Some(best) = results.iter().max_by(|a, b| a.runs[idx].net.cmp(&b.runs[idx].net)) else {
+                continue;
+            };
+            let r = &best.runs[idx];
+            println!(
+                "  {:>10} | 阈值:{} 价差:{} 滑点:{} 上限:{} | 交易:{} 单腿:{} 净盈亏:{:.4}",
+                label,
+                best.params.threshold,
+                best.params.spread,
+                best.params.slippage,
+                best.params.size,
+                r.trades,
+                r.one_sided,
+                r.net
+            );
+        }
+    }
+    println!();
+    println!("提示：模拟不计手续费与 gas，单腿按成交时买一价卖出；结果仅用于比较参数的相对优劣。");
+    Ok(())
+}
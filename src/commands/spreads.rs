@@ -0,0 +1,179 @@
+//! spreads 子命令：查询存储后端中的价差采样（SPREAD_SAMPLE_ENABLED 记录的每秒 YES+NO 卖一总价），
+//! 按市场统计套利价差出现的频率、持续时长与深度，可选用字符图绘制总价走势。
+//!
+//! 用法示例：
+//!   poly_1hour_bot spreads
+//!   poly_1hour_bot spreads --market btc --hours 72 --threshold 0.98 --plot
+
+use anyhow::{bail, Result};
+use chrono::{Duration as ChronoDuration, Utc};
+use polymarket_client_sdk::types::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal_macros::dec;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use crate::utils::storage::{self, SpreadQuery, SpreadSample};
+
+/// 相邻采样间隔超过该秒数视为中断（新窗口或停机），不连成同一段机会
+const MAX_GAP_SECS: i64 = 5;
+/// 字符图的高度刻度
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|i| args.get(i + 1))
+        .map(String::as_str)
+}
+
+fn print_usage() {
+    eprintln!("用法: poly_1hour_bot spreads [--market 名称] [--hours 24] [--threshold 0.99] [--plot] [--width 72]");
+    eprintln!("  --market     只看某个市场（系列名/币种，如 btc）");
+    eprintln!("  --hours      回看小时数（默认 24）");
+    eprintln!("  --threshold  统计总价不高于该值的时间占比与机会段（默认 0.99）");
+    eprintln!("  --plot       按时间分桶绘制每桶最低总价");
+    eprintln!("  --width      字符图宽度（桶数，默认 72）");
+}
+
+/// 一个市场的统计
+struct MarketSummary {
+    samples: usize,
+    below_one: usize,
+    below_threshold: usize,
+    min_total: Decimal,
+    median_total: Decimal,
+    /// 总价不高于阈值的连续段数、最长段秒数
+    episodes: usize,
+    longest_secs: i64,
+}
+
+fn summarize(samples: &[&SpreadSample], threshold: Decimal) -> MarketSummary {
+    let mut totals: Vec<Decimal> = samples.iter().map(|s| s.total()).collect();
+    totals.sort();
+    let mut episodes = 0;
+    let mut longest_secs = 0;
+    let mut run_start: Option<chrono::DateTime<Utc>> = None;
+    let mut prev_ts: Option<chrono::DateTime<Utc>> = None;
+    for sample in samples {
+        let gap = prev_ts.is_some_and(|p| (sample.ts - p).num_seconds() > MAX_GAP_SECS);
+        if gap {
+            if let (Some(start), Some(prev)) = (run_start.take(), prev_ts) {
+                longest_secs = longest_secs.max((prev - start).num_seconds());
+            }
+        }
+        if sample.total() <= threshold {
+            if run_start.is_none() {
+                run_start = Some(sample.ts);
+                episodes += 1;
+            }
+        } else if let Some(start) = run_start.take() {
+            longest_secs = longest_secs.max((sample.ts - start).num_seconds());
+        }
+        prev_ts = Some(sample.ts);
+    }
+    if let (Some(start), Some(prev)) = (run_start, prev_ts) {
+        longest_secs = longest_secs.max((prev - start).num_seconds());
+    }
+    MarketSummary {
+        samples: samples.len(),
+        below_one: totals.iter().filter(|t| **t < dec!(1)).count(),
+        below_threshold: totals.iter().filter(|t| **t <= threshold).count(),
+        min_total: totals.first().copied().unwrap_or_default(),
+        median_total: totals.get(totals.len() / 2).copied().unwrap_or_default(),
+        episodes,
+        longest_secs,
+    }
+}
+
+/// 把采样按时间均分为 width 个桶，每桶取最低总价绘制；低于阈值的桶在下一行标 ^
+fn plot(samples: &[&SpreadSample], threshold: Decimal, width: usize) {
+    let (Some(first), Some(last)) = (samples.first(), samples.last()) else {
+        return;
+    };
+    let span = (last.ts - first.ts).num_milliseconds().max(1);
+    let mut buckets: Vec<Option<Decimal>> = vec![None; width];
+    for sample in samples {
+        let index = (((sample.ts - first.ts).num_milliseconds() * (width as i64 - 1)) / span) as usize;
+        let slot = &mut buckets[index.min(width - 1)];
+        *slot = Some(slot.map_or(sample.total(), |m: Decimal| m.min(sample.total())));
+    }
+    let low = buckets.iter().flatten().min().copied().unwrap_or_default();
+    let high = buckets.iter().flatten().max().copied().unwrap_or_default();
+    let range = (high - low).max(dec!(0.0001));
+    let line: String = buckets
+        .iter()
+        .map(|b| match b {
+            Some(total) => {
+                let level = ((*total - low) / range * Decimal::from(BARS.len() - 1)).round();
+                BARS[level.to_usize().unwrap_or(0).min(BARS.len() - 1)]
+            }
+            None => ' ',
+        })
+        .collect();
+    let marks: String = buckets
+        .iter()
+        .map(|b| if b.is_some_and(|t| t <= threshold) { '^' } else { ' ' })
+        .collect();
+    println!("    {}  最低 {:.4} / 最高 {:.4}", line, low, high);
+    println!("    {}  ^ = 总价 ≤ {}", marks, threshold);
+    println!(
+        "    {} → {}",
+        first.ts.format("%m-%d %H:%M"),
+        last.ts.format("%m-%d %H:%M")
+    );
+}
+
+pub async fn run(args: &[String]) -> Result<()> {
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        print_usage();
+        return Ok(());
+    }
+    dotenvy::dotenv().ok();
+    let url = std::env::var("STORAGE_URL").unwrap_or_default();
+    if url.trim().is_empty() {
+        bail!("未配置 STORAGE_URL：价差采样保存在存储后端中");
+    }
+    let hours: i64 = arg_value(args, "--hours").and_then(|v| v.parse().ok()).unwrap_or(24);
+    let threshold = arg_value(args, "--threshold")
+        .and_then(|v| Decimal::from_str(v).ok())
+        .unwrap_or(dec!(0.99));
+    let width: usize = arg_value(args, "--width").and_then(|v| v.parse().ok()).unwrap_or(72).max(8);
+    let query = SpreadQuery {
+        market: arg_value(args, "--market").map(str::to_string),
+        since: Some(Utc::now() - ChronoDuration::hours(hours)),
+        until: None,
+    };
+
+    let backend = storage::connect(&url, "").await?;
+    let samples = backend.query_spread_samples(&query).await?;
+    if samples.is_empty() {
+        println!("最近 {} 小时没有价差采样（确认运行时设置了 SPREAD_SAMPLE_ENABLED=true）", hours);
+        return Ok(());
+    }
+    let mut by_market: BTreeMap<&str, Vec<&SpreadSample>> = BTreeMap::new();
+    for sample in &samples {
+        by_market.entry(sample.market.as_str()).or_default().push(sample);
+    }
+
+    println!("最近 {} 小时价差采样（阈值 {}）：", hours, threshold);
+    for (market, samples) in &by_market {
+        let s = summarize(samples, threshold);
+        let pct = |n: usize| n as f64 * 100.0 / s.samples.max(1) as f64;
+        println!(
+            "  {:<10} 采样:{:>7} | 总价<1:{:>6.2}% | ≤阈值:{:>6.2}% | 机会段:{:>4} | 最长:{:>4}s | 最低:{:.4} | 中位:{:.4}",
+            market,
+            s.samples,
+            pct(s.below_one),
+            pct(s.below_threshold),
+            s.episodes,
+            s.longest_secs,
+            s.min_total,
+            s.median_total
+        );
+        if args.iter().any(|a| a == "--plot") {
+            plot(samples, threshold, width);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,194 @@
+//! export-state / import-state 子命令：把落盘的运行状态（风控检查点中的未完结订单对、持仓与敞口成本，
+//! merge 重试队列、EV 成交模型、币种价差统计、市场元数据缓存）打包成一个 JSON 文件，
+//! 在新主机上解包到各自的配置路径，迁移或升级时新实例启动即可从检查点恢复，尽量缩短空仓时间。
+//!
+//! CLOB API 凭证每次启动由私钥派生，不落盘也无需迁移；私钥等配置请另行复制 .env。
+//! 迁移步骤：旧实例 Ctrl+C（退出前写最后一次检查点）→ export-state → 复制文件 → 新主机 import-state → 启动。
+//!
+//! 启用本地状态加密（STATE_ENCRYPTION_*）时导出前先解密、状态包本身为明文，导入时检查点按本机配置重新加密；
+//! decrypt-state 把加密的交易日志 / 检查点解密输出到标准输出，便于复盘。
+//!
+//! 用法示例：
+//!   poly_1hour_bot export-state --out state_bundle.json
+//!   poly_1hour_bot import-state --in state_bundle.json [--force]
+//!   poly_1hour_bot decrypt-state state/journal.jsonl
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::risk::checkpoint::{RiskCheckpoint, CHECKPOINT_VERSION};
+use crate::utils::state_crypto;
+
+/// 打包格式版本
+const BUNDLE_VERSION: u32 = 1;
+
+/// 打包的状态文件：(名称, 路径环境变量, 默认路径)，与 Config::from_env 一致
+const STATE_FILES: &[(&str, &str, &str)] = &[
+    ("checkpoint", "CHECKPOINT_PATH", "state/risk_checkpoint.json"),
+    ("merge_retry_queue", "MERGE_RETRY_QUEUE_PATH", "state/merge_retry_queue.json"),
+    ("fill_model", "FILL_MODEL_PATH", "state/fill_model.json"),
+    ("symbol_spreads", "SYMBOL_SPREAD_PATH", "state/symbol_spreads.json"),
+    ("market_cache", "MARKET_CACHE_PATH", "state/market_cache.json"),
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StateBundle {
+    version: u32,
+    exported_at: DateTime<Utc>,
+    /// 名称 -> 文件内容
+    files: BTreeMap<String, Value>,
+}
+
+fn state_path(env_name: &str, default: &str) -> String {
+    dotenvy::dotenv().ok();
+    std::env::var(env_name).unwrap_or_else(|_| default.to_string())
+}
+
+/// 按 STATE_ENCRYPTION_* 启用本地状态加密（与主程序一致）
+fn init_crypto() -> Result<()> {
+    dotenvy::dotenv().ok();
+    state_crypto::init_from_env()
+}
+
+fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|i| args.get(i + 1))
+        .map(String::as_str)
+}
+
+pub fn run_export(args: &[String]) -> Result<()> {
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        eprintln!("用法: poly_1hour_bot export-state [--out 文件]（默认 state_bundle.json）");
+        return Ok(());
+    }
+    let out = arg_value(args, "--out").unwrap_or("state_bundle.json");
+    init_crypto()?;
+    if !state_path("STORAGE_URL", "").trim().is_empty() {
+        println!("  ⚠️ 已配置 STORAGE_URL：检查点保存在数据库 state 表中，请直接迁移数据库，本命令只打包本地文件");
+    }
+    let mut files = BTreeMap::new();
+    for (name, env_name, default) in STATE_FILES {
+        let path = state_path(env_name, default);
+        if path.trim().is_empty() {
+            continue;
+        }
+        let body = match std::fs::read(&path) {
+            Ok(body) => body,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                println!("  跳过 {}: {} 不存在", name, path);
+                continue;
+            }
+            Err(e) => return Err(e).with_context(|| format!("读取 {} 失败", path)),
+        };
+        let body = state_crypto::open(&body).with_context(|| format!("解密 {} 失败", path))?;
+        let value: Value = serde_json::from_slice(&body).with_context(|| format!("{} 不是有效 JSON", path))?;
+        println!("  打包 {} <- {}", name, path);
+        files.insert(name.to_string(), value);
+    }
+    if let Some(checkpoint) = files.get("checkpoint") {
+        let checkpoint: RiskCheckpoint = serde_json::from_value(checkpoint.clone()).context("检查点格式无效")?;
+        println!(
+            "  检查点：写于 {} | 未完结订单对:{} | 持仓:{} | 敞口成本:{}",
+            checkpoint.saved_at,
+            checkpoint.pending_pairs.len(),
+            checkpoint.positions.len(),
+            checkpoint.exposure_costs.len()
+        );
+    } else {
+        println!("  ⚠️ 未找到检查点，新实例将从 API 重建状态（敞口成本与未完结订单对会丢失）");
+    }
+    let bundle = StateBundle {
+        version: BUNDLE_VERSION,
+        exported_at: Utc::now(),
+        files,
+    };
+    std::fs::write(out, serde_json::to_vec_pretty(&bundle)?).with_context(|| format!("写入 {} 失败", out))?;
+    println!("✅ 已导出 {} 个状态文件到 {}", bundle.files.len(), out);
+    Ok(())
+}
+
+pub fn run_import(args: &[String]) -> Result<()> {
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        eprintln!("用法: poly_1hour_bot import-state [--in 文件]（默认 state_bundle.json） [--force 覆盖已有文件]");
+        return Ok(());
+    }
+    let input = arg_value(args, "--in").unwrap_or("state_bundle.json");
+    let force = args.iter().any(|a| a == "--force");
+    init_crypto()?;
+    let body = std::fs::read(input).with_context(|| format!("读取 {} 失败", input))?;
+    let bundle: StateBundle = serde_json::from_slice(&body).context("状态包格式无效")?;
+    if bundle.version != BUNDLE_VERSION {
+        anyhow::bail!("不支持的状态包版本 {}（当前 {}）", bundle.version, BUNDLE_VERSION);
+    }
+    if let Some(checkpoint) = bundle.files.get("checkpoint") {
+        let checkpoint: RiskCheckpoint = serde_json::from_value(checkpoint.clone()).context("检查点格式无效")?;
+        if checkpoint.version != CHECKPOINT_VERSION {
+            anyhow::bail!("检查点版本 {} 与当前程序（{}）不一致，请用相同版本导入", checkpoint.version, CHECKPOINT_VERSION);
+        }
+    }
+
+    // 先检查全部目标，避免写了一半才发现冲突
+    let mut targets = Vec::new();
+    for (name, env_name, default) in STATE_FILES {
+        let Some(value) = bundle.files.get(*name) else {
+            continue;
+        };
+        let path = state_path(env_name, default);
+        if path.trim().is_empty() {
+            println!("  跳过 {}: 本机未配置落盘路径", name);
+            continue;
+        }
+        if Path::new(&path).exists() && !force {
+            anyhow::bail!("{} 已存在，确认要覆盖请加 --force", path);
+        }
+        targets.push((*name, path, value));
+    }
+    for (name, path, value) in &targets {
+        let path = Path::new(path);
+        if let Some(dir) = path.parent() {
+            if !dir.as_os_str().is_empty() {
+                std::fs::create_dir_all(dir)?;
+            }
+        }
+        let tmp = path.with_extension("json.tmp");
+        let mut body = serde_json::to_vec_pretty(value)?;
+        if *name == "checkpoint" {
+            body = state_crypto::seal(&body)?;
+        }
+        std::fs::write(&tmp, body)?;
+        std::fs::rename(&tmp, path)?;
+        println!("  写入 {} -> {}", name, path.display());
+    }
+    println!(
+        "✅ 已导入 {} 个状态文件（导出于 {}），启动后将从检查点恢复",
+        targets.len(),
+        bundle.exported_at
+    );
+    Ok(())
+}
+
+pub fn run_decrypt(args: &[String]) -> Result<()> {
+    let Some(path) = args.first().filter(|a| *a != "--help" && *a != "-h") else {
+        eprintln!("用法: poly_1hour_bot decrypt-state <文件>（解密交易日志 / 检查点 / nonce 文件并输出到标准输出）");
+        return Ok(());
+    };
+    init_crypto()?;
+    if !state_crypto::enabled() {
+        println!("  ⚠️ 未配置 STATE_ENCRYPTION_PASSPHRASE / STATE_ENCRYPTION_KEYRING，只能输出明文内容");
+    }
+    let body = std::fs::read(path).with_context(|| format!("读取 {} 失败", path))?;
+    // 交易日志逐行加密，其余文件整体加密为一行，统一按行解密
+    for (i, line) in body.split(|b| *b == b'\n').enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        let plain = state_crypto::open(line).with_context(|| format!("第 {} 行解密失败", i + 1))?;
+        println!("{}", String::from_utf8_lossy(&plain));
+    }
+    Ok(())
+}
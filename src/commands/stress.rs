@@ -0,0 +1,245 @@
+//! stress 子命令：蒙特卡洛敞口压力模拟。按当前限额（RISK_MAX_EXPOSURE_USDC、MAX_ORDER_SIZE_USDC、
+//! ARBITRAGE_EXECUTION_SPREAD）模拟多条路径：每窗口泊松到达的套利机会、按比例发生的单边成交、
+//! 单边持仓期间的价格波动与跳变、Merge 延迟占用敞口，输出盈亏分布的尾部损失（分位数、CVaR）
+//! 与被敞口上限拦截的比例，帮助理性设置总敞口与单市场上限。
+//!
+//! 用法示例：
+//!   poly_1hour_bot stress
+//!   poly_1hour_bot stress --paths 20000 --windows 168 --one-sided 0.15 --jump-prob 0.1 --merge-delay 2
+
+use anyhow::{Context, Result};
+
+use super::rng::XorShift;
+
+fn print_usage() {
+    eprintln!("用法: poly_1hour_bot stress [选项]");
+    eprintln!("  --paths N            模拟路径数，默认 10000");
+    eprintln!("  --windows N          每条路径的窗口数（1小时/窗口），默认 24");
+    eprintln!("  --trades X           每窗口平均套利次数（泊松），默认 4");
+    eprintln!("  --one-sided P        单边成交比例，默认 0.1");
+    eprintln!("  --vol X              单边持仓期间价格波动（标准差），默认 0.05");
+    eprintln!("  --jump-prob P        单边持仓期间发生价格跳变的概率，默认 0.05");
+    eprintln!("  --jump X             跳变幅度，默认 0.3");
+    eprintln!("  --leg-price X        单腿买入价（单边最大亏损上限），默认 0.5");
+    eprintln!("  --merge-delay X      成对持仓 Merge 释放敞口的平均延迟（窗口数，指数分布），默认 1");
+    eprintln!("  --markets N          同时交易的市场数，默认取 CRYPTO_SYMBOLS 个数");
+    eprintln!("  --per-market-cap X   单市场敞口上限（USD），默认不限");
+    eprintln!("  --max-exposure X     总敞口上限（USD），默认 RISK_MAX_EXPOSURE_USDC");
+    eprintln!("  --size X             单笔数量，默认 MAX_ORDER_SIZE_USDC");
+    eprintln!("  --spread X           成对成交的锁定价差，默认 ARBITRAGE_EXECUTION_SPREAD");
+    eprintln!("  --seed S             随机种子，默认取当前时间");
+}
+
+/// 与 Config::from_env 相同的变量与默认值；不加载完整配置，无需私钥即可运行
+fn env_f64(name: &str, default: f64) -> f64 {
+    std::env::var(name)
+        .unwrap_or_else(|_| default.to_string())
+        .parse()
+        .unwrap_or(default)
+}
+
+struct Scenario {
+    windows: u32,
+    trades_per_window: f64,
+    one_sided_rate: f64,
+    vol: f64,
+    jump_prob: f64,
+    jump: f64,
+    leg_price: f64,
+    merge_delay: f64,
+    markets: usize,
+    per_market_cap: f64,
+    max_exposure: f64,
+    size: f64,
+    spread: f64,
+}
+
+struct PathResult {
+    pnl: f64,
+    peak_exposure: f64,
+    attempts: u32,
+    blocked: u32,
+}
+
+/// 占用中的敞口：(释放窗口, 金额, 市场)
+type Holding = (u32, f64, usize);
+
+fn simulate_path(s: &Scenario, rng: &mut XorShift) -> PathResult {
+    let mut result = PathResult {
+        pnl: 0.0,
+        peak_exposure: 0.0,
+        attempts: 0,
+        blocked: 0,
+    };
+    let mut holdings: Vec<Holding> = Vec::new();
+    let mut market_exposure = vec![0.0; s.markets];
+    let mut exposure = 0.0;
+    for window in 0..s.windows {
+        // 到期释放：单边持仓在窗口结算时释放，成对持仓在 Merge 完成后释放
+        holdings.retain(|&(release, amount, market)| {
+            if release <= window {
+                exposure -= amount;
+                market_exposure[market] -= amount;
+                false
+            } else {
+                true
+            }
+        });
+        for _ in 0..rng.poisson(s.trades_per_window) {
+            result.attempts += 1;
+            let market = ((rng.next_f64() * s.markets as f64) as usize).min(s.markets - 1);
+            let notional = s.size * (1.0 - s.spread);
+            if exposure + notional > s.max_exposure || market_exposure[market] + notional > s.per_market_cap {
+                result.blocked += 1;
+                continue;
+            }
+            let (amount, release) = if rng.next_f64() < s.one_sided_rate {
+                // 单边：缺失腿价格不利变动（波动 + 可能的跳变），亏损不超过已买入腿的成本
+                let mut adverse = (rng.normal() * s.vol).abs();
+                if rng.next_f64() < s.jump_prob {
+                    adverse += s.jump;
+                }
+                result.pnl -= s.size * adverse.min(s.leg_price);
+                (s.size * s.leg_price, window + 1)
+            } else {
+                result.pnl += s.size * s.spread;
+                (notional, window + 1 + rng.exponential(s.merge_delay) as u32)
+            };
+            exposure += amount;
+            market_exposure[market] += amount;
+            holdings.push((release, amount, market));
+            result.peak_exposure = result.peak_exposure.max(exposure);
+        }
+    }
+    result
+}
+
+/// 取已排序样本的分位数
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let idx = (q * (sorted.len() - 1) as f64).round() as usize;
+    sorted[idx.min(sorted.len() - 1)]
+}
+
+/// 最差 q 比例路径的平均盈亏（CVaR）
+fn cvar(sorted: &[f64], q: f64) -> f64 {
+    let n = ((sorted.len() as f64 * q).ceil() as usize).clamp(1, sorted.len().max(1));
+    sorted.iter().take(n).sum::<f64>() / n as f64
+}
+
+pub async fn run(args: &[String]) -> Result<()> {
+    dotenvy::dotenv().ok();
+    let symbol_count = std::env::var("CRYPTO_SYMBOLS")
+        .unwrap_or_else(|_| "btc,eth,xrp,sol".to_string())
+        .split(',')
+        .filter(|s| !s.trim().is_empty())
+        .count()
+        .max(1);
+    let mut paths: usize = 10_000;
+    let mut seed: Option<u64> = None;
+    let mut s = Scenario {
+        windows: 24,
+        trades_per_window: 4.0,
+        one_sided_rate: 0.1,
+        vol: 0.05,
+        jump_prob: 0.05,
+        jump: 0.3,
+        leg_price: 0.5,
+        merge_delay: 1.0,
+        markets: symbol_count,
+        per_market_cap: f64::INFINITY,
+        max_exposure: env_f64("RISK_MAX_EXPOSURE_USDC", 1000.0),
+        size: env_f64("MAX_ORDER_SIZE_USDC", 100.0),
+        spread: env_f64("ARBITRAGE_EXECUTION_SPREAD", 0.01),
+    };
+
+    let mut i = 0;
+    while i < args.len() {
+        let flag = args[i].as_str();
+        let number = || -> Result<f64> {
+            let v = args.get(i + 1).with_context(|| format!("{} 需要参数", flag))?;
+            v.parse().with_context(|| format!("{} 必须为数值: {}", flag, v))
+        };
+        match flag {
+            "--paths" => paths = number()? as usize,
+            "--windows" => s.windows = number()? as u32,
+            "--trades" => s.trades_per_window = number()?,
+            "--one-sided" => s.one_sided_rate = number()?,
+            "--vol" => s.vol = number()?,
+            "--jump-prob" => s.jump_prob = number()?,
+            "--jump" => s.jump = number()?,
+            "--leg-price" => s.leg_price = number()?,
+            "--merge-delay" => s.merge_delay = number()?,
+            "--markets" => s.markets = (number()? as usize).max(1),
+            "--per-market-cap" => s.per_market_cap = number()?,
+            "--max-exposure" => s.max_exposure = number()?,
+            "--size" => s.size = number()?,
+            "--spread" => s.spread = number()?,
+            "--seed" => seed = Some(number()? as u64),
+            _ => {
+                print_usage();
+                std::process::exit(1);
+            }
+        }
+        i += 2;
+    }
+    if paths == 0 || s.windows == 0 {
+        anyhow::bail!("--paths 与 --windows 须为正数");
+    }
+
+    println!(
+        "模拟 {} 条路径 × {} 个窗口 | 每窗口 {} 次 | 单笔 {} | 总敞口上限 {} USD | 单市场上限 {} | {} 个市场",
+        paths,
+        s.windows,
+        s.trades_per_window,
+        s.size,
+        s.max_exposure,
+        if s.per_market_cap.is_finite() { format!("{} USD", s.per_market_cap) } else { "不限".to_string() },
+        s.markets
+    );
+    println!(
+        "单边比例 {} | 波动 {} | 跳变 {}×{} | 锁定价差 {} | Merge 平均延迟 {} 窗口",
+        s.one_sided_rate, s.vol, s.jump_prob, s.jump, s.spread, s.merge_delay
+    );
+
+    let mut rng = XorShift::seeded(seed);
+    let mut pnls: Vec<f64> = Vec::with_capacity(paths);
+    let mut peaks: Vec<f64> = Vec::with_capacity(paths);
+    let (mut attempts, mut blocked) = (0u64, 0u64);
+    for _ in 0..paths {
+        let r = simulate_path(&s, &mut rng);
+        pnls.push(r.pnl);
+        peaks.push(r.peak_exposure);
+        attempts += r.attempts as u64;
+        blocked += r.blocked as u64;
+    }
+    pnls.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    peaks.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let mean = pnls.iter().sum::<f64>() / pnls.len() as f64;
+    let loss_prob = pnls.iter().filter(|p| **p < 0.0).count() as f64 / pnls.len() as f64;
+
+    println!();
+    println!("盈亏分布（USD，每条路径累计）");
+    println!("  均值      {:>12.2}", mean);
+    println!("  中位数    {:>12.2}", quantile(&pnls, 0.5));
+    println!("  5% 分位   {:>12.2}", quantile(&pnls, 0.05));
+    println!("  1% 分位   {:>12.2}", quantile(&pnls, 0.01));
+    println!("  CVaR 1%   {:>12.2}", cvar(&pnls, 0.01));
+    println!("  最差      {:>12.2}", pnls.first().copied().unwrap_or(0.0));
+    println!("  亏损概率  {:>11.2}%", loss_prob * 100.0);
+    println!();
+    println!("敞口占用");
+    println!("  峰值 p50  {:>12.2}", quantile(&peaks, 0.5));
+    println!("  峰值 p99  {:>12.2}", quantile(&peaks, 0.99));
+    println!(
+        "  被上限拦截 {:>10.2}%（{} / {} 次机会）",
+        if attempts > 0 { blocked as f64 / attempts as f64 * 100.0 } else { 0.0 },
+        blocked,
+        attempts
+    );
+    println!();
+    println!("提示：拦截比例高说明上限在限制收益，可适当放宽；CVaR 超出可承受亏损时应收紧总敞口或单市场上限。");
+    Ok(())
+}
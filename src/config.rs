@@ -1,8 +1,30 @@
 use anyhow::Result;
 use polymarket_client_sdk::clob::types::OrderType;
+use std::collections::HashMap;
 use std::env;
 
 use polymarket_client_sdk::types::Address;
+use poly_1hour_bot::i18n::Locale;
+use poly_1hour_bot::subgraph::{
+    SubgraphUrls, DEFAULT_ACTIVITY_SUBGRAPH, DEFAULT_ORDERBOOK_SUBGRAPH, DEFAULT_POSITIONS_SUBGRAPH,
+};
+
+use crate::market::clock::DEFAULT_MARKET_TIMEZONE;
+use crate::market::discoverer::DiscoveryMode;
+use crate::market::series::{parse_series_list, MarketSeries};
+use crate::market::window::{self, WindowDuration};
+use crate::market::volatility::VolSettings;
+use crate::monitor::shadow::ShadowSettings;
+use crate::risk::ab_test::AbSettings;
+use crate::risk::budget::StrategyBudgets;
+use crate::risk::recovery::HedgePolicy;
+use crate::trading::executor::{ExecutionMode, LegSubmission};
+use crate::trading::credentials::RotationSettings;
+use crate::trading::slippage::SlippageSettings;
+use crate::utils::rate_limit::RateLimitSettings;
+use crate::utils::instance_guard::GuardSettings;
+use crate::utils::stream_sink::StreamSinkSettings;
+use crate::utils::webhooks::WebhookSettings;
 
 /// 解析套利订单类型：GTC、GTD、FOK、FAK，大小写不敏感，无效或未知值默认 GTD。
 fn parse_arbitrage_order_type(s: &str) -> OrderType {
@@ -29,6 +51,87 @@ fn parse_slippage(s: &str) -> [f64; 2] {
     }
 }
 
+/// 单个币种（系列）的配置覆盖，未设置的项使用全局配置
+#[derive(Debug, Clone)]
+pub struct SymbolOverride {
+    /// 是否监控该币种，默认 true
+    pub enabled: bool,
+    pub refresh_advance_secs: Option<u64>,
+    pub max_order_size_usdc: Option<f64>,
+    pub min_profit_threshold: Option<f64>,
+}
+
+impl Default for SymbolOverride {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            refresh_advance_secs: None,
+            max_order_size_usdc: None,
+            min_profit_threshold: None,
+        }
+    }
+}
+
+/// 解析币种覆盖：`币种[:周期]:键=值,键=值;币种:键=值`，
+/// 键为 enabled / refresh_advance_secs / max_order_size_usdc / min_profit_threshold，无效项忽略。
+/// 带周期的条目（如 btc:15m）只作用于该周期的市场，优先于不带周期的同币种条目。
+/// 例如 "bitcoin:max_order_size_usdc=20,min_profit_threshold=0.002;btc:15m:max_order_size_usdc=5;xrp:enabled=false"
+fn parse_symbol_overrides(s: &str) -> HashMap<String, SymbolOverride> {
+    let mut overrides = HashMap::new();
+    for entry in s.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+        let Some((symbol, settings)) = entry.rsplit_once(':') else {
+            continue;
+        };
+        let mut o = SymbolOverride::default();
+        for (key, value) in settings.split(',').filter_map(|kv| kv.split_once('=')) {
+            let value = value.trim();
+            match key.trim() {
+                "enabled" => o.enabled = value == "1" || value.eq_ignore_ascii_case("true"),
+                "refresh_advance_secs" => o.refresh_advance_secs = value.parse().ok(),
+                "max_order_size_usdc" => o.max_order_size_usdc = value.parse().ok(),
+                "min_profit_threshold" => o.min_profit_threshold = value.parse().ok(),
+                _ => {}
+            }
+        }
+        overrides.insert(symbol.trim().to_lowercase(), o);
+    }
+    overrides
+}
+
+/// 查找状态键（币种:周期，见 MarketInfo::symbol_key）对应的覆盖：先按完整键，再按币种
+fn symbol_override<'a>(overrides: &'a HashMap<String, SymbolOverride>, key: &str) -> Option<&'a SymbolOverride> {
+    overrides
+        .get(key)
+        .or_else(|| key.split_once(':').and_then(|(symbol, _)| overrides.get(symbol)))
+}
+
+/// 官方 CLOB REST 地址
+pub const DEFAULT_CLOB_REST_URL: &str = "https://clob.polymarket.com";
+/// 官方 CLOB WebSocket 地址（订单簿订阅）
+pub const DEFAULT_CLOB_WS_URL: &str = "wss://ws-subscriptions-clob.polymarket.com/ws/market";
+/// 官方 Gamma API 地址（市场发现）
+pub const DEFAULT_GAMMA_URL: &str = "https://gamma-api.polymarket.com";
+
+/// 服务端点：默认官方地址，可通过环境变量固定到其他地址/区域（例如就近的反向代理）。
+/// CLOB REST 与 Gamma 会被交易/风控/发现客户端使用；订单簿 WS 仍使用 SDK 默认地址，CLOB_WS_URL 仅供 latency 探测对比。
+#[derive(Debug, Clone)]
+pub struct Endpoints {
+    pub clob_rest: String,
+    pub clob_ws: String,
+    pub gamma: String,
+}
+
+impl Endpoints {
+    pub fn from_env() -> Self {
+        dotenvy::dotenv().ok();
+        Self {
+            clob_rest: env::var("CLOB_REST_URL").unwrap_or_else(|_| DEFAULT_CLOB_REST_URL.to_string()),
+            clob_ws: env::var("CLOB_WS_URL").unwrap_or_else(|_| DEFAULT_CLOB_WS_URL.to_string()),
+            gamma: env::var("GAMMA_API_URL").unwrap_or_else(|_| DEFAULT_GAMMA_URL.to_string()),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Config {
     pub private_key: String,
@@ -36,20 +139,144 @@ pub struct Config {
     pub min_profit_threshold: f64,
     pub max_order_size_usdc: f64,
     pub crypto_symbols: Vec<String>,
+    /// 要监控的市场系列：CRYPTO_SYMBOLS 对应的加密货币每小时系列 + MARKET_SERIES 中的自定义系列（已排除被禁用的币种）
+    pub market_series: Vec<MarketSeries>,
+    /// 监控的窗口周期（15m / 1h / 4h / 1d，可多个）；调度器按其中最短的周期切换窗口
+    pub window_durations: Vec<WindowDuration>,
+    /// 按币种（系列名称）的配置覆盖（SYMBOL_OVERRIDES）
+    pub symbol_overrides: HashMap<String, SymbolOverride>,
     pub market_refresh_advance_secs: u64,
     pub risk_max_exposure_usdc: f64,
+    /// 各策略独立的敞口额度（RISK_BUDGET_*_USDC），0 = 不单独限制，只受 RISK_MAX_EXPOSURE_USDC 约束
+    pub risk_budgets: StrategyBudgets,
     pub risk_imbalance_threshold: f64,
     pub hedge_take_profit_pct: f64, // 对冲止盈百分比（例如0.05表示5%）
     pub hedge_stop_loss_pct: f64,   // 对冲止损百分比（例如0.05表示5%）
     pub arbitrage_execution_spread: f64, // 套利执行价差：yes+no <= 1 - 套利执行价差时，执行套利
-    /// 滑点 [first, second]：仅下降侧用 second，上涨与持平用 first。如 "-0.02,0.0"
-    pub slippage: [f64; 2],
+    /// 自适应执行价差：连续单边成交后放宽、连续双腿成交后收紧，限制在 [min, max]
+    pub adaptive_spread_enabled: bool,
+    pub adaptive_spread_min: f64,
+    pub adaptive_spread_max: f64,
+    /// 每次调整的幅度
+    pub adaptive_spread_step: f64,
+    /// 连续多少次单边成交后放宽
+    pub adaptive_widen_after: u32,
+    /// 连续多少次双腿成交后收紧
+    pub adaptive_tighten_after: u32,
+    /// 按币种学习执行价差：按历史实际收益（计入单边成交成本）为每个币种选择执行价差，覆盖全局值
+    pub symbol_spread_enabled: bool,
+    /// 统计文件；空字符串表示不落盘
+    pub symbol_spread_path: String,
+    /// 价差分档宽度
+    pub symbol_spread_step: f64,
+    /// 学到的价差至少需要的成交样本数
+    pub symbol_spread_min_samples: u32,
+    /// 抢单检测：同一市场在窗口内多次出现零成交腿时提高该市场的价差要求
+    pub snipe_detection_enabled: bool,
+    /// 统计窗口（秒）
+    pub snipe_window_secs: u64,
+    /// 窗口内零成交腿次数阈值
+    pub snipe_threshold: u32,
+    /// 每次提高的价差
+    pub snipe_edge_step: f64,
+    /// 额外价差上限
+    pub snipe_max_extra_edge: f64,
+    /// 回卖止盈：成对持仓两边买一价之和扣除手续费后高于 1 时卖回订单簿，而不是等 merge
+    pub sell_back_enabled: bool,
+    /// 回卖每份（每条腿）的手续费估计（USD）
+    pub sell_back_fee_per_share: f64,
+    /// 扣除手续费后每对至少高出 1 的金额
+    pub sell_back_min_edge: f64,
+    /// 成对持仓处置策略：merge 前比较 merge（1 − gas）与按当前买盘回卖（扣手续费）的所得，选更好的一种
+    pub disposal_policy_enabled: bool,
+    /// 一笔 merge 交易的 gas 估计（USD）
+    pub disposal_merge_gas_usd: f64,
+    /// 回卖须比 merge 多得的最小金额（USD）
+    pub disposal_min_advantage_usd: f64,
+    /// 方向性策略：现货模型概率与盘口偏离超过阈值时买入被低估的一边
+    pub directional_enabled: bool,
+    /// 卖一价低于模型概率的最小差值
+    pub directional_margin: f64,
+    /// 单笔下单金额（USD）
+    pub directional_order_usdc: f64,
+    /// 单市场每窗口累计投入上限（USD）
+    pub directional_max_position_usdc: f64,
+    /// 现货 1 小时收益率标准差
+    pub directional_hourly_vol: f64,
+    /// 距窗口结束少于该秒数时不再下单
+    pub directional_min_secs_remaining: i64,
+    /// 现货行情来源（Binance 兼容的 /api/v3/klines 接口）
+    pub spot_feed_url: String,
+    /// 实现波动率估计（VOL_*）：按现货逐笔成交计算滚动波动率，供波动率过滤、方向性策略与 GTD 有效期调节
+    pub volatility: VolSettings,
+    /// 流动性奖励计划：每个窗口查询监控市场的奖励参数，并把已获得的奖励计入盈亏报告
+    pub rewards_enabled: bool,
+    /// 套利订单会挂单（GTC/GTD）时，奖励市场的执行价差放宽该值（挂单可获得奖励）
+    pub rewards_edge_discount: f64,
+    /// 成交概率调整的期望收益模型：价差达到执行价差后，再按历史成交结果估计 EV，低于下限不下单
+    pub ev_model_enabled: bool,
+    /// 单边成交时每份的预期处理成本（对冲/卖出损失）
+    pub ev_one_sided_cost: f64,
+    /// 每份 EV 下限
+    pub ev_min_per_share: f64,
+    /// 成交概率模型统计文件；空字符串表示不落盘
+    pub fill_model_path: String,
+    /// 两腿各自的滑点 [first, second]（仅下降侧用 second，上涨与持平用 first）及按币种自适应加宽
+    pub slippage: SlippageSettings,
     pub gtd_expiration_secs: u64, // GTD订单过期时间（秒），默认300秒（5分钟）；仅当 arbitrage_order_type=GTD 时有效
+    /// 套利执行方式：pair（两腿同时提交，默认）| sequenced（先吃一腿，再按补数限价挂第二腿）
+    pub execution_mode: ExecutionMode,
+    /// pair 模式两腿的提交方式：batched（默认）| parallel | sequential
+    pub leg_submission: LegSubmission,
+    /// 各提交方式下单次下单请求的超时（毫秒）
+    pub leg_timeout_batched_ms: u64,
+    pub leg_timeout_parallel_ms: u64,
+    pub leg_timeout_sequential_ms: u64,
+    /// pair 模式单腿名义金额超过该值（USD）时拆成多笔子单批量提交，0=不拆单
+    pub child_order_max_notional_usdc: f64,
+    /// 冰山挂单显示数量（份，仅 GTC/GTD）：两腿先 FAK 吃单，未成交部分每次只挂该数量、成交后补挂，0=整笔挂单
+    pub iceberg_display_size: f64,
+    /// maker 兜底（仅 FOK/FAK）：吃单两腿均未成交时，在机会价位挂出两腿 GTC 买单
+    pub maker_fallback_enabled: bool,
+    /// maker 兜底单在市场结束前 N 分钟自动撤单
+    pub maker_fallback_cancel_before_end_minutes: u64,
+    /// sequenced 模式第二腿限价上限中扣除的每份费用
+    pub sequenced_fee_per_share: f64,
+    /// 单市场成对持仓上限（份）：下单后可 merge 数量 min(YES, NO) 不超过该值，0=不限制
+    pub max_mergeable_per_market: f64,
+    /// 下单数量随机抖动比例（0~1）：实际数量在 [数量×(1−比例), 数量] 内随机，0=不抖动
+    pub order_size_jitter_pct: f64,
+    /// 挂单排队位置跟踪：GTC/GTD 套利订单未成交部分挂单后，估计成交概率可忽略时撤单
+    pub queue_tracking_enabled: bool,
+    /// 估计成交概率的时间窗口（秒）
+    pub queue_horizon_secs: u64,
+    /// 估计成交概率低于该值时撤单
+    pub queue_min_fill_prob: f64,
+    /// 挂单后至少观察多少秒才评估
+    pub queue_min_observe_secs: u64,
     /// 套利下单时的订单类型：GTC（一直有效）、GTD（配合 gtd_expiration_secs）、FOK（立即全部成交否则取消）、FAK（立即部分成交其余取消）
     pub arbitrage_order_type: OrderType,
     pub stop_arbitrage_before_end_minutes: u64, // 市场结束前N分钟停止执行套利，默认0（不停止）
     /// 定时 Merge 间隔（分钟），0 表示不启用。CONDITION_ID 与订单簿一样由当前窗口市场获取。
     pub merge_interval_minutes: u64,
+    /// 订单对双边成交后立即 merge 该市场，而不是等定时 Merge
+    pub merge_on_pair_fill: bool,
+    /// 盈亏汇总（含 gas 成本）打印间隔（秒），0=不打印
+    pub pnl_report_interval_secs: u64,
+    /// POL 价格查询失败时折算 gas 成本使用的 POL/USD 价格
+    pub pol_usd_fallback: f64,
+    /// 失败 merge 重试队列文件；空字符串表示只在内存中保存
+    pub merge_retry_queue_path: String,
+    /// 每个 merge 最多尝试次数，用尽后转为死信并告警
+    pub merge_retry_max_attempts: u32,
+    /// 重试退避：首次失败后等待秒数，之后每次翻倍
+    pub merge_retry_base_backoff_secs: u64,
+    /// 重试退避上限（秒）
+    pub merge_retry_max_backoff_secs: u64,
+    /// 每笔 merge 确认后核对回执中转入 proxy 的 USDC 是否等于合并份数，不一致时告警
+    pub merge_verify_proceeds: bool,
+    /// 等待 merge 交易回执的超时（秒）
+    pub merge_verify_timeout_secs: u64,
     /// YES 价格阈值：只有当 YES 价格 >= 此阈值时才执行套利，默认 0.0（不限制）
     pub min_yes_price_threshold: f64,
     /// NO 价格阈值：只有当 NO 价格 >= 此阈值时才执行套利，默认 0.0（不限制）
@@ -62,24 +289,199 @@ pub struct Config {
     pub position_balance_threshold: f64,
     /// 最小总持仓要求，只有当总持仓 >= 此值时才执行平衡，默认5.0
     pub position_balance_min_total: f64,
+    /// 启用子图查询：持仓同步后与链上余额对账、账龄告警以链上结算为准
+    pub subgraph_enabled: bool,
+    /// 子图地址（持仓/条件、成交、活动）
+    pub subgraph_urls: SubgraphUrls,
+    /// WS RPC 地址：订阅 CTF 转账事件并用链上余额覆盖持仓；空字符串表示不启用（需代理地址）
+    pub chain_ws_rpc_url: String,
+    /// 持仓账龄检查间隔（秒），0=不启用
+    pub position_aging_check_interval_secs: u64,
+    /// 单边（未对冲）持仓超过该时长告警（秒）
+    pub position_unhedged_max_age_secs: u64,
+    /// 市场结束超过该时长仍持有（未赎回）时告警（秒）
+    pub position_unredeemed_grace_secs: u64,
+    /// 零头持仓清理间隔（秒），0=不启用
+    pub dust_cleanup_interval_secs: u64,
+    /// 持仓低于该数量视为零头
+    pub dust_threshold: f64,
+    /// 只剩单边零头时，买一不低于该价格才卖出
+    pub dust_min_bid: f64,
     /// 窗口结束前收尾：距离当前1小时窗口结束还有多少分钟时触发收尾（取消挂单→Merge→市价卖剩余）。0=不启用。
     pub wind_down_before_window_end_minutes: u64,
     /// 收尾时单腿卖出的限价单价格（尽量快速成交），默认0.01
     pub wind_down_sell_price: f64,
+    /// 启动时撤销不属于已恢复订单对的未知挂单（false 时只告警），默认 true
+    pub startup_cancel_unknown_orders: bool,
+    /// 窗口切换时（订阅新窗口前）取消上一窗口市场上仍未成交的挂单，默认 true
+    pub cancel_on_window_switch: bool,
+    /// 收尾 TWAP：不少于 twap_min_size 份的单腿持仓在该时长（秒）内分片卖出，0=不启用（一次性按收尾卖价卖出）
+    pub twap_exit_secs: u64,
+    /// TWAP 切片数
+    pub twap_slices: u32,
+    /// TWAP 子单数量随机浮动比例
+    pub twap_size_jitter_pct: f64,
+    /// 启用 TWAP 的最小持仓（份）
+    pub twap_min_size: f64,
+    /// TWAP 买一低于该价格时本片不卖
+    pub twap_min_price: f64,
+    /// 拒单处置策略表（REJECT_POLICY），格式见 trading::reject_policy；为空时不启用
+    pub reject_policy: String,
+    /// 下单因授权不足被拒时自动检查并补齐交易所授权，成功后重试被拒订单，默认 true
+    pub allowance_auto_repair: bool,
+    /// 两次自动补授权之间的最短间隔（秒）
+    pub allowance_repair_cooldown_secs: u64,
+    /// CLOB 连接保活间隔（秒）：定时发轻量请求保持连接常驻，须小于连接池空闲超时（约90秒）。0=不启用，默认30
+    pub http_keepalive_interval_secs: u64,
+    /// 预热/保活时并发发出的轻量请求数，即连接池中保持的热连接数，默认2
+    pub http_warmup_connections: usize,
+    /// 服务端点（CLOB_REST_URL / CLOB_WS_URL / GAMMA_API_URL），默认官方地址
+    pub endpoints: Endpoints,
+    /// 检测热路径（订单簿监控+检测+下单）是否运行在专用线程的 current-thread runtime 上，默认 false
+    pub detection_dedicated_thread: bool,
+    /// 风控状态检查点写盘间隔（秒），0=不启用
+    pub checkpoint_interval_secs: u64,
+    /// 风控状态检查点文件路径
+    pub checkpoint_path: String,
+    /// 主备租约文件路径（两个实例共享）；空字符串表示不启用主备
+    pub leader_lock_path: String,
+    /// 主备租约有效期（秒），主机心跳停止超过该时长后备机接管
+    pub leader_lease_secs: u64,
+    /// 本实例 ID（租约持有者标识），默认每次启动随机生成
+    pub instance_id: String,
+    /// 重复实例保护（文件锁 + 交易所侧探测）；启用主备或共享敞口账本时不生效
+    pub instance_guard: GuardSettings,
+    /// CLOB API 凭证轮换（定时 / SIGHUP）
+    pub api_key_rotation: RotationSettings,
+    /// API 请求限速与优先级调度（下单/撤单 > 成交轮询 > 市场发现/持仓查询）
+    pub api_rate_limit: RateLimitSettings,
+    /// 多实例共享敞口账本的 Redis 地址；空字符串表示只检查本实例限额
+    pub shared_ledger_url: String,
+    /// 共享账本的 Redis 哈希键，共用同一钱包的实例须一致
+    pub shared_ledger_key: String,
+    /// 所有实例合计的敞口上限（USD）；0 表示与 RISK_MAX_EXPOSURE_USDC 相同
+    pub shared_max_exposure_usdc: f64,
+    /// 市场元数据缓存文件路径，同一窗口内重启时直接从缓存订阅；空字符串表示不落盘
+    pub market_cache_path: String,
+    /// 市场发现方式：slug（按模板猜测 slug，events 兜底，默认）| events（先按事件标签查询，slug 兜底）
+    pub discovery_mode: DiscoveryMode,
+    /// events 查询额外使用的事件标签（系列自身的标签之外）
+    pub discovery_tags: Vec<String>,
+    /// 市场发现重试的退避上限（秒），从2秒起指数增长
+    pub discovery_retry_max_backoff_secs: u64,
+    /// 每个窗口市场发现的最大重试次数，用尽后等待下一个窗口；0=不限
+    pub discovery_max_retries_per_window: u32,
+    /// 窗口内市场状态检查间隔（秒）：停止接单/关闭的市场会被退订并撤单；0=不检查
+    pub market_status_check_interval_secs: u64,
+    /// 窗口开始时有系列市场缺失时，窗口内重新发现的间隔（秒）；0=不重新发现
+    pub market_rediscovery_interval_secs: u64,
+    /// 订单簿流静默检查间隔（秒）；0=不检查
+    pub ws_stale_check_interval_secs: u64,
+    /// 订单簿流连续多少秒未收到消息视为连接失效并主动重建
+    pub ws_stale_timeout_secs: u64,
+    /// 市场时区（IANA 名称，如 America/New_York），用于窗口边界与 slug 日期时间
+    pub market_timezone: chrono_tz::Tz,
+    /// 行权价阶梯系列（STRIKE_LADDERS）：模板生成的是事件 slug，事件下每个市场为一档行权价；空=不启用阶梯套利
+    pub strike_ladders: Vec<MarketSeries>,
+    /// 阶梯套利执行价差：低行权价 YES + 高行权价 NO <= 1 - 此值时执行
+    pub ladder_execution_spread: f64,
+    /// 单边/不平衡成交后的对冲策略：hold（持有）、buy_missing（买入缺失腿）、sell_excess（止盈止损卖出多出腿）
+    pub hedge_policy: HedgePolicy,
+    /// hold 策略下的库存退出：单边持仓的买一高于买入价 inventory_exit_min_profit_pct 时卖出多出腿
+    pub inventory_exit_enabled: bool,
+    pub inventory_exit_min_profit_pct: f64,
+    /// buy_missing 策略下两腿每份总成本上限，缺失腿限价 = 上限 - 已成交腿买入价
+    pub hedge_buy_max_pair_cost: f64,
+    /// 恢复策略实现：policy（按 hedge_policy 处理）、alert_only（只告警）
+    pub recovery_strategy: String,
+    /// buy_missing 时间阶梯：保本价挂单等待秒数（T1）
+    pub hedge_passive_secs: u64,
+    /// buy_missing 时间阶梯：此后每隔多少秒撤单并提高限价（T2）
+    pub hedge_improve_interval_secs: u64,
+    /// buy_missing 时间阶梯：每次提高的价格（封顶为成本上限对应的最高限价）
+    pub hedge_improve_tick: f64,
+    /// buy_missing 时间阶梯：市场结束前多少秒为硬截止，届时撤单并吃单买入剩余部分
+    pub hedge_market_order_before_end_secs: u64,
+    /// 交易日志（JSONL）路径，记录对冲决策与下单事件；空字符串表示不记录
+    pub journal_path: String,
+    /// 订单簿录制文件（JSONL，供 optimize 子命令回放）；空字符串表示不录制
+    pub book_record_path: String,
+    /// 订阅市场时立即拉取一次 REST 订单簿快照，不等 WS 首次推送，默认 true
+    pub book_snapshot_on_subscribe: bool,
+    /// 存储后端（sqlite://路径 或 postgres://...），交易日志与检查点写入数据库；空字符串表示只用本地文件
+    pub storage_url: String,
+    /// 每秒记录各市场 YES+NO 卖一价到存储后端（需 STORAGE_URL），供 spreads 子命令分析
+    pub spread_sample_enabled: bool,
+    /// 中间价时间序列导出目录（每窗口一个 Parquet 文件，研究用）；空字符串表示不导出
+    pub mid_export_dir: String,
+    /// 影子配置对比（SHADOW_*）：候选参数只记录不下单，窗口结束时与实盘对比
+    pub shadow: ShadowSettings,
+    /// A/B 资金分配（AB_*）：敞口预算按比例分给当前参数与候选参数，按变体统计盈亏
+    pub ab_test: AbSettings,
+    /// Redis 事件输出地址（如 redis://127.0.0.1:6379），发布机会/下单/merge 事件；空字符串表示不输出
+    pub redis_url: String,
+    /// Redis 频道前缀，频道名为 {前缀}:{事件类型}
+    pub redis_channel_prefix: String,
+    /// 事件流输出（NATS JetStream / Kafka，需编译对应 feature）
+    pub stream_sink: StreamSinkSettings,
+    /// 出站 Webhook（签名 POST 推送生命周期事件）
+    pub webhooks: WebhookSettings,
+    /// 启动前执行就绪检查（私钥、认证、RPC、时钟、余额、授权）并打印汇总
+    pub startup_preflight: bool,
+    /// 严格模式：就绪检查有失败，或余额/授权/时钟有警告时拒绝启动
+    pub startup_strict: bool,
+    /// 本地时钟与 CLOB 服务器时间允许的最大偏差（秒），超过则时钟检查告警
+    pub clock_max_skew_secs: f64,
+    /// 启用终端 TUI 监控面板（日志改写入 LOG_FILE，未设置时为 state/bot.log）
+    pub tui_enabled: bool,
+    /// TUI 重绘间隔（毫秒）
+    pub tui_refresh_ms: u64,
+    /// 控制/调试 HTTP 接口监听地址（如 127.0.0.1:9900）；空字符串表示不启用
+    pub control_api_addr: String,
+    /// 控制接口 Bearer token（启用接口时必填）
+    pub control_api_token: String,
+    /// 日志/告警消息语言（LOG_LOCALE=zh|en）
+    pub log_locale: Locale,
 }
 
 impl Config {
     pub fn from_env() -> Result<Self> {
         dotenvy::dotenv().ok();
 
+        // 市场系列：加密货币涨跌系列（每个窗口周期一组）+ 自定义系列
+        let window_durations = window::parse_list(&env::var("WINDOW_DURATION").unwrap_or_else(|_| "1h".to_string()));
+        let crypto_symbols: Vec<String> = env::var("CRYPTO_SYMBOLS")
+            .unwrap_or_else(|_| "btc,eth,xrp,sol".to_string())
+            .split(',')
+            .map(|s| s.trim().to_lowercase())
+            .collect();
+        let mut market_series: Vec<MarketSeries> = window_durations
+            .iter()
+            .flat_map(|&window| {
+                crypto_symbols
+                    .iter()
+                    .filter(|s| !s.is_empty())
+                    .map(move |s| MarketSeries::crypto(s, window))
+            })
+            .collect();
+        market_series.extend(parse_series_list(&env::var("MARKET_SERIES").unwrap_or_default()));
+        let symbol_overrides = parse_symbol_overrides(&env::var("SYMBOL_OVERRIDES").unwrap_or_default());
+        market_series.retain(|s| {
+            let key = match s.window() {
+                Some(window) => format!("{}:{}", s.name, window.as_str()),
+                None => s.name.clone(),
+            };
+            symbol_override(&symbol_overrides, &key).map(|o| o.enabled).unwrap_or(true)
+        });
+
         // 解析proxy_address（可选）
         let proxy_address: Option<Address> = env::var("POLYMARKET_PROXY_ADDRESS")
             .ok()
             .and_then(|addr| addr.parse().ok());
 
         Ok(Config {
-            private_key: env::var("POLYMARKET_PRIVATE_KEY")
-                .expect("POLYMARKET_PRIVATE_KEY must be set"),
+            // 观察模式（observe 子命令）无需私钥；交易主循环启动时再校验
+            private_key: env::var("POLYMARKET_PRIVATE_KEY").unwrap_or_default(),
             proxy_address,
             min_profit_threshold: env::var("MIN_PROFIT_THRESHOLD")
                 .unwrap_or_else(|_| "0.001".to_string())
@@ -89,11 +491,10 @@ impl Config {
                 .unwrap_or_else(|_| "100.0".to_string())
                 .parse()
                 .unwrap_or(100.0),
-            crypto_symbols: env::var("CRYPTO_SYMBOLS")
-                .unwrap_or_else(|_| "btc,eth,xrp,sol".to_string())
-                .split(',')
-                .map(|s| s.trim().to_lowercase())
-                .collect(),
+            crypto_symbols,
+            market_series,
+            window_durations,
+            symbol_overrides,
             market_refresh_advance_secs: env::var("MARKET_REFRESH_ADVANCE_SECS")
                 .unwrap_or_else(|_| "5".to_string())
                 .parse()
@@ -102,6 +503,15 @@ impl Config {
                 .unwrap_or_else(|_| "1000.0".to_string())
                 .parse()
                 .unwrap_or(1000.0),
+            risk_budgets: StrategyBudgets {
+                taker_arb: env::var("RISK_BUDGET_TAKER_ARB_USDC").ok().and_then(|s| s.trim().parse().ok()).unwrap_or(0.0),
+                maker_fallback: env::var("RISK_BUDGET_MAKER_FALLBACK_USDC")
+                    .ok()
+                    .and_then(|s| s.trim().parse().ok())
+                    .unwrap_or(0.0),
+                sell_back: env::var("RISK_BUDGET_SELL_BACK_USDC").ok().and_then(|s| s.trim().parse().ok()).unwrap_or(0.0),
+                directional: env::var("RISK_BUDGET_DIRECTIONAL_USDC").ok().and_then(|s| s.trim().parse().ok()).unwrap_or(0.0),
+            },
             risk_imbalance_threshold: env::var("RISK_IMBALANCE_THRESHOLD")
                 .unwrap_or_else(|_| "0.1".to_string())
                 .parse()
@@ -118,11 +528,232 @@ impl Config {
                 .unwrap_or_else(|_| "0.01".to_string())
                 .parse()
                 .unwrap_or(0.01), // 默认0.01
-            slippage: parse_slippage(&env::var("SLIPPAGE").unwrap_or_else(|_| "0,0.01".to_string())),
+            adaptive_spread_enabled: env::var("ADAPTIVE_SPREAD_ENABLED")
+                .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(false),
+            adaptive_spread_min: env::var("ADAPTIVE_SPREAD_MIN")
+                .unwrap_or_else(|_| "0.005".to_string())
+                .parse()
+                .unwrap_or(0.005), // 默认0.005
+            adaptive_spread_max: env::var("ADAPTIVE_SPREAD_MAX")
+                .unwrap_or_else(|_| "0.05".to_string())
+                .parse()
+                .unwrap_or(0.05), // 默认0.05
+            adaptive_spread_step: env::var("ADAPTIVE_SPREAD_STEP")
+                .unwrap_or_else(|_| "0.005".to_string())
+                .parse()
+                .unwrap_or(0.005), // 默认每次0.005
+            adaptive_widen_after: env::var("ADAPTIVE_WIDEN_AFTER")
+                .unwrap_or_else(|_| "2".to_string())
+                .parse()
+                .unwrap_or(2), // 默认连续2次单边
+            adaptive_tighten_after: env::var("ADAPTIVE_TIGHTEN_AFTER")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5), // 默认连续5次双腿
+            symbol_spread_enabled: env::var("SYMBOL_SPREAD_ENABLED")
+                .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(false), // 默认不启用
+            symbol_spread_path: env::var("SYMBOL_SPREAD_PATH")
+                .unwrap_or_else(|_| "state/symbol_spreads.json".to_string()),
+            symbol_spread_step: env::var("SYMBOL_SPREAD_STEP")
+                .unwrap_or_else(|_| "0.005".to_string())
+                .parse()
+                .unwrap_or(0.005), // 默认0.005
+            symbol_spread_min_samples: env::var("SYMBOL_SPREAD_MIN_SAMPLES")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse()
+                .unwrap_or(20), // 默认20笔
+            snipe_detection_enabled: env::var("SNIPE_DETECTION_ENABLED")
+                .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(false), // 默认不启用
+            snipe_window_secs: env::var("SNIPE_WINDOW_SECS")
+                .unwrap_or_else(|_| "600".to_string())
+                .parse()
+                .unwrap_or(600), // 默认10分钟
+            snipe_threshold: env::var("SNIPE_THRESHOLD")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse()
+                .unwrap_or(3), // 默认3次
+            snipe_edge_step: env::var("SNIPE_EDGE_STEP")
+                .unwrap_or_else(|_| "0.005".to_string())
+                .parse()
+                .unwrap_or(0.005), // 默认0.005
+            snipe_max_extra_edge: env::var("SNIPE_MAX_EXTRA_EDGE")
+                .unwrap_or_else(|_| "0.02".to_string())
+                .parse()
+                .unwrap_or(0.02), // 默认0.02
+            sell_back_enabled: env::var("SELL_BACK_ENABLED")
+                .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(false), // 默认不启用
+            sell_back_fee_per_share: env::var("SELL_BACK_FEE_PER_SHARE")
+                .unwrap_or_else(|_| "0.01".to_string())
+                .parse()
+                .unwrap_or(0.01), // 默认每份0.01
+            sell_back_min_edge: env::var("SELL_BACK_MIN_EDGE")
+                .unwrap_or_else(|_| "0.005".to_string())
+                .parse()
+                .unwrap_or(0.005), // 默认0.005
+            disposal_policy_enabled: env::var("DISPOSAL_POLICY_ENABLED")
+                .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(false), // 默认不启用
+            disposal_merge_gas_usd: env::var("DISPOSAL_MERGE_GAS_USD")
+                .unwrap_or_else(|_| "0.02".to_string())
+                .parse()
+                .unwrap_or(0.02), // 默认0.02 USD
+            disposal_min_advantage_usd: env::var("DISPOSAL_MIN_ADVANTAGE_USD")
+                .unwrap_or_else(|_| "0.05".to_string())
+                .parse()
+                .unwrap_or(0.05), // 默认0.05 USD
+            directional_enabled: env::var("DIRECTIONAL_ENABLED")
+                .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(false), // 默认不启用
+            directional_margin: env::var("DIRECTIONAL_MARGIN")
+                .unwrap_or_else(|_| "0.08".to_string())
+                .parse()
+                .unwrap_or(0.08), // 默认偏离8个百分点
+            directional_order_usdc: env::var("DIRECTIONAL_ORDER_USDC")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5.0), // 默认每笔5 USD
+            directional_max_position_usdc: env::var("DIRECTIONAL_MAX_POSITION_USDC")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse()
+                .unwrap_or(20.0), // 默认单市场20 USD
+            directional_hourly_vol: env::var("DIRECTIONAL_HOURLY_VOL")
+                .unwrap_or_else(|_| "0.006".to_string())
+                .parse()
+                .unwrap_or(0.006), // 默认0.6%
+            directional_min_secs_remaining: env::var("DIRECTIONAL_MIN_SECS_REMAINING")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .unwrap_or(300), // 默认结束前5分钟停止
+            spot_feed_url: env::var("SPOT_FEED_URL")
+                .unwrap_or_else(|_| "https://api.binance.com".to_string()),
+            volatility: VolSettings {
+                enabled: env::var("VOL_ESTIMATOR_ENABLED")
+                    .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                    .unwrap_or(false), // 默认不启用
+                ws_url: env::var("SPOT_WS_URL")
+                    .unwrap_or_else(|_| "wss://stream.binance.com:9443".to_string()),
+                windows_secs: env::var("VOL_WINDOWS_SECS")
+                    .unwrap_or_else(|_| "900,300,3600".to_string())
+                    .split(',')
+                    .filter_map(|s| s.trim().parse().ok())
+                    .filter(|w: &u64| *w > 0)
+                    .collect(), // 默认主窗口15分钟，另对照5分钟与1小时
+                filter_max_hourly: env::var("VOL_FILTER_MAX_HOURLY")
+                    .unwrap_or_else(|_| "0".to_string())
+                    .parse()
+                    .unwrap_or(0.0), // 默认不过滤
+                gtd_reference_hourly: env::var("VOL_GTD_REFERENCE_HOURLY")
+                    .unwrap_or_else(|_| "0".to_string())
+                    .parse()
+                    .unwrap_or(0.0), // 默认不调节
+            },
+            rewards_enabled: env::var("REWARDS_ENABLED")
+                .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(false), // 默认关闭
+            rewards_edge_discount: env::var("REWARDS_EDGE_DISCOUNT")
+                .unwrap_or_else(|_| "0.0".to_string())
+                .parse()
+                .unwrap_or(0.0), // 默认不放宽
+            ev_model_enabled: env::var("EV_MODEL_ENABLED")
+                .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(true), // 默认启用
+            ev_one_sided_cost: env::var("EV_ONE_SIDED_COST")
+                .unwrap_or_else(|_| "0.05".to_string())
+                .parse()
+                .unwrap_or(0.05), // 默认每份0.05
+            ev_min_per_share: env::var("EV_MIN_PER_SHARE")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0.0), // 默认0（EV 为正即下单）
+            fill_model_path: env::var("FILL_MODEL_PATH")
+                .unwrap_or_else(|_| "state/fill_model.json".to_string()),
+            slippage: SlippageSettings {
+                yes: parse_slippage(
+                    &env::var("SLIPPAGE_YES")
+                        .or_else(|_| env::var("SLIPPAGE"))
+                        .unwrap_or_else(|_| "0,0.01".to_string()),
+                ),
+                no: parse_slippage(
+                    &env::var("SLIPPAGE_NO")
+                        .or_else(|_| env::var("SLIPPAGE"))
+                        .unwrap_or_else(|_| "0,0.01".to_string()),
+                ),
+                adaptive_enabled: env::var("SLIPPAGE_ADAPTIVE_ENABLED")
+                    .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                    .unwrap_or(false),
+                adaptive_max: env::var("SLIPPAGE_ADAPTIVE_MAX")
+                    .unwrap_or_else(|_| "0.01".to_string())
+                    .parse()
+                    .unwrap_or(0.01), // 默认最多加宽0.01
+                adaptive_min_samples: env::var("SLIPPAGE_ADAPTIVE_MIN_SAMPLES")
+                    .unwrap_or_else(|_| "10".to_string())
+                    .parse()
+                    .unwrap_or(10), // 默认10次下单后生效
+            },
             gtd_expiration_secs: env::var("GTD_EXPIRATION_SECS")
                 .unwrap_or_else(|_| "300".to_string())
                 .parse()
                 .unwrap_or(300), // 默认300秒（5分钟）
+            execution_mode: ExecutionMode::parse(&env::var("ARBITRAGE_EXECUTION_MODE").unwrap_or_else(|_| "pair".to_string())),
+            leg_submission: LegSubmission::parse(&env::var("LEG_SUBMISSION").unwrap_or_else(|_| "batched".to_string())),
+            leg_timeout_batched_ms: env::var("LEG_TIMEOUT_BATCHED_MS")
+                .unwrap_or_else(|_| "3000".to_string())
+                .parse()
+                .unwrap_or(3000), // 默认3秒
+            leg_timeout_parallel_ms: env::var("LEG_TIMEOUT_PARALLEL_MS")
+                .unwrap_or_else(|_| "3000".to_string())
+                .parse()
+                .unwrap_or(3000), // 默认3秒
+            leg_timeout_sequential_ms: env::var("LEG_TIMEOUT_SEQUENTIAL_MS")
+                .unwrap_or_else(|_| "1500".to_string())
+                .parse()
+                .unwrap_or(1500), // 默认1.5秒（每腿）
+            child_order_max_notional_usdc: env::var("CHILD_ORDER_MAX_NOTIONAL_USDC")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0.0), // 默认不拆单
+            iceberg_display_size: env::var("ICEBERG_DISPLAY_SIZE")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0.0), // 默认整笔挂单
+            maker_fallback_enabled: env::var("MAKER_FALLBACK_ENABLED")
+                .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(false),
+            maker_fallback_cancel_before_end_minutes: env::var("MAKER_FALLBACK_CANCEL_BEFORE_END_MINUTES")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5), // 默认市场结束前5分钟撤单
+            sequenced_fee_per_share: env::var("SEQUENCED_FEE_PER_SHARE")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0.0), // 默认0
+            max_mergeable_per_market: env::var("MAX_MERGEABLE_PER_MARKET")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0.0), // 默认0（不限制）
+            order_size_jitter_pct: env::var("ORDER_SIZE_JITTER_PCT")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0.0), // 默认0（不抖动）
+            queue_tracking_enabled: env::var("QUEUE_TRACKING_ENABLED")
+                .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(false), // 默认不启用
+            queue_horizon_secs: env::var("QUEUE_HORIZON_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30), // 默认30秒
+            queue_min_fill_prob: env::var("QUEUE_MIN_FILL_PROB")
+                .unwrap_or_else(|_| "0.2".to_string())
+                .parse()
+                .unwrap_or(0.2), // 默认0.2
+            queue_min_observe_secs: env::var("QUEUE_MIN_OBSERVE_SECS")
+                .unwrap_or_else(|_| "3".to_string())
+                .parse()
+                .unwrap_or(3), // 默认3秒
             arbitrage_order_type: parse_arbitrage_order_type(
                 &env::var("ARBITRAGE_ORDER_TYPE").unwrap_or_else(|_| "GTD".to_string()),
             ),
@@ -134,6 +765,38 @@ impl Config {
                 .unwrap_or_else(|_| "0".to_string())
                 .parse()
                 .unwrap_or(0), // 0=不启用
+            merge_on_pair_fill: env::var("MERGE_ON_PAIR_FILL")
+                .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(false), // 默认关闭
+            pnl_report_interval_secs: env::var("PNL_REPORT_INTERVAL_SECS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .unwrap_or(300), // 默认5分钟
+            pol_usd_fallback: env::var("POL_USD_FALLBACK")
+                .unwrap_or_else(|_| "0.25".to_string())
+                .parse()
+                .unwrap_or(0.25),
+            merge_retry_queue_path: env::var("MERGE_RETRY_QUEUE_PATH")
+                .unwrap_or_else(|_| "state/merge_retry_queue.json".to_string()),
+            merge_retry_max_attempts: env::var("MERGE_RETRY_MAX_ATTEMPTS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5), // 默认最多5次
+            merge_retry_base_backoff_secs: env::var("MERGE_RETRY_BASE_BACKOFF_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30), // 默认30秒起
+            merge_retry_max_backoff_secs: env::var("MERGE_RETRY_MAX_BACKOFF_SECS")
+                .unwrap_or_else(|_| "1800".to_string())
+                .parse()
+                .unwrap_or(1800), // 默认最长30分钟
+            merge_verify_proceeds: env::var("MERGE_VERIFY_PROCEEDS")
+                .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(true), // 默认开启
+            merge_verify_timeout_secs: env::var("MERGE_VERIFY_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "120".to_string())
+                .parse()
+                .unwrap_or(120), // 默认2分钟
             min_yes_price_threshold: env::var("MIN_YES_PRICE_THRESHOLD")
                 .unwrap_or_else(|_| "0.0".to_string())
                 .parse()
@@ -158,6 +821,39 @@ impl Config {
                 .unwrap_or_else(|_| "5.0".to_string())
                 .parse()
                 .unwrap_or(5.0), // 默认5.0
+            subgraph_enabled: env::var("SUBGRAPH_ENABLED")
+                .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(false), // 默认关闭
+            subgraph_urls: SubgraphUrls {
+                positions: env::var("SUBGRAPH_POSITIONS_URL").unwrap_or_else(|_| DEFAULT_POSITIONS_SUBGRAPH.to_string()),
+                orderbook: env::var("SUBGRAPH_ORDERBOOK_URL").unwrap_or_else(|_| DEFAULT_ORDERBOOK_SUBGRAPH.to_string()),
+                activity: env::var("SUBGRAPH_ACTIVITY_URL").unwrap_or_else(|_| DEFAULT_ACTIVITY_SUBGRAPH.to_string()),
+            },
+            chain_ws_rpc_url: env::var("CHAIN_WS_RPC_URL").unwrap_or_default(),
+            position_aging_check_interval_secs: env::var("POSITION_AGING_CHECK_INTERVAL_SECS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0), // 0=不启用
+            position_unhedged_max_age_secs: env::var("POSITION_UNHEDGED_MAX_AGE_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .unwrap_or(3600), // 默认1小时
+            position_unredeemed_grace_secs: env::var("POSITION_UNREDEEMED_GRACE_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .unwrap_or(3600), // 默认结束后1小时
+            dust_cleanup_interval_secs: env::var("DUST_CLEANUP_INTERVAL_SECS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0), // 0=不启用
+            dust_threshold: env::var("DUST_THRESHOLD")
+                .unwrap_or_else(|_| "1.0".to_string())
+                .parse()
+                .unwrap_or(1.0), // 默认1份
+            dust_min_bid: env::var("DUST_MIN_BID")
+                .unwrap_or_else(|_| "0.05".to_string())
+                .parse()
+                .unwrap_or(0.05), // 默认0.05
             wind_down_before_window_end_minutes: env::var("WIND_DOWN_BEFORE_WINDOW_END_MINUTES")
                 .unwrap_or_else(|_| "0".to_string())
                 .parse()
@@ -166,6 +862,303 @@ impl Config {
                 .unwrap_or_else(|_| "0.01".to_string())
                 .parse()
                 .unwrap_or(0.01), // 默认0.01
+            startup_cancel_unknown_orders: env::var("STARTUP_CANCEL_UNKNOWN_ORDERS")
+                .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(true), // 默认撤销
+            cancel_on_window_switch: env::var("CANCEL_ON_WINDOW_SWITCH")
+                .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(true), // 默认启用
+            twap_exit_secs: env::var("TWAP_EXIT_SECS")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0), // 0=不启用
+            twap_slices: env::var("TWAP_SLICES")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10), // 默认10片
+            twap_size_jitter_pct: env::var("TWAP_SIZE_JITTER_PCT")
+                .unwrap_or_else(|_| "0.3".to_string())
+                .parse()
+                .unwrap_or(0.3), // 默认±30%
+            twap_min_size: env::var("TWAP_MIN_SIZE")
+                .unwrap_or_else(|_| "50".to_string())
+                .parse()
+                .unwrap_or(50.0), // 默认50份
+            twap_min_price: env::var("TWAP_MIN_PRICE")
+                .unwrap_or_else(|_| "0.05".to_string())
+                .parse()
+                .unwrap_or(0.05), // 默认0.05
+            reject_policy: env::var("REJECT_POLICY").unwrap_or_default(),
+            allowance_auto_repair: env::var("ALLOWANCE_AUTO_REPAIR")
+                .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(true), // 默认启用
+            allowance_repair_cooldown_secs: env::var("ALLOWANCE_REPAIR_COOLDOWN_SECS")
+                .unwrap_or_else(|_| "600".to_string())
+                .parse()
+                .unwrap_or(600), // 默认10分钟
+            http_keepalive_interval_secs: env::var("HTTP_KEEPALIVE_INTERVAL_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30), // 默认30秒，0=不启用
+            http_warmup_connections: env::var("HTTP_WARMUP_CONNECTIONS")
+                .unwrap_or_else(|_| "2".to_string())
+                .parse()
+                .unwrap_or(2), // 默认2条热连接
+            endpoints: Endpoints::from_env(),
+            detection_dedicated_thread: env::var("DETECTION_DEDICATED_THREAD")
+                .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(false), // 默认不启用
+            checkpoint_interval_secs: env::var("CHECKPOINT_INTERVAL_SECS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10), // 默认10秒，0=不启用
+            checkpoint_path: env::var("CHECKPOINT_PATH")
+                .unwrap_or_else(|_| "state/risk_checkpoint.json".to_string()),
+            leader_lock_path: env::var("LEADER_LOCK_PATH").unwrap_or_default(),
+            leader_lease_secs: env::var("LEADER_LEASE_SECS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10), // 默认10秒
+            instance_id: env::var("INSTANCE_ID")
+                .ok()
+                .filter(|s| !s.trim().is_empty())
+                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            instance_guard: GuardSettings {
+                enabled: env::var("INSTANCE_GUARD_ENABLED")
+                    .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                    .unwrap_or(true), // 默认启用
+                lock_dir: env::var("INSTANCE_LOCK_DIR").unwrap_or_else(|_| "state".to_string()),
+                probe_secs: env::var("INSTANCE_GUARD_PROBE_SECS")
+                    .unwrap_or_else(|_| "10".to_string())
+                    .parse()
+                    .unwrap_or(10), // 默认10秒，0=只用文件锁
+            },
+            api_key_rotation: RotationSettings {
+                interval_hours: env::var("API_KEY_ROTATION_HOURS")
+                    .unwrap_or_else(|_| "0".to_string())
+                    .parse()
+                    .unwrap_or(0), // 默认不定时轮换
+                nonce_path: env::var("API_KEY_NONCE_PATH")
+                    .unwrap_or_else(|_| "state/api_key_nonce".to_string()),
+                revoke_grace_secs: env::var("API_KEY_REVOKE_GRACE_SECS")
+                    .unwrap_or_else(|_| "30".to_string())
+                    .parse()
+                    .unwrap_or(30), // 默认30秒
+            },
+            api_rate_limit: RateLimitSettings {
+                per_sec: env::var("API_RATE_LIMIT_PER_SEC")
+                    .unwrap_or_else(|_| "0".to_string())
+                    .parse()
+                    .unwrap_or(0.0), // 默认不限速
+                burst: env::var("API_RATE_LIMIT_BURST")
+                    .unwrap_or_else(|_| "20".to_string())
+                    .parse()
+                    .unwrap_or(20.0), // 默认20
+                low_max_wait_ms: env::var("API_LOW_PRIORITY_MAX_WAIT_MS")
+                    .unwrap_or_else(|_| "2000".to_string())
+                    .parse()
+                    .unwrap_or(2000), // 默认2秒
+            },
+            shared_ledger_url: env::var("SHARED_LEDGER_URL").unwrap_or_default(),
+            shared_ledger_key: env::var("SHARED_LEDGER_KEY")
+                .unwrap_or_else(|_| "poly_1hour_bot:exposure".to_string()),
+            shared_max_exposure_usdc: env::var("SHARED_MAX_EXPOSURE_USDC")
+                .unwrap_or_else(|_| "0".to_string())
+                .parse()
+                .unwrap_or(0.0), // 默认与单实例上限相同
+            market_cache_path: env::var("MARKET_CACHE_PATH")
+                .unwrap_or_else(|_| "state/market_cache.json".to_string()),
+            discovery_mode: DiscoveryMode::parse(&env::var("DISCOVERY_MODE").unwrap_or_else(|_| "slug".to_string())),
+            discovery_tags: env::var("DISCOVERY_TAGS")
+                .unwrap_or_else(|_| "crypto,hourlies".to_string())
+                .split(',')
+                .map(|s| s.trim().to_lowercase())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            discovery_retry_max_backoff_secs: env::var("DISCOVERY_RETRY_MAX_BACKOFF_SECS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60), // 默认60秒
+            discovery_max_retries_per_window: env::var("DISCOVERY_MAX_RETRIES_PER_WINDOW")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30), // 默认30次
+            market_status_check_interval_secs: env::var("MARKET_STATUS_CHECK_INTERVAL_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30), // 默认30秒
+            market_rediscovery_interval_secs: env::var("MARKET_REDISCOVERY_INTERVAL_SECS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60), // 默认60秒
+            ws_stale_check_interval_secs: env::var("WS_STALE_CHECK_INTERVAL_SECS")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5), // 默认5秒
+            ws_stale_timeout_secs: env::var("WS_STALE_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30), // 默认30秒
+            market_timezone: env::var("MARKET_TIMEZONE")
+                .ok()
+                .and_then(|s| s.trim().parse().ok())
+                .unwrap_or(DEFAULT_MARKET_TIMEZONE), // 默认美东时间
+            strike_ladders: parse_series_list(&env::var("STRIKE_LADDERS").unwrap_or_default()),
+            ladder_execution_spread: env::var("LADDER_EXECUTION_SPREAD")
+                .unwrap_or_else(|_| "0.02".to_string())
+                .parse()
+                .unwrap_or(0.02), // 默认0.02（阶梯两腿无法 merge，须持有到结算）
+            hedge_policy: HedgePolicy::parse(&env::var("HEDGE_POLICY").unwrap_or_else(|_| "hold".to_string())),
+            inventory_exit_enabled: env::var("INVENTORY_EXIT_ENABLED")
+                .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(false), // 默认不启用
+            inventory_exit_min_profit_pct: env::var("INVENTORY_EXIT_MIN_PROFIT_PCT")
+                .unwrap_or_else(|_| "0.02".to_string())
+                .parse()
+                .unwrap_or(0.02), // 默认2%
+            hedge_buy_max_pair_cost: env::var("HEDGE_BUY_MAX_PAIR_COST")
+                .unwrap_or_else(|_| "1.0".to_string())
+                .parse()
+                .unwrap_or(1.0), // 默认1.0（补齐后不亏损）
+            recovery_strategy: env::var("RECOVERY_STRATEGY")
+                .unwrap_or_else(|_| "policy".to_string()), // 默认按 HEDGE_POLICY 处理
+            hedge_passive_secs: env::var("HEDGE_PASSIVE_SECS")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30), // 默认30秒
+            hedge_improve_interval_secs: env::var("HEDGE_IMPROVE_INTERVAL_SECS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10), // 默认10秒
+            hedge_improve_tick: env::var("HEDGE_IMPROVE_TICK")
+                .unwrap_or_else(|_| "0.01".to_string())
+                .parse()
+                .unwrap_or(0.01), // 默认每次提高0.01
+            hedge_market_order_before_end_secs: env::var("HEDGE_MARKET_ORDER_BEFORE_END_SECS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .unwrap_or(300), // 默认结束前5分钟
+            journal_path: env::var("JOURNAL_PATH")
+                .unwrap_or_else(|_| "state/journal.jsonl".to_string()),
+            book_record_path: env::var("BOOK_RECORD_PATH").unwrap_or_default(),
+            book_snapshot_on_subscribe: env::var("BOOK_SNAPSHOT_ON_SUBSCRIBE")
+                .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(true), // 默认开启
+            storage_url: env::var("STORAGE_URL").unwrap_or_default(),
+            spread_sample_enabled: env::var("SPREAD_SAMPLE_ENABLED")
+                .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(false),
+            mid_export_dir: env::var("MID_EXPORT_DIR").unwrap_or_default(),
+            shadow: ShadowSettings {
+                enabled: env::var("SHADOW_ENABLED")
+                    .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                    .unwrap_or(false),
+                min_profit_threshold: env::var("SHADOW_MIN_PROFIT_THRESHOLD").ok().and_then(|s| s.trim().parse().ok()),
+                execution_spread: env::var("SHADOW_ARBITRAGE_EXECUTION_SPREAD").ok().and_then(|s| s.trim().parse().ok()),
+                max_order_size_usdc: env::var("SHADOW_MAX_ORDER_SIZE_USDC").ok().and_then(|s| s.trim().parse().ok()),
+                max_exposure_usdc: env::var("SHADOW_MAX_EXPOSURE_USDC").ok().and_then(|s| s.trim().parse().ok()),
+            },
+            ab_test: AbSettings {
+                enabled: env::var("AB_TEST_ENABLED")
+                    .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                    .unwrap_or(false),
+                split_a: env::var("AB_SPLIT")
+                    .unwrap_or_else(|_| "0.5".to_string())
+                    .parse()
+                    .unwrap_or(0.5), // 默认各一半
+                b_min_profit_threshold: env::var("AB_B_MIN_PROFIT_THRESHOLD").ok().and_then(|s| s.trim().parse().ok()),
+                b_execution_spread: env::var("AB_B_ARBITRAGE_EXECUTION_SPREAD").ok().and_then(|s| s.trim().parse().ok()),
+                b_max_order_size_usdc: env::var("AB_B_MAX_ORDER_SIZE_USDC").ok().and_then(|s| s.trim().parse().ok()),
+            },
+            redis_url: env::var("REDIS_URL").unwrap_or_default(),
+            redis_channel_prefix: env::var("REDIS_CHANNEL_PREFIX")
+                .unwrap_or_else(|_| "poly_1hour_bot".to_string()),
+            stream_sink: StreamSinkSettings {
+                backend: env::var("STREAM_SINK").unwrap_or_default(),
+                url: env::var("STREAM_SINK_URL").unwrap_or_default(),
+                topic_prefix: env::var("STREAM_SINK_TOPIC_PREFIX")
+                    .unwrap_or_else(|_| "poly_1hour_bot".to_string()),
+                buffer: env::var("STREAM_SINK_BUFFER")
+                    .unwrap_or_else(|_| "100000".to_string())
+                    .parse()
+                    .unwrap_or(100000), // 默认最多缓存10万条待确认事件
+            },
+            webhooks: WebhookSettings {
+                urls: env::var("WEBHOOK_URLS")
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect(),
+                secret: env::var("WEBHOOK_SECRET").unwrap_or_default(),
+                events: env::var("WEBHOOK_EVENTS")
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(|s| s.trim().to_lowercase())
+                    .filter(|s| !s.is_empty())
+                    .collect(),
+            },
+            startup_preflight: env::var("STARTUP_PREFLIGHT")
+                .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(true), // 默认启用
+            startup_strict: env::var("STARTUP_STRICT")
+                .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(false),
+            clock_max_skew_secs: env::var("CLOCK_MAX_SKEW_SECS")
+                .unwrap_or_else(|_| "2".to_string())
+                .parse()
+                .unwrap_or(2.0), // 默认2秒
+            tui_enabled: env::var("TUI_ENABLED")
+                .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+                .unwrap_or(false),
+            tui_refresh_ms: env::var("TUI_REFRESH_MS")
+                .unwrap_or_else(|_| "500".to_string())
+                .parse()
+                .unwrap_or(500), // 默认500毫秒
+            control_api_addr: env::var("CONTROL_API_ADDR").unwrap_or_default(),
+            control_api_token: env::var("CONTROL_API_TOKEN").unwrap_or_default(),
+            log_locale: env::var("LOG_LOCALE")
+                .map(|s| Locale::parse(&s))
+                .unwrap_or_default(), // 默认中文
         })
     }
+
+    /// 当前两腿提交方式对应的下单请求超时
+    pub fn leg_timeout(&self) -> std::time::Duration {
+        let ms = match self.leg_submission {
+            LegSubmission::Batched => self.leg_timeout_batched_ms,
+            LegSubmission::Parallel => self.leg_timeout_parallel_ms,
+            LegSubmission::Sequential => self.leg_timeout_sequential_ms,
+        };
+        std::time::Duration::from_millis(ms.max(1))
+    }
+
+    /// 某币种（状态键，如 btc:15m）的单笔下单上限
+    pub fn max_order_size_for(&self, symbol_key: &str) -> f64 {
+        symbol_override(&self.symbol_overrides, symbol_key)
+            .and_then(|o| o.max_order_size_usdc)
+            .unwrap_or(self.max_order_size_usdc)
+    }
+
+    /// 所有币种中最大的单笔下单上限（执行器的硬上限）
+    pub fn max_order_size_ceiling(&self) -> f64 {
+        self.symbol_overrides
+            .values()
+            .filter_map(|o| o.max_order_size_usdc)
+            .fold(self.max_order_size_usdc, f64::max)
+    }
+
+    /// 某币种（状态键，如 btc:15m）的最低利润率覆盖（未设置时返回 None，沿用执行价差判断）
+    pub fn min_profit_threshold_for(&self, symbol_key: &str) -> Option<f64> {
+        symbol_override(&self.symbol_overrides, symbol_key).and_then(|o| o.min_profit_threshold)
+    }
+
+    /// 调度器的提前刷新时间：取全局与各币种覆盖中的最大值，
+    /// 创建较晚的币种由窗口内重新发现补上
+    pub fn max_refresh_advance_secs(&self) -> u64 {
+        self.symbol_overrides
+            .values()
+            .filter_map(|o| o.refresh_advance_secs)
+            .fold(self.market_refresh_advance_secs, u64::max)
+    }
 }
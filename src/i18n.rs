@@ -0,0 +1,170 @@
+//! 日志/告警消息目录：主要运行消息按 LOG_LOCALE 输出中文（默认）或英文，方便不懂中文的运维人员运行机器人。
+//! 用法：`tr!(Msg::X)` 返回 &'static str；带参数时 `tr!(Msg::X, a, b)` 按顺序填充模板中的 `{}` / `{:.N}`，返回 String。
+
+use std::fmt::{Display, Write};
+use std::sync::OnceLock;
+
+/// 消息语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    Zh,
+    En,
+}
+
+impl Locale {
+    /// 解析 LOG_LOCALE（zh / en / en-US 等），无法识别时为中文
+    pub fn parse(s: &str) -> Self {
+        let s = s.trim().to_ascii_lowercase();
+        if s == "en" || s.starts_with("en-") || s.starts_with("en_") || s == "english" {
+            Locale::En
+        } else {
+            Locale::Zh
+        }
+    }
+}
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+/// 设置消息语言（启动加载配置后调用一次）
+pub fn set_locale(locale: Locale) {
+    let _ = LOCALE.set(locale);
+}
+
+pub fn locale() -> Locale {
+    LOCALE.get().copied().unwrap_or_default()
+}
+
+/// 填充消息模板：依次替换 `{}` 与 `{:.N}`（保留 N 位小数）
+pub fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut out = String::with_capacity(template.len() + 16 * args.len());
+    let mut args = args.iter();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let Some(len) = rest[start..].find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+        let spec = &rest[start + 1..start + len];
+        match args.next() {
+            Some(arg) => {
+                let precision = spec.strip_prefix(":.").and_then(|p| p.parse::<usize>().ok());
+                let _ = match precision {
+                    Some(p) => write!(out, "{:.*}", p, arg),
+                    None => write!(out, "{}", arg),
+                };
+            }
+            None => out.push_str(&rest[start..=start + len]),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// 取消息文本；带参数时按顺序填充模板
+#[macro_export]
+macro_rules! tr {
+    ($msg:expr) => {
+        $msg.text()
+    };
+    ($msg:expr, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::fill($msg.text(), &[$(&$arg as &dyn ::std::fmt::Display),+])
+    };
+}
+
+macro_rules! catalog {
+    ($($name:ident => ($zh:expr, $en:expr),)+) => {
+        /// 消息目录
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum Msg {
+            $($name,)+
+        }
+
+        impl Msg {
+            /// 当前语言下的消息模板
+            pub fn text(self) -> &'static str {
+                match (self, locale()) {
+                    $(
+                        (Msg::$name, Locale::Zh) => $zh,
+                        (Msg::$name, Locale::En) => $en,
+                    )+
+                }
+            }
+        }
+    };
+}
+
+catalog! {
+    // 启动与认证
+    Startup => ("Polymarket 1小时套利机器人启动", "Polymarket 1-hour arbitrage bot starting"),
+    ConfigLoaded => ("配置加载完成", "Configuration loaded"),
+    InitExecutor => ("正在初始化交易执行器（需要API认证）...", "Initializing trading executor (API authentication required)..."),
+    ExecutorAuthOk => ("交易执行器认证成功（可能使用了派生API key）", "Trading executor authenticated (derived API key may have been used)"),
+    ExecutorAuthFailed => ("交易执行器认证失败！无法继续运行。", "Trading executor authentication failed; cannot continue."),
+    InitRiskClient => ("正在初始化风险管理客户端（需要API认证）...", "Initializing risk management client (API authentication required)..."),
+    RiskAuthOk => ("风险管理客户端认证成功（可能使用了派生API key）", "Risk management client authenticated (derived API key may have been used)"),
+    RiskAuthFailed => ("风险管理客户端认证失败！无法继续运行。", "Risk management client authentication failed; cannot continue."),
+    AuthVerifyOk => ("✅ 认证验证成功，API调用正常", "✅ Authentication verified, API calls succeed"),
+    AllReady => ("✅ 所有组件初始化完成，认证验证通过", "✅ All components initialized, authentication verified"),
+    ShutdownCheckpointOk => ("🛑 收到退出信号，已写入最终检查点", "🛑 Shutdown signal received, final checkpoint written"),
+    ShutdownCheckpointFailed => ("🛑 收到退出信号，写最终检查点失败", "🛑 Shutdown signal received, failed to write final checkpoint"),
+
+    // 主循环
+    MarketFetchFailed => ("获取市场失败", "Failed to fetch markets"),
+    NoMarkets => ("未找到任何市场，跳过当前窗口", "No markets found, skipping current window"),
+    MonitorStart => ("开始监控订单簿", "Monitoring order books"),
+    StreamCreateFailed => ("创建订单簿流失败", "Failed to create order book stream"),
+    StreamRebuildFailed => ("重建订单簿流失败", "Failed to rebuild order book stream"),
+    StreamError => ("订单簿更新错误", "Order book update error"),
+    StreamEnded => ("订单簿流结束，重新创建", "Order book stream ended, recreating"),
+    WsStreamStale => ("⚠️ 订单簿流长时间未收到消息，主动重建订单簿流", "⚠️ Order book stream went silent, rebuilding it"),
+    NewWindow => ("检测到新的1小时窗口，准备取消旧订阅并切换到新窗口", "New 1-hour window detected, dropping old subscriptions and switching"),
+    WindowDone => ("当前窗口监控结束，刷新市场进入下一轮", "Window monitoring finished, refreshing markets for the next round"),
+    WindDownTriggered => ("🛑 触发收尾 | 距窗口结束 {} 分钟", "🛑 Wind-down triggered | {} minutes until window end"),
+    WindDownDone => ("🛑 收尾完成，继续监控至窗口结束", "🛑 Wind-down complete, monitoring until window end"),
+    ExecuteFailed => ("执行套利交易失败: {}", "Arbitrage execution failed: {}"),
+    ManualIntervention => ("需要手动干预: {}", "Manual intervention required: {}"),
+    RiskHandleFailed => ("风险处理失败: {}", "Risk handling failed: {}"),
+
+    // 下单
+    OrderSkippedMinNotional => (
+        "⏭️ 跳过下单 | YES金额:{:.2} USD NO金额:{:.2} USD | 双边均须 > $1",
+        "⏭️ Order skipped | YES notional:{:.2} USD NO notional:{:.2} USD | both legs must be > $1"
+    ),
+    ArbSuccess => (
+        "✅ 套利交易成功 | 订单对ID:{} | YES成交:{}份 | NO成交:{}份 | 总成交:{}份",
+        "✅ Arbitrage filled | pair:{} | YES filled:{} | NO filled:{} | total:{}"
+    ),
+    ArbOneSided => (
+        "⚠️ 单边成交 | {} | {} 成交 {} 份，{} 未成交（已交风控）",
+        "⚠️ One-sided fill | {} | {} filled {} shares, {} unfilled (handed to risk)"
+    ),
+    ArbNoneFilled => ("❌ 套利失败 | 订单对ID:{} | YES和NO都未成交", "❌ Arbitrage failed | pair:{} | neither YES nor NO filled"),
+
+    // 风控
+    RiskBothFilled => ("两个订单都完全成交，无需恢复", "Both orders fully filled, no recovery needed"),
+    RiskNoneFilled => (
+        "❌ 套利失败 | YES和NO订单都未成交，可能原因：价格已变化或流动性不足",
+        "❌ Arbitrage failed | neither YES nor NO filled; price moved or liquidity insufficient"
+    ),
+    HedgeBuyMissing => (
+        "🛡️ 对冲：买入缺失腿 | {} 多 {} 份 | 保本价:{:.2} | 最高限价:{:.2}",
+        "🛡️ Hedge: buying missing leg | {} excess {} shares | breakeven:{:.2} | max price:{:.2}"
+    ),
+    HedgeMonitorExit => ("🛡️ 对冲：监测多出腿止盈止损 | {} 多 {} 份", "🛡️ Hedge: monitoring excess leg for take-profit/stop-loss | {} excess {} shares"),
+    PnlSummary => (
+        "📊 盈亏汇总 | 成对:{}份 | 锁定毛利:{:.4} USD | gas:{}笔 {:.4} USD | 净利润:{:.4} USD",
+        "📊 PnL summary | matched:{} shares | locked gross:{:.4} USD | gas:{} txs {:.4} USD | net:{:.4} USD"
+    ),
+    RejectSummary => ("🧾 窗口下单拒绝汇总 | 窗口:{} | 共{}次 | {}", "🧾 Order rejections this window | window:{} | total:{} | {}"),
+
+    // Merge
+    BatchMergeDone => ("✅ 批量 Merge 完成 | tx={} | 共 {} 个市场", "✅ Batch merge done | tx={} | {} markets"),
+    BatchMergeFailed => ("❌ 批量 Merge 失败", "❌ Batch merge failed"),
+    MergeRouteFallback => ("↪️ Merge 路径 {} 不可用，切换到 {}", "↪️ Merge route {} unavailable, switching to {}"),
+    MergeSubmittedDirect => ("✅ Merge 已提交（直接交易）| {} 笔 | tx: {}", "✅ Merge submitted (direct tx) | {} calls | tx: {}"),
+    MergeSubmittedRelayer => ("✅ Merge 已提交（Relayer）| {} 笔 | tx: {}", "✅ Merge submitted (relayer) | {} calls | tx: {}"),
+    MergeRateLimited => ("⏳ RPC 限速，等待 {}s 后重试", "⏳ RPC rate limited, retrying in {}s"),
+}
@@ -1,5 +1,8 @@
 //! poly_1hour_bot 库：供主程序和 binaries 复用的模块。
 
+pub mod chaos;
+pub mod i18n;
 pub mod merge;
 pub mod positions;
+pub mod subgraph;
 pub mod trial;
\ No newline at end of file
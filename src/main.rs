@@ -1,3 +1,4 @@
+mod commands;
 mod config;
 mod market;
 mod monitor;
@@ -5,11 +6,15 @@ mod risk;
 mod trading;
 mod utils;
 
+use poly_1hour_bot::chaos;
+use poly_1hour_bot::i18n::{self, Msg};
 use poly_1hour_bot::merge;
 use poly_1hour_bot::positions::{get_positions, Position};
+use poly_1hour_bot::subgraph::SubgraphClient;
+use poly_1hour_bot::tr;
 
 use anyhow::Result;
-use dashmap::DashMap;
+use dashmap::{DashMap, DashSet};
 use futures::StreamExt;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
@@ -19,15 +24,41 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 use tokio::time::sleep;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, error, info, warn, Instrument};
+use polymarket_client_sdk::clob::types::{OrderType, Side};
 use polymarket_client_sdk::types::{Address, B256, U256};
 
 use crate::config::Config;
+use crate::market::status::spawn_status_watcher;
 use crate::market::{MarketDiscoverer, MarketInfo, MarketScheduler};
-use crate::monitor::{ArbitrageDetector, OrderBookMonitor};
+use crate::market::ladder::StrikeLadder;
+use crate::market::rewards::RewardsBook;
+use crate::market::spot::SpotFeed;
+use crate::monitor::adaptive::{AdaptiveSpread, AdaptiveSpreadSettings};
+use crate::monitor::directional::{DirectionalSettings, DirectionalStrategy};
+use crate::monitor::fill_model::{FillModel, FillOutcome};
+use crate::monitor::liveness::{LivenessSettings, StreamWatchdog};
+use crate::monitor::sell_back::{SellBackDetector, SellBackSettings};
+use crate::monitor::sniping::{SnipeDetector, SnipeSettings};
+use crate::monitor::symbol_spread::SymbolSpreadLearner;
+use crate::monitor::{ArbitrageDetector, LadderDetector, OrderBookMonitor};
+use crate::risk::aging::{AgingSettings, PositionAging};
+use crate::risk::budget::Strategy;
+use crate::risk::dust::{DustSettings, DustSweeper};
+use crate::risk::merge_ledger::MergeLedger;
+use crate::risk::merge_verify;
 use crate::risk::positions::PositionTracker;
-use crate::risk::{HedgeMonitor, PositionBalancer, RiskManager};
-use crate::trading::TradingExecutor;
+use crate::risk::shared_ledger::SharedExposureLedger;
+use crate::risk::hedge_monitor::HedgeLadderParams;
+use crate::risk::{HedgeMonitor, PositionBalancer, RiskCheckpoint, RiskManager};
+use crate::trading::maker_fallback::MakerFallback;
+use crate::trading::order_store::{OrderStore, TrackedOrder};
+use crate::trading::queue::{QueueSettings, QueueTracker};
+use crate::trading::retry_queue::RetryQueue;
+use crate::trading::executor::ExecutionMode;
+use crate::trading::{BatchOrder, TradingExecutor};
+use crate::utils::events::{self, BotEvent};
+use crate::utils::latency::{self, Stage};
 
 /// 从持仓中筛出 **YES 和 NO 都持仓** 的 condition_id，仅这些市场才能 merge；单边持仓直接跳过。
 /// Data API 可能返回 outcome_index 0/1（0=Yes, 1=No）或 1/2（与 CTF index_set 一致），两种都支持。
@@ -84,6 +115,48 @@ fn merge_info_with_both_sides(positions: &[Position]) -> HashMap<B256, (U256, U2
         .collect()
 }
 
+/// 市场结束时间晚于调度窗口结束超过此宽限（秒）时视为跨越本窗口（较长周期的市场）
+const WINDOW_END_GRACE_SECS: i64 = 60;
+
+/// 市场是否随结束于 window_end 的调度窗口一同结束；跨越本窗口的较长周期市场不在收尾 / 撤单范围内
+fn ends_with_window(market: &MarketInfo, window_end: chrono::DateTime<chrono::Utc>) -> bool {
+    market.end_date <= window_end + chrono::Duration::seconds(WINDOW_END_GRACE_SECS)
+}
+
+/// 风控否决：计数；敞口超限时发布告警事件并 warn，其余只记 debug
+fn report_veto(veto: &crate::risk::approval::Veto, market: &str, kind: &str) {
+    utils::metrics::incr(veto.metric());
+    match veto {
+        crate::risk::approval::Veto::ExposureLimit { current, cost, max } => {
+            events::publish(BotEvent::exposure_breached(market, *current, *cost, *max));
+            warn!("⚠️ 风控拒绝执行{} | 市场:{} | {}", kind, market, veto);
+        }
+        _ => debug!("⏸️ 风控拒绝执行{} | 市场:{} | {}", kind, market, veto),
+    }
+}
+
+/// 成对下单结束后结算敞口预留：已提交（或超时、状态未知）按两腿下单数量计入敞口成本，
+/// 确定未下单则归还额度；实际成交与持仓由风险管理器和定期同步校正
+fn settle_reservation(
+    reservation: crate::risk::positions::ExposureReservation,
+    opp: &crate::monitor::arbitrage::ArbitrageOpportunity,
+    error: Option<&anyhow::Error>,
+) {
+    match error {
+        Some(e) if !crate::trading::executor::outcome_unknown(e) => reservation.release(),
+        _ => reservation.commit(&[
+            (opp.yes_token_id, opp.yes_ask_price, opp.yes_size),
+            (opp.no_token_id, opp.no_ask_price, opp.no_size),
+        ]),
+    }
+}
+
+/// 以低优先级查询持仓（定时维护任务用）：API 限速繁忙时请求被丢弃，按查询失败处理
+async fn get_positions_low_priority() -> Result<Vec<Position>> {
+    utils::rate_limit::acquire(utils::rate_limit::Priority::Low).await?;
+    get_positions().await
+}
+
 /// 定时 Merge 任务：每 interval_minutes 分钟拉取**持仓**，仅对 YES+NO 双边都持仓的市场 **串行**执行 merge_max，
 /// 单边持仓跳过；每笔之间间隔、对 RPC 限速做一次重试。Merge 成功后扣减 position_tracker 的持仓与敞口。
 /// 首次执行前短暂延迟，避免与订单簿监听的启动抢占同一 runtime，导致阻塞 stream。
@@ -92,7 +165,9 @@ async fn run_merge_task(
     proxy: Address,
     private_key: String,
     position_tracker: Arc<PositionTracker>,
+    merge_ledger: Arc<MergeLedger>,
     wind_down_in_progress: Arc<AtomicBool>,
+    retry_queue: Arc<RetryQueue>,
 ) {
     let interval = Duration::from_secs(interval_minutes * 60);
     /// 遇限速时等待后重试的时长（略大于 "retry in 10s"）
@@ -104,12 +179,16 @@ async fn run_merge_task(
     sleep(INITIAL_DELAY).await;
 
     loop {
+        if !utils::leader::is_leader() {
+            sleep(interval).await;
+            continue;
+        }
         if wind_down_in_progress.load(Ordering::Relaxed) {
             info!("收尾进行中，本轮回 merge 跳过");
             sleep(interval).await;
             continue;
         }
-        let (condition_ids, merge_info) = match get_positions().await {
+        let (condition_ids, merge_info) = match get_positions_low_priority().await {
             Ok(positions) => (
                 condition_ids_with_both_sides(&positions),
                 merge_info_with_both_sides(&positions),
@@ -131,6 +210,21 @@ async fn run_merge_task(
             );
         }
 
+        // 处置策略：回卖更划算的市场先卖回订单簿，卖完的不再 merge
+        let mut routed = Vec::with_capacity(condition_ids.len());
+        for condition_id in condition_ids {
+            let keep = match merge_info.get(&condition_id) {
+                Some((yes_token, no_token, amount)) => {
+                    crate::risk::disposal::route(condition_id, *yes_token, *no_token, *amount).await
+                }
+                None => true,
+            };
+            if keep {
+                routed.push(condition_id);
+            }
+        }
+        let condition_ids = routed;
+
         if !condition_ids.is_empty() {
             let mut result = merge::merge_max_batch(&condition_ids, proxy, &private_key, None).await;
             if result.is_err() {
@@ -143,8 +237,9 @@ async fn run_merge_task(
             }
             match result {
                 Ok((tx, merged)) => {
-                    info!("✅ 批量 Merge 完成 | tx={} | 共 {} 个市场", tx, merged.len());
+                    info!("{}", tr!(Msg::BatchMergeDone, tx, merged.len()));
                     for (condition_id, merge_amt) in &merged {
+                        retry_queue.resolve(MERGE_OP, &condition_id.to_string());
                         if let Some((yes_token, no_token, _)) = merge_info.get(condition_id) {
                             let merge_amt_decimal =
                                 Decimal::from(merge_amt.to::<u64>()) / dec!(1_000_000);
@@ -156,15 +251,21 @@ async fn run_merge_task(
                                 "💰 Merge 已扣减敞口 | condition_id={:#x} | 数量:{}",
                                 condition_id, merge_amt_decimal
                             );
+                            merge_ledger.attribute(*condition_id, merge_amt_decimal, &tx.to_string());
                         }
                     }
+                    events::publish(BotEvent::merged(&tx.to_string(), &merged));
+                    merge_verify::spawn(tx.to_string(), proxy, merged);
                 }
                 Err(e) => {
                     let msg = e.to_string();
                     if msg.contains("无可用份额") {
                         debug!("⏭️ 跳过 merge: 无可用份额");
                     } else {
-                        warn!(error = %e, "❌ 批量 Merge 失败");
+                        warn!(error = %e, "{}", tr!(Msg::BatchMergeFailed));
+                        for condition_id in &condition_ids {
+                            retry_queue.record_failure(MERGE_OP, &condition_id.to_string(), &msg);
+                        }
                     }
                 }
             }
@@ -174,27 +275,249 @@ async fn run_merge_task(
     }
 }
 
+/// 重试队列中 merge 操作的类型名
+const MERGE_OP: &str = "merge";
+
+/// Merge 重试任务：定期检查重试队列中已到期的 condition_id，仍为 YES+NO 双边持仓的批量重试 merge；
+/// 已不再双边持仓的（已被其他轮次 merge 或已卖出）直接移出队列。成功后同定时 Merge 一样扣减持仓与敞口。
+async fn run_merge_retry_task(
+    proxy: Address,
+    private_key: String,
+    position_tracker: Arc<PositionTracker>,
+    merge_ledger: Arc<MergeLedger>,
+    wind_down_in_progress: Arc<AtomicBool>,
+    retry_queue: Arc<RetryQueue>,
+) {
+    use std::str::FromStr;
+    /// 检查重试队列的间隔；实际重试时间由各条目的退避决定
+    const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+    loop {
+        sleep(POLL_INTERVAL).await;
+        let due = retry_queue.due(MERGE_OP);
+        if due.is_empty() || wind_down_in_progress.load(Ordering::Relaxed) || !utils::leader::is_leader() {
+            continue;
+        }
+        let positions = match get_positions_low_priority().await {
+            Ok(positions) => positions,
+            Err(e) => {
+                warn!(error = %e, "❌ 获取持仓失败，跳过本轮 merge 重试");
+                continue;
+            }
+        };
+        let mergeable: HashSet<B256> = condition_ids_with_both_sides(&positions).into_iter().collect();
+        let merge_info = merge_info_with_both_sides(&positions);
+        let mut condition_ids = Vec::new();
+        for key in &due {
+            match B256::from_str(key) {
+                Ok(condition_id) if mergeable.contains(&condition_id) => condition_ids.push(condition_id),
+                _ => retry_queue.resolve(MERGE_OP, key),
+            }
+        }
+        if condition_ids.is_empty() {
+            continue;
+        }
+
+        info!("🔁 重试 Merge | 共 {} 个市场", condition_ids.len());
+        match merge::merge_max_batch(&condition_ids, proxy, &private_key, None).await {
+            Ok((tx, merged)) => {
+                info!("✅ 重试 Merge 完成 | tx={} | 共 {} 个市场", tx, merged.len());
+                // 只有实际 merge 的市场算已解决，其余记一次失败，继续退避重试或进入死信
+                let merged_ids: HashSet<B256> = merged.iter().map(|(condition_id, _)| *condition_id).collect();
+                for condition_id in &condition_ids {
+                    if merged_ids.contains(condition_id) {
+                        retry_queue.resolve(MERGE_OP, &condition_id.to_string());
+                    } else {
+                        retry_queue.record_failure(MERGE_OP, &condition_id.to_string(), "本次批量 merge 未包含该市场");
+                    }
+                }
+                for (condition_id, merge_amt) in &merged {
+                    if let Some((yes_token, no_token, _)) = merge_info.get(condition_id) {
+                        let merge_amt_decimal = Decimal::from(merge_amt.to::<u64>()) / dec!(1_000_000);
+                        position_tracker.update_exposure_cost(*yes_token, dec!(0), -merge_amt_decimal);
+                        position_tracker.update_exposure_cost(*no_token, dec!(0), -merge_amt_decimal);
+                        position_tracker.update_position(*yes_token, -merge_amt_decimal);
+                        position_tracker.update_position(*no_token, -merge_amt_decimal);
+                        info!(
+                            "💰 重试 Merge 已扣减敞口 | condition_id={:#x} | 数量:{}",
+                            condition_id, merge_amt_decimal
+                        );
+                        merge_ledger.attribute(*condition_id, merge_amt_decimal, &tx.to_string());
+                    }
+                }
+                events::publish(BotEvent::merged(&tx.to_string(), &merged));
+                merge_verify::spawn(tx.to_string(), proxy, merged);
+            }
+            Err(e) => {
+                let msg = e.to_string();
+                for condition_id in &condition_ids {
+                    retry_queue.record_failure(MERGE_OP, &condition_id.to_string(), &msg);
+                }
+            }
+        }
+    }
+}
+
+/// 成对成交后立即 merge（MERGE_ON_PAIR_FILL）：订单对双边都有成交时，不等定时器，立即对该市场执行 merge，
+/// 数秒内释放资金。同一市场同时只有一个 merge 在执行；失败进入重试队列。
+struct ImmediateMerger {
+    proxy: Address,
+    private_key: String,
+    position_tracker: Arc<PositionTracker>,
+    merge_ledger: Arc<MergeLedger>,
+    retry_queue: Arc<RetryQueue>,
+    wind_down_in_progress: Arc<AtomicBool>,
+    in_flight: DashSet<B256>,
+}
+
+impl ImmediateMerger {
+    fn trigger(self: &Arc<Self>, condition_id: B256) {
+        if self.wind_down_in_progress.load(Ordering::Relaxed) || !self.in_flight.insert(condition_id) {
+            return;
+        }
+        let this = self.clone();
+        tokio::spawn(
+            async move {
+                this.merge(condition_id).await;
+                this.in_flight.remove(&condition_id);
+            }
+            .in_current_span(),
+        );
+    }
+
+    #[tracing::instrument(name = "immediate_merge", skip(self))]
+    async fn merge(&self, condition_id: B256) {
+        let merge_info = match get_positions().await {
+            Ok(positions) => merge_info_with_both_sides(&positions),
+            Err(e) => {
+                warn!(error = %e, "立即 Merge：获取持仓失败，交由定时 Merge 处理");
+                return;
+            }
+        };
+        let Some((yes_token, no_token, amount)) = merge_info.get(&condition_id).copied() else {
+            debug!("立即 Merge：持仓尚未同步为双边，交由定时 Merge 处理 | condition_id={:#x}", condition_id);
+            return;
+        };
+        if !crate::risk::disposal::route(condition_id, yes_token, no_token, amount).await {
+            return;
+        }
+        debug!(
+            "立即 Merge：账本中跨订单对合计可 merge {} 份 | condition_id={:#x}",
+            self.merge_ledger.mergeable(condition_id),
+            condition_id
+        );
+        match merge::merge_max_batch(&[condition_id], self.proxy, &self.private_key, None).await {
+            Ok((tx, merged)) => {
+                self.retry_queue.resolve(MERGE_OP, &condition_id.to_string());
+                for (_, merge_amt) in &merged {
+                    let merge_amt_decimal = Decimal::from(merge_amt.to::<u64>()) / dec!(1_000_000);
+                    self.position_tracker.update_exposure_cost(yes_token, dec!(0), -merge_amt_decimal);
+                    self.position_tracker.update_exposure_cost(no_token, dec!(0), -merge_amt_decimal);
+                    self.position_tracker.update_position(yes_token, -merge_amt_decimal);
+                    self.position_tracker.update_position(no_token, -merge_amt_decimal);
+                    info!(
+                        "⚡💰 成交后立即 Merge 完成 | condition_id={:#x} | 数量:{} | tx={}",
+                        condition_id, merge_amt_decimal, tx
+                    );
+                    self.merge_ledger.attribute(condition_id, merge_amt_decimal, &tx.to_string());
+                }
+                events::publish(BotEvent::merged(&tx.to_string(), &merged));
+                merge_verify::spawn(tx.to_string(), self.proxy, merged);
+            }
+            Err(e) => {
+                let msg = e.to_string();
+                if msg.contains("无可用份额") {
+                    debug!("立即 Merge：无可用份额");
+                } else {
+                    warn!(error = %e, "立即 Merge 失败，加入重试队列");
+                    self.retry_queue.record_failure(MERGE_OP, &condition_id.to_string(), &msg);
+                }
+            }
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // 初始化日志
     utils::logger::init_logger()?;
 
-    tracing::info!("Polymarket 1小时套利机器人启动");
+    tracing::info!("{}", tr!(Msg::Startup));
 
     // 许可证校验：须存在有效 license.key，删除许可证将无法运行
     poly_1hour_bot::trial::check_license()?;
 
+    // 子命令（如 latency）：诊断工具，执行完即退出，不进入交易主循环
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some((command, rest)) = args.split_first() {
+        return commands::run(command, rest).await;
+    }
+
     // 加载配置
     let config = Config::from_env()?;
-    tracing::info!("配置加载完成");
+    i18n::set_locale(config.log_locale);
+    tracing::info!("{}", tr!(Msg::ConfigLoaded));
+    market::clock::init(config.market_timezone);
+    market::window::init(&config.window_durations);
+    utils::state_crypto::init_from_env()?;
+    utils::rate_limit::init(config.api_rate_limit);
+    utils::journal::init(&config.journal_path);
+    utils::storage::init(&config.storage_url, &config.instance_id).await?;
+    utils::book_recorder::init(&config.book_record_path);
+    chaos::log_active();
+    crate::monitor::shadow::init(
+        &config.shadow,
+        config.min_profit_threshold,
+        config.arbitrage_execution_spread,
+        config.max_order_size_usdc,
+        config.risk_max_exposure_usdc,
+    );
+    crate::risk::ab_test::init(
+        &config.ab_test,
+        config.risk_max_exposure_usdc,
+        config.min_profit_threshold,
+        config.arbitrage_execution_spread,
+    );
+    utils::redis_sink::spawn(&config.redis_url, &config.redis_channel_prefix);
+    utils::stream_sink::spawn(config.stream_sink.clone());
+    utils::webhooks::spawn(config.webhooks.clone());
+    if config.merge_verify_proceeds {
+        merge_verify::init(Duration::from_secs(config.merge_verify_timeout_secs));
+    }
+    tracing::info!(timezone = %config.market_timezone, "市场时区");
 
     // 初始化组件（暂时不使用，主循环已禁用）
-    let _discoverer = MarketDiscoverer::new(config.crypto_symbols.clone());
-    let _scheduler = MarketScheduler::new(_discoverer, config.market_refresh_advance_secs);
+    let _discoverer = MarketDiscoverer::new(
+        &config.endpoints.gamma,
+        config.market_series.clone(),
+        &config.market_cache_path,
+    )
+    .with_discovery(config.discovery_mode, config.discovery_tags.clone());
+    let _scheduler = MarketScheduler::new(
+        _discoverer,
+        config.max_refresh_advance_secs(),
+        config.discovery_retry_max_backoff_secs,
+        config.discovery_max_retries_per_window,
+    );
     let _detector = ArbitrageDetector::new(config.min_profit_threshold);
+
+    // 启动就绪检查：与 check-config 子命令相同，严格模式下未就绪直接退出，避免窗口中途才失败
+    if config.startup_preflight {
+        info!("正在执行启动就绪检查...");
+        let report = commands::check_config::preflight(&config).await;
+        report.log();
+        let blockers = report.strict_blockers();
+        if config.startup_strict && !blockers.is_empty() {
+            error!(blockers = ?blockers, "严格模式：启动就绪检查未通过，拒绝启动");
+            return Err(anyhow::anyhow!("启动就绪检查未通过: {}", blockers.join(", ")));
+        }
+    }
     
     // 验证私钥格式
     info!("正在验证私钥格式...");
+    if config.private_key.trim().is_empty() {
+        anyhow::bail!("POLYMARKET_PRIVATE_KEY 未设置；只想观察行情与套利机会请使用 observe 子命令");
+    }
     use alloy::signers::local::LocalSigner;
     use polymarket_client_sdk::POLYGON;
     use std::str::FromStr;
@@ -204,27 +527,35 @@ async fn main() -> Result<()> {
     info!("私钥格式验证通过");
     
     // 初始化交易执行器（需要认证）
-    info!("正在初始化交易执行器（需要API认证）...");
+    info!("{}", tr!(Msg::InitExecutor));
     if let Some(ref proxy) = config.proxy_address {
         info!(proxy_address = %proxy, "使用Proxy签名类型（Email/Magic或Browser Wallet）");
     } else {
         info!("使用EOA签名类型（直接交易）");
     }
     info!("注意：如果看到'Could not create api key'警告，这是正常的。SDK会先尝试创建新API key，失败后会自动使用派生方式，认证仍然会成功。");
+    // 使用上次轮换后持久化的 API Key nonce（未轮换过时为 0）
+    let api_key_nonce = crate::trading::credentials::load_nonce(&config.api_key_rotation.nonce_path);
     let executor = match TradingExecutor::new(
+        &config.endpoints.clob_rest,
         config.private_key.clone(),
-        config.max_order_size_usdc,
+        config.max_order_size_ceiling(),
         config.proxy_address,
-        config.slippage,
+        &config.slippage,
         config.gtd_expiration_secs,
         config.arbitrage_order_type.clone(),
+        config.leg_submission,
+        config.leg_timeout(),
+        config.child_order_max_notional_usdc,
+        config.iceberg_display_size,
+        api_key_nonce,
     ).await {
         Ok(exec) => {
-            info!("交易执行器认证成功（可能使用了派生API key）");
+            info!("{}", tr!(Msg::ExecutorAuthOk));
             Arc::new(exec)
         }
         Err(e) => {
-            error!(error = %e, "交易执行器认证失败！无法继续运行。");
+            error!(error = %e, "{}", tr!(Msg::ExecutorAuthFailed));
             error!("请检查：");
             error!("  1. POLYMARKET_PRIVATE_KEY 环境变量是否正确设置");
             error!("  2. 私钥格式是否正确（应该是64字符的十六进制字符串，不带0x前缀）");
@@ -234,8 +565,16 @@ async fn main() -> Result<()> {
         }
     };
 
+    crate::trading::reject_policy::init(&config.reject_policy);
+    crate::trading::allowance::init(
+        config.allowance_auto_repair,
+        &config.private_key,
+        config.proxy_address,
+        Duration::from_secs(config.allowance_repair_cooldown_secs),
+    );
+
     // 创建CLOB客户端用于风险管理（需要认证）
-    info!("正在初始化风险管理客户端（需要API认证）...");
+    info!("{}", tr!(Msg::InitRiskClient));
     use alloy::signers::Signer;
     use polymarket_client_sdk::clob::{Client, Config as ClobConfig};
     use polymarket_client_sdk::clob::types::SignatureType;
@@ -243,8 +582,9 @@ async fn main() -> Result<()> {
     let signer_for_risk = LocalSigner::from_str(&config.private_key)?
         .with_chain_id(Some(POLYGON));
     let clob_config = ClobConfig::builder().use_server_time(true).build();
-    let mut auth_builder_risk = Client::new("https://clob.polymarket.com", clob_config)?
-        .authentication_builder(&signer_for_risk);
+    let mut auth_builder_risk = Client::new(&config.endpoints.clob_rest, clob_config)?
+        .authentication_builder(&signer_for_risk)
+        .nonce(api_key_nonce);
     
     // 如果提供了proxy_address，设置funder和signature_type
     if let Some(funder) = config.proxy_address {
@@ -255,11 +595,11 @@ async fn main() -> Result<()> {
     
     let clob_client = match auth_builder_risk.authenticate().await {
         Ok(client) => {
-            info!("风险管理客户端认证成功（可能使用了派生API key）");
-            client
+            info!("{}", tr!(Msg::RiskAuthOk));
+            crate::trading::credentials::SharedClient::new(client)
         }
         Err(e) => {
-            error!(error = %e, "风险管理客户端认证失败！无法继续运行。");
+            error!(error = %e, "{}", tr!(Msg::RiskAuthFailed));
             error!("请检查：");
             error!("  1. POLYMARKET_PRIVATE_KEY 环境变量是否正确设置");
             error!("  2. 私钥格式是否正确");
@@ -269,23 +609,112 @@ async fn main() -> Result<()> {
         }
     };
     
-    let _risk_manager = Arc::new(RiskManager::new(clob_client.clone(), &config));
+    let recovery_strategy = crate::risk::recovery::from_config(&config);
+    info!(strategy = recovery_strategy.name(), "恢复策略");
+    let _risk_manager = Arc::new(RiskManager::new(clob_client.clone(), &config, recovery_strategy));
+    for strategy in Strategy::ALL {
+        if let Some(limit) = config.risk_budgets.limit(strategy) {
+            info!("💼 策略独立额度 | 策略:{} | 额度:{} USD", strategy.as_str(), limit);
+        }
+    }
+    if !config.shared_ledger_url.trim().is_empty() {
+        let max_total = if config.shared_max_exposure_usdc > 0.0 {
+            config.shared_max_exposure_usdc
+        } else {
+            config.risk_max_exposure_usdc
+        };
+        let ledger = SharedExposureLedger::connect(
+            &config.shared_ledger_url,
+            &config.shared_ledger_key,
+            &config.instance_id,
+            Decimal::try_from(max_total).unwrap_or(dec!(1000.0)),
+        )
+        .await?;
+        let ledger = Arc::new(ledger);
+        ledger.spawn_sync(_risk_manager.position_tracker());
+        _risk_manager.position_tracker().attach_shared_ledger(ledger);
+    }
+
+    // 挂单登记（maker 兜底单、启动时接管的挂单）：到期自动撤单，随检查点写盘
+    let order_store = Arc::new(OrderStore::new());
+
+    // 从检查点恢复风控状态：同一窗口内重启沿用敞口，跨窗口只恢复持仓与订单对
+    let checkpoint_path = std::path::PathBuf::from(&config.checkpoint_path);
+    let mut restored_window: Option<i64> = None;
+    if config.checkpoint_interval_secs > 0 {
+        match RiskCheckpoint::load_persisted(&checkpoint_path).await {
+            Ok(Some(checkpoint)) => {
+                let current_window = MarketDiscoverer::calculate_current_window_timestamp(chrono::Utc::now());
+                let same_window = checkpoint.window_timestamp == current_window;
+                match checkpoint.restore_into(&_risk_manager, &order_store, same_window) {
+                    Ok(()) if same_window => restored_window = Some(current_window),
+                    Ok(()) => info!("检查点属于上一窗口，敞口按新一轮重新累计"),
+                    Err(e) => warn!(error = %e, "检查点解析失败，从 API 重建状态"),
+                }
+            }
+            Ok(None) => info!("未找到可用检查点，从 API 重建状态"),
+            Err(e) => warn!(error = %e, path = %checkpoint_path.display(), "读取检查点失败，从 API 重建状态"),
+        }
+    }
     
-    // 创建对冲监测器（传入PositionTracker的Arc引用以更新风险敞口）
-    // 对冲策略已暂时关闭，但保留hedge_monitor变量以备将来使用
+    // 主备租约：备机不交易；接管时从（共享的）检查点恢复主机最近的状态
+    if let Some(mut leader_rx) = utils::leader::start(
+        &config.leader_lock_path,
+        &config.instance_id,
+        Duration::from_secs(config.leader_lease_secs.max(1)),
+    ) {
+        let risk_manager_ha = _risk_manager.clone();
+        let order_store_ha = order_store.clone();
+        let path = checkpoint_path.clone();
+        let checkpoint_enabled = config.checkpoint_interval_secs > 0;
+        tokio::spawn(async move {
+            while leader_rx.changed().await.is_ok() {
+                if !*leader_rx.borrow_and_update() || !checkpoint_enabled {
+                    continue;
+                }
+                match RiskCheckpoint::load_persisted(&path).await {
+                    Ok(Some(checkpoint)) => {
+                        let current_window = MarketDiscoverer::calculate_current_window_timestamp(chrono::Utc::now());
+                        let same_window = checkpoint.window_timestamp == current_window;
+                        match checkpoint.restore_into(&risk_manager_ha, &order_store_ha, same_window) {
+                            Ok(()) => info!("👑 接管：已从检查点恢复主实例状态"),
+                            Err(e) => warn!(error = %e, "接管：检查点解析失败，沿用本地状态"),
+                        }
+                    }
+                    Ok(None) => info!("接管：未找到可用检查点，沿用本地状态"),
+                    Err(e) => warn!(error = %e, "接管：读取检查点失败，沿用本地状态"),
+                }
+            }
+        });
+    }
+
+    // 创建对冲监测器（传入PositionTracker的Arc引用以更新风险敞口），按 HEDGE_POLICY 处理单边/不平衡成交
     let position_tracker = _risk_manager.position_tracker();
-    let _hedge_monitor = HedgeMonitor::new(
+    let hedge_monitor = Arc::new(HedgeMonitor::new(
         clob_client.clone(),
         config.private_key.clone(),
         config.proxy_address.clone(),
         position_tracker,
+        HedgeLadderParams {
+            passive: Duration::from_secs(config.hedge_passive_secs),
+            improve_interval: Duration::from_secs(config.hedge_improve_interval_secs),
+            improve_tick: Decimal::try_from(config.hedge_improve_tick).unwrap_or(dec!(0.01)),
+            market_order_before_end: Duration::from_secs(config.hedge_market_order_before_end_secs),
+        },
+    ));
+    info!(policy = config.hedge_policy.as_str(), "对冲策略");
+    info!(
+        mode = config.execution_mode.as_str(),
+        legs = config.leg_submission.as_str(),
+        timeout_ms = config.leg_timeout().as_millis() as u64,
+        "套利执行方式"
     );
 
     // 验证认证是否真的成功 - 尝试一个简单的API调用
     info!("正在验证认证状态（通过API调用测试）...");
     match executor.verify_authentication().await {
         Ok(_) => {
-            info!("✅ 认证验证成功，API调用正常");
+            info!("{}", tr!(Msg::AuthVerifyOk));
         }
         Err(e) => {
             error!(error = %e, "❌ 认证验证失败！虽然authenticate()没有报错，但API调用失败。");
@@ -299,17 +728,77 @@ async fn main() -> Result<()> {
         }
     }
 
-    info!("✅ 所有组件初始化完成，认证验证通过");
+    info!("{}", tr!(Msg::AllReady));
 
-    // RPC 健康检查组件（端点探测、熔断、指标）
-    let rpc_cfg = rpc_check::CheckConfig::builder()
-        .timeout(Duration::from_secs(5))
-        .build();
-    let _rpc_checker = rpc_check::RpcChecker::new(rpc_cfg);
-    let _rpc_circuit = rpc_check::CircuitBreaker::new();
-    let _rpc_metrics = rpc_check::Metrics::new();
-    let _ = _rpc_checker.validate_endpoint("https://clob.polymarket.com");
-    let _ = _rpc_checker.validate_endpoint("https://gamma-api.polymarket.com");
+    // 重复实例保护：同一账户已有另一个实例在交易时拒绝启动（主备与共享敞口账本是有意多实例，不检查）
+    if config.leader_lock_path.trim().is_empty() && config.shared_ledger_url.trim().is_empty() {
+        let funder = config
+            .proxy_address
+            .unwrap_or_else(|| LocalSigner::from_str(&config.private_key).map(|s| s.address()).unwrap_or_default());
+        utils::instance_guard::acquire(&config.instance_guard, &format!("{:#x}", funder), &config.instance_id, &executor)
+            .await?;
+    }
+
+    // 启动挂单对账：接管已恢复订单对与挂单登记中的挂单，未知挂单按配置撤销（备机不处理）
+    order_store.spawn_sweeper(executor.clone());
+    if utils::leader::is_leader() {
+        let window_end = MarketDiscoverer::window_end_timestamp(MarketDiscoverer::calculate_current_window_timestamp(
+            chrono::Utc::now(),
+        ));
+        if let Err(e) = crate::trading::startup_orders::reconcile(
+            &executor,
+            &_risk_manager,
+            &order_store,
+            chrono::DateTime::from_timestamp(window_end, 0).unwrap_or_else(chrono::Utc::now),
+            config.startup_cancel_unknown_orders,
+        )
+        .await
+        {
+            warn!(error = %e, "启动挂单对账失败");
+        }
+    }
+
+    // API 凭证轮换：定时 / SIGHUP 触发，执行器与风控客户端一起切换到新 Key（备机不轮换）
+    Arc::new(
+        crate::trading::credentials::Rotator::new(
+            &config.endpoints.clob_rest,
+            &config.private_key,
+            config.proxy_address,
+            config.api_key_rotation.clone(),
+            api_key_nonce,
+        )
+        .add(executor.shared_client(), false)
+        .add(clob_client.clone(), true),
+    )
+    .spawn();
+
+    // CLOB 连接预热与保活：先建立热连接，再定时发轻量请求，避免连接池空闲回收后首单重新握手
+    if let Err(e) = executor.warm_up_connections(config.http_warmup_connections).await {
+        warn!(error = %e, "CLOB 连接预热失败，首单可能需要重新握手");
+    }
+    let keepalive_interval = config.http_keepalive_interval_secs;
+    if keepalive_interval > 0 {
+        let executor_keepalive = executor.clone();
+        let warmup_connections = config.http_warmup_connections;
+        tokio::spawn(async move {
+            let interval = Duration::from_secs(keepalive_interval);
+            loop {
+                sleep(interval).await;
+                if let Err(e) = executor_keepalive.warm_up_connections(warmup_connections).await {
+                    debug!(error = %e, "CLOB 连接保活请求失败，下次循环重试");
+                }
+            }
+        });
+        info!(
+            interval_secs = keepalive_interval,
+            connections = warmup_connections,
+            "已启动 CLOB 连接保活任务，每 {} 秒保持 {} 条热连接",
+            keepalive_interval,
+            warmup_connections
+        );
+    } else {
+        info!("CLOB 连接保活未启用（HTTP_KEEPALIVE_INTERVAL_SECS=0）");
+    }
 
     // 创建仓位平衡器
     let position_balancer = Arc::new(PositionBalancer::new(
@@ -322,14 +811,32 @@ async fn main() -> Result<()> {
     let position_sync_interval = config.position_sync_interval_secs;
     if position_sync_interval > 0 {
         let position_tracker_sync = _risk_manager.position_tracker();
+        let risk_manager_sync = _risk_manager.clone();
+        let executor_sync = executor.clone();
+        // 启用子图时同步后与链上余额对账
+        let reconcile = match (config.subgraph_enabled, config.proxy_address) {
+            (true, Some(proxy)) => Some((SubgraphClient::new(config.subgraph_urls.clone()), proxy)),
+            _ => None,
+        };
         tokio::spawn(async move {
             let interval = Duration::from_secs(position_sync_interval);
+            // 同步连续失败期间可能漏掉成交：恢复后补查
+            let mut sync_failing = false;
             loop {
                 match position_tracker_sync.sync_from_api().await {
-                    Ok(_) => {
+                    Ok(positions) => {
+                        if std::mem::take(&mut sync_failing) {
+                            risk_manager_sync.backfill_fills(&executor_sync, "持仓同步恢复").await;
+                        }
                         // 持仓信息已在 sync_from_api 中打印
+                        if let Some((subgraph, proxy)) = reconcile.as_ref() {
+                            if let Err(e) = position_tracker_sync.reconcile_with_subgraph(&positions, subgraph, *proxy).await {
+                                debug!(error = %e, "子图持仓对账失败");
+                            }
+                        }
                     }
                     Err(e) => {
+                        sync_failing = true;
                         warn!(error = %e, "持仓同步失败，将在下次循环重试");
                     }
                 }
@@ -358,12 +865,70 @@ async fn main() -> Result<()> {
         info!("定时仓位平衡未启用（POSITION_BALANCE_INTERVAL_SECS=0）");
     }
 
+    // 盈亏统计：定期把 merge 等链上交易的 gas 花费按 POL 价格折算计入，并按间隔打印净利润汇总
+    {
+        let pnl = _risk_manager.pnl();
+        let report_interval = config.pnl_report_interval_secs;
+        tokio::spawn(async move {
+            const GAS_DRAIN_INTERVAL: Duration = Duration::from_secs(30);
+            let mut last_report = Instant::now();
+            loop {
+                sleep(GAS_DRAIN_INTERVAL).await;
+                pnl.record_gas_spends(merge::take_gas_spends()).await;
+                if report_interval > 0 && last_report.elapsed() >= Duration::from_secs(report_interval) {
+                    pnl.log_summary();
+                    last_report = Instant::now();
+                }
+            }
+        });
+    }
+
+    // 资金利用率：定期采样敞口与敞口上限，按时间加权累计（随盈亏汇总一起报告）
+    {
+        let pnl = _risk_manager.pnl();
+        let position_tracker = _risk_manager.position_tracker().clone();
+        tokio::spawn(async move {
+            const CAPITAL_SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+            loop {
+                pnl.sample_capital(position_tracker.calculate_exposure(), position_tracker.max_exposure());
+                sleep(CAPITAL_SAMPLE_INTERVAL).await;
+            }
+        });
+    }
+
     // 收尾进行中标志：定时 merge 会检查并跳过，避免与收尾 merge 竞争
     let wind_down_in_progress = Arc::new(AtomicBool::new(false));
 
-    // 两次套利交易之间的最小间隔
-    const MIN_TRADE_INTERVAL: Duration = Duration::from_secs(3);
-    let last_trade_time: Arc<Mutex<Option<Instant>>> = Arc::new(Mutex::new(None));
+    // 失败 merge 的持久化重试队列（定时 Merge 与收尾 Merge 共用）
+    let merge_retry_queue = Arc::new(RetryQueue::load(
+        &config.merge_retry_queue_path,
+        config.merge_retry_max_attempts,
+        Duration::from_secs(config.merge_retry_base_backoff_secs),
+        Duration::from_secs(config.merge_retry_max_backoff_secs),
+    ));
+    if let Some(proxy) = config.proxy_address {
+        let private_key = config.private_key.clone();
+        let position_tracker = _risk_manager.position_tracker().clone();
+        let merge_ledger = _risk_manager.merge_ledger();
+        let wind_down_flag = wind_down_in_progress.clone();
+        let retry_queue = merge_retry_queue.clone();
+        tokio::spawn(async move {
+            run_merge_retry_task(proxy, private_key, position_tracker, merge_ledger, wind_down_flag, retry_queue).await;
+        });
+    }
+
+    // 成对持仓处置策略：merge 前比较 merge 与回卖的所得（立即 Merge 与定时 Merge 共用）
+    if config.disposal_policy_enabled {
+        crate::risk::disposal::init(
+            crate::risk::disposal::DisposalSettings {
+                merge_gas_usd: Decimal::try_from(config.disposal_merge_gas_usd).unwrap_or(dec!(0.02)),
+                fee_per_share: Decimal::try_from(config.sell_back_fee_per_share).unwrap_or(dec!(0.01)),
+                min_advantage: Decimal::try_from(config.disposal_min_advantage_usd).unwrap_or(dec!(0.05)),
+            },
+            executor.clone(),
+            _risk_manager.position_tracker(),
+        );
+    }
 
     // 定时 Merge：每 N 分钟根据持仓执行 merge，仅对 YES+NO 双边都持仓的市场
     let merge_interval = config.merge_interval_minutes;
@@ -371,9 +936,11 @@ async fn main() -> Result<()> {
         if let Some(proxy) = config.proxy_address {
             let private_key = config.private_key.clone();
             let position_tracker = _risk_manager.position_tracker().clone();
+            let merge_ledger = _risk_manager.merge_ledger();
             let wind_down_flag = wind_down_in_progress.clone();
+            let retry_queue = merge_retry_queue.clone();
             tokio::spawn(async move {
-                run_merge_task(merge_interval, proxy, private_key, position_tracker, wind_down_flag).await;
+                run_merge_task(merge_interval, proxy, private_key, position_tracker, merge_ledger, wind_down_flag, retry_queue).await;
             });
             info!(
                 interval_minutes = merge_interval,
@@ -387,22 +954,389 @@ async fn main() -> Result<()> {
         info!("定时 Merge 未启用（MERGE_INTERVAL_MINUTES=0），如需启用请在 .env 中设置 MERGE_INTERVAL_MINUTES 为正数，例如 5 或 15");
     }
 
+    // 定时写检查点
+    if config.checkpoint_interval_secs > 0 {
+        let risk_manager_cp = _risk_manager.clone();
+        let order_store_cp = order_store.clone();
+        let path = checkpoint_path.clone();
+        let interval_secs = config.checkpoint_interval_secs;
+        tokio::spawn(async move {
+            let mut timer = tokio::time::interval(Duration::from_secs(interval_secs));
+            timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                timer.tick().await;
+                // 备机不写检查点，避免覆盖主机的状态
+                if !utils::leader::is_leader() {
+                    continue;
+                }
+                let window = MarketDiscoverer::calculate_current_window_timestamp(chrono::Utc::now());
+                if let Err(e) = RiskCheckpoint::capture(&risk_manager_cp, &order_store_cp, window).persist(&path).await {
+                    warn!(error = %e, "写检查点失败，下次循环重试");
+                }
+            }
+        });
+        info!(
+            interval_secs = interval_secs,
+            path = %checkpoint_path.display(),
+            "已启动风控状态检查点任务，每 {} 秒写盘",
+            interval_secs
+        );
+    } else {
+        info!("风控状态检查点未启用（CHECKPOINT_INTERVAL_SECS=0）");
+    }
+
+    // 收到 Ctrl+C：启用检查点时（且为主机）先写最后一次检查点，再刷新遥测并退出
+    {
+        let risk_manager_cp = _risk_manager.clone();
+        let order_store_cp = order_store.clone();
+        let path = checkpoint_path.clone();
+        let checkpoint_enabled = config.checkpoint_interval_secs > 0;
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                if checkpoint_enabled && utils::leader::is_leader() {
+                    let window = MarketDiscoverer::calculate_current_window_timestamp(chrono::Utc::now());
+                    match RiskCheckpoint::capture(&risk_manager_cp, &order_store_cp, window).persist(&path).await {
+                        Ok(()) => info!(path = %path.display(), "{}", tr!(Msg::ShutdownCheckpointOk)),
+                        Err(e) => error!(error = %e, "{}", tr!(Msg::ShutdownCheckpointFailed)),
+                    }
+                }
+                utils::telemetry::shutdown();
+                std::process::exit(0);
+            }
+        });
+    }
+
+    // 终端 TUI 监控面板
+    if config.tui_enabled {
+        match utils::tui::spawn(_risk_manager.clone(), Duration::from_millis(config.tui_refresh_ms.max(50))) {
+            Ok(()) => info!("已启动 TUI 监控面板，日志写入日志文件"),
+            Err(e) => warn!(error = %e, "启动 TUI 失败，继续以日志模式运行"),
+        }
+    }
+
+    // 控制/调试接口（需 token）
+    if let Err(e) = utils::control::spawn(
+        &config.control_api_addr,
+        &config.control_api_token,
+        _risk_manager.clone(),
+        &config,
+    ) {
+        warn!(error = %e, "控制接口未启动");
+    }
+
+    let main_loop = MainLoopContext {
+        config: config.clone(),
+        scheduler: _scheduler,
+        detector: _detector,
+        executor,
+        risk_manager: _risk_manager,
+        hedge_monitor,
+        position_balancer,
+        wind_down_in_progress,
+        merge_retry_queue,
+        order_store,
+        background: tokio::runtime::Handle::current(),
+        restored_window,
+    };
+
+    // 检测热路径可选在专用线程的 current-thread runtime 上运行，与日志、merge、HTTP 等后台任务隔离，降低调度抖动
+    if config.detection_dedicated_thread {
+        info!("检测热路径运行在专用线程（current-thread runtime）");
+        let detection_thread = std::thread::Builder::new()
+            .name("detection".to_string())
+            .spawn(move || -> Result<()> {
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()?;
+                runtime.block_on(run_main_loop(main_loop))
+            })?;
+        // 主 runtime 继续驱动后台任务，这里只等待检测线程退出
+        tokio::task::spawn_blocking(move || detection_thread.join())
+            .await?
+            .map_err(|_| anyhow::anyhow!("检测线程异常退出"))?
+    } else {
+        run_main_loop(main_loop).await
+    }
+}
+
+/// 订单簿流重连或持仓同步恢复后，在后台补查断线期间订单对各腿的成交
+fn spawn_fill_backfill(
+    handle: &tokio::runtime::Handle,
+    risk_manager: &Arc<RiskManager>,
+    executor: &Arc<TradingExecutor>,
+    reason: &'static str,
+) {
+    let risk_manager = risk_manager.clone();
+    let executor = executor.clone();
+    handle.spawn(async move {
+        risk_manager.backfill_fills(&executor, reason).await;
+    });
+}
+
+/// 主循环（订单簿监控 + 套利检测 + 下单）所需的共享组件
+struct MainLoopContext {
+    config: Config,
+    scheduler: MarketScheduler,
+    detector: ArbitrageDetector,
+    executor: Arc<TradingExecutor>,
+    risk_manager: Arc<RiskManager>,
+    hedge_monitor: Arc<HedgeMonitor>,
+    position_balancer: Arc<PositionBalancer>,
+    wind_down_in_progress: Arc<AtomicBool>,
+    merge_retry_queue: Arc<RetryQueue>,
+    order_store: Arc<OrderStore>,
+    /// 收尾等慢任务使用的 runtime：启用专用检测线程时为主 runtime，避免占用检测线程
+    background: tokio::runtime::Handle,
+    /// 从检查点恢复了敞口的窗口；该窗口首轮不重置敞口
+    restored_window: Option<i64>,
+}
+
+/// 主循环：按窗口发现市场、订阅订单簿、检测套利并下单，窗口切换后进入下一轮
+async fn run_main_loop(ctx: MainLoopContext) -> Result<()> {
+    let MainLoopContext {
+        config,
+        scheduler: _scheduler,
+        detector: _detector,
+        executor,
+        risk_manager: _risk_manager,
+        hedge_monitor,
+        position_balancer,
+        wind_down_in_progress,
+        merge_retry_queue,
+        order_store,
+        background,
+        mut restored_window,
+    } = ctx;
+
+    // RPC 健康检查组件（端点探测、熔断、指标）
+    let rpc_cfg = rpc_check::CheckConfig::builder()
+        .timeout(Duration::from_secs(5))
+        .build();
+    let _rpc_checker = rpc_check::RpcChecker::new(rpc_cfg);
+    let _rpc_circuit = rpc_check::CircuitBreaker::new();
+    let _rpc_metrics = rpc_check::Metrics::new();
+    let _ = _rpc_checker.validate_endpoint("https://clob.polymarket.com");
+    let _ = _rpc_checker.validate_endpoint("https://gamma-api.polymarket.com");
+
+    // 两次套利交易之间的最小间隔
+    const MIN_TRADE_INTERVAL: Duration = Duration::from_secs(3);
+    let last_trade_time: Arc<Mutex<Option<Instant>>> = Arc::new(Mutex::new(None));
+
+    // 行权价阶梯套利检测（未配置 STRIKE_LADDERS 时不启用）
+    let ladder_detector = LadderDetector::new(config.ladder_execution_spread);
+
+    // 成交概率调整的期望收益模型（跨窗口累计历史结果）
+    let fill_model: Option<Arc<FillModel>> = config.ev_model_enabled.then(|| {
+        Arc::new(FillModel::load(
+            &config.fill_model_path,
+            config.ev_one_sided_cost,
+            config.ev_min_per_share,
+        ))
+    });
+
+    // 自适应执行价差：连续单边成交后放宽、连续双腿成交后收紧
+    let adaptive_spread: Option<Arc<AdaptiveSpread>> = config.adaptive_spread_enabled.then(|| {
+        let to_dec = |v: f64| Decimal::try_from(v).unwrap_or(dec!(0));
+        Arc::new(AdaptiveSpread::new(
+            to_dec(config.arbitrage_execution_spread),
+            AdaptiveSpreadSettings {
+                min: to_dec(config.adaptive_spread_min),
+                max: to_dec(config.adaptive_spread_max),
+                step: to_dec(config.adaptive_spread_step),
+                widen_after: config.adaptive_widen_after,
+                tighten_after: config.adaptive_tighten_after,
+            },
+        ))
+    });
+
+    // 按币种学习的执行价差（覆盖全局值，跨窗口累计）
+    let symbol_spread: Option<Arc<SymbolSpreadLearner>> = config.symbol_spread_enabled.then(|| {
+        Arc::new(SymbolSpreadLearner::load(
+            &config.symbol_spread_path,
+            config.symbol_spread_step,
+            config.symbol_spread_min_samples,
+            config.ev_one_sided_cost,
+        ))
+    });
+
+    // 抢单检测：同一市场反复零成交时提高该市场的价差要求
+    let snipe_detector: Option<Arc<SnipeDetector>> = config.snipe_detection_enabled.then(|| {
+        Arc::new(SnipeDetector::new(SnipeSettings {
+            window: Duration::from_secs(config.snipe_window_secs),
+            threshold: config.snipe_threshold,
+            edge_step: Decimal::try_from(config.snipe_edge_step).unwrap_or(dec!(0.005)),
+            max_extra: Decimal::try_from(config.snipe_max_extra_edge).unwrap_or(dec!(0.02)),
+        }))
+    });
+
+    // 回卖止盈：成对持仓的两边买一价之和扣除手续费后高于 1 时卖回订单簿
+    let sell_back: Option<Arc<SellBackDetector>> = config.sell_back_enabled.then(|| {
+        Arc::new(SellBackDetector::new(SellBackSettings {
+            fee_per_share: Decimal::try_from(config.sell_back_fee_per_share).unwrap_or(dec!(0.01)),
+            min_edge: Decimal::try_from(config.sell_back_min_edge).unwrap_or(dec!(0.005)),
+        }))
+    });
+
+    // 实现波动率估计：订阅现货逐笔成交，供波动率过滤、方向性策略与 GTD 有效期调节使用
+    crate::market::volatility::spawn(config.volatility.clone(), &config.crypto_symbols);
+
+    // 方向性策略：现货模型概率与盘口偏离时买入被低估的一边（与套利并列）
+    let directional: Option<Arc<DirectionalStrategy>> = config.directional_enabled.then(|| {
+        let spot = SpotFeed::spawn(&config.spot_feed_url, &config.crypto_symbols, Duration::from_secs(2));
+        Arc::new(DirectionalStrategy::new(
+            DirectionalSettings {
+                margin: config.directional_margin,
+                order_usdc: config.directional_order_usdc,
+                max_position_usdc: config.directional_max_position_usdc,
+                hourly_vol: config.directional_hourly_vol,
+                min_secs_remaining: config.directional_min_secs_remaining,
+            },
+            spot,
+        ))
+    });
+
+    // 流动性奖励计划：每个窗口刷新监控市场的奖励参数，定期汇总已获得的奖励计入盈亏
+    let rewards_book: Option<Arc<RewardsBook>> =
+        config.rewards_enabled.then(|| Arc::new(RewardsBook::new(&config.endpoints.clob_rest)));
+    // 只有会挂单时奖励才有意义：吃单不计奖
+    let rewards_discount = if executor.resting_lifetime().is_some() {
+        Decimal::try_from(config.rewards_edge_discount).unwrap_or(dec!(0))
+    } else {
+        dec!(0)
+    };
+    if let (Some(rewards), Some(proxy)) = (rewards_book.clone(), config.proxy_address) {
+        let pnl = _risk_manager.pnl();
+        let since = chrono::Utc::now().timestamp();
+        background.spawn(async move {
+            const REWARDS_POLL_INTERVAL: Duration = Duration::from_secs(600);
+            loop {
+                sleep(REWARDS_POLL_INTERVAL).await;
+                match rewards.earned_since(proxy, since).await {
+                    Ok(total) => pnl.set_rewards(total),
+                    Err(e) => debug!(error = %e, "查询已获得的流动性奖励失败"),
+                }
+            }
+        });
+    }
+
+    // 链上 CTF 转账监听：作为成交与结算的独立数据源覆盖本地持仓
+    if let Some(proxy) = config.proxy_address {
+        crate::risk::transfer_listener::spawn(
+            config.chain_ws_rpc_url.clone(),
+            proxy,
+            _risk_manager.position_tracker(),
+            &background,
+        );
+    }
+
+    // 持仓账龄告警：结束后未赎回、长时间单边的持仓
+    let position_aging: Option<Arc<PositionAging>> = (config.position_aging_check_interval_secs > 0).then(|| {
+        let aging = Arc::new(PositionAging::new(AgingSettings {
+            max_unhedged_age: Duration::from_secs(config.position_unhedged_max_age_secs),
+            unredeemed_grace: Duration::from_secs(config.position_unredeemed_grace_secs),
+        }));
+        let checker = aging.clone();
+        let interval = Duration::from_secs(config.position_aging_check_interval_secs);
+        let subgraph = config.subgraph_enabled.then(|| SubgraphClient::new(config.subgraph_urls.clone()));
+        background.spawn(async move {
+            loop {
+                sleep(interval).await;
+                match get_positions_low_priority().await {
+                    Ok(positions) => {
+                        // 以链上结算为准：查询持仓所在市场是否已写入赔付
+                        if let Some(subgraph) = subgraph.as_ref() {
+                            let held: HashSet<B256> = positions.iter().map(|p| p.condition_id).collect();
+                            for condition_id in held.into_iter().filter(|c| !checker.is_resolved(*c)) {
+                                match subgraph.condition_resolution(condition_id).await {
+                                    Ok(Some(_)) => checker.note_resolved(condition_id),
+                                    Ok(None) => {}
+                                    Err(e) => debug!(error = %e, "子图查询结算失败"),
+                                }
+                            }
+                        }
+                        checker.check(&positions);
+                    }
+                    Err(e) => debug!(error = %e, "持仓账龄检查：获取持仓失败"),
+                }
+            }
+        });
+        aging
+    });
+
+    // 零头持仓清理（Merge 或按买一卖出）
+    let dust_sweeper: Option<Arc<DustSweeper>> = (config.dust_cleanup_interval_secs > 0).then(|| {
+        Arc::new(DustSweeper::new(
+            executor.clone(),
+            _risk_manager.position_tracker(),
+            _risk_manager.merge_ledger(),
+            config.proxy_address,
+            config.private_key.clone(),
+            DustSettings {
+                threshold: Decimal::try_from(config.dust_threshold).unwrap_or(dec!(1)),
+                min_bid: Decimal::try_from(config.dust_min_bid).unwrap_or(dec!(0.05)),
+            },
+        ))
+    });
+
+    // 挂单排队位置跟踪：仅在套利订单会挂单（GTC/GTD）时启用
+    let queue_tracker: Option<Arc<QueueTracker>> = (config.queue_tracking_enabled
+        && executor.resting_lifetime().is_some())
+    .then(|| {
+        Arc::new(QueueTracker::new(QueueSettings {
+            horizon: Duration::from_secs(config.queue_horizon_secs),
+            min_fill_prob: config.queue_min_fill_prob,
+            min_observe: Duration::from_secs(config.queue_min_observe_secs),
+        }))
+    });
+
+    // maker 兜底：吃单（FOK/FAK）落空后挂出两腿 GTC 买单，登记到挂单表，市场结束前自动撤单
+    let maker_fallback: Option<Arc<MakerFallback>> = (config.maker_fallback_enabled
+        && executor.resting_lifetime().is_none())
+    .then(|| Arc::new(MakerFallback::new(order_store.clone(), config.maker_fallback_cancel_before_end_minutes)));
+
+    // 成对成交后立即 merge（需配置代理地址）
+    let immediate_merger: Option<Arc<ImmediateMerger>> = match (config.merge_on_pair_fill, config.proxy_address) {
+        (true, Some(proxy)) => Some(Arc::new(ImmediateMerger {
+            proxy,
+            private_key: config.private_key.clone(),
+            position_tracker: _risk_manager.position_tracker(),
+            merge_ledger: _risk_manager.merge_ledger(),
+            retry_queue: merge_retry_queue.clone(),
+            wind_down_in_progress: wind_down_in_progress.clone(),
+            in_flight: DashSet::new(),
+        })),
+        (true, None) => {
+            warn!("MERGE_ON_PAIR_FILL 已开启但未设置 POLYMARKET_PROXY_ADDRESS，成交后立即 Merge 已禁用");
+            None
+        }
+        _ => None,
+    };
+
+    // 本窗口市场已全部不可交易时记录窗口时间戳，下一轮直接等待下一个窗口
+    let mut exhausted_window: Option<i64> = None;
 
     // 主循环已启用，开始监控和交易
     #[allow(unreachable_code)]
     loop {
         // 立即获取当前窗口的市场，如果失败则等待下一个窗口
-        let markets = match _scheduler.get_markets_immediately_or_wait().await {
+        let window_exhausted =
+            exhausted_window.take() == Some(MarketDiscoverer::calculate_current_window_timestamp(chrono::Utc::now()));
+        let fetched = if window_exhausted {
+            _scheduler.wait_for_next_window().await
+        } else {
+            _scheduler.get_markets_immediately_or_wait().await
+        };
+        let markets = match fetched {
             Ok(markets) => markets,
             Err(e) => {
-                error!(error = %e, "获取市场失败");
+                error!(error = %e, "{}", tr!(Msg::MarketFetchFailed));
                 sleep(Duration::from_secs(60)).await;
                 continue;
             }
         };
 
         if markets.is_empty() {
-            warn!("未找到任何市场，跳过当前窗口");
+            warn!("{}", tr!(Msg::NoMarkets));
             continue;
         }
 
@@ -416,11 +1350,19 @@ async fn main() -> Result<()> {
             _rpc_metrics.record_check(true);
         }
 
-        // 新一轮开始：重置风险敞口，使本轮从 0 敞口重新累计
-        _risk_manager.position_tracker().reset_exposure();
+        // 新一轮开始：重置已结束市场的风险敞口，使本轮从 0 敞口重新累计（刚从同一窗口的检查点恢复时沿用）；
+        // 本轮仍在交易的市场（跨越窗口的较长周期市场）保留敞口
+        let window_now = MarketDiscoverer::calculate_current_window_timestamp(chrono::Utc::now());
+        if restored_window.take() != Some(window_now) {
+            let live_tokens: HashSet<U256> = markets.iter().flat_map(|m| [m.yes_token_id, m.no_token_id]).collect();
+            _risk_manager.position_tracker().reset_exposure(live_tokens);
+        }
 
         // 初始化订单簿监控器
         let mut monitor = OrderBookMonitor::new();
+        if config.book_snapshot_on_subscribe {
+            monitor = monitor.with_snapshots(&config.endpoints.clob_rest);
+        }
 
         // 订阅所有市场
         for market in &markets {
@@ -429,34 +1371,164 @@ async fn main() -> Result<()> {
             }
         }
 
+        // 行权价阶梯：订阅阶梯中每档市场的订单簿，并记录市场 -> 阶梯下标
+        let ladders: Vec<StrikeLadder> = if config.strike_ladders.is_empty() {
+            Vec::new()
+        } else {
+            _scheduler
+                .discoverer()
+                .get_ladders_for_timestamp(&config.strike_ladders, window_now)
+                .await
+        };
+        let mut ladder_of: HashMap<B256, usize> = HashMap::new();
+        for (index, ladder) in ladders.iter().enumerate() {
+            for market in ladder.markets() {
+                if let Err(e) = monitor.subscribe_market(market) {
+                    error!(error = %e, market_id = %market.market_id, "订阅阶梯市场失败");
+                    continue;
+                }
+                ladder_of.insert(market.market_id, index);
+            }
+        }
+        // 阶梯两腿持有到结算，收尾时不卖出
+        let ladder_tokens: HashSet<U256> = ladders
+            .iter()
+            .flat_map(|l| l.markets().flat_map(|m| [m.yes_token_id, m.no_token_id]))
+            .collect();
+
+        utils::control::publish_books(monitor.books_handle());
+        crate::risk::disposal::set_books(monitor.books_handle());
+        let window_books = monitor.books_handle();
+        if let Some(aging) = position_aging.as_ref() {
+            for market in markets.iter().chain(ladders.iter().flat_map(|l| l.markets())) {
+                aging.note_market(market);
+            }
+        }
+
         // 创建订单簿流
         let mut stream = match monitor.create_orderbook_stream() {
             Ok(stream) => stream,
             Err(e) => {
-                error!(error = %e, "创建订单簿流失败");
+                error!(error = %e, "{}", tr!(Msg::StreamCreateFailed));
                 continue;
             }
         };
 
-        info!(market_count = markets.len(), "开始监控订单簿");
+        info!(market_count = markets.len(), ladder_count = ladders.len(), "{}", tr!(Msg::MonitorStart));
+
+        // 市场状态监控：发现停止接单/关闭的市场后退订、撤单并排除出检测
+        let (status_watcher, mut dead_markets) = if config.market_status_check_interval_secs > 0 {
+            let (watcher, rx) = spawn_status_watcher(
+                _scheduler.discoverer().gamma(),
+                &markets,
+                Duration::from_secs(config.market_status_check_interval_secs),
+                &background,
+            );
+            for market in ladders.iter().flat_map(|l| l.markets()) {
+                watcher.watch(market);
+            }
+            (Some(watcher), Some(rx))
+        } else {
+            (None, None)
+        };
+
+        // 窗口内重新发现：窗口开始时缺失的系列市场创建后即时订阅，而不是整小时跳过
+        let mut late_markets = if config.market_rediscovery_interval_secs > 0 {
+            _scheduler.spawn_rediscovery(
+                &markets,
+                Duration::from_secs(config.market_rediscovery_interval_secs),
+                &config.endpoints.clob_rest,
+                &background,
+            )
+        } else {
+            None
+        };
+
+        // 订单簿流存活监测：订阅流静默超过阈值时主动重建，不等订阅流报错
+        let mut ws_watchdog = (config.ws_stale_check_interval_secs > 0).then(|| {
+            StreamWatchdog::new(LivenessSettings {
+                interval: Duration::from_secs(config.ws_stale_check_interval_secs),
+                stale_after: Duration::from_secs(config.ws_stale_timeout_secs.max(1)),
+            })
+        });
 
         // 记录当前窗口的时间戳，用于检测周期切换与收尾触发
         use chrono::Utc;
         let current_window_timestamp = MarketDiscoverer::calculate_current_window_timestamp(Utc::now());
-        let window_end = chrono::DateTime::from_timestamp(current_window_timestamp + 3600, 0)
+        let window_end = chrono::DateTime::from_timestamp(MarketDiscoverer::window_end_timestamp(current_window_timestamp), 0)
             .unwrap_or_else(|| Utc::now());
         let mut wind_down_done = false;
+        if config.spread_sample_enabled {
+            crate::monitor::spread_sampler::spawn(window_books.clone(), &markets, window_end);
+        }
+        crate::monitor::mid_export::spawn(
+            &config.mid_export_dir,
+            window_books.clone(),
+            &markets,
+            current_window_timestamp,
+            window_end,
+        );
 
         // 创建市场ID到市场信息的映射
-        let market_map: HashMap<B256, &MarketInfo> = markets.iter()
-            .map(|m| (m.market_id, m))
+        let mut market_map: HashMap<B256, MarketInfo> = markets.iter()
+            .chain(ladders.iter().flat_map(|l| l.markets()))
+            .map(|m| (m.market_id, m.clone()))
             .collect();
+        utils::control::publish_markets(&market_map);
+        // 交易规则（价格单位）：检测与下单按各市场的价格单位取整
+        let rules_markets: Vec<MarketInfo> = market_map.values().cloned().collect();
+        let clob_rest_url = config.endpoints.clob_rest.clone();
+        background.spawn(async move {
+            let count = crate::market::rules::refresh(&clob_rest_url, &rules_markets).await;
+            info!("📏 市场交易规则已刷新 | {}/{} 个市场", count, rules_markets.len());
+        });
+        if let Some(rewards) = rewards_book.clone() {
+            let market_ids: Vec<B256> = market_map.keys().copied().collect();
+            background.spawn(async move {
+                match rewards.refresh(&market_ids).await {
+                    Ok(count) => info!("🎁 奖励计划已刷新 | 参与奖励的监控市场:{}", count),
+                    Err(e) => warn!(error = %e, "刷新奖励计划失败，沿用上次结果"),
+                }
+            });
+        }
+        // 各市场当前价差机会首次出现的时间（EV 模型按机会存续时长分桶）
+        let mut opportunity_since: HashMap<B256, Instant> = HashMap::new();
 
         // 创建市场映射（condition_id -> (yes_token_id, no_token_id)）用于仓位平衡
-        let market_token_map: HashMap<B256, (U256, U256)> = markets.iter()
+        let mut market_token_map: HashMap<B256, (U256, U256)> = markets.iter()
             .map(|m| (m.market_id, (m.yes_token_id, m.no_token_id)))
             .collect();
 
+        // 跨窗口携带：旧窗口中仍未完结的订单对继续做恢复与仓位平衡（merge 按 API 持仓执行，本就覆盖旧市场）
+        let pruned = _risk_manager.prune_resolved_pairs();
+        let carried_markets = _risk_manager.carried_markets();
+        let carried_pair_ids = _risk_manager.unresolved_pair_ids();
+        if !carried_pair_ids.is_empty() {
+            info!(
+                "📦 跨窗口携带未完结订单对 | 订单对:{} | 市场:{} | 已清理完结:{}",
+                carried_pair_ids.len(),
+                carried_markets.len(),
+                pruned
+            );
+            for (market_id, tokens) in carried_markets {
+                market_token_map.entry(market_id).or_insert(tokens);
+            }
+            let risk_manager_carry = _risk_manager.clone();
+            background.spawn(async move {
+                for pair_id in carried_pair_ids {
+                    match risk_manager_carry.handle_order_pair(&pair_id).await {
+                        Ok(crate::risk::recovery::RecoveryAction::ManualIntervention { reason }) => {
+                            warn!(pair_id = %pair_id, "跨窗口订单对需要手动干预: {}", reason);
+                        }
+                        Ok(_) => {}
+                        Err(e) => warn!(pair_id = %pair_id, error = %e, "跨窗口订单对恢复处理失败"),
+                    }
+                }
+            });
+        } else if pruned > 0 {
+            debug!("已清理 {} 个完结订单对", pruned);
+        }
+
         // 创建定时仓位平衡定时器
         let balance_interval = config.position_balance_interval_secs;
         let mut balance_timer = if balance_interval > 0 {
@@ -468,17 +1540,23 @@ async fn main() -> Result<()> {
             None
         };
 
+        let mut dust_timer = dust_sweeper.as_ref().map(|_| {
+            let mut timer = tokio::time::interval(Duration::from_secs(config.dust_cleanup_interval_secs));
+            timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            timer
+        });
+
         // 按市场记录上一拍卖一价，用于计算涨跌方向（仅一次 HashMap 读写，不影响监控性能）
         let last_prices: DashMap<B256, (Decimal, Decimal)> = DashMap::new();
 
         // 监控订单簿更新
         loop {
             // 收尾检查：距窗口结束 <= N 分钟时执行一次收尾（不跳出，继续监控直到窗口结束由下方「新窗口检测」自然切换）
-            if config.wind_down_before_window_end_minutes > 0 && !wind_down_done {
+            if config.wind_down_before_window_end_minutes > 0 && !wind_down_done && utils::leader::is_leader() {
                 let now = Utc::now();
                 let minutes_until_end = (window_end - now).num_minutes();
                 if minutes_until_end <= config.wind_down_before_window_end_minutes as i64 {
-                    info!("🛑 触发收尾 | 距窗口结束 {} 分钟", minutes_until_end);
+                    info!("{}", tr!(Msg::WindDownTriggered, minutes_until_end));
                     wind_down_done = true;
                     wind_down_in_progress.store(true, Ordering::Relaxed);
 
@@ -487,15 +1565,27 @@ async fn main() -> Result<()> {
                     let config_wd = config.clone();
                     let risk_manager_wd = _risk_manager.clone();
                     let wind_down_flag = wind_down_in_progress.clone();
-                    tokio::spawn(async move {
+                    let ladder_tokens_wd = ladder_tokens.clone();
+                    let retry_queue_wd = merge_retry_queue.clone();
+                    let books_wd = window_books.clone();
+                    // 收尾只处理随本窗口结束的市场；跨越本窗口的较长周期市场照常交易，其挂单与持仓不动
+                    let (ending, outliving): (Vec<&MarketInfo>, Vec<&MarketInfo>) =
+                        market_map.values().partition(|m| ends_with_window(m, window_end));
+                    let ending_tokens_wd: Vec<U256> = ending.iter().flat_map(|m| [m.yes_token_id, m.no_token_id]).collect();
+                    let outliving_markets_wd: HashSet<B256> = outliving.iter().map(|m| m.market_id).collect();
+                    let outliving_tokens_wd: HashSet<U256> =
+                        outliving.iter().flat_map(|m| [m.yes_token_id, m.no_token_id]).collect();
+                    if !outliving.is_empty() {
+                        info!("收尾：{} 个市场随本窗口结束，{} 个较长周期市场继续交易", ending.len(), outliving.len());
+                    }
+                    background.spawn(async move {
                         const DELAY_AFTER_CANCEL: Duration = Duration::from_secs(10);
                         const MERGE_INTERVAL: Duration = Duration::from_secs(30);
 
-                        // 1. 取消所有挂单
-                        if let Err(e) = executor_wd.cancel_all_orders().await {
-                            warn!(error = %e, "收尾：取消所有挂单失败，继续执行 Merge 与卖出");
-                        } else {
-                            info!("✅ 收尾：已取消所有挂单");
+                        // 1. 取消随本窗口结束的市场上的挂单
+                        match executor_wd.cancel_orders_for_tokens(&ending_tokens_wd).await {
+                            Ok(n) => info!("✅ 收尾：已取消本窗口市场的 {} 个挂单", n),
+                            Err(e) => warn!(error = %e, "收尾：取消挂单失败，继续执行 Merge 与卖出"),
                         }
 
                         // 取消后等 10 秒再 Merge，避免取消前刚成交的订单尚未上链更新持仓
@@ -507,6 +1597,10 @@ async fn main() -> Result<()> {
                         if let Some(proxy) = config_wd.proxy_address {
                             match get_positions().await {
                                 Ok(positions) => {
+                                    let positions: Vec<Position> = positions
+                                        .into_iter()
+                                        .filter(|p| !outliving_markets_wd.contains(&p.condition_id))
+                                        .collect();
                                     let condition_ids = condition_ids_with_both_sides(&positions);
                                     let merge_info = merge_info_with_both_sides(&positions);
                                     if !condition_ids.is_empty() {
@@ -522,6 +1616,7 @@ async fn main() -> Result<()> {
                                                 did_any_merge = true;
                                                 info!("✅ 收尾：批量 Merge 完成 | tx={} | 共 {} 个市场", tx, merged.len());
                                                 for (condition_id, merge_amt) in &merged {
+                                                    retry_queue_wd.resolve(MERGE_OP, &condition_id.to_string());
                                                     if let Some((yes_token, no_token, _)) = merge_info.get(condition_id) {
                                                         let merge_amt_decimal =
                                                             Decimal::from(merge_amt.to::<u64>()) / dec!(1_000_000);
@@ -530,11 +1625,20 @@ async fn main() -> Result<()> {
                                                         position_tracker.update_position(*yes_token, -merge_amt_decimal);
                                                         position_tracker.update_position(*no_token, -merge_amt_decimal);
                                                         info!("💰 收尾：Merge 已扣减敞口 | condition_id={:#x} | 数量:{}", condition_id, merge_amt_decimal);
+                                                        risk_manager_wd
+                                                            .merge_ledger()
+                                                            .attribute(*condition_id, merge_amt_decimal, &tx.to_string());
                                                     }
                                                 }
+                                                events::publish(BotEvent::merged(&tx.to_string(), &merged));
+                                                merge_verify::spawn(tx.to_string(), proxy, merged);
                                             }
                                             Err(e) => {
                                                 warn!(error = %e, "收尾：批量 Merge 失败");
+                                                let msg = e.to_string();
+                                                for condition_id in &condition_ids {
+                                                    retry_queue_wd.record_failure(MERGE_OP, &condition_id.to_string(), &msg);
+                                                }
                                             }
                                         }
                                     }
@@ -550,27 +1654,89 @@ async fn main() -> Result<()> {
                             sleep(MERGE_INTERVAL).await;
                         }
 
-                        // 3. 市价卖出剩余单腿持仓
+                        // 3. 市价卖出剩余单腿持仓（所有卖单合并为批量请求提交）；大额持仓改用 TWAP 分片卖出
                         let wind_down_sell_price = Decimal::try_from(config_wd.wind_down_sell_price).unwrap_or(dec!(0.01));
+                        let twap_min_size = Decimal::try_from(config_wd.twap_min_size).unwrap_or(dec!(0));
+                        // TWAP 须在窗口结束前 30 秒完成，留出兜底卖出的时间
+                        let twap_duration = Duration::from_secs(config_wd.twap_exit_secs)
+                            .min((window_end - Utc::now() - chrono::Duration::seconds(30)).to_std().unwrap_or_default());
+                        let mut twap_exits = Vec::new();
                         match get_positions().await {
                             Ok(positions) => {
+                                let mut sell_orders = Vec::new();
                                 for pos in positions.iter().filter(|p| p.size > dec!(0)) {
+                                    if ladder_tokens_wd.contains(&pos.asset) {
+                                        debug!(token_id = %pos.asset, "收尾：阶梯持仓持有到结算，跳过卖出");
+                                        continue;
+                                    }
+                                    if outliving_tokens_wd.contains(&pos.asset) {
+                                        debug!(token_id = %pos.asset, "收尾：市场跨越本窗口继续交易，跳过卖出");
+                                        continue;
+                                    }
                                     let size_floor = (pos.size * dec!(100)).floor() / dec!(100);
                                     if size_floor < dec!(0.01) {
                                         debug!(token_id = %pos.asset, size = %pos.size, "收尾：持仓过小，跳过卖出");
                                         continue;
                                     }
-                                    if let Err(e) = executor_wd.sell_at_price(pos.asset, wind_down_sell_price, size_floor).await {
-                                        warn!(token_id = %pos.asset, size = %pos.size, error = %e, "收尾：卖出单腿失败");
-                                    } else {
-                                        info!("✅ 收尾：已下卖单 | token_id={:#x} | 数量:{} | 价格:{:.4}", pos.asset, size_floor, wind_down_sell_price);
+                                    if config_wd.twap_exit_secs > 0 && size_floor >= twap_min_size && !twap_duration.is_zero() {
+                                        twap_exits.push((pos.asset, size_floor));
+                                        continue;
+                                    }
+                                    sell_orders.push(BatchOrder {
+                                        token_id: pos.asset,
+                                        side: Side::Sell,
+                                        price: wind_down_sell_price,
+                                        size: size_floor,
+                                        order_type: OrderType::GTC,
+                                        expiration: None,
+                                    });
+                                }
+                                match executor_wd.submit_batch(&sell_orders).await {
+                                    Ok(results) => {
+                                        for (order, result) in sell_orders.iter().zip(results.iter()) {
+                                            match result {
+                                                Ok(result) if result.success => {
+                                                    info!("✅ 收尾：已下卖单 | token_id={:#x} | 数量:{} | 价格:{:.4}", order.token_id, order.size, order.price);
+                                                }
+                                                Ok(result) => warn!(
+                                                    token_id = %order.token_id,
+                                                    size = %order.size,
+                                                    error = result.error_msg.as_deref().unwrap_or("未知错误"),
+                                                    "收尾：卖出单腿失败"
+                                                ),
+                                                Err(e) => warn!(token_id = %order.token_id, size = %order.size, error = %e, "收尾：卖出单腿失败"),
+                                            }
+                                        }
                                     }
+                                    Err(e) => { warn!(count = sell_orders.len(), error = %e, "收尾：批量卖出单腿失败"); }
                                 }
                             }
                             Err(e) => { warn!(error = %e, "收尾：获取持仓失败，跳过卖出"); }
                         }
 
-                        info!("🛑 收尾完成，继续监控至窗口结束");
+                        // TWAP 分片卖出大额持仓，未卖完的部分按收尾卖价挂单兜底
+                        let twap_settings = trading::twap::TwapSettings {
+                            duration: twap_duration,
+                            slices: config_wd.twap_slices,
+                            size_jitter_pct: config_wd.twap_size_jitter_pct,
+                            min_price: Decimal::try_from(config_wd.twap_min_price).unwrap_or(dec!(0.01)),
+                        };
+                        futures::future::join_all(twap_exits.into_iter().map(|(token_id, size)| {
+                            let executor = executor_wd.clone();
+                            let books = books_wd.clone();
+                            async move {
+                                let sold = trading::twap::sell(executor.clone(), books, token_id, size, twap_settings).await;
+                                let rest = size - sold;
+                                if rest >= dec!(0.01) {
+                                    if let Err(e) = executor.sell_at_price(token_id, wind_down_sell_price, rest).await {
+                                        warn!(token_id = %token_id, size = %rest, error = %e, "收尾：TWAP 剩余部分卖出失败");
+                                    }
+                                }
+                            }
+                        }))
+                        .await;
+
+                        info!("{}", tr!(Msg::WindDownDone));
                         wind_down_flag.store(false, Ordering::Relaxed);
                     });
                 }
@@ -581,8 +1747,171 @@ async fn main() -> Result<()> {
                 book_result = stream.next() => {
                     match book_result {
                         Some(Ok(book)) => {
+                            if let Some(watchdog) = ws_watchdog.as_mut() {
+                                watchdog.touch();
+                            }
+                            // 故障注入：模拟断线按流错误处理（重建订阅），丢消息则忽略本次更新
+                            if chaos::hit(chaos::Fault::WsDisconnect) {
+                                error!("{}", tr!(Msg::StreamError));
+                                break;
+                            }
+                            if chaos::hit(chaos::Fault::DropMessage) {
+                                continue;
+                            }
+                            let book_received = Instant::now();
                             // 然后处理订单簿更新（book会被move）
-                            if let Some(pair) = monitor.handle_book_update(book) {
+                            let pair = monitor.handle_book_update(book);
+                            latency::record(Stage::Decode, book_received.elapsed());
+                            if let Some(p) = pair.as_ref() {
+                                utils::book_recorder::record(p);
+                            }
+
+                            // 挂单排队估计：成交概率可忽略的陈旧挂单撤掉
+                            if let (Some(queue), Some(p)) = (queue_tracker.as_ref(), pair.as_ref()) {
+                                if !queue.is_empty() {
+                                    let stale: Vec<_> = [&p.yes_book, &p.no_book]
+                                        .into_iter()
+                                        .flat_map(|book| queue.on_book(book))
+                                        .collect();
+                                    if !stale.is_empty() {
+                                        for quote in &stale {
+                                            info!(
+                                                "🪑 挂单排队靠后，撤单 | 订单:{} | 价格:{} | 前方:{}份 | 估计成交概率:{:.2}",
+                                                &quote.order_id[..quote.order_id.len().min(10)],
+                                                quote.price,
+                                                quote.ahead,
+                                                quote.fill_prob
+                                            );
+                                        }
+                                        let executor_cancel = executor.clone();
+                                        background.spawn(async move {
+                                            let ids: Vec<&str> = stale.iter().map(|q| q.order_id.as_str()).collect();
+                                            match executor_cancel.cancel_order_ids(&ids).await {
+                                                Ok(()) => utils::metrics::add("queue_stale_cancelled", ids.len() as u64),
+                                                Err(e) => warn!(error = %e, "撤销陈旧挂单失败"),
+                                            }
+                                        });
+                                    }
+                                }
+                            }
+
+                            // 对冲监测：有单边持仓在监测时，按买一价检查止盈止损
+                            if hedge_monitor.has_positions() {
+                                if let Some(p) = pair.as_ref() {
+                                    for book in [&p.yes_book, &p.no_book] {
+                                        if let Err(e) = hedge_monitor.check_and_execute(book).await {
+                                            warn!(error = %e, "对冲监测检查失败");
+                                        }
+                                    }
+                                }
+                            }
+
+                            // 行权价阶梯：该市场属于某个阶梯时，检查阶梯内任意两档的单调性违背
+                            let ladder_opp = pair
+                                .as_ref()
+                                .and_then(|p| ladder_of.get(&p.market_id))
+                                .and_then(|&index| ladder_detector.check_ladder(&ladders[index], &monitor));
+                            if let Some(ladder_opp) = ladder_opp.filter(|_| utils::leader::is_leader()) {
+                                let opp = ladder_opp.legs.clone();
+                                let max_order_size = Decimal::try_from(config.max_order_size_for(&ladder_opp.ladder)).unwrap_or(dec!(100.0));
+                                // 对齐数量步长；低于任一腿的最小下单数量时为 None（提交必被拒，直接跳过）
+                                let valid_size = crate::market::rules::order_size(
+                                    &[opp.yes_token_id, opp.no_token_id],
+                                    trading::orders::jitter_size(
+                                        opp.yes_size.min(opp.no_size).min(max_order_size),
+                                        config.order_size_jitter_pct,
+                                    ),
+                                );
+                                let order_size = valid_size.unwrap_or(dec!(0));
+                                // 统一风控检查（阶梯两腿总价 < 1 即有利可图，执行阈值取 1）
+                                let approval = match valid_size {
+                                    Some(_) => Some(
+                                        _risk_manager
+                                            .approve(&crate::risk::approval::Proposal {
+                                                opportunity: &opp,
+                                                order_size,
+                                                execution_threshold: dec!(1.0),
+                                                market_end: market_map.get(&opp.market_id).map(|m| m.end_date),
+                                            })
+                                            .await,
+                                    ),
+                                    None => None,
+                                };
+                                let (reservation, veto) = match approval {
+                                    Some(Ok(approval)) => (Some(approval.reservation), None),
+                                    Some(Err(veto)) => (None, Some(veto)),
+                                    None => (None, None),
+                                };
+                                let interval_ok = reservation.is_some() && {
+                                    let mut guard = last_trade_time.lock().await;
+                                    let ok = guard.map(|last| last.elapsed() >= MIN_TRADE_INTERVAL).unwrap_or(true);
+                                    if ok {
+                                        *guard = Some(Instant::now());
+                                    }
+                                    ok
+                                };
+                                if valid_size.is_none() {
+                                    debug!("📏 下单数量低于交易所最小数量，跳过阶梯套利 | 阶梯:{}", ladder_opp.ladder);
+                                    utils::metrics::incr("min_size_skipped");
+                                } else if let Some(veto) = veto.as_ref() {
+                                    report_veto(veto, &ladder_opp.ladder, "阶梯套利");
+                                } else if !interval_ok {
+                                    debug!("⏱️ 交易间隔不足 3 秒，跳过阶梯套利 | 阶梯:{}", ladder_opp.ladder);
+                                } else if let Some(reservation) = reservation {
+                                    events::publish(BotEvent::opportunity(&opp, &ladder_opp.ladder));
+                                    info!(
+                                        "🪜 执行阶梯套利 | 阶梯:{} | 买 >{} YES {:.4} + 买 >{} NO {:.4} | 利润:{:.2}% | 下单数量:{}份",
+                                        ladder_opp.ladder,
+                                        ladder_opp.low_strike,
+                                        opp.yes_ask_price,
+                                        ladder_opp.high_strike,
+                                        opp.no_ask_price,
+                                        opp.profit_percentage,
+                                        order_size
+                                    );
+                                    let executor_clone = executor.clone();
+                                    let risk_manager_clone = _risk_manager.clone();
+                                    let ladder_name = ladder_opp.ladder.clone();
+                                    let mut opp_clone = opp;
+                                    opp_clone.yes_size = order_size;
+                                    opp_clone.no_size = order_size;
+                                    tokio::spawn(async move {
+                                        let execution = executor_clone.execute_arbitrage_pair(&opp_clone, "", "", "").await;
+                                        settle_reservation(reservation, &opp_clone, execution.as_ref().err());
+                                        match execution {
+                                            Ok(result) => {
+                                                events::publish(BotEvent::executed(&result, opp_clone.market_id, &ladder_name));
+                                                // 两腿分属不同市场：以低行权价市场登记，单边成交由风险管理器按 token 跟踪
+                                                let pair_id = result.pair_id.clone();
+                                                if result.yes_filled > dec!(0) || result.no_filled > dec!(0) {
+                                                    utils::tui::record_fill(
+                                                        &ladder_name,
+                                                        result.yes_filled,
+                                                        opp_clone.yes_ask_price,
+                                                        result.no_filled,
+                                                        opp_clone.no_ask_price,
+                                                    );
+                                                }
+                                                risk_manager_clone.register_order_pair(
+                                                    result,
+                                                    opp_clone.market_id,
+                                                    opp_clone.yes_token_id,
+                                                    opp_clone.no_token_id,
+                                                    opp_clone.yes_ask_price,
+                                                    opp_clone.no_ask_price,
+                                                );
+                                                if let Ok(crate::risk::recovery::RecoveryAction::ManualIntervention { reason }) =
+                                                    risk_manager_clone.handle_order_pair(&pair_id).await
+                                                {
+                                                    warn!("阶梯套利需要手动干预: {}", reason);
+                                                }
+                                            }
+                                            Err(e) => error!("执行阶梯套利失败: {}", e),
+                                        }
+                                    });
+                                }
+                            }
+                            if let Some(pair) = pair {
                                 // 注意：asks 最后一个为卖一价
                                 let yes_best_ask = pair.yes_book.asks.last().map(|a| (a.price, a.size));
                                 let no_best_ask = pair.no_book.asks.last().map(|a| (a.price, a.size));
@@ -608,11 +1937,121 @@ async fn main() -> Result<()> {
                                 let market_info = market_map.get(&pair.market_id);
                                 let market_title = market_info.map(|m| m.title.as_str()).unwrap_or("未知市场");
                                 let market_symbol = market_info.map(|m| m.crypto_symbol.as_str()).unwrap_or("");
+                                // 币种覆盖、学习到的执行价差与自适应滑点按币种 + 周期（如 btc:15m）区分
+                                let symbol_key = market_info.map(MarketInfo::symbol_key).unwrap_or_default();
+                                let market_end = market_info.map(|m| m.end_date).unwrap_or(window_end);
                                 let market_display = if !market_symbol.is_empty() {
                                     format!("{}预测市场", market_symbol)
                                 } else {
                                     market_title.to_string()
                                 };
+                                crate::monitor::shadow::observe(&pair, &market_display);
+                                if let Some(detector) = sell_back.as_ref().filter(|_| utils::leader::is_leader()) {
+                                    let position_tracker = _risk_manager.position_tracker();
+                                    let held = position_tracker.get_pair_positions(pair.yes_book.asset_id, pair.no_book.asset_id);
+                                    if let Some(opp) = detector
+                                        .check(market_id, &pair.yes_book, &pair.no_book, held)
+                                        .filter(|_| detector.begin(market_id))
+                                    {
+                                        // 回卖只占用回卖额度（在途卖单的名义金额），执行结束即归还
+                                        match _risk_manager.approve_leg(Strategy::SellBack, (opp.yes_bid + opp.no_bid) * opp.size).await {
+                                            Ok(approval) => {
+                                                let detector = detector.clone();
+                                                let executor = executor.clone();
+                                                let market_display = market_display.clone();
+                                                background.spawn(async move {
+                                                    if let Err(e) = crate::monitor::sell_back::execute(&executor, &position_tracker, &opp, &market_display).await {
+                                                        warn!(error = %e, "回卖止盈失败");
+                                                    }
+                                                    approval.reservation.release();
+                                                    detector.finish(opp.market_id);
+                                                });
+                                            }
+                                            Err(veto) => {
+                                                report_veto(&veto, &market_display, "回卖");
+                                                detector.finish(opp.market_id);
+                                            }
+                                        }
+                                    }
+                                }
+                                if let Some(strategy) = directional
+                                    .as_ref()
+                                    .filter(|_| !market_symbol.is_empty() && utils::leader::is_leader())
+                                {
+                                    if let Some(signal) = strategy
+                                        .evaluate(&pair, market_symbol, current_window_timestamp, market_end)
+                                        .filter(|s| !crate::trading::reject_policy::blocked(&[s.token_id]))
+                                    {
+                                        let cost = signal.price * signal.size;
+                                        let approval = _risk_manager.approve_leg(Strategy::Directional, cost).await;
+                                        if let Ok(approval) = approval {
+                                            let reservation = approval.reservation;
+                                            info!(
+                                                "🧭 方向性下单 | 市场:{} | 买{} {:.4} × {}份 | 模型概率:{:.3} | 现价:{} 参考:{}",
+                                                market_display, signal.side, signal.price, signal.size, signal.model_prob, signal.spot, signal.reference
+                                            );
+                                            utils::journal::record(
+                                                "directional_order",
+                                                serde_json::json!({
+                                                    "market_id": format!("{:#x}", signal.market_id),
+                                                    "market": market_display,
+                                                    "side": signal.side,
+                                                    "price": signal.price.to_string(),
+                                                    "size": signal.size.to_string(),
+                                                    "model_prob": signal.model_prob,
+                                                    "spot": signal.spot,
+                                                    "reference": signal.reference,
+                                                }),
+                                            );
+                                            let executor_clone = executor.clone();
+                                            let strategy_clone = strategy.clone();
+                                            tokio::spawn(async move {
+                                                let order = BatchOrder {
+                                                    token_id: signal.token_id,
+                                                    side: Side::Buy,
+                                                    price: signal.price,
+                                                    size: signal.size,
+                                                    order_type: OrderType::FAK,
+                                                    expiration: None,
+                                                };
+                                                match executor_clone.submit_single(&order).await {
+                                                    Ok(result) if result.taking_amount > dec!(0) => {
+                                                        reservation.commit(&[(signal.token_id, signal.price, signal.size)]);
+                                                        utils::metrics::incr("directional_filled");
+                                                    }
+                                                    Ok(result) => {
+                                                        reservation.release();
+                                                        strategy_clone.refund(signal.market_id, cost);
+                                                        debug!(
+                                                            error = result.error_msg.as_deref().unwrap_or(""),
+                                                            "方向性下单未成交"
+                                                        );
+                                                    }
+                                                    Err(e) if crate::trading::executor::outcome_unknown(&e) => {
+                                                        reservation.commit(&[(signal.token_id, signal.price, signal.size)]);
+                                                        warn!(error = %e, "方向性下单超时，按已下单计入敞口");
+                                                    }
+                                                    Err(e) => {
+                                                        reservation.release();
+                                                        strategy_clone.refund(signal.market_id, cost);
+                                                        warn!(error = %e, "方向性下单失败");
+                                                    }
+                                                }
+                                            });
+                                        } else if let Err(veto) = approval {
+                                            report_veto(&veto, &market_display, "方向性下单");
+                                            strategy.refund(signal.market_id, cost);
+                                        }
+                                    }
+                                }
+                                utils::tui::record_book(
+                                    market_id,
+                                    pair.yes_book.asset_id,
+                                    pair.no_book.asset_id,
+                                    &market_display,
+                                    yes_best_ask.map(|(p, _)| p),
+                                    no_best_ask.map(|(p, _)| p),
+                                );
 
                                 let (prefix, spread_info) = total_ask_price
                                     .map(|t| {
@@ -665,90 +2104,175 @@ async fn main() -> Result<()> {
 
                                 // 检测套利机会（监控阶段：只有当总价 <= 1 - 套利执行价差 时才执行套利）
                                 use rust_decimal::Decimal;
-                                let execution_threshold = dec!(1.0) - Decimal::try_from(config.arbitrage_execution_spread)
-                                    .unwrap_or(dec!(0.01));
+                                let learned_spread = symbol_spread.as_ref().and_then(|s| s.spread_for(&symbol_key));
+                                let execution_spread = match (learned_spread, adaptive_spread.as_ref()) {
+                                    (Some(learned), _) => learned,
+                                    (None, Some(adaptive)) => adaptive.current(),
+                                    (None, None) => Decimal::try_from(config.arbitrage_execution_spread).unwrap_or(dec!(0.01)),
+                                } + snipe_detector
+                                    .as_ref()
+                                    .map(|d| d.extra_edge(pair.market_id))
+                                    .unwrap_or(dec!(0));
+                                let execution_spread = match rewards_book.as_ref().and_then(|r| r.reward_for(pair.market_id)) {
+                                    Some(_) => (execution_spread - rewards_discount).max(dec!(0)),
+                                    None => execution_spread,
+                                };
+                                let execution_threshold = dec!(1.0) - execution_spread;
+                                match total_ask_price {
+                                    Some(total) if total <= execution_threshold => {
+                                        opportunity_since.entry(pair.market_id).or_insert(book_received);
+                                    }
+                                    _ => {
+                                        opportunity_since.remove(&pair.market_id);
+                                    }
+                                }
                                 if let Some(total_price) = total_ask_price {
-                                    if total_price <= execution_threshold {
-                                        if let Some(opp) = _detector.check_arbitrage(
-                                            &pair.yes_book,
-                                            &pair.no_book,
-                                            &pair.market_id,
-                                        ) {
-                                            // 检查 YES 价格是否达到阈值
-                                            if config.min_yes_price_threshold > 0.0 {
-                                                use rust_decimal::Decimal;
-                                                let min_yes_price_decimal = Decimal::try_from(config.min_yes_price_threshold)
-                                                    .unwrap_or(dec!(0.0));
-                                                if opp.yes_ask_price < min_yes_price_decimal {
+                                    let gate_threshold = crate::risk::ab_test::gate_threshold(execution_threshold);
+                                    if total_price <= gate_threshold && utils::leader::is_leader() {
+                                        // 单次套利尝试的根 span：检测 → 风控 → 下单 → 恢复/Merge
+                                        let attempt_span = tracing::info_span!(
+                                            "arbitrage_attempt",
+                                            market = %market_display,
+                                            total_ask = %total_price
+                                        );
+                                        let detect_start = Instant::now();
+                                        // A/B 分配启用时按两套参数检测并分配变体
+                                        let (opp, ab_variant) = attempt_span.in_scope(|| {
+                                            crate::risk::ab_test::detect(
+                                                &_detector,
+                                                &pair.yes_book,
+                                                &pair.no_book,
+                                                &pair.market_id,
+                                                total_price,
+                                                execution_threshold,
+                                            )
+                                        });
+                                        latency::record(Stage::Detect, detect_start.elapsed());
+                                        if let Some(opp) = opp {
+                                            events::publish(BotEvent::opportunity(&opp, &market_display));
+                                            let risk_start = Instant::now();
+                                            // 币种最低利润率覆盖（SYMBOL_OVERRIDES）
+                                            if let Some(min_profit) = config.min_profit_threshold_for(&symbol_key) {
+                                                let min_profit_pct = Decimal::try_from(min_profit * 100.0).unwrap_or(dec!(0));
+                                                if opp.profit_percentage < min_profit_pct {
                                                     debug!(
-                                                        "⏸️ YES价格未达到阈值，跳过套利执行 | 市场:{} | YES价格:{:.4} | 阈值:{:.4}",
+                                                        "⏸️ 利润率未达到币种阈值，跳过 | 市场:{} | 利润:{:.2}% | 阈值:{:.2}%",
                                                         market_display,
-                                                        opp.yes_ask_price,
-                                                        config.min_yes_price_threshold
+                                                        opp.profit_percentage,
+                                                        min_profit_pct
                                                     );
                                                     continue; // 跳过这个套利机会
                                                 }
                                             }
-                                            
-                                            // 检查 NO 价格是否达到阈值
-                                            if config.min_no_price_threshold > 0.0 {
-                                                use rust_decimal::Decimal;
-                                                let min_no_price_decimal = Decimal::try_from(config.min_no_price_threshold)
-                                                    .unwrap_or(dec!(0.0));
-                                                if opp.no_ask_price < min_no_price_decimal {
-                                                    debug!(
-                                                        "⏸️ NO价格未达到阈值，跳过套利执行 | 市场:{} | NO价格:{:.4} | 阈值:{:.4}",
-                                                        market_display,
-                                                        opp.no_ask_price,
-                                                        config.min_no_price_threshold
-                                                    );
-                                                    continue; // 跳过这个套利机会
-                                                }
+
+                                            // 波动率过滤：现货实现波动率过高时两腿之间价格变化太快，跳过
+                                            if let Some(vol) = crate::market::volatility::exceeds_filter(market_symbol) {
+                                                debug!(
+                                                    "🌪️ 实现波动率过高，跳过套利 | 市场:{} | 小时波动率:{:.4}",
+                                                    market_display,
+                                                    vol
+                                                );
+                                                utils::metrics::incr("vol_filter_skipped");
+                                                continue; // 跳过这个套利机会
                                             }
-                                            
-                                            // 检查是否接近市场结束时间（如果配置了停止时间）
-                                            if config.stop_arbitrage_before_end_minutes > 0 {
-                                                if let Some(market_info) = market_map.get(&pair.market_id) {
-                                                    use chrono::Utc;
-                                                    let now = Utc::now();
-                                                    let time_until_end = market_info.end_date.signed_duration_since(now);
-                                                    let minutes_until_end = time_until_end.num_minutes();
-                                                    
-                                                    if minutes_until_end <= config.stop_arbitrage_before_end_minutes as i64 {
+
+                                            // 拒单处置策略（REJECT_POLICY）熔断或暂停了该市场
+                                            if crate::trading::reject_policy::blocked(&[opp.yes_token_id, opp.no_token_id]) {
+                                                debug!("⏸️ 拒单处置策略暂停开仓，跳过 | 市场:{}", market_display);
+                                                utils::metrics::incr("reject_policy_blocked");
+                                                continue; // 跳过这个套利机会
+                                            }
+
+                                            // 期望收益：P(双腿成交) × 价差 − P(单边成交) × 单边处理成本，低于下限不下单
+                                            let fill_bucket = match fill_model.as_ref() {
+                                                Some(model) => {
+                                                    let edge = dec!(1.0) - total_price;
+                                                    let age = opportunity_since
+                                                        .get(&pair.market_id)
+                                                        .map(|t| t.elapsed())
+                                                        .unwrap_or_default();
+                                                    let (bucket, ev) = model.evaluate(edge, age);
+                                                    if ev < model.min_ev() {
+                                                        utils::metrics::incr("ev_rejected");
                                                         debug!(
-                                                            "⏰ 接近市场结束时间，跳过套利执行 | 市场:{} | 距离结束:{}分钟 | 停止阈值:{}分钟",
+                                                            "⏸️ 期望收益不足，跳过 | 市场:{} | 价差:{:.4} | 存续:{}ms | EV:{:.4}/份",
                                                             market_display,
-                                                            minutes_until_end,
-                                                            config.stop_arbitrage_before_end_minutes
+                                                            edge,
+                                                            age.as_millis(),
+                                                            ev
                                                         );
                                                         continue; // 跳过这个套利机会
                                                     }
+                                                    Some(bucket)
                                                 }
-                                            }
-                                            
+                                                None => None,
+                                            };
+
                                             // 计算订单成本（USD）
-                                            // 使用套利机会中的实际可用数量，但不超过配置的最大订单大小
+                                            // 使用套利机会中的实际可用数量，但不超过该币种的最大订单大小
                                             use rust_decimal::Decimal;
-                                            let max_order_size = Decimal::try_from(config.max_order_size_usdc).unwrap_or(dec!(100.0));
-                                            let order_size = opp.yes_size.min(opp.no_size).min(max_order_size);
-                                            let yes_cost = opp.yes_ask_price * order_size;
-                                            let no_cost = opp.no_ask_price * order_size;
-                                            let total_cost = yes_cost + no_cost;
-                                            
-                                            // 检查风险敞口限制
+                                            let max_order_size = Decimal::try_from(config.max_order_size_for(&symbol_key)).unwrap_or(dec!(100.0));
+                                            let mut order_size = trading::orders::jitter_size(
+                                                opp.yes_size.min(opp.no_size).min(max_order_size),
+                                                config.order_size_jitter_pct,
+                                            );
                                             let position_tracker = _risk_manager.position_tracker();
-                                            let current_exposure = position_tracker.calculate_exposure();
-                                            
-                                            if position_tracker.would_exceed_limit(yes_cost, no_cost) {
-                                                warn!(
-                                                    "⚠️ 风险敞口超限，拒绝执行套利交易 | 市场:{} | 当前敞口:{:.2} USD | 订单成本:{:.2} USD | 限制:{:.2} USD",
-                                                    market_display,
-                                                    current_exposure,
-                                                    total_cost,
-                                                    position_tracker.max_exposure()
-                                                );
-                                                continue; // 跳过这个套利机会
+
+                                            // 按现有持仓限制成对数量：成交后可 merge 数量 min(YES, NO) 不超过单市场上限
+                                            if config.max_mergeable_per_market > 0.0 {
+                                                let cap = Decimal::try_from(config.max_mergeable_per_market).unwrap_or(dec!(0));
+                                                let (yes_held, no_held) = position_tracker.get_pair_positions(opp.yes_token_id, opp.no_token_id);
+                                                let room = (cap - yes_held.min(no_held).max(dec!(0))).round_dp(2);
+                                                if room <= dec!(0) {
+                                                    debug!(
+                                                        "📦 成对持仓已达单市场上限，跳过 | 市场:{} | YES:{} NO:{} | 上限:{}",
+                                                        market_display, yes_held, no_held, cap
+                                                    );
+                                                    utils::metrics::incr("inventory_cap_skipped");
+                                                    continue;
+                                                }
+                                                if room < order_size {
+                                                    debug!(
+                                                        "📦 按成对持仓上限缩小下单数量 | 市场:{} | {} → {}",
+                                                        market_display, order_size, room
+                                                    );
+                                                    order_size = room;
+                                                }
+                                            }
+                                            if let Some(variant) = ab_variant {
+                                                order_size = crate::risk::ab_test::cap_size(variant, order_size);
+                                            }
+                                            // 交易所最小下单数量：对齐数量步长，低于任一腿的最小数量则跳过（提交必被拒）
+                                            match crate::market::rules::order_size(&[opp.yes_token_id, opp.no_token_id], order_size) {
+                                                Some(size) => order_size = size,
+                                                None => {
+                                                    debug!(
+                                                        "📏 下单数量低于交易所最小数量，跳过 | 市场:{} | 数量:{} | 最小:{}",
+                                                        market_display,
+                                                        order_size,
+                                                        crate::market::rules::min_order_size(opp.yes_token_id)
+                                                            .max(crate::market::rules::min_order_size(opp.no_token_id))
+                                                    );
+                                                    utils::metrics::incr("min_size_skipped");
+                                                    continue; // 跳过这个套利机会
+                                                }
                                             }
+
+                                            // 统一风控检查：执行阈值、YES/NO 价格下限、临近结束、风险敞口（通过即预留额度）
+                                            let proposal = crate::risk::approval::Proposal {
+                                                opportunity: &opp,
+                                                order_size,
+                                                execution_threshold: crate::risk::ab_test::threshold_for(ab_variant, execution_threshold),
+                                                market_end: market_info.map(|m| m.end_date),
+                                            };
+                                            // 预留在跳过（continue）时随丢弃归还，下单后在执行任务中提交
+                                            let (current_exposure, total_cost, reservation) = match _risk_manager.approve(&proposal).await {
+                                                Ok(approval) => (approval.current_exposure, approval.total_cost, approval.reservation),
+                                                Err(veto) => {
+                                                    report_veto(&veto, &market_display, "套利");
+                                                    continue; // 跳过这个套利机会
+                                                }
+                                            };
                                             
                                             // 检查持仓平衡（使用本地缓存，零延迟）
                                             if position_balancer.should_skip_arbitrage(opp.yes_token_id, opp.no_token_id) {
@@ -759,12 +2283,23 @@ async fn main() -> Result<()> {
                                                 continue; // 跳过这个套利机会
                                             }
 
+                                            // A/B：占用所分配变体的预算
+                                            if let Some(variant) = ab_variant {
+                                                if !crate::risk::ab_test::try_reserve(variant, total_cost) {
+                                                    utils::metrics::incr("ab_budget_skipped");
+                                                    continue; // 该变体本窗口预算已用完
+                                                }
+                                            }
+
                                             // 检查交易间隔限制：两次套利之间至少 3 秒
                                             {
                                                 let mut guard = last_trade_time.lock().await;
                                                 if let Some(last) = *guard {
                                                     let elapsed = last.elapsed();
                                                     if elapsed < MIN_TRADE_INTERVAL {
+                                                        if let Some(variant) = ab_variant {
+                                                            crate::risk::ab_test::release(variant, total_cost);
+                                                        }
                                                         debug!(
                                                             "⏱️ 交易间隔不足 3 秒，跳过 | 市场:{} | 距上次:{:.2}秒",
                                                             market_display,
@@ -775,6 +2310,7 @@ async fn main() -> Result<()> {
                                                 }
                                                 *guard = Some(Instant::now());
                                             }
+                                            latency::record(Stage::Risk, risk_start.elapsed());
                                             
                                             info!(
                                                 "⚡ 执行套利交易 | 市场:{} | 利润:{:.2}% | 下单数量:{}份 | 订单成本:{:.2} USD | 当前敞口:{:.2} USD",
@@ -784,27 +2320,188 @@ async fn main() -> Result<()> {
                                                 total_cost,
                                                 current_exposure
                                             );
-                                            // 简化敞口：只要执行套利就增加敞口，不管是否成交
-                                            let _pt = _risk_manager.position_tracker();
-                                            _pt.update_exposure_cost(opp.yes_token_id, opp.yes_ask_price, order_size);
-                                            _pt.update_exposure_cost(opp.no_token_id, opp.no_ask_price, order_size);
+                                            crate::monitor::shadow::record_live(&market_display, opp.yes_ask_price + opp.no_ask_price, order_size);
                                             
                                             // 套利执行：只要总价 <= 阈值即执行，不因涨跌组合跳过；涨跌仅用于滑点分配（仅下降=second，上涨与持平=first）
                                             // 克隆需要的变量到独立任务中（涨跌方向用于按方向分配滑点）
                                             let executor_clone = executor.clone();
                                             let risk_manager_clone = _risk_manager.clone();
-                                            let opp_clone = opp.clone();
+                                            let hedge_monitor_clone = hedge_monitor.clone();
+                                            let immediate_merger_clone = immediate_merger.clone();
+                                            let fill_model_clone = fill_model.clone();
+                                            let adaptive_spread_clone = adaptive_spread.clone();
+                                            let queue_tracker_clone = queue_tracker.clone();
+                                            let maker_fallback_clone = maker_fallback.clone();
+                                            let order_store_clone = order_store.clone();
+                                            let snipe_detector_clone = snipe_detector.clone();
+                                            let symbol_spread_clone = symbol_spread.clone();
+                                            let symbol_key_clone = symbol_key.clone();
+                                            let resting_lifetime = executor.resting_lifetime().flatten();
+                                            let execution_mode = config.execution_mode;
+                                            let sequenced_fee = Decimal::try_from(config.sequenced_fee_per_share).unwrap_or(dec!(0));
+                                            // 顺序执行第二腿挂单最晚撤单时间：与 buy_missing 硬截止一致，撤单后剩余缺口仍有时间吃单补齐
+                                            let sequenced_cancel_before_end =
+                                                chrono::Duration::seconds(config.hedge_market_order_before_end_secs as i64);
+                                            let market_display_clone = market_display.clone();
+                                            let market_end_clone = market_end;
+                                            let mut opp_clone = opp.clone();
+                                            // 执行器按其硬上限再截一次，这里先按币种上限截断
+                                            opp_clone.yes_size = order_size;
+                                            opp_clone.no_size = order_size;
                                             let yes_dir_s = yes_dir.to_string();
                                             let no_dir_s = no_dir.to_string();
                                             
                                             // 使用 tokio::spawn 异步执行套利交易，不阻塞订单簿更新处理
                                             tokio::spawn(async move {
                                                 // 执行套利交易（滑点：仅下降=second，上涨与持平=first）
-                                                match executor_clone.execute_arbitrage_pair(&opp_clone, &yes_dir_s, &no_dir_s).await {
+                                                let execution = match execution_mode {
+                                                    ExecutionMode::Pair => {
+                                                        executor_clone
+                                                            .execute_arbitrage_pair(&opp_clone, &symbol_key_clone, &yes_dir_s, &no_dir_s)
+                                                            .await
+                                                    }
+                                                    ExecutionMode::Sequenced => {
+                                                        executor_clone
+                                                            .execute_sequenced_pair(&opp_clone, &symbol_key_clone, &yes_dir_s, &no_dir_s, sequenced_fee)
+                                                            .await
+                                                    }
+                                                };
+                                                settle_reservation(reservation, &opp_clone, execution.as_ref().err());
+                                                // 吃单落空：可选挂出 maker 兜底单，占用 maker 兜底额度（挂出后按已下单计入敞口）
+                                                if let (Err(e), Some(fallback)) = (&execution, maker_fallback_clone.as_ref()) {
+                                                    if crate::trading::executor::nothing_filled(e) {
+                                                        let maker_cost = opp_clone.yes_ask_price * opp_clone.yes_size
+                                                            + opp_clone.no_ask_price * opp_clone.no_size;
+                                                        match risk_manager_clone.approve_leg(Strategy::MakerFallback, maker_cost).await {
+                                                            Ok(approval) => {
+                                                                fallback
+                                                                    .place(&executor_clone, &opp_clone, &market_display_clone, market_end_clone, approval.reservation)
+                                                                    .await;
+                                                            }
+                                                            Err(veto) => report_veto(&veto, &market_display_clone, "maker 兜底"),
+                                                        }
+                                                    }
+                                                }
+                                                match execution {
                                                     Ok(result) => {
+                                                        latency::record(Stage::Ack, book_received.elapsed());
+                                                        events::publish(BotEvent::executed(&result, opp_clone.market_id, &market_display_clone));
+                                                        executor_clone.record_leg_fills(&symbol_key_clone, &result);
+                                                        if let Some(variant) = ab_variant {
+                                                            crate::risk::ab_test::record_fill(
+                                                                variant,
+                                                                &market_display_clone,
+                                                                result.yes_filled,
+                                                                result.no_filled,
+                                                                opp_clone.yes_ask_price,
+                                                                opp_clone.no_ask_price,
+                                                            );
+                                                        }
                                                         // 先保存 pair_id，因为 result 会被移动
                                                         let pair_id = result.pair_id.clone();
+                                                        let both_sides_filled = result.yes_filled > dec!(0) && result.no_filled > dec!(0);
+                                                        let outcome = FillOutcome::from_fills(result.yes_filled, result.no_filled);
+                                                        if let (Some(model), Some(bucket)) = (fill_model_clone.as_ref(), fill_bucket) {
+                                                            model.record(bucket, outcome);
+                                                        }
+                                                        if let Some(adaptive) = adaptive_spread_clone.as_ref() {
+                                                            adaptive.record(outcome);
+                                                        }
+                                                        if let Some(learner) = symbol_spread_clone.as_ref() {
+                                                            let edge = dec!(1) - opp_clone.yes_ask_price - opp_clone.no_ask_price;
+                                                            learner.record(&symbol_key_clone, edge, outcome);
+                                                        }
+                                                        if let Some(detector) = snipe_detector_clone.as_ref() {
+                                                            detector.record(opp_clone.market_id, &market_display_clone, outcome);
+                                                        }
+                                                        // 冰山挂单（pair 模式）：两腿以 FAK 提交，未成交部分按显示数量分批挂出（GTC 最长挂到市场结束）
+                                                        let iceberg_display = executor_clone
+                                                            .iceberg_display()
+                                                            .filter(|_| execution_mode == ExecutionMode::Pair);
+                                                        if let Some(display) = iceberg_display {
+                                                            let lifetime = resting_lifetime.unwrap_or_else(|| {
+                                                                (market_end_clone - chrono::Utc::now()).to_std().unwrap_or_default()
+                                                            });
+                                                            for (token_id, price, rest) in [
+                                                                (opp_clone.yes_token_id, result.yes_price, result.yes_size - result.yes_filled),
+                                                                (opp_clone.no_token_id, result.no_price, result.no_size - result.no_filled),
+                                                            ] {
+                                                                if rest > dec!(0) {
+                                                                    tokio::spawn(trading::iceberg::run(
+                                                                        executor_clone.clone(),
+                                                                        token_id,
+                                                                        price,
+                                                                        rest,
+                                                                        display,
+                                                                        lifetime,
+                                                                    ));
+                                                                }
+                                                            }
+                                                        } else if let Some(queue) = queue_tracker_clone.as_ref().filter(|_| !result.child_orders.is_empty()) {
+                                                            // 拆单：每笔子单各自挂单，逐笔跟踪排队位置
+                                                            for child in &result.child_orders {
+                                                                queue.track(
+                                                                    &child.order_id,
+                                                                    child.token_id,
+                                                                    child.price,
+                                                                    child.size - child.filled,
+                                                                    resting_lifetime,
+                                                                );
+                                                            }
+                                                        } else if let Some(queue) = queue_tracker_clone.as_ref() {
+                                                            // 未完全成交的腿会以下单限价挂单，开始跟踪其排队位置
+                                                            queue.track(
+                                                                &result.yes_order_id,
+                                                                opp_clone.yes_token_id,
+                                                                result.yes_price,
+                                                                result.yes_size - result.yes_filled,
+                                                                resting_lifetime,
+                                                            );
+                                                            queue.track(
+                                                                &result.no_order_id,
+                                                                opp_clone.no_token_id,
+                                                                result.no_price,
+                                                                result.no_size - result.no_filled,
+                                                                resting_lifetime,
+                                                            );
+                                                        }
+                                                        if result.yes_filled > dec!(0) || result.no_filled > dec!(0) {
+                                                            utils::tui::record_fill(
+                                                                &market_display_clone,
+                                                                result.yes_filled,
+                                                                opp_clone.yes_ask_price,
+                                                                result.no_filled,
+                                                                opp_clone.no_ask_price,
+                                                            );
+                                                        }
                                                         
+                                                        // 顺序执行：第二腿未全部成交时留在簿上（GTC/GTD），登记到 OrderStore 并跟踪后续成交
+                                                        let resting_second_leg = if execution_mode == ExecutionMode::Sequenced {
+                                                            let now = chrono::Utc::now();
+                                                            let mut cancel_at = market_end_clone - sequenced_cancel_before_end;
+                                                            if let Some(lifetime) = resting_lifetime {
+                                                                cancel_at = cancel_at.min(now + chrono::Duration::from_std(lifetime).unwrap_or_default());
+                                                            }
+                                                            [
+                                                                (&result.yes_order_id, opp_clone.yes_token_id, result.yes_price, result.yes_size, result.yes_filled),
+                                                                (&result.no_order_id, opp_clone.no_token_id, result.no_price, result.no_size, result.no_filled),
+                                                            ]
+                                                            .into_iter()
+                                                            .find(|(order_id, _, _, size, filled)| !order_id.is_empty() && filled < size)
+                                                            .filter(|_| cancel_at > now)
+                                                            .map(|(order_id, token_id, price, size, _)| TrackedOrder {
+                                                                order_id: order_id.clone(),
+                                                                market_id: opp_clone.market_id,
+                                                                token_id,
+                                                                price,
+                                                                size,
+                                                                cancel_at,
+                                                                source: "sequenced_second_leg".to_string(),
+                                                            })
+                                                        } else {
+                                                            None
+                                                        };
+
                                                         // 注册到风险管理器（传入价格信息以计算风险敞口）
                                                         risk_manager_clone.register_order_pair(
                                                             result,
@@ -815,28 +2512,61 @@ async fn main() -> Result<()> {
                                                             opp_clone.no_ask_price,
                                                         );
 
-                                                        // 处理风险恢复
-                                                        // 对冲策略已暂时关闭，买进单边不做任何处理
+                                                        // 第二腿挂单期间的成交随时计入订单对与持仓；挂单结束（全部成交 / 到期撤单）后再处理风险恢复，
+                                                        // 避免对仍在簿上的一腿重复买入
+                                                        let mut both_sides_filled = both_sides_filled;
+                                                        if let Some(order) = resting_second_leg {
+                                                            order_store_clone.insert(order.clone());
+                                                            info!(
+                                                                "⏳ 顺序执行第二腿挂单跟踪中 | 订单对ID:{} | 价格:{} | 数量:{} | 撤单时间:{}",
+                                                                &pair_id[..8],
+                                                                order.price,
+                                                                order.size,
+                                                                order.cancel_at.format("%H:%M:%S")
+                                                            );
+                                                            let filled = order_store_clone
+                                                                .follow(&executor_clone, &order, |filled| {
+                                                                    risk_manager_clone.record_late_fill(&pair_id, order.token_id, filled)
+                                                                })
+                                                                .await;
+                                                            both_sides_filled |= filled > dec!(0);
+                                                        }
+
+                                                        // 双边都有成交：可选立即 merge 该市场
+                                                        if both_sides_filled {
+                                                            if let Some(merger) = immediate_merger_clone.as_ref() {
+                                                                merger.trigger(opp_clone.market_id);
+                                                            }
+                                                        }
+
+                                                        // 处理风险恢复：按 HEDGE_POLICY 买入缺失腿、监测多出腿或持有
                                                         match risk_manager_clone.handle_order_pair(&pair_id).await {
-                                                            Ok(action) => {
-                                                                // 对冲策略已关闭，不再处理MonitorForExit和SellExcess
-                                                                match action {
-                                                                    crate::risk::recovery::RecoveryAction::None => {
-                                                                        // 正常情况，无需处理
-                                                                    }
-                                                                    crate::risk::recovery::RecoveryAction::MonitorForExit { .. } => {
-                                                                        info!("单边成交，但对冲策略已关闭，不做处理");
+                                                            Ok(action) => match action {
+                                                                crate::risk::recovery::RecoveryAction::None => {
+                                                                    // 正常情况，无需处理
+                                                                }
+                                                                mut action @ crate::risk::recovery::RecoveryAction::MonitorForExit { .. } => {
+                                                                    if let crate::risk::recovery::RecoveryAction::MonitorForExit { market_display, .. } = &mut action {
+                                                                        *market_display = market_display_clone;
                                                                     }
-                                                                    crate::risk::recovery::RecoveryAction::SellExcess { .. } => {
-                                                                        info!("部分成交不平衡，但对冲策略已关闭，不做处理");
+                                                                    if let Err(e) = hedge_monitor_clone.add_position(&action) {
+                                                                        error!("添加对冲监测失败: {}", e);
                                                                     }
-                                                                    crate::risk::recovery::RecoveryAction::ManualIntervention { reason } => {
-                                                                        warn!("需要手动干预: {}", reason);
+                                                                }
+                                                                action @ crate::risk::recovery::RecoveryAction::BuyMissing { .. } => {
+                                                                    if let Err(e) = hedge_monitor_clone.buy_missing(&action, market_end).await {
+                                                                        error!("买入缺失腿失败 | 市场:{} | {}", market_display_clone, e);
                                                                     }
                                                                 }
-                                                            }
+                                                                crate::risk::recovery::RecoveryAction::SellExcess { .. } => {
+                                                                    info!("部分成交不平衡，由对冲监测处理");
+                                                                }
+                                                                crate::risk::recovery::RecoveryAction::ManualIntervention { reason } => {
+                                                                    warn!("{}", tr!(Msg::ManualIntervention, reason));
+                                                                }
+                                                            },
                                                             Err(e) => {
-                                                                error!("风险处理失败: {}", e);
+                                                                error!("{}", tr!(Msg::RiskHandleFailed, e));
                                                             }
                                                         }
                                                     }
@@ -848,32 +2578,144 @@ async fn main() -> Result<()> {
                                                             // 错误信息已经格式化好了，直接使用
                                                             error!("{}", error_msg);
                                                         } else {
-                                                            error!("执行套利交易失败: {}", error_msg);
+                                                            error!("{}", tr!(Msg::ExecuteFailed, error_msg));
                                                         }
                                                     }
                                                 }
-                                            });
+                                            }.instrument(attempt_span));
                                         }
                                     }
                                 }
                             }
                         }
                         Some(Err(e)) => {
-                            error!(error = %e, "订单簿更新错误");
+                            error!(error = %e, "{}", tr!(Msg::StreamError));
+                            spawn_fill_backfill(&background, &_risk_manager, &executor, "订单簿流错误");
                             // 流错误，重新创建流
                             break;
                         }
                         None => {
-                            warn!("订单簿流结束，重新创建");
+                            warn!("{}", tr!(Msg::StreamEnded));
+                            spawn_fill_backfill(&background, &_risk_manager, &executor, "订单簿流结束");
                             break;
                         }
                     }
                 }
 
+                // 市场不可交易：退订、撤单并排除出检测（重建订单簿流以真正退订）
+                Some((dead_market_id, reason)) = async {
+                    match dead_markets.as_mut() {
+                        Some(rx) => rx.recv().await,
+                        None => futures::future::pending().await,
+                    }
+                } => {
+                    let Some(dead_market) = market_map.remove(&dead_market_id) else {
+                        continue;
+                    };
+                    utils::control::publish_markets(&market_map);
+                    market_token_map.remove(&dead_market_id);
+                    warn!(
+                        "🚫 市场不可交易，退订并撤单 | 市场:{} | 原因:{}",
+                        dead_market.slug,
+                        reason
+                    );
+
+                    let executor_cancel = executor.clone();
+                    let tokens = [dead_market.yes_token_id, dead_market.no_token_id];
+                    let slug = dead_market.slug.clone();
+                    background.spawn(async move {
+                        match executor_cancel.cancel_orders_for_tokens(&tokens).await {
+                            Ok(0) => {}
+                            Ok(n) => info!("✅ 已取消不可交易市场的 {} 个挂单 | 市场:{}", n, slug),
+                            Err(e) => warn!(error = %e, "取消不可交易市场挂单失败 | 市场:{}", slug),
+                        }
+                    });
+
+                    drop(stream);
+                    monitor.remove_market(&dead_market_id);
+                    if monitor.market_count() == 0 {
+                        info!("本窗口所有市场均已不可交易，等待下一个窗口");
+                        exhausted_window = Some(current_window_timestamp);
+                        break;
+                    }
+                    stream = match monitor.create_orderbook_stream() {
+                        Ok(stream) => stream,
+                        Err(e) => {
+                            error!(error = %e, "{}", tr!(Msg::StreamRebuildFailed));
+                            break;
+                        }
+                    };
+                }
+
+                // 窗口内新发现的市场：加入映射并重建订单簿流订阅
+                Some(new_markets) = async {
+                    match late_markets.as_mut() {
+                        Some(rx) => rx.recv().await,
+                        None => futures::future::pending().await,
+                    }
+                } => {
+                    // 交易规则已在重新发现任务中登记；价差采样、中间价导出与持仓老化检查同样纳入新市场
+                    if config.spread_sample_enabled {
+                        crate::monitor::spread_sampler::spawn(window_books.clone(), &new_markets, window_end);
+                    }
+                    crate::monitor::mid_export::add_markets(current_window_timestamp, &new_markets);
+                    if let Some(aging) = position_aging.as_ref() {
+                        for market in &new_markets {
+                            aging.note_market(market);
+                        }
+                    }
+                    drop(stream);
+                    for market in new_markets {
+                        info!("🆕 窗口内发现新市场，即时订阅 | 市场:{}", market.slug);
+                        if let Err(e) = monitor.subscribe_market(&market) {
+                            error!(error = %e, market_id = %market.market_id, "订阅市场失败");
+                            continue;
+                        }
+                        if let Some(watcher) = status_watcher.as_ref() {
+                            watcher.watch(&market);
+                        }
+                        market_token_map.insert(market.market_id, (market.yes_token_id, market.no_token_id));
+                        market_map.insert(market.market_id, market);
+                    }
+                    utils::control::publish_markets(&market_map);
+                    stream = match monitor.create_orderbook_stream() {
+                        Ok(stream) => stream,
+                        Err(e) => {
+                            error!(error = %e, "{}", tr!(Msg::StreamRebuildFailed));
+                            break;
+                        }
+                    };
+                }
+
+                // 订单簿流静默超过阈值：主动重建订单簿流
+                silence = async {
+                    match ws_watchdog.as_mut() {
+                        Some(watchdog) => watchdog.stale().await,
+                        None => futures::future::pending().await,
+                    }
+                } => {
+                    warn!(silence_secs = silence.as_secs(), "{}", tr!(Msg::WsStreamStale));
+                    if let Some(watchdog) = ws_watchdog.as_mut() {
+                        watchdog.reset();
+                    }
+                    spawn_fill_backfill(&background, &_risk_manager, &executor, "订单簿流静默");
+                    drop(stream);
+                    stream = match monitor.create_orderbook_stream() {
+                        Ok(stream) => stream,
+                        Err(e) => {
+                            error!(error = %e, "{}", tr!(Msg::StreamRebuildFailed));
+                            break;
+                        }
+                    };
+                }
+
                 // 定时仓位平衡任务
                 _ = async {
                     if let Some(ref mut timer) = balance_timer {
                         timer.tick().await;
+                        if !utils::leader::is_leader() {
+                            return;
+                        }
                         if let Err(e) = position_balancer.check_and_balance_positions(&market_token_map).await {
                             warn!(error = %e, "仓位平衡检查失败");
                         }
@@ -884,7 +2726,21 @@ async fn main() -> Result<()> {
                     // 仓位平衡任务已执行
                 }
 
-                // 定期检查是否进入新的1小时窗口（每5秒检查一次）
+                // 定时零头持仓清理（后台执行，不阻塞订单簿处理）
+                _ = async {
+                    match dust_timer.as_mut() {
+                        Some(timer) => {
+                            timer.tick().await;
+                        }
+                        None => futures::future::pending::<()>().await,
+                    }
+                } => {
+                    if let Some(sweeper) = dust_sweeper.as_ref().filter(|_| utils::leader::is_leader()) {
+                        sweeper.spawn_sweep(&window_books, &background);
+                    }
+                }
+
+                // 定期检查是否进入新的调度窗口（每5秒检查一次）
                 _ = sleep(Duration::from_secs(5)) => {
                     let now = Utc::now();
                     let new_window_timestamp = MarketDiscoverer::calculate_current_window_timestamp(now);
@@ -894,8 +2750,53 @@ async fn main() -> Result<()> {
                         info!(
                             old_window = current_window_timestamp,
                             new_window = new_window_timestamp,
-                            "检测到新的1小时窗口，准备取消旧订阅并切换到新窗口"
+                            "{}",
+                            tr!(Msg::NewWindow)
                         );
+                        latency::report_and_reset(current_window_timestamp);
+                        crate::trading::rejection::report_and_reset(current_window_timestamp);
+                        crate::monitor::shadow::report_and_reset(current_window_timestamp);
+                        crate::risk::ab_test::report_and_reset(current_window_timestamp);
+                        // 各策略额度用量（只输出设置了额度或有用量的策略）
+                        let position_tracker = _risk_manager.position_tracker();
+                        for usage in Strategy::ALL.map(|strategy| position_tracker.budget_usage(strategy)) {
+                            if usage.limit.is_some() || usage.used() > dec!(0) {
+                                info!(
+                                    "💼 策略额度 | 策略:{} | 已提交:{:.2} USD | 已预留:{:.2} USD | 额度:{}",
+                                    usage.strategy.as_str(),
+                                    usage.committed,
+                                    usage.reserved,
+                                    usage.limit.map(|l| format!("{:.2} USD", l)).unwrap_or_else(|| "不单独限制".to_string())
+                                );
+                            }
+                        }
+                        if let Some(strategy) = directional.as_ref() {
+                            strategy.reset();
+                        }
+                        utils::tui::clear_books();
+                        // 上一窗口的市场即将结束，其上仍挂着的订单只剩风险：订阅新窗口前先撤掉
+                        // （跨越本窗口的较长周期市场仍在交易，其挂单保留）
+                        if config.cancel_on_window_switch {
+                            let tokens: Vec<U256> = market_map
+                                .values()
+                                .filter(|m| ends_with_window(m, window_end))
+                                .flat_map(|m| [m.yes_token_id, m.no_token_id])
+                                .collect();
+                            match tokio::time::timeout(
+                                Duration::from_secs(10),
+                                executor.cancel_orders_for_tokens(&tokens),
+                            )
+                            .await
+                            {
+                                Ok(Ok(0)) => {}
+                                Ok(Ok(n)) => {
+                                    info!("🧹 窗口切换：已取消上一窗口市场的 {} 个挂单", n);
+                                    utils::metrics::add("window_switch_cancelled", n as u64);
+                                }
+                                Ok(Err(e)) => warn!(error = %e, "窗口切换撤单失败"),
+                                Err(_) => warn!("窗口切换撤单超时（10秒），继续切换"),
+                            }
+                        }
                         // 先drop stream以释放对monitor的借用，然后清理旧的订阅
                         drop(stream);
                         monitor.clear();
@@ -906,7 +2807,7 @@ async fn main() -> Result<()> {
         }
 
         // monitor 会在循环结束时自动 drop，无需手动清理
-        info!("当前窗口监控结束，刷新市场进入下一轮");
+        info!("{}", tr!(Msg::WindowDone));
     }
 }
 
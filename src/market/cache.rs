@@ -0,0 +1,161 @@
+//! 市场元数据的落盘缓存：按查询 slug 保存已发现的 MarketInfo（token id、结束时间等），
+//! 同一窗口内重启时直接从缓存订阅，无需重新发现；结束时间已过的条目视为失效。
+//! 同时记录短期的「未找到」条目（负缓存），避免重启后立刻重复查询尚未创建的市场。
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use polymarket_client_sdk::types::{B256, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+use tracing::{debug, warn};
+
+use super::discoverer::MarketInfo;
+use super::window::WindowDuration;
+
+/// 负缓存有效期（秒）：在此时间内不重复查询确认不存在的 slug
+const NEGATIVE_TTL_SECS: i64 = 15;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedMarket {
+    market_id: String,
+    slug: String,
+    yes_token_id: String,
+    no_token_id: String,
+    title: String,
+    end_date: DateTime<Utc>,
+    crypto_symbol: String,
+    /// 系列周期（秒）；旧版缓存没有此字段，读出为 0（周期未知）
+    #[serde(default)]
+    cadence_secs: i64,
+}
+
+impl From<&MarketInfo> for CachedMarket {
+    fn from(m: &MarketInfo) -> Self {
+        Self {
+            market_id: m.market_id.to_string(),
+            slug: m.slug.clone(),
+            yes_token_id: m.yes_token_id.to_string(),
+            no_token_id: m.no_token_id.to_string(),
+            title: m.title.clone(),
+            end_date: m.end_date,
+            crypto_symbol: m.crypto_symbol.clone(),
+            cadence_secs: m.window.map(|w| w.secs()).unwrap_or(0),
+        }
+    }
+}
+
+impl CachedMarket {
+    fn to_market_info(&self) -> Option<MarketInfo> {
+        Some(MarketInfo {
+            market_id: B256::from_str(&self.market_id).ok()?,
+            slug: self.slug.clone(),
+            yes_token_id: U256::from_str(&self.yes_token_id).ok()?,
+            no_token_id: U256::from_str(&self.no_token_id).ok()?,
+            title: self.title.clone(),
+            end_date: self.end_date,
+            crypto_symbol: self.crypto_symbol.clone(),
+            window: WindowDuration::from_secs(self.cadence_secs),
+        })
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheFile {
+    /// slug -> 市场元数据
+    #[serde(default)]
+    markets: HashMap<String, CachedMarket>,
+    /// slug -> 最近一次确认未找到的时间
+    #[serde(default)]
+    missing: HashMap<String, DateTime<Utc>>,
+}
+
+pub struct MarketCache {
+    /// None 表示只在内存中缓存，不落盘
+    path: Option<PathBuf>,
+    state: Mutex<CacheFile>,
+}
+
+impl MarketCache {
+    /// 从文件加载缓存；path 为空字符串时不落盘，文件不存在或损坏时从空缓存开始
+    pub fn load(path: &str) -> Self {
+        let path = (!path.trim().is_empty()).then(|| PathBuf::from(path));
+        let state = path
+            .as_ref()
+            .and_then(|p| std::fs::read(p).ok())
+            .and_then(|body| match serde_json::from_slice::<CacheFile>(&body) {
+                Ok(file) => Some(file),
+                Err(e) => {
+                    warn!(error = %e, "市场缓存文件损坏，忽略");
+                    None
+                }
+            })
+            .unwrap_or_default();
+        Self {
+            path,
+            state: Mutex::new(state),
+        }
+    }
+
+    /// 读取未过期（end_date 晚于当前时间）的缓存市场
+    pub fn get(&self, slug: &str) -> Option<MarketInfo> {
+        let state = self.state.lock().ok()?;
+        let cached = state.markets.get(slug)?;
+        if cached.end_date <= Utc::now() {
+            return None;
+        }
+        cached.to_market_info()
+    }
+
+    /// slug 是否在负缓存有效期内被确认不存在
+    pub fn is_known_missing(&self, slug: &str) -> bool {
+        let Ok(state) = self.state.lock() else {
+            return false;
+        };
+        state
+            .missing
+            .get(slug)
+            .map(|at| (Utc::now() - *at).num_seconds() < NEGATIVE_TTL_SECS)
+            .unwrap_or(false)
+    }
+
+    /// 记录一次查询结果：找到的市场按查询 slug 写入缓存（events 兜底找到的市场实际 slug 可能不同），
+    /// 未找到的 slug 写入负缓存，并清理已过期条目后落盘
+    pub fn record(&self, found: &[(String, MarketInfo)], missing: &[String]) {
+        let now = Utc::now();
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        for (slug, market) in found {
+            state.missing.remove(slug);
+            state.markets.insert(slug.clone(), CachedMarket::from(market));
+        }
+        for slug in missing {
+            state.missing.insert(slug.clone(), now);
+        }
+        state.markets.retain(|_, m| m.end_date > now);
+        state
+            .missing
+            .retain(|_, at| (now - *at).num_seconds() < NEGATIVE_TTL_SECS);
+
+        if let Some(path) = self.path.as_ref() {
+            if let Err(e) = Self::save(path, &state) {
+                debug!(error = %e, "市场缓存写盘失败");
+            }
+        }
+    }
+
+    fn save(path: &Path, state: &CacheFile) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            if !dir.as_os_str().is_empty() {
+                std::fs::create_dir_all(dir)?;
+            }
+        }
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec(state)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,26 @@
+//! 市场时区：窗口边界与 slug 中的日期时间都按该时区计算（Polymarket 加密货币系列为美东时间）。
+//! 启动时由 Config.market_timezone 设置一次，之后全局只读。
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use std::sync::OnceLock;
+
+/// 默认市场时区：美东时间（自动处理夏令时）
+pub const DEFAULT_MARKET_TIMEZONE: Tz = chrono_tz::America::New_York;
+
+static MARKET_TZ: OnceLock<Tz> = OnceLock::new();
+
+/// 设置市场时区（启动时调用一次，重复调用忽略）
+pub fn init(tz: Tz) {
+    let _ = MARKET_TZ.set(tz);
+}
+
+/// 当前市场时区，未设置时为美东时间
+pub fn market_tz() -> Tz {
+    MARKET_TZ.get().copied().unwrap_or(DEFAULT_MARKET_TIMEZONE)
+}
+
+/// 转换到市场时区
+pub fn to_market_time(time: DateTime<Utc>) -> DateTime<Tz> {
+    time.with_timezone(&market_tz())
+}
@@ -1,8 +1,17 @@
 use anyhow::Result;
-use chrono::{DateTime, Datelike, FixedOffset, Timelike, Utc};
-use polymarket_client_sdk::gamma::{Client, types::request::MarketsRequest};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use polymarket_client_sdk::gamma::Client;
 use polymarket_client_sdk::types::{B256, U256};
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
+
+use super::cache::MarketCache;
+use super::events;
+use super::gamma::ResilientGamma;
+use super::ladder::StrikeLadder;
+use super::series::MarketSeries;
+use super::window;
 
 #[derive(Debug, Clone)]
 pub struct MarketInfo {
@@ -12,157 +21,361 @@ pub struct MarketInfo {
     pub no_token_id: U256,
     pub title: String,
     pub end_date: DateTime<Utc>,
+    /// 所属系列名称（加密货币系列即币种，如 btc）
     pub crypto_symbol: String,
+    /// 所属系列的窗口周期；周期不在监控周期之列（或缓存条目缺少周期）时为 None
+    pub window: Option<window::WindowDuration>,
+}
+
+impl MarketInfo {
+    /// 按币种与周期区分的状态键（如 btc:15m）：同一币种不同周期的市场流动性与价差差异很大，
+    /// 币种覆盖、学习到的执行价差与自适应滑点都按此键分开；周期未知时为币种本身，币种为空时为空
+    pub fn symbol_key(&self) -> String {
+        match self.window {
+            Some(window) if !self.crypto_symbol.is_empty() => format!("{}:{}", self.crypto_symbol, window.as_str()),
+            _ => self.crypto_symbol.clone(),
+        }
+    }
+}
+
+/// 市场发现方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiscoveryMode {
+    /// 按模板生成 slug 查询，未命中的系列再按事件标签兜底（默认）
+    Slug,
+    /// 先按事件标签 + 窗口结束时间查询 Gamma events，未找到的系列再按 slug 猜测
+    Events,
+}
+
+impl DiscoveryMode {
+    pub fn parse(s: &str) -> Self {
+        match s.trim().to_lowercase().as_str() {
+            "events" => DiscoveryMode::Events,
+            _ => DiscoveryMode::Slug,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiscoveryMode::Slug => "slug",
+            DiscoveryMode::Events => "events",
+        }
+    }
 }
 
 pub struct MarketDiscoverer {
-    gamma_client: Client,
-    crypto_symbols: Vec<String>,
+    gamma: Arc<ResilientGamma>,
+    /// events 兜底查询使用的 Gamma 地址与 HTTP 客户端
+    gamma_url: String,
+    http: reqwest::Client,
+    series: Vec<MarketSeries>,
+    /// 落盘的市场元数据缓存，同一窗口内重启时免于重新发现
+    cache: MarketCache,
+    mode: DiscoveryMode,
+    /// 除系列自身标签外，events 查询额外使用的事件标签
+    discovery_tags: Vec<String>,
 }
 
 impl MarketDiscoverer {
-    pub fn new(crypto_symbols: Vec<String>) -> Self {
+    /// gamma_url: Gamma API 地址（Config.endpoints.gamma），无效时回退到 SDK 默认地址
+    /// series: 要监控的市场系列（Config.market_series），周期不在监控的窗口周期（WINDOW_DURATION）中的系列会被跳过
+    /// cache_path: 市场元数据缓存文件（Config.market_cache_path），空字符串表示不落盘
+    pub fn new(gamma_url: &str, series: Vec<MarketSeries>, cache_path: &str) -> Self {
+        let series = series
+            .into_iter()
+            .filter(|s| {
+                if !s.window().is_some_and(|w| window::durations().contains(&w)) {
+                    warn!(
+                        series = %s.name,
+                        cadence_secs = s.cadence_secs,
+                        "系列周期不在监控的窗口周期（WINDOW_DURATION）中，暂不监控"
+                    );
+                    return false;
+                }
+                true
+            })
+            .collect();
         Self {
-            gamma_client: Client::default(),
-            crypto_symbols,
+            gamma: Arc::new(ResilientGamma::new(Client::new(gamma_url).unwrap_or_else(|e| {
+                warn!(error = %e, gamma_url, "Gamma 地址无效，使用默认地址");
+                Client::default()
+            }))),
+            gamma_url: gamma_url.to_string(),
+            http: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            series,
+            cache: MarketCache::load(cache_path),
+            mode: DiscoveryMode::Slug,
+            discovery_tags: Vec::new(),
         }
     }
 
-    /// 计算当前1小时窗口的开始时间戳（基于ET时间）
-    /// 窗口开始时间：每小时整点（例如3am开始，4am结束）
+    /// 设置发现方式（Config.discovery_mode）与额外的事件标签（Config.discovery_tags）
+    pub fn with_discovery(mut self, mode: DiscoveryMode, tags: Vec<String>) -> Self {
+        info!(mode = mode.as_str(), tags = ?tags, "市场发现方式");
+        self.mode = mode;
+        self.discovery_tags = tags;
+        self
+    }
+
+    /// 共享的 Gamma 客户端（状态监控等复用其缓存与重试）
+    pub fn gamma(&self) -> Arc<ResilientGamma> {
+        self.gamma.clone()
+    }
+
+    /// 计算当前调度窗口的开始时间戳（基于市场时区，默认美东时间）
+    /// 调度窗口为监控周期中最短的一个，例如 1 小时窗口在每小时整点开始（3am开始，4am结束）
     pub fn calculate_current_window_timestamp(now: DateTime<Utc>) -> i64 {
-        // 将UTC时间转换为ET时间（ET = UTC-5或UTC-4，取决于夏令时）
-        // 简化处理：使用UTC-5（EST）作为基准，实际应用中可能需要更精确的DST处理
-        let et_offset = FixedOffset::east_opt(-5 * 3600).unwrap();
-        let et_time = now.with_timezone(&et_offset);
-        
-        // 构建当前小时窗口开始时间（分钟和秒都设为0）
-        let target_time = et_time
-            .with_minute(0)
-            .and_then(|t| t.with_second(0))
-            .and_then(|t| t.with_nanosecond(0))
-            .unwrap_or(et_time);
-
-        // 转换回UTC时间戳
-        target_time.with_timezone(&Utc).timestamp()
-    }
-
-    /// 计算下一个1小时窗口的开始时间戳（基于ET时间）
-    /// 窗口开始时间：每小时整点（例如3am开始，4am结束）
-    pub fn calculate_next_window_timestamp(now: DateTime<Utc>) -> i64 {
-        // 将UTC时间转换为ET时间
-        let et_offset = FixedOffset::east_opt(-5 * 3600).unwrap();
-        let et_time = now.with_timezone(&et_offset);
-        
-        // 如果当前时间正好是整点且秒数为0，使用当前小时，否则使用下一个小时
-        let target_hour = if et_time.minute() == 0 && et_time.second() == 0 {
-            et_time.hour()
-        } else {
-            et_time.hour() + 1
-        };
+        window::scheduler().window_start(now)
+    }
 
-        // 处理小时溢出（超过23点）
-        let (final_hour, day_adjustment) = if target_hour >= 24 {
-            (target_hour - 24, 1)
+    /// 计算下一个调度窗口的开始时间戳（基于市场时区，默认美东时间）
+    /// 如果当前时间正好是窗口开始，返回当前窗口，否则返回下一个窗口开始
+    pub fn calculate_next_window_timestamp(now: DateTime<Utc>) -> i64 {
+        let current = Self::calculate_current_window_timestamp(now);
+        if now.timestamp() == current && now.timestamp_subsec_nanos() == 0 {
+            current
         } else {
-            (target_hour, 0)
-        };
-
-        // 构建目标时间
-        let mut target_time = et_time
-            .with_hour(final_hour)
-            .and_then(|t| t.with_minute(0))
-            .and_then(|t| t.with_second(0))
-            .and_then(|t| t.with_nanosecond(0))
-            .unwrap_or(et_time);
-
-        // 如果需要调整天数
-        if day_adjustment > 0 {
-            target_time = target_time + chrono::Duration::days(day_adjustment);
+            Self::window_end_timestamp(current)
         }
+    }
 
-        // 转换回UTC时间戳
-        target_time.with_timezone(&Utc).timestamp()
-    }
-
-    /// 将UTC时间戳转换为ET时间的slug格式
-    /// 格式：[月]-[天]-[时][am或pm]-et
-    /// 例如：january-16-3am-et
-    fn timestamp_to_slug_format(timestamp: i64) -> String {
-        let et_offset = FixedOffset::east_opt(-5 * 3600).unwrap();
-        let utc_time = DateTime::from_timestamp(timestamp, 0)
-            .unwrap_or_else(|| Utc::now());
-        let et_time = utc_time.with_timezone(&et_offset);
-
-        // 月份名称
-        let month_names = [
-            "january", "february", "march", "april", "may", "june",
-            "july", "august", "september", "october", "november", "december"
-        ];
-        let month = month_names.get((et_time.month0()) as usize)
-            .unwrap_or(&"january");
-
-        // 日期
-        let day = et_time.day();
-
-        // 12小时制时间和am/pm
-        let hour_24 = et_time.hour();
-        let (hour_12, am_pm) = if hour_24 == 0 {
-            (12, "am")
-        } else if hour_24 < 12 {
-            (hour_24, "am")
-        } else if hour_24 == 12 {
-            (12, "pm")
-        } else {
-            (hour_24 - 12, "pm")
-        };
+    /// 调度窗口的结束时间戳（window_timestamp 为窗口开始）
+    pub fn window_end_timestamp(window_timestamp: i64) -> i64 {
+        window::scheduler().window_end(window_timestamp)
+    }
 
-        format!("{}-{}-{}{}-et", month, day, hour_12, am_pm)
+    /// 可监控的系列数（即每个窗口期望发现的市场数）
+    pub fn series_count(&self) -> usize {
+        self.series.len()
     }
 
-    /// 生成市场slug列表
-    /// 格式：[币种]-up-or-down-[月]-[天]-[时][am或pm]-et
-    /// 例如：bitcoin-up-or-down-january-16-3am-et
+    /// 生成市场slug列表（每个可监控系列一个）
+    /// 例如加密货币系列：bitcoin-up-or-down-january-16-3am-et
     pub fn generate_market_slugs(&self, timestamp: i64) -> Vec<String> {
-        let time_suffix = Self::timestamp_to_slug_format(timestamp);
-        self.crypto_symbols
+        self.series
             .iter()
-            .map(|symbol| format!("{}-up-or-down-{}", symbol, time_suffix))
+            .map(|series| series.render_slug(timestamp))
             .collect()
     }
 
-    /// 获取指定时间戳的1小时市场
+    /// 获取调度窗口 timestamp 内进行中的市场（较长周期的系列取包含该窗口的本系列窗口）
     pub async fn get_markets_for_timestamp(&self, timestamp: i64) -> Result<Vec<MarketInfo>> {
-        // 生成所有加密货币的slug
-        let slugs = self.generate_market_slugs(timestamp);
-
-        info!(timestamp, slug_count = slugs.len(), "查询市场");
-
-        // 使用Gamma API批量查询
-        let request = MarketsRequest::builder()
-            .slug(slugs.clone())
-            .build();
-
-        match self.gamma_client.markets(&request).await {
-            Ok(markets) => {
-                // 过滤并解析市场
-                let valid_markets: Vec<MarketInfo> = markets
-                    .into_iter()
-                    .filter_map(|market| self.parse_market(market))
-                    .collect();
-
-                info!(count = valid_markets.len(), "找到符合条件的市场");
-                Ok(valid_markets)
+        // 生成所有系列的slug，并记录 slug -> 系列下标 以便解析时匹配结果标签
+        // （同一币种可同时监控多个周期，系列名称可能相同，按下标区分）
+        let slug_series: HashMap<String, usize> = self
+            .series
+            .iter()
+            .enumerate()
+            .map(|(index, series)| (series.render_slug(timestamp), index))
+            .collect();
+
+        // 先查落盘缓存：命中的直接使用，负缓存期内确认不存在的暂不查询
+        let mut found_by_series: Vec<(usize, MarketInfo)> = Vec::new();
+        let mut slugs: Vec<String> = Vec::new();
+        let mut skipped_series: Vec<usize> = Vec::new();
+        for (slug, &index) in &slug_series {
+            if let Some(cached) = self.cache.get(slug) {
+                found_by_series.push((index, cached));
+            } else if self.cache.is_known_missing(slug) {
+                skipped_series.push(index);
+            } else {
+                slugs.push(slug.clone());
             }
-            Err(e) => {
-                warn!(error = %e, timestamp = timestamp, "查询市场失败，可能市场尚未创建");
-                Ok(Vec::new())
+        }
+        let cached_count = found_by_series.len();
+        if cached_count > 0 {
+            info!(count = cached_count, "从缓存加载市场");
+        }
+
+        // events 模式：先按事件标签 + 窗口结束时间查询，只对未找到的系列再按 slug 猜测
+        if self.mode == DiscoveryMode::Events {
+            let pending: Vec<usize> = slug_series
+                .iter()
+                .filter(|(slug, _)| slugs.contains(slug))
+                .map(|(_, &index)| index)
+                .collect();
+            if !pending.is_empty() {
+                let discovered = self.discover_via_events(timestamp, &pending).await;
+                if !discovered.is_empty() {
+                    info!(count = discovered.len(), "events 发现市场");
+                }
+                slugs.retain(|slug| !discovered.iter().any(|(index, _)| slug_series.get(slug) == Some(index)));
+                found_by_series.extend(discovered);
+            }
+        }
+
+        if !slugs.is_empty() {
+            info!(timestamp, slug_count = slugs.len(), "查询市场");
+
+            // 使用Gamma API批量查询（带缓存、并发去重与退避重试）
+            match self.gamma.markets_by_slugs(&slugs).await {
+                Ok(markets) => {
+                    // 过滤并解析市场
+                    found_by_series.extend(markets.into_iter().filter_map(|market| {
+                        let index = *market.slug.as_ref().and_then(|slug| slug_series.get(slug))?;
+                        Some((index, Self::parse_market(&self.series[index], market)?))
+                    }));
+                }
+                Err(e) => {
+                    warn!(error = %e, timestamp = timestamp, "查询市场失败，可能市场尚未创建");
+                }
+            }
+        }
+
+        // slug 未命中的系列：按事件标签 + 窗口结束时间查询 events 兜底（slug 命名变更或延迟创建时）
+        // events 模式下已先查询过 events，不再重复
+        let missing: Vec<usize> = (0..self.series.len())
+            .filter(|_| self.mode == DiscoveryMode::Slug)
+            .filter(|&index| !self.series[index].tag_slug.is_empty())
+            .filter(|index| !skipped_series.contains(index))
+            .filter(|&index| !found_by_series.iter().any(|(i, _)| *i == index))
+            .collect();
+        if !missing.is_empty() {
+            let fallback = self.discover_via_events(timestamp, &missing).await;
+            if !fallback.is_empty() {
+                info!(count = fallback.len(), "events 兜底发现市场");
+                found_by_series.extend(fallback);
+            }
+        }
+
+        // 按查询 slug 落盘，未找到的系列写入负缓存
+        let mut found: Vec<(String, MarketInfo)> = Vec::new();
+        let mut missing_slugs: Vec<String> = Vec::new();
+        for (slug, index) in &slug_series {
+            match found_by_series.iter().find(|(i, _)| i == index) {
+                Some((_, market)) => found.push((slug.clone(), market.clone())),
+                None if !skipped_series.contains(index) => missing_slugs.push(slug.clone()),
+                None => {}
             }
         }
+        self.cache.record(&found, &missing_slugs);
+
+        let valid_markets: Vec<MarketInfo> = found_by_series.into_iter().map(|(_, market)| market).collect();
+        info!(count = valid_markets.len(), "找到符合条件的市场");
+        Ok(valid_markets)
+    }
+
+    /// 系列要查询的事件标签：系列自身的标签，events 模式下再加上 DISCOVERY_TAGS（slug 模式的兜底只查系列标签）
+    fn tags_for<'a>(&'a self, series: &'a MarketSeries) -> impl Iterator<Item = &'a str> {
+        let extra: &[String] = if self.mode == DiscoveryMode::Events { &self.discovery_tags } else { &[] };
+        std::iter::once(series.tag_slug.as_str())
+            .chain(extra.iter().map(String::as_str))
+            .filter(|tag| !tag.is_empty())
     }
 
-    /// 解析市场信息，提取YES和NO的token_id
-    fn parse_market(&self, market: polymarket_client_sdk::gamma::types::response::Market) -> Option<MarketInfo> {
+    /// 按事件标签查询结束时间为本系列窗口结束（±60秒）的市场，并按系列名称、周期与结果标签归属到系列（返回系列下标）
+    async fn discover_via_events(&self, timestamp: i64, missing: &[usize]) -> Vec<(usize, MarketInfo)> {
+        let mut found: Vec<(usize, MarketInfo)> = Vec::new();
+        let window_end_of = |index: usize| {
+            let series = &self.series[index];
+            series.window_end(series.window_start(timestamp))
+        };
+        // 同一窗口结束时间的系列共用查询结果，每个标签查询一次
+        let mut ends: Vec<i64> = missing.iter().map(|&index| window_end_of(index)).collect();
+        ends.sort_unstable();
+        ends.dedup();
+
+        for end_timestamp in ends {
+            let Some(window_end) = DateTime::from_timestamp(end_timestamp, 0) else {
+                continue;
+            };
+            let in_query: Vec<usize> = missing
+                .iter()
+                .copied()
+                .filter(|&index| window_end_of(index) == end_timestamp)
+                .collect();
+            let mut tags: Vec<&str> = in_query.iter().flat_map(|&index| self.tags_for(&self.series[index])).collect();
+            tags.sort_unstable();
+            tags.dedup();
+
+            let margin = chrono::Duration::seconds(60);
+            let mut markets: Vec<events::EventMarket> = Vec::new();
+            for tag in tags {
+                match events::fetch_markets_by_end_date(
+                    &self.http,
+                    &self.gamma_url,
+                    tag,
+                    window_end - margin,
+                    window_end + margin,
+                )
+                .await
+                {
+                    Ok(tagged) => {
+                        for market in tagged {
+                            if !markets.iter().any(|m| m.condition_id == market.condition_id) {
+                                markets.push(market);
+                            }
+                        }
+                    }
+                    Err(e) => warn!(error = %e, tag, "events 查询失败"),
+                }
+            }
+
+            for index in in_query {
+                let series = &self.series[index];
+                let matched = markets.iter().find(|m| {
+                    !found.iter().any(|(_, f)| f.market_id == m.condition_id)
+                        && series.matches_name(&m.slug, &m.question)
+                        && series.matches_window(&m.slug)
+                        && series.outcome_indices(&m.outcomes).is_some()
+                });
+                let Some(market) = matched else {
+                    debug!(series = %series.name, "events 未找到该系列的市场");
+                    continue;
+                };
+                let Some((yes_index, no_index)) = series.outcome_indices(&market.outcomes) else {
+                    continue;
+                };
+                if market.clob_token_ids.len() != 2 {
+                    continue;
+                }
+                info!(series = %series.name, slug = %market.slug, "events 匹配到市场");
+                found.push((
+                    index,
+                    MarketInfo {
+                        market_id: market.condition_id,
+                        slug: market.slug.clone(),
+                        yes_token_id: market.clob_token_ids[yes_index],
+                        no_token_id: market.clob_token_ids[no_index],
+                        title: market.question.clone(),
+                        end_date: market.end_date,
+                        crypto_symbol: series.name.clone(),
+                        window: series.window(),
+                    },
+                ));
+            }
+        }
+        found
+    }
+
+    /// 获取指定窗口的行权价阶梯：每个阶梯系列按模板生成事件 slug，查询事件下的全部市场；
+    /// 查询失败或不足两档的阶梯跳过（阶梯是附加策略，不影响主发现流程）
+    pub async fn get_ladders_for_timestamp(&self, ladders: &[MarketSeries], timestamp: i64) -> Vec<StrikeLadder> {
+        let mut found: Vec<StrikeLadder> = Vec::new();
+        for series in ladders {
+            let event_slug = series.render_slug(timestamp);
+            match events::fetch_event_markets_by_slug(&self.http, &self.gamma_url, &event_slug).await {
+                Ok(markets) => match StrikeLadder::from_event_markets(series, &markets) {
+                    Some(ladder) => {
+                        info!(ladder = %ladder.name, strikes = ladder.strikes.len(), event = %event_slug, "发现行权价阶梯");
+                        found.push(ladder);
+                    }
+                    None => debug!(ladder = %series.name, event = %event_slug, "行权价阶梯不足两档，跳过"),
+                },
+                Err(e) => warn!(error = %e, ladder = %series.name, event = %event_slug, "查询行权价阶梯失败"),
+            }
+        }
+        found
+    }
+
+    /// 解析市场信息，按系列的结果标签提取YES和NO的token_id
+    fn parse_market(
+        series: &MarketSeries,
+        market: polymarket_client_sdk::gamma::types::response::Market,
+    ) -> Option<MarketInfo> {
         // 检查市场是否活跃、启用订单簿且接受订单
         if !market.active.unwrap_or(false) 
            || !market.enable_order_book.unwrap_or(false)
@@ -170,36 +383,24 @@ impl MarketDiscoverer {
             return None;
         }
 
-        // 检查outcomes是否为["Up", "Down"]
+        // 检查outcomes是否与系列的结果标签一致（加密货币系列为["Up", "Down"]）
         let outcomes = market.outcomes.as_ref()?;
+        let (yes_index, no_index) = series.outcome_indices(outcomes)?;
 
-        if outcomes.len() != 2 
-           || !outcomes.contains(&"Up".to_string()) 
-           || !outcomes.contains(&"Down".to_string()) {
-            return None;
-        }
-
-        // 获取clobTokenIds
+        // 获取clobTokenIds，顺序与outcomes一致
         let token_ids = market.clob_token_ids.as_ref()?;
 
         if token_ids.len() != 2 {
             return None;
         }
 
-        // 第一个是"Up"的token_id，第二个是"Down"的token_id
-        let yes_token_id = token_ids[0];
-        let no_token_id = token_ids[1];
+        let yes_token_id = token_ids[yes_index];
+        let no_token_id = token_ids[no_index];
 
         // 获取conditionId
         let market_id = market.condition_id?;
 
-        // 从slug中提取加密货币符号
         let slug = market.slug.as_ref()?;
-        let crypto_symbol = slug
-            .split('-')
-            .next()
-            .unwrap_or("")
-            .to_string();
 
         // 获取endDate
         let end_date = market.end_date?;
@@ -211,7 +412,8 @@ impl MarketDiscoverer {
             no_token_id,
             title: market.question.unwrap_or_default(),
             end_date,
-            crypto_symbol,
+            crypto_symbol: series.name.clone(),
+            window: series.window(),
         })
     }
 }
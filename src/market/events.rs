@@ -0,0 +1,145 @@
+//! Gamma events 接口：按标签与结束时间范围查询事件下的市场。DISCOVERY_MODE=events 时作为主要发现方式，
+//! 否则作为 slug 查询的兜底，在 Polymarket 调整 slug 命名或市场延迟创建时仍能找到当前窗口的市场。
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use polymarket_client_sdk::types::{B256, U256};
+use serde::Deserialize;
+use serde_json::Value;
+use std::str::FromStr;
+
+use crate::utils::rate_limit::{self, Priority};
+
+/// events 接口返回的事件（只取需要的字段）
+#[derive(Debug, Deserialize)]
+struct GammaEvent {
+    #[serde(default)]
+    markets: Vec<GammaEventMarket>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GammaEventMarket {
+    condition_id: Option<String>,
+    slug: Option<String>,
+    question: Option<String>,
+    /// 事件内的分组标题，行权价阶梯事件中为行权价（如 "105,000"）
+    group_item_title: Option<String>,
+    end_date: Option<DateTime<Utc>>,
+    /// Gamma 返回 JSON 编码的字符串（如 "[\"Up\", \"Down\"]"），也兼容直接返回数组
+    outcomes: Option<Value>,
+    clob_token_ids: Option<Value>,
+    active: Option<bool>,
+    enable_order_book: Option<bool>,
+    accepting_orders: Option<bool>,
+}
+
+/// 从 events 接口解析出的可交易市场
+#[derive(Debug, Clone)]
+pub struct EventMarket {
+    pub condition_id: B256,
+    pub slug: String,
+    pub question: String,
+    pub group_item_title: String,
+    pub end_date: DateTime<Utc>,
+    pub outcomes: Vec<String>,
+    pub clob_token_ids: Vec<U256>,
+}
+
+/// 解析字符串数组字段：既可能是 JSON 数组，也可能是 JSON 编码后的字符串
+fn string_list(value: &Value) -> Option<Vec<String>> {
+    match value {
+        Value::String(encoded) => serde_json::from_str(encoded).ok(),
+        Value::Array(items) => items
+            .iter()
+            .map(|v| v.as_str().map(str::to_string))
+            .collect(),
+        _ => None,
+    }
+}
+
+impl GammaEventMarket {
+    fn into_tradable(self) -> Option<EventMarket> {
+        if !self.active.unwrap_or(false)
+            || !self.enable_order_book.unwrap_or(false)
+            || !self.accepting_orders.unwrap_or(false)
+        {
+            return None;
+        }
+        let clob_token_ids = string_list(self.clob_token_ids.as_ref()?)?
+            .iter()
+            .map(|id| U256::from_str(id).ok())
+            .collect::<Option<Vec<_>>>()?;
+        Some(EventMarket {
+            condition_id: B256::from_str(self.condition_id.as_deref()?).ok()?,
+            slug: self.slug?,
+            question: self.question.unwrap_or_default(),
+            group_item_title: self.group_item_title.unwrap_or_default(),
+            end_date: self.end_date?,
+            outcomes: string_list(self.outcomes.as_ref()?)?,
+            clob_token_ids,
+        })
+    }
+}
+
+/// 查询标签为 tag_slug、结束时间在 [end_min, end_max] 内且未关闭的事件，返回其中可交易的市场
+pub async fn fetch_markets_by_end_date(
+    http: &reqwest::Client,
+    gamma_url: &str,
+    tag_slug: &str,
+    end_min: DateTime<Utc>,
+    end_max: DateTime<Utc>,
+) -> Result<Vec<EventMarket>> {
+    let url = format!("{}/events", gamma_url.trim_end_matches('/'));
+    rate_limit::acquire(Priority::Low).await?;
+    let events: Vec<GammaEvent> = http
+        .get(&url)
+        .query(&[
+            ("tag_slug", tag_slug.to_string()),
+            ("closed", "false".to_string()),
+            ("end_date_min", end_min.to_rfc3339()),
+            ("end_date_max", end_max.to_rfc3339()),
+            ("limit", "100".to_string()),
+        ])
+        .send()
+        .await
+        .context("请求 Gamma events 失败")?
+        .error_for_status()
+        .context("Gamma events 返回错误状态")?
+        .json()
+        .await
+        .context("解析 Gamma events 响应失败")?;
+
+    Ok(events
+        .into_iter()
+        .flat_map(|event| event.markets)
+        .filter_map(GammaEventMarket::into_tradable)
+        .collect())
+}
+
+/// 按事件 slug 查询单个事件，返回其中可交易的市场（行权价阶梯等多市场事件）
+pub async fn fetch_event_markets_by_slug(
+    http: &reqwest::Client,
+    gamma_url: &str,
+    event_slug: &str,
+) -> Result<Vec<EventMarket>> {
+    let url = format!("{}/events", gamma_url.trim_end_matches('/'));
+    rate_limit::acquire(Priority::Low).await?;
+    let events: Vec<GammaEvent> = http
+        .get(&url)
+        .query(&[("slug", event_slug)])
+        .send()
+        .await
+        .context("请求 Gamma events 失败")?
+        .error_for_status()
+        .context("Gamma events 返回错误状态")?
+        .json()
+        .await
+        .context("解析 Gamma events 响应失败")?;
+
+    Ok(events
+        .into_iter()
+        .flat_map(|event| event.markets)
+        .filter_map(GammaEventMarket::into_tradable)
+        .collect())
+}
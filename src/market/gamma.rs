@@ -0,0 +1,162 @@
+//! Gamma 客户端包装：按 slug / condition_id 缓存市场元数据（带 TTL），
+//! 合并并发的相同查询，瞬时失败按指数退避重试，避免一次失败的调用让整个窗口的发现失败。
+
+use anyhow::Result;
+use dashmap::DashMap;
+use polymarket_client_sdk::gamma::types::request::MarketsRequest;
+use polymarket_client_sdk::gamma::types::response::Market;
+use polymarket_client_sdk::gamma::Client;
+use polymarket_client_sdk::types::B256;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::time::sleep;
+use tracing::{debug, warn};
+
+use crate::utils::rate_limit::{self, Priority};
+
+/// 市场元数据缓存有效期（accepting_orders 等状态会变化，不宜过长）
+const CACHE_TTL: Duration = Duration::from_secs(30);
+/// 单次查询最多尝试次数（含首次）
+const MAX_ATTEMPTS: u32 = 4;
+/// 首次重试前的等待，之后每次翻倍
+const INITIAL_BACKOFF: Duration = Duration::from_millis(250);
+
+pub struct ResilientGamma {
+    client: Client,
+    by_slug: DashMap<String, (Instant, Market)>,
+    by_condition_id: DashMap<B256, (Instant, Market)>,
+    /// 查询键 -> 查询锁：相同查询并发到达时只有一个真正请求，其余等待后直接读缓存
+    inflight: DashMap<String, Arc<tokio::sync::Mutex<()>>>,
+}
+
+impl ResilientGamma {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            by_slug: DashMap::new(),
+            by_condition_id: DashMap::new(),
+            inflight: DashMap::new(),
+        }
+    }
+
+    fn cached_by_slug(&self, slug: &str) -> Option<Market> {
+        self.by_slug
+            .get(slug)
+            .filter(|entry| entry.0.elapsed() < CACHE_TTL)
+            .map(|entry| entry.1.clone())
+    }
+
+    /// 按 condition_id 读取缓存的市场元数据（仅缓存命中，不发请求）
+    pub fn cached_by_condition_id(&self, condition_id: &B256) -> Option<Market> {
+        self.by_condition_id
+            .get(condition_id)
+            .filter(|entry| entry.0.elapsed() < CACHE_TTL)
+            .map(|entry| entry.1.clone())
+    }
+
+    fn store(&self, market: &Market) {
+        let now = Instant::now();
+        if let Some(slug) = market.slug.as_ref() {
+            self.by_slug.insert(slug.clone(), (now, market.clone()));
+        }
+        if let Some(condition_id) = market.condition_id {
+            self.by_condition_id.insert(condition_id, (now, market.clone()));
+        }
+    }
+
+    /// 按 slug 批量查询市场：缓存命中的直接返回，其余合并为一次请求，失败时退避重试
+    /// 所有重试都失败时返回错误（已命中缓存的结果不会丢失，调用方可据此决定是否重试）
+    pub async fn markets_by_slugs(&self, slugs: &[String]) -> Result<Vec<Market>> {
+        let mut result: Vec<Market> = Vec::new();
+        let mut missing: Vec<String> = Vec::new();
+        for slug in slugs {
+            match self.cached_by_slug(slug) {
+                Some(market) => result.push(market),
+                None => missing.push(slug.clone()),
+            }
+        }
+        if missing.is_empty() {
+            return Ok(result);
+        }
+
+        missing.sort_unstable();
+        let key = missing.join(",");
+        let lock = self
+            .inflight
+            .entry(key.clone())
+            .or_insert_with(|| Arc::new(tokio::sync::Mutex::new(())))
+            .clone();
+        let _guard = lock.lock().await;
+
+        // 等锁期间其他调用方可能已完成同一查询
+        let still_missing: Vec<String> = missing
+            .into_iter()
+            .filter(|slug| match self.cached_by_slug(slug) {
+                Some(market) => {
+                    result.push(market);
+                    false
+                }
+                None => true,
+            })
+            .collect();
+
+        if !still_missing.is_empty() {
+            let fetched = self.fetch_with_retry(&still_missing).await;
+            self.inflight.remove(&key);
+            for market in fetched? {
+                self.store(&market);
+                result.push(market);
+            }
+        } else {
+            self.inflight.remove(&key);
+        }
+        Ok(result)
+    }
+
+    /// 绕过缓存重新查询（用于检查市场状态变化），结果写回缓存
+    pub async fn refresh_by_slugs(&self, slugs: &[String]) -> Result<Vec<Market>> {
+        let markets = self.fetch_with_retry(slugs).await?;
+        for market in &markets {
+            self.store(market);
+        }
+        Ok(markets)
+    }
+
+    async fn fetch_with_retry(&self, slugs: &[String]) -> Result<Vec<Market>> {
+        let request = MarketsRequest::builder().slug(slugs.to_vec()).build();
+        let mut backoff = INITIAL_BACKOFF;
+        let mut attempt = 1;
+        loop {
+            let fetched = match rate_limit::acquire(Priority::Low).await {
+                Ok(()) => self.client.markets(&request).await.map_err(anyhow::Error::from),
+                Err(e) => Err(e),
+            };
+            match fetched {
+                Ok(markets) => return Ok(markets),
+                Err(e) if attempt < MAX_ATTEMPTS => {
+                    debug!(error = %e, attempt, backoff_ms = backoff.as_millis() as u64, "Gamma 查询失败，退避重试");
+                    sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    warn!(error = %e, attempts = attempt, "Gamma 查询重试耗尽");
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
+
+/// 市场已不可交易的原因（关闭、未激活、停止接单）；仍可交易时返回 None
+pub fn inactive_reason(market: &Market) -> Option<&'static str> {
+    if market.closed.unwrap_or(false) {
+        Some("已关闭")
+    } else if !market.active.unwrap_or(false) {
+        Some("未激活")
+    } else if !market.accepting_orders.unwrap_or(false) {
+        Some("停止接单")
+    } else {
+        None
+    }
+}
@@ -0,0 +1,80 @@
+//! 行权价阶梯：同一标的、同一到期时间的一组「价格高于 $X」市场（同一 Gamma 事件下的多个市场），
+//! 按行权价升序排列，供阶梯套利检测使用。
+
+use polymarket_client_sdk::types::{B256, Decimal};
+use std::str::FromStr;
+
+use super::discoverer::MarketInfo;
+use super::events::EventMarket;
+use super::series::MarketSeries;
+
+/// 阶梯中的一档：行权价与对应的二元市场（YES = 到期价格高于行权价）
+#[derive(Debug, Clone)]
+pub struct StrikeMarket {
+    pub strike: Decimal,
+    pub market: MarketInfo,
+}
+
+#[derive(Debug, Clone)]
+pub struct StrikeLadder {
+    /// 阶梯名称（STRIKE_LADDERS 中的名称，如 btc）
+    pub name: String,
+    /// 按行权价升序
+    pub strikes: Vec<StrikeMarket>,
+}
+
+impl StrikeLadder {
+    /// 由事件下的市场构建阶梯：解析行权价并按升序排列，无法解析行权价或结果标签不匹配的市场跳过；
+    /// 少于两档时返回 None
+    pub fn from_event_markets(series: &MarketSeries, markets: &[EventMarket]) -> Option<Self> {
+        let mut strikes: Vec<StrikeMarket> = markets
+            .iter()
+            .filter_map(|m| {
+                let strike = parse_strike(&m.group_item_title).or_else(|| parse_strike(&m.question))?;
+                let (yes_index, no_index) = series.outcome_indices(&m.outcomes)?;
+                if m.clob_token_ids.len() != 2 {
+                    return None;
+                }
+                Some(StrikeMarket {
+                    strike,
+                    market: MarketInfo {
+                        market_id: m.condition_id,
+                        slug: m.slug.clone(),
+                        yes_token_id: m.clob_token_ids[yes_index],
+                        no_token_id: m.clob_token_ids[no_index],
+                        title: m.question.clone(),
+                        end_date: m.end_date,
+                        crypto_symbol: series.name.clone(),
+                        window: series.window(),
+                    },
+                })
+            })
+            .collect();
+        strikes.sort_by(|a, b| a.strike.cmp(&b.strike));
+        strikes.dedup_by(|a, b| a.strike == b.strike);
+        (strikes.len() >= 2).then(|| Self {
+            name: series.name.clone(),
+            strikes,
+        })
+    }
+
+    /// 阶梯中所有市场
+    pub fn markets(&self) -> impl Iterator<Item = &MarketInfo> {
+        self.strikes.iter().map(|s| &s.market)
+    }
+
+    pub fn contains(&self, market_id: &B256) -> bool {
+        self.strikes.iter().any(|s| s.market.market_id == *market_id)
+    }
+}
+
+/// 从文本中解析行权价：取第一段数字（允许千分位逗号与小数点），如 "$105,000" → 105000、"↑ 3,250.5" → 3250.5
+pub fn parse_strike(text: &str) -> Option<Decimal> {
+    let start = text.find(|c: char| c.is_ascii_digit())?;
+    let number: String = text[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == ',' || *c == '.')
+        .filter(|c| *c != ',')
+        .collect();
+    Decimal::from_str(number.trim_end_matches('.')).ok()
+}
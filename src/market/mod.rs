@@ -1,5 +1,18 @@
+pub mod cache;
+pub mod clock;
 pub mod discoverer;
+pub mod events;
+pub mod gamma;
+pub mod ladder;
+pub mod rewards;
+pub mod rules;
 pub mod scheduler;
+pub mod series;
+pub mod spot;
+pub mod status;
+pub mod volatility;
+pub mod window;
 
 pub use discoverer::*;
 pub use scheduler::*;
+pub use series::MarketSeries;
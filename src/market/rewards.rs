@@ -0,0 +1,180 @@
+//! 流动性奖励计划：查询 CLOB 当前参与奖励计划的市场（每日奖励额、计奖最大价差、最小挂单量），
+//! 只保留正在监控的市场供策略层查询；挂单（GTC/GTD）在奖励市场中可额外获得奖励，策略可据此放宽价差要求。
+//! 另从 Data API 的 REWARD 活动记录汇总已获得的奖励，计入盈亏报告。
+
+use anyhow::{Context, Result};
+use polymarket_client_sdk::types::{Address, Decimal, B256};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::RwLock;
+use std::time::Duration;
+use tracing::info;
+
+/// Data API 地址（奖励发放记录）
+const DATA_API_URL: &str = "https://data-api.polymarket.com";
+/// 分页结束标记
+const END_CURSOR: &str = "LTE=";
+/// 最多翻页数，防止接口异常时无限翻页
+const MAX_PAGES: usize = 50;
+
+/// 某市场的奖励参数
+#[derive(Debug, Clone, Copy)]
+pub struct MarketReward {
+    /// 每日奖励总额（USDC）
+    pub rate_per_day: Decimal,
+    /// 计奖的最大挂单价差（距中间价，美分）
+    pub max_spread: Decimal,
+    /// 计奖的最小挂单数量
+    pub min_size: Decimal,
+}
+
+#[derive(Debug, Deserialize)]
+struct RewardsPage {
+    #[serde(default)]
+    data: Vec<RewardsMarket>,
+    next_cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RewardsMarket {
+    condition_id: Option<String>,
+    rewards_max_spread: Option<Value>,
+    rewards_min_size: Option<Value>,
+    #[serde(default)]
+    rewards_config: Vec<RewardsConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RewardsConfig {
+    rate_per_day: Option<Value>,
+}
+
+/// 数值字段：接口可能返回数字或字符串
+fn decimal(value: Option<&Value>) -> Decimal {
+    match value {
+        Some(Value::Number(n)) => Decimal::from_str(&n.to_string()).unwrap_or_default(),
+        Some(Value::String(s)) => Decimal::from_str(s.trim()).unwrap_or_default(),
+        _ => Decimal::ZERO,
+    }
+}
+
+pub struct RewardsBook {
+    http: reqwest::Client,
+    clob_url: String,
+    rewards: RwLock<HashMap<B256, MarketReward>>,
+}
+
+impl RewardsBook {
+    /// clob_url: CLOB REST 地址（Config.endpoints.clob_rest）
+    pub fn new(clob_url: &str) -> Self {
+        Self {
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            clob_url: clob_url.trim_end_matches('/').to_string(),
+            rewards: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 该市场的奖励参数（不在奖励计划中时为 None）
+    pub fn reward_for(&self, market_id: B256) -> Option<MarketReward> {
+        self.rewards.read().ok().and_then(|r| r.get(&market_id).copied())
+    }
+
+    /// 重新查询奖励计划，只保留 markets 中的市场；返回参与奖励的市场数
+    pub async fn refresh(&self, markets: &[B256]) -> Result<usize> {
+        let wanted: HashSet<B256> = markets.iter().copied().collect();
+        let url = format!("{}/rewards/markets/current", self.clob_url);
+        let mut found: HashMap<B256, MarketReward> = HashMap::new();
+        let mut cursor = String::new();
+        for _ in 0..MAX_PAGES {
+            let mut request = self.http.get(&url);
+            if !cursor.is_empty() {
+                request = request.query(&[("next_cursor", cursor.as_str())]);
+            }
+            let page: RewardsPage = request
+                .send()
+                .await
+                .context("请求奖励市场列表失败")?
+                .error_for_status()
+                .context("奖励市场列表返回错误状态")?
+                .json()
+                .await
+                .context("解析奖励市场列表失败")?;
+            for market in page.data {
+                let Some(condition_id) = market.condition_id.as_deref().and_then(|id| B256::from_str(id).ok()) else {
+                    continue;
+                };
+                if !wanted.contains(&condition_id) {
+                    continue;
+                }
+                let rate_per_day: Decimal = market
+                    .rewards_config
+                    .iter()
+                    .map(|c| decimal(c.rate_per_day.as_ref()))
+                    .sum();
+                if rate_per_day <= Decimal::ZERO {
+                    continue;
+                }
+                found.insert(
+                    condition_id,
+                    MarketReward {
+                        rate_per_day,
+                        max_spread: decimal(market.rewards_max_spread.as_ref()),
+                        min_size: decimal(market.rewards_min_size.as_ref()),
+                    },
+                );
+            }
+            match page.next_cursor {
+                Some(next) if !next.is_empty() && next != END_CURSOR => cursor = next,
+                _ => break,
+            }
+        }
+        for (condition_id, reward) in &found {
+            info!(
+                "🎁 奖励市场 | condition_id={:#x} | 每日奖励:{} USDC | 最大价差:{}¢ | 最小数量:{}",
+                condition_id, reward.rate_per_day, reward.max_spread, reward.min_size
+            );
+        }
+        let count = found.len();
+        if let Ok(mut rewards) = self.rewards.write() {
+            *rewards = found;
+        }
+        Ok(count)
+    }
+
+    /// 汇总 user 自 since（Unix 秒）以来获得的流动性奖励（USDC）
+    pub async fn earned_since(&self, user: Address, since: i64) -> Result<Decimal> {
+        const PAGE: usize = 500;
+        let url = format!("{}/activity", DATA_API_URL);
+        let mut total = Decimal::ZERO;
+        for page in 0..MAX_PAGES {
+            let items: Vec<Value> = self
+                .http
+                .get(&url)
+                .query(&[
+                    ("user", format!("{:#x}", user)),
+                    ("type", "REWARD".to_string()),
+                    ("start", since.to_string()),
+                    ("limit", PAGE.to_string()),
+                    ("offset", (page * PAGE).to_string()),
+                ])
+                .send()
+                .await
+                .context("请求奖励记录失败")?
+                .error_for_status()
+                .context("奖励记录返回错误状态")?
+                .json()
+                .await
+                .context("解析奖励记录失败")?;
+            total += items.iter().map(|item| decimal(item.get("usdcSize"))).sum::<Decimal>();
+            if items.len() < PAGE {
+                break;
+            }
+        }
+        Ok(total)
+    }
+}
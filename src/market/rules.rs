@@ -0,0 +1,120 @@
+//! 市场交易规则：每个窗口从 CLOB /markets/{condition_id} 读取各监控市场的最小价格单位（minimum_tick_size）
+//! 与最小下单数量（minimum_order_size），按 token 登记，供检测器与执行器按价格单位取整、把下单价限制在
+//! [价格单位, 1 − 价格单位] 内，并在提交前把下单数量对齐到数量步长、过滤低于最小数量的订单。
+//! 价格接近 0 或 1 的市场会改用 0.001 的价格单位，统一按 0.01 取整会错价或被交易所拒单；未取到规则时按 0.01 处理。
+//! 不满足最小数量的订单提交后必然被拒，白白消耗延迟与限速额度；未取到最小数量时不做限制（仍受 $1 最小金额约束）。
+
+use dashmap::DashMap;
+use polymarket_client_sdk::types::{Decimal, U256};
+use rust_decimal::RoundingStrategy;
+use rust_decimal_macros::dec;
+use serde_json::Value;
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::{debug, info};
+
+use super::MarketInfo;
+use crate::utils::metrics;
+
+/// 未取到规则时的默认价格单位
+pub const DEFAULT_TICK: Decimal = dec!(0.01);
+/// 下单数量步长（份额最多两位小数，交易所统一）
+pub const SIZE_STEP: Decimal = dec!(0.01);
+
+/// 一个 token 的交易规则
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rule {
+    tick: Decimal,
+    /// 最小下单数量（份），0 表示未知
+    min_size: Decimal,
+}
+
+static RULES: OnceLock<DashMap<U256, Rule>> = OnceLock::new();
+
+fn rules() -> &'static DashMap<U256, Rule> {
+    RULES.get_or_init(DashMap::new)
+}
+
+/// 数值字段：接口可能返回数字或字符串
+fn decimal(value: Option<&Value>) -> Option<Decimal> {
+    match value? {
+        Value::Number(n) => Decimal::from_str(&n.to_string()).ok(),
+        Value::String(s) => Decimal::from_str(s.trim()).ok(),
+        _ => None,
+    }
+}
+
+/// 查询 markets 的交易规则并登记；返回成功登记的市场数
+pub async fn refresh(clob_url: &str, markets: &[MarketInfo]) -> usize {
+    let http = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .unwrap_or_default();
+    let clob_url = clob_url.trim_end_matches('/');
+    let fetched = futures::future::join_all(markets.iter().map(|m| {
+        let http = http.clone();
+        let url = format!("{}/markets/{:#x}", clob_url, m.market_id);
+        async move {
+            let body: Value = http.get(&url).send().await.ok()?.error_for_status().ok()?.json().await.ok()?;
+            Some((m, body))
+        }
+    }))
+    .await;
+
+    let mut updated = 0;
+    for (market, body) in fetched.into_iter().flatten() {
+        let Some(tick) = decimal(body.get("minimum_tick_size")).filter(|t| *t > dec!(0) && *t < dec!(1)) else {
+            metrics::incr("market_rules_missing");
+            continue;
+        };
+        let min_size = decimal(body.get("minimum_order_size")).unwrap_or_default().max(dec!(0));
+        let rule = Rule { tick, min_size };
+        for token in [market.yes_token_id, market.no_token_id] {
+            if rules().insert(token, rule).is_some_and(|old| old != rule) {
+                info!(
+                    "📏 交易规则变更 | 市场:{} | 价格单位:{} | 最小数量:{}",
+                    market.slug, tick, min_size
+                );
+            }
+        }
+        debug!(slug = %market.slug, tick = %tick, min_size = %min_size, "已登记市场交易规则");
+        updated += 1;
+    }
+    updated
+}
+
+/// token 的最小价格单位
+pub fn tick_size(token_id: U256) -> Decimal {
+    rules().get(&token_id).map(|r| r.tick).unwrap_or(DEFAULT_TICK)
+}
+
+/// token 的最小下单数量（份），未知时为 0
+pub fn min_order_size(token_id: U256) -> Decimal {
+    rules().get(&token_id).map(|r| r.min_size).unwrap_or_default()
+}
+
+/// 把下单数量向下对齐到数量步长；低于这些 token 中最大的最小下单数量时返回 None（该订单会被交易所拒绝）
+pub fn order_size(tokens: &[U256], size: Decimal) -> Option<Decimal> {
+    let size = (size / SIZE_STEP).round_dp_with_strategy(0, RoundingStrategy::ToZero) * SIZE_STEP;
+    let min_size = tokens.iter().map(|t| min_order_size(*t)).fold(SIZE_STEP, Decimal::max);
+    (size >= min_size).then_some(size)
+}
+
+/// 按价格单位向下取整（价格单位为 0.01 时等同于保留两位小数向下取整）
+pub fn floor_to_tick(token_id: U256, price: Decimal) -> Decimal {
+    let tick = tick_size(token_id);
+    ((price / tick).round_dp_with_strategy(0, RoundingStrategy::ToZero) * tick).normalize()
+}
+
+/// 订单簿上的报价按价格单位对齐（四舍五入，去掉浮点噪声）
+pub fn align_to_tick(token_id: U256, price: Decimal) -> Decimal {
+    let tick = tick_size(token_id);
+    ((price / tick).round() * tick).normalize()
+}
+
+/// 下单价：按价格单位向下取整并限制在 [价格单位, 1 − 价格单位] 内
+pub fn order_price(token_id: U256, price: Decimal) -> Decimal {
+    let tick = tick_size(token_id);
+    floor_to_tick(token_id, price).clamp(tick, dec!(1) - tick)
+}
@@ -1,25 +1,106 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
+use polymarket_client_sdk::types::B256;
+use std::collections::HashSet;
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::mpsc;
 use tokio::time::sleep;
 use tracing::{error, info, warn};
 
 use super::discoverer::{MarketDiscoverer, MarketInfo};
+use super::{rules, window};
+use crate::utils::metrics;
+
+/// 发现重试的初始等待，之后每次翻倍直到上限
+const INITIAL_RETRY_BACKOFF: Duration = Duration::from_secs(2);
 
 pub struct MarketScheduler {
-    discoverer: MarketDiscoverer,
+    discoverer: Arc<MarketDiscoverer>,
     refresh_advance_secs: u64,
+    /// 发现重试的退避上限
+    max_retry_backoff: Duration,
+    /// 每个窗口最多重试次数，用尽后放弃本窗口、等待下一个窗口
+    max_retries_per_window: u32,
 }
 
 impl MarketScheduler {
-    pub fn new(discoverer: MarketDiscoverer, refresh_advance_secs: u64) -> Self {
+    pub fn new(
+        discoverer: MarketDiscoverer,
+        refresh_advance_secs: u64,
+        max_retry_backoff_secs: u64,
+        max_retries_per_window: u32,
+    ) -> Self {
         Self {
-            discoverer,
+            discoverer: Arc::new(discoverer),
             refresh_advance_secs,
+            max_retry_backoff: Duration::from_secs(max_retry_backoff_secs.max(INITIAL_RETRY_BACKOFF.as_secs())),
+            max_retries_per_window,
+        }
+    }
+
+    pub fn discoverer(&self) -> &MarketDiscoverer {
+        &self.discoverer
+    }
+
+    /// 窗口开始时部分系列的市场尚未创建时，后台定时重新发现本窗口市场，
+    /// 新出现的市场先在任务内登记交易规则（价格单位、最小数量），再通过通道交给主循环即时订阅，
+    /// 主循环不会因查询规则而阻塞；所有系列都已发现或窗口结束后任务退出。
+    /// 已发现全部系列时返回 None。
+    pub fn spawn_rediscovery(
+        &self,
+        known: &[MarketInfo],
+        interval: Duration,
+        rules_url: &str,
+        handle: &tokio::runtime::Handle,
+    ) -> Option<mpsc::UnboundedReceiver<Vec<MarketInfo>>> {
+        let expected = self.discoverer.series_count();
+        if known.len() >= expected {
+            return None;
         }
+        let discoverer = self.discoverer.clone();
+        let mut known: HashSet<B256> = known.iter().map(|m| m.market_id).collect();
+        let window_timestamp = MarketDiscoverer::calculate_current_window_timestamp(Utc::now());
+        let rules_url = rules_url.to_string();
+        let (tx, rx) = mpsc::unbounded_channel();
+        info!(
+            found = known.len(),
+            expected,
+            interval_secs = interval.as_secs(),
+            "部分系列市场尚未创建，窗口内将定时重新发现"
+        );
+
+        handle.spawn(async move {
+            while known.len() < expected {
+                sleep(interval).await;
+                if tx.is_closed()
+                    || MarketDiscoverer::calculate_current_window_timestamp(Utc::now()) != window_timestamp
+                {
+                    break;
+                }
+                match discoverer.get_markets_for_timestamp(window_timestamp).await {
+                    Ok(markets) => {
+                        let new_markets: Vec<MarketInfo> = markets
+                            .into_iter()
+                            .filter(|m| known.insert(m.market_id))
+                            .collect();
+                        if new_markets.is_empty() {
+                            continue;
+                        }
+                        let count = rules::refresh(&rules_url, &new_markets).await;
+                        info!("📏 新市场交易规则已刷新 | {}/{} 个市场", count, new_markets.len());
+                        if tx.send(new_markets).is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => warn!(error = %e, "窗口内重新发现市场失败，下次重试"),
+                }
+            }
+        });
+        Some(rx)
     }
 
-    /// 计算到下一个1小时窗口的等待时间
+    /// 计算到下一个调度窗口的等待时间
     pub fn calculate_wait_time(&self, now: DateTime<Utc>) -> Duration {
         let next_window_ts = MarketDiscoverer::calculate_next_window_timestamp(now);
         let next_window = DateTime::from_timestamp(next_window_ts, 0)
@@ -65,35 +146,58 @@ impl MarketScheduler {
         }
     }
 
-    /// 等待到下一个1小时窗口开始，并获取市场
+    /// 等待到下一个调度窗口开始，并获取市场
+    /// 市场尚未创建时按指数退避重试（有上限），每个窗口的重试次数有预算，用尽后等待下一个窗口
     pub async fn wait_for_next_window(&self) -> Result<Vec<MarketInfo>> {
         loop {
             let wait_time = self.calculate_wait_time(Utc::now());
             if wait_time > Duration::ZERO {
                 info!(
                     wait_secs = wait_time.as_secs(),
-                    "等待下一个1小时窗口"
+                    window = window::scheduler().as_str(),
+                    "等待下一个窗口"
                 );
                 sleep(wait_time).await;
             }
 
-            // 查询当前窗口的市场
-            let now = Utc::now();
-            let timestamp = MarketDiscoverer::calculate_current_window_timestamp(now);
-            match self.discoverer.get_markets_for_timestamp(timestamp).await {
-                Ok(markets) => {
-                    if !markets.is_empty() {
-                        info!(count = markets.len(), "发现新市场");
+            let window_timestamp = MarketDiscoverer::calculate_current_window_timestamp(Utc::now());
+            let mut backoff = INITIAL_RETRY_BACKOFF;
+            let mut retries: u32 = 0;
+
+            loop {
+                // 查询当前窗口的市场；窗口已切换时回到外层按新窗口重新计数
+                let timestamp = MarketDiscoverer::calculate_current_window_timestamp(Utc::now());
+                if timestamp != window_timestamp {
+                    break;
+                }
+                match self.discoverer.get_markets_for_timestamp(timestamp).await {
+                    Ok(markets) if !markets.is_empty() => {
+                        info!(count = markets.len(), retries, "发现新市场");
                         return Ok(markets);
                     }
-                    // 如果市场还未创建，等待一段时间后重试
-                    info!("市场尚未创建，等待重试...");
-                    sleep(Duration::from_secs(2)).await;
+                    Ok(_) => {
+                        // 如果市场还未创建，等待一段时间后重试
+                        info!(retries, backoff_secs = backoff.as_secs(), "市场尚未创建，等待重试...");
+                    }
+                    Err(e) => {
+                        error!(error = %e, retries, backoff_secs = backoff.as_secs(), "获取市场失败，重试...");
+                    }
                 }
-                Err(e) => {
-                    error!(error = %e, "获取市场失败，重试...");
-                    sleep(Duration::from_secs(2)).await;
+
+                retries += 1;
+                metrics::incr("discovery_retries");
+                if self.max_retries_per_window > 0 && retries >= self.max_retries_per_window {
+                    metrics::incr("discovery_budget_exhausted");
+                    warn!(
+                        window = window_timestamp,
+                        retries,
+                        "本窗口发现重试次数已用尽，放弃本窗口，等待下一个窗口"
+                    );
+                    // 跳过本窗口剩余时间：下一轮外层循环会等待到下一个窗口开始
+                    break;
                 }
+                sleep(backoff).await;
+                backoff = (backoff * 2).min(self.max_retry_backoff);
             }
         }
     }
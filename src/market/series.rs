@@ -0,0 +1,180 @@
+//! 市场系列：描述一类按固定周期重复上新的二元市场（slug 模板、周期、两个结果标签），
+//! 使加密货币涨跌之外的系列（如每小时股指、天气市场）也能由同一发现器/调度器监控。
+//! 周期须为监控的窗口周期之一（15 分钟 / 1 小时 / 4 小时 / 每日，见 window 模块）。
+
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use tracing::warn;
+
+use super::clock;
+use super::window::WindowDuration;
+
+const MONTH_NAMES: [&str; 12] = [
+    "january", "february", "march", "april", "may", "june",
+    "july", "august", "september", "october", "november", "december",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarketSeries {
+    /// 系列名称，写入 MarketInfo.crypto_symbol 用于日志与分组，也可在模板中以 {name} 引用
+    pub name: String,
+    /// slug 模板，占位符：{name} {ticker} {month} {day} {hour} {ampm} {hour24} {year} {end_month} {end_day} {ts}
+    /// （时间均为市场时区，默认 ET；{end_*} 为窗口结束日期，{ts} 为窗口开始的 Unix 秒，{ticker} 为币种简写如 btc）
+    pub slug_pattern: String,
+    /// 上新周期（秒）
+    pub cadence_secs: i64,
+    /// 两个结果标签：[YES 侧, NO 侧]，例如 ["Up", "Down"]
+    pub outcomes: [String; 2],
+    /// Gamma 事件标签，slug 查询无结果时按此标签 + 结束时间查询 events 兜底
+    pub tag_slug: String,
+}
+
+impl MarketSeries {
+    /// 加密货币涨跌系列，按窗口周期使用对应的 slug 格式，例如：
+    /// 15 分钟 btc-updown-15m-1768550400、1 小时 bitcoin-up-or-down-january-16-3am-et、
+    /// 4 小时 btc-updown-4h-1768550400、每日 bitcoin-up-or-down-on-january-16
+    pub fn crypto(symbol: &str, window: WindowDuration) -> Self {
+        let slug_pattern = match window {
+            WindowDuration::Min15 => "{ticker}-updown-15m-{ts}",
+            WindowDuration::Hour1 => "{name}-up-or-down-{month}-{day}-{hour}{ampm}-et",
+            WindowDuration::Hour4 => "{ticker}-updown-4h-{ts}",
+            WindowDuration::Daily => "{name}-up-or-down-on-{end_month}-{end_day}",
+        };
+        Self {
+            name: symbol.to_string(),
+            slug_pattern: slug_pattern.to_string(),
+            cadence_secs: window.secs(),
+            outcomes: ["Up".to_string(), "Down".to_string()],
+            tag_slug: "crypto".to_string(),
+        }
+    }
+
+    /// 该系列的窗口周期；周期不是 15 分钟 / 1 小时 / 4 小时 / 每日之一时为 None
+    pub fn window(&self) -> Option<WindowDuration> {
+        WindowDuration::from_secs(self.cadence_secs)
+    }
+
+    /// 包含调度窗口开始时间戳 timestamp 的本系列窗口开始时间戳
+    pub fn window_start(&self, timestamp: i64) -> i64 {
+        match (self.window(), DateTime::from_timestamp(timestamp, 0)) {
+            (Some(window), Some(time)) => window.window_start(time),
+            _ => timestamp,
+        }
+    }
+
+    /// 本系列窗口的结束时间戳（start 为本系列窗口开始）
+    pub fn window_end(&self, start: i64) -> i64 {
+        self.window()
+            .map(|window| window.window_end(start))
+            .unwrap_or(start + self.cadence_secs)
+    }
+
+    /// 币种简写：常见全称映射为 ticker（bitcoin → btc），其余原样使用
+    fn ticker(&self) -> &str {
+        match self.name.as_str() {
+            "bitcoin" => "btc",
+            "ethereum" => "eth",
+            "solana" => "sol",
+            "dogecoin" => "doge",
+            other => other,
+        }
+    }
+
+    /// 按窗口开始时间戳生成该系列的 slug（调度窗口较短时先对齐到本系列的窗口）
+    pub fn render_slug(&self, window_timestamp: i64) -> String {
+        let start = self.window_start(window_timestamp);
+        let et_time = clock::to_market_time(
+            DateTime::from_timestamp(start, 0).unwrap_or_else(Utc::now),
+        );
+        let end_time = clock::to_market_time(
+            DateTime::from_timestamp(self.window_end(start), 0).unwrap_or_else(Utc::now),
+        );
+
+        let hour_24 = et_time.hour();
+        let (hour_12, am_pm) = match hour_24 {
+            0 => (12, "am"),
+            1..=11 => (hour_24, "am"),
+            12 => (12, "pm"),
+            _ => (hour_24 - 12, "pm"),
+        };
+
+        self.slug_pattern
+            .replace("{name}", &self.name)
+            .replace("{ticker}", self.ticker())
+            .replace("{ts}", &start.to_string())
+            .replace("{end_month}", MONTH_NAMES[end_time.month0() as usize])
+            .replace("{end_day}", &end_time.day().to_string())
+            .replace("{month}", MONTH_NAMES[et_time.month0() as usize])
+            .replace("{day}", &et_time.day().to_string())
+            .replace("{hour24}", &hour_24.to_string())
+            .replace("{hour}", &hour_12.to_string())
+            .replace("{ampm}", am_pm)
+            .replace("{year}", &et_time.year().to_string())
+    }
+
+    /// events 兜底时判断市场是否属于本系列：slug 或标题中包含系列名称（不区分大小写）
+    pub fn matches_name(&self, slug: &str, title: &str) -> bool {
+        let name = self.name.to_lowercase();
+        slug.to_lowercase().contains(&name) || title.to_lowercase().contains(&name)
+    }
+
+    /// events 查询时判断市场是否属于本系列的周期：15 分钟 / 4 小时加密货币市场的 slug 带有周期标记（如 -15m-），
+    /// 同一币种、结束时间相同的不同周期市场据此区分
+    pub fn matches_window(&self, slug: &str) -> bool {
+        let slug = slug.to_lowercase();
+        let own = self.window().and_then(WindowDuration::slug_marker);
+        match own {
+            Some(marker) => slug.contains(marker),
+            None => [WindowDuration::Min15, WindowDuration::Hour4]
+                .into_iter()
+                .filter_map(WindowDuration::slug_marker)
+                .all(|marker| !slug.contains(marker)),
+        }
+    }
+
+    /// 根据市场的 outcomes 返回 (YES 下标, NO 下标)；标签不匹配时返回 None
+    pub fn outcome_indices(&self, outcomes: &[String]) -> Option<(usize, usize)> {
+        if outcomes.len() != 2 {
+            return None;
+        }
+        let yes = outcomes.iter().position(|o| o.eq_ignore_ascii_case(&self.outcomes[0]))?;
+        let no = outcomes.iter().position(|o| o.eq_ignore_ascii_case(&self.outcomes[1]))?;
+        (yes != no).then_some((yes, no))
+    }
+}
+
+/// 解析 MARKET_SERIES：多个系列以分号分隔，每个系列为 `名称|slug模板|周期秒|YES标签/NO标签[|事件标签]`
+/// 例如：`spx|spx-up-or-down-{month}-{day}-{hour}{ampm}-et|3600|Up/Down|stocks`
+/// 格式错误的条目记录警告后跳过
+pub fn parse_series_list(s: &str) -> Vec<MarketSeries> {
+    s.split(';')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let parts: Vec<&str> = entry.split('|').map(str::trim).collect();
+            let parsed = match parts.as_slice() {
+                [name, pattern, cadence, outcomes, rest @ ..] if rest.len() <= 1 => {
+                    let tag_slug = rest.first().copied().unwrap_or("").to_string();
+                    let cadence_secs = cadence.parse::<i64>().ok().filter(|c| *c > 0);
+                    let labels = outcomes.split_once('/');
+                    match (cadence_secs, labels) {
+                        (Some(cadence_secs), Some((yes, no))) if !name.is_empty() && !pattern.is_empty() => {
+                            Some(MarketSeries {
+                                name: name.to_lowercase(),
+                                slug_pattern: pattern.to_string(),
+                                cadence_secs,
+                                outcomes: [yes.trim().to_string(), no.trim().to_string()],
+                                tag_slug,
+                            })
+                        }
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            if parsed.is_none() {
+                warn!(entry, "MARKET_SERIES 条目格式错误，应为 名称|slug模板|周期秒|YES标签/NO标签[|事件标签]，已跳过");
+            }
+            parsed
+        })
+        .collect()
+}
@@ -0,0 +1,94 @@
+//! 外部现货行情：定期轮询现货交易所（默认 Binance）的 1 小时 K 线，取当前小时的开盘价与最新价。
+//! 每小时「涨/跌」市场按同一根小时 K 线的收盘价与开盘价比较结算，开盘价即窗口的参考价。
+//! 币种按系列名映射为 {SYMBOL}USDT 交易对（btc → BTCUSDT）。
+
+use dashmap::DashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+use crate::utils::metrics;
+
+/// 超过该时长未更新的报价视为陈旧，不再使用
+const STALE_AFTER: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy)]
+pub struct SpotQuote {
+    /// 当前小时 K 线开盘时间（毫秒）
+    pub open_time_ms: i64,
+    /// 当前小时开盘价（窗口参考价）
+    pub open: f64,
+    /// 最新价
+    pub last: f64,
+    pub updated: Instant,
+}
+
+pub struct SpotFeed {
+    quotes: DashMap<String, SpotQuote>,
+}
+
+impl SpotFeed {
+    /// 启动轮询任务；symbols 为系列名（如 btc、eth）
+    pub fn spawn(base_url: &str, symbols: &[String], interval: Duration) -> Arc<Self> {
+        let feed = Arc::new(Self { quotes: DashMap::new() });
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .unwrap_or_default();
+        let base_url = base_url.trim_end_matches('/').to_string();
+        let symbols: Vec<String> = symbols.iter().map(|s| s.trim().to_lowercase()).filter(|s| !s.is_empty()).collect();
+        info!("📈 现货行情已启动 | 来源:{} | 币种:{}", base_url, symbols.join(","));
+        let task_feed = feed.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                ticker.tick().await;
+                for symbol in &symbols {
+                    match fetch_hour_candle(&http, &base_url, symbol).await {
+                        Ok((open_time_ms, open, last)) => {
+                            task_feed.quotes.insert(
+                                symbol.clone(),
+                                SpotQuote { open_time_ms, open, last, updated: Instant::now() },
+                            );
+                        }
+                        Err(e) => {
+                            debug!(symbol = %symbol, error = %e, "获取现货行情失败");
+                            metrics::incr("spot_feed_error");
+                        }
+                    }
+                }
+            }
+        });
+        feed
+    }
+
+    /// 币种的最新报价；无数据或已陈旧时返回 None
+    pub fn quote(&self, symbol: &str) -> Option<SpotQuote> {
+        self.quotes
+            .get(&symbol.to_lowercase())
+            .map(|q| *q)
+            .filter(|q| q.updated.elapsed() < STALE_AFTER)
+    }
+}
+
+/// 系列名对应的现货交易对
+pub fn pair_symbol(symbol: &str) -> String {
+    format!("{}USDT", symbol.trim().to_uppercase())
+}
+
+/// 当前小时 K 线：返回 (开盘时间毫秒, 开盘价, 最新价)
+async fn fetch_hour_candle(http: &reqwest::Client, base_url: &str, symbol: &str) -> anyhow::Result<(i64, f64, f64)> {
+    let url = format!("{}/api/v3/klines?symbol={}&interval=1h&limit=1", base_url, pair_symbol(symbol));
+    let body: serde_json::Value = http.get(&url).send().await?.error_for_status()?.json().await?;
+    let candle = body
+        .get(0)
+        .and_then(|c| c.as_array())
+        .ok_or_else(|| anyhow::anyhow!("K 线响应为空"))?;
+    let number = |i: usize| -> Option<f64> { candle.get(i)?.as_str()?.parse().ok() };
+    let open_time_ms = candle.first().and_then(|v| v.as_i64()).unwrap_or_default();
+    match (number(1), number(4)) {
+        (Some(open), Some(last)) if open > 0.0 && last > 0.0 => Ok((open_time_ms, open, last)),
+        _ => anyhow::bail!("K 线价格无效"),
+    }
+}
@@ -0,0 +1,81 @@
+//! 窗口内市场状态监控：定时重新查询本窗口市场，发现停止接单、关闭或暂停的市场后
+//! 通过通道通知主循环，由其退订、撤单并将该市场排除出检测。
+
+use polymarket_client_sdk::types::B256;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+use super::discoverer::MarketInfo;
+use super::gamma::{inactive_reason, ResilientGamma};
+
+/// 不可交易市场通知：(condition_id, 原因)
+pub type DeadMarket = (B256, &'static str);
+
+/// 状态监控句柄：窗口中途新订阅的市场通过 `watch` 加入监控
+pub struct StatusWatcher {
+    add_tx: mpsc::UnboundedSender<MarketInfo>,
+}
+
+impl StatusWatcher {
+    pub fn watch(&self, market: &MarketInfo) {
+        let _ = self.add_tx.send(market.clone());
+    }
+}
+
+/// 启动状态监控任务，每个市场只通知一次；主循环丢弃接收端后任务自动退出
+pub fn spawn_status_watcher(
+    gamma: Arc<ResilientGamma>,
+    markets: &[MarketInfo],
+    interval: Duration,
+    handle: &tokio::runtime::Handle,
+) -> (StatusWatcher, mpsc::UnboundedReceiver<DeadMarket>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let (add_tx, mut add_rx) = mpsc::unbounded_channel::<MarketInfo>();
+    let mut slugs: Vec<String> = markets.iter().map(|m| m.slug.clone()).collect();
+    let mut watched: HashSet<B256> = markets.iter().map(|m| m.market_id).collect();
+
+    handle.spawn(async move {
+        let mut reported: HashSet<B256> = HashSet::new();
+        loop {
+            tokio::time::sleep(interval).await;
+            if tx.is_closed() {
+                break;
+            }
+            while let Ok(market) = add_rx.try_recv() {
+                if watched.insert(market.market_id) {
+                    slugs.push(market.slug);
+                }
+            }
+            let markets = match gamma.refresh_by_slugs(&slugs).await {
+                Ok(markets) => markets,
+                Err(e) => {
+                    debug!(error = %e, "市场状态检查失败，下次重试");
+                    continue;
+                }
+            };
+            for market in &markets {
+                let Some(condition_id) = market.condition_id else {
+                    continue;
+                };
+                if !watched.contains(&condition_id) || reported.contains(&condition_id) {
+                    continue;
+                }
+                if let Some(reason) = inactive_reason(market) {
+                    warn!(
+                        market = market.slug.as_deref().unwrap_or(""),
+                        reason,
+                        "市场已不可交易"
+                    );
+                    reported.insert(condition_id);
+                    if tx.send((condition_id, reason)).is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+    });
+    (StatusWatcher { add_tx }, rx)
+}
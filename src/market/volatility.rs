@@ -0,0 +1,216 @@
+//! 实现波动率估计：订阅现货逐笔成交流（Binance aggTrade），按秒采样各币种成交价，
+//! 在可配置的滚动窗口（VOL_WINDOWS_SECS）上计算实现波动率并折算为 1 小时标准差。
+//! 第一个窗口为主窗口，供波动率过滤、方向性策略的模型概率与 GTD 有效期调节使用；其余窗口仅用于日志对照。
+//! 未启用或样本不足时各查询返回 None，调用方回退到静态配置。
+
+use futures::StreamExt;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info, warn};
+
+use super::spot::pair_symbol;
+use crate::utils::metrics;
+
+/// 窗口内至少需要的秒级样本数
+const MIN_SAMPLES: usize = 30;
+/// 各窗口波动率的日志间隔
+const LOG_INTERVAL: Duration = Duration::from_secs(300);
+/// GTD 有效期最多缩短到基础值的比例
+const GTD_MIN_FACTOR: f64 = 0.25;
+
+#[derive(Debug, Clone)]
+pub struct VolSettings {
+    pub enabled: bool,
+    /// 现货逐笔成交 WS 地址（Binance 兼容的组合流）
+    pub ws_url: String,
+    /// 估计窗口（秒），第一个为主窗口
+    pub windows_secs: Vec<u64>,
+    /// 主窗口波动率（小时）超过该值时跳过套利；0 = 不过滤
+    pub filter_max_hourly: f64,
+    /// GTD 有效期参考波动率：实际波动率高于它时按比例缩短有效期；0 = 不调节
+    pub gtd_reference_hourly: f64,
+}
+
+pub struct RealizedVol {
+    settings: VolSettings,
+    /// 币种 -> 秒级采样 (unix 秒, 成交价)
+    samples: Mutex<HashMap<String, VecDeque<(i64, f64)>>>,
+}
+
+static VOL: OnceLock<Arc<RealizedVol>> = OnceLock::new();
+
+impl RealizedVol {
+    fn record_trade(&self, symbol: &str, ts_secs: i64, price: f64) {
+        let keep = self.settings.windows_secs.iter().copied().max().unwrap_or(900) as i64;
+        let Ok(mut samples) = self.samples.lock() else {
+            return;
+        };
+        let series = samples.entry(symbol.to_string()).or_default();
+        match series.back_mut() {
+            Some(last) if last.0 == ts_secs => last.1 = price,
+            Some(last) if last.0 > ts_secs => {}
+            _ => series.push_back((ts_secs, price)),
+        }
+        while series.front().is_some_and(|(t, _)| *t < ts_secs - keep) {
+            series.pop_front();
+        }
+    }
+
+    /// 指定窗口的实现波动率（折算为 1 小时标准差）
+    pub fn hourly_vol_over(&self, symbol: &str, window_secs: u64) -> Option<f64> {
+        let samples = self.samples.lock().ok()?;
+        let series = samples.get(&symbol.to_lowercase())?;
+        let latest = series.back()?.0;
+        let window: Vec<(i64, f64)> = series
+            .iter()
+            .filter(|(t, _)| *t >= latest - window_secs as i64)
+            .copied()
+            .collect();
+        if window.len() < MIN_SAMPLES {
+            return None;
+        }
+        let sum_sq: f64 = window.windows(2).map(|w| (w[1].1 / w[0].1).ln().powi(2)).sum();
+        let span = (window.last()?.0 - window.first()?.0).max(1) as f64;
+        Some((sum_sq * 3600.0 / span).sqrt())
+    }
+
+    fn primary_window(&self) -> u64 {
+        self.settings.windows_secs.first().copied().unwrap_or(900)
+    }
+}
+
+/// 启动成交流订阅；symbols 为系列名（如 btc、eth）
+pub fn spawn(settings: VolSettings, symbols: &[String]) {
+    if !settings.enabled {
+        if settings.filter_max_hourly > 0.0 || settings.gtd_reference_hourly > 0.0 {
+            warn!("VOL_FILTER_MAX_HOURLY / VOL_GTD_REFERENCE_HOURLY 需要 VOL_ESTIMATOR_ENABLED=true 才生效");
+        }
+        return;
+    }
+    let symbols: Vec<String> = symbols.iter().map(|s| s.trim().to_lowercase()).filter(|s| !s.is_empty()).collect();
+    if symbols.is_empty() || settings.windows_secs.is_empty() {
+        return;
+    }
+    let streams = symbols
+        .iter()
+        .map(|s| format!("{}@aggTrade", pair_symbol(s).to_lowercase()))
+        .collect::<Vec<_>>()
+        .join("/");
+    let url = format!("{}/stream?streams={}", settings.ws_url.trim_end_matches('/'), streams);
+    let vol = Arc::new(RealizedVol {
+        settings,
+        samples: Mutex::new(HashMap::new()),
+    });
+    if VOL.set(vol.clone()).is_err() {
+        return;
+    }
+    info!(
+        "📉 实现波动率估计已启动 | 币种:{} | 窗口:{:?}秒",
+        symbols.join(","),
+        vol.settings.windows_secs
+    );
+
+    let stream_vol = vol.clone();
+    tokio::spawn(async move {
+        let mut backoff = Duration::from_secs(1);
+        loop {
+            match tokio_tungstenite::connect_async(url.as_str()).await {
+                Ok((mut ws, _)) => {
+                    backoff = Duration::from_secs(1);
+                    while let Some(msg) = ws.next().await {
+                        match msg {
+                            Ok(Message::Text(text)) => {
+                                if let Some((symbol, ts, price)) = parse_agg_trade(&text) {
+                                    stream_vol.record_trade(&symbol, ts, price);
+                                }
+                            }
+                            Ok(Message::Close(_)) | Err(_) => break,
+                            Ok(_) => {}
+                        }
+                    }
+                    debug!("现货成交流断开，重连");
+                }
+                Err(e) => debug!(error = %e, "连接现货成交流失败"),
+            }
+            metrics::incr("spot_trades_reconnect");
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(Duration::from_secs(60));
+        }
+    });
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(LOG_INTERVAL);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            for symbol in &symbols {
+                let detail: Vec<String> = vol
+                    .settings
+                    .windows_secs
+                    .iter()
+                    .map(|w| match vol.hourly_vol_over(symbol, *w) {
+                        Some(v) => format!("{}s:{:.4}", w, v),
+                        None => format!("{}s:-", w),
+                    })
+                    .collect();
+                info!("📉 实现波动率（小时） | {} | {}", symbol, detail.join(" "));
+            }
+        }
+    });
+}
+
+/// 解析组合流中的 aggTrade 消息：返回 (系列名, 成交秒, 成交价)
+fn parse_agg_trade(text: &str) -> Option<(String, i64, f64)> {
+    let value: serde_json::Value = serde_json::from_str(text).ok()?;
+    let data = value.get("data").unwrap_or(&value);
+    let pair = data.get("s")?.as_str()?.to_lowercase();
+    let symbol = pair.strip_suffix("usdt").unwrap_or(&pair).to_string();
+    let price: f64 = data.get("p")?.as_str()?.parse().ok()?;
+    let ts_ms = data.get("T")?.as_i64()?;
+    (price > 0.0).then_some((symbol, ts_ms / 1000, price))
+}
+
+/// 主窗口的实现波动率（1 小时标准差）；未启用或样本不足时返回 None
+pub fn hourly_vol(symbol: &str) -> Option<f64> {
+    let vol = VOL.get()?;
+    vol.hourly_vol_over(symbol, vol.primary_window())
+}
+
+/// 波动率过滤：主窗口波动率超过上限时返回该波动率（调用方应跳过套利）
+pub fn exceeds_filter(symbol: &str) -> Option<f64> {
+    let vol = VOL.get()?;
+    if vol.settings.filter_max_hourly <= 0.0 {
+        return None;
+    }
+    hourly_vol(symbol).filter(|v| *v > vol.settings.filter_max_hourly)
+}
+
+/// 按当前波动率调节 GTD 有效期：取各币种中最高的波动率，高于参考值时按比例缩短（不低于基础值的 1/4）
+pub fn tuned_gtd_secs(base_secs: u64) -> u64 {
+    let Some(vol) = VOL.get() else {
+        return base_secs;
+    };
+    let reference = vol.settings.gtd_reference_hourly;
+    if reference <= 0.0 {
+        return base_secs;
+    }
+    let symbols: Vec<String> = match vol.samples.lock() {
+        Ok(samples) => samples.keys().cloned().collect(),
+        Err(_) => return base_secs,
+    };
+    let max_vol = symbols
+        .iter()
+        .filter_map(|s| vol.hourly_vol_over(s, vol.primary_window()))
+        .fold(0.0_f64, f64::max);
+    if max_vol <= reference {
+        return base_secs;
+    }
+    let factor = (reference / max_vol).max(GTD_MIN_FACTOR);
+    let tuned = ((base_secs as f64) * factor).round() as u64;
+    if tuned != base_secs {
+        debug!(base_secs, tuned, max_vol, "按波动率缩短 GTD 有效期");
+    }
+    tuned.max(1)
+}
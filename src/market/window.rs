@@ -0,0 +1,137 @@
+//! 窗口周期：Polymarket 加密货币涨跌市场有 15 分钟、1 小时、4 小时与每日四种周期（WINDOW_DURATION，可同时配置多个）。
+//! 窗口边界按市场时区计算：15 分钟 / 1 小时 / 4 小时对齐整点，每日窗口为中午 12 点到次日中午 12 点。
+//! 调度器以配置中最短的周期切换窗口；较长周期的市场在其窗口内每个调度窗口都会被重新发现（命中缓存），直到结束。
+//! 启动时由 Config.window_durations 设置一次，之后全局只读；未设置时为 1 小时。
+
+use chrono::{DateTime, Duration, Timelike, Utc};
+use std::sync::OnceLock;
+use tracing::warn;
+
+use super::clock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WindowDuration {
+    Min15,
+    Hour1,
+    Hour4,
+    Daily,
+}
+
+static DURATIONS: OnceLock<Vec<WindowDuration>> = OnceLock::new();
+
+impl WindowDuration {
+    /// 解析 15m / 1h / 4h / 1d（也接受 daily），无法识别时返回 None
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "15m" | "15min" => Some(WindowDuration::Min15),
+            "1h" | "60m" | "hourly" => Some(WindowDuration::Hour1),
+            "4h" => Some(WindowDuration::Hour4),
+            "1d" | "24h" | "daily" => Some(WindowDuration::Daily),
+            _ => None,
+        }
+    }
+
+    /// 按周期秒数匹配（用于 MARKET_SERIES 中的周期）
+    pub fn from_secs(secs: i64) -> Option<Self> {
+        [WindowDuration::Min15, WindowDuration::Hour1, WindowDuration::Hour4, WindowDuration::Daily]
+            .into_iter()
+            .find(|d| d.secs() == secs)
+    }
+
+    pub fn secs(self) -> i64 {
+        match self {
+            WindowDuration::Min15 => 900,
+            WindowDuration::Hour1 => 3600,
+            WindowDuration::Hour4 => 4 * 3600,
+            WindowDuration::Daily => 24 * 3600,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WindowDuration::Min15 => "15m",
+            WindowDuration::Hour1 => "1h",
+            WindowDuration::Hour4 => "4h",
+            WindowDuration::Daily => "1d",
+        }
+    }
+
+    /// slug 中的周期标记（如 btc-updown-15m-1768550400），1 小时与每日市场的 slug 没有标记
+    pub fn slug_marker(self) -> Option<&'static str> {
+        match self {
+            WindowDuration::Min15 => Some("-15m-"),
+            WindowDuration::Hour4 => Some("-4h-"),
+            _ => None,
+        }
+    }
+
+    /// 包含 now 的窗口开始时间戳（按市场时区对齐）
+    pub fn window_start(self, now: DateTime<Utc>) -> i64 {
+        let local = clock::to_market_time(now);
+        let aligned = match self {
+            WindowDuration::Min15 => local.with_minute(local.minute() / 15 * 15),
+            WindowDuration::Hour1 => local.with_minute(0),
+            WindowDuration::Hour4 => local.with_minute(0).and_then(|t| t.with_hour(t.hour() / 4 * 4)),
+            WindowDuration::Daily => {
+                // 中午 12 点之前属于前一天中午开始的窗口
+                let day = if local.hour() >= 12 { local } else { local - Duration::days(1) };
+                day.with_hour(12).and_then(|t| t.with_minute(0))
+            }
+        };
+        aligned
+            .and_then(|t| t.with_second(0))
+            .and_then(|t| t.with_nanosecond(0))
+            .unwrap_or(local)
+            .with_timezone(&Utc)
+            .timestamp()
+    }
+
+    /// 从窗口开始时间戳得到窗口结束（即下一个窗口开始）时间戳；按时区重新对齐，跨夏令时切换也准确
+    pub fn window_end(self, start: i64) -> i64 {
+        DateTime::from_timestamp(start + self.secs() + self.secs() / 2, 0)
+            .map(|t| self.window_start(t))
+            .unwrap_or(start + self.secs())
+    }
+}
+
+/// 设置监控的窗口周期（启动时调用一次，重复调用忽略）；为空时为 1 小时
+pub fn init(durations: &[WindowDuration]) {
+    let mut durations = durations.to_vec();
+    durations.sort_unstable();
+    durations.dedup();
+    if durations.is_empty() {
+        durations.push(WindowDuration::Hour1);
+    }
+    let _ = DURATIONS.set(durations);
+}
+
+/// 监控的窗口周期（从短到长）
+pub fn durations() -> &'static [WindowDuration] {
+    DURATIONS.get().map(Vec::as_slice).unwrap_or(&[WindowDuration::Hour1])
+}
+
+/// 调度窗口周期：监控的周期中最短的一个
+pub fn scheduler() -> WindowDuration {
+    durations().first().copied().unwrap_or(WindowDuration::Hour1)
+}
+
+/// 解析 WINDOW_DURATION：逗号分隔，如 "1h" 或 "15m,1h"；无法识别的条目忽略，全部无效时为 1 小时
+pub fn parse_list(s: &str) -> Vec<WindowDuration> {
+    let mut durations: Vec<WindowDuration> = s
+        .split(',')
+        .filter(|entry| !entry.trim().is_empty())
+        .filter_map(|entry| {
+            let parsed = WindowDuration::parse(entry);
+            if parsed.is_none() {
+                warn!(entry, "WINDOW_DURATION 条目无法识别，应为 15m / 1h / 4h / 1d，已跳过");
+            }
+            parsed
+        })
+        .collect();
+    durations.sort_unstable();
+    durations.dedup();
+    if durations.is_empty() {
+        durations.push(WindowDuration::Hour1);
+    }
+    durations
+}
@@ -1,7 +1,9 @@
 //! CTF Merge 模块：将等量 YES/NO 代币合并回 USDC。
 //!
 //! 支持 **Gnosis Safe**（execTransaction）与 **Magic/Email EIP-1167**（Polymarket Relayer）。
+//! 两种钱包都可走直接交易（EOA 付 gas）或 Relayer（代付 gas），首选路径失败或 gas 不足时自动切换到另一条。
 //! 合并数量自动取 `min(YES余额, NO余额)`，无需传入。
+//! 同一套提交路径也用于补交易所授权（`approve_exchanges`），供下单因授权不足被拒时自动修复。
 //!
 //! ## 调用示例
 //!
@@ -21,7 +23,9 @@ use std::env;
 use std::time::Duration;
 
 use alloy::primitives::{keccak256, Address, B256, Bytes, U256};
+use alloy::network::TransactionBuilder;
 use alloy::providers::{Provider, ProviderBuilder};
+use alloy::rpc::types::TransactionRequest;
 use alloy::signers::local::LocalSigner;
 use alloy::signers::Signer as _;
 use alloy::sol_types::SolCall;
@@ -34,6 +38,9 @@ use std::str::FromStr as _;
 use tokio::time::sleep;
 use tracing::{debug, info, warn};
 
+use crate::i18n::Msg;
+use crate::tr;
+
 use alloy::sol;
 sol! {
     #[sol(rpc)]
@@ -71,6 +78,33 @@ sol! {
     }
 }
 
+sol! {
+    interface IERC20Events {
+        event Transfer(address indexed from, address indexed to, uint256 value);
+    }
+}
+
+sol! {
+    #[sol(rpc)]
+    interface IERC20Approval {
+        function allowance(address owner, address spender) external view returns (uint256);
+        function approve(address spender, uint256 amount) external returns (bool);
+    }
+
+    #[sol(rpc)]
+    interface IERC1155Approval {
+        function isApprovedForAll(address account, address operator) external view returns (bool);
+        function setApprovalForAll(address operator, bool approved) external;
+    }
+}
+
+sol! {
+    #[sol(rpc)]
+    interface IMultiSendCallOnly {
+        function multiSend(bytes memory transactions) external payable;
+    }
+}
+
 sol! {
     struct ProxyCallTuple {
         uint8 typeCode;
@@ -81,9 +115,10 @@ sol! {
     function proxy(ProxyCallTuple[] calls) external payable returns (bytes[] returnValues);
 }
 
-const RPC_URL_DEFAULT: &str = "https://polygon-bor-rpc.publicnode.com";
+/// 默认 Polygon RPC
+pub const RPC_URL_DEFAULT: &str = "https://polygon-bor-rpc.publicnode.com";
 const RELAYER_URL_DEFAULT: &str = "https://relayer-v2.polymarket.com";
-const USDC_POLYGON: Address = address!("0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174");
+pub const USDC_POLYGON: Address = address!("0x2791Bca1f2de4661ED88A30C99A7a9449Aa84174");
 
 const RELAYER_GET_RELAY_PAYLOAD: &str = "/relay-payload";
 const RELAYER_SUBMIT: &str = "/submit";
@@ -95,10 +130,59 @@ const PROXY_INIT_CODE_HASH: [u8; 32] = [
     0x86, 0x92, 0x87, 0xab, 0x0b, 0x05, 0x8b, 0xe0, 0x5a, 0xa9, 0xe8, 0xaf, 0x63, 0x30, 0xa0, 0x0b,
 ];
 const PROXY_DEFAULT_GAS: u64 = 160_000;
+/// Safe v1.3.0 MultiSendCallOnly（Polygon），Safe 路径批量 merge 时 delegatecall 到此合约，可通过 MERGE_SAFE_MULTISEND_ADDRESS 覆盖
+const SAFE_MULTISEND_CALL_ONLY: Address = address!("0x40A2aCCbd92BCA938b02010E17A5b8929b49130D");
+/// Safe execTransaction 的 operation：0=call，1=delegatecall
+const SAFE_OP_CALL: u8 = 0;
+const SAFE_OP_DELEGATECALL: u8 = 1;
+/// 直接交易失败多少次后切换到 Relayer，可通过 MERGE_DIRECT_MAX_ATTEMPTS 覆盖
+const MERGE_DIRECT_MAX_ATTEMPTS_DEFAULT: u32 = 2;
+/// EOA 余额低于此值（POL）时视为 gas 不足，直接切换到 Relayer，可通过 MERGE_MIN_GAS_POL 覆盖
+const MERGE_MIN_GAS_POL_DEFAULT: f64 = 0.05;
 /// RPC 限速时等待后重试的时长（略大于 "retry in 10s"），可通过 MERGE_RPC_RATE_LIMIT_BACKOFF_SECS 覆盖
 const RPC_RATE_LIMIT_BACKOFF_DEFAULT: u64 = 12;
 /// 每个市场之间的 RPC 调用间隔（秒），可通过 MERGE_RPC_DELAY_SECS 覆盖
 const DELAY_BETWEEN_MARKETS_SECS_DEFAULT: u64 = 30;
+/// USDC 授权低于该值（100 万 USDC，6 位小数）时视为需要重新授权，授权额度为 uint256 最大值
+const MIN_USDC_ALLOWANCE: u64 = 1_000_000_000_000;
+
+/// 一笔由 EOA 付 gas 的链上交易的花费（Relayer 路径由 Relayer 付 gas，不记录）
+#[derive(Debug, Clone)]
+pub struct GasSpend {
+    /// 操作类型，如 "merge"
+    pub op: &'static str,
+    pub tx_hash: String,
+    pub gas_used: u64,
+    /// 实际 gas 价格（wei）
+    pub effective_gas_price: u128,
+}
+
+impl GasSpend {
+    /// 花费的 POL 数量
+    pub fn cost_pol(&self) -> f64 {
+        self.gas_used as f64 * self.effective_gas_price as f64 / 1e18
+    }
+}
+
+fn gas_spends() -> &'static std::sync::Mutex<Vec<GasSpend>> {
+    static GAS_SPENDS: std::sync::OnceLock<std::sync::Mutex<Vec<GasSpend>>> = std::sync::OnceLock::new();
+    GAS_SPENDS.get_or_init(|| std::sync::Mutex::new(Vec::new()))
+}
+
+fn record_gas_spend(spend: GasSpend) {
+    debug!(tx = %spend.tx_hash, gas_used = spend.gas_used, cost_pol = spend.cost_pol(), "记录 gas 花费");
+    if let Ok(mut spends) = gas_spends().lock() {
+        spends.push(spend);
+    }
+}
+
+/// 取出自上次调用以来记录的所有 gas 花费（供 PnL 统计按 POL 价格折算 USD）
+pub fn take_gas_spends() -> Vec<GasSpend> {
+    gas_spends()
+        .lock()
+        .map(|mut spends| std::mem::take(&mut *spends))
+        .unwrap_or_default()
+}
 
 /// 将 0x 开头的长 hex 缩短为 `0x` + 前 8 位 + `..` + 后 6 位，便于日志。
 pub fn short_hex(s: &str) -> String {
@@ -132,6 +216,11 @@ fn encode_merge_calldata(req: &MergePositionsRequest) -> Vec<u8> {
     out
 }
 
+/// EOA 对应的 Magic/Email 代理钱包地址（ProxyFactory CREATE2 推导）
+pub fn expected_proxy_wallet(eoa: Address) -> Address {
+    derive_proxy_wallet(eoa, PROXY_FACTORY)
+}
+
 fn derive_proxy_wallet(eoa: Address, proxy_factory: Address) -> Address {
     let salt = keccak256(eoa.as_slice());
     let mut buf = [0u8; 1 + 20 + 32 + 32];
@@ -189,13 +278,16 @@ async fn get_relay_payload(client: &reqwest::Client, base: &str, eoa: Address) -
     Ok((relay, nonce.to_string()))
 }
 
-/// 将多个 merge 调用编码为一次 proxy(calls[]) 调用，用于批量提交。
-fn encode_proxy_calls_batch(ctf: Address, calldatas: &[Vec<u8>]) -> Vec<u8> {
-    let calls: Vec<ProxyCallTuple> = calldatas
+/// 代理钱包要执行的一笔调用：(目标合约, calldata)
+type WalletCall = (Address, Vec<u8>);
+
+/// 将多个调用编码为一次 proxy(calls[]) 调用，用于批量提交。
+fn encode_proxy_calls_batch(calls: &[WalletCall]) -> Vec<u8> {
+    let calls: Vec<ProxyCallTuple> = calls
         .iter()
-        .map(|data| ProxyCallTuple {
+        .map(|(to, data)| ProxyCallTuple {
             typeCode: 1u8,
-            to: ctf,
+            to: *to,
             value: U256::ZERO,
             data: Bytes::from(data.clone()),
         })
@@ -203,6 +295,67 @@ fn encode_proxy_calls_batch(ctf: Address, calldatas: &[Vec<u8>]) -> Vec<u8> {
     proxyCall { calls }.abi_encode().to_vec()
 }
 
+/// 将多个调用编码为 MultiSend 的 multiSend(bytes) 调用：每笔按
+/// operation(uint8) | to(20字节) | value(uint256) | dataLength(uint256) | data 紧密拼接。
+fn encode_multisend_batch(calls: &[WalletCall]) -> Vec<u8> {
+    let mut packed = Vec::new();
+    for (to, data) in calls {
+        packed.push(SAFE_OP_CALL);
+        packed.extend_from_slice(to.as_slice());
+        packed.extend_from_slice(&U256::ZERO.to_be_bytes::<32>());
+        packed.extend_from_slice(&U256::from(data.len()).to_be_bytes::<32>());
+        packed.extend_from_slice(data);
+    }
+    IMultiSendCallOnly::multiSendCall { transactions: Bytes::from(packed) }
+        .abi_encode()
+        .to_vec()
+}
+
+/// 以 EOA 作为唯一 owner 签名并执行 Safe.execTransaction，等待回执后返回交易哈希
+async fn safe_exec_transaction<P: Provider>(
+    provider: P,
+    proxy: Address,
+    signer: &impl alloy::signers::Signer,
+    to: Address,
+    data: Vec<u8>,
+    operation: u8,
+    op: &'static str,
+) -> Result<String> {
+    let safe = IGnosisSafe::new(proxy, provider);
+    let nonce: U256 = safe.nonce().call().await.map_err(|e| {
+        let msg = e.to_string();
+        let hint = if msg.contains("revert") || msg.contains("reverted") {
+            " 该地址可能不是 Gnosis Safe；Magic/Email 请用 Relayer 或网页 merge。"
+        } else { "" };
+        anyhow::anyhow!("读取 Safe nonce 失败: {}{}", msg, hint)
+    })?;
+
+    let tx_hash_data = safe
+        .encodeTransactionData(to, U256::ZERO, data.clone().into(), operation, U256::ZERO, U256::ZERO, U256::ZERO, Address::ZERO, Address::ZERO, nonce)
+        .call().await.map_err(|e| anyhow::anyhow!("Safe.encodeTransactionData 失败: {}", e))?.0;
+
+    let tx_hash = keccak256(tx_hash_data.as_ref());
+    let sig = signer.sign_hash(&tx_hash).await.map_err(|e| anyhow::anyhow!("签名失败: {}", e))?;
+    let mut sig_bytes = sig.as_bytes().to_vec();
+    if sig_bytes.len() == 65 && (sig_bytes[64] == 0 || sig_bytes[64] == 1) {
+        sig_bytes[64] += 27;
+    }
+
+    let pending = safe
+        .execTransaction(to, U256::ZERO, data.into(), operation, U256::ZERO, U256::ZERO, U256::ZERO, Address::ZERO, Address::ZERO, sig_bytes.into())
+        .send().await.map_err(|e| anyhow::anyhow!("Safe.execTransaction 失败: {}", e))?;
+
+    let tx_hash_out = *pending.tx_hash();
+    let receipt = pending.get_receipt().await.map_err(|e| anyhow::anyhow!("等待 receipt 失败: {}", e))?;
+    record_gas_spend(GasSpend {
+        op,
+        tx_hash: format!("{:#x}", tx_hash_out),
+        gas_used: receipt.gas_used,
+        effective_gas_price: receipt.effective_gas_price,
+    });
+    Ok(format!("{:#x}", tx_hash_out))
+}
+
 fn create_struct_hash(
     from: Address,
     to: Address,
@@ -235,9 +388,17 @@ fn eip191_hash(struct_hash: B256) -> B256 {
     keccak256(msg)
 }
 
-async fn relayer_execute_merge(
-    calldatas: &[Vec<u8>],
-    ctf_address: Address,
+/// Relayer 请求的 metadata 描述
+fn relay_metadata(op: &str) -> &'static str {
+    match op {
+        "approve" => "Approve exchanges",
+        _ => "Merge positions",
+    }
+}
+
+async fn relayer_execute_proxy(
+    calls: &[WalletCall],
+    op: &'static str,
     proxy_wallet: Address,
     signer: &impl alloy::signers::Signer,
     builder_key: &str,
@@ -250,12 +411,12 @@ async fn relayer_execute_merge(
     let base = relayer_url.trim_end_matches('/');
 
     let (relay, nonce) = get_relay_payload(&client, base, eoa).await?;
-    let proxy_data = encode_proxy_calls_batch(ctf_address, calldatas);
+    let proxy_data = encode_proxy_calls_batch(calls);
     let gas_per_call: u64 = env::var("MERGE_PROXY_GAS_LIMIT")
         .ok()
         .and_then(|s| s.trim().parse().ok())
         .unwrap_or(PROXY_DEFAULT_GAS);
-    let gas_limit = gas_per_call * calldatas.len().max(1) as u64;
+    let gas_limit = gas_per_call * calls.len().max(1) as u64;
 
     if env::var("MERGE_PROXY_TO").map(|s| s.trim().eq_ignore_ascii_case("PROXY_WALLET")).unwrap_or(false) {
         info!("ℹ️ MERGE_PROXY_TO=PROXY_WALLET 已忽略，使用 to=PROXY_FACTORY");
@@ -286,9 +447,21 @@ async fn relayer_execute_merge(
         "signature": signature_hex,
         "signatureParams": signature_params,
         "type": "PROXY",
-        "metadata": "Merge positions"
+        "metadata": relay_metadata(op)
     });
-    let body_str = serde_json::to_string(&body)?;
+    relayer_submit(&client, base, &body, builder_key, builder_secret, builder_passphrase).await
+}
+
+/// 带 Builder HMAC 签名提交 Relayer /submit，返回交易哈希（响应无哈希时返回原文）
+async fn relayer_submit(
+    client: &reqwest::Client,
+    base: &str,
+    body: &serde_json::Value,
+    builder_key: &str,
+    builder_secret: &str,
+    builder_passphrase: &str,
+) -> Result<String> {
+    let body_str = serde_json::to_string(body)?;
 
     let path = RELAYER_SUBMIT;
     let method = "POST";
@@ -325,6 +498,239 @@ async fn relayer_execute_merge(
     Ok(hash.unwrap_or_else(|| text))
 }
 
+/// Gnosis Safe 经 Relayer 执行（type=SAFE）：EOA 对 SafeTx 哈希做 eth_sign（v+4），由 Relayer 代付 gas 提交
+async fn relayer_execute_safe<P: Provider>(
+    provider: P,
+    safe_address: Address,
+    signer: &impl alloy::signers::Signer,
+    to: Address,
+    data: Vec<u8>,
+    operation: u8,
+    op: &'static str,
+    creds: &BuilderCreds,
+) -> Result<String> {
+    let safe = IGnosisSafe::new(safe_address, provider);
+    let nonce: U256 = safe.nonce().call().await.map_err(|e| anyhow::anyhow!("读取 Safe nonce 失败: {}", e))?;
+    let tx_hash_data = safe
+        .encodeTransactionData(to, U256::ZERO, data.clone().into(), operation, U256::ZERO, U256::ZERO, U256::ZERO, Address::ZERO, Address::ZERO, nonce)
+        .call().await.map_err(|e| anyhow::anyhow!("Safe.encodeTransactionData 失败: {}", e))?.0;
+    let safe_tx_hash = keccak256(tx_hash_data.as_ref());
+    let sig = signer.sign_hash(&eip191_hash(safe_tx_hash)).await.map_err(|e| anyhow::anyhow!("EOA 签名失败: {}", e))?;
+    let mut sig_bytes = sig.as_bytes().to_vec();
+    // Safe 的 eth_sign 签名类型要求 v = 31/32
+    if sig_bytes.len() == 65 {
+        sig_bytes[64] = match sig_bytes[64] {
+            0 | 1 => sig_bytes[64] + 31,
+            27 | 28 => sig_bytes[64] + 4,
+            v => v,
+        };
+    }
+
+    let body = serde_json::json!({
+        "from": format!("{:#x}", signer.address()),
+        "to": format!("{:#x}", to),
+        "proxyWallet": format!("{:#x}", safe_address),
+        "data": to_hex_0x(&data),
+        "nonce": nonce.to_string(),
+        "signature": to_hex_0x(&sig_bytes),
+        "signatureParams": {
+            "gasPrice": "0",
+            "operation": operation.to_string(),
+            "safeTxnGas": "0",
+            "baseGas": "0",
+            "gasToken": format!("{:#x}", Address::ZERO),
+            "refundReceiver": format!("{:#x}", Address::ZERO)
+        },
+        "type": "SAFE",
+        "metadata": relay_metadata(op)
+    });
+    let client = reqwest::Client::new();
+    relayer_submit(&client, creds.relayer_url.trim_end_matches('/'), &body, &creds.key, &creds.secret, &creds.passphrase).await
+}
+
+/// Magic/Email 代理钱包直接由 EOA 调用 ProxyFactory.proxy(calls[])（EOA 付 gas），等待回执后返回交易哈希
+async fn direct_execute_proxy<P: Provider>(provider: P, calls: &[WalletCall], op: &'static str) -> Result<String> {
+    let proxy_data = encode_proxy_calls_batch(calls);
+    let tx = TransactionRequest::default().with_to(PROXY_FACTORY).with_input(proxy_data);
+    let pending = provider
+        .send_transaction(tx)
+        .await
+        .map_err(|e| anyhow::anyhow!("ProxyFactory.proxy 发送失败: {}", e))?;
+    let tx_hash_out = *pending.tx_hash();
+    let receipt = pending.get_receipt().await.map_err(|e| anyhow::anyhow!("等待 receipt 失败: {}", e))?;
+    if !receipt.status() {
+        anyhow::bail!("ProxyFactory.proxy 交易回滚: {:#x}", tx_hash_out);
+    }
+    record_gas_spend(GasSpend {
+        op,
+        tx_hash: format!("{:#x}", tx_hash_out),
+        gas_used: receipt.gas_used,
+        effective_gas_price: receipt.effective_gas_price,
+    });
+    Ok(format!("{:#x}", tx_hash_out))
+}
+
+/// Relayer Builder 凭证（POLY_BUILDER_API_KEY / SECRET / PASSPHRASE，RELAYER_URL 可选）
+struct BuilderCreds {
+    key: String,
+    secret: String,
+    passphrase: String,
+    relayer_url: String,
+}
+
+impl BuilderCreds {
+    fn from_env() -> Option<Self> {
+        Some(Self {
+            key: env::var("POLY_BUILDER_API_KEY").ok().filter(|s| !s.trim().is_empty())?,
+            secret: env::var("POLY_BUILDER_SECRET").ok().filter(|s| !s.trim().is_empty())?,
+            passphrase: env::var("POLY_BUILDER_PASSPHRASE").ok().filter(|s| !s.trim().is_empty())?,
+            relayer_url: env::var("RELAYER_URL").unwrap_or_else(|_| RELAYER_URL_DEFAULT.to_string()),
+        })
+    }
+}
+
+/// Merge 提交路径：直接交易（EOA 付 gas）或 Relayer（代付 gas）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MergeRoute {
+    Direct,
+    Relayer,
+}
+
+impl MergeRoute {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MergeRoute::Direct => "direct",
+            MergeRoute::Relayer => "relayer",
+        }
+    }
+}
+
+/// 按路径提交一组 merge 调用，见 `submit_wallet_calls`
+async fn submit_merge_calls<P: Provider + Clone>(
+    provider: P,
+    signer: &impl alloy::signers::Signer,
+    proxy: Address,
+    ctf: Address,
+    calldatas: &[Vec<u8>],
+    is_safe: bool,
+) -> Result<String> {
+    let calls: Vec<WalletCall> = calldatas.iter().map(|data| (ctf, data.clone())).collect();
+    submit_wallet_calls(provider, signer, proxy, &calls, is_safe, "merge").await
+}
+
+/// 按路径让代理钱包执行一组调用，首选路径失败（直接交易重试 MERGE_DIRECT_MAX_ATTEMPTS 次仍失败、EOA gas 不足、
+/// 或缺少 Builder 凭证）时自动切换到另一条路径。Safe 首选直接交易，Magic/Email 代理钱包首选 Relayer；
+/// MERGE_PREFERRED_ROUTE=direct|relayer 可覆盖。每次提交记录实际使用的路径。
+async fn submit_wallet_calls<P: Provider + Clone>(
+    provider: P,
+    signer: &impl alloy::signers::Signer,
+    proxy: Address,
+    calls: &[WalletCall],
+    is_safe: bool,
+    op: &'static str,
+) -> Result<String> {
+    let default_first = if is_safe { MergeRoute::Direct } else { MergeRoute::Relayer };
+    let first = match env::var("MERGE_PREFERRED_ROUTE").map(|s| s.trim().to_lowercase()).as_deref() {
+        Ok("direct") => MergeRoute::Direct,
+        Ok("relayer") => MergeRoute::Relayer,
+        _ => default_first,
+    };
+    let routes = if first == MergeRoute::Direct {
+        [MergeRoute::Direct, MergeRoute::Relayer]
+    } else {
+        [MergeRoute::Relayer, MergeRoute::Direct]
+    };
+    let direct_attempts: u32 = env::var("MERGE_DIRECT_MAX_ATTEMPTS")
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(MERGE_DIRECT_MAX_ATTEMPTS_DEFAULT)
+        .max(1);
+    let min_gas_pol: f64 = env::var("MERGE_MIN_GAS_POL")
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(MERGE_MIN_GAS_POL_DEFAULT);
+
+    // Safe 路径：单笔直接 call 目标合约，多笔经 MultiSend delegatecall 打包为一笔
+    let (safe_to, safe_data, safe_op) = if let [(to, data)] = calls {
+        (*to, data.clone(), SAFE_OP_CALL)
+    } else {
+        let multisend = env::var("MERGE_SAFE_MULTISEND_ADDRESS")
+            .ok()
+            .and_then(|s| s.trim().parse::<Address>().ok())
+            .unwrap_or(SAFE_MULTISEND_CALL_ONLY);
+        (multisend, encode_multisend_batch(calls), SAFE_OP_DELEGATECALL)
+    };
+
+    let mut last_err = anyhow::anyhow!("没有可用的提交路径");
+    for (index, route) in routes.iter().enumerate() {
+        if index > 0 {
+            warn!("{}", tr!(Msg::MergeRouteFallback, routes[0].as_str(), route.as_str()));
+        }
+        match route {
+            MergeRoute::Direct => {
+                let balance = provider.get_balance(signer.address()).await.unwrap_or(U256::ZERO);
+                let balance_pol = balance.to_string().parse::<f64>().unwrap_or(0.0) / 1e18;
+                if balance_pol < min_gas_pol {
+                    warn!("EOA gas 不足（{:.4} POL < {} POL），跳过直接交易", balance_pol, min_gas_pol);
+                    last_err = anyhow::anyhow!("EOA gas 不足: {:.4} POL", balance_pol);
+                    continue;
+                }
+                for attempt in 1..=direct_attempts {
+                    let result = if is_safe {
+                        safe_exec_transaction(provider.clone(), proxy, signer, safe_to, safe_data.clone(), safe_op, op).await
+                    } else {
+                        direct_execute_proxy(provider.clone(), calls, op).await
+                    };
+                    match result {
+                        Ok(tx) => {
+                            info!(route = route.as_str(), op, "{}", tr!(Msg::MergeSubmittedDirect, calls.len(), tx));
+                            return Ok(tx);
+                        }
+                        Err(e) => {
+                            warn!(route = route.as_str(), op, attempt, error = %e, "直接交易失败");
+                            last_err = e;
+                        }
+                    }
+                }
+            }
+            MergeRoute::Relayer => {
+                let Some(creds) = BuilderCreds::from_env() else {
+                    last_err = anyhow::anyhow!(
+                        "Relayer 需配置 POLY_BUILDER_API_KEY、POLY_BUILDER_SECRET、POLY_BUILDER_PASSPHRASE"
+                    );
+                    continue;
+                };
+                let result = if is_safe {
+                    relayer_execute_safe(provider.clone(), proxy, signer, safe_to, safe_data.clone(), safe_op, op, &creds).await
+                } else {
+                    relayer_execute_proxy(
+                        calls,
+                        op,
+                        proxy,
+                        signer,
+                        &creds.key,
+                        &creds.secret,
+                        &creds.passphrase,
+                        &creds.relayer_url,
+                    )
+                    .await
+                };
+                match result {
+                    Ok(tx) => {
+                        info!(route = route.as_str(), op, "{}", tr!(Msg::MergeSubmittedRelayer, calls.len(), tx));
+                        return Ok(tx);
+                    }
+                    Err(e) => {
+                        warn!(route = route.as_str(), op, error = %e, "Relayer 提交失败");
+                        last_err = e;
+                    }
+                }
+            }
+        }
+    }
+    Err(last_err)
+}
+
 /// 对指定 `condition_id` 在 `proxy` 上合并最大可用 YES+NO 为 USDC。
 ///
 /// 合并数量为 `min(YES余额, NO余额)`。支持 Gnosis Safe（execTransaction）与 Magic/Email（Relayer）。
@@ -343,6 +749,7 @@ pub async fn merge_max(
     private_key: &str,
     rpc_url: Option<&str>,
 ) -> Result<String> {
+    crate::chaos::rpc_guard("merge_max")?;
     let rpc = rpc_url.unwrap_or(RPC_URL_DEFAULT);
     let chain = POLYGON;
     let signer = LocalSigner::from_str(private_key)?.with_chain_id(Some(chain));
@@ -377,69 +784,116 @@ pub async fn merge_max(
     let merge_req = MergePositionsRequest::for_binary_market(USDC_POLYGON, condition_id, merge_amount);
     let merge_calldata = encode_merge_calldata(&merge_req);
     let code = provider.get_code_at(proxy).await.unwrap_or_default();
+    let is_safe = code.len() >= 150;
+    if !is_safe {
+        check_derived_proxy(wallet, proxy)?;
+    }
+    submit_merge_calls(provider, &signer, proxy, ctf, &[merge_calldata], is_safe).await
+}
 
-    if code.len() < 150 {
-        let derived = derive_proxy_wallet(wallet, PROXY_FACTORY);
-        let try_anyway = env::var("MERGE_TRY_ANYWAY").map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true")).unwrap_or(false);
-        if derived != proxy {
-            if !try_anyway {
-                anyhow::bail!(
-                    "POLYMARKET_PROXY_ADDRESS ({:?}) 与 ProxyFactory 的 CREATE2 推导 ({:?}) 不一致。\
-                     请改用 Polymarket 网页 merge，或设 MERGE_TRY_ANYWAY=1 强行尝试。",
-                    proxy, derived
-                );
-            }
-            warn!("MERGE_TRY_ANYWAY=1：derive != proxy，仍发 Relayer 请求。");
-        }
-        let builder_key = env::var("POLY_BUILDER_API_KEY").ok();
-        let builder_secret = env::var("POLY_BUILDER_SECRET").ok();
-        let builder_passphrase = env::var("POLY_BUILDER_PASSPHRASE").ok();
-        let relayer_url = env::var("RELAYER_URL").unwrap_or_else(|_| RELAYER_URL_DEFAULT.to_string());
-        match (builder_key.as_deref(), builder_secret.as_deref(), builder_passphrase.as_deref()) {
-            (Some(k), Some(s), Some(p)) => {
-                let out = relayer_execute_merge(&[merge_calldata], ctf, proxy, &signer, k, s, p, &relayer_url).await?;
-                info!("✅ Relayer 已提交 tx: {}", out);
-                return Ok(out);
-            }
-            _ => anyhow::bail!(
-                "Magic/Email 需配置 POLY_BUILDER_API_KEY、POLY_BUILDER_SECRET、POLY_BUILDER_PASSPHRASE；或改用网页 merge。",
-            ),
-        }
+/// Magic/Email 代理钱包：POLYMARKET_PROXY_ADDRESS 须与 EOA 经 ProxyFactory CREATE2 推导的地址一致（MERGE_TRY_ANYWAY=1 可跳过）
+fn check_derived_proxy(wallet: Address, proxy: Address) -> Result<()> {
+    let derived = derive_proxy_wallet(wallet, PROXY_FACTORY);
+    if derived == proxy {
+        return Ok(());
+    }
+    let try_anyway = env::var("MERGE_TRY_ANYWAY")
+        .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+    if !try_anyway {
+        anyhow::bail!(
+            "POLYMARKET_PROXY_ADDRESS ({:?}) 与 ProxyFactory 的 CREATE2 推导 ({:?}) 不一致。\
+             请改用 Polymarket 网页 merge，或设 MERGE_TRY_ANYWAY=1 强行尝试。",
+            proxy,
+            derived
+        );
     }
+    warn!("MERGE_TRY_ANYWAY=1：derive != proxy，仍发 Relayer 请求。");
+    Ok(())
+}
 
-    let safe = IGnosisSafe::new(proxy, provider);
-    let nonce: U256 = safe.nonce().call().await.map_err(|e| {
-        let msg = e.to_string();
-        let hint = if msg.contains("revert") || msg.contains("reverted") {
-            " 该地址可能不是 Gnosis Safe；Magic/Email 请用 Relayer 或网页 merge。"
-        } else { "" };
-        anyhow::anyhow!("读取 Safe nonce 失败: {}{}", msg, hint)
-    })?;
+/// 补齐交易所授权：检查资金地址（`proxy`，为 None 时为 EOA 本身）对 CTF Exchange 与 NegRisk Exchange 的
+/// USDC 授权（低于 100 万 USDC 视为不足）与 CTF `setApprovalForAll`，只为缺失的部分发授权。
+/// 代理钱包走与 merge 相同的提交路径（直接交易 / Relayer 自动切换），EOA 直接逐笔发交易。
+///
+/// 全部已授权时返回 `Ok(None)`（下单被拒的原因不在授权），否则返回交易哈希（多笔时逗号分隔）。
+pub async fn approve_exchanges(
+    proxy: Option<Address>,
+    private_key: &str,
+    rpc_url: Option<&str>,
+) -> Result<Option<String>> {
+    crate::chaos::rpc_guard("approve_exchanges")?;
+    let rpc = rpc_url.unwrap_or(RPC_URL_DEFAULT);
+    let chain = POLYGON;
+    let signer = LocalSigner::from_str(private_key)?.with_chain_id(Some(chain));
+    let wallet = signer.address();
+    let funder = proxy.unwrap_or(wallet);
 
-    let tx_hash_data = safe
-        .encodeTransactionData(ctf, U256::ZERO, merge_calldata.clone().into(), 0u8, U256::ZERO, U256::ZERO, U256::ZERO, Address::ZERO, Address::ZERO, nonce)
-        .call().await.map_err(|e| anyhow::anyhow!("Safe.encodeTransactionData 失败: {}", e))?.0;
+    let provider = ProviderBuilder::new().wallet(signer.clone()).connect(rpc).await?;
+    let config = contract_config(chain, false).ok_or_else(|| anyhow::anyhow!("不支持的 chain_id: {}", chain))?;
+    let mut spenders = vec![config.exchange];
+    if let Some(neg_risk) = contract_config(chain, true) {
+        spenders.push(neg_risk.exchange);
+    }
+    let usdc = IERC20Approval::new(USDC_POLYGON, provider.clone());
+    let ctf = IERC1155Approval::new(config.conditional_tokens, provider.clone());
 
-    let tx_hash = keccak256(tx_hash_data.as_ref());
-    let sig = signer.sign_hash(&tx_hash).await.map_err(|e| anyhow::anyhow!("签名失败: {}", e))?;
-    let mut sig_bytes = sig.as_bytes().to_vec();
-    if sig_bytes.len() == 65 && (sig_bytes[64] == 0 || sig_bytes[64] == 1) {
-        sig_bytes[64] += 27;
+    let mut calls: Vec<WalletCall> = Vec::new();
+    for spender in spenders {
+        let allowance: U256 = usdc.allowance(funder, spender).call().await?;
+        if allowance < U256::from(MIN_USDC_ALLOWANCE) {
+            info!("🔑 USDC 授权不足 | spender={:#x} | 当前:{}", spender, allowance);
+            calls.push((USDC_POLYGON, IERC20Approval::approveCall { spender, amount: U256::MAX }.abi_encode()));
+        }
+        if !ctf.isApprovedForAll(funder, spender).call().await? {
+            info!("🔑 CTF 未授权 | operator={:#x}", spender);
+            calls.push((
+                config.conditional_tokens,
+                IERC1155Approval::setApprovalForAllCall { operator: spender, approved: true }.abi_encode(),
+            ));
+        }
+    }
+    if calls.is_empty() {
+        return Ok(None);
     }
 
-    let pending = safe
-        .execTransaction(ctf, U256::ZERO, merge_calldata.into(), 0u8, U256::ZERO, U256::ZERO, U256::ZERO, Address::ZERO, Address::ZERO, sig_bytes.into())
-        .send().await.map_err(|e| anyhow::anyhow!("Safe.execTransaction 失败: {}", e))?;
+    let Some(proxy) = proxy else {
+        // EOA 直接持有资金：逐笔发授权交易
+        let mut txs = Vec::with_capacity(calls.len());
+        for (to, data) in calls {
+            let tx = TransactionRequest::default().with_to(to).with_input(data);
+            let pending = provider
+                .send_transaction(tx)
+                .await
+                .map_err(|e| anyhow::anyhow!("授权交易发送失败: {}", e))?;
+            let tx_hash = *pending.tx_hash();
+            let receipt = pending.get_receipt().await.map_err(|e| anyhow::anyhow!("等待 receipt 失败: {}", e))?;
+            if !receipt.status() {
+                anyhow::bail!("授权交易回滚: {:#x}", tx_hash);
+            }
+            record_gas_spend(GasSpend {
+                op: "approve",
+                tx_hash: format!("{:#x}", tx_hash),
+                gas_used: receipt.gas_used,
+                effective_gas_price: receipt.effective_gas_price,
+            });
+            txs.push(format!("{:#x}", tx_hash));
+        }
+        return Ok(Some(txs.join(",")));
+    };
 
-    let tx_hash_out = *pending.tx_hash();
-    let _receipt = pending.get_receipt().await.map_err(|e| anyhow::anyhow!("等待 receipt 失败: {}", e))?;
-    info!("✅ Merge 成功（Safe）tx: {:#x}", tx_hash_out);
-    Ok(format!("{:#x}", tx_hash_out))
+    let code = provider.get_code_at(proxy).await.unwrap_or_default();
+    let is_safe = code.len() >= 150;
+    if !is_safe {
+        check_derived_proxy(wallet, proxy)?;
+    }
+    submit_wallet_calls(provider, &signer, proxy, &calls, is_safe, "approve").await.map(Some)
 }
 
 /// 批量合并多个市场的 YES+NO 为 USDC，一次 Relayer 请求 / 一笔链上交易。
 ///
-/// 仅 **Magic/Email（Relayer）** 路径支持真正的批量；**Gnosis Safe** 会退化为串行执行。
+/// **Magic/Email（Relayer）** 路径通过 proxy(calls[]) 批量；**Gnosis Safe** 路径通过 MultiSendCallOnly
+/// （delegatecall）把多笔 merge 打包为一笔 execTransaction，失败时退化为串行执行。
 ///
 /// - `condition_ids`: 市场的 condition ID 列表
 /// - `proxy`: Proxy 地址
@@ -456,6 +910,7 @@ pub async fn merge_max_batch(
     if condition_ids.is_empty() {
         anyhow::bail!("merge_max_batch: condition_ids 为空");
     }
+    crate::chaos::rpc_guard("merge_max_batch")?;
 
     let rpc = rpc_url.unwrap_or(RPC_URL_DEFAULT);
     let chain = POLYGON;
@@ -512,7 +967,7 @@ pub async fn merge_max_batch(
                 Err(e) => {
                     let msg = e.to_string();
                     if msg.contains("rate limit") || msg.contains("retry in") {
-                        warn!(condition_id = %condition_id, "⏳ RPC 限速，等待 {}s 后重试", rate_limit_backoff.as_secs());
+                        warn!(condition_id = %condition_id, "{}", tr!(Msg::MergeRateLimited, rate_limit_backoff.as_secs()));
                         rate_limited = true;
                         break;
                     }
@@ -524,7 +979,7 @@ pub async fn merge_max_batch(
                 Err(e) => {
                     let msg = e.to_string();
                     if msg.contains("rate limit") || msg.contains("retry in") {
-                        warn!(condition_id = %condition_id, "⏳ RPC 限速，等待 {}s 后重试", rate_limit_backoff.as_secs());
+                        warn!(condition_id = %condition_id, "{}", tr!(Msg::MergeRateLimited, rate_limit_backoff.as_secs()));
                         rate_limited = true;
                         break;
                     }
@@ -545,7 +1000,7 @@ pub async fn merge_max_batch(
                 Err(e) => {
                     let msg = e.to_string();
                     if msg.contains("rate limit") || msg.contains("retry in") {
-                        warn!(condition_id = %condition_id, "⏳ RPC 限速，等待 {}s 后重试", rate_limit_backoff.as_secs());
+                        warn!(condition_id = %condition_id, "{}", tr!(Msg::MergeRateLimited, rate_limit_backoff.as_secs()));
                         rate_limited = true;
                         break;
                     }
@@ -557,7 +1012,7 @@ pub async fn merge_max_batch(
                 Err(e) => {
                     let msg = e.to_string();
                     if msg.contains("rate limit") || msg.contains("retry in") {
-                        warn!(condition_id = %condition_id, "⏳ RPC 限速，等待 {}s 后重试", rate_limit_backoff.as_secs());
+                        warn!(condition_id = %condition_id, "{}", tr!(Msg::MergeRateLimited, rate_limit_backoff.as_secs()));
                         rate_limited = true;
                         break;
                     }
@@ -599,57 +1054,78 @@ pub async fn merge_max_batch(
     }
 
     let code = provider.get_code_at(proxy).await.unwrap_or_default();
+    let is_safe = code.len() >= 150;
+    if !is_safe {
+        check_derived_proxy(wallet, proxy)?;
+        let tx = submit_merge_calls(provider, &signer, proxy, ctf, &merge_calldatas, false).await?;
+        return Ok((tx, merged_items));
+    }
 
-    if code.len() < 150 {
-        let derived = derive_proxy_wallet(wallet, PROXY_FACTORY);
-        let try_anyway = env::var("MERGE_TRY_ANYWAY")
-            .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
-            .unwrap_or(false);
-        if derived != proxy && !try_anyway {
-            anyhow::bail!(
-                "POLYMARKET_PROXY_ADDRESS ({:?}) 与 ProxyFactory 的 CREATE2 推导 ({:?}) 不一致。\
-                 请改用 Polymarket 网页 merge，或设 MERGE_TRY_ANYWAY=1 强行尝试。",
-                proxy,
-                derived
-            );
-        }
-        if derived != proxy {
-            warn!("MERGE_TRY_ANYWAY=1：derive != proxy，仍发 Relayer 请求。");
-        }
-
-        let builder_key = env::var("POLY_BUILDER_API_KEY").ok();
-        let builder_secret = env::var("POLY_BUILDER_SECRET").ok();
-        let builder_passphrase = env::var("POLY_BUILDER_PASSPHRASE").ok();
-        let relayer_url = env::var("RELAYER_URL").unwrap_or_else(|_| RELAYER_URL_DEFAULT.to_string());
-        match (builder_key.as_deref(), builder_secret.as_deref(), builder_passphrase.as_deref()) {
-            (Some(k), Some(s), Some(p)) => {
-                let out =
-                    relayer_execute_merge(&merge_calldatas, ctf, proxy, &signer, k, s, p, &relayer_url).await?;
-                info!("✅ Relayer 批量 Merge 已提交 tx: {}", out);
-                return Ok((out, merged_items));
-            }
-            _ => anyhow::bail!(
-                "Magic/Email 需配置 POLY_BUILDER_API_KEY、POLY_BUILDER_SECRET、POLY_BUILDER_PASSPHRASE；或改用网页 merge。",
-            ),
+    // Gnosis Safe：多个市场经 MultiSend 打包为一笔交易，两条路径都失败时退化为串行执行
+    match submit_merge_calls(provider, &signer, proxy, ctf, &merge_calldatas, true).await {
+        Ok(tx) => return Ok((tx, merged_items)),
+        Err(e) if merged_items.len() > 1 => {
+            warn!(error = %e, "Safe 批量 Merge 失败，退化为串行执行");
         }
+        Err(e) => return Err(e),
     }
 
-    // Gnosis Safe：不支持 proxy(calls[]) 批量，退化为串行执行
-    warn!("Gnosis Safe 不支持批量 Merge，退化为串行执行 {} 个市场", merged_items.len());
-    let mut last_tx = String::new();
-    for (condition_id, _) in &merged_items {
+    warn!("Gnosis Safe 串行执行 {} 个市场", merged_items.len());
+    // 串行时返回逗号分隔的全部交易哈希，且只返回成功的市场，便于调用方核对到账
+    let mut txs: Vec<String> = Vec::new();
+    let mut succeeded: Vec<(B256, U256)> = Vec::new();
+    for (condition_id, amount) in &merged_items {
         match merge_max(*condition_id, proxy, private_key, Some(rpc)).await {
             Ok(tx) => {
-                last_tx = tx;
-                info!("✅ Merge 完成（Safe 串行）| condition_id={:#x} | tx={}", condition_id, last_tx);
+                info!("✅ Merge 完成（Safe 串行）| condition_id={:#x} | tx={}", condition_id, tx);
+                txs.push(tx);
+                succeeded.push((*condition_id, *amount));
             }
             Err(e) => {
                 warn!(condition_id = %condition_id, error = %e, "❌ Merge 失败");
             }
         }
     }
-    if last_tx.is_empty() {
+    if txs.is_empty() {
         anyhow::bail!("Gnosis Safe 串行 Merge 全部失败");
     }
-    Ok((last_tx, merged_items))
+    Ok((txs.join(","), succeeded))
+}
+
+/// 读取 merge 交易实际转入 proxy 的 USDC（6 位小数的最小单位）：等待交易回执，
+/// 累加回执中 USDC 合约 `Transfer(to = proxy)` 事件的金额。tx_hashes 可为逗号分隔的多笔交易。
+/// 超时仍未取到回执时返回错误。
+pub async fn merge_proceeds(
+    tx_hashes: &str,
+    proxy: Address,
+    rpc_url: Option<&str>,
+    timeout: Duration,
+) -> Result<U256> {
+    crate::chaos::rpc_guard("merge_proceeds")?;
+    let provider = ProviderBuilder::new().connect(rpc_url.unwrap_or(RPC_URL_DEFAULT)).await?;
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut total = U256::ZERO;
+    for tx in tx_hashes.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let hash = B256::from_str(tx).map_err(|e| anyhow::anyhow!("交易哈希无效 {}: {}", tx, e))?;
+        let receipt = loop {
+            if let Some(receipt) = provider.get_transaction_receipt(hash).await? {
+                break receipt;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                anyhow::bail!("等待 merge 交易回执超时: {}", tx);
+            }
+            sleep(Duration::from_secs(3)).await;
+        };
+        for log in receipt.inner.logs() {
+            if log.address() != USDC_POLYGON {
+                continue;
+            }
+            if let Ok(transfer) = log.log_decode::<IERC20Events::Transfer>() {
+                if transfer.inner.data.to == proxy {
+                    total += transfer.inner.data.value;
+                }
+            }
+        }
+    }
+    Ok(total)
 }
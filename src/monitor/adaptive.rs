@@ -0,0 +1,99 @@
+//! 自适应套利执行价差：连续出现单边成交后放宽执行价差（只做更厚的价差），连续干净的双腿成交后逐步收紧，
+//! 始终限制在 [min, max] 之内，让机器人随竞争强度变化自动调整，而不必手动改配置重启。
+
+use polymarket_client_sdk::types::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tracing::info;
+
+use super::fill_model::FillOutcome;
+use crate::utils::metrics;
+
+/// 价差以百万分之一为单位存放，检测热路径只做一次原子读
+const SCALE: u32 = 6;
+
+#[derive(Debug, Clone, Copy)]
+pub struct AdaptiveSpreadSettings {
+    pub min: Decimal,
+    pub max: Decimal,
+    pub step: Decimal,
+    /// 连续多少次单边成交后放宽一档
+    pub widen_after: u32,
+    /// 连续多少次双腿成交后收紧一档
+    pub tighten_after: u32,
+}
+
+#[derive(Default)]
+struct Streaks {
+    bad: u32,
+    clean: u32,
+}
+
+pub struct AdaptiveSpread {
+    current_micros: AtomicU64,
+    settings: AdaptiveSpreadSettings,
+    streaks: Mutex<Streaks>,
+}
+
+fn to_micros(value: Decimal) -> u64 {
+    (value * Decimal::from(10u64.pow(SCALE))).round().to_u64().unwrap_or(0)
+}
+
+impl AdaptiveSpread {
+    /// 初始价差为配置的执行价差（截断到 [min, max]）
+    pub fn new(initial: Decimal, settings: AdaptiveSpreadSettings) -> Self {
+        let initial = initial.max(settings.min).min(settings.max);
+        Self {
+            current_micros: AtomicU64::new(to_micros(initial)),
+            settings,
+            streaks: Mutex::new(Streaks::default()),
+        }
+    }
+
+    /// 当前执行价差
+    pub fn current(&self) -> Decimal {
+        Decimal::new(self.current_micros.load(Ordering::Relaxed) as i64, SCALE)
+    }
+
+    /// 按一次下单结果更新连续计数，达到阈值时放宽或收紧一档（均未成交不计入）
+    pub fn record(&self, outcome: FillOutcome) {
+        let Ok(mut streaks) = self.streaks.lock() else {
+            return;
+        };
+        let s = &self.settings;
+        let current = self.current();
+        let next = match outcome {
+            FillOutcome::OneSided => {
+                streaks.clean = 0;
+                streaks.bad += 1;
+                if streaks.bad < s.widen_after.max(1) {
+                    return;
+                }
+                streaks.bad = 0;
+                (current + s.step).min(s.max)
+            }
+            FillOutcome::Both => {
+                streaks.bad = 0;
+                streaks.clean += 1;
+                if streaks.clean < s.tighten_after.max(1) {
+                    return;
+                }
+                streaks.clean = 0;
+                (current - s.step).max(s.min)
+            }
+            FillOutcome::None => return,
+        };
+        if next == current {
+            return;
+        }
+        self.current_micros.store(to_micros(next), Ordering::Relaxed);
+        if next > current {
+            metrics::incr("adaptive_spread_widened");
+            info!("📐 连续单边成交，放宽执行价差 | {} → {}", current, next);
+        } else {
+            metrics::incr("adaptive_spread_tightened");
+            info!("📐 连续双腿成交，收紧执行价差 | {} → {}", current, next);
+        }
+    }
+}
@@ -3,6 +3,8 @@ use polymarket_client_sdk::types::{B256, Decimal, U256};
 use rust_decimal_macros::dec;
 use tracing::debug;
 
+use crate::market::rules;
+
 #[derive(Debug, Clone)]
 pub struct ArbitrageOpportunity {
     pub market_id: B256,
@@ -43,8 +45,9 @@ impl ArbitrageDetector {
         let yes_best = yes_book.asks.last()?;
         let no_best = no_book.asks.last()?;
 
-        let yes_price = yes_best.price.round_dp(2);
-        let no_price = no_best.price.round_dp(2);
+        // 按各市场的价格单位对齐（接近 0/1 的市场可能是 0.001）
+        let yes_price = rules::align_to_tick(yes_book.asset_id, yes_best.price);
+        let no_price = rules::align_to_tick(no_book.asset_id, no_best.price);
         let total_price = yes_price + no_price;
 
         if total_price > dec!(1.0) {
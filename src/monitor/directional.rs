@@ -0,0 +1,169 @@
+//! 模型概率偏离的方向性策略（与套利并列的独立策略）：用外部现货价与窗口参考开盘价估计本窗口「上涨」的概率，
+//! P(上涨) = Φ(ln(现价/开盘价) / (小时波动率 × √剩余小时))；当 YES（上涨）或 NO（下跌）卖一价比模型概率低出
+//! 超过 DIRECTIONAL_MARGIN 时，买入被低估的一边，单市场累计投入有上限。方向性持仓不成对、不 merge，持有到结算。
+
+use chrono::{DateTime, Utc};
+use polymarket_client_sdk::types::{Decimal, B256, U256};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::orderbook::OrderBookPair;
+use crate::market::rules;
+use crate::market::spot::SpotFeed;
+
+/// 同一市场两次方向性下单的最小间隔
+const MIN_SIGNAL_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy)]
+pub struct DirectionalSettings {
+    /// 卖一价低于模型概率的最小差值才下单
+    pub margin: f64,
+    /// 单笔下单金额（USD）
+    pub order_usdc: f64,
+    /// 单市场每窗口累计投入上限（USD）
+    pub max_position_usdc: f64,
+    /// 现货 1 小时收益率的标准差（如 0.006 = 0.6%）；有实现波动率估计时优先使用估计值
+    pub hourly_vol: f64,
+    /// 距窗口结束少于该秒数时不再下单（临近结束模型对噪声过于敏感）
+    pub min_secs_remaining: i64,
+}
+
+/// 一次方向性下单信号
+#[derive(Debug, Clone)]
+pub struct DirectionalSignal {
+    pub market_id: B256,
+    pub token_id: U256,
+    /// "UP" 或 "DOWN"
+    pub side: &'static str,
+    /// 所买一边的模型概率
+    pub model_prob: f64,
+    pub price: Decimal,
+    pub size: Decimal,
+    /// 现价 / 参考开盘价
+    pub spot: f64,
+    pub reference: f64,
+}
+
+#[derive(Default)]
+struct MarketState {
+    spent: Decimal,
+    last_signal: Option<Instant>,
+}
+
+pub struct DirectionalStrategy {
+    settings: DirectionalSettings,
+    spot: Arc<SpotFeed>,
+    markets: Mutex<HashMap<B256, MarketState>>,
+}
+
+/// 标准正态分布累积函数（Abramowitz-Stegun 7.1.26，误差 < 1.5e-7）
+fn normal_cdf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.3275911 * x.abs() / std::f64::consts::SQRT_2);
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-(x * x) / 2.0).exp();
+    if x >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    }
+}
+
+impl DirectionalStrategy {
+    pub fn new(settings: DirectionalSettings, spot: Arc<SpotFeed>) -> Self {
+        Self {
+            settings,
+            spot,
+            markets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 本窗口「上涨」的模型概率；现货报价缺失、不属于当前窗口或已到期时返回 None
+    pub fn model_up_probability(&self, symbol: &str, window_start: i64, window_end: DateTime<Utc>) -> Option<(f64, f64, f64)> {
+        let quote = self.spot.quote(symbol)?;
+        if quote.open_time_ms != window_start * 1000 {
+            return None;
+        }
+        let remaining_hours = (window_end - Utc::now()).num_milliseconds() as f64 / 3_600_000.0;
+        let hourly_vol = crate::market::volatility::hourly_vol(symbol).unwrap_or(self.settings.hourly_vol);
+        if remaining_hours <= 0.0 || hourly_vol <= 0.0 {
+            return None;
+        }
+        let z = (quote.last / quote.open).ln() / (hourly_vol * remaining_hours.sqrt());
+        Some((normal_cdf(z), quote.last, quote.open))
+    }
+
+    /// 评估一个订单簿对（YES = 上涨，NO = 下跌）；满足偏离阈值且未超上限时返回下单信号并计入投入
+    pub fn evaluate(
+        &self,
+        pair: &OrderBookPair,
+        symbol: &str,
+        window_start: i64,
+        window_end: DateTime<Utc>,
+    ) -> Option<DirectionalSignal> {
+        if (window_end - Utc::now()).num_seconds() < self.settings.min_secs_remaining {
+            return None;
+        }
+        let (p_up, spot, reference) = self.model_up_probability(symbol, window_start, window_end)?;
+        let yes = pair.yes_book.asks.last()?;
+        let no = pair.no_book.asks.last()?;
+        let yes_edge = p_up - yes.price.to_f64()?;
+        let no_edge = (1.0 - p_up) - no.price.to_f64()?;
+        let (token_id, side, model_prob, level, edge) = if yes_edge >= no_edge {
+            (pair.yes_book.asset_id, "UP", p_up, yes, yes_edge)
+        } else {
+            (pair.no_book.asset_id, "DOWN", 1.0 - p_up, no, no_edge)
+        };
+        if edge < self.settings.margin {
+            return None;
+        }
+
+        let mut markets = self.markets.lock().ok()?;
+        let state = markets.entry(pair.market_id).or_default();
+        if state.last_signal.is_some_and(|t| t.elapsed() < MIN_SIGNAL_INTERVAL) {
+            return None;
+        }
+        let cap = Decimal::try_from(self.settings.max_position_usdc).unwrap_or(dec!(0));
+        let budget = Decimal::try_from(self.settings.order_usdc)
+            .unwrap_or(dec!(0))
+            .min(cap - state.spent);
+        if level.price <= dec!(0) {
+            return None;
+        }
+        // 对齐数量步长并校验最小下单数量；交易所最小下单金额 $1
+        let size = rules::order_size(&[token_id], (budget / level.price).min(level.size))?;
+        if size * level.price <= dec!(1) {
+            return None;
+        }
+        state.spent += size * level.price;
+        state.last_signal = Some(Instant::now());
+        Some(DirectionalSignal {
+            market_id: pair.market_id,
+            token_id,
+            side,
+            model_prob,
+            price: level.price,
+            size,
+            spot,
+            reference,
+        })
+    }
+
+    /// 下单失败时归还该市场的投入额度
+    pub fn refund(&self, market_id: B256, cost: Decimal) {
+        if let Ok(mut markets) = self.markets.lock() {
+            if let Some(state) = markets.get_mut(&market_id) {
+                state.spent = (state.spent - cost).max(dec!(0));
+            }
+        }
+    }
+
+    /// 新窗口清空各市场的投入统计
+    pub fn reset(&self) {
+        if let Ok(mut markets) = self.markets.lock() {
+            markets.clear();
+        }
+    }
+}
@@ -0,0 +1,167 @@
+//! 成交概率调整的期望收益（EV）模型：按「价差 × 机会存续时长」分桶统计历史下单结果（双腿成交 / 单边成交 / 均未成交），
+//! 估计双腿都成交的概率，EV = P(双腿) × 价差 − P(单边) × 单边处理成本（每份）。
+//! EV 低于下限的机会不再下单，避免价差接近零、成交概率又差的机会反复造成单边持仓。
+//! 统计随每次结果落盘，重启后继续使用。
+
+use anyhow::Result;
+use polymarket_client_sdk::types::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// 价差分桶上界（1 - 卖一合计）
+const EDGE_BOUNDS: [Decimal; 3] = [dec!(0.01), dec!(0.02), dec!(0.04)];
+/// 机会存续时长分桶上界（毫秒）
+const AGE_BOUNDS_MS: [u128; 3] = [500, 2_000, 10_000];
+/// 先验（伪计数）：样本很少时接近「多数成交、偶有单边」的保守假设
+const PRIOR_BOTH: u32 = 3;
+const PRIOR_ONE_SIDED: u32 = 1;
+const PRIOR_NONE: u32 = 1;
+
+/// 一次下单的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FillOutcome {
+    Both,
+    OneSided,
+    None,
+}
+
+impl FillOutcome {
+    pub fn from_fills(yes_filled: Decimal, no_filled: Decimal) -> Self {
+        match (yes_filled > dec!(0), no_filled > dec!(0)) {
+            (true, true) => FillOutcome::Both,
+            (false, false) => FillOutcome::None,
+            _ => FillOutcome::OneSided,
+        }
+    }
+}
+
+/// 分桶：(价差档, 存续时长档)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FillBucket {
+    pub edge: u8,
+    pub age: u8,
+}
+
+impl FillBucket {
+    pub fn new(edge: Decimal, age: Duration) -> Self {
+        let age_ms = age.as_millis();
+        Self {
+            edge: EDGE_BOUNDS.iter().take_while(|b| edge >= **b).count() as u8,
+            age: AGE_BOUNDS_MS.iter().take_while(|b| age_ms >= **b).count() as u8,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+struct Outcomes {
+    both: u32,
+    one_sided: u32,
+    none: u32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ModelFile {
+    #[serde(default)]
+    buckets: Vec<(FillBucket, Outcomes)>,
+}
+
+pub struct FillModel {
+    /// None 表示只在内存中保存，不落盘
+    path: Option<PathBuf>,
+    buckets: Mutex<HashMap<FillBucket, Outcomes>>,
+    /// 单边成交时每份的预期处理成本（对冲/卖出损失）
+    one_sided_cost: Decimal,
+    /// 每份 EV 下限
+    min_ev: Decimal,
+}
+
+impl FillModel {
+    /// 从文件加载历史统计；path 为空字符串时不落盘，文件不存在或损坏时从先验开始
+    pub fn load(path: &str, one_sided_cost: f64, min_ev: f64) -> Self {
+        let path = (!path.trim().is_empty()).then(|| PathBuf::from(path));
+        let file = path
+            .as_ref()
+            .and_then(|p| std::fs::read(p).ok())
+            .and_then(|body| match serde_json::from_slice::<ModelFile>(&body) {
+                Ok(file) => Some(file),
+                Err(e) => {
+                    warn!(error = %e, "成交概率模型文件损坏，忽略");
+                    None
+                }
+            })
+            .unwrap_or_default();
+        let samples: u32 = file.buckets.iter().map(|(_, o)| o.both + o.one_sided + o.none).sum();
+        if samples > 0 {
+            info!("📈 加载成交概率模型 | 分桶:{} | 样本:{}", file.buckets.len(), samples);
+        }
+        Self {
+            path,
+            buckets: Mutex::new(file.buckets.into_iter().collect()),
+            one_sided_cost: Decimal::try_from(one_sided_cost).unwrap_or(dec!(0.05)),
+            min_ev: Decimal::try_from(min_ev).unwrap_or(dec!(0)),
+        }
+    }
+
+    pub fn min_ev(&self) -> Decimal {
+        self.min_ev
+    }
+
+    /// 估计 (双腿成交概率, 单边成交概率)
+    pub fn probabilities(&self, bucket: FillBucket) -> (Decimal, Decimal) {
+        let o = self
+            .buckets
+            .lock()
+            .ok()
+            .and_then(|b| b.get(&bucket).copied())
+            .unwrap_or_default();
+        let both = Decimal::from(o.both + PRIOR_BOTH);
+        let one_sided = Decimal::from(o.one_sided + PRIOR_ONE_SIDED);
+        let total = both + one_sided + Decimal::from(o.none + PRIOR_NONE);
+        (both / total, one_sided / total)
+    }
+
+    /// 评估一次机会：返回分桶与每份 EV
+    pub fn evaluate(&self, edge: Decimal, age: Duration) -> (FillBucket, Decimal) {
+        let bucket = FillBucket::new(edge, age);
+        let (p_both, p_one_sided) = self.probabilities(bucket);
+        (bucket, p_both * edge - p_one_sided * self.one_sided_cost)
+    }
+
+    /// 记录一次下单结果并落盘
+    pub fn record(&self, bucket: FillBucket, outcome: FillOutcome) {
+        let Ok(mut buckets) = self.buckets.lock() else {
+            return;
+        };
+        let o = buckets.entry(bucket).or_default();
+        match outcome {
+            FillOutcome::Both => o.both += 1,
+            FillOutcome::OneSided => o.one_sided += 1,
+            FillOutcome::None => o.none += 1,
+        }
+        if let Some(path) = self.path.as_ref() {
+            let file = ModelFile {
+                buckets: buckets.iter().map(|(k, v)| (*k, *v)).collect(),
+            };
+            if let Err(e) = Self::save(path, &file) {
+                debug!(error = %e, "成交概率模型写盘失败");
+            }
+        }
+    }
+
+    fn save(path: &Path, file: &ModelFile) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            if !dir.as_os_str().is_empty() {
+                std::fs::create_dir_all(dir)?;
+            }
+        }
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(file)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
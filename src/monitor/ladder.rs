@@ -0,0 +1,106 @@
+use polymarket_client_sdk::types::{B256, Decimal};
+use rust_decimal_macros::dec;
+use tracing::debug;
+
+use super::arbitrage::ArbitrageOpportunity;
+use super::orderbook::OrderBookMonitor;
+use crate::market::ladder::StrikeLadder;
+
+/// 行权价阶梯套利机会：买入低行权价 YES + 高行权价 NO。
+/// 到期价格 ≤ 低行权价或 > 高行权价时恰有一腿兑付 1，介于两者之间时两腿都兑付，最少兑付 1，
+/// 因此两腿卖一价之和 < 1 即为无风险套利（等价于 P(高于低行权价) < P(高于高行权价) 的单调性违背）。
+#[derive(Debug, Clone)]
+pub struct LadderOpportunity {
+    pub ladder: String,
+    pub low_strike: Decimal,
+    pub high_strike: Decimal,
+    /// 高行权价市场（NO 腿所在市场）
+    pub high_market_id: B256,
+    /// 两腿下单参数：market_id 为低行权价市场，yes_token_id 为低行权价 YES，no_token_id 为高行权价 NO
+    pub legs: ArbitrageOpportunity,
+}
+
+pub struct LadderDetector {
+    /// 两腿总价 <= 此阈值才视为机会（1 - 阶梯执行价差）
+    execution_threshold: Decimal,
+    min_order_value_usd: Decimal, // 最小订单金额（USD）
+}
+
+impl LadderDetector {
+    pub fn new(execution_spread: f64) -> Self {
+        Self {
+            execution_threshold: dec!(1.0) - Decimal::try_from(execution_spread).unwrap_or(dec!(0.02)),
+            min_order_value_usd: dec!(1.0), // 最小订单金额$1
+        }
+    }
+
+    /// 检查阶梯中任意两档（低行权价 i < 高行权价 j）的 YES_i + NO_j 卖一价，返回总价最低的机会
+    pub fn check_ladder(&self, ladder: &StrikeLadder, monitor: &OrderBookMonitor) -> Option<LadderOpportunity> {
+        // asks 最后一个为卖一价；每档取 (YES 卖一, NO 卖一)
+        let asks: Vec<_> = ladder
+            .strikes
+            .iter()
+            .map(|s| {
+                let yes = monitor.get_book(s.market.yes_token_id).and_then(|b| b.asks.last().cloned());
+                let no = monitor.get_book(s.market.no_token_id).and_then(|b| b.asks.last().cloned());
+                (yes, no)
+            })
+            .collect();
+
+        let mut best: Option<(usize, usize, Decimal, Decimal, Decimal, Decimal)> = None;
+        for i in 0..ladder.strikes.len() {
+            let Some(yes_level) = asks[i].0.as_ref() else {
+                continue;
+            };
+            for j in (i + 1)..ladder.strikes.len() {
+                let Some(no_level) = asks[j].1.as_ref() else {
+                    continue;
+                };
+                let yes_price = yes_level.price.round_dp(2);
+                let no_price = no_level.price.round_dp(2);
+                let total = yes_price + no_price;
+                if total > self.execution_threshold {
+                    continue;
+                }
+                let size = (yes_level.size.min(no_level.size) * dec!(100.0)).floor() / dec!(100.0);
+                if yes_price * size < self.min_order_value_usd || no_price * size < self.min_order_value_usd {
+                    continue;
+                }
+                if best.as_ref().map(|b| total < b.4).unwrap_or(true) {
+                    best = Some((i, j, yes_price, no_price, total, size));
+                }
+            }
+        }
+
+        let (i, j, yes_price, no_price, total, size) = best?;
+        let low = &ladder.strikes[i];
+        let high = &ladder.strikes[j];
+        debug!(
+            ladder = %ladder.name,
+            low_strike = %low.strike,
+            high_strike = %high.strike,
+            yes_price = %yes_price,
+            no_price = %no_price,
+            total_price = %total,
+            order_size = %size,
+            "发现阶梯套利机会（卖一价）"
+        );
+        Some(LadderOpportunity {
+            ladder: ladder.name.clone(),
+            low_strike: low.strike,
+            high_strike: high.strike,
+            high_market_id: high.market.market_id,
+            legs: ArbitrageOpportunity {
+                market_id: low.market.market_id,
+                yes_token_id: low.market.yes_token_id,
+                no_token_id: high.market.no_token_id,
+                yes_ask_price: yes_price,
+                no_ask_price: no_price,
+                total_cost: total * size,
+                profit_percentage: (dec!(1.0) - total) * dec!(100.0),
+                yes_size: size,
+                no_size: size,
+            },
+        })
+    }
+}
@@ -0,0 +1,59 @@
+//! 订单簿流存活监测：记录订阅流最后一次收到订单簿消息的时间，定时检查静默时长。
+//! 检测的就是实际承载订阅的那条连接（SDK 未暴露底层连接，无法在其上 PING），静默超过阈值时
+//! 通知主循环主动重建订单簿流，而不是等到订阅流报错才发现连接已失效。
+//! 静默时长每次检查都导出为瞬时值指标（ws_book_silence_ms）。
+
+use std::time::{Duration, Instant};
+
+use crate::utils::metrics;
+
+/// 监测参数
+#[derive(Debug, Clone, Copy)]
+pub struct LivenessSettings {
+    /// 检查间隔
+    pub interval: Duration,
+    /// 静默多久视为连接失效
+    pub stale_after: Duration,
+}
+
+/// 单条订单簿流的静默监测（由主循环持有，流重建后 reset）
+pub struct StreamWatchdog {
+    settings: LivenessSettings,
+    last_message: Instant,
+    ticker: tokio::time::Interval,
+}
+
+impl StreamWatchdog {
+    pub fn new(settings: LivenessSettings) -> Self {
+        let mut ticker = tokio::time::interval(settings.interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        Self {
+            settings,
+            last_message: Instant::now(),
+            ticker,
+        }
+    }
+
+    /// 收到一条订单簿消息
+    pub fn touch(&mut self) {
+        self.last_message = Instant::now();
+    }
+
+    /// 流重建后从零开始计时
+    pub fn reset(&mut self) {
+        self.touch();
+    }
+
+    /// 等到下一次检查；静默超过阈值时返回静默时长，否则继续等待
+    pub async fn stale(&mut self) -> Duration {
+        loop {
+            self.ticker.tick().await;
+            let silence = self.last_message.elapsed();
+            metrics::set_gauge("ws_book_silence_ms", silence.as_millis() as f64);
+            if silence >= self.settings.stale_after {
+                metrics::incr("ws_stale_reconnect");
+                return silence;
+            }
+        }
+    }
+}
@@ -0,0 +1,203 @@
+//! 中间价时间序列导出（研究用）：窗口内每秒记录各市场 YES/NO 的买一、卖一、中间价与买卖价差，
+//! 按窗口写入 Parquet 文件 {MID_EXPORT_DIR}/mid_{窗口开始时间戳}.parquet，每分钟一个 row group。
+//! 与订单簿录制（BOOK_RECORD_PATH，供 optimize 回放）分开：一行一个市场一秒，列式宽表可直接用 pandas/polars/DuckDB 分析。
+//! 写入过程中文件名带 .partial 后缀，窗口结束关闭后才改名，读取方不会读到未完成的文件。
+
+use arrow_array::{ArrayRef, Float64Array, Int64Array, RecordBatch, StringArray, TimestampMillisecondArray};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use polymarket_client_sdk::clob::ws::types::response::BookUpdate;
+use polymarket_client_sdk::types::U256;
+use rust_decimal::prelude::ToPrimitive;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::market::MarketInfo;
+use crate::utils::metrics;
+
+/// 每多少次采样写一个 row group
+const ROW_GROUP_TICKS: u32 = 60;
+/// 每行的报价列：YES 与 NO 各 买一/卖一/中间价/价差，外加两边卖一价之和
+const QUOTE_COLUMNS: [&str; 9] = [
+    "yes_bid", "yes_ask", "yes_mid", "yes_spread", "no_bid", "no_ask", "no_mid", "no_spread", "ask_sum",
+];
+
+/// 已启动导出的窗口（主循环在窗口内重建订阅时不重复启动）
+static LAST_WINDOW: AtomicI64 = AtomicI64::new(0);
+/// 窗口内新发现、待导出线程纳入的市场
+static LATE_MARKETS: Mutex<Vec<ExportedMarket>> = Mutex::new(Vec::new());
+
+struct ExportedMarket {
+    market_id: String,
+    symbol: String,
+    yes_token_id: U256,
+    no_token_id: U256,
+}
+
+impl From<&MarketInfo> for ExportedMarket {
+    fn from(m: &MarketInfo) -> Self {
+        Self {
+            market_id: format!("{:#x}", m.market_id),
+            symbol: if m.crypto_symbol.is_empty() { m.slug.clone() } else { m.crypto_symbol.clone() },
+            yes_token_id: m.yes_token_id,
+            no_token_id: m.no_token_id,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Buffer {
+    ts_ms: Vec<i64>,
+    secs_to_end: Vec<i64>,
+    market_id: Vec<String>,
+    symbol: Vec<String>,
+    quotes: Vec<[Option<f64>; 9]>,
+}
+
+fn schema() -> Arc<Schema> {
+    let mut fields = vec![
+        Field::new("ts", DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())), false),
+        Field::new("window_start", DataType::Int64, false),
+        Field::new("secs_to_end", DataType::Int64, false),
+        Field::new("market_id", DataType::Utf8, false),
+        Field::new("symbol", DataType::Utf8, false),
+    ];
+    fields.extend(QUOTE_COLUMNS.iter().map(|name| Field::new(*name, DataType::Float64, true)));
+    Arc::new(Schema::new(fields))
+}
+
+/// 一侧订单簿的 [买一, 卖一, 中间价, 价差]；数组末尾为最优价
+fn side_quotes(book: Option<&BookUpdate>) -> [Option<f64>; 4] {
+    let bid = book.and_then(|b| b.bids.last()).and_then(|o| o.price.to_f64());
+    let ask = book.and_then(|b| b.asks.last()).and_then(|o| o.price.to_f64());
+    match (bid, ask) {
+        (Some(b), Some(a)) => [bid, ask, Some((a + b) / 2.0), Some(a - b)],
+        _ => [bid, ask, None, None],
+    }
+}
+
+impl Buffer {
+    fn take_batch(&mut self, schema: &Arc<Schema>, window_start: i64) -> anyhow::Result<RecordBatch> {
+        let buffer = std::mem::take(self);
+        let rows = buffer.ts_ms.len();
+        let mut columns: Vec<ArrayRef> = vec![
+            Arc::new(TimestampMillisecondArray::from(buffer.ts_ms).with_timezone("UTC")),
+            Arc::new(Int64Array::from(vec![window_start; rows])),
+            Arc::new(Int64Array::from(buffer.secs_to_end)),
+            Arc::new(StringArray::from(buffer.market_id)),
+            Arc::new(StringArray::from(buffer.symbol)),
+        ];
+        for i in 0..QUOTE_COLUMNS.len() {
+            let values: Vec<Option<f64>> = buffer.quotes.iter().map(|q| q[i]).collect();
+            columns.push(Arc::new(Float64Array::from(values)));
+        }
+        Ok(RecordBatch::try_new(schema.clone(), columns)?)
+    }
+}
+
+/// 启动本窗口的导出线程，到 until 时关闭文件；dir 为空时不启用
+pub fn spawn(
+    dir: &str,
+    books: Arc<DashMap<U256, BookUpdate>>,
+    markets: &[MarketInfo],
+    window_start: i64,
+    until: DateTime<Utc>,
+) {
+    if dir.trim().is_empty() || LAST_WINDOW.swap(window_start, Ordering::SeqCst) == window_start {
+        return;
+    }
+    if let Ok(mut late) = LATE_MARKETS.lock() {
+        late.clear();
+    }
+    let markets: Vec<ExportedMarket> = markets.iter().map(ExportedMarket::from).collect();
+    let path = Path::new(dir).join(format!("mid_{}.parquet", window_start));
+    let spawned = std::thread::Builder::new().name("mid-export".to_string()).spawn(move || {
+        if let Err(e) = export_window(&path, &books, &markets, window_start, until) {
+            warn!(error = %e, path = %path.display(), "中间价导出失败");
+            metrics::incr("mid_export_failed");
+        }
+    });
+    if let Err(e) = spawned {
+        warn!(error = %e, "启动中间价导出线程失败");
+    }
+}
+
+/// 窗口内新发现的市场加入本窗口导出（从下一秒起记录）；该窗口未启动导出时忽略
+pub fn add_markets(window_start: i64, markets: &[MarketInfo]) {
+    if LAST_WINDOW.load(Ordering::SeqCst) != window_start {
+        return;
+    }
+    if let Ok(mut late) = LATE_MARKETS.lock() {
+        late.extend(markets.iter().map(ExportedMarket::from));
+    }
+}
+
+fn export_window(
+    path: &Path,
+    books: &DashMap<U256, BookUpdate>,
+    markets: &[ExportedMarket],
+    window_start: i64,
+    until: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    let mut late_markets: Vec<ExportedMarket> = Vec::new();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let partial = PathBuf::from(format!("{}.partial", path.display()));
+    let schema = schema();
+    let props = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+    let mut writer = ArrowWriter::try_new(File::create(&partial)?, schema.clone(), Some(props))?;
+    let mut buffer = Buffer::default();
+    let mut ticks = 0u32;
+    let mut rows = 0usize;
+
+    loop {
+        let now = Utc::now();
+        if now >= until {
+            break;
+        }
+        if let Ok(mut late) = LATE_MARKETS.lock() {
+            late_markets.append(&mut late);
+        }
+        for m in markets.iter().chain(late_markets.iter()) {
+            let yes = books.get(&m.yes_token_id);
+            let no = books.get(&m.no_token_id);
+            if yes.is_none() && no.is_none() {
+                continue;
+            }
+            let [yb, ya, ym, ys] = side_quotes(yes.as_deref());
+            let [nb, na, nm, ns] = side_quotes(no.as_deref());
+            buffer.ts_ms.push(now.timestamp_millis());
+            buffer.secs_to_end.push((until - now).num_seconds());
+            buffer.market_id.push(m.market_id.clone());
+            buffer.symbol.push(m.symbol.clone());
+            buffer.quotes.push([yb, ya, ym, ys, nb, na, nm, ns, ya.zip(na).map(|(y, n)| y + n)]);
+        }
+        ticks += 1;
+        if ticks % ROW_GROUP_TICKS == 0 && !buffer.ts_ms.is_empty() {
+            rows += buffer.ts_ms.len();
+            writer.write(&buffer.take_batch(&schema, window_start)?)?;
+            writer.flush()?;
+        }
+        // 对齐到下一个整秒，采样时间不随写盘耗时漂移
+        let to_next_second = 1000 - Utc::now().timestamp_subsec_millis().min(999);
+        std::thread::sleep(Duration::from_millis(to_next_second as u64));
+    }
+
+    if !buffer.ts_ms.is_empty() {
+        rows += buffer.ts_ms.len();
+        writer.write(&buffer.take_batch(&schema, window_start)?)?;
+    }
+    writer.close()?;
+    std::fs::rename(&partial, path)?;
+    info!("🗂️ 中间价导出完成 | 窗口:{} | {}行 | {}", window_start, rows, path.display());
+    Ok(())
+}
@@ -1,5 +1,17 @@
+pub mod adaptive;
 pub mod arbitrage;
+pub mod directional;
+pub mod fill_model;
+pub mod ladder;
+pub mod liveness;
+pub mod mid_export;
 pub mod orderbook;
+pub mod sell_back;
+pub mod shadow;
+pub mod sniping;
+pub mod spread_sampler;
+pub mod symbol_spread;
 
 pub use arbitrage::*;
+pub use ladder::*;
 pub use orderbook::*;
@@ -6,9 +6,15 @@ use polymarket_client_sdk::clob::ws::{Client as WsClient, types::response::BookU
 use polymarket_client_sdk::types::{B256, U256};
 use std::collections::HashMap;
 use std::pin::Pin;
-use tracing::{debug, info};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
 
 use crate::market::MarketInfo;
+use crate::utils::rate_limit::{self, Priority};
+
+/// 订阅时 REST 快照请求的超时
+const SNAPSHOT_TIMEOUT: Duration = Duration::from_secs(3);
 
 /// 缩短 B256 用于日志：保留 0x + 前 8 位 hex，如 0xb91126b7..
 #[inline]
@@ -30,8 +36,11 @@ fn short_u256(u: &U256) -> String {
 
 pub struct OrderBookMonitor {
     ws_client: WsClient,
-    books: DashMap<U256, BookUpdate>,
+    books: Arc<DashMap<U256, BookUpdate>>,
     market_map: HashMap<B256, (U256, U256)>, // market_id -> (yes_token_id, no_token_id)
+    /// 订阅时拉取 REST 快照的 CLOB 地址；None = 只等 WS 推送
+    snapshot_url: Option<String>,
+    http: reqwest::Client,
 }
 
 pub struct OrderBookPair {
@@ -46,11 +55,23 @@ impl OrderBookMonitor {
             // 使用未认证的客户端：订单簿订阅不需要认证，这是公开数据
             // 只有订阅用户数据（如用户订单、交易等）才需要认证
             ws_client: WsClient::default(),
-            books: DashMap::new(),
+            books: Arc::new(DashMap::new()),
             market_map: HashMap::new(),
+            snapshot_url: None,
+            http: reqwest::Client::builder()
+                .timeout(SNAPSHOT_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
         }
     }
 
+    /// 创建订阅流时同时从 CLOB REST（POST /books）拉取一次订单簿快照，先于 WS 首次推送写入本地订单簿，
+    /// 窗口开始第一秒即可检测（此时价差往往最大）
+    pub fn with_snapshots(mut self, clob_url: &str) -> Self {
+        self.snapshot_url = Some(clob_url.trim_end_matches('/').to_string());
+        self
+    }
+
     /// 订阅新市场
     pub fn subscribe_market(&mut self, market: &MarketInfo) -> Result<()> {
         // 记录市场映射
@@ -89,14 +110,43 @@ impl OrderBookMonitor {
 
         info!(token_count = token_ids.len(), "创建订单簿订阅流（未认证）");
 
+        // 还没有订单簿的 token（新订阅的市场）拉取 REST 快照
+        let missing: Vec<U256> = token_ids
+            .iter()
+            .copied()
+            .filter(|token| !self.books.contains_key(token))
+            .collect();
+
         // subscribe_orderbook 不需要认证，使用未认证客户端即可
         let stream = self.ws_client.subscribe_orderbook(token_ids)?;
         // 将 SDK 的 Error 转换为 anyhow::Error
         let stream = stream.map(|result| result.map_err(|e| anyhow::anyhow!("{}", e)));
-        Ok(Box::pin(stream))
+
+        let (Some(url), false) = (self.snapshot_url.clone(), missing.is_empty()) else {
+            return Ok(Box::pin(stream));
+        };
+        // 快照与 WS 订阅并发进行，谁先到用谁；WS 已推送过的 token 不再用（更旧的）快照覆盖
+        let http = self.http.clone();
+        let books = self.books.clone();
+        let snapshots = futures::stream::once(async move {
+            match fetch_snapshots(&http, &url, &missing).await {
+                Ok(snapshots) => {
+                    debug!(count = snapshots.len(), "订单簿 REST 快照已返回");
+                    snapshots
+                }
+                Err(e) => {
+                    warn!(error = %e, "拉取订单簿快照失败，等待 WS 推送");
+                    Vec::new()
+                }
+            }
+        })
+        .flat_map(futures::stream::iter)
+        .filter(move |book| futures::future::ready(!books.contains_key(&book.asset_id)))
+        .map(Ok);
+        Ok(Box::pin(futures::stream::select(stream, snapshots)))
     }
 
-    /// 处理订单簿更新
+    /// 处理订单簿更新（WS 推送或订阅时的 REST 快照）
     pub fn handle_book_update(&self, book: BookUpdate) -> Option<OrderBookPair> {
 
         // 打印前5档买卖价格（用于调试）
@@ -150,14 +200,64 @@ impl OrderBookMonitor {
         None
     }
 
+    /// 订单簿缓存的共享句柄（供调试接口只读查看）
+    pub fn books_handle(&self) -> Arc<DashMap<U256, BookUpdate>> {
+        self.books.clone()
+    }
+
     /// 获取订单簿（如果存在）
     pub fn get_book(&self, token_id: U256) -> Option<BookUpdate> {
         self.books.get(&token_id).map(|b| b.clone())
     }
 
+    /// 移除单个市场（市场停止接单等情况），返回是否存在；须重新创建订单簿流才会真正退订
+    pub fn remove_market(&mut self, market_id: &B256) -> bool {
+        match self.market_map.remove(market_id) {
+            Some((yes_token, no_token)) => {
+                self.books.remove(&yes_token);
+                self.books.remove(&no_token);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 当前订阅的市场数
+    pub fn market_count(&self) -> usize {
+        self.market_map.len()
+    }
+
     /// 清除所有订阅
     pub fn clear(&mut self) {
         self.books.clear();
         self.market_map.clear();
     }
 }
+
+/// POST /books 批量查询订单簿；返回格式与 WS book 消息一致（bids/asks 的最后一档为最优价）
+async fn fetch_snapshots(http: &reqwest::Client, clob_url: &str, token_ids: &[U256]) -> Result<Vec<BookUpdate>> {
+    rate_limit::acquire(Priority::Normal).await?;
+    let body: Vec<serde_json::Value> = token_ids
+        .iter()
+        .map(|token| serde_json::json!({ "token_id": token.to_string() }))
+        .collect();
+    let books: Vec<serde_json::Value> = http
+        .post(format!("{}/books", clob_url))
+        .json(&body)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(books
+        .into_iter()
+        .filter_map(|mut book| {
+            if let Some(obj) = book.as_object_mut() {
+                obj.entry("event_type").or_insert_with(|| "book".into());
+            }
+            serde_json::from_value::<BookUpdate>(book)
+                .map_err(|e| debug!(error = %e, "订单簿快照格式无法解析"))
+                .ok()
+        })
+        .collect())
+}
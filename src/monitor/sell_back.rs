@@ -0,0 +1,184 @@
+//! 回卖止盈：持有成对 YES+NO 时，merge 只能按每对 1 USDC 赎回；若两边买一价之和扣除卖出手续费后仍高于 1，
+//! 直接把两腿以 FAK 卖回订单簿，多赚高出 1 的部分，而不是等 merge。
+//! 只卖两边买一档都吃得下、且不超过成对持仓的数量；某条腿只成交了一部分时，剩余持仓照常留待 merge / 收尾处理。
+
+use polymarket_client_sdk::clob::types::{OrderType, Side};
+use polymarket_client_sdk::clob::ws::types::response::BookUpdate;
+use polymarket_client_sdk::types::{Decimal, B256, U256};
+use rust_decimal_macros::dec;
+use serde_json::json;
+use std::collections::HashSet;
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::market::rules;
+use crate::risk::positions::PositionTracker;
+use crate::trading::{BatchOrder, TradingExecutor};
+use crate::utils::{journal, metrics};
+
+#[derive(Debug, Clone, Copy)]
+pub struct SellBackSettings {
+    /// 每卖出一份（每条腿）的手续费估计（USD）
+    pub fee_per_share: Decimal,
+    /// 扣除手续费后每对至少高出 1 的金额
+    pub min_edge: Decimal,
+}
+
+#[derive(Debug, Clone)]
+pub struct SellBackOpportunity {
+    pub market_id: B256,
+    pub yes_token_id: U256,
+    pub no_token_id: U256,
+    pub yes_bid: Decimal,
+    pub no_bid: Decimal,
+    pub size: Decimal,
+    /// 每对扣除手续费后相对 merge（1.0）多得的金额
+    pub edge: Decimal,
+}
+
+/// 回卖执行结果
+#[derive(Debug, Clone, Copy)]
+pub struct SellBackFill {
+    pub yes_sold: Decimal,
+    pub no_sold: Decimal,
+    /// 卖出所得（USDC，未扣手续费）
+    pub proceeds: Decimal,
+}
+
+pub struct SellBackDetector {
+    settings: SellBackSettings,
+    /// 回卖进行中的市场，避免同一持仓重复下单
+    in_flight: Mutex<HashSet<B256>>,
+}
+
+impl SellBackDetector {
+    pub fn new(settings: SellBackSettings) -> Self {
+        Self {
+            settings,
+            in_flight: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// 检查成对持仓 held = (YES, NO) 是否值得回卖
+    pub fn check(
+        &self,
+        market_id: B256,
+        yes_book: &BookUpdate,
+        no_book: &BookUpdate,
+        held: (Decimal, Decimal),
+    ) -> Option<SellBackOpportunity> {
+        if self.in_flight.lock().ok()?.contains(&market_id) {
+            return None;
+        }
+        // bids 最后一个为买一价（最高买价）
+        let yes_best = yes_book.bids.last()?;
+        let no_best = no_book.bids.last()?;
+        let edge = yes_best.price + no_best.price - self.settings.fee_per_share * dec!(2) - dec!(1);
+        if edge < self.settings.min_edge || edge <= dec!(0) {
+            return None;
+        }
+        let raw_size = held.0.min(held.1).min(yes_best.size).min(no_best.size);
+        let size = rules::order_size(&[yes_book.asset_id, no_book.asset_id], raw_size)?;
+        // 每条腿须 > $1（交易所最小下单金额）
+        if yes_best.price * size < dec!(1) || no_best.price * size < dec!(1) {
+            return None;
+        }
+        Some(SellBackOpportunity {
+            market_id,
+            yes_token_id: yes_book.asset_id,
+            no_token_id: no_book.asset_id,
+            yes_bid: yes_best.price,
+            no_bid: no_best.price,
+            size,
+            edge,
+        })
+    }
+
+    /// 标记开始回卖；该市场已在回卖时返回 false
+    pub fn begin(&self, market_id: B256) -> bool {
+        self.in_flight.lock().map(|mut s| s.insert(market_id)).unwrap_or(false)
+    }
+
+    /// 回卖结束（无论成败）
+    pub fn finish(&self, market_id: B256) {
+        if let Ok(mut s) = self.in_flight.lock() {
+            s.remove(&market_id);
+        }
+    }
+}
+
+/// 两腿按买一价 FAK 卖出，并按成交数量扣减本地持仓与敞口成本
+pub async fn execute(
+    executor: &TradingExecutor,
+    position_tracker: &PositionTracker,
+    opp: &SellBackOpportunity,
+    market_display: &str,
+) -> anyhow::Result<SellBackFill> {
+    let orders: Vec<BatchOrder> = [(opp.yes_token_id, opp.yes_bid), (opp.no_token_id, opp.no_bid)]
+        .into_iter()
+        .map(|(token_id, price)| BatchOrder {
+            token_id,
+            side: Side::Sell,
+            price,
+            size: opp.size,
+            order_type: OrderType::FAK,
+            expiration: None,
+        })
+        .collect();
+    let results = executor.submit_batch(&orders).await?;
+    // 卖单的 making_amount 为卖出份额，taking_amount 为收到的 USDC
+    let sold = |i: usize| {
+        results
+            .get(i)
+            .and_then(|r| r.as_ref().ok())
+            .filter(|r| r.success)
+            .map(|r| (r.making_amount, r.taking_amount))
+            .unwrap_or((dec!(0), dec!(0)))
+    };
+    let (yes_sold, yes_usdc) = sold(0);
+    let (no_sold, no_usdc) = sold(1);
+    for (token_id, amount) in [(opp.yes_token_id, yes_sold), (opp.no_token_id, no_sold)] {
+        if amount > dec!(0) {
+            position_tracker.update_position(token_id, -amount);
+            position_tracker.update_exposure_cost(token_id, dec!(0), -amount);
+        }
+    }
+    let fill = SellBackFill {
+        yes_sold,
+        no_sold,
+        proceeds: yes_usdc + no_usdc,
+    };
+
+    if yes_sold != no_sold {
+        warn!(
+            "⚠️ 回卖两腿成交不一致 | 市场:{} | YES卖出:{} | NO卖出:{}，剩余持仓留待 merge / 收尾处理",
+            market_display, yes_sold, no_sold
+        );
+        metrics::incr("sell_back_unbalanced");
+    }
+    if yes_sold > dec!(0) || no_sold > dec!(0) {
+        info!(
+            "💰 回卖止盈 | 市场:{} | YES {}@{} + NO {}@{} | 所得:{:.4} USDC | 每对超出 merge:{:.4}",
+            market_display, yes_sold, opp.yes_bid, no_sold, opp.no_bid, fill.proceeds, opp.edge
+        );
+        metrics::incr("sell_back_executed");
+    } else {
+        warn!("回卖未成交（买一已被吃掉） | 市场:{}", market_display);
+        metrics::incr("sell_back_missed");
+    }
+    journal::record(
+        "sell_back",
+        json!({
+            "market_id": format!("{:#x}", opp.market_id),
+            "market": market_display,
+            "yes_bid": opp.yes_bid.to_string(),
+            "no_bid": opp.no_bid.to_string(),
+            "size": opp.size.to_string(),
+            "edge": opp.edge.to_string(),
+            "yes_sold": yes_sold.to_string(),
+            "no_sold": no_sold.to_string(),
+            "proceeds": fill.proceeds.to_string(),
+        }),
+    );
+    Ok(fill)
+}
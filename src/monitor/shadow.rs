@@ -0,0 +1,218 @@
+//! 影子配置对比：用候选参数（SHADOW_*）在同一路实盘订单簿上再跑一套检测与风控，只记录「本会下的单」，从不下单；
+//! 实盘实际下单也记一笔，窗口结束时按市场输出 影子 vs 实盘 的笔数、份数、成本与按检测价计算的预期利润，
+//! 便于在替换参数前先验证效果。未设置的候选参数沿用实盘配置。
+
+use polymarket_client_sdk::types::Decimal;
+use rust_decimal_macros::dec;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::info;
+
+use super::arbitrage::ArbitrageDetector;
+use super::orderbook::OrderBookPair;
+use crate::utils::{journal, metrics};
+
+/// 与主循环一致的两次套利最小间隔
+const MIN_TRADE_INTERVAL: Duration = Duration::from_secs(3);
+
+/// 候选参数；None 表示与实盘相同
+#[derive(Debug, Clone, Default)]
+pub struct ShadowSettings {
+    pub enabled: bool,
+    pub min_profit_threshold: Option<f64>,
+    pub execution_spread: Option<f64>,
+    pub max_order_size_usdc: Option<f64>,
+    pub max_exposure_usdc: Option<f64>,
+}
+
+/// 一侧（影子或实盘）的累计结果
+#[derive(Debug, Clone, Copy, Default)]
+struct Tally {
+    trades: u64,
+    shares: Decimal,
+    cost: Decimal,
+    expected_profit: Decimal,
+}
+
+impl Tally {
+    fn add(&mut self, total_price: Decimal, size: Decimal) {
+        self.trades += 1;
+        self.shares += size;
+        self.cost += total_price * size;
+        self.expected_profit += (dec!(1) - total_price) * size;
+    }
+}
+
+#[derive(Default)]
+struct MarketTally {
+    shadow: Tally,
+    live: Tally,
+}
+
+struct Shadow {
+    detector: ArbitrageDetector,
+    execution_threshold: Decimal,
+    max_order_size: Decimal,
+    max_exposure: Decimal,
+    state: Mutex<ShadowState>,
+}
+
+#[derive(Default)]
+struct ShadowState {
+    last_trade: Option<Instant>,
+    /// 本窗口影子下单累计成本（影子不 merge，按窗口重置）
+    exposure: Decimal,
+    window: BTreeMap<String, MarketTally>,
+    since_start: MarketTally,
+}
+
+static SHADOW: OnceLock<Shadow> = OnceLock::new();
+
+/// 启用影子对比；live_* 为实盘参数，候选参数未设置时沿用
+pub fn init(
+    settings: &ShadowSettings,
+    live_min_profit: f64,
+    live_execution_spread: f64,
+    live_max_order_size: f64,
+    live_max_exposure: f64,
+) {
+    if !settings.enabled {
+        return;
+    }
+    let min_profit = settings.min_profit_threshold.unwrap_or(live_min_profit);
+    let spread = settings.execution_spread.unwrap_or(live_execution_spread);
+    let max_order_size = settings.max_order_size_usdc.unwrap_or(live_max_order_size);
+    let max_exposure = settings.max_exposure_usdc.unwrap_or(live_max_exposure);
+    let shadow = Shadow {
+        detector: ArbitrageDetector::new(min_profit),
+        execution_threshold: dec!(1) - Decimal::try_from(spread).unwrap_or(dec!(0.01)),
+        max_order_size: Decimal::try_from(max_order_size).unwrap_or(dec!(0)),
+        max_exposure: Decimal::try_from(max_exposure).unwrap_or(dec!(0)),
+        state: Mutex::new(ShadowState::default()),
+    };
+    if SHADOW.set(shadow).is_ok() {
+        info!(
+            "👥 影子配置对比已启用 | 最小利润:{} | 执行价差:{} | 单笔上限:{} USD | 敞口上限:{} USD",
+            min_profit, spread, max_order_size, max_exposure
+        );
+    }
+}
+
+/// 用候选参数检测一个订单簿对，满足条件时记录一笔影子下单
+pub fn observe(pair: &OrderBookPair, market: &str) {
+    let Some(shadow) = SHADOW.get() else {
+        return;
+    };
+    let total = match (pair.yes_book.asks.last(), pair.no_book.asks.last()) {
+        (Some(y), Some(n)) => y.price + n.price,
+        _ => return,
+    };
+    if total > shadow.execution_threshold {
+        return;
+    }
+    let Some(opp) = shadow.detector.check_arbitrage(&pair.yes_book, &pair.no_book, &pair.market_id) else {
+        return;
+    };
+    let size = opp.yes_size.min(opp.no_size).min(shadow.max_order_size);
+    let total_price = opp.yes_ask_price + opp.no_ask_price;
+    let cost = total_price * size;
+    let Ok(mut state) = shadow.state.lock() else {
+        return;
+    };
+    if size <= dec!(0)
+        || state.last_trade.is_some_and(|t| t.elapsed() < MIN_TRADE_INTERVAL)
+        || state.exposure + cost > shadow.max_exposure
+    {
+        return;
+    }
+    state.last_trade = Some(Instant::now());
+    state.exposure += cost;
+    state.window.entry(market.to_string()).or_default().shadow.add(total_price, size);
+    state.since_start.shadow.add(total_price, size);
+    drop(state);
+    metrics::incr("shadow_trades");
+    journal::record(
+        "shadow_trade",
+        json!({
+            "market_id": format!("{:#x}", pair.market_id),
+            "market": market,
+            "yes_ask": opp.yes_ask_price.to_string(),
+            "no_ask": opp.no_ask_price.to_string(),
+            "size": size.to_string(),
+            "expected_profit": ((dec!(1) - total_price) * size).to_string(),
+        }),
+    );
+}
+
+/// 记录一笔实盘下单（按检测价），用于与影子对比
+pub fn record_live(market: &str, total_price: Decimal, size: Decimal) {
+    let Some(shadow) = SHADOW.get() else {
+        return;
+    };
+    if let Ok(mut state) = shadow.state.lock() {
+        state.window.entry(market.to_string()).or_default().live.add(total_price, size);
+        state.since_start.live.add(total_price, size);
+    }
+}
+
+fn describe(t: &Tally) -> String {
+    format!(
+        "{}笔 {:.2}份 成本{:.2} 预期{:.4}",
+        t.trades, t.shares, t.cost, t.expected_profit
+    )
+}
+
+/// 窗口结束：按市场输出影子与实盘的对比并清零本窗口统计
+pub fn report_and_reset(window_timestamp: i64) {
+    let Some(shadow) = SHADOW.get() else {
+        return;
+    };
+    let Ok(mut state) = shadow.state.lock() else {
+        return;
+    };
+    let window = std::mem::take(&mut state.window);
+    state.exposure = dec!(0);
+    let since_start = (state.since_start.shadow, state.since_start.live);
+    drop(state);
+
+    let mut shadow_total = Tally::default();
+    let mut live_total = Tally::default();
+    info!("👥 影子对比 | 窗口:{}", window_timestamp);
+    for (market, tally) in &window {
+        info!("  {} | 影子:{} | 实盘:{}", market, describe(&tally.shadow), describe(&tally.live));
+        for (total, side) in [(&mut shadow_total, &tally.shadow), (&mut live_total, &tally.live)] {
+            total.trades += side.trades;
+            total.shares += side.shares;
+            total.cost += side.cost;
+            total.expected_profit += side.expected_profit;
+        }
+    }
+    info!(
+        "  合计 | 影子:{} | 实盘:{} | 预期利润差:{:.4} | 启动以来 影子:{} 实盘:{}",
+        describe(&shadow_total),
+        describe(&live_total),
+        shadow_total.expected_profit - live_total.expected_profit,
+        since_start.0.expected_profit,
+        since_start.1.expected_profit
+    );
+    journal::record(
+        "shadow_window_report",
+        json!({
+            "window": window_timestamp,
+            "shadow": {
+                "trades": shadow_total.trades,
+                "shares": shadow_total.shares.to_string(),
+                "cost": shadow_total.cost.to_string(),
+                "expected_profit": shadow_total.expected_profit.to_string(),
+            },
+            "live": {
+                "trades": live_total.trades,
+                "shares": live_total.shares.to_string(),
+                "cost": live_total.cost.to_string(),
+                "expected_profit": live_total.expected_profit.to_string(),
+            },
+        }),
+    );
+}
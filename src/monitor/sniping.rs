@@ -0,0 +1,119 @@
+//! 抢单（sniping）检测：我们按卖一价下的吃单在到达前就被别人吃掉（某条腿零成交 / 无可匹配订单），
+//! 说明该市场有更快的竞争者。在时间窗口内同一市场累计达到阈值后，对该市场额外提高所需价差一档，
+//! 只做更厚、不易被抢的机会；之后连续的双腿成交逐档回落。检测与回落结果写入交易日志。
+
+use polymarket_client_sdk::types::{Decimal, B256};
+use rust_decimal_macros::dec;
+use serde_json::json;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use super::fill_model::FillOutcome;
+use crate::utils::{journal, metrics};
+
+#[derive(Debug, Clone, Copy)]
+pub struct SnipeSettings {
+    /// 统计被抢单次数的时间窗口
+    pub window: Duration,
+    /// 窗口内被抢单多少次后提高价差要求
+    pub threshold: u32,
+    /// 每次提高的价差
+    pub edge_step: Decimal,
+    /// 额外价差上限
+    pub max_extra: Decimal,
+}
+
+#[derive(Default)]
+struct MarketState {
+    /// 窗口内被抢单的时间
+    sniped: VecDeque<Instant>,
+    /// 当前额外价差要求
+    extra: Decimal,
+}
+
+pub struct SnipeDetector {
+    settings: SnipeSettings,
+    markets: Mutex<HashMap<B256, MarketState>>,
+}
+
+impl SnipeDetector {
+    pub fn new(settings: SnipeSettings) -> Self {
+        Self {
+            settings,
+            markets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 该市场当前额外要求的价差（未被抢单为 0）
+    pub fn extra_edge(&self, market_id: B256) -> Decimal {
+        self.markets
+            .lock()
+            .ok()
+            .and_then(|m| m.get(&market_id).map(|s| s.extra))
+            .unwrap_or(dec!(0))
+    }
+
+    /// 记录一次吃单结果：有腿零成交视为被抢单，双腿成交使额外价差回落一档
+    pub fn record(&self, market_id: B256, market_display: &str, outcome: FillOutcome) {
+        let Ok(mut markets) = self.markets.lock() else {
+            return;
+        };
+        let s = &self.settings;
+        let state = markets.entry(market_id).or_default();
+        let now = Instant::now();
+        while state.sniped.front().is_some_and(|t| now.duration_since(*t) > s.window) {
+            state.sniped.pop_front();
+        }
+        match outcome {
+            FillOutcome::OneSided | FillOutcome::None => {
+                state.sniped.push_back(now);
+                metrics::incr("snipe_suspected");
+                if (state.sniped.len() as u32) < s.threshold.max(1) {
+                    return;
+                }
+                let count = state.sniped.len();
+                state.sniped.clear();
+                let next = (state.extra + s.edge_step).min(s.max_extra);
+                if next == state.extra {
+                    return;
+                }
+                state.extra = next;
+                metrics::incr("snipe_detected");
+                warn!(
+                    "🎯 疑似被抢单，提高该市场价差要求 | 市场:{} | {}秒内零成交腿:{}次 | 额外价差:{}",
+                    market_display,
+                    s.window.as_secs(),
+                    count,
+                    next
+                );
+                journal::record(
+                    "sniping_detected",
+                    json!({
+                        "market_id": format!("{:#x}", market_id),
+                        "market": market_display,
+                        "count": count,
+                        "window_secs": s.window.as_secs(),
+                        "extra_edge": next.to_string(),
+                    }),
+                );
+            }
+            FillOutcome::Both => {
+                if state.extra <= dec!(0) {
+                    return;
+                }
+                state.extra = (state.extra - s.edge_step).max(dec!(0));
+                info!("🎯 双腿成交，回落该市场额外价差 | 市场:{} | 额外价差:{}", market_display, state.extra);
+                journal::record(
+                    "sniping_relaxed",
+                    json!({
+                        "market_id": format!("{:#x}", market_id),
+                        "market": market_display,
+                        "extra_edge": state.extra.to_string(),
+                    }),
+                );
+            }
+        }
+    }
+}
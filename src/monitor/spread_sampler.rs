@@ -0,0 +1,72 @@
+//! 价差采样：窗口内每秒读取一次各市场 YES/NO 卖一价写入存储后端（spread_samples 表），
+//! 积累套利价差出现频率与深度的历史数据集，供 spreads 子命令查询与绘图。未启用存储后端时不采样。
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use polymarket_client_sdk::clob::ws::types::response::BookUpdate;
+use polymarket_client_sdk::types::{B256, U256};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::debug;
+
+use crate::market::MarketInfo;
+use crate::utils::metrics;
+use crate::utils::storage::{self, SpreadSample};
+
+/// 采样间隔
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+struct SampledMarket {
+    market_id: B256,
+    name: String,
+    yes_token_id: U256,
+    no_token_id: U256,
+}
+
+/// 启动本窗口的采样任务，到 until 时自动结束；窗口内新发现的市场另行调用一次
+pub fn spawn(books: Arc<DashMap<U256, BookUpdate>>, markets: &[MarketInfo], until: DateTime<Utc>) {
+    let Some(backend) = storage::get() else {
+        return;
+    };
+    let markets: Vec<SampledMarket> = markets
+        .iter()
+        .map(|m| SampledMarket {
+            market_id: m.market_id,
+            name: if m.crypto_symbol.is_empty() { m.slug.clone() } else { m.crypto_symbol.clone() },
+            yes_token_id: m.yes_token_id,
+            no_token_id: m.no_token_id,
+        })
+        .collect();
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(SAMPLE_INTERVAL);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            ticker.tick().await;
+            let now = Utc::now();
+            if now >= until {
+                return;
+            }
+            // asks 最后一个为卖一价
+            let best_ask = |token: U256| books.get(&token).and_then(|b| b.asks.last().map(|a| a.price));
+            let samples: Vec<SpreadSample> = markets
+                .iter()
+                .filter_map(|m| {
+                    Some(SpreadSample {
+                        ts: now,
+                        market_id: format!("{:#x}", m.market_id),
+                        market: m.name.clone(),
+                        yes_ask: best_ask(m.yes_token_id)?,
+                        no_ask: best_ask(m.no_token_id)?,
+                    })
+                })
+                .collect();
+            if samples.is_empty() {
+                continue;
+            }
+            if let Err(e) = backend.append_spread_samples(&samples).await {
+                debug!(error = %e, "价差采样写入失败");
+                metrics::incr("storage_write_failed");
+            }
+        }
+    });
+}
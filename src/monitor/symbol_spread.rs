@@ -0,0 +1,176 @@
+//! 按币种学习执行价差：按下单时的价差（1 - 卖一合计）分档统计每个币种的实际收益——
+//! 双腿成交计入锁定价差，单边成交计入单边处理成本（逆向选择的代价），均未成交不计入。
+//! 对每个币种取「该档及以上所有成交的平均实际收益为正、且样本足够」的最低一档作为该币种的执行价差，
+//! 覆盖全局 ARBITRAGE_EXECUTION_SPREAD。统计随每次结果落盘，学到的数值变化时写入交易日志。
+//! 币种按周期区分（键为 MarketInfo::symbol_key，如 btc:15m），同一币种不同周期的市场分别学习。
+
+use anyhow::Result;
+use polymarket_client_sdk::types::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{debug, info, warn};
+
+use super::fill_model::FillOutcome;
+use crate::utils::journal;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+struct BucketStats {
+    trades: u32,
+    /// 每份实际收益之和
+    realized: f64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SpreadFile {
+    /// 币种 -> (价差档 -> 统计)，档位 = floor(价差 / 档宽)
+    #[serde(default)]
+    symbols: HashMap<String, BTreeMap<u32, BucketStats>>,
+}
+
+pub struct SymbolSpreadLearner {
+    /// None 表示只在内存中保存，不落盘
+    path: Option<PathBuf>,
+    symbols: Mutex<HashMap<String, BTreeMap<u32, BucketStats>>>,
+    /// 已学到的价差（热路径只读这里）
+    learned: Mutex<HashMap<String, Decimal>>,
+    /// 档宽
+    step: Decimal,
+    /// 学到的价差至少需要的成交样本数
+    min_samples: u32,
+    /// 单边成交时每份的处理成本
+    one_sided_cost: f64,
+}
+
+impl SymbolSpreadLearner {
+    /// 从文件加载历史统计；path 为空字符串时不落盘，文件不存在或损坏时从零开始
+    pub fn load(path: &str, step: f64, min_samples: u32, one_sided_cost: f64) -> Self {
+        let path = (!path.trim().is_empty()).then(|| PathBuf::from(path));
+        let file = path
+            .as_ref()
+            .and_then(|p| std::fs::read(p).ok())
+            .and_then(|body| match serde_json::from_slice::<SpreadFile>(&body) {
+                Ok(file) => Some(file),
+                Err(e) => {
+                    warn!(error = %e, "币种价差统计文件损坏，忽略");
+                    None
+                }
+            })
+            .unwrap_or_default();
+        let learner = Self {
+            path,
+            symbols: Mutex::new(HashMap::new()),
+            learned: Mutex::new(HashMap::new()),
+            step: Decimal::try_from(step).unwrap_or(Decimal::new(5, 3)),
+            min_samples: min_samples.max(1),
+            one_sided_cost,
+        };
+        let mut learned = HashMap::new();
+        for (symbol, buckets) in &file.symbols {
+            if let Some(spread) = learner.pick(buckets) {
+                info!("📐 加载币种执行价差 | 币种:{} | 价差:{}", symbol, spread);
+                learned.insert(symbol.clone(), spread);
+            }
+        }
+        if let Ok(mut l) = learner.learned.lock() {
+            *l = learned;
+        }
+        if let Ok(mut s) = learner.symbols.lock() {
+            *s = file.symbols;
+        }
+        learner
+    }
+
+    /// 该币种学到的执行价差（样本不足或没有正收益档位时返回 None，沿用全局值）
+    pub fn spread_for(&self, symbol: &str) -> Option<Decimal> {
+        self.learned.lock().ok().and_then(|l| l.get(symbol).copied())
+    }
+
+    /// 从高到低累计，取累计平均收益为正且样本足够的最低一档
+    fn pick(&self, buckets: &BTreeMap<u32, BucketStats>) -> Option<Decimal> {
+        let (mut trades, mut realized) = (0u32, 0.0f64);
+        let mut best = None;
+        for (bucket, stats) in buckets.iter().rev() {
+            trades += stats.trades;
+            realized += stats.realized;
+            if trades >= self.min_samples && realized > 0.0 {
+                best = Some(*bucket);
+            }
+        }
+        best.map(|b| self.step * Decimal::from(b))
+    }
+
+    /// 记录一次下单结果；edge 为下单时的价差（1 - 卖一合计）
+    pub fn record(&self, symbol: &str, edge: Decimal, outcome: FillOutcome) {
+        let realized = match outcome {
+            FillOutcome::Both => edge.to_f64().unwrap_or(0.0),
+            FillOutcome::OneSided => -self.one_sided_cost,
+            FillOutcome::None => return,
+        };
+        if symbol.is_empty() || self.step <= Decimal::ZERO {
+            return;
+        }
+        let bucket = (edge / self.step).floor().to_u32().unwrap_or(0);
+        let Ok(mut symbols) = self.symbols.lock() else {
+            return;
+        };
+        let buckets = symbols.entry(symbol.to_string()).or_default();
+        let stats = buckets.entry(bucket).or_default();
+        stats.trades += 1;
+        stats.realized += realized;
+
+        let next = self.pick(buckets);
+        if let Ok(mut learned) = self.learned.lock() {
+            let previous = learned.get(symbol).copied();
+            if previous != next {
+                match next {
+                    Some(spread) => {
+                        learned.insert(symbol.to_string(), spread);
+                        info!(
+                            "📐 币种执行价差更新 | 币种:{} | {} → {}",
+                            symbol,
+                            previous.map(|d| d.to_string()).unwrap_or_else(|| "全局".to_string()),
+                            spread
+                        );
+                    }
+                    None => {
+                        learned.remove(symbol);
+                        info!("📐 币种执行价差失效，回到全局值 | 币种:{}", symbol);
+                    }
+                }
+                journal::record(
+                    "symbol_spread_learned",
+                    json!({
+                        "symbol": symbol,
+                        "previous": previous.map(|d| d.to_string()),
+                        "spread": next.map(|d| d.to_string()),
+                    }),
+                );
+            }
+        }
+
+        if let Some(path) = self.path.as_ref() {
+            let file = SpreadFile {
+                symbols: symbols.clone(),
+            };
+            if let Err(e) = Self::save(path, &file) {
+                debug!(error = %e, "币种价差统计写盘失败");
+            }
+        }
+    }
+
+    fn save(path: &Path, file: &SpreadFile) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            if !dir.as_os_str().is_empty() {
+                std::fs::create_dir_all(dir)?;
+            }
+        }
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(file)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
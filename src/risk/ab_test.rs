@@ -0,0 +1,291 @@
+//! A/B 资金分配：把敞口预算按比例（AB_SPLIT，如 0.7 = 70/30）分给两套实盘参数，
+//! A 为当前配置，B 为 AB_B_* 候选参数（未设置的沿用 A）。每个机会按两套参数分别判定，
+//! 都接受时分给预算占用比例更低的一方；每笔执行带上变体标签写入交易日志，按变体累计成对份数、锁定利润与单腿成本，
+//! 窗口结束时输出对比，用有限风险在实盘中比较阈值。各变体预算按窗口重置（持仓在窗口内 merge 或结算）。
+
+use polymarket_client_sdk::clob::ws::types::response::BookUpdate;
+use polymarket_client_sdk::types::{Decimal, B256};
+use rust_decimal_macros::dec;
+use serde_json::json;
+use std::sync::{Mutex, OnceLock};
+use tracing::{debug, info};
+
+use crate::monitor::arbitrage::{ArbitrageDetector, ArbitrageOpportunity};
+use crate::utils::journal;
+
+#[derive(Debug, Clone, Default)]
+pub struct AbSettings {
+    pub enabled: bool,
+    /// A 分得的敞口预算比例（0~1），其余给 B
+    pub split_a: f64,
+    pub b_min_profit_threshold: Option<f64>,
+    pub b_execution_spread: Option<f64>,
+    pub b_max_order_size_usdc: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    A,
+    B,
+}
+
+impl Variant {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Variant::A => "A",
+            Variant::B => "B",
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            Variant::A => 0,
+            Variant::B => 1,
+        }
+    }
+}
+
+/// 一个变体的执行结果
+#[derive(Debug, Clone, Copy, Default)]
+struct VariantPnl {
+    executions: u64,
+    matched: Decimal,
+    locked_profit: Decimal,
+    /// 未成对部分的买入成本（待对冲/卖出，风险敞口）
+    unmatched_cost: Decimal,
+}
+
+#[derive(Default)]
+struct AbState {
+    /// 本窗口各变体已占用的预算
+    used: [Decimal; 2],
+    window: [VariantPnl; 2],
+    since_start: [VariantPnl; 2],
+}
+
+struct AbTest {
+    budgets: [Decimal; 2],
+    b_detector: ArbitrageDetector,
+    b_threshold: Decimal,
+    b_max_order_size: Option<Decimal>,
+    state: Mutex<AbState>,
+}
+
+static AB: OnceLock<AbTest> = OnceLock::new();
+
+/// 启用 A/B 分配；max_exposure 为总敞口上限，live_* 为 A 的参数（B 未设置的项沿用）
+pub fn init(settings: &AbSettings, max_exposure: f64, live_min_profit: f64, live_execution_spread: f64) {
+    if !settings.enabled {
+        return;
+    }
+    let split = settings.split_a.clamp(0.0, 1.0);
+    let max_exposure = Decimal::try_from(max_exposure).unwrap_or(dec!(0));
+    let budget_a = (max_exposure * Decimal::try_from(split).unwrap_or(dec!(0.5))).round_dp(2);
+    let b_spread = settings.b_execution_spread.unwrap_or(live_execution_spread);
+    let b_min_profit = settings.b_min_profit_threshold.unwrap_or(live_min_profit);
+    let ab = AbTest {
+        budgets: [budget_a, max_exposure - budget_a],
+        b_detector: ArbitrageDetector::new(b_min_profit),
+        b_threshold: dec!(1) - Decimal::try_from(b_spread).unwrap_or(dec!(0.01)),
+        b_max_order_size: settings.b_max_order_size_usdc.and_then(|v| Decimal::try_from(v).ok()),
+        state: Mutex::new(AbState::default()),
+    };
+    info!(
+        "🅰️🅱️ A/B 资金分配已启用 | A 预算:{} USD | B 预算:{} USD | B 最小利润:{} | B 执行价差:{} | B 单笔上限:{}",
+        ab.budgets[0],
+        ab.budgets[1],
+        b_min_profit,
+        b_spread,
+        ab.b_max_order_size.map(|v| v.to_string()).unwrap_or_else(|| "同 A".to_string())
+    );
+    let _ = AB.set(ab);
+}
+
+/// 进入检测的总价门槛：启用时取 A、B 中较宽松者
+pub fn gate_threshold(live_threshold: Decimal) -> Decimal {
+    match AB.get() {
+        Some(ab) => live_threshold.max(ab.b_threshold),
+        None => live_threshold,
+    }
+}
+
+/// 所分配变体的执行阈值：B 组用 B 组阈值，其余用实盘阈值
+pub fn threshold_for(variant: Option<Variant>, live_threshold: Decimal) -> Decimal {
+    match (variant, AB.get()) {
+        (Some(Variant::B), Some(ab)) => ab.b_threshold,
+        _ => live_threshold,
+    }
+}
+
+/// 按两套参数检测并分配变体；未启用时只用实盘检测器，变体为 None
+pub fn detect(
+    live: &ArbitrageDetector,
+    yes_book: &BookUpdate,
+    no_book: &BookUpdate,
+    market_id: &B256,
+    total_price: Decimal,
+    live_threshold: Decimal,
+) -> (Option<ArbitrageOpportunity>, Option<Variant>) {
+    let Some(ab) = AB.get() else {
+        return (live.check_arbitrage(yes_book, no_book, market_id), None);
+    };
+    let a = (total_price <= live_threshold)
+        .then(|| live.check_arbitrage(yes_book, no_book, market_id))
+        .flatten();
+    let b_accepts = total_price <= ab.b_threshold;
+    match a {
+        Some(opp) if b_accepts && ab.b_detector.check_arbitrage(yes_book, no_book, market_id).is_some() => {
+            (Some(opp), Some(ab.less_utilized()))
+        }
+        Some(opp) => (Some(opp), Some(Variant::A)),
+        None if b_accepts => match ab.b_detector.check_arbitrage(yes_book, no_book, market_id) {
+            Some(opp) => (Some(opp), Some(Variant::B)),
+            None => (None, None),
+        },
+        None => (None, None),
+    }
+}
+
+impl AbTest {
+    /// 预算占用比例更低的变体（预算为 0 的变体不参与）
+    fn less_utilized(&self) -> Variant {
+        let Ok(state) = self.state.lock() else {
+            return Variant::A;
+        };
+        let ratio = |v: Variant| {
+            let budget = self.budgets[v.index()];
+            if budget <= dec!(0) {
+                Decimal::MAX
+            } else {
+                state.used[v.index()] / budget
+            }
+        };
+        if ratio(Variant::B) < ratio(Variant::A) {
+            Variant::B
+        } else {
+            Variant::A
+        }
+    }
+}
+
+/// 按变体单笔上限截断下单数量（B 未设置上限时不变）
+pub fn cap_size(variant: Variant, size: Decimal) -> Decimal {
+    match (variant, AB.get().and_then(|ab| ab.b_max_order_size)) {
+        (Variant::B, Some(max)) => size.min(max),
+        _ => size,
+    }
+}
+
+/// 占用变体预算；超出该变体预算时返回 false
+pub fn try_reserve(variant: Variant, cost: Decimal) -> bool {
+    let Some(ab) = AB.get() else {
+        return true;
+    };
+    let Ok(mut state) = ab.state.lock() else {
+        return false;
+    };
+    let used = &mut state.used[variant.index()];
+    if *used + cost > ab.budgets[variant.index()] {
+        debug!(
+            variant = variant.as_str(),
+            used = %used,
+            cost = %cost,
+            budget = %ab.budgets[variant.index()],
+            "A/B：变体预算不足"
+        );
+        return false;
+    }
+    *used += cost;
+    true
+}
+
+/// 归还预算（预留后未实际下单时）
+pub fn release(variant: Variant, cost: Decimal) {
+    if let Some(ab) = AB.get() {
+        if let Ok(mut state) = ab.state.lock() {
+            let used = &mut state.used[variant.index()];
+            *used = (*used - cost).max(dec!(0));
+        }
+    }
+}
+
+/// 记录一笔带变体标签的执行结果
+pub fn record_fill(
+    variant: Variant,
+    market: &str,
+    yes_filled: Decimal,
+    no_filled: Decimal,
+    yes_price: Decimal,
+    no_price: Decimal,
+) {
+    let Some(ab) = AB.get() else {
+        return;
+    };
+    let matched = yes_filled.min(no_filled);
+    let locked_profit = matched * (dec!(1) - yes_price - no_price);
+    let unmatched_cost = (yes_filled - matched) * yes_price + (no_filled - matched) * no_price;
+    if let Ok(mut state) = ab.state.lock() {
+        for pnl in [&mut state.window[variant.index()], &mut state.since_start[variant.index()]] {
+            pnl.executions += 1;
+            pnl.matched += matched;
+            pnl.locked_profit += locked_profit;
+            pnl.unmatched_cost += unmatched_cost;
+        }
+    }
+    journal::record(
+        "ab_execution",
+        json!({
+            "variant": variant.as_str(),
+            "market": market,
+            "yes_filled": yes_filled.to_string(),
+            "no_filled": no_filled.to_string(),
+            "yes_price": yes_price.to_string(),
+            "no_price": no_price.to_string(),
+            "locked_profit": locked_profit.to_string(),
+            "unmatched_cost": unmatched_cost.to_string(),
+        }),
+    );
+}
+
+fn describe(p: &VariantPnl) -> String {
+    format!(
+        "{}笔 成对{:.2}份 锁定利润{:.4} 单腿成本{:.2}",
+        p.executions, p.matched, p.locked_profit, p.unmatched_cost
+    )
+}
+
+/// 窗口结束：输出各变体的结果并重置预算与本窗口统计
+pub fn report_and_reset(window_timestamp: i64) {
+    let Some(ab) = AB.get() else {
+        return;
+    };
+    let Ok(mut state) = ab.state.lock() else {
+        return;
+    };
+    let window = std::mem::take(&mut state.window);
+    let since_start = state.since_start;
+    state.used = [dec!(0); 2];
+    drop(state);
+
+    info!("🅰️🅱️ A/B 对比 | 窗口:{}", window_timestamp);
+    for variant in [Variant::A, Variant::B] {
+        let i = variant.index();
+        info!(
+            "  {} | 本窗口:{} | 启动以来:{}",
+            variant.as_str(),
+            describe(&window[i]),
+            describe(&since_start[i])
+        );
+        journal::record(
+            "ab_window_report",
+            json!({
+                "window": window_timestamp,
+                "variant": variant.as_str(),
+                "executions": window[i].executions,
+                "matched": window[i].matched.to_string(),
+                "locked_profit": window[i].locked_profit.to_string(),
+                "unmatched_cost": window[i].unmatched_cost.to_string(),
+            }),
+        );
+    }
+}
@@ -0,0 +1,172 @@
+//! 持仓账龄告警：定期对照各持仓首次出现的时间与所属市场的结束时间，
+//! 市场结束超过宽限期仍未赎回、或单边（未对冲）持仓超过最长账龄时告警并写入交易日志，
+//! 启用子图时以链上结算（赔付已写入）为准判断「已结算」，否则按市场结束时间判断。
+//! 把容易被忽略的滞留资金暴露出来。同一市场同类告警只发一次，持仓消失后重置；账龄从本进程首次看到该持仓起算。
+
+use chrono::{DateTime, Utc};
+use polymarket_client_sdk::types::{Decimal, B256, U256};
+use poly_1hour_bot::positions::Position;
+use rust_decimal_macros::dec;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::warn;
+
+use crate::market::MarketInfo;
+use crate::utils::{journal, metrics};
+
+#[derive(Debug, Clone, Copy)]
+pub struct AgingSettings {
+    /// 单边持仓最长账龄
+    pub max_unhedged_age: Duration,
+    /// 市场结束后多久仍未赎回才告警（留出结算与自动赎回的时间）
+    pub unredeemed_grace: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum AlertKind {
+    Unredeemed,
+    Unhedged,
+}
+
+impl AlertKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            AlertKind::Unredeemed => "unredeemed",
+            AlertKind::Unhedged => "unhedged",
+        }
+    }
+}
+
+#[derive(Default)]
+struct AgingState {
+    /// 市场结束时间（窗口开始时登记）
+    end_dates: HashMap<B256, DateTime<Utc>>,
+    /// 子图确认已结算的市场及首次确认时间
+    resolved: HashMap<B256, DateTime<Utc>>,
+    /// 持仓首次出现的时间
+    first_seen: HashMap<U256, DateTime<Utc>>,
+    /// 已告警的 (市场, 类型)
+    alerted: HashSet<(B256, AlertKind)>,
+}
+
+pub struct PositionAging {
+    settings: AgingSettings,
+    state: Mutex<AgingState>,
+}
+
+impl PositionAging {
+    pub fn new(settings: AgingSettings) -> Self {
+        Self {
+            settings,
+            state: Mutex::new(AgingState::default()),
+        }
+    }
+
+    /// 登记市场结束时间（不在登记表中的市场只做单边账龄检查）
+    pub fn note_market(&self, market: &MarketInfo) {
+        if let Ok(mut state) = self.state.lock() {
+            state.end_dates.insert(market.market_id, market.end_date);
+        }
+    }
+
+    /// 登记子图确认的结算（结算时间按首次确认时间计）
+    pub fn note_resolved(&self, market_id: B256) {
+        if let Ok(mut state) = self.state.lock() {
+            state.resolved.entry(market_id).or_insert_with(Utc::now);
+        }
+    }
+
+    /// 是否已确认结算
+    pub fn is_resolved(&self, market_id: B256) -> bool {
+        self.state.lock().map(|s| s.resolved.contains_key(&market_id)).unwrap_or(false)
+    }
+
+    /// 用最新持仓检查账龄，返回本次新发出的告警数
+    pub fn check(&self, positions: &[Position]) -> usize {
+        let Ok(mut state) = self.state.lock() else {
+            return 0;
+        };
+        let now = Utc::now();
+        let mut by_market: HashMap<B256, (Decimal, Decimal, String)> = HashMap::new();
+        let mut held: HashSet<U256> = HashSet::new();
+        for pos in positions.iter().filter(|p| p.size > dec!(0)) {
+            held.insert(pos.asset);
+            state.first_seen.entry(pos.asset).or_insert(now);
+            let entry = by_market.entry(pos.condition_id).or_insert((dec!(0), dec!(0), pos.title.clone()));
+            match pos.outcome_index {
+                0 => entry.0 = pos.size,
+                1 => entry.1 = pos.size,
+                _ => {}
+            }
+        }
+        state.first_seen.retain(|token, _| held.contains(token));
+        state.alerted.retain(|(market, _)| by_market.contains_key(market));
+        state
+            .end_dates
+            .retain(|market, end| by_market.contains_key(market) || *end > now - chrono::Duration::days(1));
+        state.resolved.retain(|market, _| by_market.contains_key(market));
+
+        let oldest = |state: &AgingState, market: B256| -> Option<DateTime<Utc>> {
+            positions
+                .iter()
+                .filter(|p| p.condition_id == market && p.size > dec!(0))
+                .filter_map(|p| state.first_seen.get(&p.asset).copied())
+                .min()
+        };
+
+        let mut raised = 0;
+        for (market, (yes, no, title)) in &by_market {
+            let Some(since) = oldest(&state, *market) else {
+                continue;
+            };
+            let age = (now - since).to_std().unwrap_or_default();
+            let overdue = state.resolved.get(market).or(state.end_dates.get(market)).and_then(|end| {
+                let past_end = (now - *end).to_std().ok()?;
+                (past_end >= self.settings.unredeemed_grace).then_some(past_end)
+            });
+
+            let alert = if let Some(past_end) = overdue {
+                Some((
+                    AlertKind::Unredeemed,
+                    format!("市场已结束 {} 分钟仍未赎回", past_end.as_secs() / 60),
+                ))
+            } else if (*yes == dec!(0)) != (*no == dec!(0)) && age >= self.settings.max_unhedged_age {
+                Some((AlertKind::Unhedged, format!("单边持仓已 {} 分钟未对冲", age.as_secs() / 60)))
+            } else {
+                None
+            };
+            let Some((kind, reason)) = alert else {
+                continue;
+            };
+            if !state.alerted.insert((*market, kind)) {
+                continue;
+            }
+            raised += 1;
+            warn!(
+                "⏳ 持仓滞留 | {} | 市场:{} | condition_id={:#x} | YES:{} NO:{} | 持有:{}分钟",
+                reason,
+                title,
+                market,
+                yes,
+                no,
+                age.as_secs() / 60
+            );
+            metrics::incr("position_aging_alert");
+            journal::record(
+                "position_aging_alert",
+                json!({
+                    "kind": kind.as_str(),
+                    "condition_id": format!("{:#x}", market),
+                    "title": title,
+                    "yes": yes.to_string(),
+                    "no": no.to_string(),
+                    "held_secs": age.as_secs(),
+                    "end_date": state.end_dates.get(market).map(|d| d.to_rfc3339()),
+                }),
+            );
+        }
+        raised
+    }
+}
@@ -0,0 +1,157 @@
+//! 下单前风控检查：执行阈值、YES/NO 最低价格、临近市场结束停止开仓、风险敞口上限（含多实例共享额度预留）
+//! 与各策略的独立额度，统一由 RiskManager::approve / approve_leg 执行，套利、阶梯套利、maker 兜底、回卖
+//! 与方向性下单走同一套检查；通过时敞口额度已预留
+//! （Approval::reservation，下单后 commit，未下单则 release 或直接丢弃），
+//! 否则返回带原因的否决（Veto），由调用方记录日志与计数（risk_veto_*）。
+
+use chrono::{DateTime, Utc};
+use polymarket_client_sdk::types::Decimal;
+use rust_decimal_macros::dec;
+use std::fmt;
+
+use super::budget::Strategy;
+use super::positions::ExposureReservation;
+use crate::config::Config as BotConfig;
+use crate::monitor::arbitrage::ArbitrageOpportunity;
+
+/// 风控参数（来自配置）
+#[derive(Debug, Clone, Copy)]
+pub struct RiskLimits {
+    /// YES 卖一价下限，0 = 不限制
+    pub min_yes_price: Decimal,
+    /// NO 卖一价下限，0 = 不限制
+    pub min_no_price: Decimal,
+    /// 市场结束前 N 分钟停止开仓，0 = 不停止
+    pub stop_before_end_minutes: u64,
+}
+
+impl RiskLimits {
+    pub fn from_config(config: &BotConfig) -> Self {
+        Self {
+            min_yes_price: Decimal::try_from(config.min_yes_price_threshold).unwrap_or(dec!(0)),
+            min_no_price: Decimal::try_from(config.min_no_price_threshold).unwrap_or(dec!(0)),
+            stop_before_end_minutes: config.stop_arbitrage_before_end_minutes,
+        }
+    }
+}
+
+/// 待审批的成对下单
+#[derive(Debug, Clone, Copy)]
+pub struct Proposal<'a> {
+    pub opportunity: &'a ArbitrageOpportunity,
+    /// 每腿下单数量
+    pub order_size: Decimal,
+    /// 两腿卖一总价须不高于该值
+    pub execution_threshold: Decimal,
+    /// 市场结束时间；未知时不检查临近结束
+    pub market_end: Option<DateTime<Utc>>,
+}
+
+/// 审批通过：敞口额度已预留
+pub struct Approval {
+    /// 审批前的敞口（USD，含其他未提交的预留）
+    pub current_exposure: Decimal,
+    /// 本次下单成本（USD）
+    pub total_cost: Decimal,
+    /// 本次下单的敞口预留
+    pub reservation: ExposureReservation,
+}
+
+/// 否决原因
+#[derive(Debug, Clone, Copy)]
+pub enum Veto {
+    /// 两腿总价高于执行阈值
+    AboveThreshold { total: Decimal, threshold: Decimal },
+    /// YES 价格低于下限
+    YesPriceFloor { price: Decimal, floor: Decimal },
+    /// NO 价格低于下限
+    NoPriceFloor { price: Decimal, floor: Decimal },
+    /// 距市场结束不足停止开仓的分钟数
+    NearMarketEnd { minutes_left: i64, cutoff: u64 },
+    /// 下单后敞口将超过上限（本实例或多实例共享额度）
+    ExposureLimit { current: Decimal, cost: Decimal, max: Decimal },
+    /// 下单后该策略的用量将超过其独立额度
+    StrategyBudget { strategy: Strategy, current: Decimal, cost: Decimal, max: Decimal },
+}
+
+impl Veto {
+    /// 计数器名称
+    pub fn metric(&self) -> &'static str {
+        match self {
+            Veto::AboveThreshold { .. } => "risk_veto_threshold",
+            Veto::YesPriceFloor { .. } => "risk_veto_yes_price",
+            Veto::NoPriceFloor { .. } => "risk_veto_no_price",
+            Veto::NearMarketEnd { .. } => "risk_veto_near_end",
+            Veto::ExposureLimit { .. } => "risk_veto_exposure",
+            Veto::StrategyBudget { strategy, .. } => strategy.veto_metric(),
+        }
+    }
+}
+
+impl fmt::Display for Veto {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Veto::AboveThreshold { total, threshold } => {
+                write!(f, "总价高于执行阈值 | 总价:{:.4} | 阈值:{:.4}", total, threshold)
+            }
+            Veto::YesPriceFloor { price, floor } => {
+                write!(f, "YES价格未达到阈值 | YES价格:{:.4} | 阈值:{:.4}", price, floor)
+            }
+            Veto::NoPriceFloor { price, floor } => {
+                write!(f, "NO价格未达到阈值 | NO价格:{:.4} | 阈值:{:.4}", price, floor)
+            }
+            Veto::NearMarketEnd { minutes_left, cutoff } => {
+                write!(f, "接近市场结束时间 | 距离结束:{}分钟 | 停止阈值:{}分钟", minutes_left, cutoff)
+            }
+            Veto::ExposureLimit { current, cost, max } => write!(
+                f,
+                "风险敞口超限 | 当前敞口:{:.2} USD | 订单成本:{:.2} USD | 限制:{:.2} USD",
+                current, cost, max
+            ),
+            Veto::StrategyBudget { strategy, current, cost, max } => write!(
+                f,
+                "策略额度超限 | 策略:{} | 已用额度:{:.2} USD | 订单成本:{:.2} USD | 额度:{:.2} USD",
+                strategy.as_str(),
+                current,
+                cost,
+                max
+            ),
+        }
+    }
+}
+
+/// 不依赖持仓状态的检查（阈值、价格下限、临近结束），按顺序返回第一个否决
+pub(super) fn check_static(limits: &RiskLimits, proposal: &Proposal<'_>) -> Option<Veto> {
+    let opp = proposal.opportunity;
+    let total = opp.yes_ask_price + opp.no_ask_price;
+    if total > proposal.execution_threshold {
+        return Some(Veto::AboveThreshold {
+            total,
+            threshold: proposal.execution_threshold,
+        });
+    }
+    if limits.min_yes_price > dec!(0) && opp.yes_ask_price < limits.min_yes_price {
+        return Some(Veto::YesPriceFloor {
+            price: opp.yes_ask_price,
+            floor: limits.min_yes_price,
+        });
+    }
+    if limits.min_no_price > dec!(0) && opp.no_ask_price < limits.min_no_price {
+        return Some(Veto::NoPriceFloor {
+            price: opp.no_ask_price,
+            floor: limits.min_no_price,
+        });
+    }
+    if limits.stop_before_end_minutes > 0 {
+        if let Some(end) = proposal.market_end {
+            let minutes_left = end.signed_duration_since(Utc::now()).num_minutes();
+            if minutes_left <= limits.stop_before_end_minutes as i64 {
+                return Some(Veto::NearMarketEnd {
+                    minutes_left,
+                    cutoff: limits.stop_before_end_minutes,
+                });
+            }
+        }
+    }
+    None
+}
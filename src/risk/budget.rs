@@ -0,0 +1,107 @@
+//! 按策略划分的敞口额度：taker 套利（含阶梯套利）、maker 兜底、回卖、方向性下单各有独立上限
+//! （RISK_BUDGET_*_USDC，0 = 不单独限制），激进的实验性策略用满自己的额度后只会被自己的额度拒绝，
+//! 不会挤占核心套利的资金。买入类策略的预留须同时通过本策略额度与全局上限（RISK_MAX_EXPOSURE_USDC）；
+//! 回卖是卖出、不增加敞口，只占用回卖额度（在途卖单的名义金额），执行结束即归还。
+//! 策略用量 = 归属该策略的已提交敞口成本（随 merge / 卖出按比例扣减）+ 已预留未提交的额度。
+
+use polymarket_client_sdk::types::Decimal;
+use rust_decimal_macros::dec;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Strategy {
+    /// 吃单套利（成对 / 顺序执行、阶梯套利）
+    TakerArb,
+    /// 吃单落空后的 maker 兜底挂单
+    MakerFallback,
+    /// 成对持仓回卖止盈
+    SellBack,
+    /// 方向性单腿下单
+    Directional,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 4] = [
+        Strategy::TakerArb,
+        Strategy::MakerFallback,
+        Strategy::SellBack,
+        Strategy::Directional,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Strategy::TakerArb => "taker_arb",
+            Strategy::MakerFallback => "maker_fallback",
+            Strategy::SellBack => "sell_back",
+            Strategy::Directional => "directional",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|strategy| strategy.as_str() == s)
+    }
+
+    /// 是否增加敞口（买入）；回卖为卖出，只占用本策略额度，不计入全局上限
+    pub fn adds_exposure(self) -> bool {
+        !matches!(self, Strategy::SellBack)
+    }
+
+    /// 额度否决计数器名称
+    pub fn veto_metric(self) -> &'static str {
+        match self {
+            Strategy::TakerArb => "budget_veto_taker_arb",
+            Strategy::MakerFallback => "budget_veto_maker_fallback",
+            Strategy::SellBack => "budget_veto_sell_back",
+            Strategy::Directional => "budget_veto_directional",
+        }
+    }
+
+    /// 已预留额度累计（美分）计数器名称
+    pub fn reserved_metric(self) -> &'static str {
+        match self {
+            Strategy::TakerArb => "budget_reserved_cents_taker_arb",
+            Strategy::MakerFallback => "budget_reserved_cents_maker_fallback",
+            Strategy::SellBack => "budget_reserved_cents_sell_back",
+            Strategy::Directional => "budget_reserved_cents_directional",
+        }
+    }
+}
+
+/// 各策略的额度上限（USD），0 = 不单独限制（只受全局上限约束）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StrategyBudgets {
+    pub taker_arb: f64,
+    pub maker_fallback: f64,
+    pub sell_back: f64,
+    pub directional: f64,
+}
+
+impl StrategyBudgets {
+    /// 某策略的额度上限；0 或无效值时为 None（不单独限制）
+    pub fn limit(&self, strategy: Strategy) -> Option<Decimal> {
+        let limit = match strategy {
+            Strategy::TakerArb => self.taker_arb,
+            Strategy::MakerFallback => self.maker_fallback,
+            Strategy::SellBack => self.sell_back,
+            Strategy::Directional => self.directional,
+        };
+        Decimal::try_from(limit).ok().filter(|l| *l > dec!(0))
+    }
+}
+
+/// 某策略当前的额度用量（状态接口与窗口报告用）
+#[derive(Debug, Clone, Copy)]
+pub struct BudgetUsage {
+    pub strategy: Strategy,
+    /// 归属该策略的已提交敞口成本（USD）
+    pub committed: Decimal,
+    /// 已预留、尚未提交的额度（USD）
+    pub reserved: Decimal,
+    /// 额度上限；None 表示不单独限制
+    pub limit: Option<Decimal>,
+}
+
+impl BudgetUsage {
+    pub fn used(&self) -> Decimal {
+        self.committed + self.reserved
+    }
+}
@@ -0,0 +1,309 @@
+//! 风控状态检查点：定时（及正常退出时）把持仓、敞口成本、订单对与登记的挂单（OrderStore）写盘，
+//! 崩溃重启后从数秒前的状态恢复，而不是完全依赖 API 重建（API 不返回敞口成本与未完结订单对）。
+//!
+//! 文件为带版本号的 JSON；token/市场 ID 与数量均以字符串保存，避免依赖外部类型的序列化格式。
+//! 结构变更时提升 `CHECKPOINT_VERSION`，并在 `load` 中对旧版本做迁移或丢弃。
+//! 启用存储后端（STORAGE_URL）时检查点写入数据库 state 表（名称 checkpoint），不再写本地文件。
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use polymarket_client_sdk::types::{B256, Decimal, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+use tracing::{info, warn};
+
+use super::budget::Strategy;
+use super::manager::{OrderPair, PairStatus, RiskManager};
+use super::positions::PositionSnapshot;
+use crate::trading::order_store::{OrderStore, TrackedOrder};
+use crate::utils::{state_crypto, storage};
+
+/// 存储后端中检查点的状态名
+const STATE_NAME: &str = "checkpoint";
+
+/// 当前检查点结构版本
+pub const CHECKPOINT_VERSION: u32 = 1;
+
+/// 订单对的可序列化形式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderPairRecord {
+    pub pair_id: String,
+    pub market_id: String,
+    pub yes_order_id: String,
+    pub no_order_id: String,
+    /// 旧检查点无此字段时为空
+    #[serde(default)]
+    pub child_order_ids: Vec<String>,
+    pub yes_token_id: String,
+    pub no_token_id: String,
+    pub yes_size: String,
+    pub no_size: String,
+    pub yes_filled: String,
+    pub no_filled: String,
+    /// 旧检查点无此字段时为空，恢复为 0
+    #[serde(default)]
+    pub yes_price: String,
+    #[serde(default)]
+    pub no_price: String,
+    pub status: PairStatus,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&OrderPair> for OrderPairRecord {
+    fn from(pair: &OrderPair) -> Self {
+        Self {
+            pair_id: pair.pair_id.clone(),
+            market_id: pair.market_id.to_string(),
+            yes_order_id: pair.yes_order_id.clone(),
+            no_order_id: pair.no_order_id.clone(),
+            child_order_ids: pair.child_order_ids.clone(),
+            yes_token_id: pair.yes_token_id.to_string(),
+            no_token_id: pair.no_token_id.to_string(),
+            yes_size: pair.yes_size.to_string(),
+            no_size: pair.no_size.to_string(),
+            yes_filled: pair.yes_filled.to_string(),
+            no_filled: pair.no_filled.to_string(),
+            yes_price: pair.yes_price.to_string(),
+            no_price: pair.no_price.to_string(),
+            status: pair.status.clone(),
+            created_at: pair.created_at,
+        }
+    }
+}
+
+impl OrderPairRecord {
+    fn into_pair(self) -> Result<OrderPair> {
+        Ok(OrderPair {
+            market_id: B256::from_str(&self.market_id).context("market_id 格式错误")?,
+            yes_token_id: U256::from_str(&self.yes_token_id).context("yes_token_id 格式错误")?,
+            no_token_id: U256::from_str(&self.no_token_id).context("no_token_id 格式错误")?,
+            yes_size: Decimal::from_str(&self.yes_size)?,
+            no_size: Decimal::from_str(&self.no_size)?,
+            yes_filled: Decimal::from_str(&self.yes_filled)?,
+            no_filled: Decimal::from_str(&self.no_filled)?,
+            yes_price: Decimal::from_str(&self.yes_price).unwrap_or_default(),
+            no_price: Decimal::from_str(&self.no_price).unwrap_or_default(),
+            pair_id: self.pair_id,
+            yes_order_id: self.yes_order_id,
+            no_order_id: self.no_order_id,
+            child_order_ids: self.child_order_ids,
+            status: self.status,
+            created_at: self.created_at,
+        })
+    }
+}
+
+/// 登记挂单（OrderStore）的可序列化形式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestingOrderRecord {
+    pub order_id: String,
+    pub market_id: String,
+    pub token_id: String,
+    pub price: String,
+    pub size: String,
+    pub cancel_at: DateTime<Utc>,
+    pub source: String,
+}
+
+impl From<&TrackedOrder> for RestingOrderRecord {
+    fn from(order: &TrackedOrder) -> Self {
+        Self {
+            order_id: order.order_id.clone(),
+            market_id: order.market_id.to_string(),
+            token_id: order.token_id.to_string(),
+            price: order.price.to_string(),
+            size: order.size.to_string(),
+            cancel_at: order.cancel_at,
+            source: order.source.clone(),
+        }
+    }
+}
+
+impl RestingOrderRecord {
+    fn into_order(self) -> Result<TrackedOrder> {
+        Ok(TrackedOrder {
+            market_id: B256::from_str(&self.market_id).context("market_id 格式错误")?,
+            token_id: U256::from_str(&self.token_id).context("token_id 格式错误")?,
+            price: Decimal::from_str(&self.price)?,
+            size: Decimal::from_str(&self.size)?,
+            order_id: self.order_id,
+            cancel_at: self.cancel_at,
+            source: self.source,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskCheckpoint {
+    pub version: u32,
+    pub saved_at: DateTime<Utc>,
+    /// 写盘时所处窗口的开始时间戳；同一窗口内重启才沿用敞口，跨窗口按新一轮重置
+    pub window_timestamp: i64,
+    /// token_id -> 持仓数量
+    pub positions: HashMap<String, String>,
+    /// token_id -> 敞口成本（USD）
+    pub exposure_costs: HashMap<String, String>,
+    /// 策略 -> token_id -> 归属该策略的敞口成本；旧检查点无此字段时为空
+    #[serde(default)]
+    pub strategy_costs: HashMap<String, HashMap<String, String>>,
+    pub pending_pairs: Vec<OrderPairRecord>,
+    /// 登记的挂单；旧检查点无此字段时为空
+    #[serde(default)]
+    pub resting_orders: Vec<RestingOrderRecord>,
+}
+
+fn encode_map(map: &HashMap<U256, Decimal>) -> HashMap<String, String> {
+    map.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+fn decode_map(map: &HashMap<String, String>) -> Result<HashMap<U256, Decimal>> {
+    map.iter()
+        .map(|(k, v)| {
+            Ok((
+                U256::from_str(k).with_context(|| format!("token_id 格式错误: {}", k))?,
+                Decimal::from_str(v).with_context(|| format!("数量格式错误: {}", v))?,
+            ))
+        })
+        .collect()
+}
+
+impl RiskCheckpoint {
+    /// 采集 RiskManager、PositionTracker 与挂单登记的当前状态
+    pub fn capture(risk_manager: &RiskManager, order_store: &OrderStore, window_timestamp: i64) -> Self {
+        let snapshot = risk_manager.position_tracker().snapshot();
+        Self {
+            version: CHECKPOINT_VERSION,
+            saved_at: Utc::now(),
+            window_timestamp,
+            positions: encode_map(&snapshot.positions),
+            exposure_costs: encode_map(&snapshot.exposure_costs),
+            strategy_costs: snapshot
+                .strategy_costs
+                .iter()
+                .map(|(strategy, costs)| (strategy.as_str().to_string(), encode_map(costs)))
+                .collect(),
+            pending_pairs: risk_manager
+                .pending_pairs_snapshot()
+                .iter()
+                .map(OrderPairRecord::from)
+                .collect(),
+            resting_orders: order_store.snapshot().iter().map(RestingOrderRecord::from).collect(),
+        }
+    }
+
+    /// 写盘：先写临时文件再 rename，保证崩溃时不会留下半个文件
+    pub async fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            if !dir.as_os_str().is_empty() {
+                tokio::fs::create_dir_all(dir).await?;
+            }
+        }
+        let tmp = path.with_extension("json.tmp");
+        let body = state_crypto::seal(&serde_json::to_vec_pretty(self)?)?;
+        tokio::fs::write(&tmp, body).await?;
+        tokio::fs::rename(&tmp, path).await?;
+        Ok(())
+    }
+
+    /// 读取检查点；文件不存在或版本不兼容时返回 None
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        let body = match std::fs::read(path) {
+            Ok(body) => body,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let body = state_crypto::open(&body).context("解密检查点失败")?;
+        let value: serde_json::Value = serde_json::from_slice(&body).context("检查点不是合法 JSON")?;
+        Self::from_value(value)
+    }
+
+    /// 写入已启用的存储后端；未启用时写盘到 path
+    pub async fn persist(&self, path: &Path) -> Result<()> {
+        match storage::get() {
+            Some(backend) => backend.put_state(STATE_NAME, &serde_json::to_value(self)?).await,
+            None => self.save(path).await,
+        }
+    }
+
+    /// 从已启用的存储后端读取；未启用时读盘
+    pub async fn load_persisted(path: &Path) -> Result<Option<Self>> {
+        match storage::get() {
+            Some(backend) => match backend.get_state(STATE_NAME).await? {
+                Some(value) => Self::from_value(value),
+                None => Ok(None),
+            },
+            None => Self::load(path),
+        }
+    }
+
+    /// 按版本解析；版本不兼容时返回 None
+    fn from_value(value: serde_json::Value) -> Result<Option<Self>> {
+        let version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+        match version {
+            CHECKPOINT_VERSION => Ok(Some(serde_json::from_value(value)?)),
+            other => {
+                warn!(
+                    version = other,
+                    expected = CHECKPOINT_VERSION,
+                    "检查点版本不兼容，忽略并从 API 重建状态"
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    /// 把检查点状态恢复到 RiskManager / PositionTracker / OrderStore
+    /// restore_exposure: 是否沿用敞口成本（仅同一窗口内重启时为 true）
+    pub fn restore_into(self, risk_manager: &RiskManager, order_store: &OrderStore, restore_exposure: bool) -> Result<()> {
+        let positions = decode_map(&self.positions)?;
+        let exposure_costs = if restore_exposure {
+            decode_map(&self.exposure_costs)?
+        } else {
+            HashMap::new()
+        };
+        let mut strategy_costs = HashMap::new();
+        if restore_exposure {
+            for (name, costs) in &self.strategy_costs {
+                match Strategy::parse(name) {
+                    Some(strategy) => {
+                        strategy_costs.insert(strategy, decode_map(costs)?);
+                    }
+                    None => warn!(strategy = %name, "检查点中的策略未知，忽略其敞口归属"),
+                }
+            }
+        }
+        let pairs = self
+            .pending_pairs
+            .into_iter()
+            .map(OrderPairRecord::into_pair)
+            .collect::<Result<Vec<_>>>()?;
+        let resting_orders = self
+            .resting_orders
+            .into_iter()
+            .map(RestingOrderRecord::into_order)
+            .collect::<Result<Vec<_>>>()?;
+
+        info!(
+            "♻️ 从检查点恢复风控状态 | 写盘时间:{} | 持仓:{} | 敞口条目:{} | 订单对:{} | 登记挂单:{}",
+            self.saved_at.format("%H:%M:%S"),
+            positions.len(),
+            exposure_costs.len(),
+            pairs.len(),
+            resting_orders.len()
+        );
+
+        risk_manager.position_tracker().restore(PositionSnapshot {
+            positions,
+            exposure_costs,
+            total_exposure: Decimal::ZERO,
+            strategy_costs,
+        });
+        risk_manager.restore_pending_pairs(pairs);
+        for order in resting_orders {
+            order_store.insert(order);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,190 @@
+//! 成对持仓处置策略：每个可 merge 的成对持仓在 merge 前比较两种处置的预期所得，自动选更好的一种——
+//! - merge：每对按 1 USDC 赎回，扣除 merge 交易的 gas（DISPOSAL_MERGE_GAS_USD，按整笔摊到该市场）；
+//! - 回卖：按当前两边买盘逐档吃单的所得，扣除卖出手续费（SELL_BACK_FEE_PER_SHARE）。
+//!
+//! 回卖多得 DISPOSAL_MIN_ADVANTAGE_USD 以上时两腿以 FAK 卖回订单簿，未卖出的剩余照常 merge；否则直接 merge。
+//! 每次决策（含两边预期所得）写入交易日志 disposal_decision。立即 Merge 与定时 Merge 都经过这里；
+//! 未启用或当前窗口没有该市场的订单簿时一律 merge。
+
+use dashmap::DashMap;
+use polymarket_client_sdk::clob::ws::types::response::BookUpdate;
+use polymarket_client_sdk::types::{Decimal, B256, U256};
+use rust_decimal_macros::dec;
+use serde_json::json;
+use std::sync::{Arc, OnceLock, RwLock};
+use tracing::{debug, info, warn};
+
+use super::positions::PositionTracker;
+use crate::market::rules;
+use crate::monitor::sell_back::{self, SellBackOpportunity};
+use crate::trading::TradingExecutor;
+use crate::utils::{journal, metrics};
+
+#[derive(Debug, Clone, Copy)]
+pub struct DisposalSettings {
+    /// 一笔 merge 交易的 gas 估计（USD）
+    pub merge_gas_usd: Decimal,
+    /// 回卖每份（每条腿）的手续费估计（USD）
+    pub fee_per_share: Decimal,
+    /// 回卖须比 merge 多得的最小金额（USD）
+    pub min_advantage: Decimal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Disposal {
+    Merge,
+    SellBack,
+}
+
+impl Disposal {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Disposal::Merge => "merge",
+            Disposal::SellBack => "sell_back",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct DisposalDecision {
+    choice: Disposal,
+    merge_proceeds: Decimal,
+    /// 回卖预期所得（扣手续费）；买盘深度不足以卖出全部数量时为 None
+    sell_proceeds: Option<Decimal>,
+    /// 卖出全部数量需要吃到的最低买价（FAK 限价）
+    yes_limit: Decimal,
+    no_limit: Decimal,
+}
+
+struct Engine {
+    settings: DisposalSettings,
+    executor: Arc<TradingExecutor>,
+    position_tracker: Arc<PositionTracker>,
+    /// 当前窗口的订单簿（窗口切换时更新）
+    books: RwLock<Option<Arc<DashMap<U256, BookUpdate>>>>,
+}
+
+static ENGINE: OnceLock<Engine> = OnceLock::new();
+
+/// 启用处置策略
+pub fn init(settings: DisposalSettings, executor: Arc<TradingExecutor>, position_tracker: Arc<PositionTracker>) {
+    let _ = ENGINE.set(Engine {
+        settings,
+        executor,
+        position_tracker,
+        books: RwLock::new(None),
+    });
+    info!(
+        "⚖️ 成对持仓处置策略已启用 | merge gas:{} USD | 回卖手续费:{}/份 | 回卖最小优势:{} USD",
+        settings.merge_gas_usd, settings.fee_per_share, settings.min_advantage
+    );
+}
+
+/// 换上新窗口的订单簿
+pub fn set_books(books: Arc<DashMap<U256, BookUpdate>>) {
+    let Some(engine) = ENGINE.get() else {
+        return;
+    };
+    if let Ok(mut guard) = engine.books.write() {
+        *guard = Some(books);
+    }
+}
+
+/// 从买一开始逐档卖出 size 份：返回 (所得, 最低成交价)；深度不足时为 None
+fn sell_value(book: &BookUpdate, size: Decimal) -> Option<(Decimal, Decimal)> {
+    let mut remaining = size;
+    let mut proceeds = dec!(0);
+    // bids 最后一个为买一价
+    for level in book.bids.iter().rev() {
+        let take = remaining.min(level.size);
+        proceeds += take * level.price;
+        remaining -= take;
+        if remaining <= dec!(0) {
+            return Some((proceeds, level.price));
+        }
+    }
+    None
+}
+
+impl Engine {
+    fn decide(&self, yes_token: U256, no_token: U256, size: Decimal) -> Option<DisposalDecision> {
+        let books = self.books.read().ok()?.clone()?;
+        let yes = sell_value(&books.get(&yes_token)?, size);
+        let no = sell_value(&books.get(&no_token)?, size);
+        let merge_proceeds = size - self.settings.merge_gas_usd;
+        let sell = yes.zip(no).map(|((yes_value, yes_limit), (no_value, no_limit))| {
+            (yes_value + no_value - self.settings.fee_per_share * dec!(2) * size, yes_limit, no_limit)
+        });
+        let choice = match sell {
+            Some((proceeds, _, _)) if proceeds - merge_proceeds > self.settings.min_advantage => Disposal::SellBack,
+            _ => Disposal::Merge,
+        };
+        Some(DisposalDecision {
+            choice,
+            merge_proceeds,
+            sell_proceeds: sell.map(|(p, _, _)| p),
+            yes_limit: sell.map_or(dec!(0), |(_, y, _)| y),
+            no_limit: sell.map_or(dec!(0), |(_, _, n)| n),
+        })
+    }
+}
+
+/// 为 condition_id 上 size 份成对持仓选择处置方式并执行回卖；返回 true 表示调用方应继续 merge
+/// （选择了 merge，或回卖后仍有剩余）
+pub async fn route(condition_id: B256, yes_token: U256, no_token: U256, size: Decimal) -> bool {
+    let Some(engine) = ENGINE.get() else {
+        return true;
+    };
+    // 回卖数量须对齐数量步长且不低于最小下单数量；对齐后的零头留给 merge
+    let Some(sell_size) = rules::order_size(&[yes_token, no_token], size) else {
+        return true;
+    };
+    let Some(decision) = engine.decide(yes_token, no_token, sell_size) else {
+        debug!("处置策略：当前窗口无该市场订单簿，直接 merge | condition_id={:#x}", condition_id);
+        return true;
+    };
+    info!(
+        "⚖️ 处置决策 | condition_id={:#x} | {}份 | merge:{:.4} | 回卖:{} | 选择:{}",
+        condition_id,
+        sell_size,
+        decision.merge_proceeds,
+        decision
+            .sell_proceeds
+            .map_or_else(|| "深度不足".to_string(), |p| format!("{:.4}", p)),
+        decision.choice.as_str()
+    );
+    metrics::incr(match decision.choice {
+        Disposal::Merge => "disposal_merge",
+        Disposal::SellBack => "disposal_sell_back",
+    });
+
+    let mut sold = dec!(0);
+    if decision.choice == Disposal::SellBack {
+        let opp = SellBackOpportunity {
+            market_id: condition_id,
+            yes_token_id: yes_token,
+            no_token_id: no_token,
+            yes_bid: decision.yes_limit,
+            no_bid: decision.no_limit,
+            size: sell_size,
+            edge: (decision.sell_proceeds.unwrap_or(dec!(0)) - decision.merge_proceeds) / sell_size,
+        };
+        let display = format!("{:#x}", condition_id);
+        match sell_back::execute(&engine.executor, &engine.position_tracker, &opp, &display).await {
+            Ok(fill) => sold = fill.yes_sold.min(fill.no_sold),
+            Err(e) => warn!(error = %e, "处置策略：回卖失败，改为 merge"),
+        }
+    }
+    journal::record(
+        "disposal_decision",
+        json!({
+            "condition_id": format!("{:#x}", condition_id),
+            "size": sell_size.to_string(),
+            "merge_proceeds": decision.merge_proceeds.to_string(),
+            "sell_proceeds": decision.sell_proceeds.map(|p| p.to_string()),
+            "choice": decision.choice.as_str(),
+            "sold": sold.to_string(),
+        }),
+    );
+    sold < size
+}
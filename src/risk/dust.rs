@@ -0,0 +1,208 @@
+//! 零头持仓清理：定期找出低于阈值的残余持仓（价格取整、部分成交留下的零头），
+//! 另一边也有持仓时顺带 merge 掉（merge 按两边较小值，零头一边清零），只剩单边时按买一价卖出，
+//! 使 PositionTracker 与链上余额保持干净。买一低于下限时不卖，留给结算赎回。
+
+use dashmap::DashMap;
+use polymarket_client_sdk::clob::ws::types::response::BookUpdate;
+use polymarket_client_sdk::types::{Address, Decimal, B256, U256};
+use rust_decimal_macros::dec;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+use poly_1hour_bot::merge;
+use poly_1hour_bot::positions::get_positions;
+
+use super::merge_ledger::MergeLedger;
+use super::merge_verify;
+use super::positions::PositionTracker;
+use crate::trading::TradingExecutor;
+use crate::utils::events::{self, BotEvent};
+use crate::utils::rate_limit::{self, Priority};
+use crate::utils::{journal, metrics};
+
+#[derive(Debug, Clone, Copy)]
+pub struct DustSettings {
+    /// 持仓低于该数量视为零头
+    pub threshold: Decimal,
+    /// 买一不低于该价格才卖出零头
+    pub min_bid: Decimal,
+}
+
+/// 某市场两边的持仓
+#[derive(Default)]
+struct MarketHoldings {
+    yes: Option<(U256, Decimal)>,
+    no: Option<(U256, Decimal)>,
+}
+
+pub struct DustSweeper {
+    executor: Arc<TradingExecutor>,
+    position_tracker: Arc<PositionTracker>,
+    merge_ledger: Arc<MergeLedger>,
+    /// 未设置代理地址时只卖出，不 merge
+    proxy: Option<Address>,
+    private_key: String,
+    settings: DustSettings,
+    /// 上一轮清理尚未结束时跳过本轮
+    in_flight: AtomicBool,
+}
+
+fn is_dust(size: Decimal, threshold: Decimal) -> bool {
+    size > dec!(0) && size < threshold
+}
+
+impl DustSweeper {
+    pub fn new(
+        executor: Arc<TradingExecutor>,
+        position_tracker: Arc<PositionTracker>,
+        merge_ledger: Arc<MergeLedger>,
+        proxy: Option<Address>,
+        private_key: String,
+        settings: DustSettings,
+    ) -> Self {
+        Self {
+            executor,
+            position_tracker,
+            merge_ledger,
+            proxy,
+            private_key,
+            settings,
+            in_flight: AtomicBool::new(false),
+        }
+    }
+
+    /// 取当前订单簿的买一快照后在后台执行一轮清理（不阻塞订单簿处理）
+    pub fn spawn_sweep(self: &Arc<Self>, books: &DashMap<U256, BookUpdate>, handle: &tokio::runtime::Handle) {
+        if self.in_flight.swap(true, Ordering::AcqRel) {
+            debug!("上一轮零头清理尚未结束，跳过");
+            return;
+        }
+        let best_bids: HashMap<U256, Decimal> = books
+            .iter()
+            .filter_map(|entry| entry.value().bids.last().map(|o| (*entry.key(), o.price)))
+            .collect();
+        let sweeper = self.clone();
+        handle.spawn(async move {
+            if let Err(e) = sweeper.sweep(&best_bids).await {
+                warn!(error = %e, "零头持仓清理失败");
+            }
+            sweeper.in_flight.store(false, Ordering::Release);
+        });
+    }
+
+    async fn sweep(&self, best_bids: &HashMap<U256, Decimal>) -> anyhow::Result<()> {
+        rate_limit::acquire(Priority::Low).await?;
+        let positions = get_positions().await?;
+        let mut markets: HashMap<B256, MarketHoldings> = HashMap::new();
+        for pos in positions.iter().filter(|p| p.size > dec!(0)) {
+            let holdings = markets.entry(pos.condition_id).or_default();
+            match pos.outcome_index {
+                0 => holdings.yes = Some((pos.asset, pos.size)),
+                1 => holdings.no = Some((pos.asset, pos.size)),
+                _ => {}
+            }
+        }
+
+        let threshold = self.settings.threshold;
+        let mut merge_candidates: Vec<B256> = Vec::new();
+        let mut merge_tokens: HashMap<B256, (U256, U256)> = HashMap::new();
+        let mut sells: Vec<(U256, Decimal, Decimal)> = Vec::new();
+        for (condition_id, holdings) in &markets {
+            match (holdings.yes, holdings.no) {
+                (Some((yes_token, yes)), Some((no_token, no))) => {
+                    if (is_dust(yes, threshold) || is_dust(no, threshold)) && self.proxy.is_some() {
+                        merge_candidates.push(*condition_id);
+                        merge_tokens.insert(*condition_id, (yes_token, no_token));
+                    }
+                }
+                (Some((token, size)), None) | (None, Some((token, size))) => {
+                    if !is_dust(size, threshold) {
+                        continue;
+                    }
+                    match best_bids.get(&token) {
+                        Some(bid) if *bid >= self.settings.min_bid => sells.push((token, size, *bid)),
+                        _ => debug!(token_id = %token, size = %size, "零头无可接受的买一，留待结算"),
+                    }
+                }
+                (None, None) => {}
+            }
+        }
+
+        if let (false, Some(proxy)) = (merge_candidates.is_empty(), self.proxy) {
+            self.merge_dust(&merge_candidates, &merge_tokens, proxy).await;
+        }
+        for (token, size, bid) in sells {
+            self.sell_dust(token, size, bid).await;
+        }
+        Ok(())
+    }
+
+    async fn merge_dust(&self, condition_ids: &[B256], tokens: &HashMap<B256, (U256, U256)>, proxy: Address) {
+        match merge::merge_max_batch(condition_ids, proxy, &self.private_key, None).await {
+            Ok((tx, merged)) => {
+                for (condition_id, merge_amt) in &merged {
+                    let Some((yes_token, no_token)) = tokens.get(condition_id) else {
+                        continue;
+                    };
+                    let merge_amt_decimal = Decimal::from(merge_amt.to::<u64>()) / dec!(1_000_000);
+                    self.position_tracker.update_exposure_cost(*yes_token, dec!(0), -merge_amt_decimal);
+                    self.position_tracker.update_exposure_cost(*no_token, dec!(0), -merge_amt_decimal);
+                    self.position_tracker.update_position(*yes_token, -merge_amt_decimal);
+                    self.position_tracker.update_position(*no_token, -merge_amt_decimal);
+                    info!(
+                        "🧹 零头清理：Merge 完成 | condition_id={:#x} | 数量:{} | tx={}",
+                        condition_id, merge_amt_decimal, tx
+                    );
+                    self.merge_ledger.attribute(*condition_id, merge_amt_decimal, &tx);
+                    metrics::incr("dust_merged");
+                }
+                events::publish(BotEvent::merged(&tx, &merged));
+                merge_verify::spawn(tx, proxy, merged);
+            }
+            Err(e) => {
+                // 零头清理是顺带的，失败不进重试队列，下一轮再试
+                debug!(error = %e, "零头清理：Merge 失败");
+            }
+        }
+    }
+
+    async fn sell_dust(&self, token: U256, size: Decimal, bid: Decimal) {
+        let size = size.round_dp_with_strategy(2, rust_decimal::RoundingStrategy::ToZero);
+        if size <= dec!(0) {
+            return;
+        }
+        match self.executor.sell_at_price(token, bid, size).await {
+            Ok(resp) if resp.success => {
+                let sold = resp.making_amount.min(size);
+                if sold > dec!(0) {
+                    self.position_tracker.update_position(token, -sold);
+                }
+                info!("🧹 零头清理：按买一卖出 | token_id={} | 数量:{} | 价格:{} | 已成交:{}", token, size, bid, sold);
+                metrics::incr("dust_sold");
+                journal::record(
+                    "dust_sold",
+                    json!({
+                        "token_id": token.to_string(),
+                        "size": size.to_string(),
+                        "price": bid.to_string(),
+                        "filled": sold.to_string(),
+                        "order_id": resp.order_id,
+                    }),
+                );
+            }
+            Ok(resp) => {
+                // 常见原因：低于市场最小下单量
+                debug!(
+                    token_id = %token,
+                    size = %size,
+                    reason = resp.error_msg.as_deref().unwrap_or(""),
+                    "零头清理：卖单被拒"
+                );
+            }
+            Err(e) => debug!(error = %e, token_id = %token, "零头清理：卖单提交失败"),
+        }
+    }
+}
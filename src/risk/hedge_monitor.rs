@@ -1,20 +1,29 @@
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use alloy::signers::Signer;
 use alloy::signers::local::LocalSigner;
 use dashmap::DashMap;
 use polymarket_client_sdk::clob::Client;
 use polymarket_client_sdk::clob::types::{OrderType, Side};
+use polymarket_client_sdk::clob::types::response::PostOrderResponse;
 use polymarket_client_sdk::clob::ws::types::response::BookUpdate;
 use polymarket_client_sdk::types::{Address, Decimal, U256};
 use polymarket_client_sdk::POLYGON;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal_macros::dec;
+use serde_json::json;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::sleep;
 use tracing::{debug, error, info, trace, warn};
 
 use super::positions::PositionTracker;
-use super::recovery::RecoveryAction;
+use super::recovery::{breakeven_complement_price, RecoveryAction};
+use crate::trading::credentials::SharedClient;
+use crate::trading::rejection;
+use crate::utils::rate_limit::{self, Priority};
+use crate::utils::{journal, metrics};
 
 #[derive(Debug, Clone)]
 pub struct HedgePosition {
@@ -30,20 +39,36 @@ pub struct HedgePosition {
     pub pending_sell_amount: Decimal, // 待卖出的数量
 }
 
+/// 最后一步吃单买入时的限价（FAK，按簿上卖价成交，未成交部分自动取消）
+const MARKET_BUY_PRICE: Decimal = dec!(0.99);
+
+/// 补齐缺失腿的时间阶梯：保本价挂单 passive → 每 improve_interval 提高 improve_tick →
+/// 市场结束前 market_order_before_end 吃单
+#[derive(Debug, Clone, Copy)]
+pub struct HedgeLadderParams {
+    pub passive: Duration,
+    pub improve_interval: Duration,
+    pub improve_tick: Decimal,
+    pub market_order_before_end: Duration,
+}
+
 pub struct HedgeMonitor {
-    client: Client<polymarket_client_sdk::auth::state::Authenticated<polymarket_client_sdk::auth::Normal>>,
+    client: SharedClient,
     private_key: String,
     proxy_address: Option<Address>,
     positions: DashMap<String, HedgePosition>, // pair_id -> position
     position_tracker: Arc<PositionTracker>, // 用于更新风险敞口
+    /// 补齐缺失腿的时间阶梯参数
+    ladder: HedgeLadderParams,
 }
 
 impl HedgeMonitor {
     pub fn new(
-        client: Client<polymarket_client_sdk::auth::state::Authenticated<polymarket_client_sdk::auth::Normal>>,
+        client: SharedClient,
         private_key: String,
         proxy_address: Option<Address>,
         position_tracker: Arc<PositionTracker>,
+        ladder: HedgeLadderParams,
     ) -> Self {
         Self {
             client,
@@ -51,6 +76,7 @@ impl HedgeMonitor {
             proxy_address,
             positions: DashMap::new(),
             position_tracker,
+            ladder,
         }
     }
 
@@ -94,10 +120,199 @@ impl HedgeMonitor {
             };
 
             self.positions.insert(pair_id.clone(), position);
+            metrics::incr("hedge_monitor_added");
+            journal::record(
+                "hedge_monitor_added",
+                json!({
+                    "pair_id": pair_id,
+                    "token_id": token_id.to_string(),
+                    "amount": amount.to_string(),
+                    "entry_price": entry_price.to_string(),
+                    "take_profit_price": take_profit_price.to_string(),
+                    "stop_loss_price": stop_loss_price.to_string(),
+                }),
+            );
         }
         Ok(())
     }
 
+    /// 是否有监测中的仓位（主循环据此决定是否把订单簿交给 check_and_execute）
+    pub fn has_positions(&self) -> bool {
+        !self.positions.is_empty()
+    }
+
+    /// 下单买入一腿（GTC 限价挂单或 FAK 吃单），返回提交结果
+    async fn post_buy(&self, token_id: U256, price: Decimal, size: Decimal, order_type: OrderType) -> Result<PostOrderResponse> {
+        let client = self.client.get();
+        let signer = LocalSigner::from_str(&self.private_key)?
+            .with_chain_id(Some(POLYGON));
+        let order = client
+            .limit_order()
+            .token_id(token_id)
+            .side(Side::Buy)
+            .price(price)
+            .size(size)
+            .order_type(order_type)
+            .build()
+            .await?;
+        let signed = client.sign(&signer, order).await?;
+        rate_limit::acquire(Priority::Critical).await?;
+        let result = client.post_order(signed).await?;
+        if !result.success {
+            let error_msg = result.error_msg.as_deref().unwrap_or("未知错误");
+            rejection::record(error_msg);
+            return Err(anyhow::anyhow!("补齐买单失败: {}", error_msg));
+        }
+        Ok(result)
+    }
+
+    /// 查询挂单已成交数量并撤单；撤单失败时返回错误（此时挂单可能仍在簿上，调用方不应再下新单）
+    async fn settle_resting_buy(&self, order_id: &str, immediate_filled: Decimal) -> Result<Decimal> {
+        let client = self.client.get();
+        let mut filled = immediate_filled;
+        rate_limit::acquire(Priority::Normal).await?;
+        if let Ok(order) = client.order(order_id).await {
+            filled = filled.max(order.size_matched);
+        }
+        rate_limit::acquire(Priority::Critical).await?;
+        client.cancel_orders(&[order_id]).await?;
+        // 撤单与成交之间可能有竞争，撤单后再查一次
+        rate_limit::acquire(Priority::Normal).await?;
+        if let Ok(order) = client.order(order_id).await {
+            filled = filled.max(order.size_matched);
+        }
+        Ok(filled)
+    }
+
+    /// 买入缺失的一腿（BuyMissing），按时间阶梯逐步提高价格：
+    /// 1. passive：以保本价（1 − 买入价 − 手续费）挂 GTC 限价单，等待 passive 时长；
+    /// 2. improve：仍未全部成交则撤单，价格提高 improve_tick（不超过 max_price）重新挂单，每 improve_interval 一次；
+    /// 3. market：到达市场结束前 market_order_before_end 的硬截止时间时撤单，以 FAK 吃单买入剩余部分。
+    /// 每一步写入交易日志。返回累计成交份数。敞口已在执行套利时按两腿成本计入，这里只按成交更新持仓
+    #[tracing::instrument(name = "buy_missing", skip_all)]
+    pub async fn buy_missing(&self, action: &RecoveryAction, market_end: DateTime<Utc>) -> Result<Decimal> {
+        let RecoveryAction::BuyMissing { token_id, amount, entry_price, max_price, pair_id } = action else {
+            return Ok(dec!(0));
+        };
+        let size = (*amount * dec!(100.0)).floor() / dec!(100.0);
+        if size <= dec!(0) {
+            return Ok(dec!(0));
+        }
+        let pair_short = &pair_id[..8.min(pair_id.len())];
+        let deadline = market_end
+            - chrono::Duration::from_std(self.ladder.market_order_before_end).unwrap_or_default();
+
+        let mut price = breakeven_complement_price(*entry_price).min(*max_price).max(dec!(0.01));
+        let mut step = "passive";
+        let mut wait = self.ladder.passive;
+        let mut total_filled = dec!(0);
+        let mut remaining = size;
+
+        while remaining > dec!(0) {
+            // 已到硬截止时间：以 FAK 吃单买入剩余部分后结束
+            if Utc::now() >= deadline {
+                step = "market";
+                price = MARKET_BUY_PRICE;
+            }
+            let order_type = if step == "market" { OrderType::FAK } else { OrderType::GTC };
+            let result = match self.post_buy(*token_id, price, remaining, order_type).await {
+                Ok(result) => result,
+                Err(e) => {
+                    metrics::incr("hedge_buy_failed");
+                    journal::record(
+                        "hedge_buy_failed",
+                        json!({
+                            "pair_id": pair_id,
+                            "token_id": token_id.to_string(),
+                            "step": step,
+                            "price": price.to_string(),
+                            "size": remaining.to_string(),
+                            "error": e.to_string(),
+                        }),
+                    );
+                    return Err(e);
+                }
+            };
+            let immediate = result.taking_amount;
+            metrics::incr("hedge_buy_submitted");
+            journal::record(
+                "hedge_buy_submitted",
+                json!({
+                    "pair_id": pair_id,
+                    "token_id": token_id.to_string(),
+                    "order_id": result.order_id,
+                    "step": step,
+                    "price": price.to_string(),
+                    "size": remaining.to_string(),
+                    "filled": immediate.to_string(),
+                    "deadline": deadline.to_rfc3339(),
+                }),
+            );
+            info!(
+                "🛡️ 补齐买单已提交 | 订单对:{} | 阶段:{} | 限价:{:.2} | 数量:{} | 立即成交:{}",
+                pair_short, step, price, remaining, immediate
+            );
+
+            // FAK 未成交部分已自动取消；GTC 等待到下一步或截止时间，再查询成交并撤单
+            let filled = if step == "market" || immediate >= remaining {
+                immediate
+            } else {
+                let until_deadline = (deadline - Utc::now()).to_std().unwrap_or(Duration::ZERO);
+                sleep(wait.min(until_deadline)).await;
+                match self.settle_resting_buy(&result.order_id, immediate).await {
+                    Ok(filled) => filled,
+                    Err(e) => {
+                        warn!(error = %e, "撤销补齐挂单失败，停止升级以免重复买入");
+                        journal::record(
+                            "hedge_buy_cancel_failed",
+                            json!({ "pair_id": pair_id, "order_id": result.order_id, "error": e.to_string() }),
+                        );
+                        total_filled += immediate;
+                        if immediate > dec!(0) {
+                            self.position_tracker.update_position(*token_id, immediate);
+                        }
+                        break;
+                    }
+                }
+            };
+
+            if filled > dec!(0) {
+                self.position_tracker.update_position(*token_id, filled);
+                metrics::incr("hedge_buy_filled");
+            }
+            total_filled += filled;
+            remaining -= filled;
+            journal::record(
+                "hedge_buy_step_done",
+                json!({
+                    "pair_id": pair_id,
+                    "step": step,
+                    "price": price.to_string(),
+                    "filled": filled.to_string(),
+                    "remaining": remaining.to_string(),
+                }),
+            );
+            if step == "market" {
+                break;
+            }
+            if remaining > dec!(0) {
+                // 进入改价阶段：每次提高一档，封顶 max_price（封顶后保持该价直到截止时间）
+                step = "improve";
+                wait = self.ladder.improve_interval;
+                price = (price + self.ladder.improve_tick).min(*max_price);
+                info!(
+                    "⏫ 补齐单未全部成交，提高限价 | 订单对:{} | 新限价:{:.2} | 剩余:{}",
+                    pair_short, price, remaining
+                );
+            }
+        }
+
+        if remaining > dec!(0) {
+            warn!("⚠️ 补齐缺失腿未全部成交 | 订单对:{} | 剩余:{}份", pair_short, remaining);
+        }
+        Ok(total_filled)
+    }
+
     /// 更新entry_price（从订单簿获取当前卖一价）
     pub fn update_entry_price(&self, pair_id: &str, entry_price: Decimal) {
         if let Some(mut pos) = self.positions.get_mut(pair_id) {
@@ -220,7 +435,7 @@ impl HedgeMonitor {
                 let pair_id_clone = pair_id.clone();
                 let position_tracker = self.position_tracker.clone();
                 let positions = self.positions.clone();
-                let client = self.client.clone();
+                let client = self.client.get();
                 let private_key = self.private_key.clone();
                 
                 // 先标记为正在处理，避免重复下单（使用remove+insert避免阻塞）
@@ -252,6 +467,18 @@ impl HedgeMonitor {
                         sell_amount,
                     ).await {
                         Ok((order_id, filled, remaining)) => {
+                            metrics::incr("hedge_sell_submitted");
+                            journal::record(
+                                "hedge_sell_submitted",
+                                json!({
+                                    "pair_id": pair_id_clone,
+                                    "token_id": position_clone.token_id.to_string(),
+                                    "order_id": order_id,
+                                    "price": best_bid_price.to_string(),
+                                    "size": sell_amount.to_string(),
+                                    "filled": filled.to_string(),
+                                }),
+                            );
                             // 更新仓位，标记已下订单（使用remove+insert避免get_mut阻塞）
                             let order_id_short = order_id[..16].to_string();
                             if let Some((_, mut pos)) = positions.remove(&pair_id_clone) {
@@ -303,6 +530,17 @@ impl HedgeMonitor {
                             }
                         }
                         Err(e) => {
+                            metrics::incr("hedge_sell_failed");
+                            journal::record(
+                                "hedge_sell_failed",
+                                json!({
+                                    "pair_id": pair_id_clone,
+                                    "token_id": position_clone.token_id.to_string(),
+                                    "price": best_bid_price.to_string(),
+                                    "size": sell_amount.to_string(),
+                                    "error": e.to_string(),
+                                }),
+                            );
                             error!(
                                 "❌ 卖出订单失败 | 市场:{} | 价格:{:.4} | 错误:{}",
                                 position_clone.market_display,
@@ -446,10 +684,12 @@ impl HedgeMonitor {
         let signed_order = client.sign(signer, sell_order).await?;
 
         // 提交订单
+        rate_limit::acquire(Priority::Critical).await?;
         let result = client.post_order(signed_order).await?;
 
         if !result.success {
             let error_msg = result.error_msg.as_deref().unwrap_or("未知错误");
+            rejection::record(error_msg);
             return Err(anyhow::anyhow!("GTC卖出订单失败: {}", error_msg));
         }
 
@@ -486,6 +726,7 @@ impl HedgeMonitor {
         price: Decimal,
         size: Option<Decimal>,
     ) -> Result<(String, Decimal, Decimal)> {
+        let client = self.client.get();
         let signer = LocalSigner::from_str(&self.private_key)?
             .with_chain_id(Some(POLYGON));
 
@@ -541,8 +782,7 @@ impl HedgeMonitor {
         );
 
         // 构建GTC卖出订单
-        let sell_order = self
-            .client
+        let sell_order = client
             .limit_order()
             .token_id(position.token_id)
             .side(Side::Sell)
@@ -553,13 +793,15 @@ impl HedgeMonitor {
             .await?;
 
         // 签名订单
-        let signed_order = self.client.sign(&signer, sell_order).await?;
+        let signed_order = client.sign(&signer, sell_order).await?;
 
         // 提交订单
-        let result = self.client.post_order(signed_order).await?;
+        rate_limit::acquire(Priority::Critical).await?;
+        let result = client.post_order(signed_order).await?;
 
         if !result.success {
             let error_msg = result.error_msg.as_deref().unwrap_or("未知错误");
+            rejection::record(error_msg);
             return Err(anyhow::anyhow!("GTC卖出订单失败: {}", error_msg));
         }
 
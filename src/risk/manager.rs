@@ -1,17 +1,26 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use dashmap::DashMap;
-use polymarket_client_sdk::clob::Client;
 use polymarket_client_sdk::types::{B256, Decimal, U256};
 use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use poly_1hour_bot::i18n::Msg;
+use poly_1hour_bot::tr;
 use tracing::{debug, error, info};
 
-use super::positions::PositionTracker;
+use super::approval::{self, Approval, Proposal, RiskLimits, Veto};
+use super::budget::Strategy;
+use super::merge_ledger::MergeLedger;
+use super::pnl::PnlTracker;
+use super::positions::{PositionTracker, ReserveRejection};
 use super::recovery::{RecoveryAction, RecoveryStrategy};
 use crate::config::Config as BotConfig;
-use crate::trading::executor::OrderPairResult;
+use crate::trading::credentials::SharedClient;
+use crate::trading::executor::{OrderPairResult, TradingExecutor};
+use crate::utils::events::{self, BotEvent};
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum PairStatus {
     Submitted,
     BothFilled,
@@ -21,45 +30,120 @@ pub enum PairStatus {
     Recovering,
 }
 
+impl PairStatus {
+    /// 按两腿下单数量与成交数量判定状态
+    fn from_fills(yes_size: Decimal, yes_filled: Decimal, no_size: Decimal, no_filled: Decimal) -> Self {
+        if yes_filled == yes_size && no_filled == no_size {
+            PairStatus::BothFilled
+        } else if yes_filled > dec!(0) && no_filled > dec!(0) {
+            PairStatus::PartiallyFilled
+        } else if yes_filled > dec!(0) || no_filled > dec!(0) {
+            PairStatus::OneFailed
+        } else {
+            PairStatus::BothFailed
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct OrderPair {
     pub pair_id: String,
     pub market_id: B256,
     pub yes_order_id: String,
     pub no_order_id: String,
+    /// 拆单时两腿的其余子单 ID（不含 yes_order_id / no_order_id）
+    pub child_order_ids: Vec<String>,
     pub yes_token_id: U256,
     pub no_token_id: U256,
     pub yes_size: Decimal,
     pub no_size: Decimal,
     pub yes_filled: Decimal,
     pub no_filled: Decimal,
+    /// 下单时的买入价格（卖一价），对冲时用于计算保本价与止盈止损
+    pub yes_price: Decimal,
+    pub no_price: Decimal,
     pub status: PairStatus,
     pub created_at: DateTime<Utc>,
 }
 
 pub struct RiskManager {
-    clob_client: Client<polymarket_client_sdk::auth::state::Authenticated<polymarket_client_sdk::auth::Normal>>,
+    clob_client: SharedClient,
     pending_pairs: DashMap<String, OrderPair>,
     position_tracker: std::sync::Arc<PositionTracker>,
-    recovery_strategy: RecoveryStrategy,
+    recovery_strategy: Box<dyn RecoveryStrategy>,
+    pnl: std::sync::Arc<PnlTracker>,
+    merge_ledger: std::sync::Arc<MergeLedger>,
+    limits: RiskLimits,
 }
 
 impl RiskManager {
     pub fn new(
-        clob_client: Client<polymarket_client_sdk::auth::state::Authenticated<polymarket_client_sdk::auth::Normal>>,
+        clob_client: SharedClient,
         config: &BotConfig,
+        recovery_strategy: Box<dyn RecoveryStrategy>,
     ) -> Self {
         Self {
             clob_client,
             pending_pairs: DashMap::new(),
             position_tracker: std::sync::Arc::new(PositionTracker::new(
                 Decimal::try_from(config.risk_max_exposure_usdc).unwrap_or(dec!(1000.0)),
+                config.risk_budgets,
             )),
-            recovery_strategy: RecoveryStrategy::new(
-                config.risk_imbalance_threshold,
-                config.hedge_take_profit_pct,
-                config.hedge_stop_loss_pct,
-            ),
+            recovery_strategy,
+            pnl: std::sync::Arc::new(PnlTracker::new(config.pol_usd_fallback)),
+            merge_ledger: std::sync::Arc::new(MergeLedger::new()),
+            limits: RiskLimits::from_config(config),
+        }
+    }
+
+    /// 吃单套利（成对 / 阶梯）下单前的风控检查（执行阈值、价格下限、临近结束、敞口上限与 taker 套利额度）；
+    /// 通过时敞口额度已预留
+    pub async fn approve(&self, proposal: &Proposal<'_>) -> std::result::Result<Approval, Veto> {
+        if let Some(veto) = approval::check_static(&self.limits, proposal) {
+            return Err(veto);
+        }
+        let opp = proposal.opportunity;
+        self.reserve_exposure(
+            Strategy::TakerArb,
+            opp.yes_ask_price * proposal.order_size,
+            opp.no_ask_price * proposal.order_size,
+        )
+        .await
+    }
+
+    /// 其余策略（maker 兜底、回卖、方向性）的风控检查：只检查并预留该策略额度与敞口额度
+    pub async fn approve_leg(&self, strategy: Strategy, cost: Decimal) -> std::result::Result<Approval, Veto> {
+        self.reserve_exposure(strategy, cost, dec!(0)).await
+    }
+
+    async fn reserve_exposure(
+        &self,
+        strategy: Strategy,
+        yes_cost: Decimal,
+        no_cost: Decimal,
+    ) -> std::result::Result<Approval, Veto> {
+        let current_exposure = self.position_tracker.calculate_exposure() + self.position_tracker.reserved_exposure();
+        let total_cost = yes_cost + no_cost;
+        match self.position_tracker.try_reserve_exposure(strategy, yes_cost, no_cost).await {
+            Ok(reservation) => Ok(Approval {
+                current_exposure,
+                total_cost,
+                reservation,
+            }),
+            Err(ReserveRejection::Budget) => {
+                let usage = self.position_tracker.budget_usage(strategy);
+                Err(Veto::StrategyBudget {
+                    strategy,
+                    current: usage.used(),
+                    cost: total_cost,
+                    max: usage.limit.unwrap_or(dec!(0)),
+                })
+            }
+            Err(ReserveRejection::Exposure) => Err(Veto::ExposureLimit {
+                current: current_exposure,
+                cost: total_cost,
+                max: self.position_tracker.max_exposure(),
+            }),
         }
     }
 
@@ -75,29 +159,28 @@ impl RiskManager {
         yes_price: Decimal,
         no_price: Decimal,
     ) {
-        let status = if result.yes_filled == result.yes_size && result.no_filled == result.no_size {
-            PairStatus::BothFilled
-        } else if result.yes_filled > dec!(0) && result.no_filled > dec!(0) {
-            PairStatus::PartiallyFilled
-        } else if result.yes_filled > dec!(0) && result.no_filled == dec!(0) {
-            PairStatus::OneFailed
-        } else if result.yes_filled == dec!(0) && result.no_filled > dec!(0) {
-            PairStatus::OneFailed
-        } else {
-            PairStatus::BothFailed
-        };
+        let status = PairStatus::from_fills(result.yes_size, result.yes_filled, result.no_size, result.no_filled);
 
+        let child_order_ids = result
+            .child_orders
+            .iter()
+            .map(|c| c.order_id.clone())
+            .filter(|id| *id != result.yes_order_id && *id != result.no_order_id)
+            .collect();
         let pair = OrderPair {
             pair_id: result.pair_id.clone(),
             market_id,
             yes_order_id: result.yes_order_id,
             no_order_id: result.no_order_id,
+            child_order_ids,
             yes_token_id: yes_token,
             no_token_id: no_token,
             yes_size: result.yes_size,
             no_size: result.no_size,
             yes_filled: result.yes_filled,
             no_filled: result.no_filled,
+            yes_price,
+            no_price,
             status: status.clone(),
             created_at: Utc::now(),
         };
@@ -105,6 +188,9 @@ impl RiskManager {
         // 更新持仓（敞口已在「执行套利」时按订单成本增加，此处不再按成交更新敞口）
         self.position_tracker.update_position(yes_token, pair.yes_filled);
         self.position_tracker.update_position(no_token, pair.no_filled);
+        self.pnl.record_fill(pair.yes_filled, pair.no_filled, yes_price, no_price);
+        self.merge_ledger
+            .record_fill(market_id, &pair.pair_id, pair.yes_filled, pair.no_filled, yes_price, no_price);
 
         // 这个日志已经在executor中打印了，这里不再重复打印
         debug!(
@@ -119,7 +205,85 @@ impl RiskManager {
         self.pending_pairs.insert(pair.pair_id.clone(), pair);
     }
 
+    /// 登记某一腿在下单之后才陆续成交的数量（如顺序执行留在簿上的第二腿）：filled 为该腿累计成交量，
+    /// 按新增部分更新订单对状态、持仓、锁定利润与 merge 账本
+    pub fn record_late_fill(&self, pair_id: &str, token_id: U256, filled: Decimal) {
+        let Some(mut pair) = self.pending_pairs.get_mut(pair_id) else {
+            return;
+        };
+        let matched_before = pair.yes_filled.min(pair.no_filled);
+        let (yes_delta, no_delta) = if token_id == pair.yes_token_id {
+            ((filled.min(pair.yes_size) - pair.yes_filled).max(dec!(0)), dec!(0))
+        } else if token_id == pair.no_token_id {
+            (dec!(0), (filled.min(pair.no_size) - pair.no_filled).max(dec!(0)))
+        } else {
+            return;
+        };
+        if yes_delta + no_delta <= dec!(0) {
+            return;
+        }
+        pair.yes_filled += yes_delta;
+        pair.no_filled += no_delta;
+        pair.status = PairStatus::from_fills(pair.yes_size, pair.yes_filled, pair.no_size, pair.no_filled);
+
+        self.position_tracker.update_position(token_id, yes_delta + no_delta);
+        let matched = pair.yes_filled.min(pair.no_filled) - matched_before;
+        self.pnl.record_fill(matched, matched, pair.yes_price, pair.no_price);
+        self.merge_ledger
+            .record_fill(pair.market_id, pair_id, yes_delta, no_delta, pair.yes_price, pair.no_price);
+        debug!(
+            pair_id = %pair_id,
+            status = ?pair.status,
+            yes_filled = %pair.yes_filled,
+            no_filled = %pair.no_filled,
+            "订单对补记成交"
+        );
+    }
+
+    /// 断线补查：订单簿流重连或持仓同步中断恢复后，断线期间仍挂在簿上的订单对腿可能已成交，
+    /// 经 REST 逐笔查询这些腿的累计成交量并经 record_late_fill 补记（按累计量计算增量，重复补查不会重复计入）。
+    /// 拆单子单不在此补查，由持仓同步校正。返回有新增成交的腿数
+    pub async fn backfill_fills(&self, executor: &TradingExecutor, reason: &str) -> usize {
+        let open_legs: Vec<(String, String, U256, Decimal)> = self
+            .pending_pairs
+            .iter()
+            .flat_map(|entry| {
+                let pair = entry.value();
+                [
+                    (&pair.yes_order_id, pair.yes_token_id, pair.yes_filled, pair.yes_size),
+                    (&pair.no_order_id, pair.no_token_id, pair.no_filled, pair.no_size),
+                ]
+                .into_iter()
+                .filter(|(order_id, _, filled, size)| !order_id.is_empty() && filled < size)
+                .map(|(order_id, token_id, filled, _)| (pair.pair_id.clone(), order_id.clone(), token_id, filled))
+                .collect::<Vec<_>>()
+            })
+            .collect();
+        if open_legs.is_empty() {
+            return 0;
+        }
+        let mut updated = 0;
+        for (pair_id, order_id, token_id, filled) in &open_legs {
+            match executor.order_matched(order_id).await {
+                Ok(matched) if matched > *filled => {
+                    self.record_late_fill(pair_id, *token_id, matched);
+                    updated += 1;
+                }
+                Ok(_) => {}
+                Err(e) => debug!(order_id = %order_id, error = %e, "补查订单成交失败"),
+            }
+        }
+        info!(
+            "🔁 断线补查成交 | 原因:{} | 未完全成交的腿:{} | 补记:{}",
+            reason,
+            open_legs.len(),
+            updated
+        );
+        updated
+    }
+
     /// 处理订单对并决定恢复策略
+    #[tracing::instrument(name = "handle_order_pair", skip(self))]
     pub async fn handle_order_pair(&self, pair_id: &str) -> Result<RecoveryAction> {
         let pair = self
             .pending_pairs
@@ -127,30 +291,94 @@ impl RiskManager {
             .ok_or_else(|| anyhow::anyhow!("订单对 {} 不存在", pair_id))?
             .clone();
 
-        match pair.status {
+        let action = match pair.status {
             PairStatus::BothFilled => {
-                info!(pair_id = %pair.pair_id, "两个订单都完全成交，无需恢复");
+                info!(pair_id = %pair.pair_id, "{}", tr!(Msg::RiskBothFilled));
                 Ok(RecoveryAction::None)
             }
             PairStatus::PartiallyFilled => {
                 self.recovery_strategy
                     .handle_partial_fill(&pair, &self.position_tracker)
-                    .await
             }
             PairStatus::OneFailed => {
                 self.recovery_strategy
                     .handle_one_sided_fill(&pair, &self.position_tracker)
-                    .await
             }
             PairStatus::BothFailed => {
-                error!(
-                    "❌ 套利失败 | YES和NO订单都未成交，可能原因：价格已变化或流动性不足"
-                );
+                error!("{}", tr!(Msg::RiskNoneFilled));
                 Ok(RecoveryAction::ManualIntervention {
                     reason: "两个订单都失败".to_string(),
                 })
             }
             _ => Ok(RecoveryAction::None),
+        }?;
+        if let Some(event) = BotEvent::recovery(pair_id, &action) {
+            events::publish(event);
+        }
+        Ok(action)
+    }
+
+    /// 未完结（部分成交/单边成交/恢复中）且双边持仓仍不相等的订单对
+    fn is_unresolved(&self, pair: &OrderPair) -> bool {
+        if !matches!(
+            pair.status,
+            PairStatus::PartiallyFilled | PairStatus::OneFailed | PairStatus::Recovering
+        ) {
+            return false;
+        }
+        let (yes_pos, no_pos) = self
+            .position_tracker
+            .get_pair_positions(pair.yes_token_id, pair.no_token_id);
+        (yes_pos - no_pos).abs() >= dec!(0.0001)
+    }
+
+    /// 跨窗口携带的订单对 ID：窗口切换后仍需继续恢复、平衡与 merge 的未完结订单对
+    pub fn unresolved_pair_ids(&self) -> Vec<String> {
+        self.pending_pairs
+            .iter()
+            .filter(|entry| self.is_unresolved(entry.value()))
+            .map(|entry| entry.key().clone())
+            .collect()
+    }
+
+    /// 未完结订单对所在的市场：condition_id -> (yes_token_id, no_token_id)
+    /// 窗口切换后与新窗口市场合并，使仓位平衡继续覆盖旧窗口的残留持仓
+    pub fn carried_markets(&self) -> HashMap<B256, (U256, U256)> {
+        self.pending_pairs
+            .iter()
+            .filter(|entry| self.is_unresolved(entry.value()))
+            .map(|entry| {
+                let pair = entry.value();
+                (pair.market_id, (pair.yes_token_id, pair.no_token_id))
+            })
+            .collect()
+    }
+
+    /// 清理已完结的订单对（双边成交、双边失败，或持仓已恢复平衡），返回清理数量
+    /// 已提交但尚无结果的订单对保留
+    pub fn prune_resolved_pairs(&self) -> usize {
+        let before = self.pending_pairs.len();
+        let resolved: Vec<String> = self
+            .pending_pairs
+            .iter()
+            .filter(|entry| entry.value().status != PairStatus::Submitted && !self.is_unresolved(entry.value()))
+            .map(|entry| entry.key().clone())
+            .collect();
+        for pair_id in resolved {
+            self.pending_pairs.remove(&pair_id);
+        }
+        before - self.pending_pairs.len()
+    }
+
+    /// 当前所有订单对的副本（用于检查点）
+    pub fn pending_pairs_snapshot(&self) -> Vec<OrderPair> {
+        self.pending_pairs.iter().map(|entry| entry.value().clone()).collect()
+    }
+
+    /// 从检查点恢复订单对（已存在的同 ID 订单对不覆盖）
+    pub fn restore_pending_pairs(&self, pairs: Vec<OrderPair>) {
+        for pair in pairs {
+            self.pending_pairs.entry(pair.pair_id.clone()).or_insert(pair);
         }
     }
 
@@ -158,4 +386,12 @@ impl RiskManager {
     pub fn position_tracker(&self) -> std::sync::Arc<PositionTracker> {
         self.position_tracker.clone()
     }
+
+    pub fn pnl(&self) -> std::sync::Arc<PnlTracker> {
+        self.pnl.clone()
+    }
+
+    pub fn merge_ledger(&self) -> std::sync::Arc<MergeLedger> {
+        self.merge_ledger.clone()
+    }
 }
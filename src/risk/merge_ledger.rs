@@ -0,0 +1,178 @@
+//! Merge 归属账本：按市场（condition_id）把各订单对的成交分别记入 YES / NO 两个先进先出队列，
+//! 不同订单对在同一市场的成交合并计算可 merge 数量（例如 A 对多出的 YES 与 B 对多出的 NO 也能配对）。
+//! 每次 merge 完成后按先进先出把合并数量分摊回各订单对，计算实际收益（merge 所得 1 USD/份 − 两腿成本），
+//! 并把逐订单对的归属写入交易日志，便于准确的盈亏统计。
+
+use polymarket_client_sdk::types::{Decimal, B256};
+use rust_decimal_macros::dec;
+use serde_json::json;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use tracing::info;
+
+use crate::utils::journal;
+
+/// 一笔未 merge 的成交
+#[derive(Debug, Clone)]
+struct Lot {
+    pair_id: String,
+    amount: Decimal,
+    price: Decimal,
+}
+
+#[derive(Debug, Default)]
+struct MarketLots {
+    yes: VecDeque<Lot>,
+    no: VecDeque<Lot>,
+}
+
+/// 某订单对在一次 merge 中被分摊的数量与成本
+#[derive(Debug, Clone)]
+pub struct PairShare {
+    pub pair_id: String,
+    pub yes_amount: Decimal,
+    pub no_amount: Decimal,
+    pub cost: Decimal,
+}
+
+#[derive(Default)]
+pub struct MergeLedger {
+    markets: Mutex<HashMap<B256, MarketLots>>,
+}
+
+/// 从队首取出 amount 份，返回 (订单对, 数量, 价格) 列表
+fn take(lots: &mut VecDeque<Lot>, mut amount: Decimal) -> Vec<Lot> {
+    let mut taken = Vec::new();
+    while amount > dec!(0) {
+        let Some(front) = lots.front_mut() else {
+            break;
+        };
+        let used = front.amount.min(amount);
+        taken.push(Lot {
+            pair_id: front.pair_id.clone(),
+            amount: used,
+            price: front.price,
+        });
+        front.amount -= used;
+        amount -= used;
+        if front.amount <= dec!(0) {
+            lots.pop_front();
+        }
+    }
+    taken
+}
+
+impl MergeLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一个订单对的成交
+    pub fn record_fill(
+        &self,
+        condition_id: B256,
+        pair_id: &str,
+        yes_filled: Decimal,
+        no_filled: Decimal,
+        yes_price: Decimal,
+        no_price: Decimal,
+    ) {
+        let Ok(mut markets) = self.markets.lock() else {
+            return;
+        };
+        let lots = markets.entry(condition_id).or_default();
+        if yes_filled > dec!(0) {
+            lots.yes.push_back(Lot { pair_id: pair_id.to_string(), amount: yes_filled, price: yes_price });
+        }
+        if no_filled > dec!(0) {
+            lots.no.push_back(Lot { pair_id: pair_id.to_string(), amount: no_filled, price: no_price });
+        }
+    }
+
+    /// 该市场跨订单对合并后的可 merge 数量：min(YES 合计, NO 合计)
+    pub fn mergeable(&self, condition_id: B256) -> Decimal {
+        self.markets
+            .lock()
+            .ok()
+            .and_then(|m| {
+                m.get(&condition_id).map(|lots| {
+                    let yes: Decimal = lots.yes.iter().map(|l| l.amount).sum();
+                    let no: Decimal = lots.no.iter().map(|l| l.amount).sum();
+                    yes.min(no)
+                })
+            })
+            .unwrap_or(dec!(0))
+    }
+
+    /// merge 完成后按先进先出分摊到各订单对，写入交易日志并返回分摊结果。
+    /// 账本中记录不足（例如重启前的持仓）时只分摊已记录的部分。
+    pub fn attribute(&self, condition_id: B256, merged: Decimal, tx: &str) -> Vec<PairShare> {
+        let Ok(mut markets) = self.markets.lock() else {
+            return Vec::new();
+        };
+        let Some(lots) = markets.get_mut(&condition_id) else {
+            return Vec::new();
+        };
+        let yes_taken = take(&mut lots.yes, merged);
+        let no_taken = take(&mut lots.no, merged);
+        if lots.yes.is_empty() && lots.no.is_empty() {
+            markets.remove(&condition_id);
+        }
+
+        let mut shares: Vec<PairShare> = Vec::new();
+        for (lot, is_yes) in yes_taken.iter().map(|l| (l, true)).chain(no_taken.iter().map(|l| (l, false))) {
+            let index = match shares.iter().position(|s| s.pair_id == lot.pair_id) {
+                Some(index) => index,
+                None => {
+                    shares.push(PairShare {
+                        pair_id: lot.pair_id.clone(),
+                        yes_amount: dec!(0),
+                        no_amount: dec!(0),
+                        cost: dec!(0),
+                    });
+                    shares.len() - 1
+                }
+            };
+            let share = &mut shares[index];
+            if is_yes {
+                share.yes_amount += lot.amount;
+            } else {
+                share.no_amount += lot.amount;
+            }
+            share.cost += lot.amount * lot.price;
+        }
+
+        let attributed = yes_taken.iter().map(|l| l.amount).sum::<Decimal>()
+            .min(no_taken.iter().map(|l| l.amount).sum::<Decimal>());
+        let cost: Decimal = shares.iter().map(|s| s.cost).sum();
+        let realized = attributed - cost;
+        if !shares.is_empty() {
+            info!(
+                "🧾 Merge 归属 | condition_id={:#x} | 数量:{} | 涉及订单对:{} | 成本:{:.4} | 实际收益:{:.4}",
+                condition_id,
+                merged,
+                shares.len(),
+                cost,
+                realized
+            );
+        }
+        journal::record(
+            "merge_attribution",
+            json!({
+                "condition_id": format!("{:#x}", condition_id),
+                "tx": tx,
+                "merged": merged.to_string(),
+                "attributed": attributed.to_string(),
+                "cost": cost.to_string(),
+                "realized": realized.to_string(),
+                "pairs": shares.iter().map(|s| json!({
+                    "pair_id": s.pair_id,
+                    "yes": s.yes_amount.to_string(),
+                    "no": s.no_amount.to_string(),
+                    "cost": s.cost.to_string(),
+                })).collect::<Vec<_>>(),
+            }),
+        );
+        shares
+    }
+}
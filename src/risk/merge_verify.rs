@@ -0,0 +1,75 @@
+//! Merge 到账核对：每笔 merge 交易确认后，从交易回执的 USDC Transfer 事件读取实际转入 proxy 的金额，
+//! 与合并份数（1 份 = 1 USDC）比较；不一致时告警并写入交易日志，及早发现适配器/合约路由错误导致的资金损失。
+//! 启动时由 MERGE_VERIFY_PROCEEDS 初始化；未初始化时 spawn 为空操作。
+
+use polymarket_client_sdk::types::{Address, B256, U256};
+use serde_json::json;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::{debug, error, warn};
+
+use poly_1hour_bot::merge;
+
+use crate::utils::{journal, metrics};
+
+/// 等待交易回执的超时
+static VERIFY_TIMEOUT: OnceLock<Duration> = OnceLock::new();
+
+/// 启用到账核对
+pub fn init(timeout: Duration) {
+    let _ = VERIFY_TIMEOUT.set(timeout);
+}
+
+/// 后台核对一次 merge 的到账金额（未启用时为空操作）
+pub fn spawn(tx: String, proxy: Address, merged: Vec<(B256, U256)>) {
+    let Some(timeout) = VERIFY_TIMEOUT.get().copied() else {
+        return;
+    };
+    let expected: U256 = merged.iter().fold(U256::ZERO, |sum, (_, amount)| sum + *amount);
+    if expected == U256::ZERO {
+        return;
+    }
+    tokio::spawn(async move {
+        let received = match merge::merge_proceeds(&tx, proxy, None, timeout).await {
+            Ok(received) => received,
+            Err(e) => {
+                warn!(error = %e, tx = %tx, "Merge 到账核对失败（无法读取回执），请人工确认");
+                metrics::incr("merge_proceeds_unverified");
+                return;
+            }
+        };
+        if received == expected {
+            debug!(tx = %tx, amount = %expected, "Merge 到账核对一致");
+            metrics::incr("merge_proceeds_verified");
+            return;
+        }
+        error!(
+            "🚨 Merge 到账金额不符 | tx={} | 合并:{} USDC | 实际到账:{} USDC | 请检查 Merge 路由与合约地址",
+            tx,
+            format_usdc(expected),
+            format_usdc(received)
+        );
+        metrics::incr("merge_proceeds_mismatch");
+        journal::record(
+            "merge_proceeds_mismatch",
+            json!({
+                "tx": tx,
+                "expected": format_usdc(expected),
+                "received": format_usdc(received),
+                "markets": merged
+                    .iter()
+                    .map(|(condition_id, amount)| json!({
+                        "condition_id": format!("{:#x}", condition_id),
+                        "amount": format_usdc(*amount),
+                    }))
+                    .collect::<Vec<_>>(),
+            }),
+        );
+    });
+}
+
+/// 6 位小数的最小单位转为 USDC 字符串
+fn format_usdc(amount: U256) -> String {
+    let units = amount.to::<u128>();
+    format!("{}.{:06}", units / 1_000_000, units % 1_000_000)
+}
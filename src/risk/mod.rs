@@ -1,9 +1,22 @@
+pub mod ab_test;
+pub mod aging;
+pub mod approval;
+pub mod budget;
+pub mod checkpoint;
+pub mod disposal;
+pub mod dust;
 pub mod hedge_monitor;
 pub mod manager;
+pub mod merge_ledger;
+pub mod merge_verify;
+pub mod pnl;
 pub mod position_balancer;
 pub mod positions;
 pub mod recovery;
+pub mod shared_ledger;
+pub mod transfer_listener;
 
+pub use checkpoint::RiskCheckpoint;
 pub use hedge_monitor::HedgeMonitor;
 pub use manager::RiskManager;
 pub use position_balancer::PositionBalancer;
\ No newline at end of file
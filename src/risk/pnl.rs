@@ -0,0 +1,215 @@
+//! 盈亏统计：累计已锁定的套利利润（双边成交部分的 1 − YES价 − NO价）与链上交易的 gas 成本（按 POL 价格折算 USD），
+//! 报告净利润，避免只看毛利而忽略链上成本。开启奖励统计时，已获得的流动性奖励也计入净利润。
+//!
+//! 同时按时间加权统计资金利用率：定期采样当前敞口（已投入资金）与敞口上限（RISK_MAX_EXPOSURE_USDC，其余视为闲置），
+//! 累计 USD·小时，报告利用率与每投入 1 USD·小时的净收益，便于判断敞口上限相对机会流量是否设得过高或过低。
+
+use polymarket_client_sdk::types::Decimal;
+use poly_1hour_bot::i18n::Msg;
+use poly_1hour_bot::merge::GasSpend;
+use poly_1hour_bot::tr;
+use rust_decimal_macros::dec;
+use serde_json::json;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+use crate::utils::journal;
+
+/// POL/USD 价格查询地址（CoinGecko simple price）
+const POL_PRICE_URL: &str = "https://api.coingecko.com/api/v3/simple/price?ids=polygon-ecosystem-token&vs_currencies=usd";
+
+#[derive(Debug, Default, Clone)]
+pub struct PnlSummary {
+    /// 双边成交部分锁定的毛利（USD，未扣手续费前按 1 − 两腿价格计算）
+    pub locked_profit_usd: Decimal,
+    /// 已成对的份数
+    pub matched_shares: Decimal,
+    /// 链上交易 gas 成本（USD）
+    pub gas_cost_usd: Decimal,
+    /// 链上交易 gas 成本（POL）
+    pub gas_cost_pol: f64,
+    pub gas_tx_count: u64,
+    /// 启动以来获得的流动性奖励（USD）
+    pub rewards_usd: Decimal,
+    /// 时间加权的已投入资金（敞口 × 小时）
+    pub deployed_usd_hours: Decimal,
+    /// 时间加权的可用资金（敞口上限 × 小时），其中未投入的部分为闲置
+    pub capital_usd_hours: Decimal,
+}
+
+impl PnlSummary {
+    /// 净利润 = 锁定毛利 + 流动性奖励 − gas 成本
+    pub fn net_usd(&self) -> Decimal {
+        self.locked_profit_usd + self.rewards_usd - self.gas_cost_usd
+    }
+
+    /// 资金利用率（%）= 已投入资金 / 可用资金（均按时间加权）
+    pub fn utilization_pct(&self) -> Option<Decimal> {
+        (self.capital_usd_hours > dec!(0)).then(|| self.deployed_usd_hours / self.capital_usd_hours * dec!(100))
+    }
+
+    /// 每投入 1 USD·小时的净收益（%）
+    pub fn roi_per_deployed_hour_pct(&self) -> Option<Decimal> {
+        (self.deployed_usd_hours > dec!(0)).then(|| self.net_usd() / self.deployed_usd_hours * dec!(100))
+    }
+}
+
+pub struct PnlTracker {
+    state: Mutex<PnlSummary>,
+    /// 上次资金利用率采样的时间与当时的 (敞口, 敞口上限)
+    last_sample: Mutex<Option<(Instant, Decimal, Decimal)>>,
+    http: reqwest::Client,
+    /// POL 价格查询失败时使用的价格（USD）
+    pol_usd_fallback: f64,
+}
+
+impl PnlTracker {
+    pub fn new(pol_usd_fallback: f64) -> Self {
+        Self {
+            state: Mutex::new(PnlSummary::default()),
+            last_sample: Mutex::new(None),
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            pol_usd_fallback,
+        }
+    }
+
+    /// 记录一笔成交：双边成交的较小数量视为成对，毛利 = 成对份数 × (1 − YES价 − NO价)
+    pub fn record_fill(&self, yes_filled: Decimal, no_filled: Decimal, yes_price: Decimal, no_price: Decimal) {
+        let matched = yes_filled.min(no_filled);
+        if matched <= dec!(0) {
+            return;
+        }
+        let profit = matched * (dec!(1.0) - yes_price - no_price);
+        if let Ok(mut state) = self.state.lock() {
+            state.matched_shares += matched;
+            state.locked_profit_usd += profit;
+        }
+    }
+
+    /// 采样当前敞口与敞口上限：上次采样以来的时长按上次的数值计入时间加权资金
+    pub fn sample_capital(&self, exposure: Decimal, max_exposure: Decimal) {
+        let now = Instant::now();
+        let Ok(mut last) = self.last_sample.lock() else {
+            return;
+        };
+        if let Some((at, deployed, capital)) = last.replace((now, exposure, max_exposure)) {
+            let hours = Decimal::try_from(now.duration_since(at).as_secs_f64() / 3600.0).unwrap_or_default();
+            if let Ok(mut state) = self.state.lock() {
+                state.deployed_usd_hours += deployed.max(dec!(0)) * hours;
+                // 敞口可能短暂超过上限，此时按敞口计，利用率不超过 100%
+                state.capital_usd_hours += capital.max(deployed).max(dec!(0)) * hours;
+            }
+        }
+    }
+
+    /// 查询 POL/USD 价格，失败时返回配置的兜底价格
+    async fn pol_usd_price(&self) -> f64 {
+        let fetched = async {
+            let body: serde_json::Value = self.http.get(POL_PRICE_URL).send().await?.json().await?;
+            body.pointer("/polygon-ecosystem-token/usd")
+                .and_then(|v| v.as_f64())
+                .ok_or_else(|| anyhow::anyhow!("响应缺少 POL 价格"))
+        }
+        .await;
+        match fetched {
+            Ok(price) => price,
+            Err(e) => {
+                warn!(error = %e, fallback = self.pol_usd_fallback, "获取 POL 价格失败，使用兜底价格");
+                self.pol_usd_fallback
+            }
+        }
+    }
+
+    /// 按当前 POL 价格折算并计入一批 gas 花费，每笔写入交易日志
+    pub async fn record_gas_spends(&self, spends: Vec<GasSpend>) {
+        if spends.is_empty() {
+            return;
+        }
+        let pol_usd = self.pol_usd_price().await;
+        for spend in spends {
+            let cost_pol = spend.cost_pol();
+            let cost_usd = Decimal::try_from(cost_pol * pol_usd).unwrap_or_default().round_dp(6);
+            info!(
+                "⛽ 链上交易 gas | {} | tx={} | gas:{} | {:.6} POL ≈ {:.4} USD",
+                spend.op, spend.tx_hash, spend.gas_used, cost_pol, cost_usd
+            );
+            journal::record(
+                "gas_spend",
+                json!({
+                    "op": spend.op,
+                    "tx_hash": spend.tx_hash,
+                    "gas_used": spend.gas_used,
+                    "effective_gas_price": spend.effective_gas_price.to_string(),
+                    "cost_pol": cost_pol,
+                    "pol_usd": pol_usd,
+                    "cost_usd": cost_usd.to_string(),
+                }),
+            );
+            if let Ok(mut state) = self.state.lock() {
+                state.gas_cost_usd += cost_usd;
+                state.gas_cost_pol += cost_pol;
+                state.gas_tx_count += 1;
+            }
+        }
+    }
+
+    /// 更新启动以来获得的流动性奖励合计
+    pub fn set_rewards(&self, rewards_usd: Decimal) {
+        if let Ok(mut state) = self.state.lock() {
+            state.rewards_usd = rewards_usd;
+        }
+    }
+
+    pub fn summary(&self) -> PnlSummary {
+        self.state.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    /// 打印盈亏汇总
+    pub fn log_summary(&self) {
+        let s = self.summary();
+        if s.matched_shares == dec!(0) && s.gas_tx_count == 0 && s.rewards_usd == dec!(0) {
+            debug!("盈亏汇总：暂无成交与链上交易");
+            return;
+        }
+        info!(
+            "{}",
+            tr!(
+                Msg::PnlSummary,
+                s.matched_shares,
+                s.locked_profit_usd,
+                s.gas_tx_count,
+                s.gas_cost_usd,
+                s.net_usd()
+            )
+        );
+        if s.rewards_usd > dec!(0) {
+            info!("🎁 已获得流动性奖励 {:.4} USD（已计入净利润）", s.rewards_usd);
+        }
+        if let (Some(utilization), Some(roi)) = (s.utilization_pct(), s.roi_per_deployed_hour_pct()) {
+            info!(
+                "📊 资金利用率 {:.2}% | 已投入 {:.2} USD·小时 / 可用 {:.2} USD·小时 | 每投入 1 USD·小时净收益 {:.4}%",
+                utilization, s.deployed_usd_hours, s.capital_usd_hours, roi
+            );
+        }
+        journal::record(
+            "pnl_summary",
+            json!({
+                "matched_shares": s.matched_shares.to_string(),
+                "locked_profit_usd": s.locked_profit_usd.to_string(),
+                "gas_cost_usd": s.gas_cost_usd.to_string(),
+                "gas_cost_pol": s.gas_cost_pol,
+                "gas_tx_count": s.gas_tx_count,
+                "rewards_usd": s.rewards_usd.to_string(),
+                "net_usd": s.net_usd().to_string(),
+                "deployed_usd_hours": s.deployed_usd_hours.round_dp(6).to_string(),
+                "capital_usd_hours": s.capital_usd_hours.round_dp(6).to_string(),
+                "utilization_pct": s.utilization_pct().map(|p| p.round_dp(4).to_string()),
+                "roi_per_deployed_hour_pct": s.roi_per_deployed_hour_pct().map(|p| p.round_dp(6).to_string()),
+            }),
+        );
+    }
+}
@@ -1,7 +1,6 @@
 //! 仓位平衡器：定时检查持仓和挂单，取消多余挂单以保持平衡
 
 use anyhow::Result;
-use polymarket_client_sdk::clob::Client;
 use polymarket_client_sdk::clob::types::request::OrdersRequest;
 use polymarket_client_sdk::clob::types::Side;
 use polymarket_client_sdk::types::{B256, Decimal, U256};
@@ -11,11 +10,13 @@ use tracing::{debug, error, info, warn};
 
 use super::positions::PositionTracker;
 use crate::config::Config as BotConfig;
+use crate::trading::credentials::SharedClient;
+use crate::utils::rate_limit::{self, Priority};
 use poly_1hour_bot::positions::get_positions;
 
 /// 仓位平衡器
 pub struct PositionBalancer {
-    clob_client: Client<polymarket_client_sdk::auth::state::Authenticated<polymarket_client_sdk::auth::Normal>>,
+    clob_client: SharedClient,
     position_tracker: std::sync::Arc<PositionTracker>,
     threshold: Decimal,
     min_total: Decimal,
@@ -24,7 +25,7 @@ pub struct PositionBalancer {
 
 impl PositionBalancer {
     pub fn new(
-        clob_client: Client<polymarket_client_sdk::auth::state::Authenticated<polymarket_client_sdk::auth::Normal>>,
+        clob_client: SharedClient,
         position_tracker: std::sync::Arc<PositionTracker>,
         config: &BotConfig,
     ) -> Self {
@@ -42,12 +43,13 @@ impl PositionBalancer {
         &self,
         market_map: &HashMap<B256, (U256, U256)>, // condition_id -> (yes_token_id, no_token_id)
     ) -> Result<()> {
+        let client = self.clob_client.get();
         // 获取所有活跃订单（处理分页）
         let mut all_orders = Vec::new();
         let mut cursor: Option<String> = None;
         loop {
-            let page = self
-                .clob_client
+            rate_limit::acquire(Priority::Low).await?;
+            let page = client
                 .orders(&OrdersRequest::default(), cursor)
                 .await?;
             
@@ -65,6 +67,7 @@ impl PositionBalancer {
         }
 
         // 获取持仓（从PositionTracker，已通过定时同步更新）
+        rate_limit::acquire(Priority::Low).await?;
         let positions = get_positions().await?;
 
         // 按市场分组订单和持仓
@@ -138,6 +141,7 @@ impl PositionBalancer {
 
     /// 平衡单个市场
     async fn balance_market(&self, data: &MarketBalanceData) -> Result<()> {
+        let client = self.clob_client.get();
         // 计算实际持仓差异
         let position_diff = (data.yes_position - data.no_position).abs();
 
@@ -178,7 +182,8 @@ impl PositionBalancer {
                     // 取消YES订单
                     if cancel_yes_count > 0 {
                         let yes_order_ids: Vec<&str> = cancel_yes_order_ids.iter().map(|s| s.as_str()).collect();
-                        if let Err(e) = self.clob_client.cancel_orders(&yes_order_ids).await {
+                        rate_limit::acquire(Priority::Critical).await?;
+                        if let Err(e) = client.cancel_orders(&yes_order_ids).await {
                             error!(error = %e, "❌ 取消YES订单失败");
                         } else {
                             info!("✅ 已取消 {} 个YES订单", cancel_yes_count);
@@ -203,7 +208,8 @@ impl PositionBalancer {
                         
                         if !cancel_no_order_ids.is_empty() {
                             let cancel_no_order_ids_ref: Vec<&str> = cancel_no_order_ids.iter().map(|s| s.as_str()).collect();
-                            if let Err(e) = self.clob_client.cancel_orders(&cancel_no_order_ids_ref).await {
+                            rate_limit::acquire(Priority::Critical).await?;
+                            if let Err(e) = client.cancel_orders(&cancel_no_order_ids_ref).await {
                                 error!(error = %e, "取消NO订单失败");
                             } else {
                                 info!("已取消 {} 个NO订单（累计 {} 份）", cancel_no_order_ids.len(), accumulated_size);
@@ -231,7 +237,8 @@ impl PositionBalancer {
                     // 取消NO订单
                     if cancel_no_count > 0 {
                         let no_order_ids: Vec<&str> = cancel_no_order_ids.iter().map(|s| s.as_str()).collect();
-                        if let Err(e) = self.clob_client.cancel_orders(&no_order_ids).await {
+                        rate_limit::acquire(Priority::Critical).await?;
+                        if let Err(e) = client.cancel_orders(&no_order_ids).await {
                             error!(error = %e, "取消NO订单失败");
                         } else {
                             info!("已取消 {} 个NO订单", cancel_no_count);
@@ -256,7 +263,8 @@ impl PositionBalancer {
                         
                         if !cancel_yes_order_ids.is_empty() {
                             let cancel_yes_order_ids_ref: Vec<&str> = cancel_yes_order_ids.iter().map(|s| s.as_str()).collect();
-                            if let Err(e) = self.clob_client.cancel_orders(&cancel_yes_order_ids_ref).await {
+                            rate_limit::acquire(Priority::Critical).await?;
+                            if let Err(e) = client.cancel_orders(&cancel_yes_order_ids_ref).await {
                                 error!(error = %e, "❌ 取消YES订单失败");
                             } else {
                                 info!("✅ 已取消 {} 个YES订单（累计 {} 份）", cancel_yes_order_ids.len(), accumulated_size);
@@ -293,7 +301,8 @@ impl PositionBalancer {
                 info!("⚠️ YES挂单过多，取消 {} 个YES订单", cancel_order_ids.len());
 
                 let cancel_order_ids_ref: Vec<&str> = cancel_order_ids.iter().map(|s| s.as_str()).collect();
-                if let Err(e) = self.clob_client.cancel_orders(&cancel_order_ids_ref).await {
+                rate_limit::acquire(Priority::Critical).await?;
+                if let Err(e) = client.cancel_orders(&cancel_order_ids_ref).await {
                     error!(error = %e, "❌ 取消YES订单失败");
                 } else {
                     info!("✅ 已取消 {} 个YES订单", cancel_order_ids.len());
@@ -321,7 +330,8 @@ impl PositionBalancer {
                 info!("NO挂单过多，取消 {} 个NO订单", cancel_order_ids.len());
 
                 let cancel_order_ids_ref: Vec<&str> = cancel_order_ids.iter().map(|s| s.as_str()).collect();
-                if let Err(e) = self.clob_client.cancel_orders(&cancel_order_ids_ref).await {
+                rate_limit::acquire(Priority::Critical).await?;
+                if let Err(e) = client.cancel_orders(&cancel_order_ids_ref).await {
                     error!(error = %e, "取消NO订单失败");
                 } else {
                     info!("已取消 {} 个NO订单", cancel_order_ids.len());
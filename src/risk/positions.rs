@@ -1,129 +1,337 @@
+//! 持仓与风险敞口跟踪：单写者任务 + 命令通道 + 只读快照。
+//!
+//! 所有修改（持仓增减、敞口成本增减、重置、API 同步覆盖）都以命令形式发送到唯一的写者任务，
+//! 由其按到达顺序串行应用，并在每条命令后发布新的只读快照。读取方只克隆快照的 Arc，
+//! 不持有任何锁，从根本上消除此前两张 DashMap 之间的锁顺序/死锁问题。
+//! 写入是异步的：命令通常在微秒级内生效，读取看到的是最近一次已应用命令后的状态。
+//!
+//! 敞口额度采用「预留 → 提交 / 归还」：审批时在锁内原子地检查「已记敞口 + 已预留 + 新成本」并预留，
+//! 下单后提交（转为敞口成本，写者任务发布新快照后才扣除预留，期间宁可重复计入也不漏算），
+//! 被拒或取消时归还（ExposureReservation 被丢弃即归还），并发的多个机会不会合计突破上限。
+//! 每笔预留归属一个策略（见 budget 模块），同时检查该策略的独立额度；提交的成本按策略分别记账，
+//! 随 merge / 卖出与全局成本按同一比例扣减。
+
 use anyhow::Result;
-use dashmap::DashMap;
-use polymarket_client_sdk::types::{Decimal, U256};
+use polymarket_client_sdk::types::{Address, Decimal, U256};
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal_macros::dec;
-use tracing::{debug, info, trace};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::sync::{mpsc, watch};
+use tracing::{debug, error, info, trace, warn};
 
 use poly_1hour_bot::positions::{get_positions, Position};
+use poly_1hour_bot::subgraph::SubgraphClient;
+
+use super::budget::{BudgetUsage, Strategy, StrategyBudgets};
+use super::shared_ledger::SharedExposureLedger;
+use crate::utils::rate_limit::{self, Priority};
+use crate::utils::{journal, metrics};
+
+/// 写者任务处理的修改命令
+#[derive(Debug)]
+enum PositionCommand {
+    /// 持仓增减（正数=买入，负数=卖出/merge）
+    UpdatePosition { token_id: U256, delta: Decimal },
+    /// 敞口成本增减：price 为买入价格，delta 为持仓变化量
+    UpdateExposureCost { token_id: U256, price: Decimal, delta: Decimal },
+    /// 新一轮开始，清空已结束市场的敞口（keep 中的 token 所属市场仍在交易，保留其敞口）
+    ResetExposure { keep: HashSet<U256> },
+    /// 用 API 同步结果整体替换持仓
+    ReplacePositions(HashMap<U256, Decimal>),
+    /// 用链上余额覆盖单个 token 的持仓
+    SetPosition { token_id: U256, size: Decimal },
+    /// 从检查点恢复持仓与敞口成本
+    Restore(PositionSnapshot),
+    /// 提交预留：按各腿 (token, 价格, 数量) 记入敞口成本（同时记入 strategy 的成本），快照发布后扣除 amount 的预留
+    CommitReservation { legs: Vec<(U256, Decimal, Decimal)>, amount: Decimal, strategy: Strategy },
+}
+
+/// 持仓与敞口的只读快照
+#[derive(Debug, Clone, Default)]
+pub struct PositionSnapshot {
+    /// token_id -> 数量（正数=持有多头，负数=持有空头）
+    pub positions: HashMap<U256, Decimal>,
+    /// token_id -> 成本（USD），用于跟踪风险敞口
+    pub exposure_costs: HashMap<U256, Decimal>,
+    /// exposure_costs 的总和，随每条命令维护，读取 O(1)
+    pub total_exposure: Decimal,
+    /// 策略 -> token_id -> 成本（USD）：exposure_costs 中经预留提交、归属各策略的部分
+    pub strategy_costs: HashMap<Strategy, HashMap<U256, Decimal>>,
+}
+
+impl PositionSnapshot {
+    /// 归属某策略的已提交敞口成本（USD）
+    pub fn strategy_exposure(&self, strategy: Strategy) -> Decimal {
+        self.strategy_costs
+            .get(&strategy)
+            .map(|costs| costs.values().copied().sum())
+            .unwrap_or(dec!(0))
+    }
+
+    /// 某 token 的成本由 before 变为 after 后，各策略归属的成本按同一比例缩减（after 为 0 时清除）
+    fn scale_strategy_costs(&mut self, token_id: U256, before: Decimal, after: Decimal) {
+        let ratio = if before > dec!(0) { after / before } else { dec!(0) };
+        for costs in self.strategy_costs.values_mut() {
+            if let Some(cost) = costs.get_mut(&token_id) {
+                *cost *= ratio;
+                if *cost < dec!(0.01) {
+                    costs.remove(&token_id);
+                }
+            }
+        }
+    }
+
+    fn apply(&mut self, command: PositionCommand) {
+        match command {
+            PositionCommand::UpdatePosition { token_id, delta } => {
+                let entry = self.positions.entry(token_id).or_insert(dec!(0));
+                *entry += delta;
+                trace!("update_position | token_id:{} | delta:{} | 新值:{}", token_id, delta, *entry);
+                // 持仓变为0或接近0时清理，并移除对应敞口成本
+                if entry.abs() < dec!(0.0001) {
+                    *entry = dec!(0);
+                    if let Some(cost) = self.exposure_costs.remove(&token_id) {
+                        self.total_exposure -= cost;
+                        self.scale_strategy_costs(token_id, cost, dec!(0));
+                    }
+                }
+            }
+            PositionCommand::UpdateExposureCost { token_id, price, delta } => {
+                if delta == dec!(0) {
+                    return; // 没有变化，不需要更新
+                }
+                let current_pos = self.positions.get(&token_id).copied().unwrap_or(dec!(0));
+                let entry = self.exposure_costs.entry(token_id).or_insert(dec!(0));
+                let cost_before = *entry;
+                if delta > dec!(0) {
+                    // 买入，增加风险敞口（成本 = 价格 * 数量）
+                    *entry += price * delta;
+                } else if current_pos > dec!(0) {
+                    // 卖出，按卖出比例减少成本
+                    let sell_amount = (-delta).min(current_pos);
+                    let reduction_ratio = sell_amount / current_pos;
+                    *entry = (*entry * (dec!(1) - reduction_ratio)).max(dec!(0));
+                } else {
+                    *entry = dec!(0);
+                }
+                trace!(
+                    "update_exposure_cost | token_id:{} | price:{} | delta:{} | 成本:{} -> {}",
+                    token_id, price, delta, cost_before, *entry
+                );
+                // 成本接近0时清理
+                let cost_after = if *entry < dec!(0.01) {
+                    self.exposure_costs.remove(&token_id);
+                    self.total_exposure -= cost_before;
+                    dec!(0)
+                } else {
+                    self.total_exposure += *entry - cost_before;
+                    *entry
+                };
+                // 卖出 / merge 时归属各策略的成本按同一比例扣减（买入只在提交预留时按策略记入）
+                if delta < dec!(0) {
+                    self.scale_strategy_costs(token_id, cost_before, cost_after);
+                }
+            }
+            PositionCommand::ResetExposure { keep } => {
+                self.exposure_costs.retain(|token_id, _| keep.contains(token_id));
+                self.total_exposure = self.exposure_costs.values().copied().sum();
+                for costs in self.strategy_costs.values_mut() {
+                    costs.retain(|token_id, _| keep.contains(token_id));
+                }
+            }
+            PositionCommand::ReplacePositions(positions) => {
+                self.positions = positions;
+            }
+            PositionCommand::SetPosition { token_id, size } => {
+                if size <= dec!(0) {
+                    self.positions.remove(&token_id);
+                    if let Some(cost) = self.exposure_costs.remove(&token_id) {
+                        self.total_exposure -= cost;
+                        self.scale_strategy_costs(token_id, cost, dec!(0));
+                    }
+                } else {
+                    self.positions.insert(token_id, size);
+                }
+            }
+            PositionCommand::Restore(mut snapshot) => {
+                snapshot.total_exposure = snapshot.exposure_costs.values().copied().sum();
+                *self = snapshot;
+            }
+            PositionCommand::CommitReservation { legs, strategy, .. } => {
+                for (token_id, price, delta) in legs {
+                    self.apply(PositionCommand::UpdateExposureCost { token_id, price, delta });
+                    if delta > dec!(0) && self.exposure_costs.contains_key(&token_id) {
+                        *self.strategy_costs.entry(strategy).or_default().entry(token_id).or_insert(dec!(0)) += price * delta;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 已预留的敞口额度：commit 转为敞口成本，release 或丢弃时归还
+#[must_use = "未提交的预留在丢弃时归还"]
+pub struct ExposureReservation {
+    amount: Decimal,
+    strategy: Strategy,
+    reserved: Arc<Mutex<Reserved>>,
+    commands: mpsc::UnboundedSender<PositionCommand>,
+    settled: bool,
+}
+
+impl ExposureReservation {
+    /// 下单已提交：按各腿 (token, 价格, 数量) 记入敞口成本
+    pub fn commit(mut self, legs: &[(U256, Decimal, Decimal)]) {
+        self.settled = true;
+        let command = PositionCommand::CommitReservation {
+            legs: legs.to_vec(),
+            amount: self.amount,
+            strategy: self.strategy,
+        };
+        if let Err(e) = self.commands.send(command) {
+            error!(command = ?e.0, "持仓写者任务已退出，预留提交被丢弃");
+            release_reserved(&self.reserved, self.strategy, self.amount);
+        }
+    }
+
+    /// 未下单（被拒或取消）：归还额度
+    pub fn release(self) {}
+}
+
+impl Drop for ExposureReservation {
+    fn drop(&mut self) {
+        if !self.settled {
+            release_reserved(&self.reserved, self.strategy, self.amount);
+            trace!(amount = %self.amount, strategy = self.strategy.as_str(), "敞口预留已归还");
+        }
+    }
+}
+
+/// 已预留、尚未提交的额度：total 计入全局上限（只含增加敞口的策略），by_strategy 计入各策略额度
+#[derive(Debug, Default)]
+struct Reserved {
+    total: Decimal,
+    by_strategy: HashMap<Strategy, Decimal>,
+}
+
+impl Reserved {
+    fn add(&mut self, strategy: Strategy, amount: Decimal) {
+        if strategy.adds_exposure() {
+            self.total += amount;
+        }
+        *self.by_strategy.entry(strategy).or_insert(dec!(0)) += amount;
+    }
+
+    fn strategy(&self, strategy: Strategy) -> Decimal {
+        self.by_strategy.get(&strategy).copied().unwrap_or(dec!(0))
+    }
+}
+
+fn release_reserved(reserved: &Mutex<Reserved>, strategy: Strategy, amount: Decimal) {
+    let mut reserved = reserved.lock().unwrap_or_else(|p| p.into_inner());
+    if strategy.adds_exposure() {
+        reserved.total = (reserved.total - amount).max(dec!(0));
+    }
+    if let Some(by_strategy) = reserved.by_strategy.get_mut(&strategy) {
+        *by_strategy = (*by_strategy - amount).max(dec!(0));
+    }
+}
+
+/// 预留被拒绝的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReserveRejection {
+    /// 超过全局上限（本实例或多实例共享额度）
+    Exposure,
+    /// 超过该策略的独立额度
+    Budget,
+}
 
 pub struct PositionTracker {
-    positions: DashMap<U256, Decimal>, // token_id -> 数量（正数=持有多头，负数=持有空头）
-    exposure_costs: DashMap<U256, Decimal>, // token_id -> 成本（USD），用于跟踪风险敞口
+    commands: mpsc::UnboundedSender<PositionCommand>,
+    snapshot: watch::Receiver<Arc<PositionSnapshot>>,
     max_exposure: Decimal,
+    /// 各策略的独立额度
+    budgets: StrategyBudgets,
+    /// 已预留、尚未提交的敞口额度；锁同时保证「检查 + 预留」原子执行
+    reserved: Arc<Mutex<Reserved>>,
+    /// 多实例共享敞口账本（未配置时只检查本实例限额）
+    shared_ledger: OnceLock<Arc<SharedExposureLedger>>,
 }
 
 impl PositionTracker {
-    pub fn new(max_exposure: Decimal) -> Self {
+    /// 创建跟踪器并启动写者任务（须在 tokio runtime 内调用）
+    pub fn new(max_exposure: Decimal, budgets: StrategyBudgets) -> Self {
+        let (commands, mut command_rx) = mpsc::unbounded_channel::<PositionCommand>();
+        let (snapshot_tx, snapshot) = watch::channel(Arc::new(PositionSnapshot::default()));
+        let reserved = Arc::new(Mutex::new(Reserved::default()));
+
+        let writer_reserved = reserved.clone();
+        tokio::spawn(async move {
+            let mut state = PositionSnapshot::default();
+            while let Some(command) = command_rx.recv().await {
+                let mut committed: Vec<(Strategy, Decimal)> = Vec::new();
+                let mut apply = |command: PositionCommand| {
+                    if let PositionCommand::CommitReservation { amount, strategy, .. } = &command {
+                        committed.push((*strategy, *amount));
+                    }
+                    state.apply(command);
+                };
+                apply(command);
+                // 合并已排队的命令后再发布一次快照，突发写入时避免逐条克隆
+                while let Ok(command) = command_rx.try_recv() {
+                    apply(command);
+                }
+                snapshot_tx.send_replace(Arc::new(state.clone()));
+                // 新快照已包含提交的成本，再扣除对应预留
+                for (strategy, amount) in committed {
+                    release_reserved(&writer_reserved, strategy, amount);
+                }
+            }
+            debug!("持仓写者任务退出（所有发送端已释放）");
+        });
+
         Self {
-            positions: DashMap::new(),
-            exposure_costs: DashMap::new(),
+            commands,
+            snapshot,
             max_exposure,
+            budgets,
+            reserved,
+            shared_ledger: OnceLock::new(),
         }
     }
 
-    pub fn update_position(&self, token_id: U256, delta: Decimal) {
-        trace!("update_position: 开始 | token_id:{} | delta:{}", token_id, delta);
-        
-        trace!("update_position: 准备获取positions写锁");
-        let mut entry = self.positions.entry(token_id).or_insert(dec!(0));
-        trace!("update_position: positions写锁已获取");
-        *entry += delta;
-        trace!("update_position: 持仓已更新，新值:{}", *entry);
-
-        // 如果持仓变为0或接近0，可以清理
-        // 关键修复：先释放 positions 的写锁，再访问 exposure_costs
-        // 这样可以避免与 update_exposure_cost 的死锁
-        let should_remove = entry.abs() < dec!(0.0001);
-        trace!("update_position: should_remove:{}", should_remove);
-        if should_remove {
-            *entry = dec!(0);
-            trace!("update_position: 持仓已清零");
-        }
-        // 释放 positions 的锁
-        drop(entry);
-        trace!("update_position: positions写锁已释放");
-        
-        // 现在可以安全地访问 exposure_costs
-        if should_remove {
-            trace!("update_position: 准备remove exposure_costs");
-            self.exposure_costs.remove(&token_id);
-            trace!("update_position: exposure_costs已remove");
+    fn send(&self, command: PositionCommand) {
+        if let Err(e) = self.commands.send(command) {
+            error!(command = ?e.0, "持仓写者任务已退出，命令被丢弃");
         }
-        
-        trace!("update_position: 完成");
+    }
+
+    /// 当前只读快照（克隆 Arc，不持锁）
+    pub fn snapshot(&self) -> Arc<PositionSnapshot> {
+        self.snapshot.borrow().clone()
+    }
+
+    /// 用检查点中的持仓与敞口成本覆盖当前状态（启动恢复时调用），总敞口按成本重新求和
+    pub fn restore(&self, snapshot: PositionSnapshot) {
+        self.send(PositionCommand::Restore(snapshot));
+    }
+
+    /// 用链上余额覆盖单个 token 的持仓（链上转账事件触发）
+    pub fn set_position(&self, token_id: U256, size: Decimal) {
+        self.send(PositionCommand::SetPosition { token_id, size });
+    }
+
+    pub fn update_position(&self, token_id: U256, delta: Decimal) {
+        self.send(PositionCommand::UpdatePosition { token_id, delta });
     }
 
     /// 更新风险敞口成本（USD）
     /// price: 买入价格
     /// delta: 持仓变化量（正数=买入，负数=卖出）
     pub fn update_exposure_cost(&self, token_id: U256, price: Decimal, delta: Decimal) {
-        trace!("update_exposure_cost: 开始 | token_id:{} | price:{} | delta:{}", token_id, price, delta);
-        
-        if delta == dec!(0) {
-            trace!("update_exposure_cost: delta为0，直接返回");
-            return; // 没有变化，不需要更新
-        }
-        
-        trace!("update_exposure_cost: 准备获取positions读锁");
-        // 关键修复：先获取 positions 的读锁，释放后再获取 exposure_costs 的写锁
-        // 这样可以避免与 update_position 的死锁（update_position 先获取 positions 写锁，再访问 exposure_costs）
-        let current_pos = if delta < dec!(0) {
-            trace!("update_exposure_cost: 卖出操作，开始获取positions读锁");
-            // 卖出时，需要先获取当前持仓来计算比例
-            let pos = self.positions.get(&token_id);
-            trace!("update_exposure_cost: positions读锁已获取");
-            let result = pos.map(|v| *v.value()).unwrap_or(dec!(0));
-            trace!("update_exposure_cost: positions读锁已释放，current_pos:{}", result);
-            result
-        } else {
-            trace!("update_exposure_cost: 买入操作，不需要获取positions");
-            dec!(0) // 买入时不需要
-        };
-        
-        trace!("update_exposure_cost: 准备获取exposure_costs写锁");
-        // 现在 positions 的锁已经释放，可以安全地获取 exposure_costs 的写锁
-        let mut entry = self.exposure_costs.entry(token_id).or_insert(dec!(0));
-        trace!("update_exposure_cost: exposure_costs写锁已获取");
-        
-        if delta > dec!(0) {
-            trace!("update_exposure_cost: 买入分支，计算cost_delta");
-            // 买入，增加风险敞口（成本 = 价格 * 数量）
-            let cost_delta = price * delta;
-            *entry += cost_delta;
-            trace!("update_exposure_cost: 买入完成，新成本:{}", *entry);
-        } else {
-            trace!("update_exposure_cost: 卖出分支，current_pos:{}", current_pos);
-            // 卖出，减少风险敞口（按比例减少）
-            if current_pos > dec!(0) {
-                trace!("update_exposure_cost: 计算卖出比例");
-                // 计算卖出的比例
-                let sell_amount = (-delta).min(current_pos);
-                let reduction_ratio = sell_amount / current_pos;
-                trace!("update_exposure_cost: sell_amount:{} | reduction_ratio:{} | 当前成本:{}", sell_amount, reduction_ratio, *entry);
-                // 按比例减少成本
-                *entry = (*entry * (dec!(1) - reduction_ratio)).max(dec!(0));
-                trace!("update_exposure_cost: 卖出完成，新成本:{}", *entry);
-            } else {
-                trace!("update_exposure_cost: current_pos为0，直接清零");
-                *entry = dec!(0);
-            }
-        }
-        
-        trace!("update_exposure_cost: 检查是否需要清理，当前成本:{}", *entry);
-        // 如果成本接近0，清理
-        if *entry < dec!(0.01) {
-            trace!("update_exposure_cost: 成本接近0，准备清理");
-            *entry = dec!(0);
-            drop(entry); // 显式释放写锁
-            trace!("update_exposure_cost: 写锁已释放，准备remove");
-            self.exposure_costs.remove(&token_id);
-            trace!("update_exposure_cost: remove完成");
-        } else {
-            trace!("update_exposure_cost: 成本不为0，保持entry");
-            drop(entry); // 显式释放写锁
-        }
-        
-        trace!("update_exposure_cost: 完成");
+        self.send(PositionCommand::UpdateExposureCost { token_id, price, delta });
     }
 
     /// 获取最大风险敞口限制
@@ -131,23 +339,26 @@ impl PositionTracker {
         self.max_exposure
     }
 
-    /// 重置风险敞口（新一轮开始时调用，清空成本缓存，使本轮从 0 敞口重新累计）
-    pub fn reset_exposure(&self) {
-        self.exposure_costs.clear();
-        info!("🔄 风险敞口已重置（新一轮）");
+    /// 重置风险敞口（新一轮开始时调用）：清空已结束市场的成本缓存，使本轮从这些市场 0 敞口重新累计；
+    /// keep 为仍在交易的市场（较长周期、跨越本次窗口切换）的 token，其敞口保留
+    pub fn reset_exposure(&self, keep: HashSet<U256>) {
+        let kept = keep.len();
+        self.send(PositionCommand::ResetExposure { keep });
+        info!("🔄 风险敞口已重置（新一轮） | 保留仍在交易市场的token数:{}", kept);
     }
 
     pub fn get_position(&self, token_id: U256) -> Decimal {
-        self.positions
+        self.snapshot
+            .borrow()
+            .positions
             .get(&token_id)
-            .map(|v| *v.value())
+            .copied()
             .unwrap_or(dec!(0))
     }
 
     /// 计算持仓不平衡度（0.0 = 完全平衡，1.0 = 完全不平衡）
     pub fn calculate_imbalance(&self, yes_token: U256, no_token: U256) -> Decimal {
-        let yes_pos = self.get_position(yes_token);
-        let no_pos = self.get_position(no_token);
+        let (yes_pos, no_pos) = self.get_pair_positions(yes_token, no_token);
 
         let total = yes_pos + no_pos;
         if total == dec!(0) {
@@ -155,64 +366,120 @@ impl PositionTracker {
         }
 
         // 不平衡度 = abs(yes - no) / (yes + no)
-        let imbalance = (yes_pos - no_pos).abs() / total;
-        imbalance
+        (yes_pos - no_pos).abs() / total
     }
 
     /// 计算当前总风险敞口（USD）
-    /// 基于所有持仓的成本总和
+    /// 基于所有持仓的成本总和；读取快照中维护的总和，O(1)
     pub fn calculate_exposure(&self) -> Decimal {
-        // 计算总风险敞口（所有持仓的成本总和）
-        // 使用 collect 先收集到 Vec，避免长时间持有锁
-        let costs: Vec<Decimal> = self.exposure_costs
-            .iter()
-            .map(|entry| *entry.value())
-            .collect();
-        costs.iter().sum()
+        self.snapshot.borrow().total_exposure
+    }
+
+    /// 已预留、尚未提交的敞口额度（USD）
+    pub fn reserved_exposure(&self) -> Decimal {
+        self.reserved.lock().unwrap_or_else(|p| p.into_inner()).total
+    }
+
+    /// 某策略的额度用量：已提交成本 + 已预留额度
+    pub fn budget_usage(&self, strategy: Strategy) -> BudgetUsage {
+        BudgetUsage {
+            strategy,
+            committed: self.snapshot.borrow().strategy_exposure(strategy),
+            reserved: self.reserved.lock().unwrap_or_else(|p| p.into_inner()).strategy(strategy),
+            limit: self.budgets.limit(strategy),
+        }
     }
 
     pub fn is_within_limits(&self) -> bool {
         self.calculate_exposure() <= self.max_exposure
     }
 
-    /// 检查如果执行新订单，是否会超过风险敞口限制
+    /// 检查如果执行新订单，是否会超过风险敞口限制（含已预留额度）
     /// yes_cost: YES订单的成本（价格 * 数量）
     /// no_cost: NO订单的成本（价格 * 数量）
     pub fn would_exceed_limit(&self, yes_cost: Decimal, no_cost: Decimal) -> bool {
-        let current_exposure = self.calculate_exposure();
+        let current_exposure = self.calculate_exposure() + self.reserved_exposure();
         let new_order_cost = yes_cost + no_cost;
         (current_exposure + new_order_cost) > self.max_exposure
     }
 
-    /// 获取YES和NO的持仓
+    /// 接入多实例共享敞口账本（启动时调用一次）
+    pub fn attach_shared_ledger(&self, ledger: Arc<SharedExposureLedger>) {
+        let _ = self.shared_ledger.set(ledger);
+    }
+
+    /// 下单前的敞口检查：在锁内原子地检查 strategy 的独立额度与本实例限额（已记敞口 + 已预留）并预留，
+    /// 配置了共享账本时再原子地检查并预留多实例总额度；不增加敞口的策略（回卖）只检查本策略额度。
+    /// 返回 Err 表示超限（全局或策略额度），不可下单
+    pub async fn try_reserve_exposure(
+        &self,
+        strategy: Strategy,
+        yes_cost: Decimal,
+        no_cost: Decimal,
+    ) -> std::result::Result<ExposureReservation, ReserveRejection> {
+        let amount = yes_cost + no_cost;
+        {
+            let mut reserved = self.reserved.lock().unwrap_or_else(|p| p.into_inner());
+            if let Some(limit) = self.budgets.limit(strategy) {
+                let used = self.snapshot.borrow().strategy_exposure(strategy) + reserved.strategy(strategy);
+                if used + amount > limit {
+                    return Err(ReserveRejection::Budget);
+                }
+            }
+            if strategy.adds_exposure() && self.calculate_exposure() + reserved.total + amount > self.max_exposure {
+                return Err(ReserveRejection::Exposure);
+            }
+            reserved.add(strategy, amount);
+        }
+        let reservation = ExposureReservation {
+            amount,
+            strategy,
+            reserved: self.reserved.clone(),
+            commands: self.commands.clone(),
+            settled: false,
+        };
+        match self.shared_ledger.get() {
+            // 共享账本拒绝时丢弃预留，归还本实例额度
+            Some(ledger) if strategy.adds_exposure() && !ledger.try_reserve(amount).await => Err(ReserveRejection::Exposure),
+            _ => {
+                metrics::add(strategy.reserved_metric(), (amount * dec!(100)).round().to_u64().unwrap_or(0));
+                Ok(reservation)
+            }
+        }
+    }
+
+    /// 获取YES和NO的持仓（同一快照内读取，保证一致）
     pub fn get_pair_positions(&self, yes_token: U256, no_token: U256) -> (Decimal, Decimal) {
-        (self.get_position(yes_token), self.get_position(no_token))
+        let snapshot = self.snapshot.borrow();
+        (
+            snapshot.positions.get(&yes_token).copied().unwrap_or(dec!(0)),
+            snapshot.positions.get(&no_token).copied().unwrap_or(dec!(0)),
+        )
     }
 
     /// 从 Data API 同步持仓，完全覆盖本地缓存
     /// 这个方法会从API获取最新持仓，清空并重建本地positions map
     /// 用于定时同步任务，确保本地缓存与链上实际持仓一致
     pub async fn sync_from_api(&self) -> Result<Vec<Position>> {
-        use std::collections::HashMap;
         use polymarket_client_sdk::types::B256;
         
+        rate_limit::acquire(Priority::Low).await?;
         let positions = get_positions().await?;
         
-        // 清空现有持仓（敞口仅由「执行套利」时增加、Merge 时扣减，不从 API 回填）
-        self.positions.clear();
-        
-        // 从API获取的持仓更新到本地缓存
+        // 从API获取的持仓整体替换本地持仓（敞口仅由「执行套利」时增加、Merge 时扣减，不从 API 回填）
         let mut updated_count = 0;
         let mut valid_positions = Vec::new();
+        let mut replacement = HashMap::new();
         
         for pos in positions {
             if pos.size > dec!(0) {
                 // Position.asset 就是 token_id
-                self.positions.insert(pos.asset, pos.size);
+                replacement.insert(pos.asset, pos.size);
                 valid_positions.push(pos);
                 updated_count += 1;
             }
         }
+        self.send(PositionCommand::ReplacePositions(replacement));
         
         // 按市场分组打印持仓
         if !valid_positions.is_empty() {
@@ -260,4 +527,38 @@ impl PositionTracker {
         
         Ok(valid_positions)
     }
+
+    /// 与子图按链上事件累计的余额对账：Data API 持仓与链上余额相差超过 0.01 份时告警并写入交易日志
+    pub async fn reconcile_with_subgraph(&self, positions: &[Position], subgraph: &SubgraphClient, user: Address) -> Result<usize> {
+        let on_chain = subgraph.user_balances(user).await?;
+        let api: HashMap<U256, Decimal> = positions.iter().map(|p| (p.asset, p.size)).collect();
+        let tokens: HashSet<U256> = api.keys().chain(on_chain.keys()).copied().collect();
+        let mut mismatches = 0;
+        for token in tokens {
+            let api_size = api.get(&token).copied().unwrap_or(dec!(0));
+            let chain_size = on_chain.get(&token).copied().unwrap_or(dec!(0));
+            if (api_size - chain_size).abs() <= dec!(0.01) {
+                continue;
+            }
+            mismatches += 1;
+            warn!(
+                "🔍 持仓对账不一致 | token_id={} | Data API:{} | 链上(子图):{}",
+                token, api_size, chain_size
+            );
+            journal::record(
+                "position_reconcile_mismatch",
+                json!({
+                    "token_id": token.to_string(),
+                    "api": api_size.to_string(),
+                    "on_chain": chain_size.to_string(),
+                }),
+            );
+        }
+        if mismatches > 0 {
+            metrics::add("position_reconcile_mismatch", mismatches as u64);
+        } else {
+            debug!("持仓对账一致（子图）");
+        }
+        Ok(mismatches)
+    }
 }
@@ -1,10 +1,45 @@
 use anyhow::Result;
+use poly_1hour_bot::i18n::Msg;
+use poly_1hour_bot::tr;
 use polymarket_client_sdk::types::{Decimal, U256};
 use rust_decimal_macros::dec;
-use tracing::debug;
+use serde_json::json;
+use tracing::{debug, info, warn};
 
 use super::manager::OrderPair;
 use super::positions::PositionTracker;
+use crate::config::Config as BotConfig;
+use crate::utils::{journal, metrics};
+
+/// 单边/不平衡成交后的对冲策略（HEDGE_POLICY）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HedgePolicy {
+    /// 不处理，持有到结算（默认，与关闭对冲时的行为一致）
+    Hold,
+    /// 买入缺失的一腿，补齐成对持仓（两腿总成本不超过 HEDGE_BUY_MAX_PAIR_COST）
+    BuyMissing,
+    /// 监测多出的一腿，达到止盈/止损时卖出（HEDGE_TAKE_PROFIT_PCT / HEDGE_STOP_LOSS_PCT）
+    SellExcess,
+}
+
+impl HedgePolicy {
+    /// 解析 hold / buy_missing / sell_excess，大小写不敏感，无效值为 Hold
+    pub fn parse(s: &str) -> Self {
+        match s.trim().to_lowercase().as_str() {
+            "buy_missing" | "buy" => HedgePolicy::BuyMissing,
+            "sell_excess" | "sell" => HedgePolicy::SellExcess,
+            _ => HedgePolicy::Hold,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HedgePolicy::Hold => "hold",
+            HedgePolicy::BuyMissing => "buy_missing",
+            HedgePolicy::SellExcess => "sell_excess",
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum RecoveryAction {
@@ -20,17 +55,94 @@ pub enum RecoveryAction {
         pair_id: String,
         market_display: String, // 市场显示名称（例如"btc预测市场"）
     },
+    /// 买入缺失的一腿：先以保本价限价挂单，未成交再升级到 max_price
+    BuyMissing {
+        token_id: U256,
+        amount: Decimal,
+        /// 已成交腿的买入价，用于计算保本价
+        entry_price: Decimal,
+        max_price: Decimal,
+        pair_id: String,
+    },
     ManualIntervention { reason: String },
 }
 
-pub struct RecoveryStrategy {
+/// 吃单手续费占成交份额的比例：0.25 × (p × (1 − p))²（与卖出时的手续费计算一致）
+pub fn taker_fee_fraction(price: Decimal) -> Decimal {
+    let base = price * (dec!(1.0) - price);
+    dec!(0.25) * base * base
+}
+
+/// 保本补齐价：已成交腿买入价为 entry 时，缺失腿最高可付的价格，使成对后扣除两腿手续费仍不亏损，
+/// 即 1 − entry − 两腿手续费，向下取整到 0.01
+pub fn breakeven_complement_price(entry: Decimal) -> Decimal {
+    let before_missing_fee = dec!(1.0) - entry - entry * taker_fee_fraction(entry);
+    let price = before_missing_fee / (dec!(1.0) + taker_fee_fraction(before_missing_fee));
+    (price * dec!(100.0)).floor() / dec!(100.0)
+}
+
+/// 恢复策略：订单对部分成交/单边成交后决定如何处理（返回 RecoveryAction，由主程序执行）。
+/// 默认实现为 PolicyRecovery（按 HEDGE_POLICY 处理）；自定义策略实现此 trait 后传给 RiskManager::new 即可替换，
+/// 例如总是退出、总是补齐或只告警
+pub trait RecoveryStrategy: Send + Sync {
+    /// 策略名称（用于日志）
+    fn name(&self) -> &str;
+
+    /// 双边都有成交但数量不平衡
+    fn handle_partial_fill(&self, pair: &OrderPair, position_tracker: &PositionTracker) -> Result<RecoveryAction>;
+
+    /// 只有一边成交
+    fn handle_one_sided_fill(&self, pair: &OrderPair, position_tracker: &PositionTracker) -> Result<RecoveryAction>;
+}
+
+/// 按 RECOVERY_STRATEGY 构造恢复策略：policy（默认，按 HEDGE_POLICY）| alert_only（只告警，不自动处理）
+pub fn from_config(config: &BotConfig) -> Box<dyn RecoveryStrategy> {
+    match config.recovery_strategy.trim().to_lowercase().as_str() {
+        "alert_only" | "alert" => Box::new(AlertOnlyRecovery::new(config.risk_imbalance_threshold)),
+        _ => Box::new(PolicyRecovery::new(
+            config.risk_imbalance_threshold,
+            config.hedge_take_profit_pct,
+            config.hedge_stop_loss_pct,
+            config.hedge_policy,
+            config.hedge_buy_max_pair_cost,
+            config.inventory_exit_enabled.then_some(config.inventory_exit_min_profit_pct),
+        )),
+    }
+}
+
+/// 不平衡比例：|YES成交 - NO成交| / 总成交
+fn imbalance_ratio(pair: &OrderPair) -> (Decimal, Decimal) {
+    let imbalance = (pair.yes_filled - pair.no_filled).abs();
+    let total_filled = pair.yes_filled + pair.no_filled;
+    let ratio = if total_filled > dec!(0) {
+        imbalance / total_filled
+    } else {
+        dec!(0)
+    };
+    (imbalance, ratio)
+}
+
+/// 默认恢复策略：按 HedgePolicy 持有、买入缺失腿或监测多出腿止盈止损
+pub struct PolicyRecovery {
     imbalance_threshold: Decimal,
     take_profit_pct: Decimal, // 止盈百分比
     stop_loss_pct: Decimal,   // 止损百分比
+    policy: HedgePolicy,
+    /// 买入缺失腿时两腿总成本上限（每份）
+    buy_max_pair_cost: Decimal,
+    /// hold 策略下的库存退出：买一高于买入价该比例时卖出多出腿（None=持有到结算）
+    inventory_exit_pct: Option<Decimal>,
 }
 
-impl RecoveryStrategy {
-    pub fn new(imbalance_threshold: f64, take_profit_pct: f64, stop_loss_pct: f64) -> Self {
+impl PolicyRecovery {
+    pub fn new(
+        imbalance_threshold: f64,
+        take_profit_pct: f64,
+        stop_loss_pct: f64,
+        policy: HedgePolicy,
+        buy_max_pair_cost: f64,
+        inventory_exit_pct: Option<f64>,
+    ) -> Self {
         Self {
             imbalance_threshold: Decimal::try_from(imbalance_threshold)
                 .unwrap_or(dec!(0.1)),
@@ -38,127 +150,206 @@ impl RecoveryStrategy {
                 .unwrap_or(dec!(0.05)), // 默认5%止盈
             stop_loss_pct: Decimal::try_from(stop_loss_pct)
                 .unwrap_or(dec!(0.05)), // 默认5%止损
+            policy,
+            buy_max_pair_cost: Decimal::try_from(buy_max_pair_cost).unwrap_or(dec!(1.0)),
+            inventory_exit_pct: inventory_exit_pct.map(|p| Decimal::try_from(p).unwrap_or(dec!(0.02))),
         }
     }
 
-    /// 处理部分成交（GTC订单的情况）
-    /// 对冲策略已暂时关闭，部分成交不平衡不做任何处理
-    pub async fn handle_partial_fill(
-        &self,
-        pair: &OrderPair,
-        _position_tracker: &PositionTracker,
-    ) -> Result<RecoveryAction> {
-        // 计算不平衡数量
-        let imbalance = (pair.yes_filled - pair.no_filled).abs();
-        let total_filled = pair.yes_filled + pair.no_filled;
-
-        // 计算不平衡比例
-        let imbalance_ratio = if total_filled > dec!(0) {
-            imbalance / total_filled
+    /// 按对冲策略决定如何处理多出的 excess 份（多出的一侧由双边成交量比较得出）
+    fn decide(&self, pair: &OrderPair, excess: Decimal) -> RecoveryAction {
+        let yes_heavy = pair.yes_filled > pair.no_filled;
+        let (side, filled_token, filled_price, missing_token) = if yes_heavy {
+            ("YES", pair.yes_token_id, pair.yes_price, pair.no_token_id)
         } else {
-            dec!(0)
+            ("NO", pair.no_token_id, pair.no_price, pair.yes_token_id)
         };
 
-        // 对冲策略已关闭，部分成交不平衡不做任何处理
-        if imbalance_ratio > self.imbalance_threshold {
-            let (side, amount) = if pair.yes_filled > pair.no_filled {
-                // YES成交多
-                ("YES", pair.yes_filled - pair.no_filled)
-            } else {
-                // NO成交多
-                ("NO", pair.no_filled - pair.yes_filled)
-            };
-
-            debug!(
-                pair_id = %pair.pair_id,
-                side = side,
-                imbalance_amount = %amount,
-                imbalance_ratio = %imbalance_ratio,
-                "部分成交不平衡，对冲已关，不处理"
-            );
-        }
+        let action = match self.policy {
+            HedgePolicy::Hold => match self.inventory_exit_pct {
+                // 库存退出：交给对冲监测，只设止盈（买一高于买入价时卖出多出腿），不设止损，否则持有到结算
+                Some(exit_pct) => {
+                    info!(
+                        "📦 单边成交 | {} 多 {} 份 | 持有，买一达到买入价 +{}% 时卖出",
+                        side,
+                        excess,
+                        exit_pct * dec!(100)
+                    );
+                    metrics::incr("inventory_exit_armed");
+                    RecoveryAction::MonitorForExit {
+                        token_id: filled_token,
+                        opposite_token_id: missing_token,
+                        amount: excess,
+                        entry_price: filled_price,
+                        take_profit_pct: exit_pct,
+                        stop_loss_pct: dec!(1),
+                        pair_id: pair.pair_id.clone(),
+                        market_display: "未知市场".to_string(), // 占位符，由主程序按市场信息填充
+                    }
+                }
+                None => {
+                    debug!("单边成交 | {} 多 {} 份 | 对冲策略 hold，持有到结算", side, excess);
+                    RecoveryAction::None
+                }
+            },
+            HedgePolicy::BuyMissing => {
+                // 缺失腿限价 = 两腿总成本上限 - 已成交腿买入价，最高 0.99
+                let max_price = (self.buy_max_pair_cost - filled_price).min(dec!(0.99)).round_dp(2);
+                if max_price < dec!(0.01) {
+                    warn!(
+                        pair_id = %pair.pair_id,
+                        filled_price = %filled_price,
+                        "缺失腿可接受价格过低，无法买入补齐"
+                    );
+                    RecoveryAction::ManualIntervention {
+                        reason: format!("{} 单边 {} 份，买入价 {} 过高，无法在成本上限内补齐", side, excess, filled_price),
+                    }
+                } else {
+                    info!(
+                        "{}",
+                        tr!(Msg::HedgeBuyMissing, side, excess, breakeven_complement_price(filled_price), max_price)
+                    );
+                    RecoveryAction::BuyMissing {
+                        token_id: missing_token,
+                        amount: excess,
+                        entry_price: filled_price,
+                        max_price,
+                        pair_id: pair.pair_id.clone(),
+                    }
+                }
+            }
+            HedgePolicy::SellExcess => {
+                info!("{}", tr!(Msg::HedgeMonitorExit, side, excess));
+                RecoveryAction::MonitorForExit {
+                    token_id: filled_token,
+                    opposite_token_id: missing_token,
+                    amount: excess,
+                    entry_price: filled_price,
+                    take_profit_pct: self.take_profit_pct,
+                    stop_loss_pct: self.stop_loss_pct,
+                    pair_id: pair.pair_id.clone(),
+                    market_display: "未知市场".to_string(), // 占位符，由主程序按市场信息填充
+                }
+            }
+        };
+
+        metrics::incr(match &action {
+            RecoveryAction::BuyMissing { .. } => "hedge_buy_missing",
+            RecoveryAction::MonitorForExit { .. } => "hedge_sell_excess",
+            RecoveryAction::ManualIntervention { .. } => "hedge_manual",
+            _ => "hedge_hold",
+        });
+        journal::record(
+            "hedge_decision",
+            json!({
+                "pair_id": pair.pair_id,
+                "policy": self.policy.as_str(),
+                "excess_side": side,
+                "excess": excess.to_string(),
+                "filled_price": filled_price.to_string(),
+                "action": format!("{:?}", action),
+            }),
+        );
+        action
+    }
+}
 
-        // 返回None，不做任何对冲处理
-        Ok(RecoveryAction::None)
-        
-        // 旧代码：如果不平衡超过阈值，需要对冲
-        // if imbalance_ratio > self.imbalance_threshold {
-        //     let (token_to_sell, amount) = if pair.yes_filled > pair.no_filled {
-        //         // YES成交多，卖出多余的YES
-        //         (pair.yes_token_id, pair.yes_filled - pair.no_filled)
-        //     } else {
-        //         // NO成交多，卖出多余的NO
-        //         (pair.no_token_id, pair.no_filled - pair.yes_filled)
-        //     };
-        // 
-        //     info!(
-        //         pair_id = %pair.pair_id,
-        //         token_id = %token_to_sell,
-        //         amount = %amount,
-        //         imbalance_ratio = %imbalance_ratio,
-        //         "部分成交不平衡，执行对冲"
-        //     );
-        // 
-        //     return Ok(RecoveryAction::SellExcess {
-        //         token_id: token_to_sell.to_string(),
-        //         amount,
-        //     });
-        // }
-        // 
-        // // 不平衡在可接受范围内
-        // Ok(RecoveryAction::None)
+impl RecoveryStrategy for PolicyRecovery {
+    fn name(&self) -> &str {
+        self.policy.as_str()
     }
 
-    /// 处理只购买一边成功（GTC订单的情况）
-    /// 对冲策略已暂时关闭，单边成交不做任何处理
-    pub async fn handle_one_sided_fill(
-        &self,
-        pair: &OrderPair,
-        _position_tracker: &PositionTracker,
-    ) -> Result<RecoveryAction> {
-        // 确定哪个订单成功，哪个失败
-        let (side, filled_amount) =
-            if pair.yes_filled > dec!(0) && pair.no_filled == dec!(0) {
-                // YES成功，NO失败（可能还在挂单）
-                ("YES", pair.yes_filled)
-            } else if pair.no_filled > dec!(0) && pair.yes_filled == dec!(0) {
-                // NO成功，YES失败（可能还在挂单）
-                ("NO", pair.no_filled)
-            } else {
-                return Ok(RecoveryAction::None);
-            };
-
-        // 对冲策略已关闭，单边成交不做任何处理（详情由 executor 的 ⚠️ 单边成交 已记录）
+    /// 处理部分成交（GTC订单的情况）：不平衡比例超过阈值时按对冲策略处理多出的部分
+    fn handle_partial_fill(&self, pair: &OrderPair, _position_tracker: &PositionTracker) -> Result<RecoveryAction> {
+        let (imbalance, imbalance_ratio) = imbalance_ratio(pair);
+        if imbalance_ratio <= self.imbalance_threshold {
+            // 不平衡在可接受范围内
+            return Ok(RecoveryAction::None);
+        }
+
         debug!(
-            "单边成交 | {} 成交 {} 份 | 对冲已关，不处理",
-            side, filled_amount
+            pair_id = %pair.pair_id,
+            imbalance_amount = %imbalance,
+            imbalance_ratio = %imbalance_ratio,
+            policy = self.policy.as_str(),
+            "部分成交不平衡"
         );
+        Ok(self.decide(pair, imbalance))
+    }
+
+    /// 处理只购买一边成功（GTC订单的情况）：按对冲策略处理已成交的一腿
+    fn handle_one_sided_fill(&self, pair: &OrderPair, _position_tracker: &PositionTracker) -> Result<RecoveryAction> {
+        let filled_amount = if pair.yes_filled > dec!(0) && pair.no_filled == dec!(0) {
+            // YES成功，NO失败（可能还在挂单）
+            pair.yes_filled
+        } else if pair.no_filled > dec!(0) && pair.yes_filled == dec!(0) {
+            // NO成功，YES失败（可能还在挂单）
+            pair.no_filled
+        } else {
+            return Ok(RecoveryAction::None);
+        };
+
+        Ok(self.decide(pair, filled_amount))
+    }
+}
+
+/// 只告警策略：不平衡超过阈值或单边成交时只返回 ManualIntervention（由主程序打印告警），不自动下单
+pub struct AlertOnlyRecovery {
+    imbalance_threshold: Decimal,
+}
 
-        // 返回None，不做任何对冲处理
-        Ok(RecoveryAction::None)
-        
-        // 旧代码：对冲策略：监测买一价，达到止盈止损时卖出
-        // // 确定对立边的token_id
-        // let success_token = if pair.yes_filled > dec!(0) {
-        //     pair.yes_token_id
-        // } else {
-        //     pair.no_token_id
-        // };
-        // let opposite_token = if success_token == pair.yes_token_id {
-        //     pair.no_token_id
-        // } else {
-        //     pair.yes_token_id
-        // };
-        // 
-        // Ok(RecoveryAction::MonitorForExit {
-        //     token_id: success_token,
-        //     opposite_token_id: opposite_token,
-        //     amount: filled_amount,
-        //     entry_price: dec!(0), // 占位符，需要在主程序中从订单簿获取
-        //     take_profit_pct: self.take_profit_pct,
-        //     stop_loss_pct: self.stop_loss_pct,
-        //     pair_id: pair.pair_id.clone(),
-        //     market_display: "未知市场".to_string(), // 占位符，需要在主程序中从市场信息获取
-        // })
+impl AlertOnlyRecovery {
+    pub fn new(imbalance_threshold: f64) -> Self {
+        Self {
+            imbalance_threshold: Decimal::try_from(imbalance_threshold).unwrap_or(dec!(0.1)),
+        }
+    }
+
+    fn alert(&self, pair: &OrderPair, reason: String) -> RecoveryAction {
+        metrics::incr("hedge_manual");
+        journal::record(
+            "hedge_decision",
+            json!({
+                "pair_id": pair.pair_id,
+                "policy": self.name(),
+                "yes_filled": pair.yes_filled.to_string(),
+                "no_filled": pair.no_filled.to_string(),
+                "action": "ManualIntervention",
+            }),
+        );
+        RecoveryAction::ManualIntervention { reason }
+    }
+}
+
+impl RecoveryStrategy for AlertOnlyRecovery {
+    fn name(&self) -> &str {
+        "alert_only"
+    }
+
+    fn handle_partial_fill(&self, pair: &OrderPair, _position_tracker: &PositionTracker) -> Result<RecoveryAction> {
+        let (imbalance, imbalance_ratio) = imbalance_ratio(pair);
+        if imbalance_ratio <= self.imbalance_threshold {
+            return Ok(RecoveryAction::None);
+        }
+        Ok(self.alert(
+            pair,
+            format!(
+                "订单对 {} 部分成交不平衡 {} 份（YES:{} NO:{}），需人工处理",
+                pair.pair_id, imbalance, pair.yes_filled, pair.no_filled
+            ),
+        ))
+    }
+
+    fn handle_one_sided_fill(&self, pair: &OrderPair, _position_tracker: &PositionTracker) -> Result<RecoveryAction> {
+        if (pair.yes_filled > dec!(0)) == (pair.no_filled > dec!(0)) {
+            return Ok(RecoveryAction::None);
+        }
+        Ok(self.alert(
+            pair,
+            format!(
+                "订单对 {} 单边成交（YES:{} NO:{}），需人工处理",
+                pair.pair_id, pair.yes_filled, pair.no_filled
+            ),
+        ))
     }
 }
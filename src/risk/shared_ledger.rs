@@ -0,0 +1,143 @@
+//! 多实例共享敞口账本：多个实例（不同币种或周期）使用同一钱包时，各实例把自己的敞口写入 Redis 哈希
+//! （字段 = 实例 ID，值 = 敞口 USD 与过期时间），下单前用 Lua 脚本原子地「汇总其他实例 + 本实例 + 新订单成本，
+//! 不超过总限额才把成本记到本实例名下」，保证跨进程的总敞口上限。
+//!
+//! 本实例的字段每秒用本地 PositionTracker 的敞口（含已预留未提交的额度）覆盖一次（merge、收尾、新一轮重置都会随之下降），
+//! 并带过期时间：实例退出或失联后其敞口在数秒后不再计入。Redis 不可用时拒绝新订单（宁可少做也不超限）。
+
+use anyhow::{Context, Result};
+use polymarket_client_sdk::types::Decimal;
+use redis::aio::MultiplexedConnection;
+use redis::Script;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use super::positions::PositionTracker;
+use crate::utils::metrics;
+
+/// 本实例敞口的同步间隔
+const SYNC_INTERVAL: Duration = Duration::from_secs(1);
+/// 字段过期时间：超过该时长未同步的实例不再计入总敞口
+const ENTRY_TTL: Duration = Duration::from_secs(5);
+/// 下单前检查的超时，超时按拒绝处理
+const RESERVE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// KEYS[1]=哈希键；ARGV: 实例 ID, 新增成本, 总限额, 当前毫秒, 过期毫秒数
+/// 字段值格式 "敞口|过期毫秒"；返回 {是否通过, 预留前的总敞口}
+const RESERVE_SCRIPT: &str = r#"
+local entries = redis.call('HGETALL', KEYS[1])
+local now = tonumber(ARGV[4])
+local total = 0
+local mine = 0
+for i = 1, #entries, 2 do
+    local sep = string.find(entries[i + 1], '|', 1, true)
+    local amount = tonumber(string.sub(entries[i + 1], 1, sep - 1))
+    local expires = tonumber(string.sub(entries[i + 1], sep + 1))
+    if entries[i] == ARGV[1] then
+        mine = amount
+        total = total + amount
+    elseif expires > now then
+        total = total + amount
+    else
+        redis.call('HDEL', KEYS[1], entries[i])
+    end
+end
+local cost = tonumber(ARGV[2])
+if total + cost > tonumber(ARGV[3]) then
+    return {0, tostring(total)}
+end
+redis.call('HSET', KEYS[1], ARGV[1], tostring(mine + cost) .. '|' .. tostring(now + tonumber(ARGV[5])))
+return {1, tostring(total)}
+"#;
+
+pub struct SharedExposureLedger {
+    conn: MultiplexedConnection,
+    key: String,
+    instance_id: String,
+    /// 所有实例合计的敞口上限（USD）
+    max_total: Decimal,
+    script: Script,
+}
+
+impl SharedExposureLedger {
+    pub async fn connect(url: &str, key: &str, instance_id: &str, max_total: Decimal) -> Result<Self> {
+        let client = redis::Client::open(url).context("共享账本 Redis URL 无效")?;
+        let conn = client
+            .get_multiplexed_async_connection()
+            .await
+            .context("连接共享账本 Redis 失败")?;
+        info!("🤝 已连接共享敞口账本 | 键:{} | 实例:{} | 总限额:{} USD", key, instance_id, max_total);
+        Ok(Self {
+            conn,
+            key: key.to_string(),
+            instance_id: instance_id.to_string(),
+            max_total,
+            script: Script::new(RESERVE_SCRIPT),
+        })
+    }
+
+    fn now_ms() -> i64 {
+        chrono::Utc::now().timestamp_millis()
+    }
+
+    /// 原子检查并预留：所有实例敞口 + cost 不超过总限额时记到本实例名下并返回 true；Redis 出错或超时返回 false
+    pub async fn try_reserve(&self, cost: Decimal) -> bool {
+        let mut conn = self.conn.clone();
+        let mut invocation = self.script.key(&self.key);
+        invocation
+            .arg(&self.instance_id)
+            .arg(cost.to_string())
+            .arg(self.max_total.to_string())
+            .arg(Self::now_ms())
+            .arg(ENTRY_TTL.as_millis() as i64);
+        let result = tokio::time::timeout(RESERVE_TIMEOUT, invocation.invoke_async::<(i64, String)>(&mut conn)).await;
+        match result {
+            Ok(Ok((1, _))) => true,
+            Ok(Ok((_, total))) => {
+                debug!(total = %total, cost = %cost, "共享账本：多实例总敞口将超限");
+                metrics::incr("shared_ledger_rejected");
+                false
+            }
+            Ok(Err(e)) => {
+                warn!(error = %e, "共享账本检查失败，拒绝本次下单");
+                metrics::incr("shared_ledger_error");
+                false
+            }
+            Err(_) => {
+                warn!("共享账本检查超时，拒绝本次下单");
+                metrics::incr("shared_ledger_error");
+                false
+            }
+        }
+    }
+
+    /// 用本地敞口覆盖本实例的字段并续期
+    async fn publish(&self, exposure: Decimal) -> Result<()> {
+        let mut conn = self.conn.clone();
+        let value = format!("{}|{}", exposure, Self::now_ms() + ENTRY_TTL.as_millis() as i64);
+        redis::cmd("HSET")
+            .arg(&self.key)
+            .arg(&self.instance_id)
+            .arg(value)
+            .query_async::<i64>(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    /// 启动本实例敞口的周期同步任务
+    pub fn spawn_sync(self: &Arc<Self>, position_tracker: Arc<PositionTracker>) {
+        let ledger = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(SYNC_INTERVAL);
+            loop {
+                ticker.tick().await;
+                let exposure = position_tracker.calculate_exposure() + position_tracker.reserved_exposure();
+                if let Err(e) = ledger.publish(exposure).await {
+                    debug!(error = %e, "同步本实例敞口到共享账本失败");
+                    metrics::incr("shared_ledger_error");
+                }
+            }
+        });
+    }
+}
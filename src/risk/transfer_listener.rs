@@ -0,0 +1,150 @@
+//! 链上 ERC-1155 转账监听：通过 WS RPC 订阅 CTF 合约中与代理钱包相关的 TransferSingle / TransferBatch 事件，
+//! 收到后读取涉及 token 的链上余额并覆盖 PositionTracker 中的持仓，作为成交与结算的独立数据源，
+//! 弥补 CLOB 接口漏报或延迟的成交（撮合上链、merge、赎回都会产生转账）。连接断开后按退避重连。
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use alloy::primitives::{Address, U256};
+use alloy::providers::{Provider, ProviderBuilder, WsConnect};
+use alloy::rpc::types::{Filter, Log};
+use alloy::sol;
+use alloy::sol_types::SolEvent;
+use anyhow::{Context, Result};
+use futures::StreamExt;
+use polymarket_client_sdk::types::Decimal;
+use polymarket_client_sdk::{contract_config, POLYGON};
+use rust_decimal_macros::dec;
+use serde_json::json;
+use tokio::time::sleep;
+use tracing::{debug, info, warn};
+
+use super::positions::PositionTracker;
+use crate::utils::{journal, metrics};
+
+sol! {
+    #[sol(rpc)]
+    interface IConditionalTokens {
+        event TransferSingle(address indexed operator, address indexed from, address indexed to, uint256 id, uint256 value);
+        event TransferBatch(address indexed operator, address indexed from, address indexed to, uint256[] ids, uint256[] values);
+        function balanceOf(address account, uint256 id) external view returns (uint256);
+    }
+}
+
+/// 重连退避上限
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// 一条转账日志涉及的 (token, 数量, 是否转入)
+fn transfers_of(log: &Log, proxy: Address) -> Vec<(U256, U256, bool)> {
+    if let Ok(single) = log.log_decode::<IConditionalTokens::TransferSingle>() {
+        let event = &single.inner.data;
+        return vec![(event.id, event.value, event.to == proxy)];
+    }
+    if let Ok(batch) = log.log_decode::<IConditionalTokens::TransferBatch>() {
+        let event = &batch.inner.data;
+        let incoming = event.to == proxy;
+        return event
+            .ids
+            .iter()
+            .zip(event.values.iter())
+            .map(|(id, value)| (*id, *value, incoming))
+            .collect();
+    }
+    Vec::new()
+}
+
+fn shares(raw: U256) -> Decimal {
+    Decimal::from(raw.to::<u128>()) / dec!(1_000_000)
+}
+
+/// 启动监听任务（ws_url 为空时不启动）
+pub fn spawn(ws_url: String, proxy: Address, position_tracker: Arc<PositionTracker>, handle: &tokio::runtime::Handle) {
+    if ws_url.trim().is_empty() {
+        return;
+    }
+    handle.spawn(async move {
+        let mut backoff = Duration::from_secs(1);
+        loop {
+            match listen(&ws_url, proxy, &position_tracker).await {
+                Ok(()) => {
+                    warn!("链上转账订阅已结束，重新连接");
+                    backoff = Duration::from_secs(1);
+                }
+                Err(e) => warn!(error = %e, "链上转账订阅失败，{}秒后重连", backoff.as_secs()),
+            }
+            metrics::incr("chain_listener_reconnect");
+            sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    });
+}
+
+async fn listen(ws_url: &str, proxy: Address, position_tracker: &PositionTracker) -> Result<()> {
+    let ctf = contract_config(POLYGON, false)
+        .context("不支持的 chain_id")?
+        .conditional_tokens;
+    let provider = ProviderBuilder::new()
+        .connect_ws(WsConnect::new(ws_url))
+        .await
+        .context("连接 WS RPC 失败")?;
+    let signatures = vec![
+        IConditionalTokens::TransferSingle::SIGNATURE_HASH,
+        IConditionalTokens::TransferBatch::SIGNATURE_HASH,
+    ];
+    // topic2 = from，topic3 = to；同一过滤器内各 topic 为「与」关系，转出与转入分别订阅
+    let outgoing = Filter::new().address(ctf).event_signature(signatures.clone()).topic2(proxy.into_word());
+    let incoming = Filter::new().address(ctf).event_signature(signatures).topic3(proxy.into_word());
+    let outgoing = provider.subscribe_logs(&outgoing).await?.into_stream();
+    let incoming = provider.subscribe_logs(&incoming).await?.into_stream();
+    let mut logs = futures::stream::select(outgoing, incoming);
+    info!("⛓️ 已订阅链上 CTF 转账事件 | proxy={:#x}", proxy);
+
+    let ctf_contract = IConditionalTokens::new(ctf, provider.clone());
+    while let Some(log) = logs.next().await {
+        if log.removed {
+            // 链重组撤回的日志：涉及 token 的余额在下一条事件或持仓同步时修正
+            continue;
+        }
+        let transfers = transfers_of(&log, proxy);
+        if transfers.is_empty() {
+            continue;
+        }
+        metrics::incr("chain_transfer_events");
+        let tx = log.transaction_hash.map(|h| format!("{:#x}", h)).unwrap_or_default();
+        let mut tokens: HashSet<U256> = HashSet::new();
+        for (token, value, incoming) in &transfers {
+            debug!(tx = %tx, token_id = %token, amount = %shares(*value), incoming, "链上 CTF 转账");
+            journal::record(
+                "chain_transfer",
+                json!({
+                    "tx": tx,
+                    "token_id": token.to_string(),
+                    "amount": shares(*value).to_string(),
+                    "direction": if *incoming { "in" } else { "out" },
+                }),
+            );
+            tokens.insert(*token);
+        }
+        for token in tokens {
+            let balance = match ctf_contract.balanceOf(proxy, token).call().await {
+                Ok(balance) => shares(balance),
+                Err(e) => {
+                    warn!(error = %e, token_id = %token, "读取链上余额失败");
+                    continue;
+                }
+            };
+            let local = position_tracker.get_position(token);
+            if (balance - local).abs() <= dec!(0.0001) {
+                continue;
+            }
+            info!(
+                "⛓️ 链上余额覆盖本地持仓 | token_id={} | 本地:{} → 链上:{} | tx={}",
+                token, local, balance, tx
+            );
+            metrics::incr("chain_position_corrected");
+            position_tracker.set_position(token, balance);
+        }
+    }
+    Ok(())
+}
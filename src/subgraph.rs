@@ -0,0 +1,317 @@
+//! Polymarket 子图（The Graph / Goldsky）查询：条件结算赔付、历史成交与持仓变动（split / merge / redeem），
+//! 以及按链上事件累计的持仓余额。数据直接来自链上事件索引，可与 REST（CLOB / Data API）交叉核对，
+//! 在 REST 延迟或遗漏时仍能得到结算与持仓的真实情况。
+//!
+//! ## 调用示例
+//!
+//! ```ignore
+//! use poly_1hour_bot::subgraph::{SubgraphClient, SubgraphUrls};
+//!
+//! let subgraph = SubgraphClient::new(SubgraphUrls::default());
+//! if let Some(resolution) = subgraph.condition_resolution(condition_id).await? {
+//!     println!("赔付: {:?}", resolution.payouts);
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+
+use alloy::primitives::{Address, B256, U256};
+use anyhow::{Context, Result};
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// 默认子图地址（Polymarket 在 Goldsky 上的公开子图）
+pub const DEFAULT_POSITIONS_SUBGRAPH: &str =
+    "https://api.goldsky.com/api/public/project_cl6mb8i9h0003e201j6li0diw/subgraphs/positions-subgraph/0.0.7/gn";
+pub const DEFAULT_ORDERBOOK_SUBGRAPH: &str =
+    "https://api.goldsky.com/api/public/project_cl6mb8i9h0003e201j6li0diw/subgraphs/orderbook-subgraph/0.0.1/gn";
+pub const DEFAULT_ACTIVITY_SUBGRAPH: &str =
+    "https://api.goldsky.com/api/public/project_cl6mb8i9h0003e201j6li0diw/subgraphs/activity-subgraph/0.0.4/gn";
+
+/// 单次查询的最大条数（子图 first 上限）
+const PAGE_SIZE: usize = 1000;
+/// 代币与 USDC 的最小单位（6 位小数）
+const UNIT_SCALE: u32 = 6;
+
+#[derive(Debug, Clone)]
+pub struct SubgraphUrls {
+    /// 持仓与条件（赔付）子图
+    pub positions: String,
+    /// 订单成交子图
+    pub orderbook: String,
+    /// split / merge / redeem 活动子图
+    pub activity: String,
+}
+
+impl Default for SubgraphUrls {
+    fn default() -> Self {
+        Self {
+            positions: DEFAULT_POSITIONS_SUBGRAPH.to_string(),
+            orderbook: DEFAULT_ORDERBOOK_SUBGRAPH.to_string(),
+            activity: DEFAULT_ACTIVITY_SUBGRAPH.to_string(),
+        }
+    }
+}
+
+/// 条件的结算结果
+#[derive(Debug, Clone)]
+pub struct ConditionResolution {
+    pub condition_id: B256,
+    /// 各结果的赔付比例（payoutNumerators / payoutDenominator），按 outcome 下标排列
+    pub payouts: Vec<Decimal>,
+}
+
+/// 一笔链上撮合成交（OrderFilled 事件）
+#[derive(Debug, Clone)]
+pub struct OrderFill {
+    pub tx_hash: String,
+    pub timestamp: i64,
+    pub maker: Address,
+    pub taker: Address,
+    /// 资产 ID：0 表示 USDC，其余为 outcome 代币
+    pub maker_asset_id: U256,
+    pub taker_asset_id: U256,
+    pub maker_amount: Decimal,
+    pub taker_amount: Decimal,
+    pub fee: Decimal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PositionChangeKind {
+    Split,
+    Merge,
+    Redemption,
+}
+
+impl PositionChangeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PositionChangeKind::Split => "split",
+            PositionChangeKind::Merge => "merge",
+            PositionChangeKind::Redemption => "redemption",
+        }
+    }
+}
+
+/// 一次持仓变动（split / merge / redeem）
+#[derive(Debug, Clone)]
+pub struct PositionChange {
+    pub kind: PositionChangeKind,
+    pub condition_id: B256,
+    pub tx_hash: String,
+    pub timestamp: i64,
+    /// split / merge 为份数，redeem 为赎回得到的 USDC
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphResponse<T> {
+    data: Option<T>,
+    #[serde(default)]
+    errors: Vec<Value>,
+}
+
+/// 6 位小数的最小单位字符串转 Decimal
+fn units(raw: &str) -> Decimal {
+    Decimal::from_str(raw)
+        .map(|d| d / Decimal::from(10u64.pow(UNIT_SCALE)))
+        .unwrap_or_default()
+}
+
+fn text<'a>(value: &'a Value, field: &str) -> &'a str {
+    value.get(field).and_then(Value::as_str).unwrap_or_default()
+}
+
+pub struct SubgraphClient {
+    http: reqwest::Client,
+    urls: SubgraphUrls,
+}
+
+impl SubgraphClient {
+    pub fn new(urls: SubgraphUrls) -> Self {
+        Self {
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(15))
+                .build()
+                .unwrap_or_default(),
+            urls,
+        }
+    }
+
+    async fn query<T: DeserializeOwned>(&self, url: &str, query: &str, variables: Value) -> Result<T> {
+        let response: GraphResponse<T> = self
+            .http
+            .post(url)
+            .json(&json!({ "query": query, "variables": variables }))
+            .send()
+            .await
+            .context("请求子图失败")?
+            .error_for_status()
+            .context("子图返回错误状态")?
+            .json()
+            .await
+            .context("解析子图响应失败")?;
+        if !response.errors.is_empty() {
+            anyhow::bail!("子图查询错误: {}", Value::Array(response.errors));
+        }
+        response.data.context("子图响应缺少 data")
+    }
+
+    /// 查询条件的结算赔付；尚未结算时返回 None
+    pub async fn condition_resolution(&self, condition_id: B256) -> Result<Option<ConditionResolution>> {
+        #[derive(Deserialize)]
+        struct Data {
+            condition: Option<Value>,
+        }
+        let data: Data = self
+            .query(
+                &self.urls.positions,
+                "query($id: ID!) { condition(id: $id) { id payoutNumerators payoutDenominator } }",
+                json!({ "id": format!("{:#x}", condition_id) }),
+            )
+            .await?;
+        let Some(condition) = data.condition else {
+            return Ok(None);
+        };
+        let denominator = Decimal::from_str(text(&condition, "payoutDenominator")).unwrap_or_default();
+        if denominator.is_zero() {
+            return Ok(None);
+        }
+        let payouts = condition
+            .get("payoutNumerators")
+            .and_then(Value::as_array)
+            .map(|items| {
+                items
+                    .iter()
+                    .map(|n| Decimal::from_str(n.as_str().unwrap_or("0")).unwrap_or_default() / denominator)
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(Some(ConditionResolution { condition_id, payouts }))
+    }
+
+    /// 查询 user 作为 maker 或 taker、自 since（Unix 秒）以来的撮合成交
+    pub async fn fills_since(&self, user: Address, since: i64) -> Result<Vec<OrderFill>> {
+        #[derive(Deserialize)]
+        struct Data {
+            #[serde(rename = "orderFilledEvents", default)]
+            fills: Vec<Value>,
+        }
+        const QUERY: &str = "query($user: String!, $since: BigInt!, $skip: Int!, $first: Int!) { \
+            orderFilledEvents(first: $first, skip: $skip, orderBy: timestamp, orderDirection: asc, \
+              where: { or: [{ maker: $user, timestamp_gte: $since }, { taker: $user, timestamp_gte: $since }] }) { \
+              transactionHash timestamp maker taker makerAssetId takerAssetId makerAmountFilled takerAmountFilled fee } }";
+        let mut fills = Vec::new();
+        for page in 0.. {
+            let data: Data = self
+                .query(
+                    &self.urls.orderbook,
+                    QUERY,
+                    json!({
+                        "user": format!("{:#x}", user),
+                        "since": since.to_string(),
+                        "skip": page * PAGE_SIZE,
+                        "first": PAGE_SIZE,
+                    }),
+                )
+                .await?;
+            let count = data.fills.len();
+            fills.extend(data.fills.iter().map(|f| OrderFill {
+                tx_hash: text(f, "transactionHash").to_string(),
+                timestamp: text(f, "timestamp").parse().unwrap_or_default(),
+                maker: Address::from_str(text(f, "maker")).unwrap_or_default(),
+                taker: Address::from_str(text(f, "taker")).unwrap_or_default(),
+                maker_asset_id: U256::from_str(text(f, "makerAssetId")).unwrap_or_default(),
+                taker_asset_id: U256::from_str(text(f, "takerAssetId")).unwrap_or_default(),
+                maker_amount: units(text(f, "makerAmountFilled")),
+                taker_amount: units(text(f, "takerAmountFilled")),
+                fee: units(text(f, "fee")),
+            }));
+            if count < PAGE_SIZE {
+                break;
+            }
+        }
+        Ok(fills)
+    }
+
+    /// 查询 user 自 since（Unix 秒）以来的 split / merge / redeem
+    pub async fn position_changes_since(&self, user: Address, since: i64) -> Result<Vec<PositionChange>> {
+        #[derive(Deserialize)]
+        struct Data {
+            #[serde(default)]
+            splits: Vec<Value>,
+            #[serde(default)]
+            merges: Vec<Value>,
+            #[serde(default)]
+            redemptions: Vec<Value>,
+        }
+        const QUERY: &str = "query($user: String!, $since: BigInt!, $first: Int!) { \
+            splits(first: $first, orderBy: timestamp, where: { stakeholder: $user, timestamp_gte: $since }) { id timestamp condition amount } \
+            merges(first: $first, orderBy: timestamp, where: { stakeholder: $user, timestamp_gte: $since }) { id timestamp condition amount } \
+            redemptions(first: $first, orderBy: timestamp, where: { redeemer: $user, timestamp_gte: $since }) { id timestamp condition payout } }";
+        let data: Data = self
+            .query(
+                &self.urls.activity,
+                QUERY,
+                json!({ "user": format!("{:#x}", user), "since": since.to_string(), "first": PAGE_SIZE }),
+            )
+            .await?;
+        let convert = |kind: PositionChangeKind, amount_field: &'static str| {
+            move |item: &Value| -> Option<PositionChange> {
+                Some(PositionChange {
+                    kind,
+                    condition_id: B256::from_str(text(item, "condition")).ok()?,
+                    // 活动子图的 id 为「交易哈希_日志序号」
+                    tx_hash: text(item, "id").split('_').next().unwrap_or_default().to_string(),
+                    timestamp: text(item, "timestamp").parse().unwrap_or_default(),
+                    amount: units(text(item, amount_field)),
+                })
+            }
+        };
+        let mut changes: Vec<PositionChange> = data
+            .splits
+            .iter()
+            .filter_map(convert(PositionChangeKind::Split, "amount"))
+            .chain(data.merges.iter().filter_map(convert(PositionChangeKind::Merge, "amount")))
+            .chain(data.redemptions.iter().filter_map(convert(PositionChangeKind::Redemption, "payout")))
+            .collect();
+        changes.sort_by_key(|c| c.timestamp);
+        Ok(changes)
+    }
+
+    /// 按链上事件累计的 user 各 outcome 代币余额（份数，不含零余额）
+    pub async fn user_balances(&self, user: Address) -> Result<HashMap<U256, Decimal>> {
+        #[derive(Deserialize)]
+        struct Data {
+            #[serde(rename = "userBalances", default)]
+            balances: Vec<Value>,
+        }
+        const QUERY: &str = "query($user: String!, $skip: Int!, $first: Int!) { \
+            userBalances(first: $first, skip: $skip, where: { user: $user, balance_gt: \"0\" }) { asset { id } balance } }";
+        let mut balances = HashMap::new();
+        for page in 0.. {
+            let data: Data = self
+                .query(
+                    &self.urls.positions,
+                    QUERY,
+                    json!({ "user": format!("{:#x}", user), "skip": page * PAGE_SIZE, "first": PAGE_SIZE }),
+                )
+                .await?;
+            let count = data.balances.len();
+            for item in &data.balances {
+                let asset = item.get("asset").map(|a| text(a, "id")).unwrap_or_default();
+                if let Ok(token_id) = U256::from_str(asset) {
+                    balances.insert(token_id, units(text(item, "balance")));
+                }
+            }
+            if count < PAGE_SIZE {
+                break;
+            }
+        }
+        Ok(balances)
+    }
+}
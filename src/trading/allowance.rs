@@ -0,0 +1,78 @@
+//! 授权自动修复：下单因 USDC 授权 / CTF 授权不足被拒时，自动检查资金地址对交易所合约的授权并补上缺失的部分
+//! （`merge::approve_exchanges`），成功后由调用方重试被拒的订单，而不是之后每一笔都失败、直到人工处理。
+//! 修复串行执行并带冷却：冷却期内的后续请求直接复用上一次的结果，不会重复发链上交易。
+//! 未初始化（ALLOWANCE_AUTO_REPAIR=false）时各调用为 no-op。
+
+use poly_1hour_bot::merge;
+use polymarket_client_sdk::types::Address;
+use serde_json::json;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tracing::{error, info, warn};
+
+use crate::utils::{journal, metrics};
+
+struct Repairer {
+    private_key: String,
+    proxy: Option<Address>,
+    cooldown: Duration,
+    /// 上一次修复的时间与结果（是否发出了授权交易）
+    last: Mutex<Option<(Instant, bool)>>,
+}
+
+static REPAIRER: OnceLock<Repairer> = OnceLock::new();
+
+/// 启用自动修复；enabled 为 false 时不初始化
+pub fn init(enabled: bool, private_key: &str, proxy: Option<Address>, cooldown: Duration) {
+    if !enabled {
+        return;
+    }
+    let _ = REPAIRER.set(Repairer {
+        private_key: private_key.to_string(),
+        proxy,
+        cooldown,
+        last: Mutex::new(None),
+    });
+}
+
+/// 拒单信息是否指向授权不足（余额不足无法靠授权修复，但 CLOB 对两者常返回同一条信息，交由链上检查区分）
+pub fn is_allowance_error(error_msg: &str) -> bool {
+    let msg = error_msg.to_ascii_lowercase();
+    msg.contains("allowance") || msg.contains("not enough balance")
+}
+
+/// 检查并补齐授权；返回 true 表示已补发授权交易（调用方可重试被拒订单）。
+/// 冷却期内直接返回上一次的结果；修复进行中时等待其完成。
+pub async fn repair() -> bool {
+    let Some(repairer) = REPAIRER.get() else {
+        return false;
+    };
+    let mut last = repairer.last.lock().await;
+    if let Some((at, repaired)) = *last {
+        if at.elapsed() < repairer.cooldown {
+            return repaired;
+        }
+    }
+
+    info!("🔑 下单因授权不足被拒，检查交易所授权");
+    let repaired = match merge::approve_exchanges(repairer.proxy, &repairer.private_key, None).await {
+        Ok(Some(tx)) => {
+            info!("✅ 已补齐交易所授权 | tx: {}", merge::short_hex(&tx));
+            metrics::incr("allowance_repaired");
+            journal::record("allowance_repair", json!({ "tx": tx }));
+            true
+        }
+        Ok(None) => {
+            warn!("授权正常，拒单原因应为 USDC 余额不足");
+            false
+        }
+        Err(e) => {
+            error!(error = %e, "❌ 自动补授权失败，请手动检查授权");
+            metrics::incr("allowance_repair_failed");
+            false
+        }
+    };
+    *last = Some((Instant::now(), repaired));
+    repaired
+}
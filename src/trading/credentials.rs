@@ -0,0 +1,253 @@
+//! CLOB API 凭证轮换：运行中为同一私钥以新的 nonce 创建 API Key，用新凭证重新认证执行器与风控的客户端，
+//! 全部认证成功后一次性替换，宽限期过后再吊销旧 Key，长期运行的实例无需重启即可轮换密钥，不会错过窗口。
+//!
+//! - 触发：每 API_KEY_ROTATION_HOURS 小时一次（0 = 不定时轮换），Unix 下也可向进程发送 SIGHUP 立即轮换；
+//! - 任一客户端认证失败则放弃本次轮换，继续使用旧凭证；
+//! - 当前 nonce 持久化到 API_KEY_NONCE_PATH，重启后用同一 nonce 派生出轮换后的 Key；
+//! - 替换前已取出旧客户端的请求在宽限期（API_KEY_REVOKE_GRACE_SECS）内照常完成，之后旧 Key 才被吊销。
+//!
+//! 只在主实例上轮换；主备（LEADER_LOCK_PATH）共用同一钱包时，轮换后备机需重启（或共享 nonce 文件）才能拿到新 Key。
+
+use alloy::signers::local::LocalSigner;
+use alloy::signers::Signer;
+use anyhow::Result;
+use polymarket_client_sdk::auth::state::Authenticated;
+use polymarket_client_sdk::auth::Normal;
+use polymarket_client_sdk::clob::types::SignatureType;
+use polymarket_client_sdk::clob::{Client, Config};
+use polymarket_client_sdk::types::Address;
+use polymarket_client_sdk::POLYGON;
+use serde_json::json;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{error, info, warn};
+
+use crate::utils::{journal, leader, metrics, state_crypto};
+
+pub type ClobClient = Client<Authenticated<Normal>>;
+
+/// 可原子替换的认证客户端；克隆共享同一份，替换对所有持有者立即生效
+#[derive(Clone)]
+pub struct SharedClient(Arc<RwLock<ClobClient>>);
+
+impl SharedClient {
+    pub fn new(client: ClobClient) -> Self {
+        Self(Arc::new(RwLock::new(client)))
+    }
+
+    /// 当前客户端（每次请求前取一次，不要长期持有）
+    pub fn get(&self) -> ClobClient {
+        match self.0.read() {
+            Ok(client) => client.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    /// 换上新客户端，返回旧客户端
+    fn replace(&self, client: ClobClient) -> ClobClient {
+        let mut guard = match self.0.write() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        std::mem::replace(&mut *guard, client)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RotationSettings {
+    /// 定时轮换间隔（小时），0 = 不定时轮换
+    pub interval_hours: u64,
+    /// 当前 API Key nonce 的持久化文件
+    pub nonce_path: String,
+    /// 替换后等待多久再吊销旧 Key（秒）
+    pub revoke_grace_secs: u64,
+}
+
+/// 读取持久化的 nonce；文件不存在或无效时为 0（SDK 默认）
+pub fn load_nonce(path: &str) -> u32 {
+    std::fs::read(path)
+        .ok()
+        .and_then(|body| state_crypto::open(&body).ok())
+        .and_then(|body| String::from_utf8(body).ok())
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+fn save_nonce(path: &str, nonce: u32) -> Result<()> {
+    if let Some(dir) = std::path::Path::new(path).parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp = format!("{}.tmp", path);
+    std::fs::write(&tmp, state_crypto::seal(nonce.to_string().as_bytes())?)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// 用指定 nonce 的 API Key 认证一个客户端（Key 不存在时由 SDK 创建，已存在时派生）
+pub async fn authenticate(
+    clob_url: &str,
+    private_key: &str,
+    proxy_address: Option<Address>,
+    use_server_time: bool,
+    nonce: u32,
+) -> Result<ClobClient> {
+    let signer = LocalSigner::from_str(private_key)?.with_chain_id(Some(POLYGON));
+    let config = Config::builder().use_server_time(use_server_time).build();
+    let mut auth_builder = Client::new(clob_url, config)?
+        .authentication_builder(&signer)
+        .nonce(nonce);
+    if let Some(funder) = proxy_address {
+        auth_builder = auth_builder.funder(funder).signature_type(SignatureType::Proxy);
+    }
+    Ok(auth_builder.authenticate().await?)
+}
+
+/// 需要一起轮换的客户端，以及各自的认证配置
+struct Slot {
+    client: SharedClient,
+    use_server_time: bool,
+}
+
+pub struct Rotator {
+    clob_url: String,
+    private_key: String,
+    proxy_address: Option<Address>,
+    settings: RotationSettings,
+    slots: Vec<Slot>,
+    /// 当前 nonce；锁同时保证轮换串行执行
+    nonce: Mutex<u32>,
+}
+
+impl Rotator {
+    pub fn new(
+        clob_url: &str,
+        private_key: &str,
+        proxy_address: Option<Address>,
+        settings: RotationSettings,
+        nonce: u32,
+    ) -> Self {
+        Self {
+            clob_url: clob_url.to_string(),
+            private_key: private_key.to_string(),
+            proxy_address,
+            settings,
+            slots: Vec::new(),
+            nonce: Mutex::new(nonce),
+        }
+    }
+
+    /// 登记一个随轮换替换的客户端
+    pub fn add(mut self, client: SharedClient, use_server_time: bool) -> Self {
+        self.slots.push(Slot { client, use_server_time });
+        self
+    }
+
+    /// 轮换一次：创建新 Key 并认证全部客户端 → 一次性替换 → 持久化 nonce → 宽限期后吊销旧 Key
+    pub async fn rotate(&self) -> Result<()> {
+        let mut nonce = self.nonce.lock().await;
+        let next = nonce.wrapping_add(1);
+        info!("🔑 开始轮换 CLOB API 凭证 | nonce {} → {}", *nonce, next);
+
+        let mut fresh = Vec::with_capacity(self.slots.len());
+        for slot in &self.slots {
+            let client = authenticate(
+                &self.clob_url,
+                &self.private_key,
+                self.proxy_address,
+                slot.use_server_time,
+                next,
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!("新凭证认证失败，继续使用旧凭证: {}", e))?;
+            fresh.push(client);
+        }
+        // 替换前先确认新 Key 可用
+        if let Some(client) = fresh.first() {
+            client
+                .api_keys()
+                .await
+                .map_err(|e| anyhow::anyhow!("新凭证验证失败，继续使用旧凭证: {}", e))?;
+        }
+
+        let old: Vec<ClobClient> = self
+            .slots
+            .iter()
+            .zip(fresh)
+            .map(|(slot, client)| slot.client.replace(client))
+            .collect();
+        let previous = *nonce;
+        *nonce = next;
+        if let Err(e) = save_nonce(&self.settings.nonce_path, next) {
+            warn!(error = %e, path = %self.settings.nonce_path, "保存 API Key nonce 失败，重启后将使用旧 nonce 重新派生");
+        }
+        info!("✅ 已切换到新 API 凭证 | nonce {}", next);
+        metrics::incr("api_key_rotated");
+
+        tokio::time::sleep(Duration::from_secs(self.settings.revoke_grace_secs)).await;
+        let revoked = match old.first() {
+            Some(client) => match client.delete_api_key().await {
+                Ok(_) => {
+                    info!("🗑️ 已吊销旧 API Key | nonce {}", previous);
+                    true
+                }
+                Err(e) => {
+                    warn!(error = %e, "吊销旧 API Key 失败，请手动检查");
+                    metrics::incr("api_key_revoke_failed");
+                    false
+                }
+            },
+            None => false,
+        };
+        journal::record(
+            "api_key_rotation",
+            json!({ "from_nonce": previous, "to_nonce": next, "revoked": revoked }),
+        );
+        Ok(())
+    }
+
+    /// 后台定时轮换，并在 Unix 下响应 SIGHUP；只在主实例上执行
+    pub fn spawn(self: Arc<Self>) {
+        let interval = (self.settings.interval_hours > 0)
+            .then(|| Duration::from_secs(self.settings.interval_hours * 3600));
+        match interval {
+            Some(interval) => info!("API 凭证定时轮换已启用 | 每 {} 小时", interval.as_secs() / 3600),
+            None => info!("API 凭证定时轮换未启用（API_KEY_ROTATION_HOURS=0），可发送 SIGHUP 手动轮换"),
+        }
+        tokio::spawn(async move {
+            #[cfg(unix)]
+            let mut hangup = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()).ok();
+            loop {
+                let timer = async {
+                    match interval {
+                        Some(interval) => tokio::time::sleep(interval).await,
+                        None => std::future::pending::<()>().await,
+                    }
+                };
+                #[cfg(unix)]
+                let hangup_recv = async {
+                    match hangup.as_mut() {
+                        Some(signal) => {
+                            signal.recv().await;
+                        }
+                        None => std::future::pending::<()>().await,
+                    }
+                };
+                #[cfg(not(unix))]
+                let hangup_recv = std::future::pending::<()>();
+                tokio::select! {
+                    _ = timer => {}
+                    _ = hangup_recv => info!("收到 SIGHUP，轮换 API 凭证"),
+                }
+                if !leader::is_leader() {
+                    continue;
+                }
+                if let Err(e) = self.rotate().await {
+                    error!(error = %e, "❌ API 凭证轮换失败");
+                    metrics::incr("api_key_rotation_failed");
+                }
+            }
+        });
+    }
+}
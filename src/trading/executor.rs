@@ -4,15 +4,128 @@ use alloy::signers::local::LocalSigner;
 use chrono::Utc;
 use polymarket_client_sdk::clob::{Client, Config};
 use polymarket_client_sdk::clob::types::{OrderType, Side, SignatureType};
+use polymarket_client_sdk::clob::types::request::OrdersRequest;
+use polymarket_client_sdk::clob::types::response::PostOrderResponse;
 use polymarket_client_sdk::types::{Address, Decimal, U256};
 use polymarket_client_sdk::POLYGON;
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal_macros::dec;
 use std::str::FromStr;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
+use poly_1hour_bot::chaos;
+use poly_1hour_bot::i18n::Msg;
+use poly_1hour_bot::tr;
 use uuid::Uuid;
 
+use super::reject_policy::{self, RetryPlan};
+use super::rejection::{self, RejectReason};
+use super::allowance;
+use super::credentials::SharedClient;
+use super::slippage::{Leg, LegSlippage, SlippageSettings};
+use crate::market::rules;
 use crate::monitor::arbitrage::ArbitrageOpportunity;
+use crate::utils::latency::{self, Stage};
+use crate::utils::rate_limit::{self, Priority};
+
+/// post_orders 单次请求最多携带的订单数（CLOB 批量下单接口上限）
+const MAX_ORDERS_PER_BATCH: usize = 15;
+/// 拆单时每腿最多的子单数：两腿共 14 笔，一次 post_orders 即可提交
+const MAX_CHILD_ORDERS_PER_LEG: usize = 7;
+
+/// 批量下单中的一笔订单（submit_batch 使用）
+#[derive(Debug, Clone)]
+pub struct BatchOrder {
+    pub token_id: U256,
+    pub side: Side,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub order_type: OrderType,
+    /// 过期时间，仅 GTD 订单生效（SDK 规定非 GTD 不可设过期）
+    pub expiration: Option<chrono::DateTime<Utc>>,
+}
+
+/// 套利执行方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionMode {
+    /// 两腿同时提交（默认）
+    Pair,
+    /// 先吃更深（同深度取更便宜）的一腿，再按 1 − 首腿成交均价 − 费用 为上限挂第二腿，两腿总成本不超过 1
+    Sequenced,
+}
+
+impl ExecutionMode {
+    /// 解析 pair / sequenced，大小写不敏感，无效值为 Pair
+    pub fn parse(s: &str) -> Self {
+        match s.trim().to_lowercase().as_str() {
+            "sequenced" | "sequential" => ExecutionMode::Sequenced,
+            _ => ExecutionMode::Pair,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExecutionMode::Pair => "pair",
+            ExecutionMode::Sequenced => "sequenced",
+        }
+    }
+}
+
+/// 套利两腿的提交方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LegSubmission {
+    /// 一次 post_orders 批量提交（默认，请求最少）
+    Batched,
+    /// 两笔 post_order 并发提交（不依赖批量接口，单腿失败互不影响）
+    Parallel,
+    /// 先以 FAK 提交更容易被抢走的一腿，成交后再提交另一腿（单边风险最低，延迟最高）
+    Sequential,
+}
+
+impl LegSubmission {
+    /// 解析 batched / parallel / sequential，大小写不敏感，无效值为 Batched
+    pub fn parse(s: &str) -> Self {
+        match s.trim().to_lowercase().as_str() {
+            "parallel" => LegSubmission::Parallel,
+            "sequential" => LegSubmission::Sequential,
+            _ => LegSubmission::Batched,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LegSubmission::Batched => "batched",
+            LegSubmission::Parallel => "parallel",
+            LegSubmission::Sequential => "sequential",
+        }
+    }
+}
+
+/// 下单超时的错误标记：订单可能已被接受
+const STATUS_UNKNOWN: &str = "订单状态未知";
+/// 两腿均未成交（吃单落空）的错误前缀
+const NOTHING_FILLED: &str = "套利失败";
+
+/// 套利错误是否为两腿均未成交（没有任何持仓变化）
+pub fn nothing_filled(e: &anyhow::Error) -> bool {
+    format!("{:#}", e).starts_with(NOTHING_FILLED)
+}
+
+/// 下单错误是否源于超时（订单可能已被接受，敞口应按已下单处理）
+pub fn outcome_unknown(e: &anyhow::Error) -> bool {
+    format!("{:#}", e).contains(STATUS_UNKNOWN)
+}
+
+/// 带超时的下单请求；超时后订单可能已被接受，实际持仓由定期同步校正
+async fn within<T, E: std::fmt::Display>(
+    timeout: Duration,
+    request: impl std::future::Future<Output = std::result::Result<T, E>>,
+) -> Result<T> {
+    match tokio::time::timeout(timeout, request).await {
+        Ok(result) => result.map_err(|e| anyhow::anyhow!("{}", e)),
+        Err(_) => Err(anyhow::anyhow!("下单请求超时（{}ms），{}", timeout.as_millis(), STATUS_UNKNOWN)),
+    }
+}
 
 pub struct OrderPairResult {
     pub pair_id: String,
@@ -22,26 +135,56 @@ pub struct OrderPairResult {
     pub no_filled: Decimal,
     pub yes_size: Decimal,
     pub no_size: Decimal,
+    /// 含滑点的下单限价（未成交部分以该价格挂单）
+    pub yes_price: Decimal,
+    pub no_price: Decimal,
     pub success: bool,
+    /// 拆单时的全部子单（含每腿第一笔，即 yes_order_id / no_order_id）；未拆单时为空
+    pub child_orders: Vec<ChildOrder>,
+}
+
+/// 拆单提交的一笔子单
+#[derive(Debug, Clone)]
+pub struct ChildOrder {
+    pub order_id: String,
+    pub token_id: U256,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub filled: Decimal,
 }
 
 pub struct TradingExecutor {
-    client: Client<polymarket_client_sdk::auth::state::Authenticated<polymarket_client_sdk::auth::Normal>>,
+    /// 认证客户端，API 凭证轮换时原子替换
+    client: SharedClient,
     private_key: String,
     max_order_size: Decimal,
-    slippage: [Decimal; 2], // [first, second]，仅下降侧用 second，上涨与持平用 first
+    /// 两腿各自的滑点（含按币种自适应加宽）
+    slippage: LegSlippage,
     gtd_expiration_secs: u64,
     arbitrage_order_type: OrderType,
+    leg_submission: LegSubmission,
+    /// 当前提交方式下单次下单请求的超时
+    leg_timeout: Duration,
+    /// 单笔子单名义金额上限（USD）；超过时拆成多笔子单，None 表示不拆单
+    child_max_notional: Option<Decimal>,
+    /// 冰山挂单的显示数量（份）；None 表示未成交部分整笔挂单
+    iceberg_display: Option<Decimal>,
 }
 
 impl TradingExecutor {
     pub async fn new(
+        clob_url: &str,
         private_key: String,
         max_order_size_usdc: f64,
         proxy_address: Option<Address>,
-        slippage: [f64; 2],
+        slippage: &SlippageSettings,
         gtd_expiration_secs: u64,
         arbitrage_order_type: OrderType,
+        leg_submission: LegSubmission,
+        leg_timeout: Duration,
+        child_max_notional_usdc: f64,
+        iceberg_display_size: f64,
+        api_key_nonce: u32,
     ) -> Result<Self> {
         // 验证私钥格式
         let signer = LocalSigner::from_str(&private_key)
@@ -49,9 +192,10 @@ impl TradingExecutor {
             .with_chain_id(Some(POLYGON));
 
         let config = Config::builder().use_server_time(false).build();
-        let mut auth_builder = Client::new("https://clob.polymarket.com", config)
+        let mut auth_builder = Client::new(clob_url, config)
             .map_err(|e| anyhow::anyhow!("创建CLOB客户端失败: {}", e))?
-            .authentication_builder(&signer);
+            .authentication_builder(&signer)
+            .nonce(api_key_nonce);
         
         // 如果提供了proxy_address，设置funder和signature_type（按照Python SDK模式）
         if let Some(funder) = proxy_address {
@@ -71,33 +215,150 @@ impl TradingExecutor {
             })?;
 
         Ok(Self {
-            client,
+            client: SharedClient::new(client),
             private_key,
             max_order_size: Decimal::try_from(max_order_size_usdc)
                 .unwrap_or(rust_decimal_macros::dec!(100.0)),
-            slippage: [
-                Decimal::try_from(slippage[0]).unwrap_or(dec!(0.0)),
-                Decimal::try_from(slippage[1]).unwrap_or(dec!(0.01)),
-            ],
+            slippage: LegSlippage::new(slippage),
             gtd_expiration_secs,
             arbitrage_order_type,
+            leg_submission,
+            leg_timeout,
+            child_max_notional: Decimal::try_from(child_max_notional_usdc)
+                .ok()
+                .filter(|v| *v > dec!(0)),
+            iceberg_display: Decimal::try_from(iceberg_display_size)
+                .ok()
+                .filter(|v| *v > dec!(0)),
         })
     }
 
+    /// 共享的认证客户端句柄（登记到 API 凭证轮换）
+    pub fn shared_client(&self) -> SharedClient {
+        self.client.clone()
+    }
+
     /// 验证认证是否真的成功 - 按照官方示例使用 api_keys() 来验证
     pub async fn verify_authentication(&self) -> Result<()> {
+        let client = self.client.get();
         // 按照官方示例，使用 api_keys() 来验证认证状态
-        self.client.api_keys().await
+        client.api_keys().await
             .map_err(|e| anyhow::anyhow!("认证验证失败: API调用返回错误: {}", e))?;
         Ok(())
     }
 
-    /// 取消该账户所有挂单（收尾时使用）
-    pub async fn cancel_all_orders(&self) -> Result<polymarket_client_sdk::clob::types::response::CancelOrdersResponse> {
-        self.client
-            .cancel_all_orders()
+    /// 连接预热/保活：并发发出 connections 个轻量认证请求（api_keys），建立并保持连接池中的热连接，
+    /// 使突发下单时第一笔订单无需再付 TLS/TCP 握手延迟。
+    pub async fn warm_up_connections(&self, connections: usize) -> Result<()> {
+        let client = self.client.get();
+        let start = Instant::now();
+        let results = futures::future::join_all(
+            (0..connections.max(1)).map(|_| client.api_keys()),
+        )
+        .await;
+        let failed = results.iter().filter(|r| r.is_err()).count();
+        if failed > 0 {
+            return Err(anyhow::anyhow!(
+                "连接预热失败: {}/{} 个请求出错",
+                failed,
+                results.len()
+            ));
+        }
+        debug!(
+            connections = results.len(),
+            elapsed_ms = start.elapsed().as_millis() as u64,
+            "CLOB 连接预热完成"
+        );
+        Ok(())
+    }
+
+    /// 套利订单未成交部分是否会挂在订单簿上（GTC/GTD）；返回 Some(GTD 有效期)，GTC 为 Some(None)，FOK/FAK 为 None
+    pub fn resting_lifetime(&self) -> Option<Option<Duration>> {
+        match self.arbitrage_order_type {
+            OrderType::GTC => Some(None),
+            OrderType::GTD => Some(Some(Duration::from_secs(self.gtd_secs()))),
+            _ => None,
+        }
+    }
+
+    /// 冰山挂单的显示数量：仅当套利订单会挂单（GTC/GTD）且设置了 ICEBERG_DISPLAY_SIZE 时为 Some
+    pub fn iceberg_display(&self) -> Option<Decimal> {
+        self.iceberg_display.filter(|_| self.resting_lifetime().is_some())
+    }
+
+    /// 套利两腿实际提交的订单类型：启用冰山挂单时先以 FAK 吃单，剩余部分由冰山子单挂出
+    fn leg_order_type(&self) -> OrderType {
+        if self.iceberg_display().is_some() {
+            OrderType::FAK
+        } else {
+            self.arbitrage_order_type.clone()
+        }
+    }
+
+    /// 查询订单已成交数量
+    pub async fn order_matched(&self, order_id: &str) -> Result<Decimal> {
+        let client = self.client.get();
+        rate_limit::acquire(Priority::Normal).await?;
+        let order = client
+            .order(order_id)
+            .await
+            .map_err(|e| anyhow::anyhow!("查询订单失败: {}", e))?;
+        Ok(order.size_matched)
+    }
+
+    /// GTD 有效期（秒）：现货实现波动率高于参考值时按比例缩短
+    fn gtd_secs(&self) -> u64 {
+        crate::market::volatility::tuned_gtd_secs(self.gtd_expiration_secs)
+    }
+
+    /// 按订单 ID 取消挂单
+    pub async fn cancel_order_ids(&self, order_ids: &[&str]) -> Result<()> {
+        let client = self.client.get();
+        rate_limit::acquire(Priority::Critical).await?;
+        client
+            .cancel_orders(order_ids)
             .await
-            .map_err(|e| anyhow::anyhow!("取消所有挂单失败: {}", e))
+            .map_err(|e| anyhow::anyhow!("取消挂单失败: {}", e))?;
+        Ok(())
+    }
+
+    /// 账户上所有未成交挂单：(订单ID, token_id)
+    pub async fn open_orders(&self) -> Result<Vec<(String, U256)>> {
+        let client = self.client.get();
+        let mut orders = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            rate_limit::acquire(Priority::Normal).await?;
+            let page = client.orders(&OrdersRequest::default(), cursor).await?;
+            orders.extend(page.data.into_iter().map(|order| (order.id, order.asset_id)));
+            if page.next_cursor.is_empty() || page.next_cursor == "LTE=" {
+                break;
+            }
+            cursor = Some(page.next_cursor);
+        }
+        Ok(orders)
+    }
+
+    /// 取消指定 token 上的所有挂单（市场停止接单等情况），返回取消的订单数
+    pub async fn cancel_orders_for_tokens(&self, token_ids: &[U256]) -> Result<usize> {
+        let client = self.client.get();
+        let order_ids: Vec<String> = self
+            .open_orders()
+            .await?
+            .into_iter()
+            .filter(|(_, token_id)| token_ids.contains(token_id))
+            .map(|(id, _)| id)
+            .collect();
+        if order_ids.is_empty() {
+            return Ok(0);
+        }
+        let refs: Vec<&str> = order_ids.iter().map(String::as_str).collect();
+        rate_limit::acquire(Priority::Critical).await?;
+        client
+            .cancel_orders(&refs)
+            .await
+            .map_err(|e| anyhow::anyhow!("取消挂单失败: {}", e))?;
+        Ok(order_ids.len())
     }
 
     /// 以指定价格下 GTC 卖单（收尾时市价意图卖出单腿持仓）
@@ -107,10 +368,10 @@ impl TradingExecutor {
         price: Decimal,
         size: Decimal,
     ) -> Result<polymarket_client_sdk::clob::types::response::PostOrderResponse> {
+        let client = self.client.get();
         let signer = LocalSigner::from_str(&self.private_key)?
             .with_chain_id(Some(POLYGON));
-        let order = self
-            .client
+        let order = client
             .limit_order()
             .token_id(token_id)
             .side(Side::Sell)
@@ -119,45 +380,393 @@ impl TradingExecutor {
             .order_type(OrderType::GTC)
             .build()
             .await?;
-        let signed = self.client.sign(&signer, order).await?;
-        self.client
+        let signed = client.sign(&signer, order).await?;
+        rate_limit::acquire(Priority::Critical).await?;
+        client
             .post_order(signed)
             .await
             .map_err(|e| anyhow::anyhow!("卖出订单提交失败: {}", e))
     }
 
-    /// 按方向取滑点：仅下降(↓)用 second，上涨(↑)和持平(−/空)用 first
-    fn slippage_for_direction(&self, dir: &str) -> Decimal {
-        if dir == "↓" {
-            self.slippage[1]
+    /// 批量提交多笔订单（同一对的两腿，或同一拍命中的多个市场）：并行构建与签名，
+    /// 按 MAX_ORDERS_PER_BATCH 分组走 post_orders，尽量少发 HTTP 请求、少占限速额度。
+    /// 返回结果与 orders 顺序一一对应；任一订单构建或签名失败则整批不提交（返回 Err）。
+    /// 某组 post_orders 调用失败时该组每笔订单的结果为 Err（订单状态未知），其余组的结果照常返回。
+    /// 被拒订单重试一次：因授权不足被拒且自动补授权成功时原样重试，其余按拒单处置策略（REJECT_POLICY）的
+    /// retry / resize 重试。
+    pub async fn submit_batch(&self, orders: &[BatchOrder]) -> Result<Vec<Result<PostOrderResponse>>> {
+        let results = self.submit_batch_once(orders).await?;
+        Ok(self.retry_rejected(orders, results).await)
+    }
+
+    /// 重试 submit_batch_once 中被拒的订单（补授权、按拒单处置策略等待或缩量），结果原位替换。
+    /// 与首次提交分开，调用方可只对首次提交计时，补授权与重试等待不会把已下单的订单变成「状态未知」。
+    async fn retry_rejected(
+        &self,
+        orders: &[BatchOrder],
+        mut results: Vec<Result<PostOrderResponse>>,
+    ) -> Vec<Result<PostOrderResponse>> {
+        let mut rejected = Vec::new();
+        let mut retry = Vec::new();
+        let mut delay = Duration::ZERO;
+        let mut allowance_repaired = None;
+        let failed = results
+            .iter()
+            .enumerate()
+            .filter_map(|(i, r)| r.as_ref().ok().filter(|r| !r.success).map(|r| (i, r)));
+        for (i, result) in failed {
+            let error_msg = result.error_msg.as_deref().unwrap_or("");
+            let mut order = orders[i].clone();
+            if allowance::is_allowance_error(error_msg) {
+                let repaired = match allowance_repaired {
+                    Some(repaired) => repaired,
+                    None => *allowance_repaired.insert(allowance::repair().await),
+                };
+                if repaired {
+                    rejected.push(i);
+                    retry.push(order);
+                    continue;
+                }
+            }
+            match reject_policy::retry_plan(RejectReason::classify(error_msg)) {
+                Some(RetryPlan::Same { delay: d }) => delay = delay.max(d),
+                Some(RetryPlan::Resize { factor }) => match rules::order_size(&[order.token_id], order.size * factor) {
+                    Some(size) => order.size = size,
+                    None => continue,
+                },
+                None => continue,
+            }
+            rejected.push(i);
+            retry.push(order);
+        }
+        if retry.is_empty() {
+            return results;
+        }
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        info!("🔁 重试被拒订单 | {}笔", retry.len());
+        match self.submit_batch_once(&retry).await {
+            Ok(retried) => {
+                for (i, result) in rejected.into_iter().zip(retried) {
+                    results[i] = result;
+                }
+            }
+            Err(e) => warn!(error = %e, "重试被拒订单失败"),
+        }
+        results
+    }
+
+    /// 提交单笔订单（submit_batch 的单笔形式）
+    pub async fn submit_single(&self, order: &BatchOrder) -> Result<PostOrderResponse> {
+        self.submit_batch(std::slice::from_ref(order))
+            .await?
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| Err(anyhow::anyhow!("下单未返回结果，{}", STATUS_UNKNOWN)))
+    }
+
+    async fn submit_batch_once(&self, orders: &[BatchOrder]) -> Result<Vec<Result<PostOrderResponse>>> {
+        let client = self.client.get();
+        if orders.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let signer = LocalSigner::from_str(&self.private_key)?
+            .with_chain_id(Some(POLYGON));
+
+        // 并行构建所有订单
+        let built = futures::future::join_all(orders.iter().map(|o| async move {
+            let b = client
+                .limit_order()
+                .token_id(o.token_id)
+                .side(o.side.clone())
+                .price(o.price)
+                .size(o.size)
+                .order_type(o.order_type.clone());
+            match o.expiration {
+                Some(expiration) if matches!(&o.order_type, OrderType::GTD) => {
+                    b.expiration(expiration).build().await
+                }
+                _ => b.build().await,
+            }
+        }))
+        .await;
+        let mut unsigned = Vec::with_capacity(built.len());
+        for order in built {
+            unsigned.push(order?);
+        }
+
+        // 并行签名
+        let signed_results = futures::future::join_all(
+            unsigned.into_iter().map(|order| client.sign(&signer, order)),
+        )
+        .await;
+        let mut signed = Vec::with_capacity(signed_results.len());
+        for order in signed_results {
+            signed.push(order?);
+        }
+
+        // 按批量上限分组，各组并发提交；join_all 保持组顺序，拼接后即与输入顺序一致
+        let mut chunks = Vec::new();
+        while !signed.is_empty() {
+            let rest = signed.split_off(MAX_ORDERS_PER_BATCH.min(signed.len()));
+            chunks.push(std::mem::replace(&mut signed, rest));
+        }
+        let request_count = chunks.len();
+        let chunk_sizes: Vec<usize> = chunks.iter().map(Vec::len).collect();
+        let send_start = Instant::now();
+        for _ in 0..request_count {
+            rate_limit::acquire(Priority::Critical).await?;
+        }
+        let responses = futures::future::join_all(
+            chunks.into_iter().map(|chunk| client.post_orders(chunk)),
+        )
+        .await;
+
+        // 逐组展开：成功组的结果原样保留；失败组无法确认是否已被接受，该组每笔均标记为状态未知
+        let mut results: Vec<Result<PostOrderResponse>> = Vec::with_capacity(orders.len());
+        for (response, size) in responses.into_iter().zip(chunk_sizes) {
+            match response {
+                Ok(batch) if batch.len() == size => results.extend(batch.into_iter().map(Ok)),
+                Ok(batch) => {
+                    warn!(expected = size, actual = batch.len(), "批量下单返回结果数量不正确，该组按状态未知处理");
+                    results.extend((0..size).map(|_| {
+                        Err(anyhow::anyhow!(
+                            "批量下单返回结果数量不正确 | 期望:{} | 实际:{}，{}",
+                            size,
+                            batch.len(),
+                            STATUS_UNKNOWN
+                        ))
+                    }));
+                }
+                Err(e) => {
+                    rejection::record(&e.to_string());
+                    warn!(count = size, error = %e, "批量下单API调用失败，该组按状态未知处理");
+                    results.extend(
+                        (0..size).map(|_| Err(anyhow::anyhow!("批量下单API调用失败: {}，{}", e, STATUS_UNKNOWN))),
+                    );
+                }
+            }
+        }
+        for (order, result) in orders
+            .iter()
+            .zip(&results)
+            .filter_map(|(o, r)| r.as_ref().ok().filter(|r| !r.success).map(|r| (o, r)))
+        {
+            rejection::record_for(result.error_msg.as_deref().unwrap_or(""), &[order.token_id]);
+        }
+
+        debug!(
+            order_count = orders.len(),
+            request_count,
+            send_ms = send_start.elapsed().as_millis() as u64,
+            "批量下单完成"
+        );
+        Ok(results)
+    }
+
+    /// 构建、签名并提交单笔买单
+    async fn post_buy(
+        &self,
+        token_id: U256,
+        price: Decimal,
+        size: Decimal,
+        order_type: OrderType,
+    ) -> Result<PostOrderResponse> {
+        let client = self.client.get();
+        let signer = LocalSigner::from_str(&self.private_key)?
+            .with_chain_id(Some(POLYGON));
+        let b = client
+            .limit_order()
+            .token_id(token_id)
+            .side(Side::Buy)
+            .price(price)
+            .size(size)
+            .order_type(order_type.clone());
+        let order = if matches!(order_type, OrderType::GTD) {
+            b.expiration(Utc::now() + chrono::Duration::seconds(self.gtd_secs() as i64))
+                .build()
+                .await?
         } else {
-            self.slippage[0]
+            b.build().await?
+        };
+        let signed = client.sign(&signer, order).await?;
+        rate_limit::acquire(Priority::Critical).await?;
+        client.post_order(signed).await.map_err(|e| {
+            rejection::record_for(&e.to_string(), &[token_id]);
+            anyhow::anyhow!("下单失败: {}", e)
+        })
+    }
+
+    /// 顺序执行套利：先以 FAK 吃更深（同深度取更便宜）的一腿，再以
+    /// min(另一腿卖一 + 滑点, 1 − 首腿成交均价 − fee) 为限价挂第二腿（数量 = 首腿成交量按第二腿数量步长对齐），
+    /// 无论第二腿订单簿如何变动，两腿总成本都不超过 1。第二腿按 arbitrage_order_type 挂单（FOK/FAK 时改用 GTC），
+    /// 未全部成交时留在簿上，由调用方登记跟踪（结果中第二腿 filled < size）。
+    #[tracing::instrument(name = "execute_sequenced_pair", skip_all, fields(market_id = %opp.market_id))]
+    pub async fn execute_sequenced_pair(
+        &self,
+        opp: &ArbitrageOpportunity,
+        symbol: &str,
+        yes_dir: &str,
+        no_dir: &str,
+        fee_per_share: Decimal,
+    ) -> Result<OrderPairResult> {
+        if reject_policy::blocked(&[opp.yes_token_id, opp.no_token_id]) {
+            return Err(anyhow::anyhow!("拒单处置策略暂停开仓，未提交"));
+        }
+        let pair_id = Uuid::new_v4().to_string();
+        let order_size = rules::order_size(
+            &[opp.yes_token_id, opp.no_token_id],
+            opp.yes_size.min(opp.no_size).min(self.max_order_size),
+        )
+        .ok_or_else(|| anyhow::anyhow!("下单数量低于交易所最小下单数量，未提交"))?;
+        let yes_limit = rules::order_price(opp.yes_token_id, opp.yes_ask_price + self.slippage.for_leg(Leg::Yes, symbol, yes_dir));
+        let no_limit = rules::order_price(opp.no_token_id, opp.no_ask_price + self.slippage.for_leg(Leg::No, symbol, no_dir));
+
+        // 首腿：更深的一腿；深度相同取更便宜的
+        let yes_first = opp.yes_size > opp.no_size
+            || (opp.yes_size == opp.no_size && opp.yes_ask_price <= opp.no_ask_price);
+        let (first_token, first_limit, second_token, second_limit) = if yes_first {
+            (opp.yes_token_id, yes_limit, opp.no_token_id, no_limit)
+        } else {
+            (opp.no_token_id, no_limit, opp.yes_token_id, yes_limit)
+        };
+        let first_label = if yes_first { "YES" } else { "NO" };
+
+        let start = Instant::now();
+        let first = within(self.leg_timeout, self.post_buy(first_token, first_limit, order_size, OrderType::FAK)).await?;
+        latency::record(Stage::Post, start.elapsed());
+        let first_filled = first.taking_amount;
+        if first_filled <= dec!(0) {
+            return Err(anyhow::anyhow!(
+                "{}: 首腿{}未成交 | {}",
+                NOTHING_FILLED,
+                first_label,
+                first.error_msg.as_deref().unwrap_or("订单簿中无匹配订单")
+            ));
+        }
+        // 首腿成交均价 = 花费 / 成交份数（无花费数据时按限价保守估计）
+        let first_avg = if first.making_amount > dec!(0) {
+            first.making_amount / first_filled
+        } else {
+            first_limit
+        };
+
+        // 第二腿限价上限：1 − 首腿均价 − 费用，向下取到第二腿市场的价格单位
+        let cap = rules::floor_to_tick(second_token, dec!(1) - first_avg - fee_per_share);
+        let second_price = second_limit.min(cap);
+        let second_type = match self.arbitrage_order_type {
+            OrderType::GTD => OrderType::GTD,
+            _ => OrderType::GTC,
+        };
+        info!(
+            "🔗 顺序下单 | 首腿{} 成交:{}份 均价:{:.4} | 第二腿限价:{} (上限:{})",
+            first_label, first_filled, first_avg, second_price, cap
+        );
+        // 第二腿数量：首腿成交量按第二腿数量步长对齐，且在限价上限下满足交易所最小下单金额（> $1）
+        let second_size = rules::order_size(&[second_token], first_filled);
+        let (second_order_id, second_filled, second_size) = match second_size {
+            _ if second_price <= dec!(0) => {
+                warn!("⚠️ 第二腿限价上限不为正，不挂单 | 订单对ID:{}", &pair_id[..8]);
+                (String::new(), dec!(0), first_filled)
+            }
+            None => {
+                warn!(
+                    "⚠️ 首腿成交量低于第二腿最小下单数量，不挂单 | 订单对ID:{} | 成交:{} | 最小:{}",
+                    &pair_id[..8],
+                    first_filled,
+                    rules::min_order_size(second_token)
+                );
+                (String::new(), dec!(0), first_filled)
+            }
+            Some(size) if second_price * size <= dec!(1) => {
+                warn!(
+                    "⚠️ 第二腿金额不满足交易所最小要求（须 > $1），不挂单 | 订单对ID:{} | {} × {} = {:.2} USD",
+                    &pair_id[..8],
+                    second_price,
+                    size,
+                    second_price * size
+                );
+                (String::new(), dec!(0), first_filled)
+            }
+            Some(size) => match within(self.leg_timeout, self.post_buy(second_token, second_price, size, second_type)).await {
+                Ok(second) => (second.order_id.clone(), second.taking_amount, size),
+                Err(e) => {
+                    warn!(error = %e, "第二腿下单失败 | 订单对ID:{}", &pair_id[..8]);
+                    (String::new(), dec!(0), first_filled)
+                }
+            },
+        };
+
+        let (yes_order_id, no_order_id, yes_filled, no_filled, yes_price, no_price) = if yes_first {
+            (first.order_id.clone(), second_order_id, first_filled, second_filled, first_limit, second_price)
+        } else {
+            (second_order_id, first.order_id.clone(), second_filled, first_filled, second_price, first_limit)
+        };
+        if yes_filled > dec!(0) && no_filled > dec!(0) {
+            info!(
+                "{}",
+                tr!(Msg::ArbSuccess, &pair_id[..8], yes_filled, no_filled, yes_filled.min(no_filled))
+            );
+        } else {
+            let other_side = if yes_first { "NO" } else { "YES" };
+            warn!("{}", tr!(Msg::ArbOneSided, &pair_id[..8], first_label, first_filled, other_side));
         }
+
+        Ok(OrderPairResult {
+            pair_id,
+            yes_order_id,
+            no_order_id,
+            yes_filled,
+            no_filled,
+            yes_size: if yes_first { first_filled } else { second_size },
+            no_size: if yes_first { second_size } else { first_filled },
+            yes_price,
+            no_price,
+            success: true,
+            child_orders: Vec::new(),
+        })
+    }
+
+    /// 记录一次成对下单的两腿成交，供自适应滑点统计该币种较难成交的一腿
+    pub fn record_leg_fills(&self, symbol: &str, result: &OrderPairResult) {
+        self.slippage
+            .record(symbol, (result.yes_filled, result.yes_size), (result.no_filled, result.no_size));
     }
 
     /// 执行套利交易（使用post_orders批量提交YES和NO订单；订单类型由 arbitrage_order_type 配置，GTD 时配合 gtd_expiration_secs）
     /// yes_dir / no_dir：涨跌方向 "↑" "↓" "−" 或 ""，用于按方向分配滑点（仅下降=second，上涨与持平=first）
+    /// symbol：币种状态键（如 btc:15m），用于自适应滑点；空字符串时不加宽
+    #[tracing::instrument(name = "execute_arbitrage_pair", skip_all, fields(market_id = %opp.market_id))]
     pub async fn execute_arbitrage_pair(
         &self,
         opp: &ArbitrageOpportunity,
+        symbol: &str,
         yes_dir: &str,
         no_dir: &str,
     ) -> Result<OrderPairResult> {
+        let client = self.client.get();
+        if reject_policy::blocked(&[opp.yes_token_id, opp.no_token_id]) {
+            return Err(anyhow::anyhow!("拒单处置策略暂停开仓，未提交"));
+        }
         // 性能计时：总开始时间
         let total_start = Instant::now();
+        let gtd_secs = self.gtd_secs();
+        let leg_order_type = self.leg_order_type();
         
         // 这个日志已经在main.rs中打印了，这里不再重复打印
-        let expiry_info = if matches!(self.arbitrage_order_type, OrderType::GTD) {
-            format!("过期:{}秒", self.gtd_expiration_secs)
+        let expiry_info = if matches!(leg_order_type, OrderType::GTD) {
+            format!("过期:{}秒", gtd_secs)
         } else {
             "无过期".to_string()
         };
         debug!(
             market_id = %opp.market_id,
             profit_pct = %opp.profit_percentage,
-            order_type = %self.arbitrage_order_type,
+            order_type = %leg_order_type,
             "开始执行套利交易（批量下单，订单类型:{}，{}）",
-            self.arbitrage_order_type,
+            leg_order_type,
             expiry_info
         );
 
@@ -165,19 +774,25 @@ impl TradingExecutor {
         let yes_token_id = U256::from_str(&opp.yes_token_id.to_string())?;
         let no_token_id = U256::from_str(&opp.no_token_id.to_string())?;
 
-        let order_size = opp.yes_size.min(opp.no_size).min(self.max_order_size);
+        // 截断后再对齐数量步长并校验最小下单数量，不满足时不提交（必被拒）
+        let order_size = rules::order_size(
+            &[opp.yes_token_id, opp.no_token_id],
+            opp.yes_size.min(opp.no_size).min(self.max_order_size),
+        )
+        .ok_or_else(|| anyhow::anyhow!("下单数量低于交易所最小下单数量，未提交"))?;
 
         // 生成订单对ID
         let pair_id = Uuid::new_v4().to_string();
 
         // 计算过期时间：当前时间 + 配置的过期时间
-        let expiration = Utc::now() + chrono::Duration::seconds(self.gtd_expiration_secs as i64);
+        let expiration = Utc::now() + chrono::Duration::seconds(gtd_secs as i64);
 
-        // 滑点按涨跌方向分配：上涨=first，下降/持平=second
-        let yes_slippage_apply = self.slippage_for_direction(yes_dir);
-        let no_slippage_apply = self.slippage_for_direction(no_dir);
-        let yes_price_with_slippage = (opp.yes_ask_price + yes_slippage_apply).min(dec!(1.0));
-        let no_price_with_slippage = (opp.no_ask_price + no_slippage_apply).min(dec!(1.0));
+        // 滑点按腿与涨跌方向分配（仅下降=second，上涨与持平=first），再加该币种较难成交一腿的自适应加宽
+        let yes_slippage_apply = self.slippage.for_leg(Leg::Yes, symbol, yes_dir);
+        let no_slippage_apply = self.slippage.for_leg(Leg::No, symbol, no_dir);
+        // 按价格单位向下取整，并限制在 [价格单位, 1 − 价格单位] 内（交易所拒绝 1.0 及以上的买价）
+        let yes_price_with_slippage = rules::order_price(opp.yes_token_id, opp.yes_ask_price + yes_slippage_apply);
+        let no_price_with_slippage = rules::order_price(opp.no_token_id, opp.no_ask_price + no_slippage_apply);
         
         // 打印选档信息（加滑点后的价格）
         info!(
@@ -186,8 +801,8 @@ impl TradingExecutor {
             no_price_with_slippage, order_size
         );
         
-        let expiry_suffix = if matches!(self.arbitrage_order_type, OrderType::GTD) {
-            format!(" | GTD {}s", self.gtd_expiration_secs)
+        let expiry_suffix = if matches!(leg_order_type, OrderType::GTD) {
+            format!(" | GTD {}s", gtd_secs)
         } else {
             String::new()
         };
@@ -195,51 +810,70 @@ impl TradingExecutor {
             "📤 下单 | YES {:.4}→{:.4}×{} NO {:.4}→{:.4}×{} | {}{}",
             opp.yes_ask_price, yes_price_with_slippage, order_size,
             opp.no_ask_price, no_price_with_slippage, order_size,
-            self.arbitrage_order_type, expiry_suffix
+            leg_order_type, expiry_suffix
         );
 
         // 下单前检查：双边金额均须 > $1（交易所最小下单金额）
         let yes_amount_usd = yes_price_with_slippage * order_size;
         let no_amount_usd = no_price_with_slippage * order_size;
         if yes_amount_usd <= dec!(1) || no_amount_usd <= dec!(1) {
-            warn!(
-                "⏭️ 跳过下单 | YES金额:{:.2} USD NO金额:{:.2} USD | 双边均须 > $1",
-                yes_amount_usd, no_amount_usd
-            );
+            warn!("{}", tr!(Msg::OrderSkippedMinNotional, yes_amount_usd, no_amount_usd));
             return Err(anyhow::anyhow!(
                 "下单金额不满足交易所最小要求: YES {:.2} USD, NO {:.2} USD，双边均须 > $1",
                 yes_amount_usd, no_amount_usd
             ));
         }
 
+        // 单腿名义金额超过子单上限时拆成多笔子单批量提交
+        let child_sizes = self.child_sizes(
+            order_size,
+            yes_price_with_slippage.max(no_price_with_slippage),
+            yes_price_with_slippage.min(no_price_with_slippage),
+            rules::min_order_size(opp.yes_token_id).max(rules::min_order_size(opp.no_token_id)),
+        );
+        if child_sizes.len() > 1 {
+            return self
+                .execute_split_pair(opp, pair_id, &child_sizes, yes_price_with_slippage, no_price_with_slippage, expiration)
+                .await;
+        }
+
+        // 顺序提交时卖一深度更薄（更容易被抢走）的一腿先发，且首腿一律以 FAK 提交：
+        // 未成交部分立即撤销，首腿未成交或部分成交时不会留下无人跟踪的挂单
+        let sequential_yes_first = opp.yes_size <= opp.no_size;
+        let (yes_order_type, no_order_type) = match self.leg_submission {
+            LegSubmission::Sequential if sequential_yes_first => (OrderType::FAK, leg_order_type.clone()),
+            LegSubmission::Sequential => (leg_order_type.clone(), OrderType::FAK),
+            LegSubmission::Batched | LegSubmission::Parallel => (leg_order_type.clone(), leg_order_type.clone()),
+        };
+
         // 性能计时：并行构建YES和NO订单开始
         let build_start = Instant::now();
         
         // 并行构建YES和NO订单；仅 GTD 时设置 expiration（SDK 规定非 GTD 不可设过期）
         let (yes_order, no_order) = tokio::join!(
             async {
-                let b = self.client
+                let b = client
                     .limit_order()
                     .token_id(yes_token_id)
                     .side(Side::Buy)
                     .price(yes_price_with_slippage)
                     .size(order_size)
-                    .order_type(self.arbitrage_order_type.clone());
-                if matches!(&self.arbitrage_order_type, OrderType::GTD) {
+                    .order_type(yes_order_type.clone());
+                if matches!(&yes_order_type, OrderType::GTD) {
                     b.expiration(expiration).build().await
                 } else {
                     b.build().await
                 }
             },
             async {
-                let b = self.client
+                let b = client
                     .limit_order()
                     .token_id(no_token_id)
                     .side(Side::Buy)
                     .price(no_price_with_slippage)
                     .size(order_size)
-                    .order_type(self.arbitrage_order_type.clone());
-                if matches!(&self.arbitrage_order_type, OrderType::GTD) {
+                    .order_type(no_order_type.clone());
+                if matches!(&no_order_type, OrderType::GTD) {
                     b.expiration(expiration).build().await
                 } else {
                     b.build().await
@@ -249,6 +883,7 @@ impl TradingExecutor {
         
         let yes_order = yes_order?;
         let no_order = no_order?;
+        latency::record(Stage::Build, build_start.elapsed());
         let build_elapsed = build_start.elapsed().as_millis();
 
         // 性能计时：并行签名开始
@@ -260,26 +895,110 @@ impl TradingExecutor {
         
         // 并行签名YES和NO订单
         let (signed_yes_result, signed_no_result) = tokio::join!(
-            self.client.sign(&signer, yes_order),
-            self.client.sign(&signer, no_order)
+            client.sign(&signer, yes_order),
+            client.sign(&signer, no_order)
         );
         
         let signed_yes = signed_yes_result?;
         let signed_no = signed_no_result?;
+        latency::record(Stage::Sign, sign_start.elapsed());
         let sign_elapsed = sign_start.elapsed().as_millis();
 
         // 性能计时：发送订单开始
         let send_start = Instant::now();
         
-        // 单价高的排前面发送；提交后需按相同顺序从 results 中解析 yes_result / no_result
-        let yes_first = yes_price_with_slippage >= no_price_with_slippage;
-        let orders_to_send: Vec<_> = if yes_first {
-            vec![signed_yes, signed_no]
+        // 批量/并行：单价高的排前面发送；顺序提交：按上面选定的首腿先发。
+        // 提交后需按相同顺序从 results 中解析 yes_result / no_result
+        let yes_first = match self.leg_submission {
+            LegSubmission::Sequential => sequential_yes_first,
+            LegSubmission::Batched | LegSubmission::Parallel => yes_price_with_slippage >= no_price_with_slippage,
+        };
+        let (first_order, second_order) = if yes_first {
+            (signed_yes, signed_no)
         } else {
-            vec![signed_no, signed_yes]
+            (signed_no, signed_yes)
         };
-        let results = match self.client.post_orders(orders_to_send).await {
+        let timeout = self.leg_timeout;
+        let request_count = if matches!(self.leg_submission, LegSubmission::Batched) { 1 } else { 2 };
+        for _ in 0..request_count {
+            rate_limit::acquire(Priority::Critical).await?;
+        }
+        // 第二腿的下单数量：顺序提交且首腿部分成交时按首腿成交量下单
+        let mut second_size = order_size;
+        let post_result: Result<Vec<PostOrderResponse>> = match self.leg_submission {
+            LegSubmission::Batched => {
+                within(timeout, client.post_orders(vec![first_order, second_order])).await
+            }
+            LegSubmission::Parallel => {
+                let (first, second) = tokio::join!(
+                    within(timeout, client.post_order(first_order)),
+                    within(timeout, client.post_order(second_order))
+                );
+                first.and_then(|first| second.map(|second| vec![first, second]))
+            }
+            LegSubmission::Sequential => match within(timeout, client.post_order(first_order)).await {
+                Ok(first) if first.taking_amount <= dec!(0) => {
+                    // 首腿未成交（FAK 已撤销）则不提交第二腿，避免单边
+                    let reason = first.error_msg.clone().unwrap_or_default();
+                    rejection::record_for(&reason, &[opp.yes_token_id, opp.no_token_id]);
+                    return Err(anyhow::anyhow!(
+                        "{}: 首腿{}未成交，未提交第二腿 | {}",
+                        NOTHING_FILLED,
+                        if yes_first { "YES" } else { "NO" },
+                        if reason.is_empty() { "订单簿中无匹配订单" } else { reason.as_str() }
+                    ));
+                }
+                Ok(first) if first.taking_amount >= order_size => within(timeout, client.post_order(second_order))
+                    .await
+                    .map(|second| vec![first, second]),
+                Ok(first) => {
+                    // 首腿部分成交：第二腿改按首腿成交量下单，保持两腿数量一致（预先签好的全量订单不再使用）
+                    let (second_token, second_price) = if yes_first {
+                        (no_token_id, no_price_with_slippage)
+                    } else {
+                        (yes_token_id, yes_price_with_slippage)
+                    };
+                    match rules::order_size(&[second_token], first.taking_amount) {
+                        Some(size) => {
+                            second_size = size;
+                            within(timeout, self.post_buy(second_token, second_price, size, leg_order_type.clone()))
+                                .await
+                                .map(|second| vec![first, second])
+                        }
+                        None => {
+                            warn!(
+                                "⚠️ 首腿部分成交量低于第二腿最小下单数量，未提交第二腿 | 订单对ID:{} | 成交:{}",
+                                &pair_id[..8],
+                                first.taking_amount
+                            );
+                            // 第二腿按未下单处理（数量记为首腿成交量），由单腿风控对冲或平仓
+                            let first_filled = first.taking_amount;
+                            let (yes_filled, no_filled) =
+                                if yes_first { (first_filled, dec!(0)) } else { (dec!(0), first_filled) };
+                            let (yes_order_id, no_order_id) =
+                                if yes_first { (first.order_id, String::new()) } else { (String::new(), first.order_id) };
+                            return Ok(OrderPairResult {
+                                pair_id,
+                                yes_order_id,
+                                no_order_id,
+                                yes_filled,
+                                no_filled,
+                                yes_size: if yes_first { order_size } else { first_filled },
+                                no_size: if yes_first { first_filled } else { order_size },
+                                yes_price: yes_price_with_slippage,
+                                no_price: no_price_with_slippage,
+                                success: true,
+                                child_orders: Vec::new(),
+                            });
+                        }
+                    }
+                }
+                Err(e) => Err(e),
+            },
+        };
+        let mut results = match post_result {
             Ok(results) => {
+                latency::record(Stage::Post, send_start.elapsed());
                 let send_elapsed = send_start.elapsed().as_millis();
                 let total_elapsed = total_start.elapsed().as_millis();
                 
@@ -293,9 +1012,11 @@ impl TradingExecutor {
             Err(e) => {
                 let send_elapsed = send_start.elapsed().as_millis();
                 let total_elapsed = total_start.elapsed().as_millis();
+                rejection::record_for(&e.to_string(), &[opp.yes_token_id, opp.no_token_id]);
                 
                 error!(
-                    "❌ 批量下单API调用失败 | 订单对ID:{} | YES价格:{} (含滑点) | NO价格:{} (含滑点) | 数量:{} | 构建耗时:{}ms | 签名耗时:{}ms | 发送耗时:{}ms | 总耗时:{}ms | 错误:{}",
+                    "❌ 下单API调用失败（{}） | 订单对ID:{} | YES价格:{} (含滑点) | NO价格:{} (含滑点) | 数量:{} | 构建耗时:{}ms | 签名耗时:{}ms | 发送耗时:{}ms | 总耗时:{}ms | 错误:{}",
+                    self.leg_submission.as_str(),
                     &pair_id[..8],
                     yes_price_with_slippage,
                     no_price_with_slippage,
@@ -306,7 +1027,7 @@ impl TradingExecutor {
                     total_elapsed,
                     e
                 );
-                return Err(anyhow::anyhow!("批量下单API调用失败: {}", e));
+                return Err(anyhow::anyhow!("下单API调用失败: {}", e));
             }
         };
         
@@ -323,6 +1044,15 @@ impl TradingExecutor {
             ));
         }
         
+        // 故障注入：把后提交的一腿改为被拒未成交（最常见的单腿场景），演练单腿恢复
+        if chaos::hit(chaos::Fault::OrderReject) {
+            let leg = &mut results[1];
+            leg.success = false;
+            leg.taking_amount = dec!(0);
+            leg.making_amount = dec!(0);
+            leg.error_msg = Some("[chaos] injected rejection".to_string());
+        }
+
         // 提取YES和NO订单的结果（提交顺序为单价高者在前，需按 yes_first 映射）
         let (yes_result, no_result) = if yes_first {
             (&results[0], &results[1])
@@ -331,6 +1061,12 @@ impl TradingExecutor {
         };
 
         // 订单返回结果详情已移除，只保留关键信息在后续日志中
+        // 被拒绝的腿按原因分类计数
+        for result in [yes_result, no_result] {
+            if !result.success {
+                rejection::record_for(result.error_msg.as_deref().unwrap_or(""), &[opp.yes_token_id, opp.no_token_id]);
+            }
+        }
 
         // 检查成交数量（GTD订单的关键指标）
         let yes_filled = yes_result.taking_amount;
@@ -387,7 +1123,8 @@ impl TradingExecutor {
             );
 
             return Err(anyhow::anyhow!(
-                "套利失败: YES和NO订单都未成交 | YES: {}, NO: {}",
+                "{}: YES和NO订单都未成交 | YES: {}, NO: {}",
+                NOTHING_FILLED,
                 yes_error_simple,
                 no_error_simple
             ));
@@ -447,25 +1184,16 @@ impl TradingExecutor {
         // 根据成交情况打印不同的日志
         if yes_filled > dec!(0) && no_filled > dec!(0) {
             info!(
-                "✅ 套利交易成功 | 订单对ID:{} | YES成交:{}份 | NO成交:{}份 | 总成交:{}份",
-                &pair_id[..8],
-                yes_filled,
-                no_filled,
-                yes_filled.min(no_filled)
+                "{}",
+                tr!(Msg::ArbSuccess, &pair_id[..8], yes_filled, no_filled, yes_filled.min(no_filled))
             );
         } else if yes_filled > dec!(0) || no_filled > dec!(0) {
             let side = if yes_filled > dec!(0) { "YES" } else { "NO" };
             let filled = if yes_filled > dec!(0) { yes_filled } else { no_filled };
             let other_side = if yes_filled > dec!(0) { "NO" } else { "YES" };
-            warn!(
-                "⚠️ 单边成交 | {} | {} 成交 {} 份，{} 未成交（已交风控）",
-                &pair_id[..8], side, filled, other_side
-            );
+            warn!("{}", tr!(Msg::ArbOneSided, &pair_id[..8], side, filled, other_side));
         } else {
-            warn!(
-                "❌ 套利失败 | 订单对ID:{} | YES和NO都未成交",
-                &pair_id[..8]
-            );
+            warn!("{}", tr!(Msg::ArbNoneFilled, &pair_id[..8]));
         }
 
         Ok(OrderPairResult {
@@ -474,9 +1202,145 @@ impl TradingExecutor {
             no_order_id: no_result.order_id.clone(),
             yes_filled,
             no_filled,
+            yes_size: if yes_first { order_size } else { second_size },
+            no_size: if yes_first { second_size } else { order_size },
+            yes_price: yes_price_with_slippage,
+            no_price: no_price_with_slippage,
+            success: true,
+            child_orders: Vec::new(),
+        })
+    }
+
+    /// 按子单名义金额上限拆分每腿数量：笔数 = ⌈数量 × 较高限价 / 上限⌉（每腿最多 MAX_CHILD_ORDERS_PER_LEG 笔，
+    /// 超出部分不下单），各笔均分、余数归最后一笔；每笔在较低限价下也须 > $1（交易所最小下单金额）且不低于
+    /// 最小下单数量，不满足时减少笔数。
+    /// 未设置上限或无需拆分时返回只含 order_size 的一项。
+    fn child_sizes(&self, order_size: Decimal, max_price: Decimal, min_price: Decimal, min_size: Decimal) -> Vec<Decimal> {
+        let Some(max_notional) = self.child_max_notional else {
+            return vec![order_size];
+        };
+        if max_price <= dec!(0) || order_size * max_price <= max_notional {
+            return vec![order_size];
+        }
+        let per_child = (max_notional / max_price).round_dp_with_strategy(2, rust_decimal::RoundingStrategy::ToZero);
+        let needed = (order_size / per_child.max(dec!(0.01))).ceil();
+        let mut count = needed.to_usize().unwrap_or(1).clamp(1, MAX_CHILD_ORDERS_PER_LEG);
+        let total = order_size.min(per_child * Decimal::from(count as u64));
+        let child = |n: usize| (total / Decimal::from(n as u64)).round_dp_with_strategy(2, rust_decimal::RoundingStrategy::ToZero);
+        while count > 1 && (child(count) * min_price <= dec!(1) || child(count) < min_size) {
+            count -= 1;
+        }
+        let base = child(count);
+        let mut sizes = vec![base; count - 1];
+        sizes.push(total - base * Decimal::from((count - 1) as u64));
+        sizes
+    }
+
+    /// 拆单执行：两腿子单交替排列（YES₁ NO₁ YES₂ NO₂ …）一次批量提交，汇总各腿成交。
+    /// 拆单时统一走批量提交，不区分 LEG_SUBMISSION；结果中的订单 ID 为每腿第一笔子单，全部子单见 child_orders。
+    /// 只有首次提交受 leg_timeout 限制，被拒子单的补授权与重试在计时之外进行。
+    async fn execute_split_pair(
+        &self,
+        opp: &ArbitrageOpportunity,
+        pair_id: String,
+        child_sizes: &[Decimal],
+        yes_price: Decimal,
+        no_price: Decimal,
+        expiration: chrono::DateTime<Utc>,
+    ) -> Result<OrderPairResult> {
+        let order_size: Decimal = child_sizes.iter().sum();
+        let order_type = self.leg_order_type();
+        let expiration = matches!(order_type, OrderType::GTD).then_some(expiration);
+        let orders: Vec<BatchOrder> = child_sizes
+            .iter()
+            .flat_map(|size| {
+                [(opp.yes_token_id, yes_price), (opp.no_token_id, no_price)].map(|(token_id, price)| BatchOrder {
+                    token_id,
+                    side: Side::Buy,
+                    price,
+                    size: *size,
+                    order_type: order_type.clone(),
+                    expiration,
+                })
+            })
+            .collect();
+        info!(
+            "✂️ 拆单 | {} | 每腿{}笔 | 子单数量:{:?} | 合计:{}份",
+            &pair_id[..8],
+            child_sizes.len(),
+            child_sizes,
+            order_size
+        );
+
+        let send_start = Instant::now();
+        let results = within(self.leg_timeout, self.submit_batch_once(&orders)).await?;
+        latency::record(Stage::Post, send_start.elapsed());
+        let results = self.retry_rejected(&orders, results).await;
+
+        // 交替排列：偶数位为 YES，奇数位为 NO；保留每笔已被接受的子单，供排队跟踪与撤单
+        let (mut yes_filled, mut no_filled) = (dec!(0), dec!(0));
+        let mut child_orders = Vec::with_capacity(orders.len());
+        let mut unknown = 0;
+        for (i, (order, result)) in orders.iter().zip(&results).enumerate() {
+            let Ok(result) = result else {
+                unknown += 1;
+                continue;
+            };
+            if i % 2 == 0 {
+                yes_filled += result.taking_amount;
+            } else {
+                no_filled += result.taking_amount;
+            }
+            if !result.order_id.is_empty() {
+                child_orders.push(ChildOrder {
+                    order_id: result.order_id.clone(),
+                    token_id: order.token_id,
+                    price: order.price,
+                    size: order.size,
+                    filled: result.taking_amount,
+                });
+            }
+        }
+        let first_id = |token_id: U256| {
+            child_orders
+                .iter()
+                .find(|c| c.token_id == token_id)
+                .map(|c| c.order_id.clone())
+                .unwrap_or_default()
+        };
+        if yes_filled == dec!(0) && no_filled == dec!(0) {
+            if unknown > 0 {
+                return Err(anyhow::anyhow!("拆单后 {} 笔子单{}，其余均未成交", unknown, STATUS_UNKNOWN));
+            }
+            warn!("{}", tr!(Msg::ArbNoneFilled, &pair_id[..8]));
+            return Err(anyhow::anyhow!("{}: 拆单后 YES 和 NO 子单都未成交", NOTHING_FILLED));
+        }
+        if yes_filled > dec!(0) && no_filled > dec!(0) {
+            info!(
+                "{}",
+                tr!(Msg::ArbSuccess, &pair_id[..8], yes_filled, no_filled, yes_filled.min(no_filled))
+            );
+        } else {
+            let (side, filled, other_side) = if yes_filled > dec!(0) {
+                ("YES", yes_filled, "NO")
+            } else {
+                ("NO", no_filled, "YES")
+            };
+            warn!("{}", tr!(Msg::ArbOneSided, &pair_id[..8], side, filled, other_side));
+        }
+
+        Ok(OrderPairResult {
+            pair_id,
+            yes_order_id: first_id(opp.yes_token_id),
+            no_order_id: first_id(opp.no_token_id),
+            yes_filled,
+            no_filled,
             yes_size: order_size,
             no_size: order_size,
+            yes_price,
+            no_price,
             success: true,
+            child_orders,
         })
     }
 }
@@ -0,0 +1,114 @@
+//! 冰山挂单：ARBITRAGE_ORDER_TYPE 为 GTC/GTD 且设置了 ICEBERG_DISPLAY_SIZE 时，套利两腿先以 FAK 吃掉可立即成交的部分，
+//! 未成交的剩余不再整笔挂出，而是每次只挂显示数量的一笔子单（同一限价），子单全部成交后自动补挂下一笔，
+//! 直到剩余全部成交或到期，避免大额挂单暴露意图、被对手盘针对性撤单或抬价。到期时撤掉仍在簿上的子单。
+
+use polymarket_client_sdk::clob::types::{OrderType, Side};
+use polymarket_client_sdk::types::{Decimal, U256};
+use rust_decimal_macros::dec;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+use super::executor::{BatchOrder, TradingExecutor};
+use crate::market::rules;
+use crate::utils::{journal, metrics};
+
+/// 查询显示子单成交进度的间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 在 price 上以显示数量 display 分批挂出 total 份买单，lifetime 后停止；返回累计成交数量
+pub async fn run(
+    executor: Arc<TradingExecutor>,
+    token_id: U256,
+    price: Decimal,
+    total: Decimal,
+    display: Decimal,
+    lifetime: Duration,
+) -> Decimal {
+    let deadline = Instant::now() + lifetime;
+    let order_type = match executor.resting_lifetime() {
+        Some(Some(_)) => OrderType::GTD,
+        _ => OrderType::GTC,
+    };
+    // 每笔子单须 > $1（交易所最小下单金额）且不低于最小下单数量
+    let min_display = ((dec!(1) / price.max(dec!(0.01))) * dec!(100)).floor() / dec!(100) + dec!(0.01);
+    let display = display.max(min_display).max(rules::min_order_size(token_id));
+    let mut remaining = (total * dec!(100)).floor() / dec!(100);
+    let mut filled = dec!(0);
+    let mut refills = 0u32;
+    info!(
+        "🧊 冰山挂单开始 | token_id={:#x} | 价格:{} | 总量:{} | 显示:{}",
+        token_id, price, remaining, display
+    );
+
+    while remaining * price > dec!(1) && remaining >= rules::min_order_size(token_id) && Instant::now() < deadline {
+        let child = display.min(remaining);
+        let expiration = chrono::Utc::now()
+            + chrono::Duration::from_std(deadline.saturating_duration_since(Instant::now())).unwrap_or_default();
+        let order = BatchOrder {
+            token_id,
+            side: Side::Buy,
+            price,
+            size: child,
+            order_type: order_type.clone(),
+            expiration: Some(expiration),
+        };
+        let order_id = match executor.submit_single(&order).await {
+            Ok(r) if r.success => r.order_id,
+            Ok(r) => {
+                warn!(token_id = %token_id, error = r.error_msg.as_deref().unwrap_or(""), "冰山：子单被拒，停止");
+                break;
+            }
+            Err(e) => {
+                warn!(token_id = %token_id, error = %e, "冰山：子单提交失败，停止");
+                metrics::incr("iceberg_post_failed");
+                break;
+            }
+        };
+
+        // 等待子单全部成交；到期则撤单并按已成交数量结算
+        let mut matched = dec!(0);
+        loop {
+            if let Ok(m) = executor.order_matched(&order_id).await {
+                matched = m.min(child);
+            }
+            if matched >= child {
+                break;
+            }
+            if Instant::now() >= deadline {
+                if let Err(e) = executor.cancel_order_ids(&[order_id.as_str()]).await {
+                    debug!(order_id = %order_id, error = %e, "冰山：到期撤单失败（可能已成交或已被撤）");
+                }
+                // 撤单与成交之间可能有竞争，撤单后再查一次
+                if let Ok(m) = executor.order_matched(&order_id).await {
+                    matched = m.min(child);
+                }
+                break;
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+        filled += matched;
+        remaining -= matched;
+        if matched < child {
+            break;
+        }
+        refills += 1;
+        metrics::incr("iceberg_refills");
+        journal::record(
+            "iceberg_refill",
+            json!({
+                "token_id": format!("{:#x}", token_id),
+                "price": price.to_string(),
+                "filled": filled.to_string(),
+                "remaining": remaining.to_string(),
+            }),
+        );
+    }
+
+    info!(
+        "🧊 冰山挂单结束 | token_id={:#x} | 已成交:{} | 未成交:{} | 补挂{}次",
+        token_id, filled, remaining, refills
+    );
+    filled
+}
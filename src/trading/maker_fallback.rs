@@ -0,0 +1,134 @@
+//! maker 兜底：吃单（FOK/FAK）两腿均未成交时，可选在原先有利可图的价位（机会出现时两腿的卖一价）
+//! 挂出两腿 GTC 买单，留在簿上等待对手盘吃单，直到市场结束前 MAKER_FALLBACK_CANCEL_BEFORE_END_MINUTES 分钟。
+//! 挂单登记到 OrderStore，到期由其自动撤单，保证没有挂单带进结算。
+//! 只挂出一腿时立即撤掉，避免单边挂单。挂单占用 maker 兜底额度（RISK_BUDGET_MAKER_FALLBACK_USDC），
+//! 两腿都挂出时提交预留，否则归还。
+
+use chrono::{DateTime, Utc};
+use polymarket_client_sdk::clob::types::{OrderType, Side};
+use serde_json::json;
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+use super::executor::{BatchOrder, TradingExecutor};
+use super::order_store::{OrderStore, TrackedOrder};
+use crate::market::rules;
+use crate::monitor::arbitrage::ArbitrageOpportunity;
+use crate::risk::positions::ExposureReservation;
+use crate::utils::{journal, metrics};
+
+pub struct MakerFallback {
+    store: Arc<OrderStore>,
+    /// 市场结束前多久撤单
+    cancel_before_end: chrono::Duration,
+}
+
+impl MakerFallback {
+    pub fn new(store: Arc<OrderStore>, cancel_before_end_minutes: u64) -> Self {
+        Self {
+            store,
+            cancel_before_end: chrono::Duration::minutes(cancel_before_end_minutes as i64),
+        }
+    }
+
+    /// 吃单落空后挂出两腿 maker 买单（数量取 opp 的 yes_size / no_size）；两腿都挂出时按两腿提交 reservation，
+    /// 否则丢弃归还。返回是否两腿都已挂出
+    pub async fn place(
+        &self,
+        executor: &TradingExecutor,
+        opp: &ArbitrageOpportunity,
+        market_display: &str,
+        market_end: DateTime<Utc>,
+        reservation: ExposureReservation,
+    ) -> bool {
+        let cancel_at = market_end - self.cancel_before_end;
+        if Utc::now() >= cancel_at {
+            debug!("距市场结束不足撤单提前量，不挂 maker 兜底单 | 市场:{}", market_display);
+            return false;
+        }
+        let legs = [
+            (opp.yes_token_id, rules::order_price(opp.yes_token_id, opp.yes_ask_price), opp.yes_size),
+            (opp.no_token_id, rules::order_price(opp.no_token_id, opp.no_ask_price), opp.no_size),
+        ];
+        let orders: Vec<BatchOrder> = legs
+            .iter()
+            .map(|&(token_id, price, size)| BatchOrder {
+                token_id,
+                side: Side::Buy,
+                price,
+                size,
+                order_type: OrderType::GTC,
+                expiration: None,
+            })
+            .collect();
+        let results = match executor.submit_batch(&orders).await {
+            Ok(results) => results,
+            Err(e) => {
+                warn!(error = %e, "maker 兜底挂单失败 | 市场:{}", market_display);
+                metrics::incr("maker_fallback_failed");
+                return false;
+            }
+        };
+        let placed: Vec<String> = results
+            .iter()
+            .filter_map(|r| r.as_ref().ok())
+            .filter(|r| r.success && !r.order_id.is_empty())
+            .map(|r| r.order_id.clone())
+            .collect();
+        if placed.len() < legs.len() {
+            // 只挂出一腿：撤掉，避免单边挂单
+            if !placed.is_empty() {
+                let ids: Vec<&str> = placed.iter().map(String::as_str).collect();
+                if let Err(e) = executor.cancel_order_ids(&ids).await {
+                    warn!(error = %e, "maker 兜底单边挂单撤单失败 | 市场:{}", market_display);
+                }
+            }
+            let error = results.iter().find_map(|r| match r {
+                Ok(r) => r.error_msg.clone(),
+                Err(e) => Some(e.to_string()),
+            });
+            debug!(
+                error = error.as_deref().unwrap_or(""),
+                "maker 兜底未能挂出两腿 | 市场:{}",
+                market_display
+            );
+            metrics::incr("maker_fallback_failed");
+            return false;
+        }
+
+        for (order_id, &(token_id, price, size)) in placed.into_iter().zip(legs.iter()) {
+            self.store.insert(TrackedOrder {
+                order_id,
+                market_id: opp.market_id,
+                token_id,
+                price,
+                size,
+                cancel_at,
+                source: "maker_fallback".to_string(),
+            });
+        }
+        reservation.commit(&legs);
+        metrics::incr("maker_fallback_placed");
+        info!(
+            "🪝 吃单未成交，挂出 maker 兜底单 | 市场:{} | YES {} + NO {} | 数量:{}份 | 撤单时间:{}",
+            market_display,
+            legs[0].1,
+            legs[1].1,
+            opp.yes_size.min(opp.no_size),
+            cancel_at.format("%H:%M:%S")
+        );
+        journal::record(
+            "maker_fallback_placed",
+            json!({
+                "market_id": format!("{:#x}", opp.market_id),
+                "market": market_display,
+                "yes_price": legs[0].1.to_string(),
+                "no_price": legs[1].1.to_string(),
+                "yes_size": opp.yes_size.to_string(),
+                "no_size": opp.no_size.to_string(),
+                "cancel_at": cancel_at.to_rfc3339(),
+            }),
+        );
+        true
+    }
+}
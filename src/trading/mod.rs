@@ -1,4 +1,16 @@
+pub mod allowance;
+pub mod credentials;
 pub mod executor;
+pub mod iceberg;
+pub mod maker_fallback;
+pub mod order_store;
 pub mod orders;
+pub mod queue;
+pub mod reject_policy;
+pub mod rejection;
+pub mod retry_queue;
+pub mod slippage;
+pub mod startup_orders;
+pub mod twap;
 
-pub use executor::TradingExecutor;
+pub use executor::{BatchOrder, TradingExecutor};
@@ -0,0 +1,186 @@
+//! 挂单登记：机器人有意留在订单簿上的挂单（如吃单落空后的 maker 兜底单）按到期时间登记，
+//! 后台每 10 秒撤掉到期的挂单——到期时间通常为市场结束前 N 分钟，保证没有挂单带进结算。
+//! 撤单后再查一次成交数量写入交易日志；成交带来的持仓由定期同步校正。
+//! 属于订单对的挂单（顺序执行留在簿上的第二腿）由 follow 持续跟踪成交，随时计入订单对与持仓。
+//! 登记的挂单随风控检查点写盘，重启后恢复并在启动对账时接管，不会被当作未知挂单撤掉。
+
+use chrono::{DateTime, Utc};
+use polymarket_client_sdk::types::{Decimal, B256, U256};
+use rust_decimal_macros::dec;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use super::executor::TradingExecutor;
+use crate::utils::{journal, leader, metrics};
+
+/// 检查到期挂单的间隔
+const SWEEP_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 一笔登记的挂单
+#[derive(Debug, Clone)]
+pub struct TrackedOrder {
+    pub order_id: String,
+    pub market_id: B256,
+    pub token_id: U256,
+    pub price: Decimal,
+    pub size: Decimal,
+    /// 到该时间撤单
+    pub cancel_at: DateTime<Utc>,
+    /// 来源（日志与交易日志用），如 "maker_fallback"
+    pub source: String,
+}
+
+#[derive(Default)]
+pub struct OrderStore {
+    orders: Mutex<HashMap<String, TrackedOrder>>,
+}
+
+impl OrderStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&self, order: TrackedOrder) {
+        if let Ok(mut orders) = self.orders.lock() {
+            orders.insert(order.order_id.clone(), order);
+        }
+    }
+
+    pub fn contains(&self, order_id: &str) -> bool {
+        self.orders.lock().is_ok_and(|o| o.contains_key(order_id))
+    }
+
+    pub fn remove(&self, order_id: &str) -> Option<TrackedOrder> {
+        self.orders.lock().ok().and_then(|mut o| o.remove(order_id))
+    }
+
+    /// 跟踪一笔已登记挂单的成交：累计成交量增加时调用 on_fill(累计成交量)，
+    /// 直到全部成交、到期（自行撤单）或被后台清理撤掉；返回最终累计成交量
+    pub async fn follow(
+        &self,
+        executor: &TradingExecutor,
+        order: &TrackedOrder,
+        mut on_fill: impl FnMut(Decimal),
+    ) -> Decimal {
+        let mut reported = dec!(0);
+        loop {
+            tokio::time::sleep(SWEEP_INTERVAL).await;
+            let finishing = if !self.contains(&order.order_id) {
+                // 已被后台清理（或启动对账）取走：等其撤单完成后再查最终成交
+                tokio::time::sleep(SWEEP_INTERVAL).await;
+                true
+            } else if Utc::now() >= order.cancel_at && self.remove(&order.order_id).is_some() {
+                if let Err(e) = executor.cancel_order_ids(&[order.order_id.as_str()]).await {
+                    debug!(order_id = %order.order_id, error = %e, "到期挂单撤单失败（可能已成交或已被撤）");
+                }
+                metrics::incr("resting_order_expired");
+                true
+            } else {
+                false
+            };
+            match executor.order_matched(&order.order_id).await {
+                Ok(m) if m.min(order.size) > reported => {
+                    reported = m.min(order.size);
+                    on_fill(reported);
+                }
+                Ok(_) => {}
+                Err(e) => debug!(order_id = %order.order_id, error = %e, "查询挂单成交数量失败"),
+            }
+            if reported >= order.size {
+                self.remove(&order.order_id);
+                return reported;
+            }
+            if finishing {
+                info!(
+                    "🧹 挂单跟踪结束 | 来源:{} | token_id={:#x} | 价格:{} | 已成交:{}/{}",
+                    order.source, order.token_id, order.price, reported, order.size
+                );
+                return reported;
+            }
+        }
+    }
+
+    /// 当前登记的全部挂单（写检查点用）
+    pub fn snapshot(&self) -> Vec<TrackedOrder> {
+        self.orders.lock().map(|o| o.values().cloned().collect()).unwrap_or_default()
+    }
+
+    /// 只保留满足条件的挂单（启动对账时丢弃已不在订单簿上的恢复条目）
+    pub fn retain(&self, keep: impl Fn(&TrackedOrder) -> bool) {
+        if let Ok(mut orders) = self.orders.lock() {
+            orders.retain(|_, order| keep(order));
+        }
+    }
+
+    /// 取出（并移除）已到期的挂单
+    fn take_due(&self, now: DateTime<Utc>) -> Vec<TrackedOrder> {
+        let Ok(mut orders) = self.orders.lock() else {
+            return Vec::new();
+        };
+        let due: Vec<String> = orders
+            .values()
+            .filter(|o| o.cancel_at <= now)
+            .map(|o| o.order_id.clone())
+            .collect();
+        due.iter().filter_map(|id| orders.remove(id)).collect()
+    }
+
+    /// 后台定时撤掉到期挂单
+    pub fn spawn_sweeper(self: &Arc<Self>, executor: Arc<TradingExecutor>) {
+        let store = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                // 备机不撤单：其登记来自检查点，挂单仍由主机负责
+                if !leader::is_leader() {
+                    continue;
+                }
+                let due = store.take_due(Utc::now());
+                if due.is_empty() {
+                    continue;
+                }
+                let ids: Vec<&str> = due.iter().map(|o| o.order_id.as_str()).collect();
+                if let Err(e) = executor.cancel_order_ids(&ids).await {
+                    // 撤单失败（可能已全部成交或已被撤）：放回下一轮重试，直到查询确认已结束
+                    warn!(error = %e, count = due.len(), "到期挂单撤单失败，下一轮重试");
+                    for order in due {
+                        store.insert(order);
+                    }
+                    continue;
+                }
+                for order in due {
+                    // 撤单与成交之间可能有竞争，撤单后再查一次
+                    let matched = match executor.order_matched(&order.order_id).await {
+                        Ok(m) => m.min(order.size),
+                        Err(e) => {
+                            debug!(order_id = %order.order_id, error = %e, "查询到期挂单成交数量失败");
+                            dec!(0)
+                        }
+                    };
+                    metrics::incr("resting_order_expired");
+                    info!(
+                        "🧹 到期挂单已撤 | 来源:{} | token_id={:#x} | 价格:{} | 已成交:{}/{}",
+                        order.source, order.token_id, order.price, matched, order.size
+                    );
+                    journal::record(
+                        "resting_order_expired",
+                        json!({
+                            "source": order.source,
+                            "order_id": order.order_id,
+                            "market_id": format!("{:#x}", order.market_id),
+                            "token_id": format!("{:#x}", order.token_id),
+                            "price": order.price.to_string(),
+                            "size": order.size.to_string(),
+                            "filled": matched.to_string(),
+                        }),
+                    );
+                }
+            }
+        });
+    }
+}
@@ -1,2 +1,19 @@
 // 订单相关的辅助类型和函数
-// 目前为空，可以后续扩展
+
+use polymarket_client_sdk::types::Decimal;
+use rust_decimal_macros::dec;
+
+/// 下单数量随机抖动：在 [size × (1 − pct), size] 内均匀取值并保留两位小数，只向下抖动，
+/// 不会超过风控已核准的数量；pct <= 0 时原样返回。用于让固定的下单数量不易被其他机器人识别。
+pub fn jitter_size(size: Decimal, pct: f64) -> Decimal {
+    if pct <= 0.0 || size <= dec!(0) {
+        return size;
+    }
+    let factor = 1.0 - pct.min(1.0) * rand::random::<f64>();
+    let jittered = (size * Decimal::try_from(factor).unwrap_or(dec!(1))).round_dp(2);
+    if jittered > dec!(0) {
+        jittered.min(size)
+    } else {
+        size
+    }
+}
@@ -0,0 +1,190 @@
+//! 挂单排队位置估计：ARBITRAGE_ORDER_TYPE 为 GTC/GTD 时，未成交部分会作为买单挂在订单簿上（被动挂单）。
+//! 根据后续订单簿快照中我们价位的挂单量变化估计排在我们前面的数量与该价位的消耗速度，
+//! 估计在剩余有效期内的成交概率；概率可忽略（前方队列消耗不完，或买一已高于我们的价格、只会在价格反向穿越时成交）
+//! 的挂单撤掉，避免陈旧挂单只在不利行情中成交。
+//!
+//! 排队模型：挂单后第一次看到的该价位其他挂单全部视为排在前面；之后该价位的减少量按前方占比分摊
+//! （成交从队首消耗、撤单均匀分布的折中），新增挂单排在后面。
+
+use polymarket_client_sdk::clob::ws::types::response::BookUpdate;
+use polymarket_client_sdk::types::{Decimal, U256};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+#[derive(Debug, Clone, Copy)]
+pub struct QueueSettings {
+    /// 估计成交概率的时间窗口（GTD 订单取与剩余有效期的较小值）
+    pub horizon: Duration,
+    /// 成交概率低于该值时撤单
+    pub min_fill_prob: f64,
+    /// 挂单后至少观察多久才评估（积累消耗速度样本）
+    pub min_observe: Duration,
+}
+
+/// 一笔被跟踪的挂单
+struct RestingOrder {
+    token_id: U256,
+    price: Decimal,
+    /// 估计的剩余未成交数量
+    remaining: Decimal,
+    /// 估计排在前面的数量；None 表示尚未看到挂单后的第一次快照
+    ahead: Option<Decimal>,
+    /// 上次快照中该价位的总挂单量
+    last_level: Decimal,
+    /// 观察期内该价位累计减少量
+    consumed: Decimal,
+    placed_at: Instant,
+    expires_at: Option<Instant>,
+}
+
+/// 需要撤掉的挂单
+#[derive(Debug, Clone)]
+pub struct StaleQuote {
+    pub order_id: String,
+    pub token_id: U256,
+    pub price: Decimal,
+    pub ahead: Decimal,
+    pub fill_prob: f64,
+}
+
+pub struct QueueTracker {
+    settings: QueueSettings,
+    orders: Mutex<HashMap<String, RestingOrder>>,
+}
+
+/// 买盘中指定价位的挂单量
+fn bid_level(book: &BookUpdate, price: Decimal) -> Decimal {
+    book.bids
+        .iter()
+        .find(|o| o.price == price)
+        .map(|o| o.size)
+        .unwrap_or(dec!(0))
+}
+
+impl QueueTracker {
+    pub fn new(settings: QueueSettings) -> Self {
+        Self {
+            settings,
+            orders: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 开始跟踪一笔挂单；lifetime 为 GTD 有效期（GTC 为 None）
+    pub fn track(&self, order_id: &str, token_id: U256, price: Decimal, remaining: Decimal, lifetime: Option<Duration>) {
+        if order_id.is_empty() || remaining <= dec!(0) {
+            return;
+        }
+        let now = Instant::now();
+        if let Ok(mut orders) = self.orders.lock() {
+            orders.insert(
+                order_id.to_string(),
+                RestingOrder {
+                    token_id,
+                    price,
+                    remaining,
+                    ahead: None,
+                    last_level: dec!(0),
+                    consumed: dec!(0),
+                    placed_at: now,
+                    expires_at: lifetime.map(|d| now + d),
+                },
+            );
+        }
+    }
+
+    /// 停止跟踪（已撤单或已知完结）
+    pub fn forget(&self, order_id: &str) {
+        if let Ok(mut orders) = self.orders.lock() {
+            orders.remove(order_id);
+        }
+    }
+
+    /// 当前没有被跟踪的挂单（热路径先判断，避免无挂单时逐个 token 评估）
+    pub fn is_empty(&self) -> bool {
+        self.orders.lock().map(|o| o.is_empty()).unwrap_or(true)
+    }
+
+    /// 用一次订单簿快照更新该 token 上挂单的排队估计，返回应撤掉的挂单（同时停止跟踪）
+    pub fn on_book(&self, book: &BookUpdate) -> Vec<StaleQuote> {
+        let Ok(mut orders) = self.orders.lock() else {
+            return Vec::new();
+        };
+        let now = Instant::now();
+        let best_bid = book.bids.last().map(|o| o.price);
+        let mut finished: Vec<String> = Vec::new();
+        let mut stale: Vec<StaleQuote> = Vec::new();
+        for (order_id, order) in orders.iter_mut().filter(|(_, o)| o.token_id == book.asset_id) {
+            if order.expires_at.is_some_and(|t| now >= t) {
+                finished.push(order_id.clone());
+                continue;
+            }
+            let level = bid_level(book, order.price);
+            if level <= dec!(0) {
+                // 价位已不在簿上：已成交、过期或被撤
+                finished.push(order_id.clone());
+                continue;
+            }
+            // 价位总量少于我们的剩余量，说明我们已被部分成交
+            order.remaining = order.remaining.min(level);
+            let others = level - order.remaining;
+            let ahead = match order.ahead {
+                None => others,
+                Some(ahead) => {
+                    let prev_others = (order.last_level - order.remaining).max(dec!(0));
+                    let decrease = (order.last_level - level).max(dec!(0));
+                    order.consumed += decrease;
+                    if prev_others > dec!(0) && decrease > dec!(0) {
+                        (ahead - decrease * ahead / prev_others).max(dec!(0)).min(others)
+                    } else {
+                        ahead.min(others)
+                    }
+                }
+            };
+            order.ahead = Some(ahead);
+            order.last_level = level;
+
+            let observed = now.duration_since(order.placed_at);
+            if observed < self.settings.min_observe {
+                continue;
+            }
+            let fill_prob = if best_bid.is_some_and(|b| b > order.price) {
+                // 买一已高于我们：只有价格向下穿越才会成交（不利成交）
+                0.0
+            } else if ahead <= dec!(0) {
+                1.0
+            } else {
+                let horizon = order
+                    .expires_at
+                    .map(|t| t.saturating_duration_since(now).min(self.settings.horizon))
+                    .unwrap_or(self.settings.horizon);
+                let rate = order.consumed.to_f64().unwrap_or(0.0) / observed.as_secs_f64().max(0.001);
+                (rate * horizon.as_secs_f64() / ahead.to_f64().unwrap_or(f64::MAX)).min(1.0)
+            };
+            debug!(
+                order_id = %order_id,
+                price = %order.price,
+                ahead = %ahead,
+                remaining = %order.remaining,
+                fill_prob,
+                "挂单排队估计"
+            );
+            if fill_prob < self.settings.min_fill_prob {
+                stale.push(StaleQuote {
+                    order_id: order_id.clone(),
+                    token_id: order.token_id,
+                    price: order.price,
+                    ahead,
+                    fill_prob,
+                });
+            }
+        }
+        for order_id in finished.iter().chain(stale.iter().map(|s| &s.order_id)) {
+            orders.remove(order_id);
+        }
+        stale
+    }
+}
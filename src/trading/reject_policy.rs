@@ -0,0 +1,239 @@
+//! 拒单处置策略表（REJECT_POLICY）：把分类后的下单拒绝原因映射为自动动作，由执行器与主循环执行，
+//! 运维可以把事故处置手册写成配置，而不是盯着日志手动处理。
+//!
+//! 格式：`原因=动作[:参数][+动作...]`，多条以逗号分隔，例如
+//! `rate_limit=retry:500,balance=resize:50+alert,auth=halt:30+alert,min_size=skip:10`
+//!
+//! 原因：price_moved / min_size / balance / rate_limit / auth / other（与 order_rejected_* 计数器一致）。
+//! 动作：
+//! - `retry[:毫秒]`：等待后原样重试一次被拒订单（默认不等待）
+//! - `resize[:百分比]`：数量缩小到该比例后重试一次（默认 50%，缩小后低于最小下单数量则不重试）
+//! - `skip[:分钟]`：该市场暂停开新仓（默认 10 分钟）
+//! - `halt[:分钟]`：熔断，所有市场暂停开新仓（默认 10 分钟）；收尾、单腿处理等卖出不受影响
+//! - `alert`：error 日志并广播 rejection_alert 事件（Webhook 等输出端推送），同一原因 60 秒内只告警一次
+//!
+//! retry / resize 只作用于走 submit_batch 的订单（单笔、拆单、冰山、TWAP、方向性），套利两腿的机会转瞬即逝，
+//! 被拒后不重试、由下一拍重新检测。未配置的原因不做任何动作；未初始化时各查询为 no-op。
+
+use dashmap::DashMap;
+use polymarket_client_sdk::types::{Decimal, U256};
+use rust_decimal_macros::dec;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+use super::rejection::RejectReason;
+use crate::utils::events::{self, BotEvent};
+use crate::utils::metrics;
+
+/// skip / halt 未指定时长时的默认分钟数
+const DEFAULT_PAUSE_MINUTES: u64 = 10;
+/// 同一原因两次告警的最短间隔
+const ALERT_COOLDOWN: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PolicyAction {
+    Retry { delay: Duration },
+    Resize { factor: Decimal },
+    Skip { duration: Duration },
+    Halt { duration: Duration },
+    Alert,
+}
+
+impl PolicyAction {
+    /// 解析 `动作[:参数]`，无效时返回 None
+    fn parse(s: &str) -> Option<Self> {
+        let (name, arg) = match s.split_once(':') {
+            Some((name, arg)) => (name.trim(), Some(arg.trim())),
+            None => (s.trim(), None),
+        };
+        let minutes = |arg: Option<&str>| -> Option<Duration> {
+            let m = arg.map_or(Some(DEFAULT_PAUSE_MINUTES), |a| a.parse().ok())?;
+            Some(Duration::from_secs(m * 60))
+        };
+        match name.to_lowercase().as_str() {
+            "retry" => Some(PolicyAction::Retry {
+                delay: Duration::from_millis(arg.map_or(Some(0), |a| a.parse().ok())?),
+            }),
+            "resize" => {
+                let pct: Decimal = arg.map_or(Some(dec!(50)), |a| a.parse().ok())?;
+                (pct > dec!(0) && pct < dec!(100)).then_some(PolicyAction::Resize { factor: pct / dec!(100) })
+            }
+            "skip" => Some(PolicyAction::Skip { duration: minutes(arg)? }),
+            "halt" => Some(PolicyAction::Halt { duration: minutes(arg)? }),
+            "alert" => (arg.is_none()).then_some(PolicyAction::Alert),
+            _ => None,
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            PolicyAction::Retry { delay } => format!("retry:{}", delay.as_millis()),
+            PolicyAction::Resize { factor } => format!("resize:{}", factor * dec!(100)),
+            PolicyAction::Skip { duration } => format!("skip:{}", duration.as_secs() / 60),
+            PolicyAction::Halt { duration } => format!("halt:{}", duration.as_secs() / 60),
+            PolicyAction::Alert => "alert".to_string(),
+        }
+    }
+}
+
+/// 被拒订单的重试方式（submit_batch 使用）
+#[derive(Debug, Clone, Copy)]
+pub enum RetryPlan {
+    Same { delay: Duration },
+    Resize { factor: Decimal },
+}
+
+struct Policy {
+    table: BTreeMap<RejectReason, Vec<PolicyAction>>,
+    halted_until: Mutex<Option<Instant>>,
+    /// token -> 暂停开仓截止时间
+    skipped: DashMap<U256, Instant>,
+    last_alert: Mutex<BTreeMap<RejectReason, Instant>>,
+}
+
+static POLICY: OnceLock<Policy> = OnceLock::new();
+
+/// 解析策略表并启用；spec 为空时不启用，无效条目告警后忽略
+pub fn init(spec: &str) {
+    let mut table: BTreeMap<RejectReason, Vec<PolicyAction>> = BTreeMap::new();
+    for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let parsed = entry.split_once('=').and_then(|(reason, actions)| {
+            let reason = RejectReason::parse(reason)?;
+            let actions: Option<Vec<PolicyAction>> = actions.split('+').map(PolicyAction::parse).collect();
+            Some((reason, actions?))
+        });
+        match parsed {
+            Some((reason, actions)) => table.entry(reason).or_default().extend(actions),
+            None => warn!(entry, "REJECT_POLICY 条目无效，已忽略"),
+        }
+    }
+    if table.is_empty() {
+        return;
+    }
+    let summary = table
+        .iter()
+        .map(|(reason, actions)| {
+            format!(
+                "{}={}",
+                reason.key(),
+                actions.iter().map(PolicyAction::label).collect::<Vec<_>>().join("+")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    if POLICY
+        .set(Policy {
+            table,
+            halted_until: Mutex::new(None),
+            skipped: DashMap::new(),
+            last_alert: Mutex::new(BTreeMap::new()),
+        })
+        .is_ok()
+    {
+        info!("📋 拒单处置策略已启用 | {}", summary);
+    }
+}
+
+/// 执行拒单的 skip / halt / alert 动作；tokens 为被拒订单所在市场的 token（未知时为空，skip 不生效）
+pub fn apply(reason: RejectReason, error_msg: &str, tokens: &[U256]) {
+    let Some(policy) = POLICY.get() else {
+        return;
+    };
+    let Some(actions) = policy.table.get(&reason) else {
+        return;
+    };
+    for action in actions {
+        match *action {
+            PolicyAction::Skip { duration } => {
+                let until = Instant::now() + duration;
+                for token in tokens {
+                    if policy.skipped.insert(*token, until).is_none() {
+                        warn!(
+                            "⏸️ 拒单处置：市场暂停开仓 {} 分钟 | 原因:{} | token_id={:#x}",
+                            duration.as_secs() / 60,
+                            reason.label(),
+                            token
+                        );
+                        metrics::incr("reject_policy_skip");
+                    }
+                }
+            }
+            PolicyAction::Halt { duration } => {
+                let Ok(mut halted) = policy.halted_until.lock() else {
+                    continue;
+                };
+                let until = Instant::now() + duration;
+                if !halted.is_some_and(|t| t > Instant::now()) {
+                    error!(
+                        "🛑 拒单处置：熔断，全部市场暂停开仓 {} 分钟 | 原因:{} | {}",
+                        duration.as_secs() / 60,
+                        reason.label(),
+                        error_msg
+                    );
+                    metrics::incr("reject_policy_halt");
+                }
+                *halted = Some(halted.map_or(until, |t| t.max(until)));
+            }
+            PolicyAction::Alert => {
+                let Ok(mut last) = policy.last_alert.lock() else {
+                    continue;
+                };
+                if last.get(&reason).is_some_and(|t| t.elapsed() < ALERT_COOLDOWN) {
+                    continue;
+                }
+                last.insert(reason, Instant::now());
+                error!("🚨 拒单告警 | 原因:{} | {}", reason.label(), error_msg);
+                metrics::incr("reject_policy_alert");
+                events::publish(BotEvent::rejection_alert(reason.key(), error_msg));
+            }
+            PolicyAction::Retry { .. } | PolicyAction::Resize { .. } => {}
+        }
+    }
+}
+
+/// 被拒订单的重试方式；同时配置 retry 与 resize 时取 resize
+pub fn retry_plan(reason: RejectReason) -> Option<RetryPlan> {
+    let actions = POLICY.get()?.table.get(&reason)?;
+    let resize = actions.iter().find_map(|a| match a {
+        PolicyAction::Resize { factor } => Some(RetryPlan::Resize { factor: *factor }),
+        _ => None,
+    });
+    resize.or_else(|| {
+        actions.iter().find_map(|a| match a {
+            PolicyAction::Retry { delay } => Some(RetryPlan::Same { delay: *delay }),
+            _ => None,
+        })
+    })
+}
+
+/// 是否处于熔断中
+pub fn halted() -> bool {
+    POLICY
+        .get()
+        .and_then(|p| p.halted_until.lock().ok().and_then(|h| *h))
+        .is_some_and(|until| until > Instant::now())
+}
+
+/// 这些 token 所在市场是否不可开新仓（熔断或被暂停）
+pub fn blocked(tokens: &[U256]) -> bool {
+    let Some(policy) = POLICY.get() else {
+        return false;
+    };
+    if halted() {
+        return true;
+    }
+    let now = Instant::now();
+    tokens.iter().any(|t| {
+        let expired = match policy.skipped.get(t) {
+            Some(until) if *until > now => return true,
+            Some(_) => true,
+            None => false,
+        };
+        if expired {
+            policy.skipped.remove(t);
+        }
+        false
+    })
+}
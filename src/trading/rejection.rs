@@ -0,0 +1,144 @@
+//! 下单拒绝原因分类：把 CLOB 返回的错误信息归为价格已变、低于最小数量、余额/授权不足、限速、认证失败等类别，
+//! 按类别累计到进程内计数器（order_rejected_*）与当前窗口统计，窗口结束时输出分类汇总，
+//! 以便区分不同失败模式而不是只看到一个笼统的失败数。授权不足的拒单会触发自动补授权（ALLOWANCE_AUTO_REPAIR），
+//! 各类别的自动处置动作见 reject_policy。
+
+use poly_1hour_bot::i18n::Msg;
+use poly_1hour_bot::tr;
+use polymarket_client_sdk::types::U256;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+use tracing::info;
+
+use crate::utils::metrics;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RejectReason {
+    /// 价格已变化 / 无可匹配订单（FOK/FAK 无法成交）
+    PriceMoved,
+    /// 低于最小下单数量或金额、价格不符合 tick
+    MinSize,
+    /// USDC 余额或授权不足
+    Balance,
+    /// 被限速（429）
+    RateLimit,
+    /// 认证失败（API key、签名）
+    Auth,
+    Other,
+}
+
+impl RejectReason {
+    /// 按错误信息归类（不区分大小写的关键字匹配）
+    pub fn classify(error_msg: &str) -> Self {
+        let msg = error_msg.to_ascii_lowercase();
+        let has = |keys: &[&str]| keys.iter().any(|k| msg.contains(k));
+        if has(&["429", "rate limit", "too many requests", "throttle"]) {
+            RejectReason::RateLimit
+        } else if has(&["401", "403", "unauthorized", "api key", "invalid signature", "forbidden"]) {
+            RejectReason::Auth
+        } else if has(&["not enough balance", "balance", "allowance", "insufficient"]) {
+            RejectReason::Balance
+        } else if has(&["minimum", "min size", "lower than the min", "invalid amount", "tick size", "too small"]) {
+            RejectReason::MinSize
+        } else if has(&["no orders found to match", "couldn't be fully filled", "fok", "fak", "crosses the book", "price"]) {
+            RejectReason::PriceMoved
+        } else {
+            RejectReason::Other
+        }
+    }
+
+    /// 配置中使用的名称（REJECT_POLICY），与计数器名称的后缀一致
+    pub fn key(&self) -> &'static str {
+        match self {
+            RejectReason::PriceMoved => "price_moved",
+            RejectReason::MinSize => "min_size",
+            RejectReason::Balance => "balance",
+            RejectReason::RateLimit => "rate_limit",
+            RejectReason::Auth => "auth",
+            RejectReason::Other => "other",
+        }
+    }
+
+    /// 按 key 解析，大小写不敏感
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim().to_lowercase();
+        [
+            RejectReason::PriceMoved,
+            RejectReason::MinSize,
+            RejectReason::Balance,
+            RejectReason::RateLimit,
+            RejectReason::Auth,
+            RejectReason::Other,
+        ]
+        .into_iter()
+        .find(|r| r.key() == s)
+    }
+
+    /// 计数器名称
+    pub fn metric(&self) -> &'static str {
+        match self {
+            RejectReason::PriceMoved => "order_rejected_price_moved",
+            RejectReason::MinSize => "order_rejected_min_size",
+            RejectReason::Balance => "order_rejected_balance",
+            RejectReason::RateLimit => "order_rejected_rate_limit",
+            RejectReason::Auth => "order_rejected_auth",
+            RejectReason::Other => "order_rejected_other",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            RejectReason::PriceMoved => "价格已变",
+            RejectReason::MinSize => "最小数量",
+            RejectReason::Balance => "余额/授权",
+            RejectReason::RateLimit => "限速",
+            RejectReason::Auth => "认证",
+            RejectReason::Other => "其他",
+        }
+    }
+}
+
+fn window_counts() -> &'static Mutex<BTreeMap<RejectReason, u64>> {
+    static COUNTS: OnceLock<Mutex<BTreeMap<RejectReason, u64>>> = OnceLock::new();
+    COUNTS.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+/// 记录一次下单拒绝并返回分类；授权不足时在后台触发自动补授权（见 allowance）
+pub fn record(error_msg: &str) -> RejectReason {
+    record_for(error_msg, &[])
+}
+
+/// 同 record，并按拒单处置策略（REJECT_POLICY）执行动作；tokens 为被拒订单所在市场的 token，供按市场暂停使用
+pub fn record_for(error_msg: &str, tokens: &[U256]) -> RejectReason {
+    let reason = RejectReason::classify(error_msg);
+    super::reject_policy::apply(reason, error_msg, tokens);
+    if reason == RejectReason::Balance && super::allowance::is_allowance_error(error_msg) {
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            handle.spawn(super::allowance::repair());
+        }
+    }
+    metrics::incr("order_rejected");
+    metrics::incr(reason.metric());
+    if let Ok(mut counts) = window_counts().lock() {
+        *counts.entry(reason).or_insert(0) += 1;
+    }
+    reason
+}
+
+/// 输出本窗口的拒绝原因汇总并清零（窗口切换时调用）
+pub fn report_and_reset(window_timestamp: i64) {
+    let counts = match window_counts().lock() {
+        Ok(mut counts) => std::mem::take(&mut *counts),
+        Err(_) => return,
+    };
+    if counts.is_empty() {
+        return;
+    }
+    let total: u64 = counts.values().sum();
+    let detail = counts
+        .iter()
+        .map(|(reason, n)| format!("{}:{}", reason.label(), n))
+        .collect::<Vec<_>>()
+        .join(" | ");
+    info!("{}", tr!(Msg::RejectSummary, window_timestamp, total, detail));
+}
@@ -0,0 +1,192 @@
+//! 失败链上操作（目前为 merge）的持久化重试队列：每个条目记录尝试次数、下次重试时间与最近一次错误，
+//! 按指数退避重试；用尽重试次数后进入死信状态并告警（error 日志 + 交易日志 + 指标），不再自动重试。
+//! 队列随每次变更落盘，重启后继续重试。
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+use crate::utils::{journal, metrics};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryState {
+    Pending,
+    /// 重试次数用尽，等待人工处理
+    DeadLetter,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryItem {
+    /// 操作类型，如 "merge"
+    pub op: String,
+    /// 操作对象，merge 为 condition_id（十六进制字符串）
+    pub key: String,
+    pub attempts: u32,
+    pub state: RetryState,
+    pub first_failed_at: DateTime<Utc>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_error: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct QueueFile {
+    #[serde(default)]
+    items: Vec<RetryItem>,
+}
+
+pub struct RetryQueue {
+    /// None 表示只在内存中保存，不落盘
+    path: Option<PathBuf>,
+    state: Mutex<QueueFile>,
+    max_attempts: u32,
+    base_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl RetryQueue {
+    /// 从文件加载队列；path 为空字符串时不落盘，文件不存在或损坏时从空队列开始
+    pub fn load(path: &str, max_attempts: u32, base_backoff: Duration, max_backoff: Duration) -> Self {
+        let path = (!path.trim().is_empty()).then(|| PathBuf::from(path));
+        let state = path
+            .as_ref()
+            .and_then(|p| std::fs::read(p).ok())
+            .and_then(|body| match serde_json::from_slice::<QueueFile>(&body) {
+                Ok(file) => Some(file),
+                Err(e) => {
+                    warn!(error = %e, "重试队列文件损坏，忽略");
+                    None
+                }
+            })
+            .unwrap_or_default();
+        let pending = state.items.iter().filter(|i| i.state == RetryState::Pending).count();
+        let dead = state.items.len() - pending;
+        if !state.items.is_empty() {
+            info!("♻️ 加载重试队列 | 待重试:{} | 死信:{}", pending, dead);
+        }
+        Self {
+            path,
+            state: Mutex::new(state),
+            max_attempts: max_attempts.max(1),
+            base_backoff,
+            max_backoff,
+        }
+    }
+
+    /// 第 attempts 次失败后的退避时长：base × 2^(attempts-1)，上限 max_backoff
+    fn backoff(&self, attempts: u32) -> Duration {
+        let factor = 1u32 << attempts.saturating_sub(1).min(16);
+        (self.base_backoff * factor).min(self.max_backoff)
+    }
+
+    /// 记录一次失败：新条目加入队列，已有条目尝试次数 +1；用尽次数后转为死信并告警
+    pub fn record_failure(&self, op: &str, key: &str, error_msg: &str) {
+        let now = Utc::now();
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let index = match state.items.iter().position(|i| i.op == op && i.key == key) {
+            Some(index) => index,
+            None => {
+                state.items.push(RetryItem {
+                    op: op.to_string(),
+                    key: key.to_string(),
+                    attempts: 0,
+                    state: RetryState::Pending,
+                    first_failed_at: now,
+                    next_attempt_at: now,
+                    last_error: String::new(),
+                });
+                state.items.len() - 1
+            }
+        };
+        let attempts = state.items[index].attempts + 1;
+        let backoff = self.backoff(attempts);
+        let item = &mut state.items[index];
+        if item.state == RetryState::DeadLetter {
+            return;
+        }
+        item.attempts = attempts;
+        item.last_error = error_msg.to_string();
+        item.next_attempt_at = now + chrono::Duration::from_std(backoff).unwrap_or_default();
+
+        if attempts >= self.max_attempts {
+            item.state = RetryState::DeadLetter;
+            error!(
+                "🚨 {} 重试 {} 次仍失败，转入死信，需人工处理 | 对象:{} | 最近错误:{}",
+                op, attempts, key, error_msg
+            );
+            metrics::incr("retry_dead_letter");
+            journal::record(
+                "retry_dead_letter",
+                json!({ "op": op, "key": key, "attempts": attempts, "error": error_msg }),
+            );
+        } else {
+            warn!(
+                "⏳ {} 失败，加入重试队列 | 对象:{} | 第{}次 | {}秒后重试",
+                op,
+                key,
+                attempts,
+                backoff.as_secs()
+            );
+            metrics::incr("retry_scheduled");
+            journal::record(
+                "retry_scheduled",
+                json!({ "op": op, "key": key, "attempts": attempts, "backoff_secs": backoff.as_secs(), "error": error_msg }),
+            );
+        }
+        self.persist(&state);
+    }
+
+    /// 操作成功或已无需执行：移出队列（死信条目也一并移除）
+    pub fn resolve(&self, op: &str, key: &str) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let before = state.items.len();
+        state.items.retain(|i| !(i.op == op && i.key == key));
+        if state.items.len() != before {
+            debug!(op, key, "重试队列条目已完成");
+            self.persist(&state);
+        }
+    }
+
+    /// 已到重试时间的待重试条目（对象 key）
+    pub fn due(&self, op: &str) -> Vec<String> {
+        let now = Utc::now();
+        let Ok(state) = self.state.lock() else {
+            return Vec::new();
+        };
+        state
+            .items
+            .iter()
+            .filter(|i| i.op == op && i.state == RetryState::Pending && i.next_attempt_at <= now)
+            .map(|i| i.key.clone())
+            .collect()
+    }
+
+    fn persist(&self, state: &QueueFile) {
+        if let Some(path) = self.path.as_ref() {
+            if let Err(e) = Self::save(path, state) {
+                debug!(error = %e, "重试队列写盘失败");
+            }
+        }
+    }
+
+    fn save(path: &Path, state: &QueueFile) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            if !dir.as_os_str().is_empty() {
+                std::fs::create_dir_all(dir)?;
+            }
+        }
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(state)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+//! 分腿滑点：YES、NO 两腿各自配置滑点 [first, second]（SLIPPAGE_YES / SLIPPAGE_NO，未设置时沿用 SLIPPAGE），
+//! 订单簿较薄的一腿通常需要更大的滑点才能成交。
+//!
+//! 自适应模式（SLIPPAGE_ADAPTIVE_ENABLED）：按币种 + 周期（如 btc:15m）统计两腿的历史成交比例（指数移动平均），
+//! 对较难成交的一腿额外加宽滑点，加宽量 = 两腿成交比例之差 × SLIPPAGE_ADAPTIVE_MAX；
+//! 样本少于 SLIPPAGE_ADAPTIVE_MIN_SAMPLES 时不加宽。统计只在内存中保存，跨窗口累计。
+
+use polymarket_client_sdk::types::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tracing::debug;
+
+/// 成交比例移动平均的权重
+const EWMA_ALPHA: f64 = 0.1;
+
+#[derive(Debug, Clone, Copy)]
+pub struct SlippageSettings {
+    /// YES 腿滑点 [first, second]：仅下降侧用 second，上涨与持平用 first
+    pub yes: [f64; 2],
+    /// NO 腿滑点 [first, second]
+    pub no: [f64; 2],
+    /// 是否按币种自适应加宽较难成交一腿的滑点
+    pub adaptive_enabled: bool,
+    /// 自适应加宽的上限（价格单位）
+    pub adaptive_max: f64,
+    /// 自适应生效至少需要的下单样本数
+    pub adaptive_min_samples: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Leg {
+    Yes,
+    No,
+}
+
+/// 某币种两腿的成交统计
+#[derive(Debug, Clone, Copy, Default)]
+struct LegStats {
+    samples: u32,
+    /// 成交比例（成交数量 / 下单数量）的移动平均
+    yes_rate: f64,
+    no_rate: f64,
+}
+
+struct Adaptive {
+    max_extra: Decimal,
+    min_samples: u32,
+    stats: Mutex<HashMap<String, LegStats>>,
+}
+
+pub struct LegSlippage {
+    yes: [Decimal; 2],
+    no: [Decimal; 2],
+    adaptive: Option<Adaptive>,
+}
+
+fn to_pair(values: [f64; 2]) -> [Decimal; 2] {
+    [
+        Decimal::try_from(values[0]).unwrap_or(dec!(0.0)),
+        Decimal::try_from(values[1]).unwrap_or(dec!(0.01)),
+    ]
+}
+
+impl LegSlippage {
+    pub fn new(settings: &SlippageSettings) -> Self {
+        Self {
+            yes: to_pair(settings.yes),
+            no: to_pair(settings.no),
+            adaptive: settings.adaptive_enabled.then(|| Adaptive {
+                max_extra: Decimal::try_from(settings.adaptive_max).unwrap_or(dec!(0.01)).max(dec!(0)),
+                min_samples: settings.adaptive_min_samples.max(1),
+                stats: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+
+    /// 该腿的滑点：按方向取 first / second（仅下降(↓)用 second），再加上该币种的自适应加宽
+    pub fn for_leg(&self, leg: Leg, symbol: &str, dir: &str) -> Decimal {
+        let base = match leg {
+            Leg::Yes => self.yes,
+            Leg::No => self.no,
+        };
+        let base = if dir == "↓" { base[1] } else { base[0] };
+        base + self.adaptive_extra(leg, symbol)
+    }
+
+    /// 自适应加宽量：只加在历史成交比例较低的一腿
+    fn adaptive_extra(&self, leg: Leg, symbol: &str) -> Decimal {
+        let Some(adaptive) = self.adaptive.as_ref().filter(|_| !symbol.is_empty()) else {
+            return dec!(0);
+        };
+        let Some(stats) = adaptive
+            .stats
+            .lock()
+            .ok()
+            .and_then(|s| s.get(symbol).copied())
+            .filter(|s| s.samples >= adaptive.min_samples)
+        else {
+            return dec!(0);
+        };
+        let gap = match leg {
+            Leg::Yes => stats.no_rate - stats.yes_rate,
+            Leg::No => stats.yes_rate - stats.no_rate,
+        };
+        if gap <= 0.0 {
+            return dec!(0);
+        }
+        (adaptive.max_extra * Decimal::try_from(gap.min(1.0)).unwrap_or(dec!(0))).round_dp(4)
+    }
+
+    /// 记录一次成对下单的两腿成交数量；两腿都未成交时不计入（无法区分哪一腿更难成交）
+    pub fn record(&self, symbol: &str, yes: (Decimal, Decimal), no: (Decimal, Decimal)) {
+        let Some(adaptive) = self.adaptive.as_ref().filter(|_| !symbol.is_empty()) else {
+            return;
+        };
+        let rate = |(filled, size): (Decimal, Decimal)| {
+            if size > dec!(0) {
+                (filled / size).to_f64().unwrap_or(0.0).clamp(0.0, 1.0)
+            } else {
+                0.0
+            }
+        };
+        let (yes_rate, no_rate) = (rate(yes), rate(no));
+        if yes_rate == 0.0 && no_rate == 0.0 {
+            return;
+        }
+        let Ok(mut stats) = adaptive.stats.lock() else {
+            return;
+        };
+        let entry = stats.entry(symbol.to_string()).or_default();
+        if entry.samples == 0 {
+            entry.yes_rate = yes_rate;
+            entry.no_rate = no_rate;
+        } else {
+            entry.yes_rate += EWMA_ALPHA * (yes_rate - entry.yes_rate);
+            entry.no_rate += EWMA_ALPHA * (no_rate - entry.no_rate);
+        }
+        entry.samples = entry.samples.saturating_add(1);
+        debug!(
+            symbol,
+            samples = entry.samples,
+            yes_rate = entry.yes_rate,
+            no_rate = entry.no_rate,
+            "分腿成交比例更新"
+        );
+    }
+}
@@ -0,0 +1,105 @@
+//! 启动时挂单对账：列出账户上所有未成交挂单，属于已恢复订单对（检查点中的 pending_pairs）或挂单登记
+//! （检查点中的 OrderStore 条目，如 maker 兜底单）的挂单予以接管：订单对的挂单登记到 OrderStore，到期自动撤单；
+//! 其余未知挂单（上次运行遗留、或人工在同一账户下的单）按配置撤销或仅告警，
+//! 避免重启前后、或机器人与人工交易之间互相干扰（未知挂单成交会带来机器人不知道的持仓与敞口）。
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use super::executor::TradingExecutor;
+use super::order_store::{OrderStore, TrackedOrder};
+use crate::risk::manager::OrderPair;
+use crate::risk::RiskManager;
+use crate::utils::{journal, metrics};
+
+/// 对账并处理未知挂单；订单对的挂单接管后在 cancel_at 撤单；cancel_unknown 为 false 时只告警不撤单
+pub async fn reconcile(
+    executor: &Arc<TradingExecutor>,
+    risk_manager: &Arc<RiskManager>,
+    order_store: &OrderStore,
+    cancel_at: DateTime<Utc>,
+    cancel_unknown: bool,
+) -> Result<()> {
+    let pairs: Vec<OrderPair> = risk_manager.pending_pairs_snapshot();
+    let pair_of: HashMap<String, &OrderPair> = pairs
+        .iter()
+        .flat_map(|pair| {
+            [&pair.yes_order_id, &pair.no_order_id]
+                .into_iter()
+                .chain(&pair.child_order_ids)
+                .map(move |id| (id.clone(), pair))
+        })
+        .filter(|(id, _)| !id.is_empty())
+        .collect();
+    let open = executor.open_orders().await?;
+
+    // 检查点中登记、但已不在订单簿上的挂单（已成交或已撤）不再跟踪
+    let open_ids: HashSet<&str> = open.iter().map(|(id, _)| id.as_str()).collect();
+    order_store.retain(|order| open_ids.contains(order.order_id.as_str()));
+
+    let (adopted, unknown): (Vec<_>, Vec<_>) = open
+        .into_iter()
+        .partition(|(id, _)| order_store.contains(id) || pair_of.contains_key(id));
+    if adopted.is_empty() && unknown.is_empty() {
+        return Ok(());
+    }
+
+    if !adopted.is_empty() {
+        for (id, token_id) in &adopted {
+            let Some(pair) = pair_of.get(id).filter(|_| !order_store.contains(id)) else {
+                continue;
+            };
+            let (price, size) = if *token_id == pair.yes_token_id {
+                (pair.yes_price, pair.yes_size - pair.yes_filled)
+            } else {
+                (pair.no_price, pair.no_size - pair.no_filled)
+            };
+            order_store.insert(TrackedOrder {
+                order_id: id.clone(),
+                market_id: pair.market_id,
+                token_id: *token_id,
+                price,
+                size,
+                cancel_at,
+                source: "startup_adopted".to_string(),
+            });
+        }
+        info!(
+            "🤝 启动对账：接管 {} 个挂单（订单对与挂单登记），{} 前自动撤单",
+            adopted.len(),
+            cancel_at.format("%H:%M:%S")
+        );
+        metrics::add("startup_orders_adopted", adopted.len() as u64);
+    }
+    let mut cancelled = 0;
+    if !unknown.is_empty() {
+        for (id, token_id) in &unknown {
+            warn!(order_id = %id, token_id = %token_id, "启动对账：未知挂单");
+        }
+        if cancel_unknown {
+            let refs: Vec<&str> = unknown.iter().map(|(id, _)| id.as_str()).collect();
+            executor.cancel_order_ids(&refs).await?;
+            cancelled = unknown.len();
+            info!("🧹 启动对账：已撤销 {} 个未知挂单", cancelled);
+            metrics::add("startup_orders_cancelled", cancelled as u64);
+        } else {
+            warn!(
+                "⚠️ 启动对账：{} 个未知挂单未撤销（STARTUP_CANCEL_UNKNOWN_ORDERS=false），其成交不计入机器人持仓与敞口",
+                unknown.len()
+            );
+        }
+    }
+    journal::record(
+        "startup_orders",
+        json!({
+            "adopted": adopted.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            "unknown": unknown.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            "cancelled": cancelled,
+        }),
+    );
+    Ok(())
+}
@@ -0,0 +1,124 @@
+//! TWAP 卖出：把一笔较大的持仓在设定时长内切成若干子单依次卖出，子单数量在均分值附近随机浮动，
+//! 每片按当时的买一价以 FAK 吃单（买一低于下限时本片跳过，数量顺延到后面的片），减少在每小时市场的薄盘口上一次性砸价。
+//! 用于收尾平仓等大额退出；返回实际卖出数量，未卖完的部分由调用方兜底处理。
+
+use dashmap::DashMap;
+use polymarket_client_sdk::clob::types::{OrderType, Side};
+use polymarket_client_sdk::clob::ws::types::response::BookUpdate;
+use polymarket_client_sdk::types::{Decimal, U256};
+use rust_decimal_macros::dec;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+use super::executor::{BatchOrder, TradingExecutor};
+use crate::market::rules;
+use crate::utils::{journal, metrics};
+
+#[derive(Debug, Clone, Copy)]
+pub struct TwapSettings {
+    /// 总时长
+    pub duration: Duration,
+    /// 切片数
+    pub slices: u32,
+    /// 子单数量随机浮动比例：每片在 均分值 × [1 − pct, 1 + pct] 内取值
+    pub size_jitter_pct: f64,
+    /// 买一低于该价格时本片不卖
+    pub min_price: Decimal,
+}
+
+/// 本片数量：剩余 / 剩余片数，按比例随机浮动，保留两位小数，不超过剩余
+fn slice_size(remaining: Decimal, slices_left: u32, jitter_pct: f64) -> Decimal {
+    if slices_left <= 1 {
+        return remaining;
+    }
+    let even = remaining / Decimal::from(slices_left);
+    let pct = jitter_pct.clamp(0.0, 1.0);
+    let factor = 1.0 - pct + 2.0 * pct * rand::random::<f64>();
+    let size = (even * Decimal::try_from(factor).unwrap_or(dec!(1))).round_dp(2);
+    size.clamp(dec!(0.01), remaining)
+}
+
+/// 在 settings.duration 内分片卖出 token 的 total 份，返回实际卖出数量
+pub async fn sell(
+    executor: Arc<TradingExecutor>,
+    books: Arc<DashMap<U256, BookUpdate>>,
+    token_id: U256,
+    total: Decimal,
+    settings: TwapSettings,
+) -> Decimal {
+    let slices = settings.slices.max(1);
+    let interval = settings.duration / slices;
+    let mut remaining = (total * dec!(100)).floor() / dec!(100);
+    let mut sold = dec!(0);
+    info!(
+        "⏳ TWAP 卖出开始 | token_id={:#x} | 数量:{} | {}片 | 时长:{}秒",
+        token_id,
+        remaining,
+        slices,
+        settings.duration.as_secs()
+    );
+
+    for slice in 0..slices {
+        if slice > 0 {
+            tokio::time::sleep(interval).await;
+        }
+        if remaining < dec!(0.01) {
+            break;
+        }
+        // 数组末尾为最优价
+        let Some(bid) = books.get(&token_id).and_then(|b| b.bids.last().map(|o| o.price)) else {
+            debug!(token_id = %token_id, slice, "TWAP：无买盘，本片跳过");
+            continue;
+        };
+        if bid < settings.min_price {
+            debug!(token_id = %token_id, slice, bid = %bid, "TWAP：买一低于下限，本片跳过");
+            continue;
+        }
+        let size = slice_size(remaining, slices - slice, settings.size_jitter_pct);
+        // 交易所最小下单金额 $1：不足时并入后面的片（最后一片照常提交）；
+        // 低于最小下单数量的也并入后面的片，最后一片仍不足时不提交（必被拒），留给调用方兜底
+        let below_min = size < rules::min_order_size(token_id);
+        if (size * bid <= dec!(1) || below_min) && slice + 1 < slices {
+            continue;
+        }
+        if below_min {
+            debug!(token_id = %token_id, size = %size, "TWAP：剩余低于最小下单数量，停止");
+            break;
+        }
+        let order = BatchOrder {
+            token_id,
+            side: Side::Sell,
+            price: bid,
+            size,
+            order_type: OrderType::FAK,
+            expiration: None,
+        };
+        let filled = match executor.submit_single(&order).await {
+            Ok(result) => result.making_amount,
+            Err(e) => {
+                warn!(token_id = %token_id, slice, error = %e, "TWAP：子单提交失败");
+                metrics::incr("twap_slice_failed");
+                dec!(0)
+            }
+        };
+        let filled = filled.min(remaining);
+        remaining -= filled;
+        sold += filled;
+        journal::record(
+            "twap_slice",
+            json!({
+                "token_id": format!("{:#x}", token_id),
+                "slice": slice,
+                "price": bid.to_string(),
+                "size": size.to_string(),
+                "filled": filled.to_string(),
+                "remaining": remaining.to_string(),
+            }),
+        );
+    }
+
+    info!("⏳ TWAP 卖出结束 | token_id={:#x} | 已卖:{} | 剩余:{}", token_id, sold, remaining);
+    sold
+}
@@ -0,0 +1,108 @@
+//! 订单簿录制（JSONL）：每次 YES/NO 订单簿对更新时记录双边卖盘前几档与买一价，供 optimize 子命令离线回放。
+//! 写盘在独立线程中进行，热路径只做一次 try_send；队列满时丢弃该条记录。未设置 BOOK_RECORD_PATH 时为空操作。
+
+use polymarket_client_sdk::clob::ws::types::response::BookUpdate;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::mpsc::{self, RecvTimeoutError, SyncSender};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::monitor::OrderBookPair;
+
+/// 每侧录制的卖盘档数
+const RECORD_DEPTH: usize = 5;
+/// 写盘队列容量
+const QUEUE_CAPACITY: usize = 10_000;
+
+/// 一条录制记录；价格与数量以字符串保存，避免浮点误差
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookRecord {
+    /// 毫秒时间戳
+    pub ts_ms: i64,
+    pub market_id: String,
+    /// 卖盘，最优价在前：[价格, 数量]
+    pub yes_asks: Vec<[String; 2]>,
+    pub no_asks: Vec<[String; 2]>,
+    /// 买一价
+    pub yes_bid: Option<String>,
+    pub no_bid: Option<String>,
+}
+
+static RECORDER: OnceLock<SyncSender<BookRecord>> = OnceLock::new();
+
+/// 启动录制线程（追加写入）；path 为空时不启用
+pub fn init(path: &str) {
+    if path.trim().is_empty() {
+        return;
+    }
+    let path = Path::new(path);
+    if let Some(dir) = path.parent() {
+        if !dir.as_os_str().is_empty() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+    }
+    let file = match OpenOptions::new().create(true).append(true).open(path) {
+        Ok(file) => file,
+        Err(e) => {
+            warn!(error = %e, path = %path.display(), "打开订单簿录制文件失败，不录制");
+            return;
+        }
+    };
+    let (tx, rx) = mpsc::sync_channel::<BookRecord>(QUEUE_CAPACITY);
+    let spawned = std::thread::Builder::new().name("book-recorder".to_string()).spawn(move || {
+        let mut writer = BufWriter::new(file);
+        loop {
+            match rx.recv_timeout(Duration::from_secs(1)) {
+                Ok(record) => {
+                    if let Ok(line) = serde_json::to_string(&record) {
+                        let _ = writeln!(writer, "{}", line);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    let _ = writer.flush();
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    let _ = writer.flush();
+                    return;
+                }
+            }
+        }
+    });
+    match spawned {
+        Ok(_) => {
+            let _ = RECORDER.set(tx);
+            info!(path = %path.display(), "📼 订单簿录制已启用");
+        }
+        Err(e) => warn!(error = %e, "启动订单簿录制线程失败，不录制"),
+    }
+}
+
+fn top_asks(book: &BookUpdate) -> Vec<[String; 2]> {
+    // 数组末尾为最优价，倒序取前几档
+    book.asks
+        .iter()
+        .rev()
+        .take(RECORD_DEPTH)
+        .map(|o| [o.price.to_string(), o.size.to_string()])
+        .collect()
+}
+
+/// 录制一次订单簿对（未启用时为空操作）
+pub fn record(pair: &OrderBookPair) {
+    let Some(tx) = RECORDER.get() else {
+        return;
+    };
+    let record = BookRecord {
+        ts_ms: chrono::Utc::now().timestamp_millis(),
+        market_id: format!("{:#x}", pair.market_id),
+        yes_asks: top_asks(&pair.yes_book),
+        no_asks: top_asks(&pair.no_book),
+        yes_bid: pair.yes_book.bids.last().map(|o| o.price.to_string()),
+        no_bid: pair.no_book.bids.last().map(|o| o.price.to_string()),
+    };
+    let _ = tx.try_send(record);
+}
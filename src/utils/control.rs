@@ -0,0 +1,288 @@
+//! 控制/调试 HTTP 接口：需 Bearer token 认证，默认不启用。
+//! `GET /debug/state` 导出当前内存状态（订阅市场、本地订单簿前几档、订单对、持仓、敞口与各策略额度用量、盈亏、计数器与脱敏配置）为 JSON，
+//! 线上出问题时无需挂调试器即可查看机器人眼中的世界。
+//! `GET /log/level` 查看、`PUT /log/level`（请求体为过滤指令，如 `info,poly_1hour_bot::monitor=debug`）
+//! 运行时调整各模块日志级别，排查问题无需重启而丢失当前窗口。
+
+use anyhow::Result;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::Utc;
+use dashmap::DashMap;
+use polymarket_client_sdk::clob::ws::types::response::BookUpdate;
+use polymarket_client_sdk::types::{B256, U256};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+use tracing::{info, warn};
+
+use super::{logger, metrics};
+use crate::config::Config;
+use crate::market::{MarketDiscoverer, MarketInfo};
+use crate::risk::budget::Strategy;
+use crate::risk::checkpoint::OrderPairRecord;
+use crate::risk::RiskManager;
+
+/// 调试输出的订单簿档数
+const BOOK_DEPTH: usize = 5;
+
+/// 主循环发布的实时状态：当前订阅的市场与订单簿缓存（与 OrderBookMonitor 共享同一 DashMap，不额外复制）
+#[derive(Default)]
+struct LiveState {
+    markets: RwLock<Vec<MarketInfo>>,
+    books: RwLock<Option<Arc<DashMap<U256, BookUpdate>>>>,
+}
+
+static LIVE: OnceLock<LiveState> = OnceLock::new();
+
+/// 发布当前订阅的市场（窗口开始、市场增减时调用；接口未启用时为空操作）
+pub fn publish_markets(markets: &HashMap<B256, MarketInfo>) {
+    let Some(live) = LIVE.get() else {
+        return;
+    };
+    if let Ok(mut guard) = live.markets.write() {
+        *guard = markets.values().cloned().collect();
+    }
+}
+
+/// 发布当前窗口订单簿缓存的句柄（每个窗口新建 OrderBookMonitor 后调用）
+pub fn publish_books(books: Arc<DashMap<U256, BookUpdate>>) {
+    let Some(live) = LIVE.get() else {
+        return;
+    };
+    if let Ok(mut guard) = live.books.write() {
+        *guard = Some(books);
+    }
+}
+
+/// 去掉 URL 中可能携带凭据的部分（userinfo、路径中的 API Key、查询参数），只保留 `scheme://host[:port]`
+fn redact_url(url: &str) -> String {
+    let url = url.trim();
+    if url.is_empty() {
+        return String::new();
+    }
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return "<redacted>".to_string();
+    };
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority.rsplit_once('@').map(|(_, host)| host).unwrap_or(authority);
+    format!("{}://{}", scheme, host)
+}
+
+/// /debug/state 中展示的配置：逐项列出非敏感字段，不转储整个 Config，
+/// 私钥、控制接口 token、webhook 密钥、共享账本密钥等永远不会出现；URL 一律经 redact_url 处理。
+/// 新增配置项默认不展示，确认不含敏感信息后再加入。
+fn config_summary(config: &Config) -> Value {
+    json!({
+        "crypto_symbols": config.crypto_symbols,
+        "market_timezone": config.market_timezone.to_string(),
+        "arbitrage_order_type": format!("{:?}", config.arbitrage_order_type),
+        "hedge_policy": format!("{:?}", config.hedge_policy),
+        "min_profit_threshold": config.min_profit_threshold,
+        "max_order_size_usdc": config.max_order_size_usdc,
+        "arbitrage_execution_spread": config.arbitrage_execution_spread,
+        "ladder_execution_spread": config.ladder_execution_spread,
+        "risk_max_exposure_usdc": config.risk_max_exposure_usdc,
+        "risk_imbalance_threshold": config.risk_imbalance_threshold,
+        "stop_arbitrage_before_end_minutes": config.stop_arbitrage_before_end_minutes,
+        "wind_down_before_window_end_minutes": config.wind_down_before_window_end_minutes,
+        "merge_on_pair_fill": config.merge_on_pair_fill,
+        "merge_interval_minutes": config.merge_interval_minutes,
+        "endpoints": {
+            "clob_rest": redact_url(&config.endpoints.clob_rest),
+            "clob_ws": redact_url(&config.endpoints.clob_ws),
+            "gamma": redact_url(&config.endpoints.gamma),
+        },
+    })
+}
+
+#[derive(Clone)]
+struct ControlState {
+    token: Arc<String>,
+    risk_manager: Arc<RiskManager>,
+    /// 配置摘要（只含白名单内的非敏感字段）
+    config_summary: Arc<Value>,
+}
+
+/// 启动控制接口；addr 为空时不启用，未设置 token 时拒绝启动（接口会暴露持仓与配置）
+pub fn spawn(addr: &str, token: &str, risk_manager: Arc<RiskManager>, config: &Config) -> Result<()> {
+    if addr.trim().is_empty() {
+        return Ok(());
+    }
+    if token.trim().is_empty() {
+        anyhow::bail!("CONTROL_API_ADDR 已设置但 CONTROL_API_TOKEN 为空，控制接口未启动");
+    }
+    let _ = LIVE.set(LiveState::default());
+
+    let state = ControlState {
+        token: Arc::new(token.trim().to_string()),
+        risk_manager,
+        config_summary: Arc::new(config_summary(config)),
+    };
+    let app = Router::new()
+        .route("/debug/state", get(debug_state))
+        .route("/log/level", get(get_log_level).put(put_log_level))
+        .with_state(state);
+
+    let addr = addr.trim().to_string();
+    tokio::spawn(async move {
+        let listener = match tokio::net::TcpListener::bind(&addr).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                warn!(error = %e, addr = %addr, "控制接口监听失败");
+                return;
+            }
+        };
+        info!(addr = %addr, "🔧 控制接口已启动（GET /debug/state，GET/PUT /log/level）");
+        if let Err(e) = axum::serve(listener, app).await {
+            warn!(error = %e, "控制接口退出");
+        }
+    });
+    Ok(())
+}
+
+/// 校验 Authorization: Bearer <token>
+fn authorized(headers: &HeaderMap, token: &str) -> bool {
+    headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(|v| v.trim() == token)
+        .unwrap_or(false)
+}
+
+async fn get_log_level(State(state): State<ControlState>, headers: HeaderMap) -> Response {
+    if !authorized(&headers, &state.token) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    Json(json!({ "filter": logger::current_filter() })).into_response()
+}
+
+async fn put_log_level(State(state): State<ControlState>, headers: HeaderMap, body: String) -> Response {
+    if !authorized(&headers, &state.token) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    if body.trim().is_empty() {
+        return (StatusCode::BAD_REQUEST, "请求体须为日志过滤指令").into_response();
+    }
+    match logger::set_filter(&body) {
+        Ok(filter) => {
+            info!(filter = %filter, "🔧 日志级别已调整");
+            Json(json!({ "filter": filter })).into_response()
+        }
+        Err(e) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    }
+}
+
+fn levels(book: &BookUpdate) -> Value {
+    // 订单簿数组末尾为最优价，倒序取前几档
+    let bids: Vec<Value> = book
+        .bids
+        .iter()
+        .rev()
+        .take(BOOK_DEPTH)
+        .map(|o| json!([o.price.to_string(), o.size.to_string()]))
+        .collect();
+    let asks: Vec<Value> = book
+        .asks
+        .iter()
+        .rev()
+        .take(BOOK_DEPTH)
+        .map(|o| json!([o.price.to_string(), o.size.to_string()]))
+        .collect();
+    json!({ "bids": bids, "asks": asks })
+}
+
+async fn debug_state(State(state): State<ControlState>, headers: HeaderMap) -> Response {
+    if !authorized(&headers, &state.token) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    let live = LIVE.get();
+    let books = live.and_then(|l| l.books.read().ok().and_then(|b| b.clone()));
+    let book_of = |token: &U256| -> Value {
+        books
+            .as_ref()
+            .and_then(|b| b.get(token).map(|book| levels(&book)))
+            .unwrap_or(Value::Null)
+    };
+    let markets: Vec<Value> = live
+        .and_then(|l| l.markets.read().ok().map(|m| m.clone()))
+        .unwrap_or_default()
+        .iter()
+        .map(|m| {
+            json!({
+                "market_id": m.market_id.to_string(),
+                "slug": m.slug,
+                "title": m.title,
+                "symbol": m.crypto_symbol,
+                "end_date": m.end_date,
+                "yes_token_id": m.yes_token_id.to_string(),
+                "no_token_id": m.no_token_id.to_string(),
+                "yes_book": book_of(&m.yes_token_id),
+                "no_book": book_of(&m.no_token_id),
+            })
+        })
+        .collect();
+
+    let position_tracker = state.risk_manager.position_tracker();
+    let snapshot = position_tracker.snapshot();
+    let positions: HashMap<String, String> = snapshot
+        .positions
+        .iter()
+        .filter(|(_, qty)| !qty.is_zero())
+        .map(|(token, qty)| (token.to_string(), qty.to_string()))
+        .collect();
+    let exposure_costs: HashMap<String, String> = snapshot
+        .exposure_costs
+        .iter()
+        .map(|(token, cost)| (token.to_string(), cost.to_string()))
+        .collect();
+    let pairs: Vec<OrderPairRecord> = state
+        .risk_manager
+        .pending_pairs_snapshot()
+        .iter()
+        .map(OrderPairRecord::from)
+        .collect();
+    let budgets: Vec<Value> = Strategy::ALL
+        .into_iter()
+        .map(|strategy| {
+            let usage = position_tracker.budget_usage(strategy);
+            json!({
+                "strategy": strategy.as_str(),
+                "committed": usage.committed.to_string(),
+                "reserved": usage.reserved.to_string(),
+                "limit": usage.limit.map(|l| l.to_string()),
+            })
+        })
+        .collect();
+    let pnl = state.risk_manager.pnl().summary();
+    let counters: HashMap<&str, u64> = metrics::snapshot().into_iter().collect();
+    let gauges: HashMap<&str, f64> = metrics::gauge_snapshot().into_iter().collect();
+
+    Json(json!({
+        "ts": Utc::now(),
+        "window": MarketDiscoverer::calculate_current_window_timestamp(Utc::now()),
+        "markets": markets,
+        "pairs": pairs,
+        "positions": positions,
+        "exposure_costs": exposure_costs,
+        "total_exposure": snapshot.total_exposure.to_string(),
+        "max_exposure": position_tracker.max_exposure().to_string(),
+        "strategy_budgets": budgets,
+        "pnl": {
+            "matched_shares": pnl.matched_shares.to_string(),
+            "locked_profit_usd": pnl.locked_profit_usd.to_string(),
+            "gas_cost_usd": pnl.gas_cost_usd.to_string(),
+            "net_usd": pnl.net_usd().to_string(),
+            "utilization_pct": pnl.utilization_pct().map(|p| p.round_dp(4).to_string()),
+            "roi_per_deployed_hour_pct": pnl.roi_per_deployed_hour_pct().map(|p| p.round_dp(6).to_string()),
+        },
+        "metrics": counters,
+        "gauges": gauges,
+        "config": state.config_summary.as_ref(),
+    }))
+    .into_response()
+}
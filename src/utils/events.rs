@@ -0,0 +1,245 @@
+//! 内部事件总线：套利机会、下单结果、merge 完成等生命周期事件以统一的 JSON 结构广播给外部输出（Redis 等），
+//! 供看板、分析任务或其他机器人消费实时数据，而不必侵入主进程。没有任何输出订阅时 publish 为空操作。
+//!
+//! 事件 JSON 结构（版本 v=1；价格、数量、金额均为十进制字符串，ID 为 0x 开头的十六进制或十进制字符串）：
+//!
+//! ```text
+//! 公共字段: { "v": 1, "id": "事件唯一 ID（UUID，供下游去重）", "ts": "RFC3339 时间", "type": "<事件类型>", ... }
+//!
+//! opportunity_detected  检测到套利机会（尚未经过阈值/风控过滤）
+//!   market_id, market, yes_token_id, no_token_id,
+//!   yes_ask, no_ask, total_cost, profit_pct, yes_size, no_size
+//!
+//! order_executed        一对订单提交完成（含部分成交）
+//!   pair_id, market_id, market, yes_order_id, no_order_id,
+//!   yes_price, no_price, yes_size, no_size, yes_filled, no_filled
+//!
+//! merge_completed       一笔 merge 交易成功
+//!   tx, total_usdc, markets: [{ condition_id, amount }]
+//!
+//! recovery_action       订单对部分/单边成交后风控决定的恢复动作
+//!   pair_id, action（buy_missing / monitor_for_exit / sell_excess / manual_intervention）,
+//!   token_id, amount, detail
+//!
+//! limit_breached        下单被风控限额拒绝
+//!   limit（max_exposure）, market, current, attempted, max
+//!
+//! rejection_alert       拒单处置策略（REJECT_POLICY）中配置了 alert 的拒单
+//!   reason（price_moved / min_size / balance / rate_limit / auth / other）, detail
+//! ```
+
+use chrono::Utc;
+use polymarket_client_sdk::types::{Decimal, B256, U256};
+use serde::Serialize;
+use std::sync::{Arc, OnceLock};
+use tokio::sync::broadcast;
+
+use crate::monitor::arbitrage::ArbitrageOpportunity;
+use crate::risk::recovery::RecoveryAction;
+use crate::trading::executor::OrderPairResult;
+
+/// 事件结构版本，字段有不兼容变更时递增
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// 总线缓冲：输出端落后超过该条数时丢弃最旧的事件
+const BUS_CAPACITY: usize = 1024;
+
+static BUS: OnceLock<broadcast::Sender<Arc<EventEnvelope>>> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MergedMarket {
+    pub condition_id: String,
+    pub amount: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BotEvent {
+    OpportunityDetected {
+        market_id: String,
+        market: String,
+        yes_token_id: String,
+        no_token_id: String,
+        yes_ask: String,
+        no_ask: String,
+        total_cost: String,
+        profit_pct: String,
+        yes_size: String,
+        no_size: String,
+    },
+    OrderExecuted {
+        pair_id: String,
+        market_id: String,
+        market: String,
+        yes_order_id: String,
+        no_order_id: String,
+        yes_price: String,
+        no_price: String,
+        yes_size: String,
+        no_size: String,
+        yes_filled: String,
+        no_filled: String,
+    },
+    MergeCompleted {
+        tx: String,
+        total_usdc: String,
+        markets: Vec<MergedMarket>,
+    },
+    RecoveryAction {
+        pair_id: String,
+        action: String,
+        token_id: String,
+        amount: String,
+        detail: String,
+    },
+    LimitBreached {
+        limit: String,
+        market: String,
+        current: String,
+        attempted: String,
+        max: String,
+    },
+    RejectionAlert {
+        reason: String,
+        detail: String,
+    },
+}
+
+impl BotEvent {
+    pub fn opportunity(opp: &ArbitrageOpportunity, market: &str) -> Self {
+        BotEvent::OpportunityDetected {
+            market_id: format!("{:#x}", opp.market_id),
+            market: market.to_string(),
+            yes_token_id: opp.yes_token_id.to_string(),
+            no_token_id: opp.no_token_id.to_string(),
+            yes_ask: opp.yes_ask_price.to_string(),
+            no_ask: opp.no_ask_price.to_string(),
+            total_cost: opp.total_cost.to_string(),
+            profit_pct: opp.profit_percentage.to_string(),
+            yes_size: opp.yes_size.to_string(),
+            no_size: opp.no_size.to_string(),
+        }
+    }
+
+    pub fn executed(result: &OrderPairResult, market_id: B256, market: &str) -> Self {
+        BotEvent::OrderExecuted {
+            pair_id: result.pair_id.clone(),
+            market_id: format!("{:#x}", market_id),
+            market: market.to_string(),
+            yes_order_id: result.yes_order_id.clone(),
+            no_order_id: result.no_order_id.clone(),
+            yes_price: result.yes_price.to_string(),
+            no_price: result.no_price.to_string(),
+            yes_size: result.yes_size.to_string(),
+            no_size: result.no_size.to_string(),
+            yes_filled: result.yes_filled.to_string(),
+            no_filled: result.no_filled.to_string(),
+        }
+    }
+
+    /// merged 为 (condition_id, 合并数量的最小单位)，1 份 = 1 USDC（6 位小数）
+    pub fn merged(tx: &str, merged: &[(B256, U256)]) -> Self {
+        let to_usdc = |amount: U256| Decimal::from(amount.to::<u64>()) / Decimal::from(1_000_000u64);
+        let total = merged.iter().fold(Decimal::ZERO, |sum, (_, amount)| sum + to_usdc(*amount));
+        BotEvent::MergeCompleted {
+            tx: tx.to_string(),
+            total_usdc: total.to_string(),
+            markets: merged
+                .iter()
+                .map(|(condition_id, amount)| MergedMarket {
+                    condition_id: format!("{:#x}", condition_id),
+                    amount: to_usdc(*amount).to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    /// 恢复动作事件；RecoveryAction::None 不产生事件
+    pub fn recovery(pair_id: &str, action: &RecoveryAction) -> Option<Self> {
+        let (name, token_id, amount, detail) = match action {
+            RecoveryAction::None => return None,
+            RecoveryAction::SellExcess { token_id, amount } => ("sell_excess", token_id.clone(), *amount, String::new()),
+            RecoveryAction::MonitorForExit { token_id, amount, entry_price, .. } => (
+                "monitor_for_exit",
+                token_id.to_string(),
+                *amount,
+                format!("entry_price={}", entry_price),
+            ),
+            RecoveryAction::BuyMissing { token_id, amount, max_price, .. } => (
+                "buy_missing",
+                token_id.to_string(),
+                *amount,
+                format!("max_price={}", max_price),
+            ),
+            RecoveryAction::ManualIntervention { reason } => {
+                ("manual_intervention", String::new(), Decimal::ZERO, reason.clone())
+            }
+        };
+        Some(BotEvent::RecoveryAction {
+            pair_id: pair_id.to_string(),
+            action: name.to_string(),
+            token_id,
+            amount: amount.to_string(),
+            detail,
+        })
+    }
+
+    /// 风险敞口超限拒单
+    pub fn exposure_breached(market: &str, current: Decimal, attempted: Decimal, max: Decimal) -> Self {
+        BotEvent::LimitBreached {
+            limit: "max_exposure".to_string(),
+            market: market.to_string(),
+            current: current.to_string(),
+            attempted: attempted.to_string(),
+            max: max.to_string(),
+        }
+    }
+
+    /// 拒单告警
+    pub fn rejection_alert(reason: &str, detail: &str) -> Self {
+        BotEvent::RejectionAlert {
+            reason: reason.to_string(),
+            detail: detail.to_string(),
+        }
+    }
+
+    /// 事件类型名（与 JSON 中 type 字段一致），输出端用于拼接频道名
+    pub fn kind(&self) -> &'static str {
+        match self {
+            BotEvent::OpportunityDetected { .. } => "opportunity_detected",
+            BotEvent::OrderExecuted { .. } => "order_executed",
+            BotEvent::MergeCompleted { .. } => "merge_completed",
+            BotEvent::RecoveryAction { .. } => "recovery_action",
+            BotEvent::LimitBreached { .. } => "limit_breached",
+            BotEvent::RejectionAlert { .. } => "rejection_alert",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EventEnvelope {
+    pub v: u32,
+    pub id: String,
+    pub ts: String,
+    #[serde(flatten)]
+    pub event: BotEvent,
+}
+
+/// 订阅总线（首次订阅时创建总线）；由各输出端在启动时调用
+pub fn subscribe() -> broadcast::Receiver<Arc<EventEnvelope>> {
+    BUS.get_or_init(|| broadcast::channel(BUS_CAPACITY).0).subscribe()
+}
+
+/// 广播一条事件（没有输出端订阅时为空操作）
+pub fn publish(event: BotEvent) {
+    let Some(bus) = BUS.get() else {
+        return;
+    };
+    let envelope = EventEnvelope {
+        v: EVENT_SCHEMA_VERSION,
+        id: uuid::Uuid::new_v4().to_string(),
+        ts: Utc::now().to_rfc3339(),
+        event,
+    };
+    let _ = bus.send(Arc::new(envelope));
+}
@@ -0,0 +1,93 @@
+//! 重复实例保护：同一私钥 / 代理钱包意外启动了两个机器人时，两边各自按完整敞口预算下单，实际敞口翻倍。
+//! 启动交易前做两项检查，任一发现另一个存活实例即拒绝启动：
+//! - 本机文件锁：`{INSTANCE_LOCK_DIR}/instance-{资金地址}.lock` 上的排他锁，进程存活期间一直持有，
+//!   进程退出（含崩溃）后由操作系统释放，不会留下需要手动清理的陈旧锁；
+//! - 交易所侧探测：间隔 INSTANCE_GUARD_PROBE_SECS 两次列出账户挂单，期间出现了新挂单说明有别的进程正在用同一账户下单
+//!   （另一台机器上的实例，或人工交易；只用 FAK 吃单、从不挂单的实例探测不到）。
+//!
+//! 主备（LEADER_LOCK_PATH）与共享敞口账本（SHARED_LEDGER_URL）是有意让多个实例共用一个钱包，两者任一启用时不做检查。
+
+use anyhow::Result;
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use super::metrics;
+use crate::trading::TradingExecutor;
+
+/// 进程存活期间持有的锁文件
+static LOCK: OnceLock<File> = OnceLock::new();
+
+#[derive(Debug, Clone)]
+pub struct GuardSettings {
+    pub enabled: bool,
+    /// 锁文件目录
+    pub lock_dir: String,
+    /// 交易所侧探测的间隔（秒），0 = 只用文件锁
+    pub probe_secs: u64,
+}
+
+/// 检查是否有另一个实例在使用同一资金地址（funder）；发现时返回错误（调用方应拒绝启动交易）。
+/// instance_id 写入锁文件便于排查
+pub async fn acquire(
+    settings: &GuardSettings,
+    funder: &str,
+    instance_id: &str,
+    executor: &Arc<TradingExecutor>,
+) -> Result<()> {
+    if !settings.enabled {
+        return Ok(());
+    }
+    lock_file(&settings.lock_dir, funder, instance_id)?;
+    if settings.probe_secs > 0 {
+        probe_exchange(executor, Duration::from_secs(settings.probe_secs)).await?;
+    }
+    info!("🔒 未发现使用同一账户的其他实例 | 资金地址:{}", funder);
+    Ok(())
+}
+
+fn lock_file(dir: &str, funder: &str, instance_id: &str) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let path = Path::new(dir).join(format!("instance-{}.lock", funder.to_lowercase()));
+    let mut file = OpenOptions::new().create(true).truncate(false).write(true).open(&path)?;
+    if file.try_lock().is_err() {
+        metrics::incr("duplicate_instance_detected");
+        anyhow::bail!(
+            "另一个实例正在使用同一账户（锁文件 {} 被占用），拒绝启动交易",
+            path.display()
+        );
+    }
+    file.set_len(0)?;
+    writeln!(file, "pid={} instance_id={}", std::process::id(), instance_id)?;
+    let _ = LOCK.set(file);
+    Ok(())
+}
+
+async fn probe_exchange(executor: &Arc<TradingExecutor>, interval: Duration) -> Result<()> {
+    let before: HashSet<String> = executor.open_orders().await?.into_iter().map(|(id, _)| id).collect();
+    info!("🔍 探测是否有其他实例在同一账户下单（{}秒）", interval.as_secs());
+    tokio::time::sleep(interval).await;
+    let appeared: Vec<String> = executor
+        .open_orders()
+        .await?
+        .into_iter()
+        .map(|(id, _)| id)
+        .filter(|id| !before.contains(id))
+        .collect();
+    if appeared.is_empty() {
+        return Ok(());
+    }
+    metrics::incr("duplicate_instance_detected");
+    for id in &appeared {
+        warn!(order_id = %id, "探测期间账户上出现了新挂单");
+    }
+    anyhow::bail!(
+        "探测期间账户上出现了 {} 个新挂单，另一个实例（或人工交易）正在使用同一账户，拒绝启动交易；\
+         确认无冲突后可设 INSTANCE_GUARD_PROBE_SECS=0 跳过该项检查",
+        appeared.len()
+    )
+}
@@ -0,0 +1,57 @@
+//! 交易日志（JSONL）：每个关键决策/下单事件追加一行 JSON（时间、事件名与字段），便于事后复盘与统计。
+//! 启动时由 Config.journal_path 初始化一次；未初始化或路径为空时不写文件。
+//! 启用存储后端（STORAGE_URL）时每条事件同时写入数据库的 journal 表。
+//! 启用本地状态加密（state_crypto）时每行单独加密后追加。
+
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use tracing::{debug, warn};
+
+static JOURNAL: OnceLock<Mutex<File>> = OnceLock::new();
+
+/// 打开（追加）日志文件；path 为空时不启用
+pub fn init(path: &str) {
+    if path.trim().is_empty() {
+        return;
+    }
+    let path = Path::new(path);
+    if let Some(dir) = path.parent() {
+        if !dir.as_os_str().is_empty() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+    }
+    match OpenOptions::new().create(true).append(true).open(path) {
+        Ok(file) => {
+            let _ = JOURNAL.set(Mutex::new(file));
+        }
+        Err(e) => warn!(error = %e, path = %path.display(), "打开交易日志失败，日志不落盘"),
+    }
+}
+
+/// 追加一条事件：fields 须为 JSON 对象，写入时附加 ts 与 event 字段
+pub fn record(event: &str, fields: Value) {
+    let mut line = json!({ "ts": Utc::now().to_rfc3339(), "event": event });
+    if let (Some(line_obj), Value::Object(extra)) = (line.as_object_mut(), fields) {
+        line_obj.extend(extra);
+    }
+    super::storage::record_event(event, &line);
+    let Some(journal) = JOURNAL.get() else {
+        return;
+    };
+    let text = match super::state_crypto::seal_line(&line.to_string()) {
+        Ok(text) => text,
+        Err(e) => {
+            debug!(error = %e, event, "加密交易日志失败");
+            return;
+        }
+    };
+    if let Ok(mut file) = journal.lock() {
+        if let Err(e) = writeln!(file, "{}", text) {
+            debug!(error = %e, event, "写入交易日志失败");
+        }
+    }
+}
@@ -0,0 +1,114 @@
+//! 热路径分阶段耗时统计：各阶段（解析、检测、风控、构建、签名、发送、回执）记录样本，
+//! 每个窗口结束时输出 p50/p95/p99 并清空，无需外部工具即可发现性能退化。
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tracing::info;
+
+/// 热路径阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+    /// 订单簿更新解析与缓存（handle_book_update）
+    Decode,
+    /// 套利检测（check_arbitrage）
+    Detect,
+    /// 下单前风控检查（敞口、持仓平衡、交易间隔等）
+    Risk,
+    /// 订单构建
+    Build,
+    /// 订单签名
+    Sign,
+    /// 批量下单 HTTP 往返
+    Post,
+    /// 端到端：收到订单簿更新 → 收到下单回执
+    Ack,
+}
+
+impl Stage {
+    const ALL: [Stage; 7] = [
+        Stage::Decode,
+        Stage::Detect,
+        Stage::Risk,
+        Stage::Build,
+        Stage::Sign,
+        Stage::Post,
+        Stage::Ack,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Stage::Decode => "decode",
+            Stage::Detect => "detect",
+            Stage::Risk => "risk",
+            Stage::Build => "build",
+            Stage::Sign => "sign",
+            Stage::Post => "post",
+            Stage::Ack => "ack",
+        }
+    }
+}
+
+/// 每个阶段最多保留的样本数；超出后循环覆盖最旧样本，避免高频订单簿更新下内存无限增长
+const MAX_SAMPLES_PER_STAGE: usize = 100_000;
+
+/// 阶段 -> (样本微秒数, 累计记录次数)
+type StageSamples = HashMap<Stage, (Vec<u64>, u64)>;
+
+fn samples() -> &'static Mutex<StageSamples> {
+    static SAMPLES: OnceLock<Mutex<StageSamples>> = OnceLock::new();
+    SAMPLES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 记录某阶段的一次耗时
+pub fn record(stage: Stage, elapsed: Duration) {
+    let micros = elapsed.as_micros() as u64;
+    if let Ok(mut map) = samples().lock() {
+        let (values, count) = map.entry(stage).or_default();
+        if values.len() < MAX_SAMPLES_PER_STAGE {
+            values.push(micros);
+        } else {
+            values[(*count as usize) % MAX_SAMPLES_PER_STAGE] = micros;
+        }
+        *count += 1;
+    }
+}
+
+/// 取已排序样本的百分位数（毫秒）
+fn percentile_ms(sorted: &[u64], pct: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let idx = ((pct / 100.0) * (sorted.len() - 1) as f64).round() as usize;
+    sorted[idx.min(sorted.len() - 1)] as f64 / 1000.0
+}
+
+/// 输出本窗口各阶段的 p50/p95/p99 并清空样本（窗口切换时调用）
+pub fn report_and_reset(window_timestamp: i64) {
+    let taken = match samples().lock() {
+        Ok(mut map) => std::mem::take(&mut *map),
+        Err(_) => return,
+    };
+
+    if taken.is_empty() {
+        info!("⏱️ 窗口延迟统计 | 窗口:{} | 无样本", window_timestamp);
+        return;
+    }
+
+    info!("⏱️ 窗口延迟统计 | 窗口:{} | 单位:ms", window_timestamp);
+    for stage in Stage::ALL {
+        if let Some((values, count)) = taken.get(&stage) {
+            let mut sorted = values.clone();
+            sorted.sort_unstable();
+            info!(
+                "  {:<6} | 次数:{} | p50:{:.2} p95:{:.2} p99:{:.2} max:{:.2}",
+                stage.label(),
+                count,
+                percentile_ms(&sorted, 50.0),
+                percentile_ms(&sorted, 95.0),
+                percentile_ms(&sorted, 99.0),
+                sorted.last().copied().unwrap_or(0) as f64 / 1000.0
+            );
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! 主备高可用：两个实例共享同一租约文件（如 NFS / 共享卷），持有未过期租约的实例为主（leader）负责交易，
+//! 备机照常订阅行情、维护状态但不下单、不 merge、不写检查点；主机心跳停止、租约过期后备机在数秒内接管，
+//! 既避免停机也避免双实例同时交易。未启用时 `is_leader` 恒为 true。
+//!
+//! 租约文件为 JSON（持有者 ID + 过期时间），写临时文件后 rename 原子替换，写后回读确认持有者，
+//! 两个实例同时抢占时只有最后 rename 的一方生效。续约失败时立即降为备机，宁可短暂无人交易也不双开。
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use super::{journal, metrics};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Lease {
+    owner: String,
+    /// 过期时间（Unix 毫秒）
+    expires_at_ms: i64,
+}
+
+static IS_LEADER: OnceLock<AtomicBool> = OnceLock::new();
+
+/// 当前实例是否为主（未启用主备时恒为 true）
+pub fn is_leader() -> bool {
+    IS_LEADER.get().map(|l| l.load(Ordering::Acquire)).unwrap_or(true)
+}
+
+fn read_lease(path: &Path) -> Option<Lease> {
+    std::fs::read(path)
+        .ok()
+        .and_then(|body| serde_json::from_slice(&body).ok())
+}
+
+/// 尝试获取或续约租约，返回是否持有
+fn try_acquire(path: &Path, instance_id: &str, ttl: Duration) -> Result<bool> {
+    let now = chrono::Utc::now().timestamp_millis();
+    if let Some(lease) = read_lease(path) {
+        if lease.owner != instance_id && lease.expires_at_ms > now {
+            return Ok(false);
+        }
+    }
+    if let Some(dir) = path.parent() {
+        if !dir.as_os_str().is_empty() {
+            std::fs::create_dir_all(dir)?;
+        }
+    }
+    let lease = Lease {
+        owner: instance_id.to_string(),
+        expires_at_ms: now + ttl.as_millis() as i64,
+    };
+    // 每个实例使用自己的临时文件，避免两个实例互相覆盖写了一半的内容
+    let tmp = path.with_extension(format!("{}.tmp", instance_id));
+    std::fs::write(&tmp, serde_json::to_vec(&lease)?)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(read_lease(path).is_some_and(|l| l.owner == instance_id))
+}
+
+/// 启动租约心跳：先同步尝试一次（决定启动时的角色），之后每 ttl/3 续约或抢占。
+/// 返回角色变化通知（true=成为主）；path 为空时不启用，返回 None。
+pub fn start(path: &str, instance_id: &str, ttl: Duration) -> Option<watch::Receiver<bool>> {
+    if path.trim().is_empty() {
+        return None;
+    }
+    let path = PathBuf::from(path);
+    let instance_id = instance_id.to_string();
+    let initial = try_acquire(&path, &instance_id, ttl).unwrap_or_else(|e| {
+        warn!(error = %e, "获取主备租约失败，按备机启动");
+        false
+    });
+    let flag = IS_LEADER.get_or_init(|| AtomicBool::new(initial));
+    flag.store(initial, Ordering::Release);
+    if initial {
+        info!("👑 已获得主备租约，本实例为主 | 实例:{}", instance_id);
+    } else {
+        info!("🛌 主备租约由其他实例持有，本实例为备机（只维护行情与状态，不交易） | 实例:{}", instance_id);
+    }
+
+    let (tx, rx) = watch::channel(initial);
+    tokio::spawn(async move {
+        let heartbeat = (ttl / 3).max(Duration::from_millis(500));
+        loop {
+            tokio::time::sleep(heartbeat).await;
+            let was_leader = flag.load(Ordering::Acquire);
+            let holding = match try_acquire(&path, &instance_id, ttl) {
+                Ok(holding) => holding,
+                Err(e) => {
+                    if was_leader {
+                        warn!(error = %e, "续约主备租约失败，降为备机");
+                    }
+                    false
+                }
+            };
+            if holding == was_leader {
+                continue;
+            }
+            flag.store(holding, Ordering::Release);
+            metrics::incr(if holding { "leader_acquired" } else { "leader_lost" });
+            if holding {
+                warn!("👑 主实例心跳已停止，本实例接管交易 | 实例:{}", instance_id);
+            } else {
+                warn!("🛌 主备租约已被其他实例持有，本实例停止交易 | 实例:{}", instance_id);
+            }
+            journal::record(
+                if holding { "leader_acquired" } else { "leader_lost" },
+                json!({ "instance": instance_id }),
+            );
+            let _ = tx.send(holding);
+        }
+    });
+    Some(rx)
+}
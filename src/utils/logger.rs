@@ -1,19 +1,71 @@
 use anyhow::Result;
+use serde_json::{json, Map, Value};
 use std::fs::File;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::{layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Layer, Registry};
+
+/// 运行时可替换的日志过滤器句柄（控制接口调整日志级别用）
+static FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// 补全过滤指令：未显式配置 SDK 级别时屏蔽 polymarket SDK 的 serde unknown field 警告（如 feeType）
+fn normalize_directives(directives: &str) -> String {
+    if directives.contains("polymarket_client_sdk") {
+        directives.to_string()
+    } else {
+        format!("{},polymarket_client_sdk::serde_helpers=error", directives)
+    }
+}
+
+/// 运行时替换日志过滤指令（如 "info,poly_1hour_bot::monitor=debug"），无需重启、不丢失当前窗口状态
+pub fn set_filter(directives: &str) -> Result<String> {
+    let handle = FILTER_HANDLE
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("日志尚未初始化"))?;
+    let directives = normalize_directives(directives.trim());
+    let filter = EnvFilter::try_new(&directives)
+        .map_err(|e| anyhow::anyhow!("无效的日志过滤指令 {}: {}", directives, e))?;
+    handle.reload(filter)?;
+    Ok(directives)
+}
+
+/// 当前生效的日志过滤指令
+pub fn current_filter() -> Option<String> {
+    FILTER_HANDLE
+        .get()
+        .and_then(|handle| handle.with_current(|filter| filter.to_string()).ok())
+}
 
 pub fn init_logger() -> Result<()> {
+    // 先加载 .env，使 RUST_LOG / LOG_FILE / TUI_ENABLED 写在 .env 中也生效
+    dotenvy::dotenv().ok();
     // 设置默认日志级别为 info，如果没有设置 RUST_LOG 环境变量
-    // 屏蔽 polymarket SDK 的 serde unknown field 警告（如 feeType）
-    let filter_str = std::env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string());
-    let filter_str = if filter_str.contains("polymarket_client_sdk") {
-        filter_str
-    } else {
-        format!("{},polymarket_client_sdk::serde_helpers=error", filter_str)
-    };
+    let filter_str = normalize_directives(&std::env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string()));
     let env_filter = EnvFilter::try_new(&filter_str).unwrap_or_else(|_| EnvFilter::new("info"));
-    
-    if let Ok(path) = std::env::var("LOG_FILE") {
+    let (env_filter, filter_handle) = reload::Layer::new(env_filter);
+    let _ = FILTER_HANDLE.set(filter_handle);
+
+    // TUI 占用终端时日志必须写文件，未设置 LOG_FILE 则写到 state/bot.log
+    let tui_enabled = std::env::var("TUI_ENABLED")
+        .map(|s| s.trim() == "1" || s.trim().eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+    let log_file = std::env::var("LOG_FILE")
+        .ok()
+        .or_else(|| tui_enabled.then(|| "state/bot.log".to_string()));
+
+    // 可选：集中式日志（Loki / Elasticsearch）与 OpenTelemetry 追踪
+    let shipper = ShipLayer::from_env();
+
+    if let Some(path) = log_file {
+        if let Some(dir) = std::path::Path::new(&path).parent() {
+            if !dir.as_os_str().is_empty() {
+                std::fs::create_dir_all(dir)?;
+            }
+        }
         let file = File::create(path)?;
         tracing_subscriber::registry()
             .with(env_filter)
@@ -22,12 +74,218 @@ pub fn init_logger() -> Result<()> {
                     .with_writer(file)
                     .with_ansi(false),
             )
+            .with(shipper)
+            .with(super::telemetry::layer())
             .init();
     } else {
-        tracing_subscriber::fmt()
-            .with_env_filter(env_filter)
+        tracing_subscriber::registry()
+            .with(env_filter)
+            .with(tracing_subscriber::fmt::layer())
+            .with(shipper)
+            .with(super::telemetry::layer())
             .init();
     }
 
     Ok(())
 }
+
+/// 日志投递后端
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ShipBackend {
+    Loki,
+    Elasticsearch,
+}
+
+/// 投递任务配置（LOG_SHIP_* 环境变量）
+struct ShipConfig {
+    backend: ShipBackend,
+    url: String,
+    /// Elasticsearch 索引名
+    index: String,
+    /// 可选 Authorization 头（如 "Basic xxx" 或 "Bearer xxx"）
+    auth: Option<String>,
+    host: String,
+    batch_size: usize,
+    flush_interval: Duration,
+}
+
+/// 把结构化日志事件非阻塞地送入队列的 tracing 层；后台任务按批发送到 Loki 或 Elasticsearch。
+/// 队列满（后端不可用或过慢）时直接丢弃，绝不阻塞交易热路径。
+struct ShipLayer {
+    tx: mpsc::Sender<Value>,
+}
+
+impl ShipLayer {
+    /// 按环境变量创建；未设置 LOG_SHIP_BACKEND / LOG_SHIP_URL 时返回 None（须在 tokio runtime 内调用）
+    fn from_env() -> Option<Self> {
+        let backend = match std::env::var("LOG_SHIP_BACKEND").ok()?.trim().to_ascii_lowercase().as_str() {
+            "loki" => ShipBackend::Loki,
+            "elasticsearch" | "es" => ShipBackend::Elasticsearch,
+            "" => return None,
+            other => {
+                eprintln!("LOG_SHIP_BACKEND={} 无效（可选 loki | elasticsearch），日志投递未启用", other);
+                return None;
+            }
+        };
+        let url = std::env::var("LOG_SHIP_URL").ok().filter(|s| !s.trim().is_empty())?;
+        let config = ShipConfig {
+            backend,
+            url: url.trim_end_matches('/').to_string(),
+            index: std::env::var("LOG_SHIP_INDEX").unwrap_or_else(|_| "poly-1hour-bot".to_string()),
+            auth: std::env::var("LOG_SHIP_AUTH").ok().filter(|s| !s.trim().is_empty()),
+            host: std::env::var("HOSTNAME").unwrap_or_else(|_| "unknown".to_string()),
+            batch_size: std::env::var("LOG_SHIP_BATCH_SIZE")
+                .unwrap_or_else(|_| "200".to_string())
+                .parse()
+                .unwrap_or(200), // 默认每批200条
+            flush_interval: Duration::from_secs(
+                std::env::var("LOG_SHIP_FLUSH_SECS")
+                    .unwrap_or_else(|_| "2".to_string())
+                    .parse()
+                    .unwrap_or(2), // 默认2秒
+            ),
+        };
+        let capacity = std::env::var("LOG_SHIP_QUEUE_SIZE")
+            .unwrap_or_else(|_| "10000".to_string())
+            .parse()
+            .unwrap_or(10000); // 默认缓存10000条
+        let (tx, rx) = mpsc::channel(capacity);
+        tokio::spawn(run_shipper(config, rx));
+        Some(Self { tx })
+    }
+}
+
+/// 收集事件字段为 JSON 对象
+struct JsonVisitor(Map<String, Value>);
+
+impl Visit for JsonVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(field.name().to_string(), json!(format!("{:?}", value)));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+}
+
+impl<S: Subscriber> Layer<S> for ShipLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let meta = event.metadata();
+        // 投递本身的 HTTP 客户端日志不再投递，避免自我放大
+        let target = meta.target();
+        if ["hyper", "reqwest", "h2", "rustls"].iter().any(|t| target.starts_with(t)) {
+            return;
+        }
+        let mut visitor = JsonVisitor(Map::new());
+        event.record(&mut visitor);
+        let mut doc = visitor.0;
+        doc.insert("@timestamp".to_string(), json!(chrono::Utc::now().to_rfc3339()));
+        doc.insert("level".to_string(), json!(meta.level().as_str().to_ascii_lowercase()));
+        doc.insert("target".to_string(), json!(target));
+        let _ = self.tx.try_send(Value::Object(doc));
+    }
+}
+
+/// 后台投递任务：攒够一批或到达刷新间隔即发送，失败只打印到 stderr（不走 tracing，避免循环）
+async fn run_shipper(config: ShipConfig, mut rx: mpsc::Receiver<Value>) {
+    let http = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .unwrap_or_default();
+    let mut batch: Vec<Value> = Vec::with_capacity(config.batch_size);
+    let mut timer = tokio::time::interval(config.flush_interval);
+    timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            doc = rx.recv() => match doc {
+                Some(doc) => {
+                    batch.push(doc);
+                    if batch.len() < config.batch_size {
+                        continue;
+                    }
+                }
+                None => {
+                    ship_batch(&http, &config, &mut batch).await;
+                    return;
+                }
+            },
+            _ = timer.tick() => {}
+        }
+        ship_batch(&http, &config, &mut batch).await;
+    }
+}
+
+async fn ship_batch(http: &reqwest::Client, config: &ShipConfig, batch: &mut Vec<Value>) {
+    if batch.is_empty() {
+        return;
+    }
+    let request = match config.backend {
+        ShipBackend::Loki => {
+            // 按级别分流，标签：app、host、level；每行为完整 JSON
+            let mut streams: Map<String, Value> = Map::new();
+            for doc in batch.iter() {
+                let level = doc.get("level").and_then(|v| v.as_str()).unwrap_or("info").to_string();
+                let ts_ns = doc
+                    .get("@timestamp")
+                    .and_then(|v| v.as_str())
+                    .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+                    .and_then(|t| t.timestamp_nanos_opt())
+                    .unwrap_or_default();
+                let entry = streams.entry(level.clone()).or_insert_with(|| {
+                    json!({
+                        "stream": { "app": "poly_1hour_bot", "host": config.host, "level": level },
+                        "values": []
+                    })
+                });
+                if let Some(values) = entry.get_mut("values").and_then(|v| v.as_array_mut()) {
+                    values.push(json!([ts_ns.to_string(), doc.to_string()]));
+                }
+            }
+            let body = json!({ "streams": streams.into_iter().map(|(_, v)| v).collect::<Vec<_>>() });
+            http.post(format!("{}/loki/api/v1/push", config.url)).json(&body)
+        }
+        ShipBackend::Elasticsearch => {
+            let action = json!({ "index": { "_index": config.index } }).to_string();
+            let mut body = String::new();
+            for doc in batch.iter() {
+                let mut doc = doc.clone();
+                if let Some(obj) = doc.as_object_mut() {
+                    obj.insert("host".to_string(), json!(config.host));
+                }
+                body.push_str(&action);
+                body.push('\n');
+                body.push_str(&doc.to_string());
+                body.push('\n');
+            }
+            http.post(format!("{}/_bulk", config.url))
+                .header("Content-Type", "application/x-ndjson")
+                .body(body)
+        }
+    };
+    let request = match config.auth.as_ref() {
+        Some(auth) => request.header("Authorization", auth),
+        None => request,
+    };
+    match request.send().await {
+        Ok(resp) if resp.status().is_success() => {}
+        Ok(resp) => eprintln!("日志投递失败：HTTP {}，丢弃 {} 条", resp.status(), batch.len()),
+        Err(e) => eprintln!("日志投递失败：{}，丢弃 {} 条", e, batch.len()),
+    }
+    batch.clear();
+}
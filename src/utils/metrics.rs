@@ -0,0 +1,59 @@
+//! 进程内计数器：按名称累计事件次数（如发现重试、下单拒绝），供日志汇总与调试接口读取；
+//! 另有按名称记录最新值的瞬时值指标（gauge，如订单簿流静默时长）。
+
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+
+fn counters() -> &'static Mutex<BTreeMap<&'static str, u64>> {
+    static COUNTERS: OnceLock<Mutex<BTreeMap<&'static str, u64>>> = OnceLock::new();
+    COUNTERS.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+fn gauges() -> &'static Mutex<BTreeMap<&'static str, f64>> {
+    static GAUGES: OnceLock<Mutex<BTreeMap<&'static str, f64>>> = OnceLock::new();
+    GAUGES.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+/// 计数器加 n
+pub fn add(name: &'static str, n: u64) {
+    if let Ok(mut map) = counters().lock() {
+        *map.entry(name).or_insert(0) += n;
+    }
+}
+
+/// 计数器加 1
+pub fn incr(name: &'static str) {
+    add(name, 1);
+}
+
+/// 读取单个计数器，未记录过时为 0
+pub fn get(name: &str) -> u64 {
+    counters()
+        .lock()
+        .ok()
+        .and_then(|map| map.get(name).copied())
+        .unwrap_or(0)
+}
+
+/// 所有计数器的快照（按名称排序）
+pub fn snapshot() -> Vec<(&'static str, u64)> {
+    counters()
+        .lock()
+        .map(|map| map.iter().map(|(k, v)| (*k, *v)).collect())
+        .unwrap_or_default()
+}
+
+/// 设置瞬时值指标（覆盖上次的值）
+pub fn set_gauge(name: &'static str, value: f64) {
+    if let Ok(mut map) = gauges().lock() {
+        map.insert(name, value);
+    }
+}
+
+/// 所有瞬时值指标的快照（按名称排序）
+pub fn gauge_snapshot() -> Vec<(&'static str, f64)> {
+    gauges()
+        .lock()
+        .map(|map| map.iter().map(|(k, v)| (*k, *v)).collect())
+        .unwrap_or_default()
+}
@@ -1,3 +1,19 @@
 pub mod arbitrage_logger;
+pub mod book_recorder;
+pub mod control;
 pub mod errors;
+pub mod events;
+pub mod instance_guard;
+pub mod journal;
+pub mod latency;
+pub mod leader;
 pub mod logger;
+pub mod metrics;
+pub mod rate_limit;
+pub mod redis_sink;
+pub mod state_crypto;
+pub mod storage;
+pub mod stream_sink;
+pub mod telemetry;
+pub mod tui;
+pub mod webhooks;
@@ -0,0 +1,161 @@
+//! API 请求限速与优先级调度：所有 CLOB / Gamma / Data API 请求共用一个令牌桶（API_RATE_LIMIT_PER_SEC），
+//! 请求按优先级分三档排队，保证风控相关的请求不会排在日常维护请求后面：
+//! - Critical：下单、撤单；从不丢弃，也不为低档预留额度；
+//! - Normal：成交轮询（订单成交数量、挂单列表）；令牌须高于桶容量的 20% 才取用；
+//! - Low：市场发现、持仓查询；令牌须高于桶容量的 50% 才取用，排队超过 API_LOW_PRIORITY_MAX_WAIT_MS 即丢弃（本次请求报错，由调用方按失败处理）。
+//!
+//! 有更高档的请求在排队时，低档请求一律让行。未启用（API_RATE_LIMIT_PER_SEC=0）时不限速。
+
+use anyhow::Result;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+use crate::utils::metrics;
+
+/// 让行 / 等待令牌时的最长单次休眠
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+/// 等待超过该时长的请求记一条调试日志
+const SLOW_WAIT: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitSettings {
+    /// 每秒请求数，0 = 不限速
+    pub per_sec: f64,
+    /// 令牌桶容量（允许的突发请求数）
+    pub burst: f64,
+    /// 低优先级请求的最长排队时间（毫秒），超过即丢弃
+    pub low_max_wait_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    /// 下单、撤单
+    Critical,
+    /// 成交轮询
+    Normal,
+    /// 市场发现、持仓查询
+    Low,
+}
+
+impl Priority {
+    fn index(self) -> usize {
+        match self {
+            Priority::Critical => 0,
+            Priority::Normal => 1,
+            Priority::Low => 2,
+        }
+    }
+
+    /// 取用令牌前须为更高档保留的桶容量比例
+    fn reserve(self) -> f64 {
+        match self {
+            Priority::Critical => 0.0,
+            Priority::Normal => 0.2,
+            Priority::Low => 0.5,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Priority::Critical => "critical",
+            Priority::Normal => "normal",
+            Priority::Low => "low",
+        }
+    }
+}
+
+struct Limiter {
+    settings: RateLimitSettings,
+    /// (当前令牌数, 上次补充时间)
+    bucket: Mutex<(f64, Instant)>,
+    /// 各档排队中的请求数
+    waiting: [AtomicUsize; 3],
+}
+
+static LIMITER: OnceLock<Limiter> = OnceLock::new();
+
+/// 启用限速；per_sec 为 0 时不启用
+pub fn init(settings: RateLimitSettings) {
+    if settings.per_sec <= 0.0 {
+        return;
+    }
+    let burst = settings.burst.max(1.0);
+    let settings = RateLimitSettings { burst, ..settings };
+    if LIMITER
+        .set(Limiter {
+            settings,
+            bucket: Mutex::new((burst, Instant::now())),
+            waiting: Default::default(),
+        })
+        .is_ok()
+    {
+        info!(
+            "🚦 API 限速已启用 | {}/秒 | 突发:{} | 低优先级最长排队:{}ms",
+            settings.per_sec, burst, settings.low_max_wait_ms
+        );
+    }
+}
+
+/// 排队计数：离开（取得令牌、被丢弃或调用方取消）时减一
+struct Waiting<'a>(&'a AtomicUsize);
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl Limiter {
+    /// 尝试取一个令牌；取不到时返回建议的等待时长
+    fn try_take(&self, priority: Priority) -> Option<Duration> {
+        if self.waiting[..priority.index()]
+            .iter()
+            .any(|w| w.load(Ordering::SeqCst) > 0)
+        {
+            return Some(POLL_INTERVAL);
+        }
+        let mut bucket = self.bucket.lock().unwrap_or_else(|p| p.into_inner());
+        let now = Instant::now();
+        let refill = now.duration_since(bucket.1).as_secs_f64() * self.settings.per_sec;
+        bucket.0 = (bucket.0 + refill).min(self.settings.burst);
+        bucket.1 = now;
+        let need = 1.0 + self.settings.burst * priority.reserve();
+        if bucket.0 >= need {
+            bucket.0 -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64((need - bucket.0) / self.settings.per_sec))
+        }
+    }
+}
+
+/// 按优先级等待一个请求额度；低优先级排队超时被丢弃时返回错误
+pub async fn acquire(priority: Priority) -> Result<()> {
+    let Some(limiter) = LIMITER.get() else {
+        return Ok(());
+    };
+    let counter = &limiter.waiting[priority.index()];
+    counter.fetch_add(1, Ordering::SeqCst);
+    let _waiting = Waiting(counter);
+    let start = Instant::now();
+    let max_wait = Duration::from_millis(limiter.settings.low_max_wait_ms);
+    while let Some(wait) = limiter.try_take(priority) {
+        if priority == Priority::Low && start.elapsed() + wait > max_wait {
+            metrics::incr("api_request_shed");
+            anyhow::bail!("API 限速繁忙，已丢弃低优先级请求（排队 {}ms）", start.elapsed().as_millis());
+        }
+        tokio::time::sleep(wait.min(POLL_INTERVAL)).await;
+    }
+    let waited = start.elapsed();
+    if waited >= SLOW_WAIT {
+        debug!(priority = priority.as_str(), wait_ms = waited.as_millis() as u64, "API 限速排队");
+        metrics::incr(match priority {
+            Priority::Critical => "api_wait_slow_critical",
+            Priority::Normal => "api_wait_slow_normal",
+            Priority::Low => "api_wait_slow_low",
+        });
+    }
+    Ok(())
+}
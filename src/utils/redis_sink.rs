@@ -0,0 +1,71 @@
+//! Redis 发布输出：订阅内部事件总线，把每条事件的 JSON 以 PUBLISH 发到 `{前缀}:{事件类型}` 频道，
+//! 如 `poly_1hour_bot:opportunity_detected`（结构见 events 模块文档）。pub/sub 不持久化，消费端离线期间的事件会丢失。
+//! 连接失败时按退避重连，期间事件只计数丢弃，不阻塞交易路径。
+
+use anyhow::{Context, Result};
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn};
+
+use super::events;
+use super::metrics;
+
+/// 重连退避上限
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// 启动 Redis 输出任务（url 为空时不启动）
+pub fn spawn(url: &str, channel_prefix: &str) {
+    if url.trim().is_empty() {
+        return;
+    }
+    let url = url.to_string();
+    let prefix = channel_prefix.to_string();
+    let mut rx = events::subscribe();
+    tokio::spawn(async move {
+        let mut backoff = Duration::from_secs(1);
+        loop {
+            match publish_loop(&url, &prefix, &mut rx).await {
+                Ok(()) => return,
+                Err(e) => warn!(error = %e, "Redis 事件输出中断，{}秒后重连", backoff.as_secs()),
+            }
+            metrics::incr("redis_sink_reconnect");
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+            // 断线期间积压的事件直接丢弃，重连后只发送新事件
+            rx = rx.resubscribe();
+        }
+    });
+}
+
+/// 持续转发事件，总线关闭时返回 Ok
+async fn publish_loop(
+    url: &str,
+    prefix: &str,
+    rx: &mut tokio::sync::broadcast::Receiver<std::sync::Arc<events::EventEnvelope>>,
+) -> Result<()> {
+    let client = redis::Client::open(url).context("Redis URL 无效")?;
+    let mut conn = client
+        .get_multiplexed_async_connection()
+        .await
+        .context("连接 Redis 失败")?;
+    info!("📡 已连接 Redis，事件发布到 {}:<事件类型> 频道", prefix);
+    loop {
+        let envelope = match rx.recv().await {
+            Ok(envelope) => envelope,
+            Err(RecvError::Lagged(skipped)) => {
+                metrics::add("redis_sink_dropped", skipped);
+                continue;
+            }
+            Err(RecvError::Closed) => return Ok(()),
+        };
+        let channel = format!("{}:{}", prefix, envelope.event.kind());
+        let payload = serde_json::to_string(envelope.as_ref())?;
+        redis::cmd("PUBLISH")
+            .arg(&channel)
+            .arg(payload)
+            .query_async::<i64>(&mut conn)
+            .await
+            .with_context(|| format!("PUBLISH {} 失败", channel))?;
+        metrics::incr("redis_sink_published");
+    }
+}
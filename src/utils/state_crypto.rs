@@ -0,0 +1,145 @@
+//! 本地状态文件加密：交易日志（JOURNAL_PATH）、风控检查点（持仓快照）与 API Key nonce 缓存会暴露交易活动与账户信息，
+//! 在共享主机上可按配置加密落盘（AES-256-GCM）。
+//!
+//! 密钥由口令派生（PBKDF2-HMAC-SHA256），口令取自 STATE_ENCRYPTION_PASSPHRASE，
+//! 或系统钥匙串条目 STATE_ENCRYPTION_KEYRING=`服务名[:账户名]`（macOS Keychain / Windows 凭据管理器 / Linux Secret Service）。
+//! 每个进程随机生成一个盐，密文格式为 `PBSTATE1:` + base64(盐 || nonce || 密文)；交易日志逐行加密，便于继续追加。
+//!
+//! 读取时遇到明文照常解析，启用加密后旧的明文文件在下次写盘时自动改为密文；
+//! 未启用（两项都为空）时读写均为明文，遇到密文文件则报错提示配置口令。
+//! 查看加密文件：`poly_1hour_bot decrypt-state <文件>`。
+
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Nonce,
+};
+use anyhow::{Context, Result};
+use pbkdf2::pbkdf2_hmac;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use tracing::info;
+
+/// 密文前缀（含格式版本）
+const MAGIC: &str = "PBSTATE1:";
+/// PBKDF2 迭代次数
+const KDF_ITERATIONS: u32 = 600_000;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+struct StateCipher {
+    passphrase: String,
+    /// 本进程加密使用的盐与派生密钥
+    salt: [u8; SALT_LEN],
+    key: [u8; 32],
+    /// 解密时按盐缓存派生密钥（历史文件 / 日志行可能来自不同进程）
+    derived: Mutex<HashMap<[u8; SALT_LEN], [u8; 32]>>,
+}
+
+static CIPHER: OnceLock<StateCipher> = OnceLock::new();
+
+/// 按 STATE_ENCRYPTION_PASSPHRASE / STATE_ENCRYPTION_KEYRING 启用加密。
+/// 口令只在这里读取并保存在加密器内，不进入 Config，避免随配置转储（/debug/state 等）泄露。
+pub fn init_from_env() -> Result<()> {
+    init(
+        &std::env::var("STATE_ENCRYPTION_PASSPHRASE").unwrap_or_default(),
+        &std::env::var("STATE_ENCRYPTION_KEYRING").unwrap_or_default(),
+    )
+}
+
+/// 启用加密；passphrase 与 keyring 都为空时不启用，两者都设置时优先口令
+fn init(passphrase: &str, keyring: &str) -> Result<()> {
+    let passphrase = if !passphrase.is_empty() {
+        passphrase.to_string()
+    } else if !keyring.trim().is_empty() {
+        keyring_secret(keyring.trim())?
+    } else {
+        return Ok(());
+    };
+    let salt: [u8; SALT_LEN] = rand::random();
+    let key = derive_key(&passphrase, &salt);
+    let mut derived = HashMap::new();
+    derived.insert(salt, key);
+    if CIPHER
+        .set(StateCipher {
+            passphrase,
+            salt,
+            key,
+            derived: Mutex::new(derived),
+        })
+        .is_ok()
+    {
+        info!("🔐 本地状态文件加密已启用（交易日志、风控检查点、API Key nonce）");
+    }
+    Ok(())
+}
+
+/// 是否已启用加密
+pub fn enabled() -> bool {
+    CIPHER.get().is_some()
+}
+
+/// 从系统钥匙串读取口令；spec 为 `服务名[:账户名]`，账户名默认 state
+fn keyring_secret(spec: &str) -> Result<String> {
+    let (service, account) = spec.split_once(':').unwrap_or((spec, "state"));
+    keyring::Entry::new(service, account)
+        .and_then(|entry| entry.get_password())
+        .with_context(|| format!("读取系统钥匙串条目 {}:{} 失败", service, account))
+}
+
+/// PBKDF2-HMAC-SHA256 派生 32 字节密钥
+fn derive_key(passphrase: &str, salt: &[u8]) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, KDF_ITERATIONS, &mut key);
+    key
+}
+
+/// 加密一段内容；未启用时原样返回
+pub fn seal(plain: &[u8]) -> Result<Vec<u8>> {
+    let Some(cipher) = CIPHER.get() else {
+        return Ok(plain.to_vec());
+    };
+    let aead = Aes256Gcm::new_from_slice(&cipher.key).context("初始化加密失败")?;
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = aead
+        .encrypt(&nonce, plain)
+        .map_err(|e| anyhow::anyhow!("加密失败: {}", e))?;
+    let mut payload = cipher.salt.to_vec();
+    payload.extend_from_slice(&nonce);
+    payload.extend_from_slice(&ciphertext);
+    let encoded = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &payload);
+    Ok(format!("{}{}", MAGIC, encoded).into_bytes())
+}
+
+/// 加密一行文本（交易日志）；未启用时原样返回
+pub fn seal_line(line: &str) -> Result<String> {
+    Ok(String::from_utf8(seal(line.as_bytes())?)?)
+}
+
+/// 解密 seal 的输出；明文内容原样返回
+pub fn open(data: &[u8]) -> Result<Vec<u8>> {
+    let Some(encoded) = data.trim_ascii().strip_prefix(MAGIC.as_bytes()) else {
+        return Ok(data.to_vec());
+    };
+    let Some(cipher) = CIPHER.get() else {
+        anyhow::bail!("文件已加密，请设置 STATE_ENCRYPTION_PASSPHRASE 或 STATE_ENCRYPTION_KEYRING");
+    };
+    let payload = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, encoded)
+        .context("密文格式无效（base64 解码失败）")?;
+    if payload.len() < SALT_LEN + NONCE_LEN {
+        anyhow::bail!("密文无效（数据过短）");
+    }
+    let (salt, rest) = payload.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let salt: [u8; SALT_LEN] = salt.try_into().expect("salt length");
+    let key = {
+        let mut derived = cipher.derived.lock().unwrap_or_else(|p| p.into_inner());
+        *derived
+            .entry(salt)
+            .or_insert_with(|| derive_key(&cipher.passphrase, &salt))
+    };
+    let aead = Aes256Gcm::new_from_slice(&key).context("初始化解密失败")?;
+    let nonce: [u8; NONCE_LEN] = nonce.try_into().expect("nonce length");
+    aead.decrypt(&Nonce::from(nonce), ciphertext)
+        .map_err(|_| anyhow::anyhow!("解密失败：口令错误或文件已被篡改"))
+}
@@ -0,0 +1,416 @@
+//! 存储后端：交易日志事件与运行状态（风控检查点等）的持久化抽象，提供 SQLite 与 Postgres 两种实现，
+//! 供多实例或需要长期留存的部署使用有并发访问能力的数据库。STORAGE_URL 为空时不启用，
+//! 交易日志与检查点仍按原方式写本地文件。
+//!
+//! 表结构（启动时自动创建）：
+//!   journal(id, ts, instance, event, fields)  —— 每条交易日志一行，fields 为完整 JSON
+//!   state(name, body, updated_at)            —— 按名称存取的状态 JSON（如 checkpoint），整体覆盖
+//!   spread_samples(ts, market_id, market, yes_ask, no_ask) —— 每市场每秒一次的双边卖一价采样（SPREAD_SAMPLE_ENABLED）
+//!
+//! 交易日志经无界通道异步写入，不阻塞调用方；写库失败只计数与 debug 日志。
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures::future::BoxFuture;
+use polymarket_client_sdk::types::Decimal;
+use serde_json::Value;
+use std::sync::{Arc, OnceLock};
+use tokio::sync::mpsc;
+use tracing::{debug, info};
+
+use super::metrics;
+
+/// 一次双边卖一价采样
+#[derive(Debug, Clone)]
+pub struct SpreadSample {
+    pub ts: DateTime<Utc>,
+    pub market_id: String,
+    /// 市场简称（系列名/币种）
+    pub market: String,
+    pub yes_ask: Decimal,
+    pub no_ask: Decimal,
+}
+
+impl SpreadSample {
+    pub fn total(&self) -> Decimal {
+        self.yes_ask + self.no_ask
+    }
+}
+
+/// 价差采样查询条件
+#[derive(Debug, Clone, Default)]
+pub struct SpreadQuery {
+    /// 只查该市场简称；None 为全部
+    pub market: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// 统一的时间字符串格式（UTC、毫秒、Z 结尾），SQLite 中按字符串比较即按时间排序
+fn format_ts(ts: DateTime<Utc>) -> String {
+    ts.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn parse_sample(ts: &str, market_id: String, market: String, yes_ask: &str, no_ask: &str) -> Result<SpreadSample> {
+    use std::str::FromStr;
+    Ok(SpreadSample {
+        ts: DateTime::parse_from_rfc3339(ts)?.with_timezone(&Utc),
+        market_id,
+        market,
+        yes_ask: Decimal::from_str(yes_ask)?,
+        no_ask: Decimal::from_str(no_ask)?,
+    })
+}
+
+/// 存储后端接口：追加日志事件、按名称存取状态
+pub trait Storage: Send + Sync {
+    /// 追加一条日志事件；line 为完整 JSON（含 ts 与 event）
+    fn append_event<'a>(&'a self, event: &'a str, line: &'a Value) -> BoxFuture<'a, Result<()>>;
+    /// 写入（覆盖）名为 name 的状态
+    fn put_state<'a>(&'a self, name: &'a str, body: &'a Value) -> BoxFuture<'a, Result<()>>;
+    /// 读取名为 name 的状态，不存在时返回 None
+    fn get_state<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Option<Value>>>;
+    /// 批量追加价差采样
+    fn append_spread_samples<'a>(&'a self, samples: &'a [SpreadSample]) -> BoxFuture<'a, Result<()>>;
+    /// 按条件查询价差采样，按时间升序
+    fn query_spread_samples<'a>(&'a self, query: &'a SpreadQuery) -> BoxFuture<'a, Result<Vec<SpreadSample>>>;
+    /// 后端名称（日志用）
+    fn name(&self) -> &'static str;
+}
+
+pub struct SqliteStorage {
+    pool: sqlx::SqlitePool,
+    instance: String,
+}
+
+impl SqliteStorage {
+    pub async fn connect(url: &str, instance: &str) -> Result<Self> {
+        use sqlx::sqlite::SqliteConnectOptions;
+        use std::str::FromStr;
+        let options = SqliteConnectOptions::from_str(url)?.create_if_missing(true);
+        if let Some(dir) = options.get_filename().parent() {
+            if !dir.as_os_str().is_empty() {
+                std::fs::create_dir_all(dir)?;
+            }
+        }
+        let pool = sqlx::SqlitePool::connect_with(options).await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS journal (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                ts TEXT NOT NULL,
+                instance TEXT NOT NULL,
+                event TEXT NOT NULL,
+                fields TEXT NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS journal_event_ts ON journal (event, ts)")
+            .execute(&pool)
+            .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS state (
+                name TEXT PRIMARY KEY,
+                body TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS spread_samples (
+                ts TEXT NOT NULL,
+                market_id TEXT NOT NULL,
+                market TEXT NOT NULL,
+                yes_ask TEXT NOT NULL,
+                no_ask TEXT NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS spread_samples_market_ts ON spread_samples (market, ts)")
+            .execute(&pool)
+            .await?;
+        Ok(Self {
+            pool,
+            instance: instance.to_string(),
+        })
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn append_event<'a>(&'a self, event: &'a str, line: &'a Value) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let ts = line.get("ts").and_then(Value::as_str).unwrap_or_default().to_string();
+            sqlx::query("INSERT INTO journal (ts, instance, event, fields) VALUES (?, ?, ?, ?)")
+                .bind(ts)
+                .bind(&self.instance)
+                .bind(event)
+                .bind(line.to_string())
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn put_state<'a>(&'a self, name: &'a str, body: &'a Value) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            sqlx::query(
+                "INSERT INTO state (name, body, updated_at) VALUES (?, ?, ?)
+                 ON CONFLICT(name) DO UPDATE SET body = excluded.body, updated_at = excluded.updated_at",
+            )
+            .bind(name)
+            .bind(body.to_string())
+            .bind(Utc::now().to_rfc3339())
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn get_state<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Option<Value>>> {
+        Box::pin(async move {
+            let body: Option<String> = sqlx::query_scalar("SELECT body FROM state WHERE name = ?")
+                .bind(name)
+                .fetch_optional(&self.pool)
+                .await?;
+            body.map(|b| serde_json::from_str(&b).context("状态不是合法 JSON"))
+                .transpose()
+        })
+    }
+
+    fn append_spread_samples<'a>(&'a self, samples: &'a [SpreadSample]) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let mut tx = self.pool.begin().await?;
+            for sample in samples {
+                sqlx::query("INSERT INTO spread_samples (ts, market_id, market, yes_ask, no_ask) VALUES (?, ?, ?, ?, ?)")
+                    .bind(format_ts(sample.ts))
+                    .bind(&sample.market_id)
+                    .bind(&sample.market)
+                    .bind(sample.yes_ask.to_string())
+                    .bind(sample.no_ask.to_string())
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            tx.commit().await?;
+            Ok(())
+        })
+    }
+
+    fn query_spread_samples<'a>(&'a self, query: &'a SpreadQuery) -> BoxFuture<'a, Result<Vec<SpreadSample>>> {
+        Box::pin(async move {
+            let rows: Vec<(String, String, String, String, String)> = sqlx::query_as(
+                "SELECT ts, market_id, market, yes_ask, no_ask FROM spread_samples
+                 WHERE (?1 IS NULL OR market = ?1) AND (?2 IS NULL OR ts >= ?2) AND (?3 IS NULL OR ts < ?3)
+                 ORDER BY ts",
+            )
+            .bind(query.market.as_deref())
+            .bind(query.since.map(format_ts))
+            .bind(query.until.map(format_ts))
+            .fetch_all(&self.pool)
+            .await?;
+            rows.into_iter()
+                .map(|(ts, market_id, market, yes, no)| parse_sample(&ts, market_id, market, &yes, &no))
+                .collect()
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "sqlite"
+    }
+}
+
+pub struct PostgresStorage {
+    pool: sqlx::PgPool,
+    instance: String,
+}
+
+impl PostgresStorage {
+    pub async fn connect(url: &str, instance: &str) -> Result<Self> {
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .max_connections(4)
+            .connect(url)
+            .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS journal (
+                id BIGSERIAL PRIMARY KEY,
+                ts TIMESTAMPTZ NOT NULL,
+                instance TEXT NOT NULL,
+                event TEXT NOT NULL,
+                fields JSONB NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS journal_event_ts ON journal (event, ts)")
+            .execute(&pool)
+            .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS state (
+                name TEXT PRIMARY KEY,
+                body JSONB NOT NULL,
+                updated_at TIMESTAMPTZ NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS spread_samples (
+                ts TIMESTAMPTZ NOT NULL,
+                market_id TEXT NOT NULL,
+                market TEXT NOT NULL,
+                yes_ask NUMERIC NOT NULL,
+                no_ask NUMERIC NOT NULL
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS spread_samples_market_ts ON spread_samples (market, ts)")
+            .execute(&pool)
+            .await?;
+        Ok(Self {
+            pool,
+            instance: instance.to_string(),
+        })
+    }
+}
+
+impl Storage for PostgresStorage {
+    fn append_event<'a>(&'a self, event: &'a str, line: &'a Value) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let ts = line.get("ts").and_then(Value::as_str).unwrap_or_default().to_string();
+            sqlx::query("INSERT INTO journal (ts, instance, event, fields) VALUES ($1::timestamptz, $2, $3, $4::jsonb)")
+                .bind(ts)
+                .bind(&self.instance)
+                .bind(event)
+                .bind(line.to_string())
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn put_state<'a>(&'a self, name: &'a str, body: &'a Value) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            sqlx::query(
+                "INSERT INTO state (name, body, updated_at) VALUES ($1, $2::jsonb, now())
+                 ON CONFLICT (name) DO UPDATE SET body = excluded.body, updated_at = excluded.updated_at",
+            )
+            .bind(name)
+            .bind(body.to_string())
+            .execute(&self.pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn get_state<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Option<Value>>> {
+        Box::pin(async move {
+            let body: Option<String> = sqlx::query_scalar("SELECT body::text FROM state WHERE name = $1")
+                .bind(name)
+                .fetch_optional(&self.pool)
+                .await?;
+            body.map(|b| serde_json::from_str(&b).context("状态不是合法 JSON"))
+                .transpose()
+        })
+    }
+
+    fn append_spread_samples<'a>(&'a self, samples: &'a [SpreadSample]) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let mut tx = self.pool.begin().await?;
+            for sample in samples {
+                sqlx::query(
+                    "INSERT INTO spread_samples (ts, market_id, market, yes_ask, no_ask)
+                     VALUES ($1::timestamptz, $2, $3, $4::numeric, $5::numeric)",
+                )
+                .bind(format_ts(sample.ts))
+                .bind(&sample.market_id)
+                .bind(&sample.market)
+                .bind(sample.yes_ask.to_string())
+                .bind(sample.no_ask.to_string())
+                .execute(&mut *tx)
+                .await?;
+            }
+            tx.commit().await?;
+            Ok(())
+        })
+    }
+
+    fn query_spread_samples<'a>(&'a self, query: &'a SpreadQuery) -> BoxFuture<'a, Result<Vec<SpreadSample>>> {
+        Box::pin(async move {
+            let rows: Vec<(String, String, String, String, String)> = sqlx::query_as(
+                "SELECT to_char(ts AT TIME ZONE 'UTC', 'YYYY-MM-DD\"T\"HH24:MI:SS.MS\"Z\"'), market_id, market,
+                        yes_ask::text, no_ask::text
+                 FROM spread_samples
+                 WHERE ($1::text IS NULL OR market = $1)
+                   AND ($2::timestamptz IS NULL OR ts >= $2::timestamptz)
+                   AND ($3::timestamptz IS NULL OR ts < $3::timestamptz)
+                 ORDER BY ts",
+            )
+            .bind(query.market.as_deref())
+            .bind(query.since.map(format_ts))
+            .bind(query.until.map(format_ts))
+            .fetch_all(&self.pool)
+            .await?;
+            rows.into_iter()
+                .map(|(ts, market_id, market, yes, no)| parse_sample(&ts, market_id, market, &yes, &no))
+                .collect()
+        })
+    }
+
+    fn name(&self) -> &'static str {
+        "postgres"
+    }
+}
+
+/// 按 URL 前缀连接后端：sqlite:// 或 postgres:// / postgresql://
+pub async fn connect(url: &str, instance: &str) -> Result<Arc<dyn Storage>> {
+    let url = url.trim();
+    if url.starts_with("sqlite:") {
+        Ok(Arc::new(SqliteStorage::connect(url, instance).await?))
+    } else if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+        Ok(Arc::new(PostgresStorage::connect(url, instance).await?))
+    } else {
+        bail!("不支持的 STORAGE_URL（应以 sqlite:// 或 postgres:// 开头）")
+    }
+}
+
+struct Backend {
+    storage: Arc<dyn Storage>,
+    events: mpsc::UnboundedSender<(String, Value)>,
+}
+
+static BACKEND: OnceLock<Backend> = OnceLock::new();
+
+/// 连接存储后端并启动日志写入任务；url 为空时不启用
+pub async fn init(url: &str, instance: &str) -> Result<()> {
+    if url.trim().is_empty() {
+        return Ok(());
+    }
+    let storage = connect(url, instance).await.context("连接存储后端失败")?;
+    let (events, mut rx) = mpsc::unbounded_channel::<(String, Value)>();
+    let writer = storage.clone();
+    tokio::spawn(async move {
+        while let Some((event, line)) = rx.recv().await {
+            if let Err(e) = writer.append_event(&event, &line).await {
+                debug!(error = %e, event = %event, "交易日志写入存储后端失败");
+                metrics::incr("storage_write_failed");
+            }
+        }
+    });
+    info!("🗄️ 存储后端已启用 | 类型:{}", storage.name());
+    let _ = BACKEND.set(Backend { storage, events });
+    Ok(())
+}
+
+/// 已启用的存储后端
+pub fn get() -> Option<Arc<dyn Storage>> {
+    BACKEND.get().map(|b| b.storage.clone())
+}
+
+/// 把一条交易日志排入写库队列（未启用时为空操作）
+pub fn record_event(event: &str, line: &Value) {
+    if let Some(backend) = BACKEND.get() {
+        let _ = backend.events.send((event.to_string(), line.clone()));
+    }
+}
@@ -0,0 +1,185 @@
+//! 事件流输出（可选编译）：把内部事件总线写入 NATS JetStream 或 Kafka 主题 `{前缀}.{事件类型}`，
+//! 供多机器人部署接入持久化的下游管道。需以 `--features nats` 或 `--features kafka` 编译，
+//! 未编译对应后端时只告警不启动。
+//!
+//! 至少一次投递：事件先进入本地缓冲，只有收到 broker 确认（JetStream ack / Kafka acks=all）后才出队，
+//! 发送失败按退避重试同一条；因此下游可能收到重复事件，请按事件 `id` 去重。
+//! 缓冲满（broker 长时间不可用）时丢弃最旧的事件并计数 stream_sink_dropped。
+//! NATS 需预先创建覆盖 `{前缀}.>` 的 JetStream stream，否则发布会因无人确认而持续重试。
+
+/// 事件流输出配置
+#[derive(Debug, Clone)]
+pub struct StreamSinkSettings {
+    /// 后端：nats、kafka；空或 none 表示不启用
+    pub backend: String,
+    /// NATS 服务地址或 Kafka bootstrap.servers
+    pub url: String,
+    /// 主题前缀
+    pub topic_prefix: String,
+    /// 本地待确认缓冲上限（条）
+    pub buffer: usize,
+}
+
+/// 启动事件流输出任务（未配置后端时不启动）
+pub fn spawn(settings: StreamSinkSettings) {
+    let backend = settings.backend.trim().to_ascii_lowercase();
+    if backend.is_empty() || backend == "none" {
+        return;
+    }
+    #[cfg(any(feature = "nats", feature = "kafka"))]
+    producer::spawn(backend, settings);
+    #[cfg(not(any(feature = "nats", feature = "kafka")))]
+    tracing::warn!(
+        "STREAM_SINK={} 但编译时未启用 nats/kafka feature，事件流输出不启动（cargo build --features {}）",
+        backend, backend
+    );
+}
+
+#[cfg(any(feature = "nats", feature = "kafka"))]
+mod producer {
+    use anyhow::{bail, Context, Result};
+    use std::collections::VecDeque;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+    use tokio::sync::broadcast::Receiver;
+    use tracing::{info, warn};
+
+    use super::super::events::{self, EventEnvelope};
+    use super::super::metrics;
+    use super::StreamSinkSettings;
+
+    /// 重试退避上限
+    const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+    enum Producer {
+        #[cfg(feature = "nats")]
+        Nats(async_nats::jetstream::Context),
+        #[cfg(feature = "kafka")]
+        Kafka(rdkafka::producer::FutureProducer),
+    }
+
+    impl Producer {
+        async fn connect(backend: &str, url: &str) -> Result<Self> {
+            match backend {
+                #[cfg(feature = "nats")]
+                "nats" => {
+                    let client = async_nats::connect(url).await.context("连接 NATS 失败")?;
+                    Ok(Producer::Nats(async_nats::jetstream::new(client)))
+                }
+                #[cfg(feature = "kafka")]
+                "kafka" => {
+                    let producer = rdkafka::ClientConfig::new()
+                        .set("bootstrap.servers", url)
+                        .set("acks", "all")
+                        .set("enable.idempotence", "true")
+                        .create()
+                        .context("创建 Kafka producer 失败")?;
+                    Ok(Producer::Kafka(producer))
+                }
+                other => bail!("不支持的事件流后端 {}（或编译时未启用对应 feature）", other),
+            }
+        }
+
+        /// 发送一条事件并等待 broker 确认（Kafka 以事件 id 作消息 key）
+        #[cfg_attr(not(feature = "kafka"), allow(unused_variables))]
+        async fn send(&self, topic: &str, key: &str, payload: &[u8]) -> Result<()> {
+            match self {
+                #[cfg(feature = "nats")]
+                Producer::Nats(jetstream) => {
+                    jetstream
+                        .publish(topic.to_string(), payload.to_vec().into())
+                        .await?
+                        .await
+                        .context("JetStream 未确认")?;
+                }
+                #[cfg(feature = "kafka")]
+                Producer::Kafka(producer) => {
+                    let record = rdkafka::producer::FutureRecord::to(topic).key(key).payload(payload);
+                    producer
+                        .send(record, Duration::from_secs(10))
+                        .await
+                        .map_err(|(e, _)| e)
+                        .context("Kafka 未确认")?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    pub(super) fn spawn(backend: String, settings: StreamSinkSettings) {
+        let mut rx = events::subscribe();
+        tokio::spawn(async move {
+            let mut pending: VecDeque<Arc<EventEnvelope>> = VecDeque::new();
+            let mut backoff = Duration::from_secs(1);
+            let mut producer: Option<Producer> = None;
+            loop {
+                if pending.is_empty() {
+                    match rx.recv().await {
+                        Ok(envelope) => pending.push_back(envelope),
+                        Err(RecvError::Lagged(skipped)) => {
+                            metrics::add("stream_sink_dropped", skipped);
+                            continue;
+                        }
+                        Err(RecvError::Closed) => return,
+                    }
+                }
+                drain(&mut rx, &mut pending, settings.buffer);
+
+                if producer.is_none() {
+                    match Producer::connect(&backend, &settings.url).await {
+                        Ok(p) => {
+                            info!("📡 事件流输出已连接 | 后端:{} | 主题:{}.<事件类型>", backend, settings.topic_prefix);
+                            producer = Some(p);
+                        }
+                        Err(e) => {
+                            warn!(error = %e, "事件流输出连接失败，{}秒后重试", backoff.as_secs());
+                            tokio::time::sleep(backoff).await;
+                            backoff = (backoff * 2).min(MAX_BACKOFF);
+                            continue;
+                        }
+                    }
+                }
+                let Some(p) = producer.as_ref() else {
+                    continue;
+                };
+
+                // 按顺序发送，收到确认才出队
+                while let Some(envelope) = pending.front().cloned() {
+                    let topic = format!("{}.{}", settings.topic_prefix, envelope.event.kind());
+                    let payload = serde_json::to_vec(envelope.as_ref()).unwrap_or_default();
+                    match p.send(&topic, &envelope.id, &payload).await {
+                        Ok(()) => {
+                            pending.pop_front();
+                            metrics::incr("stream_sink_published");
+                            backoff = Duration::from_secs(1);
+                        }
+                        Err(e) => {
+                            warn!(error = %e, topic = %topic, "事件流发送失败，{}秒后重试", backoff.as_secs());
+                            metrics::incr("stream_sink_retry");
+                            tokio::time::sleep(backoff).await;
+                            backoff = (backoff * 2).min(MAX_BACKOFF);
+                            break;
+                        }
+                    }
+                    drain(&mut rx, &mut pending, settings.buffer);
+                }
+            }
+        });
+    }
+
+    /// 把总线中已到达的事件移入本地缓冲，超出上限时丢弃最旧的
+    fn drain(rx: &mut Receiver<Arc<EventEnvelope>>, pending: &mut VecDeque<Arc<EventEnvelope>>, buffer: usize) {
+        loop {
+            match rx.try_recv() {
+                Ok(envelope) => pending.push_back(envelope),
+                Err(TryRecvError::Lagged(skipped)) => metrics::add("stream_sink_dropped", skipped),
+                Err(_) => break,
+            }
+        }
+        while pending.len() > buffer.max(1) {
+            pending.pop_front();
+            metrics::incr("stream_sink_dropped");
+        }
+    }
+}
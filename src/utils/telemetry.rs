@@ -0,0 +1,110 @@
+//! OpenTelemetry 导出（OTLP/gRPC）：把 tracing span 导出为分布式追踪，在 Jaeger/Tempo 中查看单次套利
+//! 从检测 → 下单 → 恢复/Merge 的完整链路；同时把进程内计数器与瞬时值指标（utils::metrics）周期性导出为 OTLP 指标。
+//! 设置 OTEL_EXPORTER_OTLP_ENDPOINT 时启用，未设置时不创建任何导出器。
+
+use opentelemetry::metrics::MeterProvider as _;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
+use opentelemetry_sdk::trace::TracerProvider;
+use opentelemetry_sdk::{runtime, Resource};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::Subscriber;
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::registry::LookupSpan;
+
+use super::metrics;
+
+const SERVICE_NAME: &str = "poly_1hour_bot";
+
+static PROVIDERS: OnceLock<(TracerProvider, SdkMeterProvider)> = OnceLock::new();
+
+/// 按 OTEL_EXPORTER_OTLP_ENDPOINT 创建追踪层并启动指标导出（须在 tokio runtime 内调用）；
+/// 未设置端点或创建导出器失败时返回 None
+pub fn layer<S>() -> Option<OpenTelemetryLayer<S, opentelemetry_sdk::trace::Tracer>>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let endpoint = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+        .ok()
+        .filter(|s| !s.trim().is_empty())?;
+    let service_name = std::env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| SERVICE_NAME.to_string());
+    let resource = Resource::new(vec![KeyValue::new("service.name", service_name)]);
+
+    let span_exporter = match opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint.clone())
+        .build()
+    {
+        Ok(exporter) => exporter,
+        Err(e) => {
+            eprintln!("创建 OTLP 追踪导出器失败，OpenTelemetry 未启用: {}", e);
+            return None;
+        }
+    };
+    let tracer_provider = TracerProvider::builder()
+        .with_batch_exporter(span_exporter, runtime::Tokio)
+        .with_resource(resource.clone())
+        .build();
+    let tracer = tracer_provider.tracer(SERVICE_NAME);
+    opentelemetry::global::set_tracer_provider(tracer_provider.clone());
+
+    let metric_interval_secs: u64 = std::env::var("OTEL_METRIC_EXPORT_INTERVAL_SECS")
+        .unwrap_or_else(|_| "30".to_string())
+        .parse()
+        .unwrap_or(30); // 默认30秒
+    let meter_provider = match opentelemetry_otlp::MetricExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()
+    {
+        Ok(exporter) => {
+            let reader = PeriodicReader::builder(exporter, runtime::Tokio)
+                .with_interval(Duration::from_secs(metric_interval_secs))
+                .build();
+            SdkMeterProvider::builder()
+                .with_reader(reader)
+                .with_resource(resource)
+                .build()
+        }
+        Err(e) => {
+            eprintln!("创建 OTLP 指标导出器失败，仅导出追踪: {}", e);
+            SdkMeterProvider::builder().with_resource(resource).build()
+        }
+    };
+    // 进程内计数器按名称作为属性导出为单调计数
+    meter_provider
+        .meter(SERVICE_NAME)
+        .u64_observable_counter("bot_events")
+        .with_description("进程内事件计数（发现重试、下单拒绝、Merge 重试等）")
+        .with_callback(|observer| {
+            for (name, value) in metrics::snapshot() {
+                observer.observe(value, &[KeyValue::new("name", name)]);
+            }
+        })
+        .build();
+    meter_provider
+        .meter(SERVICE_NAME)
+        .f64_observable_gauge("bot_gauges")
+        .with_description("进程内瞬时值（订单簿流静默时长等）")
+        .with_callback(|observer| {
+            for (name, value) in metrics::gauge_snapshot() {
+                observer.observe(value, &[KeyValue::new("name", name)]);
+            }
+        })
+        .build();
+    opentelemetry::global::set_meter_provider(meter_provider.clone());
+
+    let _ = PROVIDERS.set((tracer_provider, meter_provider));
+    Some(tracing_opentelemetry::layer().with_tracer(tracer))
+}
+
+/// 退出前刷出尚未导出的 span 与指标
+pub fn shutdown() {
+    if let Some((tracer_provider, meter_provider)) = PROVIDERS.get() {
+        let _ = tracer_provider.force_flush();
+        let _ = meter_provider.force_flush();
+    }
+}
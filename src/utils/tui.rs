@@ -0,0 +1,305 @@
+//! 终端 TUI 监控面板（ratatui）：实时显示各市场卖一价合计、未完结订单对、持仓、风险敞口与最近成交，
+//! 代替翻看滚动日志。主循环通过 record_book / record_fill 推送数据；未启用时这两个函数为空操作。
+//! 面板在独立线程中绘制，按 q 或 Ctrl+C 关闭面板（机器人继续运行，日志仍写入日志文件）。
+
+use anyhow::Result;
+use chrono::{DateTime, Local, Utc};
+use dashmap::DashMap;
+use polymarket_client_sdk::types::{Decimal, B256, U256};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Row, Table};
+use ratatui::Frame;
+use rust_decimal_macros::dec;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::risk::RiskManager;
+
+/// 最近成交保留条数
+const MAX_RECENT_FILLS: usize = 20;
+
+struct BookRow {
+    display: String,
+    yes_ask: Option<Decimal>,
+    no_ask: Option<Decimal>,
+    updated_at: Instant,
+}
+
+struct FillRow {
+    time: DateTime<Utc>,
+    market: String,
+    yes_filled: Decimal,
+    yes_price: Decimal,
+    no_filled: Decimal,
+    no_price: Decimal,
+}
+
+#[derive(Default)]
+struct TuiState {
+    books: DashMap<B256, BookRow>,
+    /// token_id -> 市场名称，用于持仓表显示
+    token_labels: DashMap<U256, String>,
+    fills: Mutex<VecDeque<FillRow>>,
+}
+
+static STATE: OnceLock<TuiState> = OnceLock::new();
+
+/// 记录某市场最新的 YES/NO 卖一价（TUI 未启用时为空操作）
+pub fn record_book(
+    market_id: B256,
+    yes_token: U256,
+    no_token: U256,
+    display: &str,
+    yes_ask: Option<Decimal>,
+    no_ask: Option<Decimal>,
+) {
+    let Some(state) = STATE.get() else {
+        return;
+    };
+    if !state.token_labels.contains_key(&yes_token) {
+        state.token_labels.insert(yes_token, format!("{} YES", display));
+        state.token_labels.insert(no_token, format!("{} NO", display));
+    }
+    state.books.insert(
+        market_id,
+        BookRow {
+            display: display.to_string(),
+            yes_ask,
+            no_ask,
+            updated_at: Instant::now(),
+        },
+    );
+}
+
+/// 记录一次套利成交（TUI 未启用时为空操作）
+pub fn record_fill(market: &str, yes_filled: Decimal, yes_price: Decimal, no_filled: Decimal, no_price: Decimal) {
+    let Some(state) = STATE.get() else {
+        return;
+    };
+    if let Ok(mut fills) = state.fills.lock() {
+        fills.push_front(FillRow {
+            time: Utc::now(),
+            market: market.to_string(),
+            yes_filled,
+            yes_price,
+            no_filled,
+            no_price,
+        });
+        fills.truncate(MAX_RECENT_FILLS);
+    }
+}
+
+/// 新窗口开始时清空上一窗口的市场行
+pub fn clear_books() {
+    if let Some(state) = STATE.get() {
+        state.books.clear();
+    }
+}
+
+/// 启动 TUI 线程；refresh 为重绘间隔
+pub fn spawn(risk_manager: Arc<RiskManager>, refresh: Duration) -> Result<()> {
+    let _ = STATE.set(TuiState::default());
+    std::thread::Builder::new().name("tui".to_string()).spawn(move || {
+        let mut terminal = ratatui::init();
+        let result = (|| -> std::io::Result<()> {
+            loop {
+                terminal.draw(|frame| render(frame, &risk_manager))?;
+                if event::poll(refresh)? {
+                    if let Event::Key(key) = event::read()? {
+                        let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                        if key.kind == KeyEventKind::Press && (key.code == KeyCode::Char('q') || ctrl_c) {
+                            return Ok(());
+                        }
+                    }
+                }
+            }
+        })();
+        ratatui::restore();
+        match result {
+            Ok(()) => info!("TUI 已关闭，机器人继续运行（再次 Ctrl+C 退出）"),
+            Err(e) => warn!(error = %e, "TUI 绘制失败，已关闭"),
+        }
+    })?;
+    Ok(())
+}
+
+fn fmt_price(price: Option<Decimal>) -> String {
+    price.map(|p| format!("{:.4}", p)).unwrap_or_else(|| "-".to_string())
+}
+
+fn render(frame: &mut Frame, risk_manager: &RiskManager) {
+    let Some(state) = STATE.get() else {
+        return;
+    };
+    let [header_area, books_area, middle_area, fills_area] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(6),
+        Constraint::Min(6),
+        Constraint::Length(8),
+    ])
+    .areas(frame.area());
+    let [pairs_area, positions_area] =
+        Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)]).areas(middle_area);
+
+    // 概览：敞口、盈亏
+    let position_tracker = risk_manager.position_tracker();
+    let exposure = position_tracker.calculate_exposure();
+    let max_exposure = position_tracker.max_exposure();
+    let pnl = risk_manager.pnl().summary();
+    let header = Line::from(format!(
+        "{} | 敞口:{:.2}/{:.2} USD | 成对:{}份 | 锁定毛利:{:.4} | gas:{:.4} | 净利润:{:.4} USD | q 关闭",
+        Local::now().format("%H:%M:%S"),
+        exposure,
+        max_exposure,
+        pnl.matched_shares,
+        pnl.locked_profit_usd,
+        pnl.gas_cost_usd,
+        pnl.net_usd()
+    ));
+    frame.render_widget(Paragraph::new(header).block(Block::bordered().title(" Polymarket 1小时套利 ")), header_area);
+
+    // 各市场卖一价合计
+    let mut books: Vec<Row> = Vec::new();
+    let mut book_entries: Vec<_> = state
+        .books
+        .iter()
+        .map(|e| {
+            let b = e.value();
+            (b.display.clone(), b.yes_ask, b.no_ask, b.updated_at.elapsed())
+        })
+        .collect();
+    book_entries.sort_by(|a, b| a.0.cmp(&b.0));
+    for (display, yes_ask, no_ask, age) in book_entries {
+        let total = yes_ask.zip(no_ask).map(|(y, n)| y + n);
+        let style = match total {
+            Some(t) if t < dec!(1.0) => Style::new().fg(Color::Green).bold(),
+            _ => Style::new(),
+        };
+        books.push(
+            Row::new(vec![
+                display,
+                fmt_price(yes_ask),
+                fmt_price(no_ask),
+                fmt_price(total),
+                total
+                    .map(|t| format!("{:.2}%", (dec!(1.0) - t) * dec!(100)))
+                    .unwrap_or_else(|| "-".to_string()),
+                format!("{:.1}s", age.as_secs_f64()),
+            ])
+            .style(style),
+        );
+    }
+    let books_table = Table::new(
+        books,
+        [
+            Constraint::Min(16),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(8),
+        ],
+    )
+    .header(Row::new(vec!["市场", "YES卖一", "NO卖一", "合计", "价差", "更新"]).bold())
+    .block(Block::bordered().title(" 订单簿 "));
+    frame.render_widget(books_table, books_area);
+
+    // 未完结订单对
+    let mut pairs = risk_manager.pending_pairs_snapshot();
+    pairs.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    let pair_rows: Vec<Row> = pairs
+        .iter()
+        .map(|p| {
+            let market = state
+                .token_labels
+                .get(&p.yes_token_id)
+                .map(|l| l.trim_end_matches(" YES").to_string())
+                .unwrap_or_else(|| format!("{:#x}", p.market_id).chars().take(10).collect());
+            Row::new(vec![
+                p.pair_id.chars().take(8).collect::<String>(),
+                market,
+                format!("{:?}", p.status),
+                format!("{}/{}", p.yes_filled, p.yes_size),
+                format!("{}/{}", p.no_filled, p.no_size),
+            ])
+        })
+        .collect();
+    let pairs_table = Table::new(
+        pair_rows,
+        [
+            Constraint::Length(8),
+            Constraint::Min(12),
+            Constraint::Length(15),
+            Constraint::Length(12),
+            Constraint::Length(12),
+        ],
+    )
+    .header(Row::new(vec!["订单对", "市场", "状态", "YES成交", "NO成交"]).bold())
+    .block(Block::bordered().title(" 订单对 "));
+    frame.render_widget(pairs_table, pairs_area);
+
+    // 持仓
+    let snapshot = position_tracker.snapshot();
+    let mut positions: Vec<_> = snapshot
+        .positions
+        .iter()
+        .filter(|(_, qty)| !qty.is_zero())
+        .map(|(token, qty)| {
+            let label = state
+                .token_labels
+                .get(token)
+                .map(|l| l.clone())
+                .unwrap_or_else(|| format!("{:#x}", token).chars().take(12).collect());
+            let cost = snapshot.exposure_costs.get(token).copied().unwrap_or(dec!(0));
+            (label, *qty, cost)
+        })
+        .collect();
+    positions.sort_by(|a, b| a.0.cmp(&b.0));
+    let position_rows: Vec<Row> = positions
+        .into_iter()
+        .map(|(label, qty, cost)| Row::new(vec![label, qty.to_string(), format!("{:.2}", cost)]))
+        .collect();
+    let positions_table = Table::new(
+        position_rows,
+        [Constraint::Min(14), Constraint::Length(10), Constraint::Length(10)],
+    )
+    .header(Row::new(vec!["Token", "数量", "成本"]).bold())
+    .block(Block::bordered().title(" 持仓 "));
+    frame.render_widget(positions_table, positions_area);
+
+    // 最近成交
+    let fill_rows: Vec<Row> = state
+        .fills
+        .lock()
+        .map(|fills| {
+            fills
+                .iter()
+                .map(|f| {
+                    Row::new(vec![
+                        f.time.with_timezone(&Local).format("%H:%M:%S").to_string(),
+                        f.market.clone(),
+                        format!("{}@{:.4}", f.yes_filled, f.yes_price),
+                        format!("{}@{:.4}", f.no_filled, f.no_price),
+                    ])
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let fills_table = Table::new(
+        fill_rows,
+        [
+            Constraint::Length(8),
+            Constraint::Min(16),
+            Constraint::Length(16),
+            Constraint::Length(16),
+        ],
+    )
+    .header(Row::new(vec!["时间", "市场", "YES", "NO"]).bold())
+    .block(Block::bordered().title(" 最近成交 "));
+    frame.render_widget(fills_table, fills_area);
+}
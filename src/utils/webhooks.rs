@@ -0,0 +1,128 @@
+//! 出站 Webhook：订阅内部事件总线，把下单结果、恢复动作、限额拒单与 merge 完成等事件以签名的 POST 推送到
+//! 用户配置的 URL（PagerDuty、自建风控看板等），无需轮询。请求体即事件 JSON（结构见 events 模块文档）。
+//!
+//! 签名：请求头 `X-Poly-Timestamp`（Unix 秒）与 `X-Poly-Signature: sha256=<hex>`，
+//! 其中 hex = HMAC-SHA256(WEBHOOK_SECRET, "{timestamp}.{body}")；接收端应校验签名并拒绝过旧的时间戳。
+//! 每个 URL 独立投递，失败按退避重试数次后放弃；同一市场的限额拒单事件按冷却时间去重，避免逐拍刷屏。
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, info, warn};
+
+use super::events::{self, BotEvent, EventEnvelope};
+use super::metrics;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 单个 URL 的投递尝试次数
+const MAX_ATTEMPTS: u32 = 3;
+/// 同一市场限额拒单事件的推送冷却
+const BREACH_COOLDOWN: Duration = Duration::from_secs(300);
+
+/// Webhook 配置
+#[derive(Debug, Clone)]
+pub struct WebhookSettings {
+    /// 接收地址；为空表示不启用
+    pub urls: Vec<String>,
+    /// 签名密钥；为空时不附带签名头
+    pub secret: String,
+    /// 推送的事件类型；为空表示除 opportunity_detected 外的全部事件
+    pub events: Vec<String>,
+}
+
+fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC key");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// 启动 Webhook 推送任务（未配置 URL 时不启动）
+pub fn spawn(settings: WebhookSettings) {
+    if settings.urls.is_empty() {
+        return;
+    }
+    let wanted: HashSet<String> = settings.events.iter().map(|e| e.trim().to_string()).collect();
+    let mut rx = events::subscribe();
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap_or_default();
+    let settings = Arc::new(settings);
+    info!("🔔 Webhook 推送已启用 | 地址数:{}", settings.urls.len());
+    tokio::spawn(async move {
+        let mut last_breach: HashMap<String, Instant> = HashMap::new();
+        loop {
+            let envelope = match rx.recv().await {
+                Ok(envelope) => envelope,
+                Err(RecvError::Lagged(skipped)) => {
+                    metrics::add("webhook_dropped", skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => return,
+            };
+            let kind = envelope.event.kind();
+            let selected = if wanted.is_empty() {
+                kind != "opportunity_detected"
+            } else {
+                wanted.contains(kind)
+            };
+            if !selected {
+                continue;
+            }
+            if let BotEvent::LimitBreached { limit, market, .. } = &envelope.event {
+                let key = format!("{}|{}", limit, market);
+                if last_breach.get(&key).is_some_and(|t| t.elapsed() < BREACH_COOLDOWN) {
+                    continue;
+                }
+                last_breach.insert(key, Instant::now());
+            }
+            for url in &settings.urls {
+                tokio::spawn(deliver(client.clone(), url.clone(), settings.secret.clone(), envelope.clone()));
+            }
+        }
+    });
+}
+
+async fn deliver(client: reqwest::Client, url: String, secret: String, envelope: Arc<EventEnvelope>) {
+    let body = match serde_json::to_string(envelope.as_ref()) {
+        Ok(body) => body,
+        Err(e) => {
+            warn!(error = %e, "Webhook 事件序列化失败");
+            return;
+        }
+    };
+    let mut backoff = Duration::from_secs(1);
+    for attempt in 1..=MAX_ATTEMPTS {
+        let timestamp = chrono::Utc::now().timestamp();
+        let mut request = client
+            .post(&url)
+            .header("Content-Type", "application/json")
+            .header("X-Poly-Event", envelope.event.kind())
+            .header("X-Poly-Timestamp", timestamp.to_string());
+        if !secret.is_empty() {
+            request = request.header("X-Poly-Signature", format!("sha256={}", sign(&secret, timestamp, &body)));
+        }
+        match request.body(body.clone()).send().await {
+            Ok(resp) if resp.status().is_success() => {
+                debug!(url = %url, event = envelope.event.kind(), "Webhook 已送达");
+                metrics::incr("webhook_delivered");
+                return;
+            }
+            Ok(resp) => warn!(url = %url, status = %resp.status(), attempt, "Webhook 接收端返回错误"),
+            Err(e) => warn!(url = %url, error = %e, attempt, "Webhook 请求失败"),
+        }
+        if attempt < MAX_ATTEMPTS {
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+    }
+    metrics::incr("webhook_failed");
+}